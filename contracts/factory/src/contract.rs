@@ -1,37 +1,51 @@
 use std::collections::HashSet;
+use std::str::FromStr;
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, ensure, to_json_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply,
-    ReplyOn, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, WasmMsg,
+    attr, coin, ensure, from_json, to_json_binary, wasm_execute, Addr, Binary, CosmosMsg, Decimal,
+    Deps, DepsMut, Env, MessageInfo, Order, Reply, ReplyOn, Response, StdError, StdResult, SubMsg,
+    SubMsgResponse, SubMsgResult, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
+use cw20::Cw20ExecuteMsg;
 use cw_utils::parse_instantiate_response_data;
 use itertools::Itertools;
 
-use astroport::asset::{addr_opt_validate, AssetInfo, PairInfo};
-use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
+use astroport::asset::{addr_opt_validate, Asset, AssetInfo, PairInfo};
+use astroport::common::{
+    claim_ownership, drop_ownership_proposal, propose_new_owner, query_is_owner, LP_SUBDENOM,
+};
 use astroport::factory::{
-    Config, ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, MigrateMsg, PairConfig,
-    PairType, PairsResponse, QueryMsg, TrackerConfig,
+    Config, ConfigResponse, CreatePairParams, ExecuteMsg, FeeChange, FeeInfoResponse,
+    InstantiateMsg, LockedLiquidityResponse, MigrateMsg, PairConfig, PairType, PairsResponse,
+    PendingFeeChange, PendingFeeChangeDelay, QueryMsg, TrackerConfig, FEE_CHANGE_EXPIRY_BLOCKS,
 };
 use astroport::incentives::ExecuteMsg::DeactivatePool;
-use astroport::pair::InstantiateMsg as PairInstantiateMsg;
+use astroport::pair::{
+    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg, WeightedPoolParams,
+    MAX_ALLOWED_SLIPPAGE, WEIGHTED_PAIR_TYPE, WEIGHTS_TOTAL,
+};
 
 use crate::error::ContractError;
-use crate::migration::migrate_pair_configs;
-use crate::querier::query_pair_info;
+use crate::migration::{backfill_pairs_by_lp_token, migrate_pair_configs};
+use crate::querier::{query_cumulative_prices, query_pair_info};
 use crate::state::{
-    check_asset_infos, pair_key, read_pairs, TmpPairInfo, CONFIG, OWNERSHIP_PROPOSAL, PAIRS,
-    PAIR_CONFIGS, TMP_PAIR_INFO, TRACKER_CONFIG,
+    check_asset_infos, fee_change_delay, pair_key, read_pairs, read_pairs_created_between,
+    LockedLiquidityInfo, TmpPairInfo, CONFIG, DEFAULT_MAX_SPREADS, FEE_CHANGE_DELAY,
+    LOCKED_LIQUIDITY, NEXT_REPLY_ID, OWNERSHIP_PROPOSAL, PAIRS, PAIRS_BY_CREATION_BLOCK,
+    PAIRS_BY_LP_TOKEN, PAIRS_TO_MIGRATE, PAIR_CONFIGS, PAIR_CREATION_BLOCKS, PAIR_LP_TOKENS,
+    PAIR_ORACLES, PENDING_FEE_CHANGES, PENDING_FEE_CHANGE_DELAY, REJECT_LP_TOKEN_ASSETS,
+    REQUIRE_FEE_ADDRESS, TMP_PAIR_INFO, TRACKER_CONFIG,
 };
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-factory";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-/// A `reply` call code ID used in a sub-message.
+/// The first `reply` call code ID used in a pair instantiation sub-message.
+/// Every subsequent pair instantiation gets the next ID.
 const INSTANTIATE_PAIR_REPLY_ID: u64 = 1;
 
 /// Creates a new contract with the specified parameters packed in the `msg` variable.
@@ -53,6 +67,7 @@ pub fn instantiate(
         generator_address: None,
         whitelist_code_id: msg.whitelist_code_id,
         coin_registry_address: deps.api.addr_validate(&msg.coin_registry_address)?,
+        pair_admin: None,
     };
 
     config.generator_address = addr_opt_validate(deps.api, &msg.generator_address)?;
@@ -126,9 +141,36 @@ pub struct UpdateConfig {
 ///             init_params,
 ///         }** Creates a new pair with the specified input parameters.
 ///
+/// * **ExecuteMsg::CreatePairs { pairs }** Creates several pairs of any pair types at once.
+///
+/// * **ExecuteMsg::CreateReplacementPair { pair_type, asset_infos, init_params, replaced_pair }**
+/// Creates a pair which continues the cumulative prices of a deregistered pair.
+///
 /// * **ExecuteMsg::Deregister { asset_infos }** Removes an existing pair from the factory.
 /// * The asset information is for the assets that are traded in the pair.
 ///
+/// * **ExecuteMsg::SetDefaultMaxSpread { pair_type, max_spread }** Sets or removes the default max spread
+/// inherited by new pairs of the specified type.
+///
+/// * **ExecuteMsg::SetPairAdmin { admin }** Sets or removes the admin of newly instantiated pairs.
+///
+/// * **ExecuteMsg::SetPairOracle { asset_infos, oracle }** Sets or removes the TWAP oracle linked to a pair.
+///
+/// * **ExecuteMsg::SetPairsToMigrate { add, remove }** Updates the list of pairs which must be migrated.
+///
+/// * **ExecuteMsg::SetRequireFeeAddress { require }** Enables or disables rejecting pairs which charge
+/// maker fees while the fee address is not set.
+///
+/// * **ExecuteMsg::SetRejectLpTokenAssets { reject }** Enables or disables rejecting pairs which
+/// include LP tokens of registered pairs.
+///
+/// * **ExecuteMsg::SetFeeChangeDelay { delay_blocks }** Sets or removes the timelock of fee changes.
+///
+/// * **ExecuteMsg::ProposeFeeChange { pair_type, config }** Records a fee change of a pair type
+/// which can be applied once the fee change delay passes.
+///
+/// * **ExecuteMsg::ApplyFeeChange { pair_type }** Applies the pending fee change of a pair type.
+///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a request to change contract ownership.
 ///
 /// * **ExecuteMsg::DropOwnershipProposal {}** Removes a request to change contract ownership.
@@ -159,13 +201,79 @@ pub fn execute(
                 coin_registry_address,
             },
         ),
-        ExecuteMsg::UpdatePairConfig { config } => execute_update_pair_config(deps, info, config),
+        ExecuteMsg::UpdatePairConfig { config } => {
+            execute_update_pair_config(deps, env, info, config)
+        }
         ExecuteMsg::CreatePair {
             pair_type,
             asset_infos,
             init_params,
-        } => execute_create_pair(deps, info, env, pair_type, asset_infos, init_params),
+        } => execute_create_pair(
+            deps,
+            info,
+            env,
+            pair_type,
+            asset_infos,
+            init_params,
+            None,
+            None,
+        ),
+        ExecuteMsg::CreatePairs { pairs } => execute_create_pairs(deps, info, env, pairs),
+        ExecuteMsg::CreatePairWithLockedLiquidity {
+            pair_type,
+            asset_infos,
+            init_params,
+            initial_liquidity,
+        } => execute_create_pair(
+            deps,
+            info,
+            env,
+            pair_type,
+            asset_infos,
+            init_params,
+            Some(initial_liquidity),
+            None,
+        ),
+        ExecuteMsg::CreateReplacementPair {
+            pair_type,
+            asset_infos,
+            init_params,
+            replaced_pair,
+        } => execute_create_replacement_pair(
+            deps,
+            info,
+            env,
+            pair_type,
+            asset_infos,
+            init_params,
+            replaced_pair,
+        ),
         ExecuteMsg::Deregister { asset_infos } => deregister(deps, info, asset_infos),
+        ExecuteMsg::SetDefaultMaxSpread {
+            pair_type,
+            max_spread,
+        } => set_default_max_spread(deps, info, pair_type, max_spread),
+        ExecuteMsg::SetPairAdmin { admin } => set_pair_admin(deps, info, admin),
+        ExecuteMsg::SetPairOracle {
+            asset_infos,
+            oracle,
+        } => set_pair_oracle(deps, info, asset_infos, oracle),
+        ExecuteMsg::SetPairsToMigrate { add, remove } => {
+            set_pairs_to_migrate(deps, info, add, remove)
+        }
+        ExecuteMsg::SetRequireFeeAddress { require } => {
+            set_require_fee_address(deps, info, require)
+        }
+        ExecuteMsg::SetRejectLpTokenAssets { reject } => {
+            set_reject_lp_token_assets(deps, info, reject)
+        }
+        ExecuteMsg::SetFeeChangeDelay { delay_blocks } => {
+            set_fee_change_delay(deps, env, info, delay_blocks)
+        }
+        ExecuteMsg::ProposeFeeChange { pair_type, config } => {
+            propose_fee_change(deps, env, info, pair_type, config)
+        }
+        ExecuteMsg::ApplyFeeChange { pair_type } => apply_fee_change(deps, env, info, pair_type),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config = CONFIG.load(deps.storage)?;
 
@@ -257,6 +365,7 @@ pub fn execute_update_config(
 /// Only the owner can execute this.
 pub fn execute_update_pair_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     pair_config: PairConfig,
 ) -> Result<Response, ContractError> {
@@ -272,6 +381,19 @@ pub fn execute_update_pair_config(
         return Err(ContractError::PairConfigInvalidFeeBps {});
     }
 
+    // Fees of existing pair types are timelocked if the fee change delay is set
+    if fee_change_delay(deps.storage, env.block.height)?.is_some() {
+        if let Some(current) =
+            PAIR_CONFIGS.may_load(deps.storage, pair_config.pair_type.to_string())?
+        {
+            ensure!(
+                current.total_fee_bps == pair_config.total_fee_bps
+                    && current.maker_fee_bps == pair_config.maker_fee_bps,
+                ContractError::FeeChangeTimelocked {}
+            );
+        }
+    }
+
     PAIR_CONFIGS.save(
         deps.storage,
         pair_config.pair_type.to_string(),
@@ -288,6 +410,10 @@ pub fn execute_update_pair_config(
 /// * **asset_infos** is a vector with assets for which we create a pair.
 ///
 /// * **init_params** These are packed params used for custom pair types that need extra data to be instantiated.
+///
+/// * **locked_liquidity** is the optional initial liquidity which is provided right after the pair
+/// is instantiated. The minted LP tokens stay in the factory forever.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_create_pair(
     deps: DepsMut,
     info: MessageInfo,
@@ -295,15 +421,44 @@ pub fn execute_create_pair(
     pair_type: PairType,
     asset_infos: Vec<AssetInfo>,
     init_params: Option<Binary>,
+    locked_liquidity: Option<Vec<Asset>>,
+    carry_over_from: Option<Addr>,
 ) -> Result<Response, ContractError> {
     check_asset_infos(deps.api, &asset_infos)?;
 
+    if matches!(&pair_type, PairType::Custom(name) if name == WEIGHTED_PAIR_TYPE) {
+        let weights = init_params
+            .as_ref()
+            .and_then(|params| from_json::<WeightedPoolParams>(params).ok())
+            .map(|params| params.weights)
+            .ok_or(ContractError::InvalidWeights {})?;
+        if asset_infos.len() != 2
+            || weights.contains(&0)
+            || weights[0].checked_add(weights[1]) != Some(WEIGHTS_TOTAL)
+        {
+            return Err(ContractError::InvalidWeights {});
+        }
+    }
+
     let config = CONFIG.load(deps.storage)?;
 
     if PAIRS.has(deps.storage, &pair_key(&asset_infos)) {
         return Err(ContractError::PairWasCreated {});
     }
 
+    if REJECT_LP_TOKEN_ASSETS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+    {
+        for asset_info in &asset_infos {
+            let asset = asset_info.to_string();
+            ensure!(
+                !is_registered_lp_token(deps.as_ref(), &asset)?,
+                ContractError::LpTokenAsAsset { asset }
+            );
+        }
+    }
+
     // Get pair type from config
     let pair_config = PAIR_CONFIGS
         .load(deps.storage, pair_type.to_string())
@@ -318,13 +473,90 @@ pub fn execute_create_pair(
         return Err(ContractError::PairConfigDisabled {});
     }
 
+    // Maker fees stay in the pool if the fee address is not set
+    let mut attrs = vec![];
+    if pair_config.maker_fee_bps > 0 && config.fee_address.is_none() {
+        ensure!(
+            !REQUIRE_FEE_ADDRESS
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+            ContractError::FeeAddressNotSet {}
+        );
+        attrs.push(attr(
+            "warning",
+            "fee address is not set, maker fees are not collected",
+        ));
+    }
+
+    // Liquidity funds are kept in the factory, the rest is passed to the pair
+    let mut pair_funds = info.funds;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(locked_liquidity) = &locked_liquidity {
+        ensure!(
+            locked_liquidity.len() == asset_infos.len()
+                && asset_infos.iter().all(|asset_info| {
+                    locked_liquidity
+                        .iter()
+                        .any(|asset| asset.info.equal(asset_info) && !asset.amount.is_zero())
+                }),
+            ContractError::InvalidLockedLiquidity {}
+        );
+
+        for asset in locked_liquidity {
+            match &asset.info {
+                AssetInfo::NativeToken { denom } => {
+                    let coin = pair_funds
+                        .iter_mut()
+                        .find(|coin| coin.denom == *denom && coin.amount >= asset.amount)
+                        .ok_or_else(|| ContractError::InsufficientLockedLiquidityFunds {
+                            denom: denom.clone(),
+                        })?;
+                    coin.amount -= asset.amount;
+                }
+                AssetInfo::Token { contract_addr } => messages.push(
+                    wasm_execute(
+                        contract_addr,
+                        &Cw20ExecuteMsg::TransferFrom {
+                            owner: info.sender.to_string(),
+                            recipient: env.contract.address.to_string(),
+                            amount: asset.amount,
+                        },
+                        vec![],
+                    )?
+                    .into(),
+                ),
+            }
+        }
+        pair_funds.retain(|coin| !coin.amount.is_zero());
+    }
+
+    let reply_id = NEXT_REPLY_ID
+        .may_load(deps.storage)?
+        .unwrap_or(INSTANTIATE_PAIR_REPLY_ID);
+    NEXT_REPLY_ID.save(deps.storage, &(reply_id + 1))?;
+
     let pair_key = pair_key(&asset_infos);
-    TMP_PAIR_INFO.save(deps.storage, &TmpPairInfo { pair_key })?;
+    TMP_PAIR_INFO.save(
+        deps.storage,
+        reply_id,
+        &TmpPairInfo {
+            pair_key,
+            locked_liquidity,
+            carry_over_from,
+        },
+    )?;
 
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
-        id: INSTANTIATE_PAIR_REPLY_ID,
+        id: reply_id,
         msg: WasmMsg::Instantiate {
-            admin: Some(config.owner.to_string()),
+            // Pairs are administered by the owner unless a dedicated pair admin is set
+            admin: Some(
+                config
+                    .pair_admin
+                    .as_ref()
+                    .unwrap_or(&config.owner)
+                    .to_string(),
+            ),
             code_id: pair_config.code_id,
             msg: to_json_binary(&PairInstantiateMsg {
                 pair_type,
@@ -334,7 +566,7 @@ pub fn execute_create_pair(
                 init_params,
             })?,
             // Pass executor funds to pair contract to pay for LP token creation
-            funds: info.funds,
+            funds: pair_funds,
             label: "Astroport pair".to_string(),
         }
         .into(),
@@ -343,25 +575,143 @@ pub fn execute_create_pair(
     }];
 
     Ok(Response::new()
+        .add_messages(messages)
         .add_submessages(sub_msg)
         .add_attributes(vec![
             attr("action", "create_pair"),
             attr("pair", asset_infos.iter().join("-")),
-        ]))
+        ])
+        .add_attributes(attrs))
+}
+
+/// Checks whether `asset` is the LP token of a registered pair.
+/// LP tokens missing from [`PAIRS_BY_LP_TOKEN`] are resolved from the token factory LP denom
+/// `factory/{pair}/astroport/share` by querying the pair.
+fn is_registered_lp_token(deps: Deps, asset: &str) -> StdResult<bool> {
+    if PAIRS_BY_LP_TOKEN.has(deps.storage, asset) {
+        return Ok(true);
+    }
+
+    let pair_addr = match asset
+        .strip_prefix("factory/")
+        .and_then(|rest| rest.strip_suffix(&format!("/{LP_SUBDENOM}")))
+    {
+        Some(pair_addr) => pair_addr,
+        None => return Ok(false),
+    };
+
+    match query_pair_info(&deps.querier, pair_addr) {
+        Ok(pair_info) => Ok(pair_info.liquidity_token == asset
+            && PAIRS.may_load(deps.storage, &pair_key(&pair_info.asset_infos))?
+                == Some(pair_info.contract_addr)),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Creates a pair which replaces a deregistered pair. The cumulative prices of the replaced pair
+/// are seeded into the new pair once it is instantiated, so nobody can trade in between.
+///
+/// * **replaced_pair** is the deregistered pair whose cumulative prices are carried over.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_create_replacement_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    pair_type: PairType,
+    asset_infos: Vec<AssetInfo>,
+    init_params: Option<Binary>,
+    replaced_pair: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let replaced_pair = deps.api.addr_validate(&replaced_pair)?;
+    let replaced_pair_info = query_pair_info(&deps.querier, &replaced_pair)?;
+
+    // Cumulative prices are only comparable between pairs of the same type
+    ensure!(
+        replaced_pair_info.pair_type == pair_type
+            && pair_key(&replaced_pair_info.asset_infos) == pair_key(&asset_infos),
+        ContractError::InvalidReplacementPair {}
+    );
+
+    let response = execute_create_pair(
+        deps,
+        info,
+        env,
+        pair_type,
+        asset_infos,
+        init_params,
+        None,
+        Some(replaced_pair.clone()),
+    )?;
+
+    Ok(response.add_attribute("replaced_pair", replaced_pair))
+}
+
+/// Creates several pairs in a single transaction.
+/// Every pair instantiation gets its own reply id from [`NEXT_REPLY_ID`], so the reply handler
+/// loads the right [`TmpPairInfo`] regardless of the pair types mixed in the batch.
+///
+/// * **pairs** are the parameters of the pairs to create.
+pub fn execute_create_pairs(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    pairs: Vec<CreatePairParams>,
+) -> Result<Response, ContractError> {
+    ensure!(!pairs.is_empty(), ContractError::EmptyPairsBatch {});
+    ensure!(info.funds.is_empty(), ContractError::FundsInPairsBatch {});
+
+    // Pairs are registered in replies, so duplicates within the batch are checked upfront
+    ensure!(
+        pairs
+            .iter()
+            .map(|pair| pair_key(&pair.asset_infos))
+            .all_unique(),
+        ContractError::PairWasCreated {}
+    );
+
+    pairs
+        .into_iter()
+        .try_fold(Response::new(), |response, pair| {
+            let pair_response = execute_create_pair(
+                deps.branch(),
+                info.clone(),
+                env.clone(),
+                pair.pair_type,
+                pair.asset_infos,
+                pair.init_params,
+                None,
+                None,
+            )?;
+
+            Ok(response
+                .add_submessages(pair_response.messages)
+                .add_attributes(pair_response.attributes))
+        })
 }
 
 /// The entry point to the contract for processing replies from submessages.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg {
         Reply {
-            id: INSTANTIATE_PAIR_REPLY_ID,
+            id,
             result:
                 SubMsgResult::Ok(SubMsgResponse {
                     data: Some(data), ..
                 }),
         } => {
-            let tmp = TMP_PAIR_INFO.load(deps.storage)?;
+            let tmp = TMP_PAIR_INFO
+                .may_load(deps.storage, id)?
+                .ok_or(ContractError::FailedToParseReply {})?;
+
             if PAIRS.has(deps.storage, &tmp.pair_key) {
                 return Err(ContractError::PairWasRegistered {});
             }
@@ -371,9 +721,87 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 
             let pair_contract = deps.api.addr_validate(&init_response.contract_address)?;
 
+            // Make sure the instantiated contract is the pair this reply was created for
+            let pair_info = query_pair_info(&deps.querier, &pair_contract)?;
+            ensure!(
+                pair_key(&pair_info.asset_infos) == tmp.pair_key,
+                ContractError::PairInfoMismatch(id)
+            );
+
+            TMP_PAIR_INFO.remove(deps.storage, id);
             PAIRS.save(deps.storage, &tmp.pair_key, &pair_contract)?;
+            PAIRS_BY_LP_TOKEN.save(deps.storage, &pair_info.liquidity_token, &tmp.pair_key)?;
+            PAIR_LP_TOKENS.save(deps.storage, &tmp.pair_key, &pair_info.liquidity_token)?;
+            PAIR_CREATION_BLOCKS.save(deps.storage, &tmp.pair_key, &env.block.height)?;
+            PAIRS_BY_CREATION_BLOCK.save(
+                deps.storage,
+                (env.block.height, &tmp.pair_key),
+                &pair_contract,
+            )?;
+
+            let mut messages: Vec<CosmosMsg> = vec![];
+            if let Some(locked_liquidity) = tmp.locked_liquidity {
+                let mut funds = vec![];
+                for asset in &locked_liquidity {
+                    match &asset.info {
+                        AssetInfo::NativeToken { denom } => {
+                            funds.push(coin(asset.amount.u128(), denom))
+                        }
+                        AssetInfo::Token { contract_addr } => messages.push(
+                            wasm_execute(
+                                contract_addr,
+                                &Cw20ExecuteMsg::IncreaseAllowance {
+                                    spender: pair_contract.to_string(),
+                                    amount: asset.amount,
+                                    expires: None,
+                                },
+                                vec![],
+                            )?
+                            .into(),
+                        ),
+                    }
+                }
+                funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+                messages.push(
+                    wasm_execute(
+                        &pair_contract,
+                        &PairExecuteMsg::ProvideLiquidity {
+                            assets: locked_liquidity,
+                            slippage_tolerance: None,
+                            auto_stake: Some(false),
+                            receiver: Some(env.contract.address.to_string()),
+                            min_lp_to_receive: None,
+                        },
+                        funds,
+                    )?
+                    .into(),
+                );
+
+                LOCKED_LIQUIDITY.save(
+                    deps.storage,
+                    &tmp.pair_key,
+                    &LockedLiquidityInfo {
+                        pair_addr: pair_contract.clone(),
+                        lp_token: pair_info.liquidity_token,
+                    },
+                )?;
+            }
 
-            Ok(Response::new().add_attributes(vec![
+            if let Some(replaced_pair) = tmp.carry_over_from {
+                let prices =
+                    query_cumulative_prices(&deps.querier, &replaced_pair)?.cumulative_prices;
+                messages.push(
+                    wasm_execute(
+                        &pair_contract,
+                        &PairExecuteMsg::SeedCumulativePrices { prices },
+                        vec![],
+                    )?
+                    .into(),
+                );
+            }
+
+            Ok(Response::new().add_messages(messages).add_attributes(vec![
                 attr("action", "register"),
                 attr("pair_contract_addr", pair_contract),
             ]))
@@ -403,6 +831,19 @@ pub fn deregister(
 
     let pair_addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
     PAIRS.remove(deps.storage, &pair_key(&asset_infos));
+    PAIR_ORACLES.remove(deps.storage, &pair_key(&asset_infos));
+    LOCKED_LIQUIDITY.remove(deps.storage, &pair_key(&asset_infos));
+    if let Some(created_at) =
+        PAIR_CREATION_BLOCKS.may_load(deps.storage, &pair_key(&asset_infos))?
+    {
+        PAIR_CREATION_BLOCKS.remove(deps.storage, &pair_key(&asset_infos));
+        PAIRS_BY_CREATION_BLOCK.remove(deps.storage, (created_at, &pair_key(&asset_infos)));
+    }
+
+    if let Some(lp_token) = PAIR_LP_TOKENS.may_load(deps.storage, &pair_key(&asset_infos))? {
+        PAIR_LP_TOKENS.remove(deps.storage, &pair_key(&asset_infos));
+        PAIRS_BY_LP_TOKEN.remove(deps.storage, &lp_token);
+    }
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if let Some(generator) = config.generator_address {
@@ -424,6 +865,377 @@ pub fn deregister(
     ]))
 }
 
+/// Sets or removes the default max spread inherited by new pairs of the specified type.
+///
+/// * **pair_type** is the pair type the default applies to.
+///
+/// * **max_spread** is the default max spread. None removes it.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_default_max_spread(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_type: PairType,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Only xyk pairs read the default, once at instantiation
+    ensure!(
+        pair_type == PairType::Xyk {},
+        ContractError::DefaultMaxSpreadNotSupported {}
+    );
+
+    if !PAIR_CONFIGS.has(deps.storage, pair_type.to_string()) {
+        return Err(ContractError::PairConfigNotFound {});
+    }
+
+    let mut attrs = vec![
+        attr("action", "set_default_max_spread"),
+        attr("pair_type", pair_type.to_string()),
+    ];
+
+    if let Some(max_spread) = max_spread {
+        ensure!(
+            max_spread <= Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?,
+            ContractError::InvalidDefaultMaxSpread {}
+        );
+        attrs.push(attr("max_spread", max_spread.to_string()));
+        DEFAULT_MAX_SPREADS.save(deps.storage, pair_type.to_string(), &max_spread)?;
+    } else {
+        DEFAULT_MAX_SPREADS.remove(deps.storage, pair_type.to_string());
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Sets or removes the admin of newly instantiated pair contracts.
+///
+/// * **admin** is the pair admin address. None makes the owner administer new pairs.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_pair_admin(
+    deps: DepsMut,
+    info: MessageInfo,
+    admin: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attrs = vec![attr("action", "set_pair_admin")];
+
+    config.pair_admin = addr_opt_validate(deps.api, &admin)?;
+    if let Some(admin) = &config.pair_admin {
+        attrs.push(attr("pair_admin", admin));
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Links a TWAP oracle to a registered pair or removes the existing link.
+///
+/// * **asset_infos** is a vector with assets traded in the pair.
+///
+/// * **oracle** is the oracle address. None removes the link.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_pair_oracle(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_infos: Vec<AssetInfo>,
+    oracle: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = pair_key(&asset_infos);
+    let pair_addr = PAIRS.load(deps.storage, &key)?;
+
+    let mut attrs = vec![
+        attr("action", "set_pair_oracle"),
+        attr("pair_contract_addr", pair_addr),
+    ];
+
+    if let Some(oracle) = oracle {
+        let oracle = deps.api.addr_validate(&oracle)?;
+        attrs.push(attr("oracle", &oracle));
+        PAIR_ORACLES.save(deps.storage, &key, &oracle)?;
+    } else {
+        PAIR_ORACLES.remove(deps.storage, &key);
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Adds pairs to and removes them from the list of pairs which must be migrated.
+///
+/// * **add** is a vector with pair addresses to add to the list.
+///
+/// * **remove** is a vector with pair addresses to remove from the list.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_pairs_to_migrate(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut pairs = PAIRS_TO_MIGRATE.may_load(deps.storage)?.unwrap_or_default();
+
+    for pair in &add {
+        let pair = deps.api.addr_validate(pair)?;
+        if !pairs.contains(&pair) {
+            pairs.push(pair);
+        }
+    }
+
+    let to_remove = remove
+        .iter()
+        .map(|pair| deps.api.addr_validate(pair))
+        .collect::<StdResult<Vec<_>>>()?;
+    pairs.retain(|pair| !to_remove.contains(pair));
+
+    PAIRS_TO_MIGRATE.save(deps.storage, &pairs)?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_pairs_to_migrate"),
+        attr("added", add.join(",")),
+        attr("removed", remove.join(",")),
+    ]))
+}
+
+/// Enables or disables rejecting the creation of pairs which charge maker fees while the
+/// fee address is not set.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_require_fee_address(
+    deps: DepsMut,
+    info: MessageInfo,
+    require: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if require {
+        REQUIRE_FEE_ADDRESS.save(deps.storage, &true)?;
+    } else {
+        REQUIRE_FEE_ADDRESS.remove(deps.storage);
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_require_fee_address"),
+        attr("require", require.to_string()),
+    ]))
+}
+
+/// Enables or disables rejecting the creation of pairs which include an LP token
+/// of a pair registered in this factory.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_reject_lp_token_assets(
+    deps: DepsMut,
+    info: MessageInfo,
+    reject: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if reject {
+        REJECT_LP_TOKEN_ASSETS.save(deps.storage, &true)?;
+    } else {
+        REJECT_LP_TOKEN_ASSETS.remove(deps.storage);
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_reject_lp_token_assets"),
+        attr("reject", reject.to_string()),
+    ]))
+}
+
+/// Sets or removes the number of blocks a fee change must wait before it can be applied.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_fee_change_delay(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delay_blocks: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Settle a pending reduction which already passed its timelock
+    let current_delay = fee_change_delay(deps.storage, env.block.height)?;
+    PENDING_FEE_CHANGE_DELAY.remove(deps.storage);
+
+    let mut attrs = vec![
+        attr("action", "set_fee_change_delay"),
+        attr(
+            "delay_blocks",
+            delay_blocks.map_or("none".to_string(), |delay| delay.to_string()),
+        ),
+    ];
+
+    // Reducing or removing the delay is timelocked by the current delay
+    match (current_delay, delay_blocks) {
+        (Some(current), new) if new.filter(|new| *new >= current).is_none() => {
+            FEE_CHANGE_DELAY.save(deps.storage, &current)?;
+            let effective_block = env.block.height + current;
+            PENDING_FEE_CHANGE_DELAY.save(
+                deps.storage,
+                &PendingFeeChangeDelay {
+                    delay_blocks,
+                    effective_block,
+                },
+            )?;
+            attrs.push(attr("effective_block", effective_block.to_string()));
+        }
+        (_, Some(delay_blocks)) => FEE_CHANGE_DELAY.save(deps.storage, &delay_blocks)?,
+        (_, None) => FEE_CHANGE_DELAY.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Records a fee change of `pair_type` which can be applied with [`apply_fee_change`]
+/// once the fee change delay passes. Replaces the pending change of the pair type if any.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn propose_fee_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_type: PairType,
+    fee_change: FeeChange,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_config = PAIR_CONFIGS
+        .may_load(deps.storage, pair_type.to_string())?
+        .ok_or(ContractError::PairConfigNotFound {})?;
+
+    let new_config = PairConfig {
+        total_fee_bps: fee_change.total_fee_bps,
+        maker_fee_bps: fee_change.maker_fee_bps,
+        ..pair_config
+    };
+    if !new_config.valid_fee_bps() {
+        return Err(ContractError::PairConfigInvalidFeeBps {});
+    }
+
+    let effective_block =
+        env.block.height + fee_change_delay(deps.storage, env.block.height)?.unwrap_or_default();
+    let expiry_block = effective_block + FEE_CHANGE_EXPIRY_BLOCKS;
+    PENDING_FEE_CHANGES.save(
+        deps.storage,
+        pair_type.to_string(),
+        &PendingFeeChange {
+            config: fee_change.clone(),
+            proposed_block: env.block.height,
+            effective_block,
+            expiry_block,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "propose_fee_change"),
+        attr("pair_type", pair_type.to_string()),
+        attr("total_fee_bps", fee_change.total_fee_bps.to_string()),
+        attr("maker_fee_bps", fee_change.maker_fee_bps.to_string()),
+        attr("effective_block", effective_block.to_string()),
+        attr("expiry_block", expiry_block.to_string()),
+    ]))
+}
+
+/// Applies the pending fee change of `pair_type` once its effective block is reached
+/// and before it expires. A delay raised after the proposal applies to it as well.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn apply_fee_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_type: PairType,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pending = PENDING_FEE_CHANGES
+        .may_load(deps.storage, pair_type.to_string())?
+        .ok_or(ContractError::FeeChangeNotFound {})?;
+
+    // The delay might have been raised after the proposal
+    let effective_block = pending.effective_block.max(
+        pending.proposed_block
+            + fee_change_delay(deps.storage, env.block.height)?.unwrap_or_default(),
+    );
+    ensure!(
+        env.block.height >= effective_block,
+        ContractError::FeeChangeNotReady { effective_block }
+    );
+    ensure!(
+        env.block.height <= pending.expiry_block,
+        ContractError::FeeChangeExpired {
+            expiry_block: pending.expiry_block
+        }
+    );
+
+    PAIR_CONFIGS.update::<_, ContractError>(deps.storage, pair_type.to_string(), |config| {
+        let mut config = config.ok_or(ContractError::PairConfigNotFound {})?;
+        config.total_fee_bps = pending.config.total_fee_bps;
+        config.maker_fee_bps = pending.config.maker_fee_bps;
+        Ok(config)
+    })?;
+    PENDING_FEE_CHANGES.remove(deps.storage, pair_type.to_string());
+
+    Ok(Response::new().add_attributes([
+        attr("action", "apply_fee_change"),
+        attr("pair_type", pair_type.to_string()),
+    ]))
+}
+
 pub fn update_tracker_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -463,20 +1275,116 @@ pub fn update_tracker_config(
 /// * **QueryMsg::Pairs { start_after, limit }** Returns an array that contains items of type [`PairInfo`].
 /// This returns information about multiple Astroport pairs
 ///
+/// * **QueryMsg::PairExists { asset_infos }** Returns whether a pair with the specified assets is registered.
+///
+/// * **QueryMsg::PairByLpToken { lp_token }** Returns a [`PairInfo`] object for the pair which owns the specified LP token.
+///
 /// * **QueryMsg::FeeInfo { pair_type }** Returns the fee structure (total and maker fees) for a specific pair type.
 ///
+/// * **QueryMsg::FeeInfoByPair { pair_addr }** Returns the fee structure (total and maker fees) for a registered pair.
+///
 /// * **QueryMsg::BlacklistedPairTypes {}** Returns a vector that contains blacklisted pair types (pair types that cannot get ASTRO emissions).
+///
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
+///
+/// * **QueryMsg::LockedLiquidity { asset_infos }** Returns the protocol-owned liquidity locked for a pair.
+///
+/// * **QueryMsg::DefaultMaxSpread { pair_type }** Returns the default max spread inherited by new pairs of the specified type.
+///
+/// * **QueryMsg::PairOracle { asset_infos }** Returns the TWAP oracle linked to a pair if it is set.
+///
+/// * **QueryMsg::PairsCreatedBetween { start_block, end_block, start_after, limit }** Returns an array of [`PairInfo`]
+/// objects for pairs registered within the specified block range. Pairs registered before 1.10.0 are not included.
+///
+/// * **QueryMsg::PairKey { asset_infos }** Returns the key under which a pair with the specified assets is stored.
+///
+/// * **QueryMsg::PairsToMigrate {}** Returns the pairs which must be migrated before they can be used again.
+///
+/// * **QueryMsg::IsOwner { address }** Returns whether the specified address is the current owner.
+///
+/// * **QueryMsg::PairTypesInfo {}** Returns all configured pair types with their fee and status settings.
+///
+/// * **QueryMsg::MakerFeeCollectionActive {}** Returns whether the fee address is set and maker fees are collected.
+///
+/// * **QueryMsg::RejectLpTokenAssets {}** Returns whether pairs including LP tokens of registered pairs are rejected.
+///
+/// * **QueryMsg::FeeChangeDelay {}** Returns the number of blocks fee changes must wait before they can be applied.
+///
+/// * **QueryMsg::PendingFeeChange { pair_type }** Returns the pending fee change of a pair type.
+///
+/// * **QueryMsg::PendingFeeChangeDelay {}** Returns the pending reduction or removal of the fee change delay.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
         QueryMsg::Pair { asset_infos } => to_json_binary(&query_pair(deps, asset_infos)?),
         QueryMsg::Pairs { start_after, limit } => {
             to_json_binary(&query_pairs(deps, start_after, limit)?)
         }
+        QueryMsg::PairExists { asset_infos } => {
+            to_json_binary(&PAIRS.has(deps.storage, &pair_key(&asset_infos)))
+        }
+        QueryMsg::PairByLpToken { lp_token } => {
+            to_json_binary(&query_pair_by_lp_token(deps, lp_token)?)
+        }
         QueryMsg::FeeInfo { pair_type } => to_json_binary(&query_fee_info(deps, pair_type)?),
+        QueryMsg::FeeInfoByPair { pair_addr } => {
+            to_json_binary(&query_fee_info_by_pair(deps, pair_addr)?)
+        }
         QueryMsg::BlacklistedPairTypes {} => to_json_binary(&query_blacklisted_pair_types(deps)?),
         QueryMsg::TrackerConfig {} => to_json_binary(&query_tracker_config(deps)?),
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::LockedLiquidity { asset_infos } => {
+            to_json_binary(&query_locked_liquidity(deps, env, asset_infos)?)
+        }
+        QueryMsg::DefaultMaxSpread { pair_type } => {
+            to_json_binary(&DEFAULT_MAX_SPREADS.may_load(deps.storage, pair_type.to_string())?)
+        }
+        QueryMsg::PairOracle { asset_infos } => {
+            to_json_binary(&PAIR_ORACLES.may_load(deps.storage, &pair_key(&asset_infos))?)
+        }
+        QueryMsg::PairsCreatedBetween {
+            start_block,
+            end_block,
+            start_after,
+            limit,
+        } => to_json_binary(&query_pairs_created_between(
+            deps,
+            start_block,
+            end_block,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::PairKey { asset_infos } => to_json_binary(&Binary(pair_key(&asset_infos))),
+        QueryMsg::PairsToMigrate {} => {
+            to_json_binary(&PAIRS_TO_MIGRATE.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::IsOwner { address } => to_json_binary(&query_is_owner(
+            deps.api,
+            &CONFIG.load(deps.storage)?.owner,
+            &address,
+        )?),
+        QueryMsg::PairTypesInfo {} => to_json_binary(&query_pair_types_info(deps)?),
+        QueryMsg::MakerFeeCollectionActive {} => {
+            to_json_binary(&CONFIG.load(deps.storage)?.fee_address.is_some())
+        }
+        QueryMsg::RejectLpTokenAssets {} => to_json_binary(
+            &REJECT_LP_TOKEN_ASSETS
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::FeeChangeDelay {} => {
+            to_json_binary(&fee_change_delay(deps.storage, env.block.height)?)
+        }
+        QueryMsg::PendingFeeChange { pair_type } => {
+            to_json_binary(&PENDING_FEE_CHANGES.may_load(deps.storage, pair_type.to_string())?)
+        }
+        QueryMsg::PendingFeeChangeDelay {} => {
+            let pending = PENDING_FEE_CHANGE_DELAY
+                .may_load(deps.storage)?
+                .filter(|pending| env.block.height < pending.effective_block);
+            to_json_binary(&pending)
+        }
     }
 }
 
@@ -497,20 +1405,26 @@ pub fn query_blacklisted_pair_types(deps: Deps) -> StdResult<Vec<PairType>> {
         .collect()
 }
 
+/// Returns the configurations of all pair types.
+pub fn query_pair_types_info(deps: Deps) -> StdResult<Vec<PairConfig>> {
+    PAIR_CONFIGS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
 /// Returns general contract parameters using a custom [`ConfigResponse`] structure.
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     let resp = ConfigResponse {
         owner: config.owner,
         token_code_id: config.token_code_id,
-        pair_configs: PAIR_CONFIGS
-            .range(deps.storage, None, None, Order::Ascending)
-            .map(|item| Ok(item?.1))
-            .collect::<StdResult<Vec<_>>>()?,
+        pair_configs: query_pair_types_info(deps)?,
         fee_address: config.fee_address,
         generator_address: config.generator_address,
         whitelist_code_id: config.whitelist_code_id,
         coin_registry_address: config.coin_registry_address,
+        pair_admin: config.pair_admin,
     };
 
     Ok(resp)
@@ -523,6 +1437,39 @@ pub fn query_pair(deps: Deps, asset_infos: Vec<AssetInfo>) -> StdResult<PairInfo
     query_pair_info(&deps.querier, pair_addr)
 }
 
+/// Returns the amount of LP tokens locked in the factory for a pair created with locked liquidity.
+///
+/// * **asset_infos** is a vector with assets traded in the pair.
+pub fn query_locked_liquidity(
+    deps: Deps,
+    env: Env,
+    asset_infos: Vec<AssetInfo>,
+) -> StdResult<LockedLiquidityResponse> {
+    let info = LOCKED_LIQUIDITY
+        .may_load(deps.storage, &pair_key(&asset_infos))?
+        .ok_or_else(|| StdError::generic_err("Pair has no locked liquidity"))?;
+    let amount = deps
+        .querier
+        .query_balance(&env.contract.address, &info.lp_token)?
+        .amount;
+
+    Ok(LockedLiquidityResponse {
+        pair_addr: info.pair_addr,
+        lp_token: info.lp_token,
+        lock_addr: env.contract.address,
+        amount,
+    })
+}
+
+/// Returns a pair's data using the LP token denom minted by that pair.
+///
+/// * **lp_token** is the LP token denom of the pair.
+pub fn query_pair_by_lp_token(deps: Deps, lp_token: String) -> StdResult<PairInfo> {
+    let key = PAIRS_BY_LP_TOKEN.load(deps.storage, &lp_token)?;
+    let pair_addr = PAIRS.load(deps.storage, &key)?;
+    query_pair_info(&deps.querier, pair_addr)
+}
+
 /// Returns a vector with pair data that contains items of type [`PairInfo`]. Querying starts at `start_after` and returns `limit` pairs.
 /// * **start_after** is a field which accepts a vector with items of type [`AssetInfo`].
 /// This is the pair from which we start a query.
@@ -541,6 +1488,32 @@ pub fn query_pairs(
     Ok(PairsResponse { pairs })
 }
 
+/// Returns an array with pairs registered between `start_block` and `end_block` (both inclusive)
+/// ordered by their creation block.
+/// The creation block of pairs registered before 1.10.0 is unknown, thus they are never returned.
+///
+/// * **limit** sets the number of pairs to be retrieved.
+pub fn query_pairs_created_between(
+    deps: Deps,
+    start_block: u64,
+    end_block: u64,
+    start_after: Option<Vec<AssetInfo>>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    if start_block > end_block {
+        return Err(StdError::generic_err(
+            "start_block must be less than or equal to end_block",
+        ));
+    }
+
+    let pairs = read_pairs_created_between(deps, start_block, end_block, start_after, limit)?
+        .iter()
+        .map(|pair_addr| query_pair_info(&deps.querier, pair_addr))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PairsResponse { pairs })
+}
+
 /// Returns the fee setup for a specific pair type using a [`FeeInfoResponse`] struct.
 /// * **pair_type** is a struct that represents the fee information (total and maker fees) for a specific pair type.
 pub fn query_fee_info(deps: Deps, pair_type: PairType) -> StdResult<FeeInfoResponse> {
@@ -554,6 +1527,23 @@ pub fn query_fee_info(deps: Deps, pair_type: PairType) -> StdResult<FeeInfoRespo
     })
 }
 
+/// Returns the fee setup of a registered pair using a [`FeeInfoResponse`] struct.
+/// * **pair_addr** is the address of the pair. The fee setup is resolved by the pair's type.
+pub fn query_fee_info_by_pair(deps: Deps, pair_addr: String) -> StdResult<FeeInfoResponse> {
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    let pair_info = query_pair_info(&deps.querier, &pair_addr)?;
+
+    // Make sure the pair is registered in this factory
+    let registered = PAIRS.may_load(deps.storage, &pair_key(&pair_info.asset_infos))?;
+    if registered.as_ref() != Some(&pair_addr) {
+        return Err(StdError::generic_err(format!(
+            "Pair {pair_addr} is not registered in the factory"
+        )));
+    }
+
+    query_fee_info(deps, pair_info.pair_type)
+}
+
 pub fn query_tracker_config(deps: Deps) -> StdResult<TrackerConfig> {
     let tracker_config = TRACKER_CONFIG.load(deps.storage).map_err(|_| {
         StdError::generic_err("Tracker config is not set in the factory. It can't be provided")
@@ -571,42 +1561,48 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
     let contract_version = get_contract_version(deps.storage)?;
 
     match contract_version.contract.as_ref() {
-        "astroport-factory" => match contract_version.version.as_ref() {
-            // pisco-1, phoenix-1, injective-1, pion-1, neutron-1, pacific-1: 1.5.1
-            // injective-888: 1.6.0
-            // atlantic-2: 1.3.1
-            "1.3.1" | "1.5.1" | "1.6.0" => {
-                migrate_pair_configs(deps.storage)?;
-                if let Some(tracker_config) = msg.tracker_config {
-                    TRACKER_CONFIG.save(
-                        deps.storage,
-                        &TrackerConfig {
-                            code_id: tracker_config.code_id,
-                            token_factory_addr: deps
-                                .api
-                                .addr_validate(&tracker_config.token_factory_addr)?
-                                .to_string(),
-                        },
-                    )?;
+        "astroport-factory" => {
+            match contract_version.version.as_ref() {
+                // pisco-1, phoenix-1, injective-1, pion-1, neutron-1, pacific-1: 1.5.1
+                // injective-888: 1.6.0
+                // atlantic-2: 1.3.1
+                "1.3.1" | "1.5.1" | "1.6.0" => {
+                    migrate_pair_configs(deps.storage)?;
+                    if let Some(tracker_config) = msg.tracker_config {
+                        TRACKER_CONFIG.save(
+                            deps.storage,
+                            &TrackerConfig {
+                                code_id: tracker_config.code_id,
+                                token_factory_addr: deps
+                                    .api
+                                    .addr_validate(&tracker_config.token_factory_addr)?
+                                    .to_string(),
+                            },
+                        )?;
+                    }
                 }
-            }
-            "1.7.0" => {
-                if let Some(tracker_config) = msg.tracker_config {
-                    TRACKER_CONFIG.save(
-                        deps.storage,
-                        &TrackerConfig {
-                            code_id: tracker_config.code_id,
-                            token_factory_addr: deps
-                                .api
-                                .addr_validate(&tracker_config.token_factory_addr)?
-                                .to_string(),
-                        },
-                    )?;
+                "1.7.0" => {
+                    if let Some(tracker_config) = msg.tracker_config {
+                        TRACKER_CONFIG.save(
+                            deps.storage,
+                            &TrackerConfig {
+                                code_id: tracker_config.code_id,
+                                token_factory_addr: deps
+                                    .api
+                                    .addr_validate(&tracker_config.token_factory_addr)?
+                                    .to_string(),
+                            },
+                        )?;
+                    }
                 }
+                "1.8.0" | "1.8.1" | "1.9.0" => {}
+                _ => return Err(ContractError::MigrationError {}),
             }
-            "1.8.0" | "1.8.1" => {}
-            _ => return Err(ContractError::MigrationError {}),
-        },
+
+            // Every version accepted above predates the LP token index. Later versions index pairs
+            // on registration and must not rerun the backfill as it queries every registered pair
+            backfill_pairs_by_lp_token(deps.storage, deps.querier)?;
+        }
         _ => return Err(ContractError::MigrationError {}),
     }
 
@@ -1,3 +1,4 @@
+use astroport::pair::{MAX_ALLOWED_SLIPPAGE, WEIGHTS_TOTAL};
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
@@ -31,9 +32,57 @@ pub enum ContractError {
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
+    #[error(
+        "Weighted pair must have two assets with non-zero weights that sum to {}",
+        WEIGHTS_TOTAL
+    )]
+    InvalidWeights {},
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
+
+    #[error("Instantiated pair doesn't match the assets requested in reply {0}")]
+    PairInfoMismatch(u64),
+
+    #[error("Locked liquidity must contain a non-zero amount of every pair asset")]
+    InvalidLockedLiquidity {},
+
+    #[error("Insufficient funds sent for locked liquidity: {denom}")]
+    InsufficientLockedLiquidityFunds { denom: String },
+
+    #[error("Default max spread must not exceed {MAX_ALLOWED_SLIPPAGE}")]
+    InvalidDefaultMaxSpread {},
+
+    #[error("Default max spread is supported by xyk pairs only")]
+    DefaultMaxSpreadNotSupported {},
+
+    #[error("The batch must contain at least one pair")]
+    EmptyPairsBatch {},
+
+    #[error("Funds can't be sent along with a batch of pairs")]
+    FundsInPairsBatch {},
+
+    #[error("Pairs charging maker fees can't be created while the fee address is not set")]
+    FeeAddressNotSet {},
+
+    #[error("The replacement pair must have the same type and assets as the replaced pair")]
+    InvalidReplacementPair {},
+
+    #[error("{asset} is an LP token of a registered pair and can't be a pair asset")]
+    LpTokenAsAsset { asset: String },
+
+    #[error("Fees of existing pair types can only be changed via ProposeFeeChange while the fee change delay is set")]
+    FeeChangeTimelocked {},
+
+    #[error("There is no pending fee change for this pair type")]
+    FeeChangeNotFound {},
+
+    #[error("The fee change can't be applied before block {effective_block}")]
+    FeeChangeNotReady { effective_block: u64 },
+
+    #[error("The fee change expired at block {expiry_block}")]
+    FeeChangeExpired { expiry_block: u64 },
 }
@@ -1,10 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{StdResult, Storage};
+use cosmwasm_std::{Order, QuerierWrapper, StdResult, Storage};
 use cw_storage_plus::Map;
 
 use astroport::factory::{PairConfig, PairType};
 
-use crate::state::PAIR_CONFIGS;
+use crate::querier::query_pair_info;
+use crate::state::{PAIRS, PAIRS_BY_LP_TOKEN, PAIR_CONFIGS, PAIR_LP_TOKENS};
 
 #[cw_serde]
 pub enum OldPairType {
@@ -66,3 +67,24 @@ pub fn migrate_pair_configs(storage: &mut dyn Storage) -> StdResult<()> {
 
     Ok(())
 }
+
+/// Indexes the LP tokens of pairs registered before [`PAIRS_BY_LP_TOKEN`] was introduced.
+/// Queries every registered pair, thus must only run once when migrating from a version
+/// without the index. Pairs which can't be queried are skipped.
+pub fn backfill_pairs_by_lp_token(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+) -> StdResult<()> {
+    let pairs = PAIRS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (key, pair_addr) in pairs {
+        if let Ok(pair_info) = query_pair_info(&querier, &pair_addr) {
+            PAIRS_BY_LP_TOKEN.save(storage, &pair_info.liquidity_token, &key)?;
+            PAIR_LP_TOKENS.save(storage, &key, &pair_info.liquidity_token)?;
+        }
+    }
+
+    Ok(())
+}
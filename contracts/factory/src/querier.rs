@@ -1,5 +1,5 @@
 use astroport::asset::PairInfo;
-use astroport::pair::QueryMsg;
+use astroport::pair::{CumulativePricesResponse, QueryMsg};
 use cosmwasm_std::{QuerierWrapper, StdResult};
 
 /// Returns information about a pair (using the [`PairInfo`] struct).
@@ -11,3 +11,13 @@ pub fn query_pair_info(
 ) -> StdResult<PairInfo> {
     querier.query_wasm_smart(pair_contract, &QueryMsg::Pair {})
 }
+
+/// Returns the current cumulative prices of a pair.
+///
+/// `pair_contract` is the pair for which to retrieve the cumulative prices.
+pub fn query_cumulative_prices(
+    querier: &QuerierWrapper,
+    pair_contract: impl Into<String>,
+) -> StdResult<CumulativePricesResponse> {
+    querier.query_wasm_smart(pair_contract, &QueryMsg::CumulativePrices {})
+}
@@ -1,46 +1,72 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Api, Deps, Order, StdResult};
+use cosmwasm_std::{Addr, Api, Decimal, Deps, Order, StdResult, Storage};
 use cw_storage_plus::{Bound, Item, Map};
 use itertools::Itertools;
 
 use crate::error::ContractError;
-use astroport::asset::AssetInfo;
+use astroport::asset::{Asset, AssetInfo};
 use astroport::common::OwnershipProposal;
-use astroport::factory::{Config, PairConfig, TrackerConfig};
+pub use astroport::factory::{pair_key, PAIRS};
+use astroport::factory::{
+    Config, PairConfig, PendingFeeChange, PendingFeeChangeDelay, TrackerConfig,
+};
 /// This is an intermediate structure for storing a pair's key. It is used in a submessage response.
 #[cw_serde]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
+    /// The initial liquidity to provide and lock once the pair is instantiated
+    #[serde(default)]
+    pub locked_liquidity: Option<Vec<Asset>>,
+    /// The replaced pair whose cumulative prices are seeded into the new pair
+    #[serde(default)]
+    pub carry_over_from: Option<Addr>,
 }
 
-/// Saves a pair's key
-pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
+/// This structure describes a pair whose initial liquidity is locked in the factory.
+#[cw_serde]
+pub struct LockedLiquidityInfo {
+    pub pair_addr: Addr,
+    pub lp_token: String,
+}
+
+/// Saves a pair's key for every pair instantiation in flight. The key is the reply id of the
+/// instantiation submessage so overlapping creations can't clobber each other's data.
+pub const TMP_PAIR_INFO: Map<u64, TmpPairInfo> = Map::new("tmp_pair_infos");
+
+/// Stores the reply id which will be used for the next pair instantiation
+pub const NEXT_REPLY_ID: Item<u64> = Item::new("next_reply_id");
 
 /// Saves factory settings
 pub const CONFIG: Item<Config> = Item::new("config");
 
-/// Saves created pairs (from olders to latest)
-pub const PAIRS: Map<&[u8], Addr> = Map::new("pair_info");
+/// Maps a pair key to the block height at which the pair was registered
+pub const PAIR_CREATION_BLOCKS: Map<&[u8], u64> = Map::new("pair_creation_blocks");
+
+/// Indexes registered pairs by their creation block. Key: (block height, pair key)
+pub const PAIRS_BY_CREATION_BLOCK: Map<(u64, &[u8]), Addr> = Map::new("pairs_by_creation_block");
+
+/// Maps an LP token denom to the key of the pair it belongs to
+pub const PAIRS_BY_LP_TOKEN: Map<&str, Vec<u8>> = Map::new("pairs_by_lp_token");
+
+/// Maps a pair key to the LP token denom of the pair. Used to clean up [`PAIRS_BY_LP_TOKEN`]
+/// when the pair is deregistered
+pub const PAIR_LP_TOKENS: Map<&[u8], String> = Map::new("pair_lp_tokens");
+
+/// Maps a pair key to the pair whose initial LP tokens are locked in the factory
+pub const LOCKED_LIQUIDITY: Map<&[u8], LockedLiquidityInfo> = Map::new("locked_liquidity");
+
+/// Maps a pair key to the TWAP oracle registered for that pair
+pub const PAIR_ORACLES: Map<&[u8], Addr> = Map::new("pair_oracles");
 
 /// Track config for tracking contract
 pub const TRACKER_CONFIG: Item<TrackerConfig> = Item::new("tracker_config");
 
-/// Calculates a pair key from the specified parameters in the `asset_infos` variable.
-///
-/// `asset_infos` is an array with multiple items of type [`AssetInfo`].
-pub fn pair_key(asset_infos: &[AssetInfo]) -> Vec<u8> {
-    asset_infos
-        .iter()
-        .map(AssetInfo::as_bytes)
-        .sorted()
-        .flatten()
-        .copied()
-        .collect()
-}
-
 /// Saves pair type configurations
 pub const PAIR_CONFIGS: Map<String, PairConfig> = Map::new("pair_configs");
 
+/// Default max spreads inherited by new pairs. Key: pair type
+pub const DEFAULT_MAX_SPREADS: Map<String, Decimal> = Map::new("default_max_spreads");
+
 /// ## Pagination settings
 /// The maximum limit for reading pairs from [`PAIRS`]
 const MAX_LIMIT: u32 = 30;
@@ -86,6 +112,48 @@ pub fn read_pairs(
     }
 }
 
+/// Reads pairs registered between `start_block` and `end_block` (both inclusive)
+/// from the oldest to the latest one.
+///
+/// `start_after` is the pair after which to start reading. It must be registered in the factory.
+///
+/// `limit` is the number of items to retrieve.
+pub fn read_pairs_created_between(
+    deps: Deps,
+    start_block: u64,
+    end_block: u64,
+    start_after: Option<Vec<AssetInfo>>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start_after = start_after
+        .map(|asset_infos| {
+            let key = pair_key(&asset_infos);
+            PAIR_CREATION_BLOCKS
+                .load(deps.storage, &key)
+                .map(|block| (block, key))
+        })
+        .transpose()?;
+    let min = match &start_after {
+        Some((block, key)) if *block >= start_block => Bound::exclusive((*block, key.as_slice())),
+        _ => Bound::inclusive((start_block, &[] as &[u8])),
+    };
+
+    PAIRS_BY_CREATION_BLOCK
+        .range(deps.storage, Some(min), None, Order::Ascending)
+        .take_while(|item| {
+            item.as_ref()
+                .map_or(true, |((block, _), _)| *block <= end_block)
+        })
+        .take(limit)
+        .map(|item| {
+            let (_, pair_addr) = item?;
+            Ok(pair_addr)
+        })
+        .collect()
+}
+
 /// Calculates the key of a pair from which to start reading data.
 ///
 /// `start_after` is an [`Option`] type that accepts [`AssetInfo`] elements.
@@ -115,9 +183,38 @@ pub(crate) fn check_asset_infos(
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
-/// This state key isn't used anymore but left for backward compatability with old pairs
+/// Pairs which must be migrated before they can be used again.
+/// Only older pair versions read this key
 pub const PAIRS_TO_MIGRATE: Item<Vec<Addr>> = Item::new("pairs_to_migrate");
 
+/// Whether pairs charging maker fees can only be created while the fee address is set.
+/// Creation is allowed with a warning if not set
+pub const REQUIRE_FEE_ADDRESS: Item<bool> = Item::new("require_fee_address");
+
+/// Whether pairs including an LP token of a registered pair can't be created.
+/// Such pairs are allowed if not set
+pub const REJECT_LP_TOKEN_ASSETS: Item<bool> = Item::new("reject_lp_token_assets");
+
+/// Number of blocks a fee change must wait before it can be applied.
+/// Fee changes take effect immediately if not set
+pub const FEE_CHANGE_DELAY: Item<u64> = Item::new("fee_change_delay");
+
+/// A reduction or removal of [`FEE_CHANGE_DELAY`] waiting for the current delay to pass
+pub const PENDING_FEE_CHANGE_DELAY: Item<PendingFeeChangeDelay> =
+    Item::new("pending_fee_change_delay");
+
+/// Returns the fee change delay in effect at `block_height`, including a pending reduction
+/// which already passed its timelock.
+pub fn fee_change_delay(storage: &dyn Storage, block_height: u64) -> StdResult<Option<u64>> {
+    match PENDING_FEE_CHANGE_DELAY.may_load(storage)? {
+        Some(pending) if block_height >= pending.effective_block => Ok(pending.delay_blocks),
+        _ => FEE_CHANGE_DELAY.may_load(storage),
+    }
+}
+
+/// Proposed fee changes waiting for their timelock to pass. Key: pair type
+pub const PENDING_FEE_CHANGES: Map<String, PendingFeeChange> = Map::new("pending_fee_changes");
+
 #[cfg(test)]
 mod tests {
     use astroport::asset::{native_asset_info, token_asset_info};
@@ -1,19 +1,21 @@
 use cosmwasm_std::{
-    attr, from_json, to_json_binary, Addr, Reply, ReplyOn, SubMsg, SubMsgResponse, SubMsgResult,
-    WasmMsg,
+    attr, from_json, to_json_binary, Addr, Binary, OwnedDeps, Reply, ReplyOn, Storage, SubMsg,
+    SubMsgResponse, SubMsgResult, WasmMsg,
 };
 
 use crate::mock_querier::mock_dependencies;
-use crate::state::CONFIG;
+use crate::state::{pair_key, CONFIG, PAIRS};
 use crate::{
-    contract::{execute, instantiate, query},
+    contract::{execute, instantiate, migrate, query},
     error::ContractError,
 };
 
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, PairConfig, PairType, PairsResponse, QueryMsg,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairConfig, PairType, PairsResponse,
+    QueryMsg,
 };
+use cw2::set_contract_version;
 
 use crate::contract::reply;
 use astroport::pair::InstantiateMsg as PairInstantiateMsg;
@@ -618,9 +620,9 @@ fn register() {
         }
     );
 
-    // Check pair was registered
+    // Temporary pair info is consumed by the first reply so it can't be replayed
     let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
-    assert_eq!(res, ContractError::PairWasRegistered {});
+    assert_eq!(res, ContractError::FailedToParseReply {});
 
     // Store one more item to test query pairs
     let asset_infos_2 = vec![
@@ -666,7 +668,7 @@ fn register() {
         .unwrap();
 
     let reply_msg = Reply {
-        id: 1,
+        id: 2,
         result: SubMsgResult::Ok(SubMsgResponse {
             events: vec![],
             data: Some(encoded_instantiate_reply.into()),
@@ -781,3 +783,400 @@ fn register() {
         },]
     );
 }
+
+fn instantiate_reply_msg(id: u64, contract_address: &str) -> Reply {
+    let instantiate_reply = MsgInstantiateContractResponse {
+        contract_address: contract_address.to_string(),
+        data: vec![],
+    };
+
+    let mut encoded_instantiate_reply = Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+    instantiate_reply
+        .encode(&mut encoded_instantiate_reply)
+        .unwrap();
+
+    Reply {
+        id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(encoded_instantiate_reply.into()),
+        }),
+    }
+}
+
+#[test]
+fn overlapping_create_pair_replies() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 123u64,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 100,
+            maker_fee_bps: 10,
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: "owner0000".to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos_1 = vec![
+        AssetInfo::native("uluna"),
+        AssetInfo::cw20_unchecked("asset0000"),
+    ];
+    let asset_infos_2 = vec![
+        AssetInfo::native("uluna"),
+        AssetInfo::cw20_unchecked("asset0001"),
+    ];
+
+    // Both pairs are created before any reply is processed
+    for (asset_infos, expected_id) in [(&asset_infos_1, 1), (&asset_infos_2, 2)] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0000", &[]),
+            ExecuteMsg::CreatePair {
+                pair_type: PairType::Xyk {},
+                asset_infos: asset_infos.clone(),
+                init_params: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages[0].id, expected_id);
+    }
+
+    let pair1_addr = "pair0000".to_string();
+    let pair1_info = PairInfo {
+        asset_infos: asset_infos_1.clone(),
+        contract_addr: Addr::unchecked(&pair1_addr),
+        liquidity_token: "liquidity0000".to_owned(),
+        pair_type: PairType::Xyk {},
+    };
+    let pair2_addr = "pair0001".to_string();
+    let pair2_info = PairInfo {
+        asset_infos: asset_infos_2.clone(),
+        contract_addr: Addr::unchecked(&pair2_addr),
+        liquidity_token: "liquidity0001".to_owned(),
+        pair_type: PairType::Xyk {},
+    };
+    deps.querier
+        .with_astroport_pairs(&[(&pair1_addr, &pair1_info), (&pair2_addr, &pair2_info)]);
+
+    // A reply carrying the wrong pair contract is rejected and doesn't corrupt anything
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply_msg(2, &pair1_addr),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::PairInfoMismatch(2));
+
+    // Replies are processed in reverse order
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply_msg(2, &pair2_addr),
+    )
+    .unwrap();
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply_msg(1, &pair1_addr),
+    )
+    .unwrap();
+
+    for (asset_infos, pair_info) in [(asset_infos_1, pair1_info), (asset_infos_2, pair2_info)] {
+        let res: PairInfo =
+            from_json(&query(deps.as_ref(), mock_env(), QueryMsg::Pair { asset_infos }).unwrap())
+                .unwrap();
+        assert_eq!(res, pair_info);
+    }
+
+    // Unknown reply ids are rejected
+    let err = reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply_msg(3, &pair2_addr),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FailedToParseReply {});
+}
+
+#[test]
+fn pair_key_query() {
+    let deps = mock_dependencies(&[]);
+
+    let asset_infos = vec![
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+    ];
+    let query_key = |asset_infos: Vec<AssetInfo>| -> Binary {
+        from_json(&query(deps.as_ref(), mock_env(), QueryMsg::PairKey { asset_infos }).unwrap())
+            .unwrap()
+    };
+
+    let key = query_key(asset_infos.clone());
+    assert_eq!(key, Binary(pair_key(&asset_infos)));
+    assert_eq!(key, Binary(b"asset0000uusd".to_vec()));
+
+    // The key doesn't depend on the assets order
+    let reversed = asset_infos.into_iter().rev().collect();
+    assert_eq!(query_key(reversed), key);
+}
+
+#[test]
+fn set_pairs_to_migrate() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![],
+        token_code_id: 123u64,
+        fee_address: None,
+        owner: owner.to_string(),
+        generator_address: None,
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+
+    // Mirrors the raw query older pairs run before each operation
+    let migration_check = |deps: &OwnedDeps<_, _, _>, pair: &str| -> bool {
+        deps.storage
+            .get(b"pairs_to_migrate")
+            .map(|value| from_json::<Vec<Addr>>(&value).unwrap())
+            .unwrap_or_default()
+            .contains(&Addr::unchecked(pair))
+    };
+    let query_pairs = |deps: &OwnedDeps<_, _, _>| -> Vec<Addr> {
+        from_json(&query(deps.as_ref(), mock_env(), QueryMsg::PairsToMigrate {}).unwrap()).unwrap()
+    };
+
+    assert!(query_pairs(&deps).is_empty());
+    assert!(!migration_check(&deps, "pair0000"));
+
+    let msg = ExecuteMsg::SetPairsToMigrate {
+        add: vec!["pair0000".to_string(), "pair0001".to_string()],
+        remove: vec![],
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("random", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    // Adding the same pairs again doesn't duplicate them
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+    assert_eq!(
+        query_pairs(&deps),
+        vec![Addr::unchecked("pair0000"), Addr::unchecked("pair0001")]
+    );
+    assert!(migration_check(&deps, "pair0000"));
+
+    let msg = ExecuteMsg::SetPairsToMigrate {
+        add: vec![],
+        remove: vec!["pair0000".to_string()],
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+    assert_eq!(query_pairs(&deps), vec![Addr::unchecked("pair0001")]);
+    assert!(!migration_check(&deps, "pair0000"));
+    assert!(migration_check(&deps, "pair0001"));
+}
+
+#[test]
+fn migrate_backfills_pairs_by_lp_token() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![],
+        token_code_id: 123u64,
+        fee_address: None,
+        owner: owner.to_string(),
+        generator_address: None,
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+
+    let asset_infos = vec![AssetInfo::native("uusd"), AssetInfo::native("uluna")];
+    let broken_asset_infos = vec![AssetInfo::native("uusd"), AssetInfo::native("uatom")];
+    let pair_addr = "pair0000".to_string();
+    let pair_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked(&pair_addr),
+        liquidity_token: "liquidity0000".to_owned(),
+        pair_type: PairType::Xyk {},
+    };
+    deps.querier
+        .with_astroport_pairs(&[(&pair_addr, &pair_info)]);
+
+    // Emulate pairs registered by an older version. The second one can't be queried
+    PAIRS
+        .save(
+            deps.as_mut().storage,
+            &pair_key(&asset_infos),
+            &Addr::unchecked(&pair_addr),
+        )
+        .unwrap();
+    PAIRS
+        .save(
+            deps.as_mut().storage,
+            &pair_key(&broken_asset_infos),
+            &Addr::unchecked("pair0001"),
+        )
+        .unwrap();
+    set_contract_version(deps.as_mut().storage, "astroport-factory", "1.9.0").unwrap();
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            tracker_config: None,
+        },
+    )
+    .unwrap();
+
+    let res: PairInfo = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairByLpToken {
+                lp_token: "liquidity0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res, pair_info);
+
+    // The backfill runs once. Migrating from the current version is rejected
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            tracker_config: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+
+    // A pair which can't be queried can still be deregistered
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::Deregister {
+            asset_infos: broken_asset_infos,
+        },
+    )
+    .unwrap();
+
+    // Deregistering removes the backfilled LP token from the index
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::Deregister { asset_infos },
+    )
+    .unwrap();
+    query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByLpToken {
+            lp_token: "liquidity0000".to_string(),
+        },
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn reject_unindexed_lp_token_assets() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 123u64,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 100,
+            maker_fee_bps: 10,
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        owner: owner.to_string(),
+        generator_address: None,
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info(owner, &[]), msg).unwrap();
+
+    // A pair registered before the LP token index existed
+    let asset_infos = vec![AssetInfo::native("uusd"), AssetInfo::native("uluna")];
+    let pair_addr = "pair0000".to_string();
+    let lp_token = "factory/pair0000/astroport/share".to_string();
+    let pair_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked(&pair_addr),
+        liquidity_token: lp_token.clone(),
+        pair_type: PairType::Xyk {},
+    };
+    deps.querier
+        .with_astroport_pairs(&[(&pair_addr, &pair_info)]);
+    PAIRS
+        .save(
+            deps.as_mut().storage,
+            &pair_key(&asset_infos),
+            &Addr::unchecked(&pair_addr),
+        )
+        .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SetRejectLpTokenAssets { reject: true },
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: vec![AssetInfo::native("uusd"), AssetInfo::native(&lp_token)],
+            init_params: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::LpTokenAsAsset { asset: lp_token });
+}
@@ -2,12 +2,21 @@
 
 mod factory_helper;
 
-use cosmwasm_std::{attr, Addr, StdError};
+use cosmwasm_std::{
+    attr, coin, from_json, to_json_binary, Addr, Decimal, DepsMut, Env, MessageInfo, Response,
+    StdError, Uint128,
+};
+use cw2::ContractVersion;
 
-use astroport::asset::{AssetInfo, PairInfo};
+use astroport::asset::{native_asset_info, token_asset_info, AssetInfo, AssetInfoExt, PairInfo};
 use astroport::factory::{
-    ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, PairConfig, PairType, QueryMsg,
-    TrackerConfig,
+    ConfigResponse, CreatePairParams, ExecuteMsg, FeeChange, FeeInfoResponse, InstantiateMsg,
+    LockedLiquidityResponse, PairConfig, PairType, PairsResponse, PendingFeeChange,
+    PendingFeeChangeDelay, QueryMsg, TrackerConfig, FEE_CHANGE_EXPIRY_BLOCKS,
+};
+use astroport::pair::{
+    InstantiateMsg as PairInstantiateMsg, QueryMsg as PairQueryMsg, StablePoolParams,
+    WeightedPoolParams, XYKPoolConfig, XYKPoolParams, WEIGHTED_PAIR_TYPE,
 };
 
 use crate::factory_helper::{instantiate_token, FactoryHelper};
@@ -83,6 +92,18 @@ fn proper_initialization() {
     assert_eq!(123, config_res.token_code_id);
     assert_eq!(pair_configs, config_res.pair_configs);
     assert_eq!(owner, config_res.owner);
+
+    let version: ContractVersion = app
+        .wrap()
+        .query_wasm_smart(&factory_instance, &QueryMsg::Version {})
+        .unwrap();
+    assert_eq!(
+        version,
+        ContractVersion {
+            contract: "astroport-factory".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
 }
 
 #[test]
@@ -259,6 +280,275 @@ fn test_create_pair() {
     assert_eq!(pair_types, vec![PairType::Custom("Custom".to_string())]);
 }
 
+#[test]
+fn test_create_pair_with_pair_admin() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenX", "tokenY", "tokenZ"]
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, None));
+
+    let query_pair_admin = |app: &TestApp, factory: &Addr, tokens: [&Addr; 2]| {
+        let pair_info: PairInfo = app
+            .wrap()
+            .query_wasm_smart(
+                factory,
+                &QueryMsg::Pair {
+                    asset_infos: tokens.map(|token| token_asset_info(token.clone())).to_vec(),
+                },
+            )
+            .unwrap();
+        app.wrap()
+            .query_wasm_contract_info(pair_info.contract_addr)
+            .unwrap()
+            .admin
+    };
+
+    // Pairs are administered by the owner by default
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&tokens[0], &tokens[1]],
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        query_pair_admin(&app, &helper.factory, [&tokens[0], &tokens[1]]),
+        Some(owner.to_string())
+    );
+
+    let set_admin_msg = ExecuteMsg::SetPairAdmin {
+        admin: Some("multisig".to_string()),
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random"),
+            helper.factory.clone(),
+            &set_admin_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(owner.clone(), helper.factory.clone(), &set_admin_msg, &[])
+        .unwrap();
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config_res.pair_admin, Some(Addr::unchecked("multisig")));
+
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&tokens[0], &tokens[2]],
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        query_pair_admin(&app, &helper.factory, [&tokens[0], &tokens[2]]),
+        Some("multisig".to_string())
+    );
+
+    // Removing the pair admin makes the owner administer new pairs again
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetPairAdmin { admin: None },
+        &[],
+    )
+    .unwrap();
+    let config_res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config_res.pair_admin, None);
+
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&tokens[1], &tokens[2]],
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        query_pair_admin(&app, &helper.factory, [&tokens[1], &tokens[2]]),
+        Some(owner.to_string())
+    );
+}
+
+/// Stands in for a weighted pair contract by running the xyk pair without the weighted init params
+fn instantiate_weighted_pair(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: PairInstantiateMsg,
+) -> Result<Response, astroport_pair::error::ContractError> {
+    astroport_pair::contract::instantiate(
+        deps,
+        env,
+        info,
+        PairInstantiateMsg {
+            init_params: None,
+            ..msg
+        },
+    )
+}
+
+#[test]
+fn test_create_weighted_pair() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+
+    let weighted = PairType::Custom(WEIGHTED_PAIR_TYPE.to_string());
+    let weights = |weights| Some(to_json_binary(&WeightedPoolParams { weights }).unwrap());
+
+    // Weighted pair type isn't configured yet
+    let err = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            weighted.clone(),
+            [&token1, &token2],
+            weights([80, 20]),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PairConfigNotFound {}
+    );
+
+    let weighted_pair_code_id = app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair::contract::execute,
+            instantiate_weighted_pair,
+            astroport_pair::contract::query,
+        )
+        .with_reply_empty(astroport_pair::contract::reply),
+    ));
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                code_id: weighted_pair_code_id,
+                pair_type: weighted.clone(),
+                total_fee_bps: 30,
+                maker_fee_bps: 3000,
+                is_disabled: false,
+                is_generator_disabled: false,
+                permissioned: false,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let invalid_params = [
+        None,
+        Some(
+            to_json_binary(&StablePoolParams {
+                amp: 100,
+                owner: None,
+            })
+            .unwrap(),
+        ),
+        weights([80, 0]),
+        weights([70, 20]),
+        weights([u64::MAX, 2]),
+    ];
+    for init_params in invalid_params {
+        let err = helper
+            .create_pair(
+                &mut app,
+                &owner,
+                weighted.clone(),
+                [&token1, &token2],
+                init_params,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidWeights {}
+        );
+    }
+
+    // Weights only cover two assets
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::CreatePair {
+                pair_type: weighted.clone(),
+                asset_infos: [&token1, &token2, &token3]
+                    .map(|token| token_asset_info(token.clone()))
+                    .to_vec(),
+                init_params: weights([80, 20]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidWeights {}
+    );
+
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            weighted.clone(),
+            [&token1, &token2],
+            weights([80, 20]),
+        )
+        .unwrap();
+
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: vec![
+                    AssetInfo::Token {
+                        contract_addr: token1.clone(),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: token2.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+    assert_eq!(pair_info.pair_type, weighted);
+
+    let fee_info: FeeInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::FeeInfo {
+                pair_type: pair_info.pair_type,
+            },
+        )
+        .unwrap();
+    assert_eq!(fee_info.total_fee_bps, 30);
+    assert_eq!(fee_info.maker_fee_bps, 3000);
+}
+
 #[test]
 fn check_update_owner() {
     let mut app = mock_app();
@@ -367,21 +657,145 @@ fn check_update_owner() {
 }
 
 #[test]
-fn test_create_permissioned_pair() {
+fn check_is_owner() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let new_owner = Addr::unchecked("new_owner");
+    let is_owner = |app: &TestApp, address: &Addr| -> bool {
+        app.wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::IsOwner {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    };
+
+    assert!(is_owner(&app, &owner));
+    assert!(!is_owner(&app, &Addr::unchecked("random")));
+
+    // Proposed but unclaimed owner is not the owner yet
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::ProposeNewOwner {
+            owner: new_owner.to_string(),
+            expires_in: 100,
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(is_owner(&app, &owner));
+    assert!(!is_owner(&app, &new_owner));
+
+    app.execute_contract(
+        new_owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::ClaimOwnership {},
+        &[],
+    )
+    .unwrap();
+    assert!(!is_owner(&app, &owner));
+    assert!(is_owner(&app, &new_owner));
+}
+
+#[test]
+fn test_pair_types_info() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let stable_config = PairConfig {
+        code_id: 0,
+        pair_type: PairType::Stable {},
+        total_fee_bps: 5,
+        maker_fee_bps: 5000,
+        is_disabled: true,
+        is_generator_disabled: true,
+        permissioned: false,
+    };
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig {
+            config: stable_config.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let pair_types: Vec<PairConfig> = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::PairTypesInfo {})
+        .unwrap();
+    assert_eq!(pair_types.len(), 3);
+
+    // (pair type, total fee bps, maker fee bps, is disabled, is generator disabled)
+    for (pair_type, total_fee_bps, maker_fee_bps, is_disabled, is_generator_disabled) in [
+        (PairType::Xyk {}, 100, 10, false, false),
+        (
+            PairType::Custom("transmuter".to_string()),
+            0,
+            0,
+            false,
+            false,
+        ),
+        (PairType::Stable {}, 5, 5000, true, true),
+    ] {
+        let config = pair_types
+            .iter()
+            .find(|config| config.pair_type == pair_type)
+            .unwrap();
+        assert_eq!(config.total_fee_bps, total_fee_bps);
+        assert_eq!(config.maker_fee_bps, maker_fee_bps);
+        assert_eq!(config.is_disabled, is_disabled);
+        assert_eq!(config.is_generator_disabled, is_generator_disabled);
+    }
+
+    // The response is consistent with the pair configs in the factory config
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.pair_configs, pair_types);
+}
+
+#[test]
+fn test_maker_fee_collection_active() {
     let mut app = mock_app();
     let owner = Addr::unchecked("owner");
     let mut helper = FactoryHelper::init(&mut app, &owner);
 
+    let active: bool = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::MakerFeeCollectionActive {})
+        .unwrap();
+    assert!(!active);
+
     let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
     let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
 
-    let err = helper
-        .create_pair(
-            &mut app,
-            &Addr::unchecked("random_stranger"),
-            PairType::Custom("transmuter".to_string()),
-            [&token1, &token2],
-            None,
+    // Pairs charging maker fees are created with a warning by default
+    let res = helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    let warning = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "warning");
+    assert!(warning.is_some());
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random_stranger"),
+            helper.factory.clone(),
+            &ExecuteMsg::SetRequireFeeAddress { require: true },
+            &[],
         )
         .unwrap_err();
     assert_eq!(
@@ -389,30 +803,825 @@ fn test_create_permissioned_pair() {
         ContractError::Unauthorized {}
     );
 
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetRequireFeeAddress { require: true },
+        &[],
+    )
+    .unwrap();
+
+    let err = helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token3], None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeAddressNotSet {}
+    );
+
+    // Pairs without maker fees are not affected
     helper
         .create_pair(
             &mut app,
             &owner,
             PairType::Custom("transmuter".to_string()),
-            [&token1, &token2],
+            [&token1, &token3],
             None,
         )
         .unwrap();
-}
-
-#[test]
-fn tracker_config() {
-    let mut app = mock_app();
-    let owner = Addr::unchecked("owner");
-    let mut helper = FactoryHelper::init(&mut app, &owner);
-
-    // Should return an error since tracker config is not set
-    let err = helper.query_tracker_config(&mut app).unwrap_err();
-
-    assert_eq!(
-        err,
-        StdError::generic_err("Querier contract error: Generic error: Tracker config is not set in the factory. It can't be provided")
-    );
+
+    helper
+        .update_config(
+            &mut app,
+            &owner,
+            None,
+            Some("fee_address".to_string()),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    let active: bool = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::MakerFeeCollectionActive {})
+        .unwrap();
+    assert!(active);
+
+    let res = helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token2, &token3], None)
+        .unwrap();
+    let warning = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "warning");
+    assert!(warning.is_none());
+}
+
+#[test]
+fn test_reject_lp_token_assets() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: vec![
+                    token_asset_info(token1.clone()),
+                    token_asset_info(token2.clone()),
+                ],
+            },
+        )
+        .unwrap();
+
+    let reject: bool = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::RejectLpTokenAssets {})
+        .unwrap();
+    assert!(!reject);
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random_stranger"),
+            helper.factory.clone(),
+            &ExecuteMsg::SetRejectLpTokenAssets { reject: true },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetRejectLpTokenAssets { reject: true },
+        &[],
+    )
+    .unwrap();
+    let reject: bool = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::RejectLpTokenAssets {})
+        .unwrap();
+    assert!(reject);
+
+    let create_msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
+        asset_infos: vec![
+            native_asset_info(pair_info.liquidity_token.clone()),
+            token_asset_info(token3.clone()),
+        ],
+        init_params: None,
+    };
+    let err = app
+        .execute_contract(owner.clone(), helper.factory.clone(), &create_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LpTokenAsAsset {
+            asset: pair_info.liquidity_token.clone()
+        }
+    );
+
+    // Pairs of regular assets are not affected
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token3], None)
+        .unwrap();
+
+    // LP tokens are allowed again once the check is disabled
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetRejectLpTokenAssets { reject: false },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(owner.clone(), helper.factory.clone(), &create_msg, &[])
+        .unwrap();
+}
+
+#[test]
+fn test_timelocked_fee_change() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let fee_change = FeeChange {
+        total_fee_bps: 50,
+        maker_fee_bps: 20,
+    };
+    let query_fees = |app: &TestApp| -> FeeInfoResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::FeeInfo {
+                    pair_type: PairType::Xyk {},
+                },
+            )
+            .unwrap()
+    };
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetFeeChangeDelay {
+            delay_blocks: Some(100),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Fees of existing pair types can't be changed instantly anymore
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    let mut xyk_config = config
+        .pair_configs
+        .into_iter()
+        .find(|config| config.pair_type == PairType::Xyk {})
+        .unwrap();
+    xyk_config.total_fee_bps = fee_change.total_fee_bps;
+    xyk_config.maker_fee_bps = fee_change.maker_fee_bps;
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::UpdatePairConfig {
+                config: xyk_config.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeChangeTimelocked {}
+    );
+
+    // Other settings can still be updated
+    xyk_config.total_fee_bps = 100;
+    xyk_config.maker_fee_bps = 10;
+    xyk_config.is_generator_disabled = true;
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig { config: xyk_config },
+        &[],
+    )
+    .unwrap();
+
+    let propose_msg = ExecuteMsg::ProposeFeeChange {
+        pair_type: PairType::Xyk {},
+        config: fee_change.clone(),
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random_stranger"),
+            helper.factory.clone(),
+            &propose_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(owner.clone(), helper.factory.clone(), &propose_msg, &[])
+        .unwrap();
+
+    let effective_block = app.block_info().height + 100;
+    let pending: Option<PendingFeeChange> = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::PendingFeeChange {
+                pair_type: PairType::Xyk {},
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        pending,
+        Some(PendingFeeChange {
+            config: fee_change.clone(),
+            proposed_block: effective_block - 100,
+            effective_block,
+            expiry_block: effective_block + FEE_CHANGE_EXPIRY_BLOCKS,
+        })
+    );
+
+    // The change can't be applied before the delay passes
+    let apply_msg = ExecuteMsg::ApplyFeeChange {
+        pair_type: PairType::Xyk {},
+    };
+    app.update_block(|block| block.height += 99);
+    let err = app
+        .execute_contract(owner.clone(), helper.factory.clone(), &apply_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeChangeNotReady { effective_block }
+    );
+    assert_eq!(query_fees(&app).total_fee_bps, 100);
+    assert_eq!(query_fees(&app).maker_fee_bps, 10);
+
+    app.update_block(|block| block.height += 1);
+    app.execute_contract(owner.clone(), helper.factory.clone(), &apply_msg, &[])
+        .unwrap();
+    assert_eq!(query_fees(&app).total_fee_bps, fee_change.total_fee_bps);
+    assert_eq!(query_fees(&app).maker_fee_bps, fee_change.maker_fee_bps);
+
+    // The pending change is consumed
+    let err = app
+        .execute_contract(owner.clone(), helper.factory.clone(), &apply_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeChangeNotFound {}
+    );
+
+    // Proposals which are not applied in time expire
+    app.execute_contract(owner.clone(), helper.factory.clone(), &propose_msg, &[])
+        .unwrap();
+    let expiry_block = app.block_info().height + 100 + FEE_CHANGE_EXPIRY_BLOCKS;
+    app.update_block(|block| block.height = expiry_block + 1);
+    let err = app
+        .execute_contract(owner.clone(), helper.factory.clone(), &apply_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeChangeExpired { expiry_block }
+    );
+
+    // Removing the delay is timelocked by the current delay
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetFeeChangeDelay { delay_blocks: None },
+        &[],
+    )
+    .unwrap();
+    let effective_block = app.block_info().height + 100;
+    let pending_delay: Option<PendingFeeChangeDelay> = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::PendingFeeChangeDelay {})
+        .unwrap();
+    assert_eq!(
+        pending_delay,
+        Some(PendingFeeChangeDelay {
+            delay_blocks: None,
+            effective_block,
+        })
+    );
+
+    let mut xyk_config: PairConfig = app
+        .wrap()
+        .query_wasm_smart::<ConfigResponse>(&helper.factory, &QueryMsg::Config {})
+        .unwrap()
+        .pair_configs
+        .into_iter()
+        .find(|config| config.pair_type == PairType::Xyk {})
+        .unwrap();
+    xyk_config.total_fee_bps = 30;
+    let update_msg = ExecuteMsg::UpdatePairConfig { config: xyk_config };
+    let err = app
+        .execute_contract(owner.clone(), helper.factory.clone(), &update_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeChangeTimelocked {}
+    );
+
+    app.update_block(|block| block.height = effective_block);
+    let delay: Option<u64> = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::FeeChangeDelay {})
+        .unwrap();
+    assert_eq!(delay, None);
+    app.execute_contract(owner.clone(), helper.factory.clone(), &update_msg, &[])
+        .unwrap();
+    assert_eq!(query_fees(&app).total_fee_bps, 30);
+
+    // A delay raised after the proposal applies to it
+    app.execute_contract(owner.clone(), helper.factory.clone(), &propose_msg, &[])
+        .unwrap();
+    let proposed_block = app.block_info().height;
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetFeeChangeDelay {
+            delay_blocks: Some(50),
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(owner.clone(), helper.factory.clone(), &apply_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeChangeNotReady {
+            effective_block: proposed_block + 50
+        }
+    );
+    assert_eq!(query_fees(&app).total_fee_bps, 30);
+
+    app.update_block(|block| block.height = proposed_block + 50);
+    app.execute_contract(owner.clone(), helper.factory.clone(), &apply_msg, &[])
+        .unwrap();
+    assert_eq!(query_fees(&app).total_fee_bps, fee_change.total_fee_bps);
+}
+
+#[test]
+fn test_create_pairs_batch() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let stable_code_id = app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair_stable::contract::execute,
+            astroport_pair_stable::contract::instantiate,
+            astroport_pair_stable::contract::query,
+        )
+        .with_reply_empty(astroport_pair_stable::contract::reply),
+    ));
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                code_id: stable_code_id,
+                pair_type: PairType::Stable {},
+                total_fee_bps: 5,
+                maker_fee_bps: 5000,
+                is_disabled: false,
+                is_generator_disabled: false,
+                permissioned: false,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
+
+    let xyk_assets = vec![token_asset_info(token1.clone()), token_asset_info(token2)];
+    let stable_assets = vec![token_asset_info(token1), token_asset_info(token3)];
+    let xyk_params = CreatePairParams {
+        pair_type: PairType::Xyk {},
+        asset_infos: xyk_assets.clone(),
+        init_params: None,
+    };
+    let stable_params = CreatePairParams {
+        pair_type: PairType::Stable {},
+        asset_infos: stable_assets.clone(),
+        init_params: Some(
+            to_json_binary(&StablePoolParams {
+                amp: 100,
+                owner: None,
+            })
+            .unwrap(),
+        ),
+    };
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::CreatePairs { pairs: vec![] },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::EmptyPairsBatch {}
+    );
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::CreatePairs {
+                pairs: vec![xyk_params.clone(), xyk_params.clone()],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PairWasCreated {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::CreatePairs {
+            pairs: vec![xyk_params, stable_params],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Both pairs are registered with their own types
+    for (asset_infos, pair_type) in [
+        (xyk_assets, PairType::Xyk {}),
+        (stable_assets, PairType::Stable {}),
+    ] {
+        let pair_info: PairInfo = app
+            .wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::Pair {
+                    asset_infos: asset_infos.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(pair_info.pair_type, pair_type);
+        assert_eq!(pair_info.asset_infos, asset_infos);
+
+        let pair_info_from_pair: PairInfo = app
+            .wrap()
+            .query_wasm_smart(&pair_info.contract_addr, &PairQueryMsg::Pair {})
+            .unwrap();
+        assert_eq!(pair_info_from_pair, pair_info);
+    }
+
+    let pairs: PairsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pairs {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs.len(), 2);
+}
+
+#[test]
+fn test_create_permissioned_pair() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+
+    let err = helper
+        .create_pair(
+            &mut app,
+            &Addr::unchecked("random_stranger"),
+            PairType::Custom("transmuter".to_string()),
+            [&token1, &token2],
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Custom("transmuter".to_string()),
+            [&token1, &token2],
+            None,
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_pair_by_lp_token() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token1.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: token2.clone(),
+        },
+    ];
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap();
+
+    let res: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::PairByLpToken {
+                lp_token: pair_info.liquidity_token.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res, pair_info);
+
+    // Unknown LP token
+    app.wrap()
+        .query_wasm_smart::<PairInfo>(
+            &helper.factory,
+            &QueryMsg::PairByLpToken {
+                lp_token: "factory/random/astroport/share".to_string(),
+            },
+        )
+        .unwrap_err();
+
+    // Deregistered pairs can't be resolved anymore
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister { asset_infos },
+        &[],
+    )
+    .unwrap();
+    app.wrap()
+        .query_wasm_smart::<PairInfo>(
+            &helper.factory,
+            &QueryMsg::PairByLpToken {
+                lp_token: pair_info.liquidity_token,
+            },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn test_fee_info_by_pair() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: vec![
+                    AssetInfo::Token {
+                        contract_addr: token1.clone(),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: token2.clone(),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+
+    let by_pair: FeeInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::FeeInfoByPair {
+                pair_addr: pair_info.contract_addr.to_string(),
+            },
+        )
+        .unwrap();
+    let by_type: FeeInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::FeeInfo {
+                pair_type: PairType::Xyk {},
+            },
+        )
+        .unwrap();
+    assert_eq!(by_pair, by_type);
+
+    // Non-pair contracts can't be resolved
+    app.wrap()
+        .query_wasm_smart::<FeeInfoResponse>(
+            &helper.factory,
+            &QueryMsg::FeeInfoByPair {
+                pair_addr: token1.to_string(),
+            },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn test_pair_oracle() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token1.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: token2.clone(),
+        },
+    ];
+    let query_oracle = |app: &TestApp, asset_infos: &[AssetInfo]| -> Option<Addr> {
+        app.wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::PairOracle {
+                    asset_infos: asset_infos.to_vec(),
+                },
+            )
+            .unwrap()
+    };
+
+    assert_eq!(query_oracle(&app, &asset_infos), None);
+
+    let set_oracle_msg = ExecuteMsg::SetPairOracle {
+        asset_infos: asset_infos.clone(),
+        oracle: Some("oracle".to_string()),
+    };
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random"),
+            helper.factory.clone(),
+            &set_oracle_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Oracles can be linked only to registered pairs
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetPairOracle {
+            asset_infos: vec![
+                AssetInfo::Token {
+                    contract_addr: token1.clone(),
+                },
+                AssetInfo::Token {
+                    contract_addr: token3.clone(),
+                },
+            ],
+            oracle: Some("oracle".to_string()),
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    app.execute_contract(owner.clone(), helper.factory.clone(), &set_oracle_msg, &[])
+        .unwrap();
+
+    // The oracle is found regardless of the asset order
+    let reversed = asset_infos.iter().rev().cloned().collect::<Vec<_>>();
+    assert_eq!(
+        query_oracle(&app, &reversed),
+        Some(Addr::unchecked("oracle"))
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::SetPairOracle {
+            asset_infos: asset_infos.clone(),
+            oracle: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_oracle(&app, &asset_infos), None);
+}
+
+#[test]
+fn test_pair_exists() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+
+    let pair_exists = |app: &TestApp, tokens: [&Addr; 2]| -> bool {
+        app.wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::PairExists {
+                    asset_infos: tokens
+                        .iter()
+                        .map(|token| AssetInfo::Token {
+                            contract_addr: (*token).clone(),
+                        })
+                        .collect(),
+                },
+            )
+            .unwrap()
+    };
+
+    assert!(pair_exists(&app, [&token1, &token2]));
+    // Asset order doesn't matter
+    assert!(pair_exists(&app, [&token2, &token1]));
+    assert!(!pair_exists(&app, [&token1, &token3]));
+}
+
+#[test]
+fn tracker_config() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    // Should return an error since tracker config is not set
+    let err = helper.query_tracker_config(&mut app).unwrap_err();
+
+    assert_eq!(
+        err,
+        StdError::generic_err("Querier contract error: Generic error: Tracker config is not set in the factory. It can't be provided")
+    );
 
     // should return an error since the sender is not the owner
     let err = helper
@@ -484,3 +1693,502 @@ fn tracker_config() {
     assert_eq!(tracker_config.token_factory_addr, "token_factory_addr");
     assert_eq!(tracker_config.code_id, 64);
 }
+
+#[test]
+fn test_create_pair_with_locked_liquidity() {
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+    let mut app = AppBuilder::new_custom()
+        .with_stargate(MockStargate::default())
+        .build(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &user, vec![coin(1_000_000000, "uusd")])
+                .unwrap()
+        });
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    app.execute_contract(
+        owner.clone(),
+        token.clone(),
+        &cw20::Cw20ExecuteMsg::Mint {
+            recipient: user.to_string(),
+            amount: Uint128::new(1_000_000000),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user.clone(),
+        token.clone(),
+        &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+            spender: helper.factory.to_string(),
+            amount: Uint128::new(100_000000),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let asset_infos = vec![
+        token_asset_info(token.clone()),
+        native_asset_info("uusd".to_string()),
+    ];
+    let initial_liquidity = vec![
+        asset_infos[0].with_balance(100_000000u128),
+        asset_infos[1].with_balance(100_000000u128),
+    ];
+    let create_msg = |initial_liquidity: Vec<_>| ExecuteMsg::CreatePairWithLockedLiquidity {
+        pair_type: PairType::Xyk {},
+        asset_infos: asset_infos.clone(),
+        init_params: None,
+        initial_liquidity,
+    };
+
+    let err = app
+        .execute_contract(
+            user.clone(),
+            helper.factory.clone(),
+            &create_msg(initial_liquidity[..1].to_vec()),
+            &[coin(100_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidLockedLiquidity {}
+    );
+
+    let err = app
+        .execute_contract(
+            user.clone(),
+            helper.factory.clone(),
+            &create_msg(initial_liquidity.clone()),
+            &[coin(50_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientLockedLiquidityFunds {
+            denom: "uusd".to_string()
+        }
+    );
+
+    app.execute_contract(
+        user.clone(),
+        helper.factory.clone(),
+        &create_msg(initial_liquidity),
+        &[coin(100_000000, "uusd")],
+    )
+    .unwrap();
+
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap();
+    let locked: LockedLiquidityResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::LockedLiquidity {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        locked,
+        LockedLiquidityResponse {
+            pair_addr: pair_info.contract_addr.clone(),
+            lp_token: pair_info.liquidity_token.clone(),
+            lock_addr: helper.factory.clone(),
+            // sqrt(100_000000 * 100_000000) - MINIMUM_LIQUIDITY_AMOUNT
+            amount: Uint128::new(99_999000),
+        }
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&user, &pair_info.liquidity_token)
+            .unwrap()
+            .amount,
+        Uint128::zero()
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&pair_info.contract_addr, "uusd")
+            .unwrap()
+            .amount,
+        Uint128::new(100_000000)
+    );
+
+    // Regular pairs have no locked liquidity
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token, &token2], None)
+        .unwrap();
+    let err = app
+        .wrap()
+        .query_wasm_smart::<LockedLiquidityResponse>(
+            &helper.factory,
+            &QueryMsg::LockedLiquidity {
+                asset_infos: vec![token_asset_info(token), token_asset_info(token2)],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Querier contract error: Generic error: Pair has no locked liquidity"
+        )
+    );
+
+    // The lock record is cleared when the pair is deregistered
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .wrap()
+        .query_wasm_smart::<LockedLiquidityResponse>(
+            &helper.factory,
+            &QueryMsg::LockedLiquidity { asset_infos },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Querier contract error: Generic error: Pair has no locked liquidity"
+        )
+    );
+}
+
+#[test]
+fn test_default_max_spread() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
+
+    let default_max_spread = Decimal::percent(2);
+    let set_default_msg = ExecuteMsg::SetDefaultMaxSpread {
+        pair_type: PairType::Xyk {},
+        max_spread: Some(default_max_spread),
+    };
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random"),
+            helper.factory.clone(),
+            &set_default_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::SetDefaultMaxSpread {
+                pair_type: PairType::Xyk {},
+                max_spread: Some(Decimal::percent(51)),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidDefaultMaxSpread {}
+    );
+
+    // Other pair types don't read the default
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::SetDefaultMaxSpread {
+                pair_type: PairType::Stable {},
+                max_spread: Some(default_max_spread),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DefaultMaxSpreadNotSupported {}
+    );
+
+    app.execute_contract(owner.clone(), helper.factory.clone(), &set_default_msg, &[])
+        .unwrap();
+
+    let res: Option<Decimal> = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::DefaultMaxSpread {
+                pair_type: PairType::Xyk {},
+            },
+        )
+        .unwrap();
+    assert_eq!(res, Some(default_max_spread));
+
+    let query_pair_max_spread = |app: &TestApp, tokens: [&Addr; 2]| -> Option<Decimal> {
+        let pair_info: PairInfo = app
+            .wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::Pair {
+                    asset_infos: vec![
+                        token_asset_info(tokens[0].clone()),
+                        token_asset_info(tokens[1].clone()),
+                    ],
+                },
+            )
+            .unwrap();
+        let config: astroport::pair::ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&pair_info.contract_addr, &PairQueryMsg::Config {})
+            .unwrap();
+        from_json::<XYKPoolConfig>(config.params.unwrap())
+            .unwrap()
+            .default_max_spread
+    };
+
+    // The pair inherits the default of its type
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    assert_eq!(
+        query_pair_max_spread(&app, [&token1, &token2]),
+        Some(default_max_spread)
+    );
+
+    // A local value overrides the default
+    let local_max_spread = Decimal::percent(5);
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&token1, &token3],
+            Some(
+                to_json_binary(&XYKPoolParams {
+                    track_asset_balances: None,
+                    max_swap_ratio: None,
+                    trading_start_time: None,
+                    default_max_spread: Some(local_max_spread),
+                    max_pool_balance: None,
+                })
+                .unwrap(),
+            ),
+        )
+        .unwrap();
+    assert_eq!(
+        query_pair_max_spread(&app, [&token1, &token3]),
+        Some(local_max_spread)
+    );
+}
+
+#[test]
+fn test_pairs_pagination() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenA", "tokenB", "tokenC", "tokenD"]
+        .into_iter()
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, None))
+        .collect::<Vec<_>>();
+
+    for (i, token1) in tokens.iter().enumerate() {
+        for token2 in &tokens[i + 1..] {
+            helper
+                .create_pair(&mut app, &owner, PairType::Xyk {}, [token1, token2], None)
+                .unwrap();
+        }
+    }
+
+    let query_pairs = |app: &TestApp, start_after: Option<Vec<AssetInfo>>, limit: u32| {
+        app.wrap()
+            .query_wasm_smart::<PairsResponse>(
+                &helper.factory,
+                &QueryMsg::Pairs {
+                    start_after,
+                    limit: Some(limit),
+                },
+            )
+            .unwrap()
+            .pairs
+    };
+
+    let all_pairs = query_pairs(&app, None, 30);
+    assert_eq!(all_pairs.len(), 6);
+
+    // Paging one by one yields each pair exactly once in the same order
+    for reverse_start in [false, true] {
+        let mut paged = vec![];
+        let mut start_after = None;
+        while let Some(pair) = query_pairs(&app, start_after.take(), 1).into_iter().next() {
+            let mut asset_infos = pair.asset_infos.clone();
+            // The start pair is found regardless of the asset order
+            if reverse_start {
+                asset_infos.reverse();
+            }
+            start_after = Some(asset_infos);
+            paged.push(pair);
+        }
+
+        assert_eq!(paged, all_pairs);
+    }
+}
+
+#[test]
+fn test_pairs_created_between() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenA", "tokenB", "tokenC", "tokenD"]
+        .into_iter()
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, None))
+        .collect::<Vec<_>>();
+
+    // Pairs are created at blocks start, start + 10, start + 20
+    let start_block = app.block_info().height;
+    let mut created = vec![];
+    for token in &tokens[1..] {
+        helper
+            .create_pair(
+                &mut app,
+                &owner,
+                PairType::Xyk {},
+                [&tokens[0], token],
+                None,
+            )
+            .unwrap();
+        created.push(vec![
+            token_asset_info(tokens[0].clone()),
+            token_asset_info(token.clone()),
+        ]);
+        app.update_block(|block| block.height += 10);
+    }
+
+    let query_after = |app: &TestApp,
+                       start_block: u64,
+                       end_block: u64,
+                       start_after: Option<Vec<AssetInfo>>,
+                       limit: Option<u32>| {
+        app.wrap()
+            .query_wasm_smart::<PairsResponse>(
+                &helper.factory,
+                &QueryMsg::PairsCreatedBetween {
+                    start_block,
+                    end_block,
+                    start_after,
+                    limit,
+                },
+            )
+            .map(|resp| {
+                resp.pairs
+                    .into_iter()
+                    .map(|pair| pair.asset_infos)
+                    .collect::<Vec<_>>()
+            })
+    };
+    let query = |app: &TestApp, start_block: u64, end_block: u64, limit: Option<u32>| {
+        query_after(app, start_block, end_block, None, limit)
+    };
+
+    assert_eq!(
+        query(&app, start_block, start_block + 20, None).unwrap(),
+        created
+    );
+    // Sub-window only returns the pairs created within it
+    assert_eq!(
+        query(&app, start_block + 1, start_block + 20, None).unwrap(),
+        created[1..].to_vec()
+    );
+    assert_eq!(
+        query(&app, start_block + 5, start_block + 15, None).unwrap(),
+        created[1..2].to_vec()
+    );
+    assert_eq!(
+        query(&app, start_block, start_block + 20, Some(1)).unwrap(),
+        created[..1].to_vec()
+    );
+    assert_eq!(
+        query(&app, start_block + 21, start_block + 100, None).unwrap(),
+        Vec::<Vec<AssetInfo>>::new()
+    );
+
+    // Paginate over the pairs created within the window
+    assert_eq!(
+        query_after(
+            &app,
+            start_block,
+            start_block + 20,
+            Some(created[0].clone()),
+            Some(1)
+        )
+        .unwrap(),
+        created[1..2].to_vec()
+    );
+    assert_eq!(
+        query_after(
+            &app,
+            start_block,
+            start_block + 20,
+            Some(created[2].clone()),
+            None
+        )
+        .unwrap(),
+        Vec::<Vec<AssetInfo>>::new()
+    );
+    // A pair created before the window doesn't skip pairs within it
+    assert_eq!(
+        query_after(
+            &app,
+            start_block + 10,
+            start_block + 20,
+            Some(created[0].clone()),
+            None
+        )
+        .unwrap(),
+        created[1..].to_vec()
+    );
+
+    let err = query(&app, start_block + 1, start_block, None).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("start_block must be less than or equal to end_block"));
+
+    // Deregistered pairs are no longer returned
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: created[1].clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query(&app, start_block, start_block + 20, None).unwrap(),
+        vec![created[0].clone(), created[2].clone()]
+    );
+}
@@ -10,7 +10,7 @@ use cosmwasm_std::{
     QuerierWrapper, Reply, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult,
     Uint128, Uint256, Uint64, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw_utils::{
     one_coin, parse_reply_instantiate_data, MsgInstantiateContractResponse, PaymentError,
@@ -21,14 +21,15 @@ use astroport::asset::{
     MINIMUM_LIQUIDITY_AMOUNT,
 };
 use astroport::common::LP_SUBDENOM;
+use astroport::factory::QueryMsg as FactoryQueryMsg;
 use astroport::incentives::ExecuteMsg as IncentiveExecuteMsg;
 use astroport::pair::{
-    ConfigResponse, FeeShareConfig, ReplyIds, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
-    DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS,
+    assert_belief_price, ConfigResponse, FeeShareConfig, ReplyIds, XYKPoolConfig, XYKPoolParams,
+    XYKPoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS,
 };
 use astroport::pair::{
     CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
+    ReverseSimulationResponse, SimulateWithSpreadResponse, SimulationResponse, TWAP_PRECISION,
 };
 use astroport::querier::{
     query_factory_config, query_fee_info, query_native_supply, query_tracker_config,
@@ -66,10 +67,34 @@ pub fn instantiate(
     }
 
     let mut track_asset_balances = false;
+    let mut max_swap_ratio = None;
+    let mut trading_start_time = None;
+    let mut default_max_spread = None;
+    let mut max_pool_balance = None;
 
     if let Some(init_params) = msg.init_params {
         let params: XYKPoolParams = from_json(init_params)?;
         track_asset_balances = params.track_asset_balances.unwrap_or_default();
+        max_swap_ratio = validate_max_swap_ratio(params.max_swap_ratio)?;
+        trading_start_time = params.trading_start_time;
+        default_max_spread = params.default_max_spread;
+        max_pool_balance = validate_max_pool_balance(params.max_pool_balance, &msg.asset_infos)?;
+    }
+
+    let factory_addr = deps.api.addr_validate(msg.factory_addr.as_str())?;
+
+    if let Some(max_spread) = default_max_spread {
+        if max_spread > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)? {
+            return Err(ContractError::AllowedSpreadAssertion {});
+        }
+    } else {
+        // Inherit the default of the pair type from the factory
+        default_max_spread = deps.querier.query_wasm_smart(
+            &factory_addr,
+            &FactoryQueryMsg::DefaultMaxSpread {
+                pair_type: msg.pair_type.clone(),
+            },
+        )?;
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -81,13 +106,17 @@ pub fn instantiate(
             asset_infos: msg.asset_infos.clone(),
             pair_type: msg.pair_type,
         },
-        factory_addr: deps.api.addr_validate(msg.factory_addr.as_str())?,
+        factory_addr,
         block_time_last: 0,
         price0_cumulative_last: Uint128::zero(),
         price1_cumulative_last: Uint128::zero(),
         track_asset_balances,
         fee_share: None,
         tracker_addr: None,
+        max_swap_ratio,
+        trading_start_time,
+        default_max_spread,
+        max_pool_balance,
     };
 
     if track_asset_balances {
@@ -251,8 +280,14 @@ pub fn execute(
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
             ..
         } => {
+            if referral.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
+
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -269,6 +304,7 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
             )
         }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, info, params),
@@ -289,13 +325,22 @@ pub fn receive_cw20(
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    match from_json(&cw20_msg.msg)? {
+    // Unknown hooks fail explicitly so the cw20 Send is reverted as a whole
+    let hook_msg = from_json(&cw20_msg.msg).map_err(|_| ContractError::UnknownCw20Hook {})?;
+
+    match hook_msg {
         Cw20HookMsg::Swap {
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
             ..
         } => {
+            if referral.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
+
             // Only asset contract can execute this message
             let mut authorized = false;
             let config = CONFIG.load(deps.storage)?;
@@ -327,11 +372,136 @@ pub fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
             )
         }
+        Cw20HookMsg::SwapWithTarget {
+            ask_asset_info,
+            target_out,
+            max_spread,
+        } => swap_with_target(
+            deps,
+            env,
+            info,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            ask_asset_info,
+            target_out,
+            max_spread,
+        ),
     }
 }
 
+/// Swaps only as much of the received cw20 amount as is needed to receive **target_out** ask tokens
+/// and refunds the rest to the sender. If the received amount is not enough to reach the target,
+/// or the target is at or beyond the ask pool net of fees, the whole amount is swapped.
+///
+/// * **sender** is the sender of the cw20 tokens.
+///
+/// * **received** is the amount of cw20 tokens sent to the pair.
+///
+/// * **ask_asset_info** is an optional ask asset which must be the other pool asset.
+///
+/// * **target_out** is the amount of ask tokens the sender wants to receive.
+///
+/// * **max_spread** sets the maximum spread of the swap operation.
+#[allow(clippy::too_many_arguments)]
+fn swap_with_target(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    received: Uint128,
+    ask_asset_info: Option<AssetInfo>,
+    target_out: Uint128,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Only asset contract can execute this message
+    let offer_info = AssetInfo::Token {
+        contract_addr: info.sender.clone(),
+    };
+    if !config.pair_info.asset_infos.contains(&offer_info) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+    let (offer_pool, ask_pool) = if offer_info.equal(&pools[0].info) {
+        (pools[0].clone(), pools[1].clone())
+    } else {
+        (pools[1].clone(), pools[0].clone())
+    };
+
+    if let Some(ask_asset_info) = &ask_asset_info {
+        if !ask_asset_info.equal(&ask_pool.info) {
+            return Err(ContractError::AssetMismatch {});
+        }
+    }
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+
+    // The pool balance already includes the received tokens
+    let offer_pool_amount = offer_pool.amount.checked_sub(received)?;
+    // No offer amount can return the whole ask pool net of fees
+    let max_return = ask_pool.amount * (Decimal::one() - fee_info.total_fee_rate);
+    let swap_amount = if target_out < max_return {
+        let (mut required, ..) = compute_offer_amount(
+            offer_pool_amount,
+            ask_pool.amount,
+            target_out,
+            fee_info.total_fee_rate,
+        )?;
+        // The offer amount is rounded down, so it may fall one unit short of the target
+        let (return_amount, ..) = compute_swap(
+            offer_pool_amount,
+            ask_pool.amount,
+            required,
+            fee_info.total_fee_rate,
+        )?;
+        if return_amount < target_out {
+            required += Uint128::one();
+        }
+        required.min(received)
+    } else {
+        received
+    };
+    let refund_amount = received - swap_amount;
+
+    let mut response = swap_with_deposit(
+        deps,
+        env,
+        sender.clone(),
+        Asset {
+            info: offer_info.clone(),
+            amount: swap_amount,
+        },
+        received,
+        None,
+        max_spread,
+        None,
+        false,
+    )?;
+
+    if !refund_amount.is_zero() {
+        response = response.add_message(
+            Asset {
+                info: offer_info,
+                amount: refund_amount,
+            }
+            .into_msg(&sender)?,
+        );
+    }
+
+    Ok(response.add_attribute("refund_amount", refund_amount))
+}
+
 /// Provides liquidity in the pair with the specified input parameters.
 ///
 /// * **assets** is an array with assets available in the pool.
@@ -391,6 +561,22 @@ pub fn provide_liquidity(
         }
     }
 
+    if let Some(max_pool_balance) = &config.max_pool_balance {
+        for (pool, deposit) in pools.iter().zip(deposits.iter()) {
+            if let Some((_, cap)) = max_pool_balance
+                .iter()
+                .find(|(asset_info, _)| asset_info == &pool.info)
+            {
+                if pool.amount.checked_add(*deposit)? > *cap {
+                    return Err(ContractError::MaxPoolBalanceExceeded {
+                        asset: pool.info.to_string(),
+                        max_pool_balance: *cap,
+                    });
+                }
+            }
+        }
+    }
+
     let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
     let share = calculate_shares(&deposits, &pools, total_share, slippage_tolerance)?;
 
@@ -611,6 +797,8 @@ pub fn get_share_in_assets(pools: &[Asset], amount: Uint128, total_share: Uint12
 ///
 /// * **to** sets the recipient of the swap operation.
 ///
+/// * **strict_belief_price** if true, the swap fails when executed at a price worse than **belief_price**.
+///
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
 #[allow(clippy::too_many_arguments)]
 pub fn swap(
@@ -622,11 +810,46 @@ pub fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    strict_belief_price: bool,
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
 
+    let deposit = offer_asset.amount;
+    swap_with_deposit(
+        deps,
+        env,
+        sender,
+        offer_asset,
+        deposit,
+        belief_price,
+        max_spread,
+        to,
+        strict_belief_price,
+    )
+}
+
+/// Performs a swap of **offer_asset** where the pair already received **deposit** offer tokens.
+/// **deposit** may exceed the swapped amount if the caller refunds the difference afterwards.
+#[allow(clippy::too_many_arguments)]
+fn swap_with_deposit(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    deposit: Uint128,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    strict_belief_price: bool,
+) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
+    if let Some(start_time) = config.trading_start_time {
+        if env.block.time.seconds() < start_time {
+            return Err(ContractError::TradingNotStarted { start_time });
+        }
+    }
+
     // If the asset balance is already increased, we should subtract the user deposit from the pool amount
     let pools = config
         .pair_info
@@ -634,7 +857,7 @@ pub fn swap(
         .into_iter()
         .map(|mut p| {
             if p.info.equal(&offer_asset.info) {
-                p.amount = p.amount.checked_sub(offer_asset.amount)?;
+                p.amount = p.amount.checked_sub(deposit)?;
             }
             Ok(p)
         })
@@ -662,6 +885,16 @@ pub fn swap(
 
     let offer_amount = offer_asset.amount;
 
+    if let Some(max_swap_ratio) = config.max_swap_ratio {
+        if offer_amount > offer_pool.amount * max_swap_ratio {
+            return Err(ContractError::MaxSwapRatioExceeded {
+                offer_amount,
+                offer_pool: offer_pool.amount,
+                max_swap_ratio,
+            });
+        }
+    }
+
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
         ask_pool.amount,
@@ -669,14 +902,17 @@ pub fn swap(
         fee_info.total_fee_rate,
     )?;
 
-    // Check the max spread limit (if it was specified)
+    // Check the max spread limit (falls back to the pair default if it wasn't specified)
     assert_max_spread(
         belief_price,
-        max_spread,
+        max_spread.or(config.default_max_spread),
         offer_amount,
         return_amount + commission_amount,
         spread_amount,
     )?;
+    if strict_belief_price {
+        assert_belief_price(belief_price, offer_amount, return_amount)?;
+    }
 
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -829,11 +1065,75 @@ pub fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        XYKPoolUpdateParams::SetMaxSwapRatio { max_swap_ratio } => {
+            config.max_swap_ratio = validate_max_swap_ratio(max_swap_ratio)?;
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "set_max_swap_ratio"),
+                attr(
+                    "max_swap_ratio",
+                    max_swap_ratio.map_or("unlimited".to_string(), |v| v.to_string()),
+                ),
+            ]);
+        }
+        XYKPoolUpdateParams::SetMaxPoolBalance { max_pool_balance } => {
+            config.max_pool_balance =
+                validate_max_pool_balance(max_pool_balance, &config.pair_info.asset_infos)?;
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "set_max_pool_balance"),
+                attr(
+                    "max_pool_balance",
+                    config
+                        .max_pool_balance
+                        .as_ref()
+                        .map_or("unlimited".to_string(), |caps| {
+                            caps.iter()
+                                .map(|(asset_info, cap)| format!("{cap}{asset_info}"))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        }),
+                ),
+            ]);
+        }
     }
 
     Ok(response)
 }
 
+/// Ensures the max swap ratio, if set, is not zero.
+fn validate_max_swap_ratio(
+    max_swap_ratio: Option<Decimal>,
+) -> Result<Option<Decimal>, ContractError> {
+    if matches!(max_swap_ratio, Some(ratio) if ratio.is_zero()) {
+        return Err(ContractError::InvalidMaxSwapRatio {});
+    }
+
+    Ok(max_swap_ratio)
+}
+
+/// Ensures each capped asset belongs to the pair and is listed only once.
+fn validate_max_pool_balance(
+    max_pool_balance: Option<Vec<(AssetInfo, Uint128)>>,
+    asset_infos: &[AssetInfo],
+) -> Result<Option<Vec<(AssetInfo, Uint128)>>, ContractError> {
+    if let Some(max_pool_balance) = &max_pool_balance {
+        for (i, (asset_info, _)) in max_pool_balance.iter().enumerate() {
+            if !asset_infos.contains(asset_info)
+                || max_pool_balance[..i]
+                    .iter()
+                    .any(|(prev_info, _)| prev_info == asset_info)
+            {
+                return Err(ContractError::InvalidMaxPoolBalance {});
+            }
+        }
+    }
+
+    Ok(max_pool_balance)
+}
+
 /// Accumulate token prices for the assets in the pool.
 /// Note that this function shifts **block_time** when any of the token prices is zero in order to not
 /// fill an accumulator with a null price for that period.
@@ -915,6 +1215,9 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation  using
 /// a [`ReverseSimulationResponse`] object.
 ///
+/// * **QueryMsg::SimulateWithSpread { offer_asset, belief_price }** Returns the result of a swap simulation
+/// and the spread relative to the belief price using a [`SimulateWithSpreadResponse`] object.
+///
 /// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
 /// pool using a [`CumulativePricesResponse`] object.
 ///
@@ -925,6 +1228,9 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amount of assets that could be withdrawn from the pool
 /// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
 /// * **QueryMsg::SimulateProvide { msg }** Simulates the liquidity provision in the pair contract.
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
+///
+/// * **QueryMsg::MinProvideAmounts {}** Returns the minimum provide amounts which mint at least one LP token unit.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -937,6 +1243,14 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::ReverseSimulation { ask_asset, .. } => {
             to_json_binary(&query_reverse_simulation(deps, ask_asset)?)
         }
+        QueryMsg::SimulateWithSpread {
+            offer_asset,
+            belief_price,
+        } => to_json_binary(&query_simulate_with_spread(
+            deps,
+            offer_asset,
+            belief_price,
+        )?),
         QueryMsg::CumulativePrices {} => to_json_binary(&query_cumulative_prices(deps, env)?),
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
         QueryMsg::AssetBalanceAt {
@@ -948,6 +1262,8 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             assets,
             slippage_tolerance,
         } => to_json_binary(&query_simulate_provide(deps, assets, slippage_tolerance)?),
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::MinProvideAmounts {} => to_json_binary(&query_min_provide_amounts(deps)?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -1023,6 +1339,38 @@ pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationR
     })
 }
 
+/// Returns a swap simulation and the spread relative to a belief price in a [`SimulateWithSpreadResponse`] object.
+/// The spread is computed the same way [`assert_max_spread`] does during a swap.
+///
+/// * **offer_asset** is the asset to swap as well as an amount of the said asset.
+///
+/// * **belief_price** is the expected price, i.e. the amount of offer asset paid per one ask asset.
+pub fn query_simulate_with_spread(
+    deps: Deps,
+    offer_asset: Asset,
+    belief_price: Decimal,
+) -> StdResult<SimulateWithSpreadResponse> {
+    let simulation = query_simulation(deps, offer_asset.clone())?;
+
+    let expected_return = offer_asset.amount
+        * belief_price
+            .inv()
+            .ok_or_else(|| StdError::generic_err("Belief price must not be zero!"))?;
+    let return_amount = simulation.return_amount + simulation.commission_amount;
+    let spread = if return_amount < expected_return {
+        Decimal::from_ratio(expected_return - return_amount, expected_return)
+    } else {
+        Decimal::zero()
+    };
+
+    Ok(SimulateWithSpreadResponse {
+        return_amount: simulation.return_amount,
+        commission_amount: simulation.commission_amount,
+        expected_return,
+        spread,
+    })
+}
+
 /// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
 ///
 /// * **ask_asset** is the asset to swap to as well as the desired amount of ask
@@ -1120,6 +1468,10 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         params: Some(to_json_binary(&XYKPoolConfig {
             track_asset_balances: config.track_asset_balances,
             fee_share: config.fee_share,
+            max_swap_ratio: config.max_swap_ratio,
+            trading_start_time: config.trading_start_time,
+            default_max_spread: config.default_max_spread,
+            max_pool_balance: config.max_pool_balance,
         })?),
         owner: factory_config.owner,
         factory_addr: config.factory_addr,
@@ -1155,6 +1507,35 @@ fn query_simulate_provide(
     Ok(share)
 }
 
+/// Returns the minimum amount of each pool asset which mints at least one LP token unit.
+/// The first provide must exceed [`MINIMUM_LIQUIDITY_AMOUNT`] which is locked in the pool.
+/// Subsequent provides mint `deposit * total_share / pool` LP tokens for each asset thus
+/// each deposit must be at least `ceil(pool / total_share)`.
+/// NOTE: the deposits ratio must still match the pool price within the slippage tolerance.
+pub fn query_min_provide_amounts(deps: Deps) -> StdResult<Vec<Asset>> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+
+    pools
+        .into_iter()
+        .map(|pool| {
+            let amount = if total_share.is_zero() {
+                MINIMUM_LIQUIDITY_AMOUNT + Uint128::one()
+            } else {
+                pool.amount
+                    .checked_add(total_share - Uint128::one())?
+                    .checked_div(total_share)?
+                    .max(Uint128::one())
+            };
+
+            Ok(Asset {
+                info: pool.info,
+                amount,
+            })
+        })
+        .collect()
+}
+
 /// Returns the balance of the specified asset that was in the pool
 /// just preceeding the moment of the specified block height creation.
 /// It will return None (null) if the balance was not tracked up to the specified block height
@@ -1,5 +1,5 @@
 use astroport::{asset::MINIMUM_LIQUIDITY_AMOUNT, pair::MAX_FEE_SHARE_BPS};
-use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cosmwasm_std::{Decimal, OverflowError, StdError, Uint128};
 use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
@@ -70,11 +70,36 @@ pub enum ContractError {
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 
+    #[error("Unknown cw20 hook message. The transfer is reverted and tokens stay with the sender")]
+    UnknownCw20Hook {},
+
     #[error(
         "Fee share is 0 or exceeds maximum allowed value of {} bps",
         MAX_FEE_SHARE_BPS
     )]
     FeeShareOutOfBounds {},
+
+    #[error("Max swap ratio must be greater than 0")]
+    InvalidMaxSwapRatio {},
+
+    #[error("Swap offer {offer_amount} exceeds the max swap ratio {max_swap_ratio} of the offer reserve {offer_pool}")]
+    MaxSwapRatioExceeded {
+        offer_amount: Uint128,
+        offer_pool: Uint128,
+        max_swap_ratio: Decimal,
+    },
+
+    #[error("Trading starts at {start_time}")]
+    TradingNotStarted { start_time: u64 },
+
+    #[error("Max pool balance must be set at most once for each pool asset")]
+    InvalidMaxPoolBalance {},
+
+    #[error("Provide exceeds the max pool balance {max_pool_balance} of {asset}")]
+    MaxPoolBalanceExceeded {
+        asset: String,
+        max_pool_balance: Uint128,
+    },
 }
 
 impl From<OverflowError> for ContractError {
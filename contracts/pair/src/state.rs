@@ -3,7 +3,7 @@ use astroport::{
     pair::FeeShareConfig,
 };
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw_storage_plus::{Item, SnapshotMap};
 
 /// This structure stores the main config parameters for a constant product pair contract.
@@ -25,6 +25,14 @@ pub struct Config {
     pub fee_share: Option<FeeShareConfig>,
     /// Stores the tracker contract address
     pub tracker_addr: Option<Addr>,
+    /// The maximum swap offer amount as a fraction of the offer asset reserve
+    pub max_swap_ratio: Option<Decimal>,
+    /// Timestamp (in seconds) before which swaps are rejected
+    pub trading_start_time: Option<u64>,
+    /// The max spread applied to swaps that don't specify one
+    pub default_max_spread: Option<Decimal>,
+    /// The maximum pool balance of each listed asset
+    pub max_pool_balance: Option<Vec<(AssetInfo, Uint128)>>,
 }
 
 /// Stores the config struct at the given key
@@ -12,8 +12,8 @@ use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::PairType;
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse, TWAP_PRECISION,
+    assert_belief_price, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse,
+    ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
 };
 use astroport::token_factory::{MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint};
 
@@ -849,6 +849,8 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -1057,12 +1059,27 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
     assert_eq!(res, ContractError::Cw20DirectSwap {});
 
+    // Unknown hook messages are rejected with a clear error
+    for hook in [Binary::default(), to_json_binary(&"withdraw").unwrap()] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            amount: offer_amount,
+            msg: hook,
+        });
+        let env = mock_env_with_block_time(1000);
+        let info = mock_info("asset0000", &[]);
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(res, ContractError::UnknownCw20Hook {});
+    }
+
     // Normal sell
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: String::from("addr0000"),
@@ -1072,6 +1089,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: Some(Decimal::percent(50)),
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
     });
@@ -1195,6 +1214,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
     });
@@ -1252,6 +1273,46 @@ fn test_max_spread() {
     .unwrap_err();
 }
 
+#[test]
+fn test_strict_belief_price() {
+    // 1% worse than belief price is tolerated by max_spread
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Uint128::from(1200000000u128),
+        Uint128::from(990000u128),
+        Uint128::zero(),
+    )
+    .unwrap();
+    // but is rejected in strict mode
+    assert_eq!(
+        assert_belief_price(
+            Some(Decimal::from_ratio(1200u128, 1u128)),
+            Uint128::from(1200000000u128),
+            Uint128::from(990000u128),
+        )
+        .unwrap_err(),
+        StdError::generic_err("Operation is executed at a price worse than belief price")
+    );
+
+    assert_belief_price(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Uint128::from(1200000000u128),
+        Uint128::from(1000000u128),
+    )
+    .unwrap();
+
+    assert_eq!(
+        assert_belief_price(
+            None,
+            Uint128::from(1200000000u128),
+            Uint128::from(1000000u128)
+        )
+        .unwrap_err(),
+        StdError::generic_err("Strict belief price check requires belief_price to be set")
+    );
+}
+
 #[test]
 fn test_query_pool() {
     let total_share_amount = Uint128::from(111u128);
@@ -1471,6 +1532,10 @@ fn test_accumulate_prices() {
                 price0_cumulative_last: Uint128::new(case.last0),
                 price1_cumulative_last: Uint128::new(case.last1),
                 track_asset_balances: false,
+                max_swap_ratio: None,
+                trading_start_time: None,
+                default_max_spread: None,
+                max_pool_balance: None,
                 fee_share: None,
                 tracker_addr: None,
             },
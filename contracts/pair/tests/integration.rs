@@ -1,12 +1,16 @@
 #![cfg(not(tarpaulin_include))]
 
+use std::str::FromStr;
+
 use cosmwasm_std::{
-    attr, coin, to_json_binary, Addr, Coin, Decimal, DepsMut, Empty, Env, MessageInfo, Response,
-    StdResult, Uint128, Uint64,
+    attr, coin, from_json, to_json_binary, Addr, Binary, Coin, Decimal, DepsMut, Empty, Env,
+    MessageInfo, Response, StdResult, Uint128, Uint64,
 };
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 
-use astroport::asset::{native_asset_info, Asset, AssetInfo, PairInfo, MINIMUM_LIQUIDITY_AMOUNT};
+use astroport::asset::{
+    native_asset_info, Asset, AssetInfo, AssetInfoExt, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+};
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::{
     ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
@@ -14,8 +18,8 @@ use astroport::factory::{
 };
 use astroport::pair::{
     ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FeeShareConfig,
-    InstantiateMsg, PoolResponse, QueryMsg, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
-    MAX_FEE_SHARE_BPS, TWAP_PRECISION,
+    InstantiateMsg, PoolResponse, QueryMsg, Referral, SimulateWithSpreadResponse, XYKPoolConfig,
+    XYKPoolParams, XYKPoolUpdateParams, MAX_FEE_SHARE_BPS, TWAP_PRECISION,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport::tokenfactory_tracker::{
@@ -93,7 +97,15 @@ fn store_tracker_contract(app: &mut TestApp) -> u64 {
     app.store_code(tracker_contract)
 }
 
-fn instantiate_pair(mut router: &mut TestApp, owner: &Addr) -> Addr {
+fn instantiate_pair(router: &mut TestApp, owner: &Addr) -> Addr {
+    instantiate_pair_with_params(router, owner, None)
+}
+
+fn instantiate_pair_with_params(
+    mut router: &mut TestApp,
+    owner: &Addr,
+    init_params: Option<Binary>,
+) -> Addr {
     let token_contract_code_id = store_token_code(&mut router);
 
     let pair_contract_code_id = store_pair_code(&mut router);
@@ -141,7 +153,7 @@ fn instantiate_pair(mut router: &mut TestApp, owner: &Addr) -> Addr {
         ],
         token_code_id: token_contract_code_id,
         factory_addr: factory_instance.to_string(),
-        init_params: None,
+        init_params,
     };
 
     let pair = router
@@ -477,7 +489,11 @@ fn test_provide_and_withdraw_liquidity() {
             params: Some(
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
+                    max_swap_ratio: None,
+                    trading_start_time: None,
+                    default_max_spread: None,
                     fee_share: None,
+                    max_pool_balance: None,
                 })
                 .unwrap()
             ),
@@ -530,6 +546,77 @@ fn provide_liquidity_msg(
     (msg, coins)
 }
 
+#[test]
+fn test_min_provide_amounts() {
+    let owner = Addr::unchecked(OWNER);
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+        ],
+    );
+    let pair_instance = instantiate_pair(&mut router, &owner);
+
+    let query_min_amounts = |router: &TestApp| -> Vec<u128> {
+        router
+            .wrap()
+            .query_wasm_smart::<Vec<Asset>>(&pair_instance, &QueryMsg::MinProvideAmounts {})
+            .unwrap()
+            .into_iter()
+            .map(|asset| asset.amount.u128())
+            .collect()
+    };
+    let simulate_provide = |router: &TestApp, amount: u128| -> StdResult<Uint128> {
+        router.wrap().query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::SimulateProvide {
+                assets: vec![
+                    native_asset_info("uusd".to_string()).with_balance(amount),
+                    native_asset_info("uluna".to_string()).with_balance(amount),
+                ],
+                slippage_tolerance: None,
+            },
+        )
+    };
+
+    // The first provide must exceed the locked minimum liquidity
+    let min_amount = MINIMUM_LIQUIDITY_AMOUNT.u128() + 1;
+    assert_eq!(query_min_amounts(&router), vec![min_amount, min_amount]);
+    simulate_provide(&router, min_amount - 1).unwrap_err();
+    assert_eq!(
+        simulate_provide(&router, min_amount).unwrap(),
+        Uint128::one()
+    );
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(100_000_000),
+        Uint128::new(100_000_000),
+        None,
+        None,
+        None,
+    );
+    router
+        .execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    // Donate assets so the pool holds 2.5 units of each asset per LP token
+    router
+        .send_tokens(
+            owner.clone(),
+            pair_instance.clone(),
+            &[
+                coin(150_000_000u128, "uusd"),
+                coin(150_000_000u128, "uluna"),
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(query_min_amounts(&router), vec![3, 3]);
+    assert_eq!(simulate_provide(&router, 2).unwrap(), Uint128::zero());
+    assert_eq!(simulate_provide(&router, 3).unwrap(), Uint128::one());
+}
+
 #[test]
 fn test_compatibility_of_tokens_with_different_precision() {
     let owner = Addr::unchecked(OWNER);
@@ -705,6 +792,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -751,6 +840,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -774,6 +865,182 @@ fn test_compatibility_of_tokens_with_different_precision() {
     assert_eq!(res.balance, y_expected_return - acceptable_spread_amount);
 }
 
+#[test]
+fn test_swap_with_target() {
+    let owner = Addr::unchecked(OWNER);
+    let mut app = mock_app(owner.clone(), vec![]);
+
+    let token_code_id = store_token_code(&mut app);
+    let pair_code_id = store_pair_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+
+    let pool_amount = Uint128::new(1_000_000);
+    let offer_amount = Uint128::new(10_000);
+
+    let mut tokens = vec![];
+    for name in ["Xtoken", "Ytoken"] {
+        let init_msg = TokenInstantiateMsg {
+            name: name.to_string(),
+            symbol: name.to_string(),
+            decimals: 6,
+            initial_balances: vec![Cw20Coin {
+                address: OWNER.to_string(),
+                amount: pool_amount + offer_amount,
+            }],
+            mint: None,
+            marketing: None,
+        };
+        let token = app
+            .instantiate_contract(token_code_id, owner.clone(), &init_msg, &[], name, None)
+            .unwrap();
+        tokens.push(token);
+    }
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: tokens[0].clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: tokens[1].clone(),
+        },
+    ];
+
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 0,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 0,
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
+        token_code_id,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+    let factory_instance = app
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        factory_instance.clone(),
+        &FactoryExecuteMsg::CreatePair {
+            asset_infos: asset_infos.clone(),
+            pair_type: PairType::Xyk {},
+            init_params: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let pair_instance = app
+        .wrap()
+        .query_wasm_smart::<PairInfo>(
+            &factory_instance,
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap()
+        .contract_addr;
+
+    for token in &tokens {
+        app.execute_contract(
+            owner.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: pair_instance.to_string(),
+                expires: None,
+                amount: pool_amount,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: asset_infos
+            .iter()
+            .map(|info| info.with_balance(pool_amount))
+            .collect(),
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+    };
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let target_out = Uint128::new(1_000);
+    let swap_msg = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        msg: to_json_binary(&Cw20HookMsg::SwapWithTarget {
+            ask_asset_info: None,
+            target_out,
+            max_spread: Some(Decimal::percent(50)),
+        })
+        .unwrap(),
+        amount: offer_amount,
+    };
+    app.execute_contract(owner.clone(), tokens[0].clone(), &swap_msg, &[])
+        .unwrap();
+
+    let balance = |app: &TestApp, token: &Addr| {
+        app.wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: OWNER.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+
+    // Only 1002 X tokens are needed to receive 1000 Y tokens, the rest is refunded
+    assert_eq!(balance(&app, &tokens[0]).u128(), 10_000 - 1_002);
+    assert_eq!(balance(&app, &tokens[1]), offer_amount + target_out);
+
+    // The pool only keeps the swapped amount
+    let res: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(res.assets[0].amount.u128(), 1_000_000 + 1_002);
+    assert_eq!(res.assets[1].amount, pool_amount - target_out);
+
+    // A target beyond the ask pool can't be reached, so the whole amount is swapped
+    let swap_msg = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        msg: to_json_binary(&Cw20HookMsg::SwapWithTarget {
+            ask_asset_info: None,
+            target_out: pool_amount * Uint128::new(2),
+            max_spread: Some(Decimal::percent(50)),
+        })
+        .unwrap(),
+        amount: Uint128::new(5_000),
+    };
+    app.execute_contract(owner.clone(), tokens[0].clone(), &swap_msg, &[])
+        .unwrap();
+    assert_eq!(balance(&app, &tokens[0]).u128(), 10_000 - 1_002 - 5_000);
+
+    let res: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(res.assets[0].amount.u128(), 1_000_000 + 1_002 + 5_000);
+}
+
 #[test]
 fn test_if_twap_is_calculated_correctly_when_pool_idles() {
     let owner = Addr::unchecked("owner");
@@ -922,6 +1189,42 @@ fn create_pair_with_same_assets() {
     )
 }
 
+#[test]
+fn default_max_spread_query_error() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+
+    let token_contract_code_id = store_token_code(&mut router);
+    let pair_contract_code_id = store_pair_code(&mut router);
+
+    // The default max spread can't be queried from a non-factory address
+    let msg = InstantiateMsg {
+        pair_type: PairType::Xyk {},
+        asset_infos: vec![
+            native_asset_info("uusd".to_string()),
+            native_asset_info("uluna".to_string()),
+        ],
+        token_code_id: token_contract_code_id,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let err = router
+        .instantiate_contract(
+            pair_contract_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            String::from("PAIR"),
+            None,
+        )
+        .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("No such contract: factory"));
+}
+
 #[test]
 fn wrong_number_of_assets() {
     let owner = Addr::unchecked("owner");
@@ -1051,6 +1354,10 @@ fn asset_balances_tracking_works_correctly() {
         init_params: Some(
             to_json_binary(&XYKPoolParams {
                 track_asset_balances: Some(true),
+                max_swap_ratio: None,
+                trading_start_time: None,
+                default_max_spread: None,
+                max_pool_balance: None,
             })
             .unwrap(),
         ),
@@ -1138,6 +1445,8 @@ fn asset_balances_tracking_works_correctly() {
         belief_price: None,
         max_spread: None,
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
@@ -1303,7 +1612,11 @@ fn update_pair_config() {
             params: Some(
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
+                    max_swap_ratio: None,
+                    trading_start_time: None,
+                    default_max_spread: None,
                     fee_share: None,
+                    max_pool_balance: None,
                 })
                 .unwrap()
             ),
@@ -1314,6 +1627,378 @@ fn update_pair_config() {
     );
 }
 
+#[test]
+fn test_max_swap_ratio() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let (msg, send_funds) = provide_liquidity_msg(
+        Uint128::new(100_000000),
+        Uint128::new(100_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    let set_ratio_msg = |max_swap_ratio| ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::SetMaxSwapRatio { max_swap_ratio }).unwrap(),
+    };
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &set_ratio_msg(Some(Decimal::zero())),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidMaxSwapRatio {},
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &set_ratio_msg(Some(Decimal::percent(10))),
+        &[],
+    )
+    .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    let params: XYKPoolConfig = from_json(res.params.unwrap()).unwrap();
+    assert_eq!(params.max_swap_ratio, Some(Decimal::percent(10)));
+
+    let swap_msg = |amount: u128| ExecuteMsg::Swap {
+        offer_asset: native_asset_info("uusd".to_string()).with_balance(amount),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        strict_belief_price: false,
+        referral: None,
+    };
+
+    // A swap above 10% of the offer reserve is rejected
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &swap_msg(11_000000),
+            &[coin(11_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MaxSwapRatioExceeded {
+            offer_amount: Uint128::new(11_000000),
+            offer_pool: Uint128::new(100_000000),
+            max_swap_ratio: Decimal::percent(10),
+        },
+        err.downcast().unwrap()
+    );
+
+    // A swap below the ratio goes through
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &swap_msg(5_000000),
+        &[coin(5_000000, "uusd")],
+    )
+    .unwrap();
+
+    // Removing the limit allows large swaps again
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &set_ratio_msg(None),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &swap_msg(11_000000),
+        &[coin(11_000000, "uusd")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_referral_is_not_supported() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let (msg, send_funds) = provide_liquidity_msg(
+        Uint128::new(100_000000),
+        Uint128::new(100_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: native_asset_info("uusd".to_string()).with_balance(1_000000u128),
+                ask_asset_info: None,
+                belief_price: None,
+                max_spread: Some(Decimal::percent(50)),
+                to: None,
+                strict_belief_price: false,
+                referral: Some(Referral {
+                    address: "referrer".to_string(),
+                    fee_bps: 100,
+                }),
+            },
+            &[coin(1_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::NonSupported {}, err.downcast().unwrap());
+}
+
+#[test]
+fn test_trading_start_time() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+        ],
+    );
+
+    let start_time = app.block_info().time.seconds() + 3600;
+    let pair_instance = instantiate_pair_with_params(
+        &mut app,
+        &owner,
+        Some(
+            to_json_binary(&XYKPoolParams {
+                track_asset_balances: None,
+                max_swap_ratio: None,
+                trading_start_time: Some(start_time),
+                default_max_spread: None,
+                max_pool_balance: None,
+            })
+            .unwrap(),
+        ),
+    );
+
+    // Liquidity can be provided before trading starts
+    let (msg, send_funds) = provide_liquidity_msg(
+        Uint128::new(100_000000),
+        Uint128::new(100_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: native_asset_info("uusd".to_string()).with_balance(1_000000u128),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        strict_belief_price: false,
+        referral: None,
+    };
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &swap_msg,
+            &[coin(1_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::TradingNotStarted { start_time },
+        err.downcast().unwrap()
+    );
+
+    app.update_block(|b| b.time = b.time.plus_seconds(3600));
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &swap_msg,
+        &[coin(1_000000, "uusd")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_max_pool_balance() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+        ],
+    );
+
+    let pair_instance = instantiate_pair_with_params(
+        &mut app,
+        &owner,
+        Some(
+            to_json_binary(&XYKPoolParams {
+                track_asset_balances: None,
+                max_swap_ratio: None,
+                trading_start_time: None,
+                default_max_spread: None,
+                max_pool_balance: Some(vec![(
+                    native_asset_info("uusd".to_string()),
+                    Uint128::new(150_000000),
+                )]),
+            })
+            .unwrap(),
+        ),
+    );
+
+    let set_cap_msg = |max_pool_balance| ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::SetMaxPoolBalance { max_pool_balance })
+            .unwrap(),
+    };
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &set_cap_msg(Some(vec![(
+                native_asset_info("uatom".to_string()),
+                Uint128::new(1_000000),
+            )])),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidMaxPoolBalance {},
+        err.downcast().unwrap()
+    );
+
+    // Fill the pool up to the cap
+    for amount in [100_000000, 50_000000] {
+        let (msg, send_funds) =
+            provide_liquidity_msg(Uint128::new(amount), Uint128::new(amount), None, None, None);
+        app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+            .unwrap();
+    }
+
+    let (msg, send_funds) =
+        provide_liquidity_msg(Uint128::new(1), Uint128::new(1), None, None, None);
+    let err = app
+        .execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MaxPoolBalanceExceeded {
+            asset: "uusd".to_string(),
+            max_pool_balance: Uint128::new(150_000000),
+        },
+        err.downcast().unwrap()
+    );
+
+    // Removing the cap allows provides again
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &set_cap_msg(None),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+}
+
+#[test]
+fn test_simulate_with_spread() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let (msg, send_funds) = provide_liquidity_msg(
+        Uint128::new(100_000000),
+        Uint128::new(100_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    let offer_asset = native_asset_info("uusd".to_string()).with_balance(1_000000u128);
+    let simulate = |belief_price: Decimal| -> SimulateWithSpreadResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &pair_instance,
+                &QueryMsg::SimulateWithSpread {
+                    offer_asset: offer_asset.clone(),
+                    belief_price,
+                },
+            )
+            .unwrap()
+    };
+
+    // 1 uusd returns 0.990099 uluna
+    for (belief_price, expected_return, spread) in [
+        ("1", 1_000000u128, "0.009901"),
+        ("0.5", 2_000000, "0.5049505"),
+        ("1.01", 990099, "0"),
+        ("2", 500000, "0"),
+    ] {
+        let res = simulate(Decimal::from_str(belief_price).unwrap());
+        assert_eq!(res.return_amount.u128(), 990099);
+        assert_eq!(res.commission_amount.u128(), 0);
+        assert_eq!(res.expected_return.u128(), expected_return);
+        assert_eq!(res.spread, Decimal::from_str(spread).unwrap());
+    }
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<SimulateWithSpreadResponse>(
+            &pair_instance,
+            &QueryMsg::SimulateWithSpread {
+                offer_asset,
+                belief_price: Decimal::zero(),
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Belief price must not be zero!"));
+}
+
 #[test]
 fn enable_disable_fee_sharing() {
     let owner = Addr::unchecked(OWNER);
@@ -1396,7 +2081,11 @@ fn enable_disable_fee_sharing() {
             params: Some(
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
+                    max_swap_ratio: None,
+                    trading_start_time: None,
+                    default_max_spread: None,
                     fee_share: None,
+                    max_pool_balance: None,
                 })
                 .unwrap()
             ),
@@ -1466,10 +2155,14 @@ fn enable_disable_fee_sharing() {
             params: Some(
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
+                    max_swap_ratio: None,
+                    trading_start_time: None,
+                    default_max_spread: None,
                     fee_share: Some(FeeShareConfig {
                         bps: fee_share_bps,
                         recipient: Addr::unchecked(fee_share_contract),
                     }),
+                    max_pool_balance: None,
                 })
                 .unwrap()
             ),
@@ -1499,7 +2192,11 @@ fn enable_disable_fee_sharing() {
             params: Some(
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
+                    max_swap_ratio: None,
+                    trading_start_time: None,
+                    default_max_spread: None,
                     fee_share: None,
+                    max_pool_balance: None,
                 })
                 .unwrap()
             ),
@@ -1640,6 +2337,10 @@ fn provide_liquidity_with_autostaking_to_generator() {
         init_params: Some(
             to_json_binary(&XYKPoolParams {
                 track_asset_balances: Some(true),
+                max_swap_ratio: None,
+                trading_start_time: None,
+                default_max_spread: None,
+                max_pool_balance: None,
             })
             .unwrap(),
         ),
@@ -2002,6 +2703,10 @@ fn test_fee_share(
         init_params: Some(
             to_json_binary(&XYKPoolParams {
                 track_asset_balances: Some(true),
+                max_swap_ratio: None,
+                trading_start_time: None,
+                default_max_spread: None,
+                max_pool_balance: None,
             })
             .unwrap(),
         ),
@@ -2092,6 +2797,8 @@ fn test_fee_share(
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -2335,6 +3042,10 @@ fn test_tracker_contract() {
         init_params: Some(
             to_json_binary(&XYKPoolParams {
                 track_asset_balances: Some(true),
+                max_swap_ratio: None,
+                trading_start_time: None,
+                default_max_spread: None,
+                max_pool_balance: None,
             })
             .unwrap(),
         ),
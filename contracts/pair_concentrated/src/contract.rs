@@ -22,11 +22,12 @@ use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_ow
 use astroport::cosmwasm_ext::{DecimalToInteger, IntegerToDecimal};
 use astroport::observation::{PrecommitObservation, OBSERVATIONS_SIZE};
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, FeeShareConfig, InstantiateMsg, ReplyIds, MAX_FEE_SHARE_BPS,
-    MIN_TRADE_SIZE,
+    assert_belief_price, Cw20HookMsg, ExecuteMsg, FeeShareConfig, InstantiateMsg, Referral,
+    ReplyIds, MAX_FEE_SHARE_BPS, MAX_REFERRAL_FEE_BPS, MIN_TRADE_SIZE,
 };
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, UpdatePoolParams,
+    ConcentratedPoolParams, ConcentratedPoolUpdateParams, FeeAccrualConfig, OracleGuardConfig,
+    UpdatePoolParams, MAX_LOCAL_FEE_SHARE_BPS, MAX_REBALANCE_BOUNTY_BPS,
 };
 use astroport::querier::{
     query_factory_config, query_fee_info, query_native_supply, query_tracker_config,
@@ -41,14 +42,21 @@ use astroport_pcl_common::state::{
 };
 use astroport_pcl_common::utils::{
     accumulate_prices, assert_max_spread, before_swap_check, calc_last_prices, check_asset_infos,
-    check_cw20_in_pool, compute_swap, get_share_in_assets, mint_liquidity_token_message,
+    check_cw20_in_pool, compute_swap_with_fee_floor, get_share_in_assets,
+    mint_liquidity_token_message,
 };
 use astroport_pcl_common::{calc_d, get_xcp};
 
 use crate::error::ContractError;
-use crate::state::{BALANCES, CONFIG, OBSERVATIONS, OWNERSHIP_PROPOSAL};
+use crate::state::{
+    ACCRUED_MAKER_FEES, BALANCES, CONFIG, CUMULATIVE_MAKER_FEES, FEE_ACCRUAL, FEE_DISCOUNTS,
+    FEE_FREE_PROVIDE_UNTIL, HAS_SWAPS, LOCAL_FEE_SHARE, MAX_PRICE_STALENESS, MAX_REFERRAL_FEE,
+    MIN_ORACLE_LIQUIDITY, OBSERVATIONS, ORACLE_GUARD, OWNERSHIP_PROPOSAL, REBALANCE_BOUNTY_BPS,
+    VOLATILITY_FEE_FACTOR,
+};
 use crate::utils::{
-    accumulate_swap_sizes, calculate_shares, get_assets_with_precision, query_pools,
+    accumulate_swap_sizes, assert_oracle_divergence, calculate_shares, get_assets_with_precision,
+    is_provide_fee_free, query_pools, volatility_fee_floor,
 };
 
 /// Contract name that is used for migration.
@@ -57,6 +65,8 @@ const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// An LP token's precision.
 pub(crate) const LP_TOKEN_PRECISION: u8 = 6;
+/// The maximum spread allowed for swaps while the pool price is stale (1%).
+const STALE_PRICE_MAX_SPREAD: Decimal = Decimal::percent(1);
 
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -152,6 +162,18 @@ pub fn instantiate(
 
     CONFIG.save(deps.storage, &config)?;
 
+    if let Some(fee_free_provide_until) = params.fee_free_provide_until {
+        FEE_FREE_PROVIDE_UNTIL.save(deps.storage, &fee_free_provide_until)?;
+    }
+
+    if let Some(factor) = params.volatility_fee_factor {
+        ensure!(
+            !factor.is_zero(),
+            ContractError::InvalidVolatilityFeeFactor {}
+        );
+        VOLATILITY_FEE_FACTOR.save(deps.storage, &factor)?;
+    }
+
     BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
 
     // Create LP token
@@ -273,6 +295,10 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 ///             max_spread,
 ///             to,
 ///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::Rebalance {}** Runs the repeg step and pays the caller a bounty if the pool was repegged.
+///
+/// * **ExecuteMsg::SeedCumulativePrices { prices }** Overwrites the cumulative prices before the first swap.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -305,12 +331,23 @@ pub fn execute(
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
             ..
         } => {
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
             }
+            let extra_denoms = info
+                .funds
+                .iter()
+                .filter(|coin| !offer_asset.info.equal(&AssetInfo::native(&coin.denom)))
+                .map(|coin| coin.denom.as_str())
+                .join(", ");
+            if !extra_denoms.is_empty() {
+                return Err(ContractError::UnexpectedSwapFunds(extra_denoms));
+            }
             offer_asset.assert_sent_native_token_balance(&info)?;
 
             if !config.pair_info.asset_infos.contains(&offer_asset.info) {
@@ -327,6 +364,8 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
+                referral,
             )
         }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
@@ -367,6 +406,10 @@ pub fn execute(
             .map_err(Into::into)
         }
         ExecuteMsg::WithdrawLiquidity { assets, .. } => withdraw_liquidity(deps, env, info, assets),
+        ExecuteMsg::Rebalance {} => rebalance(deps, env, info),
+        ExecuteMsg::SeedCumulativePrices { prices } => {
+            seed_cumulative_prices(deps, env, info, prices)
+        }
     }
 }
 
@@ -384,6 +427,8 @@ fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
             ..
         } => {
             let config = CONFIG.load(deps.storage)?;
@@ -400,8 +445,11 @@ fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
+                referral,
             )
         }
+        Cw20HookMsg::SwapWithTarget { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -437,7 +485,7 @@ pub fn provide_liquidity(
 
     let precisions = Precisions::new(deps.storage)?;
 
-    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let mut pools = query_pools(deps.as_ref(), &env.contract.address, &config, &precisions)?;
 
     let old_real_price = config.pool_state.price_state.last_price;
 
@@ -485,7 +533,14 @@ pub fn provide_liquidity(
         total_share,
         deposits.clone(),
         slippage_tolerance,
+        is_provide_fee_free(deps.storage, &env)?,
     )?;
+    // Tiny provides (e.g. in 18-decimal pools) may be worth less than the smallest LP token unit.
+    // Reject them instead of absorbing the deposit without minting anything
+    ensure!(
+        !share_uint128.is_zero(),
+        ContractError::ProvideTooSmall(LP_TOKEN_PRECISION)
+    );
 
     if total_share.is_zero() {
         messages.extend(mint_liquidity_token_message(
@@ -569,7 +624,7 @@ fn withdraw_liquidity(
 
     let precisions = Precisions::new(deps.storage)?;
     let pools = query_pools(
-        deps.querier,
+        deps.as_ref(),
         &config.pair_info.contract_addr,
         &config,
         &precisions,
@@ -657,6 +712,11 @@ fn withdraw_liquidity(
 /// * **max_spread** sets the maximum spread of the swap operation.
 ///
 /// * **to** sets the recipient of the swap operation.
+///
+/// * **strict_belief_price** if true, the swap fails when executed at a price worse than **belief_price**.
+///
+/// * **referral** optional referral which receives a part of the swap fee.
+#[allow(clippy::too_many_arguments)]
 fn swap(
     deps: DepsMut,
     env: Env,
@@ -665,13 +725,29 @@ fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    strict_belief_price: bool,
+    referral: Option<Referral>,
 ) -> Result<Response, ContractError> {
     let precisions = Precisions::new(deps.storage)?;
     let offer_asset_prec = precisions.get_precision(&offer_asset.info)?;
     let offer_asset_dec = offer_asset.to_decimal_asset(offer_asset_prec)?;
     let mut config = CONFIG.load(deps.storage)?;
 
-    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    // The internal price may be far from the market after a long period without swaps.
+    // Make the trader state the expected price explicitly and bound the spread tighter in this case
+    let mut max_spread = max_spread;
+    if let Some(max_price_staleness) = MAX_PRICE_STALENESS.may_load(deps.storage)? {
+        let last_update = config.pool_state.price_state.last_price_update;
+        if last_update.saturating_add(max_price_staleness) < env.block.time.seconds() {
+            ensure!(
+                belief_price.is_some() && max_spread.is_some(),
+                ContractError::StalePrice { last_update }
+            );
+            max_spread = max_spread.map(|spread| spread.min(STALE_PRICE_MAX_SPREAD));
+        }
+    }
+
+    let mut pools = query_pools(deps.as_ref(), &env.contract.address, &config, &precisions)?;
 
     let (offer_ind, _) = pools
         .iter()
@@ -702,8 +778,36 @@ fn swap(
     if let Some(fee_share) = config.fee_share.clone() {
         share_fee_share = Decimal256::from_ratio(fee_share.bps, 10000u16);
     }
+    // Discounted senders pay only a part of the swap fee
+    let fee_multiplier = FEE_DISCOUNTS
+        .may_load(deps.storage, &sender)?
+        .map_or(Decimal256::one(), Decimal256::from);
+    // The referral fee is carved out of the LP/maker part of the swap fee
+    let referral = referral
+        .map(|referral| -> Result<_, ContractError> {
+            let max_bps = MAX_REFERRAL_FEE
+                .may_load(deps.storage)?
+                .unwrap_or(MAX_REFERRAL_FEE_BPS);
+            ensure!(
+                referral.fee_bps > 0 && referral.fee_bps <= max_bps,
+                ContractError::ReferralFeeOutOfBounds { max_bps }
+            );
+            let referral_addr = deps.api.addr_validate(&referral.address)?;
+            ensure!(
+                referral_addr != sender && Some(&referral_addr) != to.as_ref(),
+                ContractError::SelfReferral {}
+            );
+            Ok((
+                referral_addr,
+                Decimal256::from_ratio(referral.fee_bps, 10000u16),
+            ))
+        })
+        .transpose()?;
+    let referral_fee_share = referral
+        .as_ref()
+        .map_or(Decimal256::zero(), |(_, share)| *share);
 
-    let swap_result = compute_swap(
+    let swap_result = compute_swap_with_fee_floor(
         &xs,
         offer_asset_dec.amount,
         ask_ind,
@@ -711,9 +815,21 @@ fn swap(
         &env,
         maker_fee_share,
         share_fee_share,
+        referral_fee_share,
+        fee_multiplier,
+        volatility_fee_floor(deps.storage, &config, &env)?,
     )?;
     xs[offer_ind] += offer_asset_dec.amount;
-    xs[ask_ind] -= swap_result.dy + swap_result.maker_fee + swap_result.share_fee;
+    xs[ask_ind] -= swap_result.ask_amount_out();
+
+    assert_oracle_divergence(
+        deps.as_ref(),
+        &env,
+        &config,
+        &precisions,
+        old_real_price,
+        &xs,
+    )?;
 
     let return_amount = swap_result.dy.to_uint(ask_asset_prec)?;
     let spread_amount = swap_result.spread_fee.to_uint(ask_asset_prec)?;
@@ -724,15 +840,16 @@ fn swap(
         return_amount,
         spread_amount,
     )?;
+    if strict_belief_price {
+        assert_belief_price(belief_price, offer_asset.amount, return_amount)?;
+    }
 
     let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?
         .to_decimal256(LP_TOKEN_PRECISION)?;
 
     // Skip very small trade sizes which could significantly mess up the price due to rounding errors,
     // especially if token precisions are 18.
-    if (swap_result.dy + swap_result.maker_fee + swap_result.share_fee) >= MIN_TRADE_SIZE
-        && offer_asset_dec.amount >= MIN_TRADE_SIZE
-    {
+    if swap_result.ask_amount_out() >= MIN_TRADE_SIZE && offer_asset_dec.amount >= MIN_TRADE_SIZE {
         let last_price = swap_result.calc_last_price(offer_asset_dec.amount, offer_ind);
 
         // update_price() works only with internal representation
@@ -760,18 +877,73 @@ fn swap(
         }
     }
 
+    // Send the referral fee
+    let mut referral_fee_amount = Uint128::zero();
+    if let Some((referral_addr, _)) = referral {
+        referral_fee_amount = swap_result.referral_fee.to_uint(ask_asset_prec)?;
+        if !referral_fee_amount.is_zero() {
+            let fee = pools[ask_ind].info.with_balance(referral_fee_amount);
+            messages.push(fee.into_msg(referral_addr)?);
+        }
+    }
+
     // Send the maker fee
     let mut maker_fee = Uint128::zero();
+    let mut local_fee_share_amount = Uint128::zero();
     if let Some(fee_address) = fee_info.fee_address {
         maker_fee = swap_result.maker_fee.to_uint(ask_asset_prec)?;
-        if !maker_fee.is_zero() {
-            let fee = pools[ask_ind].info.with_balance(maker_fee);
-            messages.push(fee.into_msg(fee_address)?);
+
+        // Divert the local share of the maker fee
+        if let Some(local_fee_share) = LOCAL_FEE_SHARE.may_load(deps.storage)? {
+            local_fee_share_amount = maker_fee.multiply_ratio(local_fee_share.bps, 10000u16);
+            if !local_fee_share_amount.is_zero() {
+                let fee = pools[ask_ind].info.with_balance(local_fee_share_amount);
+                messages.push(fee.into_msg(local_fee_share.recipient)?);
+            }
+        }
+
+        let global_maker_fee = maker_fee - local_fee_share_amount;
+        if !global_maker_fee.is_zero() {
+            match FEE_ACCRUAL.may_load(deps.storage)? {
+                Some(fee_accrual) => {
+                    // Keep the fee in the contract until it is swept
+                    let mut accrued_fees = ACCRUED_MAKER_FEES
+                        .may_load(deps.storage)?
+                        .unwrap_or_default();
+                    accrued_fees[ask_ind] += global_maker_fee;
+
+                    if matches!(fee_accrual.sweep_threshold, Some(threshold) if accrued_fees[ask_ind] >= threshold)
+                    {
+                        messages.extend(sweep_accrued_fees(
+                            &config.pair_info.asset_infos,
+                            &accrued_fees,
+                            &fee_address,
+                        )?);
+                        accrued_fees = Default::default();
+                    }
+
+                    ACCRUED_MAKER_FEES.save(deps.storage, &accrued_fees)?;
+                }
+                None => {
+                    let fee = pools[ask_ind].info.with_balance(global_maker_fee);
+                    messages.push(fee.into_msg(fee_address)?);
+                }
+            }
+
+            let mut maker_fees = CUMULATIVE_MAKER_FEES
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            maker_fees[ask_ind] += global_maker_fee;
+            CUMULATIVE_MAKER_FEES.save(deps.storage, &maker_fees)?;
         }
     }
 
     accumulate_prices(&env, &mut config, old_real_price);
 
+    if !HAS_SWAPS.exists(deps.storage) {
+        HAS_SWAPS.save(deps.storage, &true)?;
+    }
+
     // Store observation from precommit data
     accumulate_swap_sizes(deps.storage, &env)?;
 
@@ -802,7 +974,8 @@ fn swap(
             &(pools[ask_ind].amount.to_uint(ask_asset_prec)?
                 - return_amount
                 - maker_fee
-                - fee_share_amount),
+                - fee_share_amount
+                - referral_fee_amount),
             env.block.height,
         )?;
     }
@@ -821,10 +994,136 @@ fn swap(
             swap_result.total_fee.to_uint(ask_asset_prec)?,
         ),
         attr("maker_fee_amount", maker_fee),
+        attr("local_fee_share_amount", local_fee_share_amount),
         attr("fee_share_amount", fee_share_amount),
+        attr("referral_fee_amount", referral_fee_amount),
     ]))
 }
 
+/// Runs the repeg step on the current pool state so the price scale can follow the internal
+/// oracle price during periods without swaps. Nothing is changed if the pool is not repegged,
+/// otherwise the caller receives the configured share of the accrued maker fees.
+fn rebalance(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+
+    let mut response =
+        Response::new().add_attributes([attr("action", "rebalance"), attr("sender", &info.sender)]);
+
+    let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?
+        .to_decimal256(LP_TOKEN_PRECISION)?;
+    if total_share.is_zero() {
+        return Ok(response.add_attribute("repegged", "false"));
+    }
+
+    let pools = query_pools(deps.as_ref(), &env.contract.address, &config, &precisions)?;
+    let mut xs = pools.iter().map(|asset| asset.amount).collect_vec();
+    let old_real_price = calc_last_prices(&xs, &config, &env)?;
+    let old_price_scale = config.pool_state.price_state.price_scale;
+    let last_price = config.pool_state.price_state.last_price;
+
+    // update_price() works only with internal representation
+    xs[1] *= old_price_scale;
+    config
+        .pool_state
+        .update_price(&config.pool_params, &env, total_share, &xs, last_price)?;
+
+    if config.pool_state.price_state.price_scale == old_price_scale {
+        return Ok(response.add_attribute("repegged", "false"));
+    }
+
+    accumulate_prices(&env, &mut config, old_real_price);
+    CONFIG.save(deps.storage, &config)?;
+
+    let mut bounty = vec![];
+    if let Some(bounty_bps) = REBALANCE_BOUNTY_BPS.may_load(deps.storage)? {
+        let mut accrued_fees = ACCRUED_MAKER_FEES
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        for (asset_info, accrued) in config
+            .pair_info
+            .asset_infos
+            .iter()
+            .zip(accrued_fees.iter_mut())
+        {
+            let amount = accrued.multiply_ratio(bounty_bps, 10000u16);
+            if !amount.is_zero() {
+                *accrued -= amount;
+                bounty.push(asset_info.with_balance(amount));
+            }
+        }
+        ACCRUED_MAKER_FEES.save(deps.storage, &accrued_fees)?;
+    }
+
+    for asset in &bounty {
+        response = response.add_message(asset.clone().into_msg(&info.sender)?);
+    }
+
+    Ok(response.add_attributes([
+        attr("repegged", "true"),
+        attr(
+            "price_scale",
+            config.pool_state.price_state.price_scale.to_string(),
+        ),
+        attr("bounty", bounty.iter().join(", ")),
+    ]))
+}
+
+/// Overwrites the cumulative prices with a starting baseline. Only allowed before the first swap.
+/// Besides the owner, the factory can seed a pair which replaces another one.
+///
+/// * **prices** (offer asset, ask asset, cumulative price) triples to set.
+fn seed_cumulative_prices(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    let owner = config.owner.as_ref().unwrap_or(&factory_config.owner);
+    // The factory carries cumulative prices over when a pair is replaced
+    if info.sender != *owner && info.sender != config.factory_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ensure!(
+        !HAS_SWAPS.exists(deps.storage),
+        ContractError::CumulativePricesAlreadyUsed {}
+    );
+
+    for (from, to, value) in prices {
+        let (.., cumulative_price) = config
+            .cumulative_prices
+            .iter_mut()
+            .find(|(offer, ask, _)| offer == &from && ask == &to)
+            .ok_or_else(|| ContractError::InvalidAsset(format!("{from}-{to}")))?;
+        *cumulative_price = value;
+    }
+    // Seeded prices are accumulated up to the current block
+    config.block_time_last = env.block.time.seconds();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "seed_cumulative_prices"))
+}
+
+/// Builds transfer messages sending the accrued maker fees to the fee address.
+///
+/// * **accrued_fees** accrued amounts in pool asset order.
+fn sweep_accrued_fees(
+    asset_infos: &[AssetInfo],
+    accrued_fees: &[Uint128; 2],
+    fee_address: &Addr,
+) -> StdResult<Vec<CosmosMsg>> {
+    asset_infos
+        .iter()
+        .zip(accrued_fees)
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(info, amount)| info.with_balance(*amount).into_msg(fee_address))
+        .collect()
+}
+
 /// Updates the pool configuration with the specified parameters in the `params` variable.
 ///
 /// * **params** new parameter values in [`Binary`] form.
@@ -837,6 +1136,8 @@ fn update_config(
     let mut config = CONFIG.load(deps.storage)?;
     let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
 
+    let params = from_json::<ConcentratedPoolUpdateParams>(&params)?;
+
     let owner = config.owner.as_ref().unwrap_or(&factory_config.owner);
     if info.sender != *owner {
         return Err(ContractError::Unauthorized {});
@@ -844,7 +1145,7 @@ fn update_config(
 
     let mut response = Response::default();
 
-    match from_json::<ConcentratedPoolUpdateParams>(&params)? {
+    match params {
         ConcentratedPoolUpdateParams::Update(update_params) => {
             config.pool_params.update_params(update_params)?;
 
@@ -893,6 +1194,230 @@ fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        ConcentratedPoolUpdateParams::SetFeeDiscount {
+            address,
+            multiplier,
+        } => {
+            ensure!(
+                multiplier <= Decimal::one(),
+                ContractError::InvalidFeeMultiplier {}
+            );
+            let addr = deps.api.addr_validate(&address)?;
+            FEE_DISCOUNTS.save(deps.storage, &addr, &multiplier)?;
+
+            response.attributes.extend(vec![
+                attr("action", "set_fee_discount"),
+                attr("address", address),
+                attr("multiplier", multiplier.to_string()),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::RemoveFeeDiscount { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            FEE_DISCOUNTS.remove(deps.storage, &addr);
+
+            response.attributes.extend(vec![
+                attr("action", "remove_fee_discount"),
+                attr("address", address),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::SetVolatilityFeeFactor { factor } => {
+            response
+                .attributes
+                .push(attr("action", "set_volatility_fee_factor"));
+
+            if let Some(factor) = factor {
+                ensure!(
+                    !factor.is_zero(),
+                    ContractError::InvalidVolatilityFeeFactor {}
+                );
+                VOLATILITY_FEE_FACTOR.save(deps.storage, &factor)?;
+                response.attributes.push(attr("factor", factor.to_string()));
+            } else {
+                VOLATILITY_FEE_FACTOR.remove(deps.storage);
+            }
+        }
+        ConcentratedPoolUpdateParams::EnableLocalFeeShare { bps, address } => {
+            ensure!(
+                bps > 0 && bps <= MAX_LOCAL_FEE_SHARE_BPS,
+                ContractError::LocalFeeShareOutOfBounds {}
+            );
+
+            LOCAL_FEE_SHARE.save(
+                deps.storage,
+                &FeeShareConfig {
+                    bps,
+                    recipient: deps.api.addr_validate(&address)?,
+                },
+            )?;
+
+            response.attributes.extend(vec![
+                attr("action", "enable_local_fee_share"),
+                attr("local_fee_share_bps", bps.to_string()),
+                attr("local_fee_share_address", address),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::DisableLocalFeeShare {} => {
+            LOCAL_FEE_SHARE.remove(deps.storage);
+            response
+                .attributes
+                .push(attr("action", "disable_local_fee_share"));
+        }
+        ConcentratedPoolUpdateParams::EnableOracleGuard {
+            oracle,
+            max_divergence,
+        } => {
+            ensure!(
+                !max_divergence.is_zero(),
+                ContractError::InvalidMaxDivergence {}
+            );
+
+            ORACLE_GUARD.save(
+                deps.storage,
+                &OracleGuardConfig {
+                    oracle: deps.api.addr_validate(&oracle)?,
+                    max_divergence,
+                },
+            )?;
+
+            response.attributes.extend(vec![
+                attr("action", "enable_oracle_guard"),
+                attr("oracle", oracle),
+                attr("max_divergence", max_divergence.to_string()),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::DisableOracleGuard {} => {
+            ORACLE_GUARD.remove(deps.storage);
+            response
+                .attributes
+                .push(attr("action", "disable_oracle_guard"));
+        }
+        ConcentratedPoolUpdateParams::SetMaxPriceStaleness {
+            max_price_staleness,
+        } => {
+            match max_price_staleness {
+                Some(max_price_staleness) => {
+                    ensure!(
+                        max_price_staleness > 0,
+                        ContractError::InvalidMaxPriceStaleness {}
+                    );
+                    MAX_PRICE_STALENESS.save(deps.storage, &max_price_staleness)?;
+                }
+                None => MAX_PRICE_STALENESS.remove(deps.storage),
+            }
+
+            response.attributes.extend(vec![
+                attr("action", "set_max_price_staleness"),
+                attr(
+                    "max_price_staleness",
+                    max_price_staleness.map_or("none".to_string(), |v| v.to_string()),
+                ),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::EnableFeeAccrual { sweep_threshold } => {
+            ensure!(
+                sweep_threshold != Some(Uint128::zero()),
+                ContractError::InvalidSweepThreshold {}
+            );
+
+            FEE_ACCRUAL.save(deps.storage, &FeeAccrualConfig { sweep_threshold })?;
+
+            response.attributes.extend(vec![
+                attr("action", "enable_fee_accrual"),
+                attr(
+                    "sweep_threshold",
+                    sweep_threshold.map_or("none".to_string(), |v| v.to_string()),
+                ),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::DisableFeeAccrual {} => {
+            FEE_ACCRUAL.remove(deps.storage);
+            response
+                .attributes
+                .push(attr("action", "disable_fee_accrual"));
+        }
+        ConcentratedPoolUpdateParams::CollectFees {} => {
+            let accrued_fees = ACCRUED_MAKER_FEES
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            ensure!(
+                accrued_fees.iter().any(|amount| !amount.is_zero()),
+                ContractError::NoAccruedFees {}
+            );
+
+            let fee_address = query_fee_info(
+                &deps.querier,
+                &config.factory_addr,
+                config.pair_info.pair_type.clone(),
+            )?
+            .fee_address
+            .ok_or(ContractError::FeeAddressNotSet {})?;
+
+            response.messages.extend(
+                sweep_accrued_fees(&config.pair_info.asset_infos, &accrued_fees, &fee_address)?
+                    .into_iter()
+                    .map(SubMsg::new),
+            );
+            ACCRUED_MAKER_FEES.remove(deps.storage);
+
+            response.attributes.extend(vec![
+                attr("action", "collect_fees"),
+                attr("fee_address", fee_address),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::SetRebalanceBounty { bps } => {
+            match bps {
+                Some(bps) => {
+                    ensure!(
+                        bps > 0 && bps <= MAX_REBALANCE_BOUNTY_BPS,
+                        ContractError::RebalanceBountyOutOfBounds {}
+                    );
+                    REBALANCE_BOUNTY_BPS.save(deps.storage, &bps)?;
+                }
+                None => REBALANCE_BOUNTY_BPS.remove(deps.storage),
+            }
+
+            response.attributes.extend(vec![
+                attr("action", "set_rebalance_bounty"),
+                attr(
+                    "rebalance_bounty_bps",
+                    bps.map_or("none".to_string(), |v| v.to_string()),
+                ),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::SetMinOracleLiquidity {
+            min_oracle_liquidity,
+        } => {
+            match min_oracle_liquidity {
+                Some(min_oracle_liquidity) => {
+                    ensure!(
+                        !min_oracle_liquidity.is_zero(),
+                        ContractError::InvalidMinOracleLiquidity {}
+                    );
+                    MIN_ORACLE_LIQUIDITY.save(deps.storage, &min_oracle_liquidity)?;
+                }
+                None => MIN_ORACLE_LIQUIDITY.remove(deps.storage),
+            }
+
+            response.attributes.extend(vec![
+                attr("action", "set_min_oracle_liquidity"),
+                attr(
+                    "min_oracle_liquidity",
+                    min_oracle_liquidity.map_or("none".to_string(), |v| v.to_string()),
+                ),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::SetMaxReferralFee { bps } => {
+            ensure!(
+                bps <= MAX_REFERRAL_FEE_BPS,
+                ContractError::MaxReferralFeeOutOfBounds {}
+            );
+            MAX_REFERRAL_FEE.save(deps.storage, &bps)?;
+
+            response.attributes.extend(vec![
+                attr("action", "set_max_referral_fee"),
+                attr("max_referral_fee_bps", bps.to_string()),
+            ]);
+        }
     };
     CONFIG.save(deps.storage, &config)?;
 
@@ -905,7 +1430,22 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, Contra
 
     match contract_version.contract.as_ref() {
         "astroport-pair-concentrated" => match contract_version.version.as_ref() {
-            "4.0.0" | "4.0.1" => {}
+            "4.0.0" | "4.0.1" | "4.1.0" => {
+                // Pools which already traded must not accept seeded cumulative prices
+                let config = CONFIG.load(deps.storage)?;
+                if config
+                    .cumulative_prices
+                    .iter()
+                    .any(|(_, _, price)| !price.is_zero())
+                {
+                    HAS_SWAPS.save(deps.storage, &true)?;
+                }
+
+                // Maker fee counters start from zero. The oracle guard, fee accrual and the other
+                // optional settings stay disabled until the owner enables them
+                CUMULATIVE_MAKER_FEES.save(deps.storage, &[Uint128::zero(); 2])?;
+                ACCRUED_MAKER_FEES.save(deps.storage, &[Uint128::zero(); 2])?;
+            }
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
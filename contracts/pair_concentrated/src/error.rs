@@ -3,7 +3,9 @@ use thiserror::Error;
 
 use cw_utils::{ParseReplyError, PaymentError};
 
-use astroport::{asset::MINIMUM_LIQUIDITY_AMOUNT, pair::MAX_FEE_SHARE_BPS};
+use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
+use astroport::pair::{MAX_FEE_SHARE_BPS, MAX_REFERRAL_FEE_BPS};
+use astroport::pair_concentrated::{MAX_LOCAL_FEE_SHARE_BPS, MAX_REBALANCE_BOUNTY_BPS};
 use astroport_circular_buffer::error::BufferError;
 use astroport_pcl_common::error::PclError;
 
@@ -40,11 +42,17 @@ pub enum ContractError {
     #[error("You need to provide init params")]
     InitParamsNotFound {},
 
+    #[error("Provided amount is too small to mint LP tokens with {0} decimals")]
+    ProvideTooSmall(u8),
+
     #[error("Initial provide can not be one-sided")]
     InvalidZeroAmount {},
 
-    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
-    MinimumLiquidityAmountError {},
+    #[error(
+        "Initial liquidity must be more than {} LP tokens. Deposit more than {min_deposit} keeping the same asset ratio",
+        MINIMUM_LIQUIDITY_AMOUNT
+    )]
+    MinimumLiquidityAmountError { min_deposit: String },
 
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
@@ -67,6 +75,85 @@ pub enum ContractError {
     )]
     FeeShareOutOfBounds {},
 
+    #[error("Referral fee is 0 or exceeds maximum allowed value of {max_bps} bps")]
+    ReferralFeeOutOfBounds { max_bps: u16 },
+
+    #[error(
+        "Max referral fee exceeds maximum allowed value of {} bps",
+        MAX_REFERRAL_FEE_BPS
+    )]
+    MaxReferralFeeOutOfBounds {},
+
+    #[error("Referral address can't be the swap sender or receiver")]
+    SelfReferral {},
+
+    #[error(
+        "Local fee share is 0 or exceeds maximum allowed value of {} bps",
+        MAX_LOCAL_FEE_SHARE_BPS
+    )]
+    LocalFeeShareOutOfBounds {},
+
+    #[error("Fee multiplier must be within [0, 1]")]
+    InvalidFeeMultiplier {},
+
+    #[error("Volatility fee factor must be greater than zero")]
+    InvalidVolatilityFeeFactor {},
+
+    #[error("Swap accepts only the offer asset denom, but also received: {0}")]
+    UnexpectedSwapFunds(String),
+
+    #[error("Max oracle divergence must be greater than zero")]
+    InvalidMaxDivergence {},
+
+    #[error(
+        "Swap halted: pool price {spot_price} diverges too far from oracle price {oracle_price}"
+    )]
+    OracleDivergence {
+        spot_price: Decimal256,
+        oracle_price: Decimal256,
+    },
+
+    #[error("Swap halted: the oracle guard price is unavailable")]
+    OraclePriceUnavailable {},
+
+    #[error("Fee sweep threshold must be greater than zero")]
+    InvalidSweepThreshold {},
+
+    #[error("There are no accrued maker fees to collect")]
+    NoAccruedFees {},
+
+    #[error(
+        "Rebalance bounty must be between 0 and {} bps",
+        MAX_REBALANCE_BOUNTY_BPS
+    )]
+    RebalanceBountyOutOfBounds {},
+
+    #[error("Fee address is not set in the factory")]
+    FeeAddressNotSet {},
+
+    #[error("Max price staleness must be greater than zero")]
+    InvalidMaxPriceStaleness {},
+
+    #[error("Pool price was last updated at {last_update}. Swaps must specify both belief_price and max_spread (capped at 1%)")]
+    StalePrice { last_update: u64 },
+
+    #[error("Min oracle liquidity must be greater than zero")]
+    InvalidMinOracleLiquidity {},
+
+    #[error(
+        "Oracle price is not reliable: LP supply {total_share} is below {min_oracle_liquidity}"
+    )]
+    PriceNotReliable {
+        total_share: Uint128,
+        min_oracle_liquidity: Uint128,
+    },
+
+    #[error("Cumulative prices can be seeded only before the first swap")]
+    CumulativePricesAlreadyUsed {},
+
     #[error("Slippage is more than expected: received {0}, expected {1} LP tokens")]
     ProvideSlippageViolation(Uint128, Uint128),
+
+    #[error("Operation non supported")]
+    NonSupported {},
 }
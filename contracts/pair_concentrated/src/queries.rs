@@ -1,30 +1,45 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Decimal, Decimal256, Deps, Env, StdError, StdResult, Uint128, Uint64,
+    ensure, to_json_binary, Binary, Decimal, Decimal256, Deps, Env, StdError, StdResult, Uint128,
+    Uint64,
 };
+use cw2::get_contract_version;
 use itertools::Itertools;
 
-use astroport::asset::{Asset, AssetInfo};
+use astroport::asset::{Asset, AssetInfo, AssetInfoExt, MINIMUM_LIQUIDITY_AMOUNT};
+use astroport::common::query_is_owner;
 use astroport::cosmwasm_ext::{DecimalToInteger, IntegerToDecimal};
 use astroport::observation::query_observation;
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, DecodedConfigResponse, PoolResponse,
+    PoolSnapshotResponse, ReverseSimulationResponse, SimulationResponse,
+};
+use astroport::pair_concentrated::{
+    ConcentratedPoolConfig, EffectivePriceResponse, InternalReservesResponse,
+    OracleSmoothingResponse, PriceBandResponse, PriceFreshnessResponse, QueryMsg,
 };
-use astroport::pair_concentrated::{ConcentratedPoolConfig, QueryMsg};
 use astroport::querier::{query_factory_config, query_fee_info, query_native_supply};
-use astroport_pcl_common::state::Precisions;
+use astroport::to_decimal;
+use astroport_pcl_common::consts::{OFFER_PERCENT, TWO};
+use astroport_pcl_common::state::{Config, Precisions};
 use astroport_pcl_common::utils::{
-    accumulate_prices, before_swap_check, calc_last_prices, compute_offer_amount, compute_swap,
-    get_share_in_assets,
+    accumulate_prices, before_swap_check, calc_last_prices, compute_offer_amount_with_fee_floor,
+    compute_swap_with_fee_floor, get_share_in_assets,
 };
-use astroport_pcl_common::{calc_d, get_xcp};
+use astroport_pcl_common::{calc_d, get_xcp, half_float_pow};
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
-use crate::state::{BALANCES, CONFIG, OBSERVATIONS};
-use crate::utils::{calculate_shares, get_assets_with_precision, pool_info, query_pools};
+use crate::state::{
+    ACCRUED_MAKER_FEES, BALANCES, CONFIG, CUMULATIVE_MAKER_FEES, FEE_ACCRUAL, FEE_DISCOUNTS,
+    FEE_FREE_PROVIDE_UNTIL, LOCAL_FEE_SHARE, MAX_PRICE_STALENESS, MAX_REFERRAL_FEE,
+    MIN_ORACLE_LIQUIDITY, OBSERVATIONS, ORACLE_GUARD, REBALANCE_BOUNTY_BPS, VOLATILITY_FEE_FACTOR,
+};
+use crate::utils::{
+    calculate_shares, current_oracle_price, get_assets_with_precision, is_provide_fee_free,
+    pool_info, query_pools, volatility_fee_floor,
+};
 
 /// Exposes all the queries available in the contract.
 ///
@@ -49,6 +64,41 @@ use crate::utils::{calculate_shares, get_assets_with_precision, pool_info, query
 ///
 /// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified
 /// asset that was in the pool just preceding the moment of the specified block height creation.
+///
+/// * **QueryMsg::FeeMultiplier { address }** Returns the swap fee multiplier applied to the specified address.
+///
+/// * **QueryMsg::PriceFreshness {}** Returns the last internal price state update time and the number
+/// of seconds elapsed since then using a [`PriceFreshnessResponse`] object.
+///
+/// * **QueryMsg::MakerFees {}** Returns the total maker fees collected by the pool for each asset.
+///
+/// * **QueryMsg::AccruedMakerFees {}** Returns the maker fees accrued in the contract for each asset.
+///
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
+///
+/// * **QueryMsg::PoolSnapshot {}** Returns pool reserves, total share and fees using a
+/// [`PoolSnapshotResponse`] object.
+///
+/// * **QueryMsg::PriceBand {}** Returns the no-arbitrage price bands around the price scale at the
+/// mid and out fees using a [`PriceBandResponse`] object.
+///
+/// * **QueryMsg::OraclePrice {}** Returns the EMA oracle price advanced to the current block.
+///
+/// * **QueryMsg::DecodedConfig {}** Returns the pair configuration with already decoded pool
+/// parameters using a [`DecodedConfigResponse`] object.
+///
+/// * **QueryMsg::InternalReserves {}** Returns the internal reserves (the second one scaled by
+/// the price scale) and the invariant D computed on them using an [`InternalReservesResponse`] object.
+///
+/// * **QueryMsg::EffectivePrice { offer_asset_info, ask_asset_info }** Returns the price of a unit
+/// trade with and without the dynamic fee using an [`EffectivePriceResponse`] object.
+///
+/// * **QueryMsg::IsOwner { address }** Returns whether the specified address is the current owner.
+///
+/// * **QueryMsg::ProvideAmountsForLp { lp_amount }** Returns the balanced asset amounts which mint
+/// approximately the specified amount of LP tokens.
+///
+/// * **QueryMsg::OracleSmoothing {}** Returns the per second decay factor of the EMA oracle price.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -91,6 +141,45 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(
             &query_share(deps, lp_amount).map_err(|err| StdError::generic_err(err.to_string()))?,
         ),
+        QueryMsg::FeeMultiplier { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(
+                &FEE_DISCOUNTS
+                    .may_load(deps.storage, &address)?
+                    .unwrap_or(Decimal::one()),
+            )
+        }
+        QueryMsg::PriceFreshness {} => to_json_binary(&query_price_freshness(deps, env)?),
+        QueryMsg::MakerFees {} => to_json_binary(&query_maker_fees(deps)?),
+        QueryMsg::AccruedMakerFees {} => to_json_binary(&query_accrued_maker_fees(deps)?),
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::PoolSnapshot {} => to_json_binary(&query_pool_snapshot(deps, env)?),
+        QueryMsg::PriceBand {} => to_json_binary(&query_price_band(deps)?),
+        QueryMsg::OraclePrice {} => to_json_binary(
+            &query_oracle_price(deps, env).map_err(|err| StdError::generic_err(err.to_string()))?,
+        ),
+        QueryMsg::DecodedConfig {} => to_json_binary(&query_decoded_config(deps, env)?),
+        QueryMsg::InternalReserves {} => to_json_binary(&query_internal_reserves(deps, env)?),
+        QueryMsg::EffectivePrice {
+            offer_asset_info,
+            ask_asset_info,
+        } => to_json_binary(
+            &query_effective_price(deps, env, offer_asset_info, ask_asset_info)
+                .map_err(|err| StdError::generic_err(format!("{err}")))?,
+        ),
+        QueryMsg::IsOwner { address } => {
+            let config = CONFIG.load(deps.storage)?;
+            let owner = match config.owner {
+                Some(owner) => owner,
+                None => query_factory_config(&deps.querier, &config.factory_addr)?.owner,
+            };
+            to_json_binary(&query_is_owner(deps.api, &owner, &address)?)
+        }
+        QueryMsg::ProvideAmountsForLp { lp_amount } => to_json_binary(
+            &query_provide_amounts_for_lp(deps, env, lp_amount)
+                .map_err(|err| StdError::generic_err(format!("{err}")))?,
+        ),
+        QueryMsg::OracleSmoothing {} => to_json_binary(&query_oracle_smoothing(deps)?),
     }
 }
 
@@ -98,7 +187,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 /// tokens currently minted in an object of type [`PoolResponse`].
 fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     let config = CONFIG.load(deps.storage)?;
-    let (assets, total_share) = pool_info(deps.querier, &config)?;
+    let (assets, total_share) = pool_info(deps, &config)?;
 
     let resp = PoolResponse {
         assets,
@@ -108,6 +197,153 @@ fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     Ok(resp)
 }
 
+/// Returns pool reserves, the amount of LP tokens currently minted and the pool fees
+/// in an object of type [`PoolSnapshotResponse`]. The total fee is computed at the current pool balances.
+fn query_pool_snapshot(deps: Deps, env: Env) -> StdResult<PoolSnapshotResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, &config)?;
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+
+    Ok(PoolSnapshotResponse {
+        assets,
+        total_share,
+        total_fee: query_current_fee(deps, &env, &config)?,
+        maker_fee: fee_info.maker_fee_rate,
+    })
+}
+
+/// Returns the dynamic fee at the current pool balances. An empty pool reports the mid fee.
+/// The fee is raised to the volatility fee floor if the pool has one configured.
+fn query_current_fee(deps: Deps, env: &Env, config: &Config) -> StdResult<Decimal> {
+    let precisions = Precisions::new(deps.storage)?;
+    let mut xs = query_pools(deps, &env.contract.address, config, &precisions)
+        .map_err(|err| StdError::generic_err(err.to_string()))?
+        .into_iter()
+        .map(|asset| asset.amount)
+        .collect_vec();
+    let fee = if xs[0].is_zero() || xs[1].is_zero() {
+        config.pool_params.mid_fee.into()
+    } else {
+        xs[1] *= config.pool_state.price_state.price_scale;
+        config.pool_params.fee(&xs)
+    };
+
+    to_decimal(fee.max(volatility_fee_floor(deps.storage, config, env)?))
+}
+
+/// Returns the no-arbitrage price bands around the current price scale.
+/// Arbitrage becomes profitable only once the external price leaves
+/// \[price_scale * (1 - fee), price_scale / (1 - fee)\] where fee is the dynamic fee.
+/// The bands are computed at both fee bounds, `mid_fee` and `out_fee`.
+fn query_price_band(deps: Deps) -> StdResult<PriceBandResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let price_scale = config.pool_state.price_state.price_scale;
+    let band = |fee: Decimal| -> StdResult<(Decimal256, Decimal256)> {
+        let one_minus_fee = Decimal256::one() - Decimal256::from(fee);
+        let upper = price_scale
+            .checked_div(one_minus_fee)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        Ok((price_scale * one_minus_fee, upper))
+    };
+    let (mid_fee_lower, mid_fee_upper) = band(config.pool_params.mid_fee)?;
+    let (out_fee_lower, out_fee_upper) = band(config.pool_params.out_fee)?;
+
+    Ok(PriceBandResponse {
+        mid_fee_lower,
+        mid_fee_upper,
+        out_fee_lower,
+        out_fee_upper,
+    })
+}
+
+/// Returns the weight the previous EMA oracle price keeps after one second,
+/// i.e. the same factor the price state update applies per second of elapsed time.
+fn query_oracle_smoothing(deps: Deps) -> StdResult<OracleSmoothingResponse> {
+    let ma_half_time = CONFIG.load(deps.storage)?.pool_params.ma_half_time;
+
+    Ok(OracleSmoothingResponse {
+        decay_factor: half_float_pow(Decimal256::from_ratio(1u8, ma_half_time))?,
+        ma_half_time,
+    })
+}
+
+/// Simulates a unit trade (one whole offer token or 0.01% of the offer pool if smaller)
+/// and returns the received rate with and without the dynamic fee.
+/// Maker and share fees are part of the dynamic fee thus they don't change the effective price.
+fn query_effective_price(
+    deps: Deps,
+    env: Env,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+) -> Result<EffectivePriceResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+    let pools = query_pools(deps, &env.contract.address, &config, &precisions)?;
+
+    let (offer_ind, _) = pools
+        .iter()
+        .find_position(|asset| asset.info == offer_asset_info)
+        .ok_or_else(|| ContractError::InvalidAsset(offer_asset_info.to_string()))?;
+    let ask_ind = 1 - offer_ind;
+    if pools[ask_ind].info != ask_asset_info {
+        return Err(ContractError::InvalidAsset(ask_asset_info.to_string()));
+    }
+
+    let xs = pools.iter().map(|asset| asset.amount).collect_vec();
+    let mut offer_amount = Decimal256::one().min(xs[offer_ind] * OFFER_PERCENT);
+    if offer_amount.is_zero() {
+        offer_amount = Decimal256::raw(1u128);
+    }
+
+    before_swap_check(&pools, offer_amount)?;
+
+    let swap_result = compute_swap_with_fee_floor(
+        &xs,
+        offer_amount,
+        ask_ind,
+        &config,
+        &env,
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::one(),
+        volatility_fee_floor(deps.storage, &config, &env)?,
+    )?;
+
+    Ok(EffectivePriceResponse {
+        effective_price: swap_result.dy / offer_amount,
+        spot_price: (swap_result.dy + swap_result.total_fee) / offer_amount,
+    })
+}
+
+/// Returns the internal oracle price as it would be after applying the EMA at the current block.
+fn query_oracle_price(deps: Deps, env: Env) -> Result<Decimal256, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_oracle_liquidity(deps, &config)?;
+
+    Ok(current_oracle_price(&config, &env)?)
+}
+
+/// Ensures the pool holds enough liquidity for its prices to be served to oracles.
+fn ensure_oracle_liquidity(deps: Deps, config: &Config) -> Result<(), ContractError> {
+    if let Some(min_oracle_liquidity) = MIN_ORACLE_LIQUIDITY.may_load(deps.storage)? {
+        let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+        ensure!(
+            total_share >= min_oracle_liquidity,
+            ContractError::PriceNotReliable {
+                total_share,
+                min_oracle_liquidity,
+            }
+        );
+    }
+
+    Ok(())
+}
+
 /// Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
 /// The result is returned in a vector that contains objects of type [`Asset`].
 ///
@@ -115,12 +351,7 @@ fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
 fn query_share(deps: Deps, amount: Uint128) -> Result<Vec<Asset>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let precisions = Precisions::new(deps.storage)?;
-    let pools = query_pools(
-        deps.querier,
-        &config.pair_info.contract_addr,
-        &config,
-        &precisions,
-    )?;
+    let pools = query_pools(deps, &config.pair_info.contract_addr, &config, &precisions)?;
     let total_share =
         query_native_supply(&deps.querier, config.pair_info.liquidity_token.to_string())?;
     let refund_assets =
@@ -152,7 +383,7 @@ pub fn query_simulation(
     let offer_asset_prec = precisions.get_precision(&offer_asset.info)?;
     let offer_asset_dec = offer_asset.to_decimal_asset(offer_asset_prec)?;
 
-    let pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let pools = query_pools(deps, &env.contract.address, &config, &precisions)?;
 
     let (offer_ind, _) = pools
         .iter()
@@ -181,7 +412,7 @@ pub fn query_simulation(
         share_fee_share = Decimal256::from_ratio(fee_share.bps, 10000u16);
     }
 
-    let swap_result = compute_swap(
+    let swap_result = compute_swap_with_fee_floor(
         &xs,
         offer_asset_dec.amount,
         ask_ind,
@@ -189,6 +420,9 @@ pub fn query_simulation(
         &env,
         maker_fee_share,
         share_fee_share,
+        Decimal256::zero(),
+        Decimal256::one(),
+        volatility_fee_floor(deps.storage, &config, &env)?,
     )?;
 
     Ok(SimulationResponse {
@@ -209,7 +443,7 @@ pub fn query_reverse_simulation(
     let ask_asset_prec = precisions.get_precision(&ask_asset.info)?;
     let ask_asset_dec = ask_asset.to_decimal_asset(ask_asset_prec)?;
 
-    let pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let pools = query_pools(deps, &env.contract.address, &config, &precisions)?;
 
     let (ask_ind, _) = pools
         .iter()
@@ -219,8 +453,14 @@ pub fn query_reverse_simulation(
     let offer_asset_prec = precisions.get_precision(&pools[offer_ind].info)?;
 
     let xs = pools.iter().map(|asset| asset.amount).collect_vec();
-    let (offer_amount, spread_amount, commission_amount) =
-        compute_offer_amount(&xs, ask_asset_dec.amount, ask_ind, &config, &env)?;
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount_with_fee_floor(
+        &xs,
+        ask_asset_dec.amount,
+        ask_ind,
+        &config,
+        &env,
+        volatility_fee_floor(deps.storage, &config, &env)?,
+    )?;
 
     Ok(ReverseSimulationResponse {
         offer_amount: offer_amount.to_uint(offer_asset_prec)?,
@@ -235,15 +475,17 @@ fn query_cumulative_prices(
     env: Env,
 ) -> Result<CumulativePricesResponse, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
+    ensure_oracle_liquidity(deps, &config)?;
+
     let precisions = Precisions::new(deps.storage)?;
-    let pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let pools = query_pools(deps, &env.contract.address, &config, &precisions)?;
 
     let xs = pools.iter().map(|asset| asset.amount).collect_vec();
     let last_real_price = calc_last_prices(&xs, &config, &env)?;
 
     accumulate_prices(&env, &mut config, last_real_price);
 
-    let (assets, total_share) = pool_info(deps.querier, &config)?;
+    let (assets, total_share) = pool_info(deps, &config)?;
 
     Ok(CumulativePricesResponse {
         assets,
@@ -259,7 +501,7 @@ pub fn query_lp_price(deps: Deps, env: Env) -> StdResult<Decimal256> {
         .to_decimal256(LP_TOKEN_PRECISION)?;
     if !total_lp.is_zero() {
         let precisions = Precisions::new(deps.storage)?;
-        let mut ixs = query_pools(deps.querier, &env.contract.address, &config, &precisions)
+        let mut ixs = query_pools(deps, &env.contract.address, &config, &precisions)
             .map_err(|err| StdError::generic_err(err.to_string()))?
             .into_iter()
             .map(|asset| asset.amount)
@@ -275,59 +517,208 @@ pub fn query_lp_price(deps: Deps, env: Env) -> StdResult<Decimal256> {
     }
 }
 
+/// Returns the last internal price state update time and the seconds elapsed since then.
+pub fn query_price_freshness(deps: Deps, env: Env) -> StdResult<PriceFreshnessResponse> {
+    let last_price_update = CONFIG
+        .load(deps.storage)?
+        .pool_state
+        .price_state
+        .last_price_update;
+
+    Ok(PriceFreshnessResponse {
+        last_price_update,
+        elapsed: env.block.time.seconds().saturating_sub(last_price_update),
+    })
+}
+
+/// Returns the total maker fees collected by the pool for each asset.
+pub fn query_maker_fees(deps: Deps) -> StdResult<Vec<Asset>> {
+    let config = CONFIG.load(deps.storage)?;
+    let maker_fees = CUMULATIVE_MAKER_FEES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    Ok(config
+        .pair_info
+        .asset_infos
+        .iter()
+        .zip(maker_fees)
+        .map(|(info, amount)| info.with_balance(amount))
+        .collect())
+}
+
 /// Returns the pair contract configuration.
 pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
-    let amp_gamma = config.pool_state.get_amp_gamma(&env);
-    let dec256_price_scale = config.pool_state.price_state.price_scale;
-    let price_scale = Decimal::from_atomics(
-        Uint128::try_from(dec256_price_scale.atomics())?,
-        dec256_price_scale.decimal_places(),
-    )
-    .map_err(|e| StdError::generic_err(format!("{e}")))?;
-
     let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
 
     Ok(ConfigResponse {
         block_time_last: config.block_time_last,
-        params: Some(to_json_binary(&ConcentratedPoolConfig {
-            amp: amp_gamma.amp,
-            gamma: amp_gamma.gamma,
-            mid_fee: config.pool_params.mid_fee,
-            out_fee: config.pool_params.out_fee,
-            fee_gamma: config.pool_params.fee_gamma,
-            repeg_profit_threshold: config.pool_params.repeg_profit_threshold,
-            min_price_scale_delta: config.pool_params.min_price_scale_delta,
-            price_scale,
-            ma_half_time: config.pool_params.ma_half_time,
-            track_asset_balances: config.track_asset_balances,
-            fee_share: config.fee_share,
-        })?),
+        params: Some(to_json_binary(&pool_config(deps, &env, &config)?)?),
         owner: config.owner.unwrap_or(factory_config.owner),
         factory_addr: config.factory_addr,
         tracker_addr: config.tracker_addr,
     })
 }
 
+/// Returns the pair configuration with decoded pool parameters and fees in percents.
+fn query_decoded_config(
+    deps: Deps,
+    env: Env,
+) -> StdResult<DecodedConfigResponse<ConcentratedPoolConfig>> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+    let hundred = Decimal::from_ratio(100u8, 1u8);
+
+    Ok(DecodedConfigResponse {
+        block_time_last: config.block_time_last,
+        params: pool_config(deps, &env, &config)?,
+        total_fee_percent: query_current_fee(deps, &env, &config)? * hundred,
+        maker_fee_percent: fee_info.maker_fee_rate * hundred,
+        owner: config.owner.unwrap_or(factory_config.owner),
+        factory_addr: config.factory_addr,
+        tracker_addr: config.tracker_addr,
+    })
+}
+
+/// Returns the maker fees accrued in the contract and not yet sent to the fee address.
+pub fn query_accrued_maker_fees(deps: Deps) -> StdResult<Vec<Asset>> {
+    let config = CONFIG.load(deps.storage)?;
+    let accrued_fees = ACCRUED_MAKER_FEES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    Ok(config
+        .pair_info
+        .asset_infos
+        .iter()
+        .zip(accrued_fees)
+        .map(|(info, amount)| info.with_balance(amount))
+        .collect())
+}
+
+/// Assembles the concentrated pool parameters from the pair config.
+fn pool_config(deps: Deps, env: &Env, config: &Config) -> StdResult<ConcentratedPoolConfig> {
+    let amp_gamma = config.pool_state.get_amp_gamma(env);
+    let dec256_price_scale = config.pool_state.price_state.price_scale;
+    let price_scale = Decimal::from_atomics(
+        Uint128::try_from(dec256_price_scale.atomics())?,
+        dec256_price_scale.decimal_places(),
+    )
+    .map_err(|e| StdError::generic_err(format!("{e}")))?;
+
+    Ok(ConcentratedPoolConfig {
+        amp: amp_gamma.amp,
+        gamma: amp_gamma.gamma,
+        mid_fee: config.pool_params.mid_fee,
+        out_fee: config.pool_params.out_fee,
+        fee_gamma: config.pool_params.fee_gamma,
+        repeg_profit_threshold: config.pool_params.repeg_profit_threshold,
+        min_price_scale_delta: config.pool_params.min_price_scale_delta,
+        price_scale,
+        ma_half_time: config.pool_params.ma_half_time,
+        track_asset_balances: config.track_asset_balances,
+        fee_share: config.fee_share.clone(),
+        fee_free_provide_until: FEE_FREE_PROVIDE_UNTIL.may_load(deps.storage)?,
+        volatility_fee_factor: VOLATILITY_FEE_FACTOR.may_load(deps.storage)?,
+        local_fee_share: LOCAL_FEE_SHARE.may_load(deps.storage)?,
+        oracle_guard: ORACLE_GUARD.may_load(deps.storage)?,
+        max_price_staleness: MAX_PRICE_STALENESS.may_load(deps.storage)?,
+        fee_accrual: FEE_ACCRUAL.may_load(deps.storage)?,
+        rebalance_bounty_bps: REBALANCE_BOUNTY_BPS.may_load(deps.storage)?,
+        min_oracle_liquidity: MIN_ORACLE_LIQUIDITY.may_load(deps.storage)?,
+        max_referral_fee_bps: MAX_REFERRAL_FEE.may_load(deps.storage)?,
+    })
+}
+
 /// Compute the current pool D value.
 pub fn query_compute_d(deps: Deps, env: Env) -> StdResult<Decimal256> {
+    let InternalReservesResponse { xs, d } = query_internal_reserves(deps, env)?;
+
+    if xs[0].is_zero() || xs[1].is_zero() {
+        return Err(StdError::generic_err("Pools are empty"));
+    }
+
+    Ok(d)
+}
+
+/// Returns the internal reserves with the second one scaled by the price scale
+/// and the invariant D computed on them. D is zero if any of the reserves is empty.
+pub fn query_internal_reserves(deps: Deps, env: Env) -> StdResult<InternalReservesResponse> {
     let config = CONFIG.load(deps.storage)?;
     let precisions = Precisions::new(deps.storage)?;
 
-    let mut xs = query_pools(deps.querier, &env.contract.address, &config, &precisions)
+    let mut xs = query_pools(deps, &env.contract.address, &config, &precisions)
         .map_err(|e| StdError::generic_err(e.to_string()))?
         .into_iter()
         .map(|a| a.amount)
         .collect_vec();
 
-    if xs[0].is_zero() || xs[1].is_zero() {
-        return Err(StdError::generic_err("Pools are empty"));
-    }
-
     xs[1] *= config.pool_state.price_state.price_scale;
 
-    let amp_gamma = config.pool_state.get_amp_gamma(&env);
-    calc_d(&xs, &amp_gamma)
+    let d = if xs[0].is_zero() || xs[1].is_zero() {
+        Decimal256::zero()
+    } else {
+        calc_d(&xs, &config.pool_state.get_amp_gamma(&env))?
+    };
+
+    Ok(InternalReservesResponse { xs, d })
+}
+
+/// Returns the asset amounts which, provided balanced at the current price scale (i.e. equal in
+/// the internal representation), mint approximately `lp_amount` LP tokens. Such provides are not
+/// charged the provide fee.
+fn query_provide_amounts_for_lp(
+    deps: Deps,
+    env: Env,
+    lp_amount: Uint128,
+) -> Result<Vec<Asset>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+    let pools = query_pools(deps, &env.contract.address, &config, &precisions)?;
+    let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?
+        .to_decimal256(LP_TOKEN_PRECISION)?;
+    let lp_amount = lp_amount.to_decimal256(LP_TOKEN_PRECISION)?;
+    let price_scale = config.pool_state.price_state.price_scale;
+
+    // The internal deposit of each asset
+    let deposit = if total_share.is_zero() {
+        // The initial provide mints xcp = deposit / sqrt(price_scale) minus the minimum liquidity
+        (lp_amount + MINIMUM_LIQUIDITY_AMOUNT.to_decimal256(LP_TOKEN_PRECISION)?)
+            * price_scale.sqrt()
+    } else {
+        let amp_gamma = config.pool_state.get_amp_gamma(&env);
+        let xs = [pools[0].amount, pools[1].amount * price_scale];
+        let old_d = calc_d(&xs, &amp_gamma)?;
+
+        // D grows almost linearly with small balanced deposits thus a few refinements
+        // of the initial estimate are enough
+        let mut deposit = lp_amount * old_d / (total_share * TWO);
+        for _ in 0..3 {
+            let new_d = calc_d(&[xs[0] + deposit, xs[1] + deposit], &amp_gamma)?;
+            let share = (total_share * new_d / old_d).saturating_sub(total_share);
+            if share.is_zero() {
+                break;
+            }
+            deposit = deposit * lp_amount / share;
+        }
+
+        deposit
+    };
+
+    pools
+        .into_iter()
+        .zip([deposit, deposit / price_scale])
+        .map(|(pool, amount)| {
+            let prec = precisions.get_precision(&pool.info)?;
+            Ok(pool.info.with_balance(amount.to_uint(prec)?))
+        })
+        .collect()
 }
 
 /// Returns the balance of the specified asset that was in the pool
@@ -354,7 +745,7 @@ pub fn query_simulate_provide(
 
     let precisions = Precisions::new(deps.storage)?;
 
-    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)
+    let mut pools = query_pools(deps, &env.contract.address, &config, &precisions)
         .map_err(|e| StdError::generic_err(e.to_string()))?;
 
     let deposits =
@@ -368,6 +759,7 @@ pub fn query_simulate_provide(
         total_share,
         deposits.clone(),
         slippage_tolerance,
+        is_provide_fee_free(deps.storage, &env)?,
     )
     .map_err(|e| StdError::generic_err(e.to_string()))?;
 
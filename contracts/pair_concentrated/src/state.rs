@@ -1,15 +1,62 @@
-use cosmwasm_std::Uint128;
-use cw_storage_plus::{Item, SnapshotMap};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotMap};
 
 use astroport::asset::AssetInfo;
 use astroport::common::OwnershipProposal;
 use astroport::observation::Observation;
+use astroport::pair::FeeShareConfig;
+use astroport::pair_concentrated::{FeeAccrualConfig, OracleGuardConfig};
 use astroport_circular_buffer::CircularBuffer;
 use astroport_pcl_common::state::Config;
 
 /// Stores pool parameters and state.
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Stores the timestamp (in seconds) until which provides are not charged the provide fee
+pub const FEE_FREE_PROVIDE_UNTIL: Item<u64> = Item::new("fee_free_provide_until");
+
+/// The factor of the volatility-linked swap fee floor. The floor is disabled if not set
+pub const VOLATILITY_FEE_FACTOR: Item<Decimal> = Item::new("volatility_fee_factor");
+
+/// The share of the maker fee diverted to a local recipient. The whole maker fee goes
+/// to the factory fee address if not set
+pub const LOCAL_FEE_SHARE: Item<FeeShareConfig> = Item::new("local_fee_share");
+
+/// The oracle divergence check applied to swaps. Swaps are not checked if not set
+pub const ORACLE_GUARD: Item<OracleGuardConfig> = Item::new("oracle_guard");
+
+/// The maximum age (in seconds) of the internal oracle price before swaps require
+/// an explicit belief price and max spread. The price age is not checked if not set
+pub const MAX_PRICE_STALENESS: Item<u64> = Item::new("max_price_staleness");
+
+/// Stores swap fee multipliers for discounted swap senders
+pub const FEE_DISCOUNTS: Map<&Addr, Decimal> = Map::new("fee_discounts");
+
+/// Stores the total maker fees collected by the pool for each asset (in pool asset order)
+pub const CUMULATIVE_MAKER_FEES: Item<[Uint128; 2]> = Item::new("cumulative_maker_fees");
+
+/// Maker fee accrual settings. Maker fees are sent to the fee address on every swap if not set
+pub const FEE_ACCRUAL: Item<FeeAccrualConfig> = Item::new("fee_accrual");
+
+/// Stores maker fees kept in the contract until they are swept to the fee address (in pool asset order).
+/// These funds are not part of the pool liquidity
+pub const ACCRUED_MAKER_FEES: Item<[Uint128; 2]> = Item::new("accrued_maker_fees");
+
+/// The share of the accrued maker fees (in bps) paid to the caller of a rebalance which repegs the pool.
+/// No bounty is paid if not set
+pub const REBALANCE_BOUNTY_BPS: Item<u16> = Item::new("rebalance_bounty_bps");
+
+/// The minimum LP token supply required to serve oracle prices. Prices are always served if not set
+pub const MIN_ORACLE_LIQUIDITY: Item<Uint128> = Item::new("min_oracle_liquidity");
+
+/// The maximum referral fee (in bps of the swap fee) accepted by swaps.
+/// [`astroport::pair::MAX_REFERRAL_FEE_BPS`] applies if not set
+pub const MAX_REFERRAL_FEE: Item<u16> = Item::new("max_referral_fee");
+
+/// Set once the pool has processed its first swap.
+/// Migrated pools with non-zero cumulative prices are marked as having swaps.
+pub const HAS_SWAPS: Item<bool> = Item::new("has_swaps");
+
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
@@ -1,15 +1,17 @@
 use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
 use astroport_pcl_common::utils::{
-    assert_slippage_tolerance, calc_provide_fee, check_assets, check_pair_registered,
+    assert_slippage_tolerance, calc_last_prices, calc_provide_fee, check_assets,
+    check_pair_registered,
 };
-use astroport_pcl_common::{calc_d, get_xcp};
+use astroport_pcl_common::{calc_d, get_xcp, half_float_pow};
 use cosmwasm_std::{
-    Addr, Decimal, Decimal256, Deps, Env, QuerierWrapper, StdError, StdResult, Storage, Uint128,
+    Addr, Decimal, Decimal256, Deps, Env, StdError, StdResult, Storage, Uint128, Uint256,
 };
 
-use astroport::asset::{Asset, Decimal256Ext, DecimalAsset, MINIMUM_LIQUIDITY_AMOUNT};
+use astroport::asset::{Asset, AssetInfo, Decimal256Ext, DecimalAsset, MINIMUM_LIQUIDITY_AMOUNT};
 use astroport::observation::{safe_sma_buffer_not_full, safe_sma_calculation};
 use astroport::observation::{Observation, PrecommitObservation};
+use astroport::oracle;
 use astroport::pair::MIN_TRADE_SIZE;
 use astroport::querier::query_native_supply;
 use astroport_circular_buffer::error::BufferResult;
@@ -19,32 +21,36 @@ use itertools::Itertools;
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
-use crate::state::OBSERVATIONS;
+use crate::state::{
+    ACCRUED_MAKER_FEES, FEE_FREE_PROVIDE_UNTIL, OBSERVATIONS, ORACLE_GUARD, VOLATILITY_FEE_FACTOR,
+};
 
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
-pub(crate) fn pool_info(
-    querier: QuerierWrapper,
-    config: &Config,
-) -> StdResult<(Vec<Asset>, Uint128)> {
-    let pools = config
+/// Maker fees accrued in the contract are not part of the pool.
+pub(crate) fn pool_info(deps: Deps, config: &Config) -> StdResult<(Vec<Asset>, Uint128)> {
+    let mut pools = config
         .pair_info
-        .query_pools(&querier, &config.pair_info.contract_addr)?;
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+    exclude_accrued_maker_fees(deps.storage, &mut pools)?;
 
-    let total_share = query_native_supply(&querier, config.pair_info.liquidity_token.to_string())?;
+    let total_share =
+        query_native_supply(&deps.querier, config.pair_info.liquidity_token.to_string())?;
 
     Ok((pools, total_share))
 }
 
 /// Returns current pool's volumes where amount is in [`Decimal256`] form.
+/// Maker fees accrued in the contract are not part of the pool.
 pub(crate) fn query_pools(
-    querier: QuerierWrapper,
+    deps: Deps,
     addr: &Addr,
     config: &Config,
     precisions: &Precisions,
 ) -> Result<Vec<DecimalAsset>, ContractError> {
-    config
-        .pair_info
-        .query_pools(&querier, addr)?
+    let mut pools = config.pair_info.query_pools(&deps.querier, addr)?;
+    exclude_accrued_maker_fees(deps.storage, &mut pools)?;
+
+    pools
         .into_iter()
         .map(|asset| {
             asset
@@ -54,6 +60,17 @@ pub(crate) fn query_pools(
         .collect()
 }
 
+/// Subtracts maker fees accrued in the contract from the contract balances (in pool asset order).
+fn exclude_accrued_maker_fees(storage: &dyn Storage, pools: &mut [Asset]) -> StdResult<()> {
+    if let Some(accrued_fees) = ACCRUED_MAKER_FEES.may_load(storage)? {
+        for (pool, fee) in pools.iter_mut().zip(accrued_fees) {
+            pool.amount = pool.amount.checked_sub(fee)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Calculate and save price moving average
 pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResult<()> {
     if let Some(PrecommitObservation {
@@ -167,6 +184,57 @@ pub(crate) fn get_assets_with_precision(
     ])
 }
 
+/// Returns whether provides are currently exempt from the provide fee.
+pub(crate) fn is_provide_fee_free(storage: &dyn Storage, env: &Env) -> StdResult<bool> {
+    Ok(FEE_FREE_PROVIDE_UNTIL
+        .may_load(storage)?
+        .map(|until| env.block.time.seconds() < until)
+        .unwrap_or_default())
+}
+
+/// Returns the internal oracle price as it would be after applying the EMA at the current block.
+/// The stored value is only refreshed on the next swap, provide or withdraw, hence we advance
+/// it here in the same way as [`astroport_pcl_common::state::PoolState::update_price`] does.
+pub(crate) fn current_oracle_price(config: &Config, env: &Env) -> StdResult<Decimal256> {
+    let price_state = &config.pool_state.price_state;
+    let block_time = env.block.time.seconds();
+
+    if price_state.last_price_update < block_time {
+        let arg = Decimal256::from_ratio(
+            block_time - price_state.last_price_update,
+            config.pool_params.ma_half_time,
+        );
+        let alpha = half_float_pow(arg)?;
+        Ok(price_state.last_price * (Decimal256::one() - alpha) + price_state.oracle_price * alpha)
+    } else {
+        Ok(price_state.oracle_price)
+    }
+}
+
+/// Returns the volatility-linked swap fee floor:
+/// min(out_fee, factor * |last_price - oracle_price| / oracle_price).
+/// The floor is zero if the pool has no volatility fee factor set.
+pub(crate) fn volatility_fee_floor(
+    storage: &dyn Storage,
+    config: &Config,
+    env: &Env,
+) -> StdResult<Decimal256> {
+    if let Some(factor) = VOLATILITY_FEE_FACTOR.may_load(storage)? {
+        let oracle_price = current_oracle_price(config, env)?;
+        let divergence = config
+            .pool_state
+            .price_state
+            .last_price
+            .diff(oracle_price)
+            .checked_div(oracle_price)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+        Ok((divergence * Decimal256::from(factor)).min(config.pool_params.out_fee.into()))
+    } else {
+        Ok(Decimal256::zero())
+    }
+}
+
 pub(crate) fn calculate_shares(
     env: &Env,
     config: &mut Config,
@@ -174,6 +242,7 @@ pub(crate) fn calculate_shares(
     total_share: Decimal256,
     deposits: Vec<Decimal256>,
     slippage_tolerance: Option<Decimal>,
+    fee_free: bool,
 ) -> Result<(Uint128, Decimal256), ContractError> {
     // Initial provide can not be one-sided
     if total_share.is_zero() && (deposits[0].is_zero() || deposits[1].is_zero()) {
@@ -192,14 +261,26 @@ pub(crate) fn calculate_shares(
 
     let share = if total_share.is_zero() {
         let xcp = get_xcp(new_d, config.pool_state.price_state.price_scale);
-        let mint_amount = xcp
-            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT.to_decimal256(LP_TOKEN_PRECISION)?)
-            .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
+        let min_liquidity = MINIMUM_LIQUIDITY_AMOUNT.to_decimal256(LP_TOKEN_PRECISION)?;
 
         // share cannot become zero after minimum liquidity subtraction
-        if mint_amount.is_zero() {
-            return Err(ContractError::MinimumLiquidityAmountError {});
+        if xcp <= min_liquidity {
+            // xcp scales linearly with deposits thus we can derive the deposit which
+            // would mint exactly MINIMUM_LIQUIDITY_AMOUNT
+            let scale = min_liquidity.checked_div(xcp).map_err(|_| {
+                ContractError::MinimumLiquidityAmountError {
+                    min_deposit: "a non-dust amount of both assets".to_string(),
+                }
+            })?;
+            let min_deposit = pools
+                .iter()
+                .zip(&deposits)
+                .map(|(pool, deposit)| format!("{} {}", *deposit * scale, pool.info))
+                .join(", ");
+
+            return Err(ContractError::MinimumLiquidityAmountError { min_deposit });
         }
+        let mint_amount = xcp - min_liquidity;
 
         config.pool_state.price_state.xcp_profit_real = Decimal256::one();
         config.pool_state.price_state.xcp_profit = Decimal256::one();
@@ -214,7 +295,11 @@ pub(crate) fn calculate_shares(
         let mut ideposits = deposits.clone();
         ideposits[1] *= config.pool_state.price_state.price_scale;
 
-        share * (Decimal256::one() - calc_provide_fee(&ideposits, &new_xp, &config.pool_params))
+        if fee_free {
+            share
+        } else {
+            share * (Decimal256::one() - calc_provide_fee(&ideposits, &new_xp, &config.pool_params))
+        }
     };
 
     // calculate accrued share
@@ -252,6 +337,76 @@ pub(crate) fn calculate_shares(
     Ok((share.to_uint(LP_TOKEN_PRECISION)?, slippage))
 }
 
+/// Queries the oracle TWAP of the second pool asset denominated in the first one.
+fn query_guard_oracle_price(
+    deps: Deps,
+    oracle: &Addr,
+    config: &Config,
+    precisions: &Precisions,
+) -> StdResult<Decimal256> {
+    let asset_infos = &config.pair_info.asset_infos;
+    let base_precision = precisions.get_precision(&asset_infos[1])?;
+    let quote_precision = precisions.get_precision(&asset_infos[0])?;
+
+    // The oracle returns the amount of the first asset for one unit of the second asset
+    let prices: Vec<(AssetInfo, Uint256)> = deps.querier.query_wasm_smart(
+        oracle,
+        &oracle::QueryMsg::Consult {
+            token: asset_infos[1].clone(),
+            amount: Uint128::new(10u128.pow(base_precision.into())),
+        },
+    )?;
+    let quote_amount = prices
+        .into_iter()
+        .find_map(|(info, amount)| info.equal(&asset_infos[0]).then_some(amount))
+        .ok_or_else(|| StdError::generic_err("Oracle doesn't provide the pair price"))?;
+
+    Decimal256::with_precision(quote_amount, quote_precision)
+}
+
+/// Halts a swap if the pool spot price after the trade diverges from the oracle TWAP
+/// by more than the configured threshold and the trade doesn't bring the spot price closer
+/// to the oracle price. Does nothing if the oracle guard is not enabled.
+///
+/// The guard fails closed: if the oracle can't be queried or returns a zero price,
+/// swaps are halted until the oracle recovers or the guard is disabled.
+///
+/// * **old_spot_price** is the pool spot price before the swap.
+///
+/// * **xs** are the pool reserves after the swap.
+pub(crate) fn assert_oracle_divergence(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    precisions: &Precisions,
+    old_spot_price: Decimal256,
+    xs: &[Decimal256],
+) -> Result<(), ContractError> {
+    let guard = if let Some(guard) = ORACLE_GUARD.may_load(deps.storage)? {
+        guard
+    } else {
+        return Ok(());
+    };
+
+    let oracle_price = match query_guard_oracle_price(deps, &guard.oracle, config, precisions) {
+        Ok(oracle_price) if !oracle_price.is_zero() => oracle_price,
+        _ => return Err(ContractError::OraclePriceUnavailable {}),
+    };
+
+    let spot_price = calc_last_prices(xs, config, env)?;
+    let divergence = spot_price.diff(oracle_price) / oracle_price;
+    let old_divergence = old_spot_price.diff(oracle_price) / oracle_price;
+    // Swaps moving the spot price towards the oracle price are always allowed
+    if divergence > Decimal256::from(guard.max_divergence) && divergence >= old_divergence {
+        return Err(ContractError::OracleDivergence {
+            spot_price,
+            oracle_price,
+        });
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fmt::Display;
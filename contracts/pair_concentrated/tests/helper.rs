@@ -18,11 +18,13 @@ use astroport::asset::{native_asset_info, token_asset_info, Asset, AssetInfo, Pa
 use astroport::factory::{PairConfig, PairType};
 use astroport::observation::OracleObservation;
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, PoolResponse,
-    ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, DecodedConfigResponse, ExecuteMsg,
+    PoolResponse, PoolSnapshotResponse, Referral, ReverseSimulationResponse, SimulationResponse,
 };
 use astroport::pair_concentrated::{
-    ConcentratedPoolConfig, ConcentratedPoolParams, ConcentratedPoolUpdateParams, QueryMsg,
+    ConcentratedPoolConfig, ConcentratedPoolParams, ConcentratedPoolUpdateParams,
+    EffectivePriceResponse, InternalReservesResponse, OracleSmoothingResponse, PriceBandResponse,
+    PriceFreshnessResponse, QueryMsg,
 };
 use astroport_pair_concentrated::contract::{execute, instantiate, reply};
 use astroport_pair_concentrated::queries::query;
@@ -50,6 +52,8 @@ pub fn common_pcl_params() -> ConcentratedPoolParams {
         ma_half_time: 600,
         track_asset_balances: None,
         fee_share: None,
+        fee_free_provide_until: None,
+        volatility_fee_factor: None,
     }
 }
 
@@ -84,7 +88,7 @@ fn token_contract() -> Box<dyn Contract<Empty>> {
     ))
 }
 
-fn pair_contract() -> Box<dyn Contract<Empty>> {
+pub fn pair_contract() -> Box<dyn Contract<Empty>> {
     Box::new(ContractWrapper::new_with_empty(execute, instantiate, query).with_reply_empty(reply))
 }
 
@@ -406,7 +410,7 @@ impl Helper {
         offer_asset: &Asset,
         max_spread: Option<Decimal>,
     ) -> AnyResult<AppResponse> {
-        self.swap_full_params(sender, offer_asset, max_spread, None)
+        self.swap_full_params(sender, offer_asset, max_spread, None, false)
     }
 
     pub fn swap_full_params(
@@ -415,6 +419,7 @@ impl Helper {
         offer_asset: &Asset,
         max_spread: Option<Decimal>,
         belief_price: Option<Decimal>,
+        strict_belief_price: bool,
     ) -> AnyResult<AppResponse> {
         match &offer_asset.info {
             AssetInfo::Token { contract_addr } => {
@@ -426,6 +431,8 @@ impl Helper {
                         belief_price,
                         max_spread,
                         to: None,
+                        strict_belief_price,
+                        referral: None,
                     })
                     .unwrap(),
                 };
@@ -447,6 +454,8 @@ impl Helper {
                     belief_price,
                     max_spread,
                     to: None,
+                    strict_belief_price,
+                    referral: None,
                 };
 
                 self.app
@@ -455,6 +464,29 @@ impl Helper {
         }
     }
 
+    pub fn swap_with_referral(
+        &mut self,
+        sender: &Addr,
+        offer_asset: &Asset,
+        referral: Option<Referral>,
+    ) -> AnyResult<AppResponse> {
+        let funds =
+            offer_asset.mock_coin_sent(&mut self.app, sender, &self.pair_addr, SendType::None);
+
+        let msg = ExecuteMsg::Swap {
+            offer_asset: offer_asset.clone(),
+            ask_asset_info: None,
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            strict_belief_price: false,
+            referral,
+        };
+
+        self.app
+            .execute_contract(sender.clone(), self.pair_addr.clone(), &msg, &funds)
+    }
+
     pub fn query_incentives_deposit(&self, denom: impl Into<String>, user: &Addr) -> Uint128 {
         self.app
             .wrap()
@@ -588,12 +620,98 @@ impl Helper {
             .query_wasm_smart(&self.pair_addr, &QueryMsg::Pool {})
     }
 
+    pub fn query_pool_snapshot(&self) -> StdResult<PoolSnapshotResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::PoolSnapshot {})
+    }
+
+    pub fn query_price_band(&self) -> StdResult<PriceBandResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::PriceBand {})
+    }
+
+    pub fn query_oracle_smoothing(&self) -> StdResult<OracleSmoothingResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::OracleSmoothing {})
+    }
+
+    pub fn query_effective_price(
+        &self,
+        offer_asset: &TestCoin,
+        ask_asset: &TestCoin,
+    ) -> StdResult<EffectivePriceResponse> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::EffectivePrice {
+                offer_asset_info: self.assets[offer_asset].clone(),
+                ask_asset_info: self.assets[ask_asset].clone(),
+            },
+        )
+    }
+
+    pub fn query_provide_amounts_for_lp(&self, lp_amount: u128) -> StdResult<Vec<Asset>> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::ProvideAmountsForLp {
+                lp_amount: lp_amount.into(),
+            },
+        )
+    }
+
+    pub fn query_internal_reserves(&self) -> StdResult<InternalReservesResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::InternalReserves {})
+    }
+
+    pub fn query_oracle_price(&self) -> StdResult<Decimal256> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::OraclePrice {})
+    }
+
+    pub fn query_decoded_config(&self) -> StdResult<DecodedConfigResponse<ConcentratedPoolConfig>> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::DecodedConfig {})
+    }
+
     pub fn query_lp_price(&self) -> StdResult<Decimal256> {
         self.app
             .wrap()
             .query_wasm_smart(&self.pair_addr, &QueryMsg::LpPrice {})
     }
 
+    pub fn query_fee_multiplier(&self, address: &Addr) -> StdResult<Decimal> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::FeeMultiplier {
+                address: address.to_string(),
+            },
+        )
+    }
+
+    pub fn query_price_freshness(&self) -> StdResult<PriceFreshnessResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::PriceFreshness {})
+    }
+
+    pub fn query_maker_fees(&self) -> StdResult<Vec<Asset>> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::MakerFees {})
+    }
+
+    pub fn query_accrued_maker_fees(&self) -> StdResult<Vec<Asset>> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::AccruedMakerFees {})
+    }
+
     pub fn query_asset_balance_at(
         &self,
         asset_info: &AssetInfo,
@@ -623,6 +741,37 @@ impl Helper {
         )
     }
 
+    pub fn seed_cumulative_prices(
+        &mut self,
+        sender: &Addr,
+        prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.pair_addr.clone(),
+            &ExecuteMsg::SeedCumulativePrices { prices },
+            &[],
+        )
+    }
+
+    pub fn rebalance(&mut self, sender: &Addr) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.pair_addr.clone(),
+            &ExecuteMsg::Rebalance {},
+            &[],
+        )
+    }
+
+    pub fn query_is_owner(&self, address: &Addr) -> StdResult<bool> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::IsOwner {
+                address: address.to_string(),
+            },
+        )
+    }
+
     pub fn query_amp_gamma(&self) -> StdResult<AmpGammaResponse> {
         let config_resp: ConfigResponse = self
             .app
@@ -2,30 +2,44 @@
 
 use std::str::FromStr;
 
-use cosmwasm_std::{Addr, Coin, Decimal, Decimal256, StdError, Uint128};
+use cosmwasm_std::{
+    from_json, to_json_binary, to_json_vec, Addr, Binary, Coin, Decimal, Decimal256, Deps, DepsMut,
+    Empty, Env, MessageInfo, Response, StdError, StdResult, Uint128, Uint256,
+};
 use itertools::{max, Itertools};
 
 use astroport::asset::{
-    native_asset_info, Asset, AssetInfo, AssetInfoExt, MINIMUM_LIQUIDITY_AMOUNT,
+    native_asset_info, Asset, AssetInfo, AssetInfoExt, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
 use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
+use astroport::factory::{ExecuteMsg as FactoryExecuteMsg, PairType, QueryMsg as FactoryQueryMsg};
 use astroport::observation::OracleObservation;
-use astroport::pair::{ExecuteMsg, PoolResponse, MAX_FEE_SHARE_BPS};
+use astroport::pair::{
+    ConfigResponse, ExecuteMsg, PoolResponse, Referral, MAX_FEE_SHARE_BPS, MAX_REFERRAL_FEE_BPS,
+};
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, PromoteParams, QueryMsg, UpdatePoolParams,
+    ConcentratedPoolConfig, ConcentratedPoolParams, ConcentratedPoolUpdateParams, FeeAccrualConfig,
+    OracleGuardConfig, PromoteParams, QueryMsg, UpdatePoolParams, MAX_LOCAL_FEE_SHARE_BPS,
+    MAX_REBALANCE_BOUNTY_BPS,
 };
 use astroport::tokenfactory_tracker::{
     ConfigResponse as TrackerConfigResponse, QueryMsg as TrackerQueryMsg,
 };
+use astroport_factory::error::ContractError as FactoryError;
 use astroport_pair_concentrated::error::ContractError;
-use astroport_pcl_common::consts::{AMP_MAX, AMP_MIN, MA_HALF_TIME_LIMITS};
+use astroport_pcl_common::calc_d;
+use astroport_pcl_common::consts::{
+    AMP_MAX, AMP_MIN, MA_HALF_TIME_LIMITS, MIN_AMP_CHANGING_TIME, REPEG_PROFIT_THRESHOLD_MAX,
+    REPEG_PROFIT_THRESHOLD_MIN,
+};
 use astroport_pcl_common::error::PclError;
+use astroport_pcl_common::state::AmpGamma;
 
 use astroport_test::coins::TestCoin;
 use astroport_test::convert::{dec_to_f64, f64_to_dec};
-use astroport_test::cw_multi_test::{Executor, TOKEN_FACTORY_MODULE};
+use astroport_test::cw_multi_test::{ContractWrapper, Executor, TOKEN_FACTORY_MODULE};
 
-use crate::helper::{common_pcl_params, AppExtension, Helper};
+use crate::helper::{common_pcl_params, pair_contract, AppExtension, Helper};
 
 mod helper;
 
@@ -163,6 +177,43 @@ fn check_wrong_initialization() {
     .unwrap();
 }
 
+#[test]
+fn check_duplicate_assets_initialization() {
+    let owner = Addr::unchecked("owner");
+
+    let mut helper = Helper::new(
+        &owner,
+        vec![TestCoin::native("uluna"), TestCoin::native("uusdc")],
+        common_pcl_params(),
+    )
+    .unwrap();
+
+    // Instantiate the pair directly to bypass the factory checks
+    let pair_code_id = helper.app.store_code(pair_contract());
+    let err = helper
+        .app
+        .instantiate_contract(
+            pair_code_id,
+            owner.clone(),
+            &astroport::pair::InstantiateMsg {
+                pair_type: PairType::Custom("concentrated".to_string()),
+                asset_infos: vec![native_asset_info("uluna".to_string()); 2],
+                token_code_id: 0,
+                factory_addr: helper.factory.to_string(),
+                init_params: Some(to_json_binary(&common_pcl_params()).unwrap()),
+            },
+            &[],
+            "duplicate pair",
+            None,
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        ContractError::PclError(PclError::DoublingAssets {}),
+        err.downcast().unwrap(),
+    );
+}
+
 #[test]
 fn check_create_pair_with_unsupported_denom() {
     let owner = Addr::unchecked("owner");
@@ -277,10 +328,22 @@ fn provide_and_withdraw() {
             ],
         )
         .unwrap_err();
-    assert_eq!(
-        ContractError::MinimumLiquidityAmountError {},
-        err.downcast().unwrap()
+    let err_msg = err.root_cause().to_string();
+    assert!(
+        err_msg
+            .starts_with("Initial liquidity must be more than 1000 LP tokens. Deposit more than "),
+        "{err_msg}"
+    );
+    // The error names the minimum deposit for both pool assets
+    assert!(err_msg.contains(" uluna, "), "{err_msg}");
+    assert!(
+        err_msg.contains(&format!(" {}", helper.assets[&test_coins[1]])),
+        "{err_msg}"
     );
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::MinimumLiquidityAmountError { .. }
+    ));
 
     // This is normal provision
     helper.provide_liquidity(&user1, &assets).unwrap();
@@ -444,6 +507,84 @@ fn check_imbalanced_provide() {
     assert_eq!(0, helper.coin_balance(&test_coins[1], &user1));
 }
 
+#[test]
+fn check_fee_free_provide_window() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut fee_helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let fee_free_provide_until = fee_helper.app.block_info().time.seconds() + 3600;
+    let params = ConcentratedPoolParams {
+        fee_free_provide_until: Some(fee_free_provide_until),
+        ..common_pcl_params()
+    };
+    let mut free_helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let config: ConfigResponse = free_helper
+        .app
+        .wrap()
+        .query_wasm_smart(&free_helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let pool_config: ConcentratedPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(
+        pool_config.fee_free_provide_until,
+        Some(fee_free_provide_until)
+    );
+
+    let user1 = Addr::unchecked("user1");
+    for helper in [&mut fee_helper, &mut free_helper] {
+        let assets = vec![
+            helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+            helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        ];
+        helper.give_me_money(&assets, &user1);
+        helper.provide_liquidity(&user1, &assets).unwrap();
+    }
+
+    let simulate = |helper: &Helper, assets: &[Asset]| -> Uint128 {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(
+                helper.pair_addr.to_string(),
+                &QueryMsg::SimulateProvide {
+                    assets: assets.to_vec(),
+                    slippage_tolerance: None,
+                },
+            )
+            .unwrap()
+    };
+
+    let imbalanced = vec![
+        fee_helper.assets[&test_coins[0]].with_balance(10_000_000000u128),
+        fee_helper.assets[&test_coins[1]].with_balance(1_000_000000u128),
+    ];
+
+    // Imbalanced provides are not charged the provide fee before the deadline
+    let with_fee = simulate(&fee_helper, &imbalanced);
+    let fee_free = simulate(&free_helper, &imbalanced);
+    assert!(fee_free > with_fee, "{fee_free} <= {with_fee}");
+
+    fee_helper.app.next_block(3600);
+    free_helper.app.next_block(3600);
+
+    // Once the window is over the normal provide fee applies
+    let with_fee = simulate(&fee_helper, &imbalanced);
+    assert_eq!(simulate(&free_helper, &imbalanced), with_fee);
+
+    let user2 = Addr::unchecked("user2");
+    for helper in [&mut fee_helper, &mut free_helper] {
+        helper.give_me_money(&imbalanced, &user2);
+        helper.provide_liquidity(&user2, &imbalanced).unwrap();
+        assert_eq!(
+            helper.native_balance(&helper.lp_token, &user2),
+            with_fee.u128()
+        );
+    }
+}
+
 #[test]
 fn provide_with_different_precision() {
     let owner = Addr::unchecked("owner");
@@ -633,946 +774,1085 @@ fn check_reverse_swap() {
 }
 
 #[test]
-fn check_swaps_simple() {
+fn check_referral_fee() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+    let init_helper = || {
+        let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+        let assets = vec![
+            helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+            helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        ];
+        helper.provide_liquidity(&owner, &assets).unwrap();
+        helper
+    };
 
     let user = Addr::unchecked("user");
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user);
+    let referrer = Addr::unchecked("referrer");
+    let offer_asset = test_coins[0].clone();
+
+    // Swap without referral
+    let mut helper = init_helper();
+    let offer = helper.assets[&offer_asset].with_balance(100_000000u128);
+    helper.give_me_money(&[offer.clone()], &user);
+    helper.swap_with_referral(&user, &offer, None).unwrap();
+    let return_no_referral = helper.coin_balance(&test_coins[1], &user);
+    let maker_fee_no_referral = helper.coin_balance(&test_coins[1], &helper.fake_maker);
+    assert_eq!(helper.coin_balance(&test_coins[1], &referrer), 0);
+
+    // The same swap with a referral
+    let mut helper = init_helper();
+    let offer = helper.assets[&offer_asset].with_balance(100_000000u128);
+    helper.give_me_money(&[offer.clone()], &user);
+    let sim = helper.simulate_swap(&offer, None).unwrap();
+
+    for fee_bps in [0, MAX_REFERRAL_FEE_BPS + 1] {
+        let err = helper
+            .swap_with_referral(
+                &user,
+                &offer,
+                Some(Referral {
+                    address: referrer.to_string(),
+                    fee_bps,
+                }),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ReferralFeeOutOfBounds {
+                max_bps: MAX_REFERRAL_FEE_BPS
+            },
+            err.downcast().unwrap()
+        );
+    }
 
-    // Check swap does not work if pool is empty
-    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: One of the pools is empty"
-    );
+    // The trader can't refer themselves
+    let err = helper
+        .swap_with_referral(
+            &user,
+            &offer,
+            Some(Referral {
+                address: user.to_string(),
+                fee_bps: 1000,
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::SelfReferral {}, err.downcast().unwrap());
 
-    // Try to swap a wrong asset
-    let wrong_coin = native_asset_info("random-coin".to_string());
-    let wrong_asset = wrong_coin.with_balance(100_000000u128);
-    helper.give_me_money(&[wrong_asset.clone()], &user);
-    let err = helper.swap(&user, &wrong_asset, None).unwrap_err();
+    // The owner can lower the referral fee cap
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMaxReferralFee {
+                bps: MAX_REFERRAL_FEE_BPS + 1,
+            },
+        )
+        .unwrap_err();
     assert_eq!(
-        ContractError::InvalidAsset(wrong_coin.to_string()),
+        ContractError::MaxReferralFeeOutOfBounds {},
         err.downcast().unwrap()
     );
-
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
-    ];
-    helper.provide_liquidity(&owner, &assets).unwrap();
-
-    // trying to swap cw20 without calling Cw20::Send method
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMaxReferralFee { bps: 500 },
+        )
+        .unwrap();
+    assert_eq!(
+        helper
+            .query_decoded_config()
+            .unwrap()
+            .params
+            .max_referral_fee_bps,
+        Some(500)
+    );
     let err = helper
-        .app
-        .execute_contract(
-            owner.clone(),
-            helper.pair_addr.clone(),
-            &ExecuteMsg::Swap {
-                offer_asset: helper.assets[&test_coins[1]].with_balance(1u8),
-                ask_asset_info: None,
-                belief_price: None,
-                max_spread: None,
-                to: None,
-            },
-            &[],
+        .swap_with_referral(
+            &user,
+            &offer,
+            Some(Referral {
+                address: referrer.to_string(),
+                fee_bps: 1000,
+            }),
         )
         .unwrap_err();
-    assert_eq!(ContractError::Cw20DirectSwap {}, err.downcast().unwrap());
-
-    let d = helper.query_d().unwrap();
-    assert_eq!(dec_to_f64(d), 200000f64);
-
-    assert_eq!(0, helper.coin_balance(&test_coins[1], &user));
-    helper.swap(&user, &offer_asset, None).unwrap();
-    assert_eq!(0, helper.coin_balance(&test_coins[0], &user));
-    assert_eq!(99_737929, helper.coin_balance(&test_coins[1], &user));
-
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(90_000_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user);
-    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
     assert_eq!(
-        ContractError::PclError(PclError::MaxSpreadAssertion {}),
+        ContractError::ReferralFeeOutOfBounds { max_bps: 500 },
         err.downcast().unwrap()
     );
-
-    let user2 = Addr::unchecked("user2");
-    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user2);
-    helper.swap(&user2, &offer_asset, None).unwrap();
-    assert_eq!(0, helper.coin_balance(&test_coins[1], &user2));
-    assert_eq!(99_741246, helper.coin_balance(&test_coins[0], &user2));
-
-    let d = helper.query_d().unwrap();
-    assert_eq!(dec_to_f64(d), 200000.260415);
-
-    let price1 = helper.observe_price(0).unwrap();
-    helper.app.next_block(10);
-    // Swapping the lowest amount possible which results in positive return amount
     helper
-        .swap(
-            &user,
-            &helper.assets[&test_coins[1]].with_balance(2u128),
-            None,
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMaxReferralFee {
+                bps: MAX_REFERRAL_FEE_BPS,
+            },
         )
         .unwrap();
-    let price2 = helper.observe_price(0).unwrap();
-    // With such a small swap size contract doesn't store observation
-    assert_eq!(price1, price2);
 
-    helper.app.next_block(10);
-    // Swap the smallest possible amount which gets observation saved
     helper
-        .swap(
+        .swap_with_referral(
             &user,
-            &helper.assets[&test_coins[1]].with_balance(1005u128),
-            None,
+            &offer,
+            Some(Referral {
+                address: referrer.to_string(),
+                fee_bps: 1000,
+            }),
         )
         .unwrap();
-    let price3 = helper.observe_price(0).unwrap();
-    // Prove that price didn't jump that much
-    let diff = price3.diff(price2);
+
+    // The trader receives the same amount; the referral is paid from the LP/maker part
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &user),
+        return_no_referral
+    );
+    let referral_fee = helper.coin_balance(&test_coins[1], &referrer);
+    let expected_referral_fee = sim.commission_amount.u128() / 10;
     assert!(
-        diff / price2 < f64_to_dec(0.005),
-        "price jumped from {price2} to {price3} which is more than 0.5%"
+        referral_fee.abs_diff(expected_referral_fee) <= 1,
+        "{referral_fee} != {expected_referral_fee}"
+    );
+    let maker_fee = helper.coin_balance(&test_coins[1], &helper.fake_maker);
+    let expected_maker_fee = maker_fee_no_referral * 9 / 10;
+    assert!(
+        maker_fee.abs_diff(expected_maker_fee) <= 1,
+        "{maker_fee} != {expected_maker_fee}"
     );
 }
 
 #[test]
-fn check_swaps_with_price_update() {
+fn check_fee_discounts() {
     let owner = Addr::unchecked("owner");
-    let half = Decimal::from_ratio(1u8, 2u8);
 
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
     let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    helper.app.next_block(1000);
-
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
         helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
 
-    helper.app.next_block(1000);
+    let discounted = Addr::unchecked("discounted");
+    let regular = Addr::unchecked("regular");
 
-    let user1 = Addr::unchecked("user1");
-    let offer_asset = helper.assets[&test_coins[1]].with_balance(10_000_000000u128);
-    let mut prev_vlp_price = helper.query_lp_price().unwrap();
+    let set_discount = ConcentratedPoolUpdateParams::SetFeeDiscount {
+        address: discounted.to_string(),
+        multiplier: Decimal::percent(50),
+    };
+    let err = helper.update_config(&regular, &set_discount).unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
 
-    for i in 0..4 {
-        helper.give_me_money(&[offer_asset.clone()], &user1);
-        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
-        let new_vlp_price = helper.query_lp_price().unwrap();
-        assert!(
-            new_vlp_price >= prev_vlp_price,
-            "{i}: new_vlp_price <= prev_vlp_price ({new_vlp_price} <= {prev_vlp_price})",
-        );
-        prev_vlp_price = new_vlp_price;
-        helper.app.next_block(1000);
-    }
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetFeeDiscount {
+                address: discounted.to_string(),
+                multiplier: Decimal::percent(110),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidFeeMultiplier {},
+        err.downcast().unwrap()
+    );
 
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(10_000_000000u128);
-    for _i in 0..4 {
-        helper.give_me_money(&[offer_asset.clone()], &user1);
-        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
-        helper.app.next_block(1000);
-    }
+    helper.update_config(&owner, &set_discount).unwrap();
+    assert_eq!(
+        helper.query_fee_multiplier(&discounted).unwrap(),
+        Decimal::percent(50)
+    );
+    assert_eq!(
+        helper.query_fee_multiplier(&regular).unwrap(),
+        Decimal::one()
+    );
+
+    // Discounted address pays half of the fee
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    let sim = helper.simulate_swap(&offer_asset, None).unwrap();
+    helper.give_me_money(&[offer_asset.clone()], &discounted);
+    helper.swap(&discounted, &offer_asset, None).unwrap();
+    let received = helper.coin_balance(&test_coins[1], &discounted);
+    assert!(received > sim.return_amount.u128());
+    let expected = (sim.return_amount + sim.commission_amount / Uint128::new(2)).u128();
+    assert!(received.abs_diff(expected) <= 1, "{received} != {expected}");
+
+    // Other addresses pay the full fee
+    let sim = helper.simulate_swap(&offer_asset, None).unwrap();
+    helper.give_me_money(&[offer_asset.clone()], &regular);
+    helper.swap(&regular, &offer_asset, None).unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &regular),
+        sim.return_amount.u128()
+    );
+
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::RemoveFeeDiscount {
+                address: discounted.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        helper.query_fee_multiplier(&discounted).unwrap(),
+        Decimal::one()
+    );
 }
 
 #[test]
-fn provides_and_swaps() {
+fn check_volatility_fee_floor() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
-
-    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    helper.app.next_block(1000);
+    let mut plain_helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
-    ];
-    helper.provide_liquidity(&owner, &assets).unwrap();
+    let params = ConcentratedPoolParams {
+        volatility_fee_factor: Some(Decimal::one()),
+        ..common_pcl_params()
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
 
-    helper.app.next_block(1000);
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let pool_config: ConcentratedPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(pool_config.volatility_fee_factor, Some(Decimal::one()));
 
     let user = Addr::unchecked("user");
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user);
-    helper.swap(&user, &offer_asset, None).unwrap();
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(10_000_000000u128);
+    for helper in [&mut plain_helper, &mut helper] {
+        let assets = vec![
+            helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+            helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        ];
+        helper.provide_liquidity(&owner, &assets).unwrap();
+        helper.app.next_block(1000);
+    }
 
-    let provider = Addr::unchecked("provider");
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(1_000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(1_000_000000u128),
-    ];
-    helper.give_me_money(&assets, &provider);
-    helper.provide_liquidity(&provider, &assets).unwrap();
+    // No divergence between the last and the oracle prices yet
+    assert_eq!(
+        helper.query_pool_snapshot().unwrap().total_fee,
+        plain_helper.query_pool_snapshot().unwrap().total_fee
+    );
 
-    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user);
-    helper.swap(&user, &offer_asset, None).unwrap();
+    // A large swap moves the last price away from the oracle price
+    for helper in [&mut plain_helper, &mut helper] {
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+    }
+
+    let out_fee = common_pcl_params().out_fee;
+    let plain_fee = plain_helper.query_pool_snapshot().unwrap().total_fee;
+    let volatile_fee = helper.query_pool_snapshot().unwrap().total_fee;
+    assert!(volatile_fee > plain_fee, "{volatile_fee} <= {plain_fee}");
+    assert!(volatile_fee <= out_fee, "{volatile_fee} > {out_fee}");
+
+    let plain_sim = plain_helper.simulate_swap(&offer_asset, None).unwrap();
+    let volatile_sim = helper.simulate_swap(&offer_asset, None).unwrap();
+    assert!(volatile_sim.commission_amount > plain_sim.commission_amount);
+    assert!(volatile_sim.return_amount < plain_sim.return_amount);
+
+    // The floor decays as the oracle price catches up with the last price
+    plain_helper.app.next_block(6000);
+    helper.app.next_block(6000);
+    assert_eq!(
+        helper.query_pool_snapshot().unwrap().total_fee,
+        plain_helper.query_pool_snapshot().unwrap().total_fee
+    );
+
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetVolatilityFeeFactor {
+                factor: Some(Decimal::zero()),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidVolatilityFeeFactor {},
+        err.downcast().unwrap()
+    );
 
     helper
-        .withdraw_liquidity(&provider, 999_999354, vec![])
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetVolatilityFeeFactor { factor: None },
+        )
+        .unwrap();
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
         .unwrap();
+    let pool_config: ConcentratedPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(pool_config.volatility_fee_factor, None);
+}
+
+#[test]
+fn check_price_freshness() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let freshness = helper.query_price_freshness().unwrap();
+    let created_at = helper.app.block_info().time.seconds();
+    assert_eq!(freshness.last_price_update, created_at);
+    assert_eq!(freshness.elapsed, 0);
+
+    helper.app.next_block(1000);
+    assert_eq!(helper.query_price_freshness().unwrap().elapsed, 1000);
+
+    helper.app.next_block(500);
+    let freshness = helper.query_price_freshness().unwrap();
+    assert_eq!(freshness.last_price_update, created_at);
+    assert_eq!(freshness.elapsed, 1500);
+
+    // Swap updates internal price state
+    let user = Addr::unchecked("user");
     let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
     helper.give_me_money(&[offer_asset.clone()], &user);
     helper.swap(&user, &offer_asset, None).unwrap();
+
+    let freshness = helper.query_price_freshness().unwrap();
+    assert_eq!(freshness.last_price_update, created_at + 1500);
+    assert_eq!(freshness.elapsed, 0);
 }
 
 #[test]
-fn check_amp_gamma_change() {
+fn check_cumulative_maker_fees() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
-
-    let params = ConcentratedPoolParams {
-        amp: f64_to_dec(40f64),
-        gamma: f64_to_dec(0.0001),
-        ..common_pcl_params()
-    };
-    let mut helper = Helper::new(&owner, test_coins, params).unwrap();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let random_user = Addr::unchecked("random");
-    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
-        mid_fee: Some(f64_to_dec(0.002)),
-        out_fee: None,
-        fee_gamma: None,
-        repeg_profit_threshold: None,
-        min_price_scale_delta: None,
-        ma_half_time: None,
-    });
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    let err = helper.update_config(&random_user, &action).unwrap_err();
-    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
-    helper.update_config(&owner, &action).unwrap();
+    let maker_fees = helper.query_maker_fees().unwrap();
+    assert_eq!(
+        maker_fees,
+        vec![
+            helper.assets[&test_coins[0]].with_balance(0u128),
+            helper.assets[&test_coins[1]].with_balance(0u128),
+        ]
+    );
 
-    helper.app.next_block(86400);
+    let user = Addr::unchecked("user");
+    for (i, amount) in [(0, 100_000000u128), (1, 50_000000), (0, 30_000000)] {
+        let offer_asset = helper.assets[&test_coins[i]].with_balance(amount);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+
+        let maker_fees = helper.query_maker_fees().unwrap();
+        for (coin, fee) in test_coins.iter().zip(maker_fees) {
+            assert_eq!(
+                fee.amount.u128(),
+                helper.coin_balance(coin, &helper.fake_maker)
+            );
+        }
+    }
 
-    let future_time = helper.app.block_info().time.seconds() + 100_000;
-    let target_amp = 44f64;
-    let target_gamma = 0.00009;
-    let action = ConcentratedPoolUpdateParams::Promote(PromoteParams {
-        next_amp: f64_to_dec(target_amp),
-        next_gamma: f64_to_dec(target_gamma),
-        future_time,
-    });
-    helper.update_config(&owner, &action).unwrap();
+    let maker_fees = helper.query_maker_fees().unwrap();
+    assert!(!maker_fees[0].amount.is_zero());
+    assert!(!maker_fees[1].amount.is_zero());
+}
 
-    let amp_gamma = helper.query_amp_gamma().unwrap();
-    assert_eq!(dec_to_f64(amp_gamma.amp), 40f64);
-    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.0001);
-    assert_eq!(amp_gamma.future_time, future_time);
+#[test]
+fn check_seed_cumulative_prices() {
+    let owner = Addr::unchecked("owner");
 
-    helper.app.next_block(50_000);
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let amp_gamma = helper.query_amp_gamma().unwrap();
-    assert_eq!(dec_to_f64(amp_gamma.amp), 42f64);
-    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.000095);
-    assert_eq!(amp_gamma.future_time, future_time);
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    helper.app.next_block(50_000);
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
-    let amp_gamma = helper.query_amp_gamma().unwrap();
-    assert_eq!(dec_to_f64(amp_gamma.amp), target_amp);
-    assert_eq!(dec_to_f64(amp_gamma.gamma), target_gamma);
-    assert_eq!(amp_gamma.future_time, future_time);
+    let luna = helper.assets[&test_coins[0]].clone();
+    let usd = helper.assets[&test_coins[1]].clone();
+    let seed = vec![
+        (luna.clone(), usd.clone(), Uint128::new(5_000_000000)),
+        (usd.clone(), luna.clone(), Uint128::new(4_000_000000)),
+    ];
 
-    // change values back
-    let future_time = helper.app.block_info().time.seconds() + 100_000;
-    let action = ConcentratedPoolUpdateParams::Promote(PromoteParams {
-        next_amp: f64_to_dec(40f64),
-        next_gamma: f64_to_dec(0.000099),
-        future_time,
-    });
-    helper.update_config(&owner, &action).unwrap();
+    let err = helper
+        .seed_cumulative_prices(&Addr::unchecked("random"), seed.clone())
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
 
-    helper.app.next_block(50_000);
+    helper.seed_cumulative_prices(&owner, seed.clone()).unwrap();
 
-    let amp_gamma = helper.query_amp_gamma().unwrap();
-    assert_eq!(dec_to_f64(amp_gamma.amp), 42f64);
-    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.0000945);
-    assert_eq!(amp_gamma.future_time, future_time);
+    // The first oracle read reflects the seed
+    let prices = helper.query_prices().unwrap();
+    assert_eq!(
+        prices.cumulative_prices,
+        vec![
+            (luna.clone(), usd.clone(), Uint128::new(5_000_000000)),
+            (usd.clone(), luna.clone(), Uint128::new(4_000_000000)),
+        ]
+    );
 
-    // stop changing amp and gamma thus fixing current values
-    let action = ConcentratedPoolUpdateParams::StopChangingAmpGamma {};
-    helper.update_config(&owner, &action).unwrap();
-    let amp_gamma = helper.query_amp_gamma().unwrap();
-    let last_change_time = helper.app.block_info().time.seconds();
-    assert_eq!(amp_gamma.future_time, last_change_time);
+    // Prices keep accumulating on top of the seed
+    helper.app.next_block(1000);
+    let prices = helper.query_prices().unwrap();
+    assert!(prices.cumulative_prices[0].2 > Uint128::new(5_000_000000));
+    assert!(prices.cumulative_prices[1].2 > Uint128::new(4_000_000000));
 
-    helper.app.next_block(50_000);
+    let user = Addr::unchecked("user");
+    let offer_asset = luna.with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
 
-    let amp_gamma = helper.query_amp_gamma().unwrap();
-    assert_eq!(dec_to_f64(amp_gamma.amp), 42f64);
-    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.0000945);
-    assert_eq!(amp_gamma.future_time, last_change_time);
+    let err = helper.seed_cumulative_prices(&owner, seed).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CumulativePricesAlreadyUsed {}
+    );
 }
 
 #[test]
-fn check_prices() {
+fn check_pool_snapshot() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
-
-    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
-    helper.app.next_block(50_000);
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let check_prices = |helper: &Helper| {
-        let prices = helper.query_prices().unwrap();
+    let params = common_pcl_params();
+    let mut helper = Helper::new(&owner, test_coins.clone(), params.clone()).unwrap();
 
-        test_coins
-            .iter()
-            .cartesian_product(test_coins.iter())
-            .filter(|(a, b)| a != b)
-            .for_each(|(from_coin, to_coin)| {
-                let price = prices
-                    .cumulative_prices
-                    .iter()
-                    .filter(|(from, to, _)| {
-                        from.eq(&helper.assets[from_coin]) && to.eq(&helper.assets[to_coin])
-                    })
-                    .collect::<Vec<_>>();
-                assert_eq!(price.len(), 1);
-                assert!(!price[0].2.is_zero());
-            });
-    };
+    // Empty pool reports the mid fee
+    let snapshot = helper.query_pool_snapshot().unwrap();
+    assert_eq!(snapshot.total_share, Uint128::zero());
+    assert_eq!(snapshot.total_fee, params.mid_fee);
+    assert_eq!(snapshot.maker_fee, Decimal::percent(50));
 
     let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(100_000_000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(100_000_000_000000u128),
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
-    check_prices(&helper);
 
-    helper.app.next_block(1000);
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(10_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
 
-    let user1 = Addr::unchecked("user1");
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user1);
+    let snapshot = helper.query_pool_snapshot().unwrap();
+    let pool = helper.query_pool().unwrap();
+    assert_eq!(snapshot.assets, pool.assets);
+    assert_eq!(snapshot.total_share, pool.total_share);
+    assert!(!snapshot.total_share.is_zero());
+    assert!(snapshot.total_fee >= params.mid_fee && snapshot.total_fee <= params.out_fee);
+    assert_eq!(snapshot.maker_fee, Decimal::percent(50));
+}
 
-    helper.swap(&user1, &offer_asset, None).unwrap();
-    check_prices(&helper);
+#[test]
+fn check_price_band() {
+    let owner = Addr::unchecked("owner");
 
-    helper.app.next_block(86400);
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
     let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(100_000000u128),
-        helper.assets[&test_coins[1]].with_balance(100_000000u128),
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
-    helper.give_me_money(&assets, &user1);
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
-    helper.provide_liquidity(&user1, &assets).unwrap();
-    check_prices(&helper);
+    let band = helper.query_price_band().unwrap();
+    let params = common_pcl_params();
+    let mid_fee = Decimal256::from(params.mid_fee);
+    let out_fee = Decimal256::from(params.out_fee);
+    assert_eq!(band.mid_fee_lower, Decimal256::one() - mid_fee);
+    assert_eq!(
+        band.mid_fee_upper,
+        Decimal256::one() / (Decimal256::one() - mid_fee)
+    );
+    assert_eq!(band.out_fee_lower, Decimal256::one() - out_fee);
+    assert_eq!(
+        band.out_fee_upper,
+        Decimal256::one() / (Decimal256::one() - out_fee)
+    );
+    assert!(band.mid_fee_lower < Decimal256::one() && band.mid_fee_upper > Decimal256::one());
+    // The out fee band contains the mid fee band
+    assert!(band.out_fee_lower < band.mid_fee_lower);
+    assert!(band.out_fee_upper > band.mid_fee_upper);
 
-    helper.app.next_block(14 * 86400);
+    // Higher fees widen the band
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.005)),
+        out_fee: Some(f64_to_dec(0.01)),
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
+    helper.update_config(&owner, &action).unwrap();
 
-    let offer_asset = helper.assets[&test_coins[1]].with_balance(10_000_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user1);
-    helper.swap(&user1, &offer_asset, None).unwrap();
-    check_prices(&helper);
+    let wider_band = helper.query_price_band().unwrap();
+    assert!(wider_band.mid_fee_lower < band.mid_fee_lower);
+    assert!(wider_band.mid_fee_upper > band.mid_fee_upper);
+    assert!(wider_band.out_fee_lower < band.out_fee_lower);
+    assert!(wider_band.out_fee_upper > band.out_fee_upper);
 }
 
 #[test]
-fn update_owner() {
+fn check_oracle_smoothing() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
-
-    let mut helper = Helper::new(&owner, test_coins, common_pcl_params()).unwrap();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let new_owner = String::from("new_owner");
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    // New owner
-    let msg = ExecuteMsg::ProposeNewOwner {
-        owner: new_owner.clone(),
-        expires_in: 100, // seconds
-    };
+    let smoothing = helper.query_oracle_smoothing().unwrap();
+    assert_eq!(smoothing.ma_half_time, common_pcl_params().ma_half_time);
+    assert!(smoothing.decay_factor < Decimal256::one());
+    assert!(!smoothing.decay_factor.is_zero());
 
-    // Unauthorized check
-    let err = helper
-        .app
-        .execute_contract(
-            Addr::unchecked("not_owner"),
-            helper.pair_addr.clone(),
-            &msg,
-            &[],
-        )
-        .unwrap_err();
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    // A larger half-time yields a decay factor closer to one, i.e. slower smoothing
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: None,
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: Some(smoothing.ma_half_time * 10),
+    });
+    helper.update_config(&owner, &action).unwrap();
 
-    // Claim before proposal
-    let err = helper
-        .app
-        .execute_contract(
-            Addr::unchecked(new_owner.clone()),
-            helper.pair_addr.clone(),
-            &ExecuteMsg::ClaimOwnership {},
-            &[],
-        )
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Ownership proposal not found"
-    );
+    let slower = helper.query_oracle_smoothing().unwrap();
+    assert_eq!(slower.ma_half_time, smoothing.ma_half_time * 10);
+    assert!(slower.decay_factor > smoothing.decay_factor);
+    assert!(slower.decay_factor < Decimal256::one());
+}
 
-    // Propose new owner
-    helper
-        .app
-        .execute_contract(
-            Addr::unchecked(&helper.owner),
-            helper.pair_addr.clone(),
-            &msg,
-            &[],
-        )
-        .unwrap();
+#[test]
+fn check_effective_price() {
+    let owner = Addr::unchecked("owner");
 
-    // Claim from invalid addr
-    let err = helper
-        .app
-        .execute_contract(
-            Addr::unchecked("invalid_addr"),
-            helper.pair_addr.clone(),
-            &ExecuteMsg::ClaimOwnership {},
-            &[],
-        )
-        .unwrap_err();
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    // Drop ownership proposal
-    let err = helper
-        .app
-        .execute_contract(
-            Addr::unchecked("invalid_addr"),
-            helper.pair_addr.clone(),
-            &ExecuteMsg::DropOwnershipProposal {},
-            &[],
-        )
-        .unwrap_err();
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    helper
-        .app
-        .execute_contract(
-            helper.owner.clone(),
-            helper.pair_addr.clone(),
-            &ExecuteMsg::DropOwnershipProposal {},
-            &[],
-        )
-        .unwrap();
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
-    // Propose new owner
-    helper
-        .app
-        .execute_contract(
-            Addr::unchecked(&helper.owner),
-            helper.pair_addr.clone(),
-            &msg,
-            &[],
-        )
-        .unwrap();
+    let fee = Decimal256::from(helper.query_pool_snapshot().unwrap().total_fee);
+    let tolerance = Decimal256::from_ratio(1u8, 1_000_000u32);
 
-    // Claim ownership
-    helper
-        .app
-        .execute_contract(
-            Addr::unchecked(new_owner.clone()),
-            helper.pair_addr.clone(),
-            &ExecuteMsg::ClaimOwnership {},
-            &[],
-        )
-        .unwrap();
+    for (offer, ask) in [(0, 1), (1, 0)] {
+        let price = helper
+            .query_effective_price(&test_coins[offer], &test_coins[ask])
+            .unwrap();
+        assert!(price.effective_price < price.spot_price);
+        assert!(price.spot_price.abs_diff(Decimal256::one()) < f64_to_dec(0.0001));
 
-    let config = helper.query_config().unwrap();
-    assert_eq!(config.owner.unwrap().to_string(), new_owner)
+        // The effective price is worse than the spot price by the fee
+        let price_fee = Decimal256::one() - price.effective_price / price.spot_price;
+        assert!(price_fee.abs_diff(fee) < tolerance);
+    }
+
+    let err = helper
+        .query_effective_price(&test_coins[0], &test_coins[0])
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("does not belong to the pair"),
+        "{err}"
+    );
 }
 
 #[test]
-fn query_d_test() {
+fn check_provide_amounts_for_lp() {
     let owner = Addr::unchecked("owner");
-    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
 
-    // create pair with test_coins
-    let helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    // query current pool D value before providing any liquidity
-    let err = helper.query_d().unwrap_err();
-    assert_eq!(
-        err.to_string(),
-        "Generic error: Querier contract error: Generic error: Pools are empty"
-    );
+    let params = ConcentratedPoolParams {
+        price_scale: Decimal::from_ratio(2u8, 1u8),
+        ..common_pcl_params()
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assert_minted = |minted: u128, target: u128| {
+        assert!(
+            minted.abs_diff(target) * 1000 <= target,
+            "minted {minted} LP while {target} LP was expected"
+        );
+    };
+
+    // Initial provide
+    let target = 100_000_000000u128;
+    let assets = helper.query_provide_amounts_for_lp(target).unwrap();
+    assert_eq!(assets[0].amount, assets[1].amount * Uint128::new(2));
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    assert_minted(helper.native_balance(&helper.lp_token, &owner), target);
+
+    // Move the pool away from the balanced state
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(10_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    for target in [1_000000u128, 10_000_000000] {
+        let provider = Addr::unchecked(format!("provider{target}"));
+        let assets = helper.query_provide_amounts_for_lp(target).unwrap();
+        helper.give_me_money(&assets, &provider);
+        helper.provide_liquidity(&provider, &assets).unwrap();
+        assert_minted(helper.native_balance(&helper.lp_token, &provider), target);
+    }
 }
 
 #[test]
-fn asset_balances_tracking_with_in_params() {
+fn check_internal_reserves() {
     let owner = Addr::unchecked("owner");
+
     let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
     let params = ConcentratedPoolParams {
-        track_asset_balances: Some(true),
+        price_scale: Decimal::from_ratio(2u8, 1u8),
         ..common_pcl_params()
     };
-
-    // Instantiate pair without asset balances tracking
     let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
 
+    let reserves = helper.query_internal_reserves().unwrap();
+    assert_eq!(reserves.xs, vec![Decimal256::zero(); 2]);
+    assert_eq!(reserves.d, Decimal256::zero());
+
     let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(5_000000u128),
-        helper.assets[&test_coins[1]].with_balance(5_000000u128),
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
     ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
-    // Check that asset balances were not tracked before instantiation
-    // The query AssetBalanceAt returns None for this case
-    let res = helper
-        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
-        .unwrap();
-    assert!(res.is_none());
-
-    let res = helper
-        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
-        .unwrap();
-    assert!(res.is_none());
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
 
-    // Check that asset balances were not tracked before instantiation
-    // The query AssetBalanceAt returns None for this case
-    let res = helper
-        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
-        .unwrap();
-    assert!(res.is_none());
+    let reserves = helper.query_internal_reserves().unwrap();
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    let expected_d = calc_d(
+        &reserves.xs,
+        &AmpGamma {
+            amp: amp_gamma.amp,
+            gamma: amp_gamma.gamma,
+        },
+    )
+    .unwrap();
+    assert_eq!(reserves.d, expected_d);
+    assert_eq!(reserves.d, helper.query_d().unwrap());
 
-    let res = helper
-        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
-        .unwrap();
-    assert!(res.is_none());
+    let price_scale = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .price_scale;
+    let pool = helper.query_pool().unwrap();
+    let uusd_reserve = pool.assets[1].amount.to_decimal256(6u8).unwrap();
+    assert_eq!(reserves.xs[1], uusd_reserve * price_scale);
+}
 
-    // Check that asset balances had zero balances before next block upon instantiation
-    helper.app.update_block(|b| b.height += 1);
+#[test]
+fn check_update_params_bundle_is_atomic() {
+    let owner = Addr::unchecked("owner");
 
-    let res = helper
-        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
-        .unwrap();
-    assert!(res.unwrap().is_zero());
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let res = helper
-        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
-        .unwrap();
-    assert!(res.unwrap().is_zero());
+    let mut helper = Helper::new(&owner, test_coins, common_pcl_params()).unwrap();
+    let initial_params = helper.query_config().unwrap().pool_params;
 
-    // Provide liquidity
-    helper
-        .provide_liquidity(
-            &owner,
-            &[
-                assets[0].info.with_balance(999_000000u128),
-                assets[1].info.with_balance(1000_000000u128),
-            ],
-        )
-        .unwrap();
+    // All fields are valid except repeg_profit_threshold which exceeds its upper bound
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.003)),
+        out_fee: Some(f64_to_dec(0.005)),
+        fee_gamma: Some(f64_to_dec(0.0003)),
+        repeg_profit_threshold: Some(f64_to_dec(0.02)),
+        min_price_scale_delta: Some(f64_to_dec(0.0002)),
+        ma_half_time: Some(300),
+    });
+    let err = helper.update_config(&owner, &action).unwrap_err();
+    assert_eq!(
+        ContractError::PclError(PclError::IncorrectPoolParam(
+            "repeg_profit_threshold".to_string(),
+            REPEG_PROFIT_THRESHOLD_MIN.to_string(),
+            REPEG_PROFIT_THRESHOLD_MAX.to_string()
+        )),
+        err.downcast().unwrap()
+    );
+    assert_eq!(helper.query_config().unwrap().pool_params, initial_params);
 
+    // mid_fee alone can't be raised above the current out_fee
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.005)),
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
+    let err = helper.update_config(&owner, &action).unwrap_err();
     assert_eq!(
-        helper.native_balance(&helper.lp_token, &owner),
-        999_498998u128
+        err.root_cause().to_string(),
+        "Generic error: out_fee 0.0045 must be more than mid_fee 0.005"
     );
+    assert_eq!(helper.query_config().unwrap().pool_params, initial_params);
 
-    // Check that asset balances changed after providing liquidity
-    helper.app.update_block(|b| b.height += 1);
-    let res = helper
-        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(999_000000));
+    // Fields are validated against the resulting bundle regardless of their order
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.005)),
+        out_fee: Some(f64_to_dec(0.008)),
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
+    helper.update_config(&owner, &action).unwrap();
+    let pool_params = helper.query_config().unwrap().pool_params;
+    assert_eq!(pool_params.mid_fee, f64_to_dec(0.005));
+    assert_eq!(pool_params.out_fee, f64_to_dec(0.008));
+}
 
-    let res = helper
-        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(1000_000000));
+#[test]
+fn check_oracle_price_smoothing() {
+    let owner = Addr::unchecked("owner");
 
-    // Swap
-    helper
-        .swap(
-            &owner,
-            &Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_owned(),
-                },
-                amount: Uint128::new(1_000000),
-            },
-            None,
-        )
-        .unwrap();
-
-    // Check that asset balances changed after swapping
-    helper.app.update_block(|b| b.height += 1);
-    let res = helper
-        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(998_001335));
-
-    let res = helper
-        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(1001_000000));
-
-    // Withdraw liquidity
-    helper
-        .withdraw_liquidity(&owner, 500_000000, vec![])
-        .unwrap();
-
-    // Check that asset balances changed after withdrawing
-    helper.app.update_block(|b| b.height += 1);
-    let res = helper
-        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(498_751043));
-
-    let res = helper
-        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(500_249625));
-}
-
-#[test]
-fn provides_and_swaps_and_withdraw() {
-    let owner = Addr::unchecked("owner");
-    let half = Decimal::from_ratio(1u8, 2u8);
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
-
-    let params = ConcentratedPoolParams {
-        price_scale: Decimal::from_ratio(1u8, 2u8),
-        ..common_pcl_params()
-    };
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    helper.app.next_block(1000);
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(200_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
-
-    // swap uluna
-    let user = Addr::unchecked("user");
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user);
-    helper.swap(&user, &offer_asset, Some(half)).unwrap();
-
     helper.app.next_block(1000);
 
-    // swap usdc
-    let offer_asset = helper.assets[&test_coins[1]].with_balance(1000_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user);
-    helper.swap(&user, &offer_asset, Some(half)).unwrap();
-
-    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
-    helper.give_me_money(&[offer_asset.clone()], &user);
-    helper.swap(&user, &offer_asset, Some(half)).unwrap();
+    let initial_oracle_price = helper.query_oracle_price().unwrap();
+    assert_eq!(initial_oracle_price, Decimal256::one());
 
-    // swap uluna
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(10_000_000000u128);
     helper.give_me_money(&[offer_asset.clone()], &user);
-    helper.swap(&user, &offer_asset, Some(half)).unwrap();
-    let res: PoolResponse = helper
-        .app
-        .wrap()
-        .query_wasm_smart(helper.pair_addr.to_string(), &QueryMsg::Pool {})
-        .unwrap();
-
-    assert_eq!(res.total_share.u128(), 141_421_356_237u128);
-    let owner_balance = helper.native_balance(&helper.lp_token, &owner);
-
-    helper
-        .withdraw_liquidity(&owner, owner_balance, vec![])
-        .unwrap();
-    let res: PoolResponse = helper
-        .app
-        .wrap()
-        .query_wasm_smart(helper.pair_addr.to_string(), &QueryMsg::Pool {})
-        .unwrap();
-
-    assert_eq!(res.total_share.u128(), 1000u128);
-}
-
-#[test]
-fn provide_liquidity_with_autostaking_to_generator() {
-    let owner = Addr::unchecked("owner");
-
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
-
-    let params = ConcentratedPoolParams {
-        price_scale: Decimal::from_ratio(1u8, 2u8),
-        ..common_pcl_params()
-    };
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
-
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(100_000u128),
-        helper.assets[&test_coins[1]].with_balance(100_000u128),
-    ];
-
-    helper
-        .provide_liquidity_with_auto_staking(&owner, &assets, None)
-        .unwrap();
+    helper.swap(&user, &offer_asset, None).unwrap();
 
-    let amount = helper.query_incentives_deposit(helper.lp_token.to_string(), &owner);
-    assert_eq!(amount, Uint128::new(99003));
+    let last_price = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .last_price;
+    let price_jump = last_price.diff(initial_oracle_price);
+    assert!(price_jump > Decimal256::percent(1));
+
+    // The oracle price doesn't react to the sudden swap within the same block
+    assert_eq!(helper.query_oracle_price().unwrap(), initial_oracle_price);
+
+    // After half of ma_half_time it only partially follows the last price
+    helper.app.next_block(300);
+    let oracle_price = helper.query_oracle_price().unwrap();
+    let moved = oracle_price.diff(initial_oracle_price);
+    let remaining = oracle_price.diff(last_price);
+    assert!(!moved.is_zero() && moved + remaining == price_jump);
+    assert!(remaining > moved);
+
+    // Eventually it converges to the last price
+    helper.app.next_block(10 * 600);
+    let oracle_price = helper.query_oracle_price().unwrap();
+    assert!(oracle_price.diff(last_price) < Decimal256::from_str("0.001").unwrap());
 }
 
 #[test]
-fn provide_withdraw_provide() {
+fn check_decoded_config() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
-
-    let params = ConcentratedPoolParams {
-        amp: f64_to_dec(10f64),
-        price_scale: Decimal::from_ratio(10u8, 1u8),
-        ..common_pcl_params()
-    };
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
     let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(10_938039u128),
-        helper.assets[&test_coins[1]].with_balance(1_093804u128),
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
-    helper.app.next_block(90);
-    helper.provide_liquidity(&owner, &assets).unwrap();
-
-    helper.app.next_block(90);
-    let uusd = helper.assets[&test_coins[0]].with_balance(5_000000u128);
-    helper.swap(&owner, &uusd, Some(f64_to_dec(0.5))).unwrap();
-
-    helper.app.next_block(600);
-    // Withdraw all
-    let lp_amount = helper.native_balance(&helper.lp_token, &owner);
-    helper
-        .withdraw_liquidity(&owner, lp_amount, vec![])
-        .unwrap();
 
-    // Provide again
-    helper
-        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
         .unwrap();
+    let params: ConcentratedPoolConfig = from_json(config.params.unwrap()).unwrap();
+
+    let decoded = helper.query_decoded_config().unwrap();
+    assert_eq!(decoded.params, params);
+    assert_eq!(decoded.block_time_last, config.block_time_last);
+    assert_eq!(decoded.owner, config.owner);
+    assert_eq!(decoded.factory_addr, config.factory_addr);
+    assert_eq!(decoded.tracker_addr, config.tracker_addr);
+
+    // Fees are reported in percents
+    let snapshot = helper.query_pool_snapshot().unwrap();
+    let hundred = Decimal::from_ratio(100u8, 1u8);
+    assert_eq!(decoded.total_fee_percent, snapshot.total_fee * hundred);
+    assert_eq!(decoded.maker_fee_percent, Decimal::percent(50) * hundred);
 }
 
 #[test]
-fn provide_withdraw_slippage() {
+fn check_strict_belief_price() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
-
-    let params = ConcentratedPoolParams {
-        amp: f64_to_dec(10f64),
-        price_scale: Decimal::from_ratio(10u8, 1u8),
-        ..common_pcl_params()
-    };
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
 
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    // Fully balanced provide
     let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(10_000000u128),
-        helper.assets[&test_coins[1]].with_balance(1_000000u128),
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
-    helper
-        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.02)))
-        .unwrap();
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
-    // Imbalanced provide. Slippage is more than 2% while we enforce 2% max slippage
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(5_000000u128),
-        helper.assets[&test_coins[1]].with_balance(1_000000u128),
-    ];
-    let err = helper
-        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.02)))
-        .unwrap_err();
-    assert_eq!(
-        ContractError::PclError(PclError::MaxSpreadAssertion {}),
-        err.downcast().unwrap(),
-    );
-    // With 3% slippage it should work
-    helper
-        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.03)))
-        .unwrap();
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
 
-    // Provide with a huge imbalance. Slippage is ~42.2%
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(1000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(1000_000000u128),
-    ];
+    // Strict mode requires belief price
     let err = helper
-        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.02)))
+        .swap_full_params(&user, &offer_asset, None, None, true)
         .unwrap_err();
     assert_eq!(
-        ContractError::PclError(PclError::MaxSpreadAssertion {}),
+        ContractError::Std(StdError::generic_err(
+            "Strict belief price check requires belief_price to be set"
+        )),
         err.downcast().unwrap(),
     );
-    helper
-        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
-        .unwrap();
 
+    // Swap returns ~99.74 USDC thus the execution price is worse than 1:1
     let err = helper
-        .provide_liquidity_full(
-            &owner,
-            &assets,
+        .swap_full_params(
+            &user,
+            &offer_asset,
             Some(f64_to_dec(0.5)),
-            None,
-            None,
-            Some(10000000000u128.into()),
+            Some(Decimal::one()),
+            true,
         )
         .unwrap_err();
     assert_eq!(
-        ContractError::ProvideSlippageViolation(1000229863u128.into(), 10000000000u128.into()),
+        ContractError::Std(StdError::generic_err(
+            "Operation is executed at a price worse than belief price"
+        )),
         err.downcast().unwrap(),
     );
 
+    // The same belief price passes in lenient mode as the spread is within max_spread
     helper
-        .provide_liquidity_full(
-            &owner,
-            &assets,
+        .swap_full_params(
+            &user,
+            &offer_asset,
             Some(f64_to_dec(0.5)),
-            None,
-            None,
-            Some(1000229863u128.into()),
+            Some(Decimal::one()),
+            false,
         )
         .unwrap();
+    assert_eq!(0, helper.coin_balance(&test_coins[0], &user));
+    assert_eq!(99_737929, helper.coin_balance(&test_coins[1], &user));
+
+    // Strict mode passes if the execution price is better than belief price
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper
+        .swap_full_params(&user, &offer_asset, None, Some(f64_to_dec(1.01)), true)
+        .unwrap();
+    assert_eq!(0, helper.coin_balance(&test_coins[0], &user));
 }
 
 #[test]
-fn test_frontrun_before_initial_provide() {
+fn check_swaps_simple() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
-
-    let params = ConcentratedPoolParams {
-        amp: f64_to_dec(10f64),
-        price_scale: Decimal::from_ratio(10u8, 1u8),
-        ..common_pcl_params()
-    };
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
 
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    // Random person tries to frontrun initial provide and imbalance pool upfront
-    helper
-        .app
-        .send_tokens(
-            owner.clone(),
-            helper.pair_addr.clone(),
-            &[helper.assets[&test_coins[0]]
-                .with_balance(10_000_000000u128)
-                .as_coin()
-                .unwrap()],
-        )
-        .unwrap();
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
 
-    // Fully balanced provide
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(10_000000u128),
-        helper.assets[&test_coins[1]].with_balance(1_000000u128),
+    // Check swap does not work if pool is empty
+    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: One of the pools is empty"
+    );
+
+    // Try to swap a wrong asset
+    let wrong_coin = native_asset_info("random-coin".to_string());
+    let wrong_asset = wrong_coin.with_balance(100_000000u128);
+    helper.give_me_money(&[wrong_asset.clone()], &user);
+    let err = helper.swap(&user, &wrong_asset, None).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAsset(wrong_coin.to_string()),
+        err.downcast().unwrap()
+    );
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
-    // Now pool became imbalanced with value (10010, 1)  (or in internal representation (10010, 10))
-    // while price scale stays 10
 
-    let arber = Addr::unchecked("arber");
-    let offer_asset_luna = helper.assets[&test_coins[1]].with_balance(1_000000u128);
-    // Arber spinning pool back to balanced state
-    loop {
-        helper.app.next_block(10);
-        helper.give_me_money(&[offer_asset_luna.clone()], &arber);
-        // swapping until price satisfies an arber
-        if helper
-            .swap_full_params(
-                &arber,
-                &offer_asset_luna,
-                Some(f64_to_dec(0.02)),
-                Some(f64_to_dec(0.1)), // imagine market price is 10 -> i.e. inverted price is 1/10
-            )
-            .is_err()
-        {
-            break;
-        }
-    }
+    // trying to swap cw20 without calling Cw20::Send method
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: helper.assets[&test_coins[1]].with_balance(1u8),
+                ask_asset_info: None,
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                strict_belief_price: false,
+                referral: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Cw20DirectSwap {}, err.downcast().unwrap());
 
-    // price scale changed, however it isn't equal to 10 because of repegging
-    // But next swaps will align price back to the market value
-    let config = helper.query_config().unwrap();
-    let price_scale = config.pool_state.price_state.price_scale;
-    assert!(
-        dec_to_f64(price_scale) - 77.255853 < 1e-5,
-        "price_scale: {price_scale} is far from expected price",
+    let d = helper.query_d().unwrap();
+    assert_eq!(dec_to_f64(d), 200000f64);
+
+    assert_eq!(0, helper.coin_balance(&test_coins[1], &user));
+    helper.swap(&user, &offer_asset, None).unwrap();
+    assert_eq!(0, helper.coin_balance(&test_coins[0], &user));
+    assert_eq!(99_737929, helper.coin_balance(&test_coins[1], &user));
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(90_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
+    assert_eq!(
+        ContractError::PclError(PclError::MaxSpreadAssertion {}),
+        err.downcast().unwrap()
     );
 
-    // Arber collected significant profit (denominated in uusd)
-    // Essentially 10_000 - fees (which settled in the pool)
-    let arber_balance = helper.coin_balance(&test_coins[0], &arber);
-    assert_eq!(arber_balance, 9667_528248);
+    let user2 = Addr::unchecked("user2");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user2);
+    helper.swap(&user2, &offer_asset, None).unwrap();
+    assert_eq!(0, helper.coin_balance(&test_coins[1], &user2));
+    assert_eq!(99_741246, helper.coin_balance(&test_coins[0], &user2));
 
-    // Pool's TVL increased from (10, 1) i.e. 20 to (320, 32) i.e. 640 considering market price is 10.0
-    let pools = config
-        .pair_info
-        .query_pools(&helper.app.wrap(), &helper.pair_addr)
+    let d = helper.query_d().unwrap();
+    assert_eq!(dec_to_f64(d), 200000.260415);
+
+    let price1 = helper.observe_price(0).unwrap();
+    helper.app.next_block(10);
+    // Swapping the lowest amount possible which results in positive return amount
+    helper
+        .swap(
+            &user,
+            &helper.assets[&test_coins[1]].with_balance(2u128),
+            None,
+        )
         .unwrap();
-    assert_eq!(pools[0].amount.u128(), 320_624088);
-    assert_eq!(pools[1].amount.u128(), 32_000000);
+    let price2 = helper.observe_price(0).unwrap();
+    // With such a small swap size contract doesn't store observation
+    assert_eq!(price1, price2);
+
+    helper.app.next_block(10);
+    // Swap the smallest possible amount which gets observation saved
+    helper
+        .swap(
+            &user,
+            &helper.assets[&test_coins[1]].with_balance(1005u128),
+            None,
+        )
+        .unwrap();
+    let price3 = helper.observe_price(0).unwrap();
+    // Prove that price didn't jump that much
+    let diff = price3.diff(price2);
+    assert!(
+        diff / price2 < f64_to_dec(0.005),
+        "price jumped from {price2} to {price3} which is more than 0.5%"
+    );
 }
 
 #[test]
-fn check_correct_fee_share() {
+fn check_swaps_with_price_update() {
     let owner = Addr::unchecked("owner");
+    let half = Decimal::from_ratio(1u8, 2u8);
 
     let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
 
     let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
-    let share_recipient = Addr::unchecked("share_recipient");
-    // Attempt setting fee share with max+1 fee share
-    let action = ConcentratedPoolUpdateParams::EnableFeeShare {
-        fee_share_bps: MAX_FEE_SHARE_BPS + 1,
-        fee_share_address: share_recipient.to_string(),
-    };
-    let err = helper.update_config(&owner, &action).unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::FeeShareOutOfBounds {}
-    );
+    helper.app.next_block(1000);
 
-    // Attempt setting fee share with max+1 fee share
-    let action = ConcentratedPoolUpdateParams::EnableFeeShare {
-        fee_share_bps: 0,
-        fee_share_address: share_recipient.to_string(),
-    };
-    let err = helper.update_config(&owner, &action).unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::FeeShareOutOfBounds {}
-    );
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
     helper.app.next_block(1000);
 
-    // Set to 5% fee share
-    let action = ConcentratedPoolUpdateParams::EnableFeeShare {
-        fee_share_bps: 1000,
-        fee_share_address: share_recipient.to_string(),
-    };
-    helper.update_config(&owner, &action).unwrap();
+    let user1 = Addr::unchecked("user1");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(10_000_000000u128);
+    let mut prev_vlp_price = helper.query_lp_price().unwrap();
 
-    let config = helper.query_config().unwrap();
-    let fee_share = config.fee_share.unwrap();
-    assert_eq!(fee_share.bps, 1000u16);
-    assert_eq!(fee_share.recipient, share_recipient.to_string());
+    for i in 0..4 {
+        helper.give_me_money(&[offer_asset.clone()], &user1);
+        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
+        let new_vlp_price = helper.query_lp_price().unwrap();
+        assert!(
+            new_vlp_price >= prev_vlp_price,
+            "{i}: new_vlp_price <= prev_vlp_price ({new_vlp_price} <= {prev_vlp_price})",
+        );
+        prev_vlp_price = new_vlp_price;
+        helper.app.next_block(1000);
+    }
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(10_000_000000u128);
+    for _i in 0..4 {
+        helper.give_me_money(&[offer_asset.clone()], &user1);
+        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
+        helper.app.next_block(1000);
+    }
+}
+
+#[test]
+fn provides_and_swaps() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
     helper.app.next_block(1000);
 
@@ -1589,22 +1869,6 @@ fn check_correct_fee_share() {
     helper.give_me_money(&[offer_asset.clone()], &user);
     helper.swap(&user, &offer_asset, None).unwrap();
 
-    let last_price = helper
-        .query_config()
-        .unwrap()
-        .pool_state
-        .price_state
-        .last_price;
-    assert_eq!(
-        last_price,
-        Decimal256::from_str("1.001187607454013938").unwrap()
-    );
-
-    // Check that the shared fees are sent
-    let expected_fee_share = 26081u128;
-    let recipient_balance = helper.coin_balance(&test_coins[1], &share_recipient);
-    assert_eq!(recipient_balance, expected_fee_share);
-
     let provider = Addr::unchecked("provider");
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(1_000_000000u128),
@@ -1617,17 +1881,6 @@ fn check_correct_fee_share() {
     helper.give_me_money(&[offer_asset.clone()], &user);
     helper.swap(&user, &offer_asset, None).unwrap();
 
-    let last_price = helper
-        .query_config()
-        .unwrap()
-        .pool_state
-        .price_state
-        .last_price;
-    assert_eq!(
-        last_price,
-        Decimal256::from_str("0.998842355796925899").unwrap()
-    );
-
     helper
         .withdraw_liquidity(&provider, 999_999354, vec![])
         .unwrap();
@@ -1635,425 +1888,2278 @@ fn check_correct_fee_share() {
     let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
     helper.give_me_money(&[offer_asset.clone()], &user);
     helper.swap(&user, &offer_asset, None).unwrap();
-
-    let last_price = helper
-        .query_config()
-        .unwrap()
-        .pool_state
-        .price_state
-        .last_price;
-    assert_eq!(
-        last_price,
-        Decimal256::from_str("1.00118760696709103").unwrap()
-    );
-
-    // Disable fee share
-    let action = ConcentratedPoolUpdateParams::DisableFeeShare {};
-    helper.update_config(&owner, &action).unwrap();
-
-    let config = helper.query_config().unwrap();
-    assert!(config.fee_share.is_none());
 }
 
 #[test]
-fn check_small_trades() {
+fn check_amp_gamma_change() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
 
     let params = ConcentratedPoolParams {
-        price_scale: f64_to_dec(4.360000915600192),
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.0001),
         ..common_pcl_params()
     };
+    let mut helper = Helper::new(&owner, test_coins, params).unwrap();
 
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
-
-    // Fully balanced but small provide
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(8_000000u128),
-        helper.assets[&test_coins[1]].with_balance(1_834862u128),
-    ];
-    helper.provide_liquidity(&owner, &assets).unwrap();
+    let random_user = Addr::unchecked("random");
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.002)),
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
 
-    // Trying to mess the last price with lowest possible swap
-    for _ in 0..1000 {
-        helper.app.next_block(30);
-        let offer_asset = helper.assets[&test_coins[1]].with_balance(1u8);
-        helper
-            .swap_full_params(&owner, &offer_asset, None, Some(Decimal::MAX))
-            .unwrap();
-    }
+    let err = helper.update_config(&random_user, &action).unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
 
-    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
-    let config = helper.query_config().unwrap();
-    let pool = helper
-        .query_pool()
-        .unwrap()
-        .assets
-        .into_iter()
-        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
-        .collect_vec();
+    helper.update_config(&owner, &action).unwrap();
 
-    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
-    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+    helper.app.next_block(86400);
 
-    assert!(
-        relative_diff < Decimal256::percent(3),
-        "Internal PCL value is off. Relative_diff: {}",
-        relative_diff
-    );
+    let future_time = helper.app.block_info().time.seconds() + 100_000;
+    let target_amp = 44f64;
+    let target_gamma = 0.00009;
+    let action = ConcentratedPoolUpdateParams::Promote(PromoteParams {
+        next_amp: f64_to_dec(target_amp),
+        next_gamma: f64_to_dec(target_gamma),
+        future_time,
+    });
+    helper.update_config(&owner, &action).unwrap();
 
-    // Trying to mess the last price with lowest possible provide
-    for _ in 0..1000 {
-        helper.app.next_block(30);
-        let assets = vec![helper.assets[&test_coins[1]].with_balance(1u8)];
-        helper
-            .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
-            .unwrap();
-    }
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    assert_eq!(dec_to_f64(amp_gamma.amp), 40f64);
+    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.0001);
+    assert_eq!(amp_gamma.future_time, future_time);
 
-    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
-    let config = helper.query_config().unwrap();
-    let pool = helper
-        .query_pool()
-        .unwrap()
-        .assets
-        .into_iter()
-        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
-        .collect_vec();
+    helper.app.next_block(50_000);
 
-    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
-    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    assert_eq!(dec_to_f64(amp_gamma.amp), 42f64);
+    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.000095);
+    assert_eq!(amp_gamma.future_time, future_time);
 
-    assert!(
-        relative_diff < Decimal256::percent(3),
-        "Internal PCL value is off. Relative_diff: {}",
-        relative_diff
-    );
+    helper.app.next_block(50_000);
+
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    assert_eq!(dec_to_f64(amp_gamma.amp), target_amp);
+    assert_eq!(dec_to_f64(amp_gamma.gamma), target_gamma);
+    assert_eq!(amp_gamma.future_time, future_time);
+
+    // change values back
+    let future_time = helper.app.block_info().time.seconds() + 100_000;
+    let action = ConcentratedPoolUpdateParams::Promote(PromoteParams {
+        next_amp: f64_to_dec(40f64),
+        next_gamma: f64_to_dec(0.000099),
+        future_time,
+    });
+    helper.update_config(&owner, &action).unwrap();
+
+    helper.app.next_block(50_000);
+
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    assert_eq!(dec_to_f64(amp_gamma.amp), 42f64);
+    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.0000945);
+    assert_eq!(amp_gamma.future_time, future_time);
+
+    // stop changing amp and gamma thus fixing current values
+    let action = ConcentratedPoolUpdateParams::StopChangingAmpGamma {};
+    helper.update_config(&owner, &action).unwrap();
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    let last_change_time = helper.app.block_info().time.seconds();
+    assert_eq!(amp_gamma.future_time, last_change_time);
+
+    helper.app.next_block(50_000);
+
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    assert_eq!(dec_to_f64(amp_gamma.amp), 42f64);
+    assert_eq!(dec_to_f64(amp_gamma.gamma), 0.0000945);
+    assert_eq!(amp_gamma.future_time, last_change_time);
 }
 
 #[test]
-fn check_small_trades_18decimals() {
+fn check_amp_gamma_promotion_interval() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![
-        TestCoin::cw20precise("ETH", 18),
-        TestCoin::cw20precise("USD", 18),
-    ];
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
 
     let params = ConcentratedPoolParams {
-        price_scale: f64_to_dec(4.360000915600192),
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.0001),
         ..common_pcl_params()
     };
+    let mut helper = Helper::new(&owner, test_coins, params).unwrap();
 
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
-
-    // Fully balanced but small provide
-    let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(8e18 as u128),
-        helper.assets[&test_coins[1]].with_balance(1_834862000000000000u128),
-    ];
-    helper.provide_liquidity(&owner, &assets).unwrap();
-
-    // Trying to mess the last price with lowest possible swap
-    for _ in 0..1000 {
-        helper.app.next_block(30);
-        let offer_asset = helper.assets[&test_coins[1]].with_balance(1u8);
-        helper
-            .swap_full_params(&owner, &offer_asset, None, Some(Decimal::MAX))
-            .unwrap();
-    }
-
-    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
-    let config = helper.query_config().unwrap();
-    let pool = helper
-        .query_pool()
-        .unwrap()
-        .assets
-        .into_iter()
-        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
-        .collect_vec();
+    helper.app.next_block(MIN_AMP_CHANGING_TIME);
 
-    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
-    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+    let promote = |future_time: u64, next_amp: f64| {
+        ConcentratedPoolUpdateParams::Promote(PromoteParams {
+            next_amp: f64_to_dec(next_amp),
+            next_gamma: f64_to_dec(0.0001),
+            future_time,
+        })
+    };
 
-    assert!(
-        relative_diff < Decimal256::percent(3),
-        "Internal PCL value is off. Relative_diff: {}",
-        relative_diff
+    // Promotion must last at least MIN_AMP_CHANGING_TIME
+    let now = helper.app.block_info().time.seconds();
+    let err = helper
+        .update_config(&owner, &promote(now + MIN_AMP_CHANGING_TIME - 1, 44f64))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PclError(PclError::MinChangingTimeAssertion {}),
+        err.downcast().unwrap()
     );
+    helper
+        .update_config(&owner, &promote(now + MIN_AMP_CHANGING_TIME, 44f64))
+        .unwrap();
 
-    // Trying to mess the last price with lowest possible provide
-    for _ in 0..1000 {
-        helper.app.next_block(30);
-        // 0.000001 USD. minimum provide is limited to LP token precision which is 6 decimals.
-        let assets = vec![helper.assets[&test_coins[1]].with_balance(1000000000000u128)];
-        helper
-            .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
-            .unwrap();
-    }
-
-    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
-    let config = helper.query_config().unwrap();
-    let pool = helper
-        .query_pool()
-        .unwrap()
-        .assets
-        .into_iter()
-        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
-        .collect_vec();
+    // The next promotion can't start until MIN_AMP_CHANGING_TIME has passed since the last one
+    helper.app.next_block(MIN_AMP_CHANGING_TIME - 1);
+    let now = helper.app.block_info().time.seconds();
+    let err = helper
+        .update_config(&owner, &promote(now + MIN_AMP_CHANGING_TIME, 42f64))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PclError(PclError::MinChangingTimeAssertion {}),
+        err.downcast().unwrap()
+    );
 
-    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
-    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+    helper.app.next_block(1);
+    let now = helper.app.block_info().time.seconds();
+    helper
+        .update_config(&owner, &promote(now + MIN_AMP_CHANGING_TIME, 42f64))
+        .unwrap();
 
-    assert!(
-        relative_diff < Decimal256::percent(3),
-        "Internal PCL value is off. Relative_diff: {}",
-        relative_diff
-    );
+    let amp_gamma = helper.query_amp_gamma().unwrap();
+    assert_eq!(dec_to_f64(amp_gamma.amp), 44f64);
+    assert_eq!(amp_gamma.future_time, now + MIN_AMP_CHANGING_TIME);
 }
 
 #[test]
-fn check_lsd_swaps_with_price_update() {
+fn check_prices() {
     let owner = Addr::unchecked("owner");
-    let half = Decimal::from_ratio(1u8, 2u8);
-    let price_scale = 0.87;
 
-    let test_coins = vec![TestCoin::native("wsteth"), TestCoin::native("eth")];
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
 
-    // checking swaps in PCL pair with LSD params
-    let params = ConcentratedPoolParams {
-        amp: f64_to_dec(500_f64),
-        gamma: f64_to_dec(0.00000001),
-        mid_fee: f64_to_dec(0.0003),
-        out_fee: f64_to_dec(0.0045),
-        fee_gamma: f64_to_dec(0.3),
-        repeg_profit_threshold: f64_to_dec(0.00000001),
-        min_price_scale_delta: f64_to_dec(0.0000055),
-        price_scale: f64_to_dec(price_scale),
-        ma_half_time: 600,
-        track_asset_balances: None,
-        fee_share: None,
-    };
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+    helper.app.next_block(50_000);
 
-    helper.app.next_block(1000);
+    let check_prices = |helper: &Helper| {
+        let prices = helper.query_prices().unwrap();
+
+        test_coins
+            .iter()
+            .cartesian_product(test_coins.iter())
+            .filter(|(a, b)| a != b)
+            .for_each(|(from_coin, to_coin)| {
+                let price = prices
+                    .cumulative_prices
+                    .iter()
+                    .filter(|(from, to, _)| {
+                        from.eq(&helper.assets[from_coin]) && to.eq(&helper.assets[to_coin])
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(price.len(), 1);
+                assert!(!price[0].2.is_zero());
+            });
+    };
 
     let assets = vec![
-        helper.assets[&test_coins[0]].with_balance((1e18 * price_scale) as u128),
-        helper.assets[&test_coins[1]].with_balance(1e18 as u128),
+        helper.assets[&test_coins[0]].with_balance(100_000_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000_000000u128),
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
+    check_prices(&helper);
+
     helper.app.next_block(1000);
 
-    for _ in 0..10 {
-        let assets = vec![
-            helper.assets[&test_coins[0]].with_balance((1e15 * price_scale) as u128),
-            helper.assets[&test_coins[1]].with_balance(1e15 as u128),
-        ];
-        helper.provide_liquidity(&owner, &assets).unwrap();
+    let user1 = Addr::unchecked("user1");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user1);
+
+    helper.swap(&user1, &offer_asset, None).unwrap();
+    check_prices(&helper);
+
+    helper.app.next_block(86400);
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000000u128),
+    ];
+    helper.give_me_money(&assets, &user1);
+
+    helper.provide_liquidity(&user1, &assets).unwrap();
+    check_prices(&helper);
+
+    helper.app.next_block(14 * 86400);
+
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(10_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user1);
+    helper.swap(&user1, &offer_asset, None).unwrap();
+    check_prices(&helper);
+}
+
+#[test]
+fn check_is_owner() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
+
+    let mut helper = Helper::new(&owner, test_coins, common_pcl_params()).unwrap();
+
+    // The pair falls back to the factory owner
+    assert!(helper.query_is_owner(&owner).unwrap());
+    assert!(!helper.query_is_owner(&Addr::unchecked("random")).unwrap());
+
+    // Proposed but unclaimed owner is not the owner yet
+    let new_owner = Addr::unchecked("new_owner");
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::ProposeNewOwner {
+                owner: new_owner.to_string(),
+                expires_in: 100,
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(helper.query_is_owner(&owner).unwrap());
+    assert!(!helper.query_is_owner(&new_owner).unwrap());
+
+    helper
+        .app
+        .execute_contract(
+            new_owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap();
+    assert!(!helper.query_is_owner(&owner).unwrap());
+    assert!(helper.query_is_owner(&new_owner).unwrap());
+}
+
+#[test]
+fn update_owner() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
+
+    let mut helper = Helper::new(&owner, test_coins, common_pcl_params()).unwrap();
+
+    let new_owner = String::from("new_owner");
+
+    // New owner
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: new_owner.clone(),
+        expires_in: 100, // seconds
+    };
+
+    // Unauthorized check
+    let err = helper
+        .app
+        .execute_contract(
+            Addr::unchecked("not_owner"),
+            helper.pair_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+
+    // Claim before proposal
+    let err = helper
+        .app
+        .execute_contract(
+            Addr::unchecked(new_owner.clone()),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Ownership proposal not found"
+    );
+
+    // Propose new owner
+    helper
+        .app
+        .execute_contract(
+            Addr::unchecked(&helper.owner),
+            helper.pair_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+
+    // Claim from invalid addr
+    let err = helper
+        .app
+        .execute_contract(
+            Addr::unchecked("invalid_addr"),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+
+    // Drop ownership proposal
+    let err = helper
+        .app
+        .execute_contract(
+            Addr::unchecked("invalid_addr"),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::DropOwnershipProposal {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+
+    helper
+        .app
+        .execute_contract(
+            helper.owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::DropOwnershipProposal {},
+            &[],
+        )
+        .unwrap();
+
+    // Propose new owner
+    helper
+        .app
+        .execute_contract(
+            Addr::unchecked(&helper.owner),
+            helper.pair_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+
+    // Claim ownership
+    helper
+        .app
+        .execute_contract(
+            Addr::unchecked(new_owner.clone()),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap();
+
+    let config = helper.query_config().unwrap();
+    assert_eq!(config.owner.unwrap().to_string(), new_owner)
+}
+
+#[test]
+fn query_d_test() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
+
+    // create pair with test_coins
+    let helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    // query current pool D value before providing any liquidity
+    let err = helper.query_d().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: Pools are empty"
+    );
+}
+
+#[test]
+fn asset_balances_tracking_with_in_params() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let params = ConcentratedPoolParams {
+        track_asset_balances: Some(true),
+        ..common_pcl_params()
+    };
+
+    // Instantiate pair without asset balances tracking
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(5_000000u128),
+        helper.assets[&test_coins[1]].with_balance(5_000000u128),
+    ];
+
+    // Check that asset balances were not tracked before instantiation
+    // The query AssetBalanceAt returns None for this case
+    let res = helper
+        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
+        .unwrap();
+    assert!(res.is_none());
+
+    let res = helper
+        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
+        .unwrap();
+    assert!(res.is_none());
+
+    // Check that asset balances were not tracked before instantiation
+    // The query AssetBalanceAt returns None for this case
+    let res = helper
+        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
+        .unwrap();
+    assert!(res.is_none());
+
+    let res = helper
+        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
+        .unwrap();
+    assert!(res.is_none());
+
+    // Check that asset balances had zero balances before next block upon instantiation
+    helper.app.update_block(|b| b.height += 1);
+
+    let res = helper
+        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
+        .unwrap();
+    assert!(res.unwrap().is_zero());
+
+    let res = helper
+        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
+        .unwrap();
+    assert!(res.unwrap().is_zero());
+
+    // Provide liquidity
+    helper
+        .provide_liquidity(
+            &owner,
+            &[
+                assets[0].info.with_balance(999_000000u128),
+                assets[1].info.with_balance(1000_000000u128),
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(
+        helper.native_balance(&helper.lp_token, &owner),
+        999_498998u128
+    );
+
+    // Check that asset balances changed after providing liquidity
+    helper.app.update_block(|b| b.height += 1);
+    let res = helper
+        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(999_000000));
+
+    let res = helper
+        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(1000_000000));
+
+    // Swap
+    helper
+        .swap(
+            &owner,
+            &Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_owned(),
+                },
+                amount: Uint128::new(1_000000),
+            },
+            None,
+        )
+        .unwrap();
+
+    // Check that asset balances changed after swapping
+    helper.app.update_block(|b| b.height += 1);
+    let res = helper
+        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(998_001335));
+
+    let res = helper
+        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(1001_000000));
+
+    // Withdraw liquidity
+    helper
+        .withdraw_liquidity(&owner, 500_000000, vec![])
+        .unwrap();
+
+    // Check that asset balances changed after withdrawing
+    helper.app.update_block(|b| b.height += 1);
+    let res = helper
+        .query_asset_balance_at(&assets[0].info, helper.app.block_info().height)
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(498_751043));
+
+    let res = helper
+        .query_asset_balance_at(&assets[1].info, helper.app.block_info().height)
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(500_249625));
+}
+
+#[test]
+fn provides_and_swaps_and_withdraw() {
+    let owner = Addr::unchecked("owner");
+    let half = Decimal::from_ratio(1u8, 2u8);
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let params = ConcentratedPoolParams {
+        price_scale: Decimal::from_ratio(1u8, 2u8),
+        ..common_pcl_params()
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    helper.app.next_block(1000);
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(200_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // swap uluna
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, Some(half)).unwrap();
+
+    helper.app.next_block(1000);
+
+    // swap usdc
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, Some(half)).unwrap();
+
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, Some(half)).unwrap();
+
+    // swap uluna
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, Some(half)).unwrap();
+    let res: PoolResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(helper.pair_addr.to_string(), &QueryMsg::Pool {})
+        .unwrap();
+
+    assert_eq!(res.total_share.u128(), 141_421_356_237u128);
+    let owner_balance = helper.native_balance(&helper.lp_token, &owner);
+
+    helper
+        .withdraw_liquidity(&owner, owner_balance, vec![])
+        .unwrap();
+    let res: PoolResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(helper.pair_addr.to_string(), &QueryMsg::Pool {})
+        .unwrap();
+
+    assert_eq!(res.total_share.u128(), 1000u128);
+}
+
+#[test]
+fn provide_liquidity_with_autostaking_to_generator() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let params = ConcentratedPoolParams {
+        price_scale: Decimal::from_ratio(1u8, 2u8),
+        ..common_pcl_params()
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000u128),
+    ];
+
+    helper
+        .provide_liquidity_with_auto_staking(&owner, &assets, None)
+        .unwrap();
+
+    let amount = helper.query_incentives_deposit(helper.lp_token.to_string(), &owner);
+    assert_eq!(amount, Uint128::new(99003));
+}
+
+#[test]
+fn provide_withdraw_provide() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(10f64),
+        price_scale: Decimal::from_ratio(10u8, 1u8),
+        ..common_pcl_params()
+    };
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(10_938039u128),
+        helper.assets[&test_coins[1]].with_balance(1_093804u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    helper.app.next_block(90);
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    helper.app.next_block(90);
+    let uusd = helper.assets[&test_coins[0]].with_balance(5_000000u128);
+    helper.swap(&owner, &uusd, Some(f64_to_dec(0.5))).unwrap();
+
+    helper.app.next_block(600);
+    // Withdraw all
+    let lp_amount = helper.native_balance(&helper.lp_token, &owner);
+    helper
+        .withdraw_liquidity(&owner, lp_amount, vec![])
+        .unwrap();
+
+    // Provide again
+    helper
+        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
+        .unwrap();
+}
+
+#[test]
+fn provide_withdraw_slippage() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(10f64),
+        price_scale: Decimal::from_ratio(10u8, 1u8),
+        ..common_pcl_params()
+    };
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    // Fully balanced provide
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(10_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1_000000u128),
+    ];
+    helper
+        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.02)))
+        .unwrap();
+
+    // Imbalanced provide. Slippage is more than 2% while we enforce 2% max slippage
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(5_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1_000000u128),
+    ];
+    let err = helper
+        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.02)))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PclError(PclError::MaxSpreadAssertion {}),
+        err.downcast().unwrap(),
+    );
+    // With 3% slippage it should work
+    helper
+        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.03)))
+        .unwrap();
+
+    // Provide with a huge imbalance. Slippage is ~42.2%
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(1000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1000_000000u128),
+    ];
+    let err = helper
+        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.02)))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PclError(PclError::MaxSpreadAssertion {}),
+        err.downcast().unwrap(),
+    );
+    helper
+        .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
+        .unwrap();
+
+    let err = helper
+        .provide_liquidity_full(
+            &owner,
+            &assets,
+            Some(f64_to_dec(0.5)),
+            None,
+            None,
+            Some(10000000000u128.into()),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ProvideSlippageViolation(1000229863u128.into(), 10000000000u128.into()),
+        err.downcast().unwrap(),
+    );
+
+    helper
+        .provide_liquidity_full(
+            &owner,
+            &assets,
+            Some(f64_to_dec(0.5)),
+            None,
+            None,
+            Some(1000229863u128.into()),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_frontrun_before_initial_provide() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(10f64),
+        price_scale: Decimal::from_ratio(10u8, 1u8),
+        ..common_pcl_params()
+    };
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    // Random person tries to frontrun initial provide and imbalance pool upfront
+    helper
+        .app
+        .send_tokens(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &[helper.assets[&test_coins[0]]
+                .with_balance(10_000_000000u128)
+                .as_coin()
+                .unwrap()],
+        )
+        .unwrap();
+
+    // Fully balanced provide
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(10_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    // Now pool became imbalanced with value (10010, 1)  (or in internal representation (10010, 10))
+    // while price scale stays 10
+
+    let arber = Addr::unchecked("arber");
+    let offer_asset_luna = helper.assets[&test_coins[1]].with_balance(1_000000u128);
+    // Arber spinning pool back to balanced state
+    loop {
+        helper.app.next_block(10);
+        helper.give_me_money(&[offer_asset_luna.clone()], &arber);
+        // swapping until price satisfies an arber
+        if helper
+            .swap_full_params(
+                &arber,
+                &offer_asset_luna,
+                Some(f64_to_dec(0.02)),
+                Some(f64_to_dec(0.1)), // imagine market price is 10 -> i.e. inverted price is 1/10
+                false,
+            )
+            .is_err()
+        {
+            break;
+        }
+    }
+
+    // price scale changed, however it isn't equal to 10 because of repegging
+    // But next swaps will align price back to the market value
+    let config = helper.query_config().unwrap();
+    let price_scale = config.pool_state.price_state.price_scale;
+    assert!(
+        dec_to_f64(price_scale) - 77.255853 < 1e-5,
+        "price_scale: {price_scale} is far from expected price",
+    );
+
+    // Arber collected significant profit (denominated in uusd)
+    // Essentially 10_000 - fees (which settled in the pool)
+    let arber_balance = helper.coin_balance(&test_coins[0], &arber);
+    assert_eq!(arber_balance, 9667_528248);
+
+    // Pool's TVL increased from (10, 1) i.e. 20 to (320, 32) i.e. 640 considering market price is 10.0
+    let pools = config
+        .pair_info
+        .query_pools(&helper.app.wrap(), &helper.pair_addr)
+        .unwrap();
+    assert_eq!(pools[0].amount.u128(), 320_624088);
+    assert_eq!(pools[1].amount.u128(), 32_000000);
+}
+
+#[test]
+fn check_correct_fee_share() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let share_recipient = Addr::unchecked("share_recipient");
+    // Attempt setting fee share with max+1 fee share
+    let action = ConcentratedPoolUpdateParams::EnableFeeShare {
+        fee_share_bps: MAX_FEE_SHARE_BPS + 1,
+        fee_share_address: share_recipient.to_string(),
+    };
+    let err = helper.update_config(&owner, &action).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeShareOutOfBounds {}
+    );
+
+    // Attempt setting fee share with max+1 fee share
+    let action = ConcentratedPoolUpdateParams::EnableFeeShare {
+        fee_share_bps: 0,
+        fee_share_address: share_recipient.to_string(),
+    };
+    let err = helper.update_config(&owner, &action).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::FeeShareOutOfBounds {}
+    );
+
+    helper.app.next_block(1000);
+
+    // Set to 5% fee share
+    let action = ConcentratedPoolUpdateParams::EnableFeeShare {
+        fee_share_bps: 1000,
+        fee_share_address: share_recipient.to_string(),
+    };
+    helper.update_config(&owner, &action).unwrap();
+
+    let config = helper.query_config().unwrap();
+    let fee_share = config.fee_share.unwrap();
+    assert_eq!(fee_share.bps, 1000u16);
+    assert_eq!(fee_share.recipient, share_recipient.to_string());
+
+    helper.app.next_block(1000);
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    helper.app.next_block(1000);
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let last_price = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .last_price;
+    assert_eq!(
+        last_price,
+        Decimal256::from_str("1.001187607454013938").unwrap()
+    );
+
+    // Check that the shared fees are sent
+    let expected_fee_share = 26081u128;
+    let recipient_balance = helper.coin_balance(&test_coins[1], &share_recipient);
+    assert_eq!(recipient_balance, expected_fee_share);
+
+    let provider = Addr::unchecked("provider");
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(1_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1_000_000000u128),
+    ];
+    helper.give_me_money(&assets, &provider);
+    helper.provide_liquidity(&provider, &assets).unwrap();
+
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let last_price = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .last_price;
+    assert_eq!(
+        last_price,
+        Decimal256::from_str("0.998842355796925899").unwrap()
+    );
+
+    helper
+        .withdraw_liquidity(&provider, 999_999354, vec![])
+        .unwrap();
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let last_price = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .last_price;
+    assert_eq!(
+        last_price,
+        Decimal256::from_str("1.00118760696709103").unwrap()
+    );
+
+    // Disable fee share
+    let action = ConcentratedPoolUpdateParams::DisableFeeShare {};
+    helper.update_config(&owner, &action).unwrap();
+
+    let config = helper.query_config().unwrap();
+    assert!(config.fee_share.is_none());
+}
+
+#[test]
+fn check_local_fee_share() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let local_recipient = Addr::unchecked("local_recipient");
+    for bps in [0, MAX_LOCAL_FEE_SHARE_BPS + 1] {
+        let err = helper
+            .update_config(
+                &owner,
+                &ConcentratedPoolUpdateParams::EnableLocalFeeShare {
+                    bps,
+                    address: local_recipient.to_string(),
+                },
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::LocalFeeShareOutOfBounds {}
+        );
+    }
+
+    // Divert 30% of the maker fee
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableLocalFeeShare {
+                bps: 3000,
+                address: local_recipient.to_string(),
+            },
+        )
+        .unwrap();
+
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let pool_config: ConcentratedPoolConfig = from_json(config.params.unwrap()).unwrap();
+    let local_fee_share = pool_config.local_fee_share.unwrap();
+    assert_eq!(local_fee_share.bps, 3000);
+    assert_eq!(local_fee_share.recipient, local_recipient);
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let resp = helper.swap(&user, &offer_asset, None).unwrap();
+
+    let maker_fee: u128 = resp
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .find(|attr| attr.key == "maker_fee_amount")
+        .map(|attr| attr.value.parse().unwrap())
+        .unwrap();
+    assert_ne!(maker_fee, 0);
+
+    let local_amount = helper.coin_balance(&test_coins[1], &local_recipient);
+    let global_amount = helper.coin_balance(&test_coins[1], &helper.fake_maker);
+    assert_eq!(local_amount, maker_fee * 3000 / 10000);
+    assert_eq!(local_amount + global_amount, maker_fee);
+
+    // Only the global part is accounted as maker fees
+    let maker_fees = helper.query_maker_fees().unwrap();
+    assert_eq!(maker_fees[1].amount.u128(), global_amount);
+
+    // Disabling sends the whole maker fee to the factory fee address
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::DisableLocalFeeShare {},
+        )
+        .unwrap();
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &local_recipient),
+        local_amount
+    );
+}
+
+#[test]
+fn check_fee_accrual() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableFeeAccrual {
+                sweep_threshold: Some(Uint128::zero()),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidSweepThreshold {}
+    );
+
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableFeeAccrual {
+                sweep_threshold: None,
+            },
+        )
+        .unwrap();
+    let pool_config = helper.query_decoded_config().unwrap().params;
+    assert_eq!(
+        pool_config.fee_accrual,
+        Some(FeeAccrualConfig {
+            sweep_threshold: None
+        })
+    );
+
+    let err = helper
+        .update_config(&owner, &ConcentratedPoolUpdateParams::CollectFees {})
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoAccruedFees {}
+    );
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let mut expected_fees = [0u128; 2];
+    for (offer_ind, ask_ind) in [(0, 1), (0, 1), (1, 0)] {
+        let offer_asset = helper.assets[&test_coins[offer_ind]].with_balance(100_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        let resp = helper.swap(&user, &offer_asset, None).unwrap();
+
+        let maker_fee: u128 = resp
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "maker_fee_amount")
+            .map(|attr| attr.value.parse().unwrap())
+            .unwrap();
+        assert_ne!(maker_fee, 0);
+        expected_fees[ask_ind] += maker_fee;
+    }
+
+    // Fees are kept in the pair contract and are not part of the pool
+    for (ind, coin) in test_coins.iter().enumerate() {
+        assert_eq!(helper.coin_balance(coin, &helper.fake_maker), 0);
+
+        let accrued = helper.query_accrued_maker_fees().unwrap();
+        assert_eq!(accrued[ind].amount.u128(), expected_fees[ind]);
+        let cumulative = helper.query_maker_fees().unwrap();
+        assert_eq!(cumulative[ind].amount.u128(), expected_fees[ind]);
+
+        let pool = helper.query_pool().unwrap();
+        let contract_balance = helper.coin_balance(coin, &helper.pair_addr);
+        assert_eq!(
+            pool.assets[ind].amount.u128(),
+            contract_balance - expected_fees[ind]
+        );
+    }
+
+    // Sweeping sends the total to the fee address
+    helper
+        .update_config(&owner, &ConcentratedPoolUpdateParams::CollectFees {})
+        .unwrap();
+    for (ind, coin) in test_coins.iter().enumerate() {
+        assert_eq!(
+            helper.coin_balance(coin, &helper.fake_maker),
+            expected_fees[ind]
+        );
+        assert!(helper.query_accrued_maker_fees().unwrap()[ind]
+            .amount
+            .is_zero());
+    }
+
+    // Reaching the threshold sweeps the fees within the swap
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableFeeAccrual {
+                sweep_threshold: Some(Uint128::one()),
+            },
+        )
+        .unwrap();
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    assert!(helper.coin_balance(&test_coins[1], &helper.fake_maker) > expected_fees[1]);
+    assert!(helper.query_accrued_maker_fees().unwrap()[1]
+        .amount
+        .is_zero());
+}
+
+#[test]
+fn check_rebalance_bounty() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetRebalanceBounty {
+                bps: Some(MAX_REBALANCE_BOUNTY_BPS + 1),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RebalanceBountyOutOfBounds {}
+    );
+
+    for action in [
+        ConcentratedPoolUpdateParams::EnableFeeAccrual {
+            sweep_threshold: None,
+        },
+        ConcentratedPoolUpdateParams::SetRebalanceBounty { bps: Some(1000) },
+    ] {
+        helper.update_config(&owner, &action).unwrap();
+    }
+    let pool_config = helper.query_decoded_config().unwrap().params;
+    assert_eq!(pool_config.rebalance_bounty_bps, Some(1000));
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // Move the price within a single block so the internal oracle lags behind
+    let user = Addr::unchecked("user");
+    for _ in 0..5 {
+        let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+    }
+    let accrued_fees = helper.query_accrued_maker_fees().unwrap();
+    assert!(!accrued_fees[1].amount.is_zero());
+    let price_scale = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .price_scale;
+
+    helper.app.next_block(3600);
+
+    // Accrued profit is not enough to repeg
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+                repeg_profit_threshold: Some(REPEG_PROFIT_THRESHOLD_MAX),
+                mid_fee: None,
+                out_fee: None,
+                fee_gamma: None,
+                min_price_scale_delta: None,
+                ma_half_time: None,
+            }),
+        )
+        .unwrap();
+    let keeper = Addr::unchecked("keeper");
+    let resp = helper.rebalance(&keeper).unwrap();
+    let repegged = resp
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .find(|attr| attr.key == "repegged")
+        .map(|attr| attr.value.clone())
+        .unwrap();
+    assert_eq!(repegged, "false");
+    assert_eq!(
+        helper
+            .query_config()
+            .unwrap()
+            .pool_state
+            .price_state
+            .price_scale,
+        price_scale
+    );
+    assert_eq!(helper.query_accrued_maker_fees().unwrap(), accrued_fees);
+    assert_eq!(helper.coin_balance(&test_coins[1], &keeper), 0);
+
+    // Profitable repeg pays the keeper from the accrued fees
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+                repeg_profit_threshold: Some(f64_to_dec(0.000002)),
+                mid_fee: None,
+                out_fee: None,
+                fee_gamma: None,
+                min_price_scale_delta: None,
+                ma_half_time: None,
+            }),
+        )
+        .unwrap();
+    let resp = helper.rebalance(&keeper).unwrap();
+    let repegged = resp
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .find(|attr| attr.key == "repegged")
+        .map(|attr| attr.value.clone())
+        .unwrap();
+    assert_eq!(repegged, "true");
+    assert_ne!(
+        helper
+            .query_config()
+            .unwrap()
+            .pool_state
+            .price_state
+            .price_scale,
+        price_scale
+    );
+
+    let bounty = accrued_fees[1].amount.multiply_ratio(1000u16, 10000u16);
+    assert!(!bounty.is_zero());
+    assert_eq!(helper.coin_balance(&test_coins[1], &keeper), bounty.u128());
+    assert_eq!(
+        helper.query_accrued_maker_fees().unwrap()[1].amount,
+        accrued_fees[1].amount - bounty
+    );
+}
+
+#[test]
+fn check_small_trades() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::native("uluna")];
+
+    let params = ConcentratedPoolParams {
+        price_scale: f64_to_dec(4.360000915600192),
+        ..common_pcl_params()
+    };
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    // Fully balanced but small provide
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(8_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1_834862u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // Trying to mess the last price with lowest possible swap
+    for _ in 0..1000 {
+        helper.app.next_block(30);
+        let offer_asset = helper.assets[&test_coins[1]].with_balance(1u8);
+        helper
+            .swap_full_params(&owner, &offer_asset, None, Some(Decimal::MAX), false)
+            .unwrap();
+    }
+
+    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
+    let config = helper.query_config().unwrap();
+    let pool = helper
+        .query_pool()
+        .unwrap()
+        .assets
+        .into_iter()
+        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
+        .collect_vec();
+
+    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
+    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+
+    assert!(
+        relative_diff < Decimal256::percent(3),
+        "Internal PCL value is off. Relative_diff: {}",
+        relative_diff
+    );
+
+    // Trying to mess the last price with lowest possible provide
+    for _ in 0..1000 {
+        helper.app.next_block(30);
+        let assets = vec![helper.assets[&test_coins[1]].with_balance(1u8)];
+        helper
+            .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
+            .unwrap();
+    }
+
+    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
+    let config = helper.query_config().unwrap();
+    let pool = helper
+        .query_pool()
+        .unwrap()
+        .assets
+        .into_iter()
+        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
+        .collect_vec();
+
+    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
+    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+
+    assert!(
+        relative_diff < Decimal256::percent(3),
+        "Internal PCL value is off. Relative_diff: {}",
+        relative_diff
+    );
+}
+
+#[test]
+fn check_small_trades_18decimals() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::cw20precise("ETH", 18),
+        TestCoin::cw20precise("USD", 18),
+    ];
+
+    let params = ConcentratedPoolParams {
+        price_scale: f64_to_dec(4.360000915600192),
+        ..common_pcl_params()
+    };
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    // Fully balanced but small provide
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(8e18 as u128),
+        helper.assets[&test_coins[1]].with_balance(1_834862000000000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // Trying to mess the last price with lowest possible swap
+    for _ in 0..1000 {
+        helper.app.next_block(30);
+        let offer_asset = helper.assets[&test_coins[1]].with_balance(1u8);
+        helper
+            .swap_full_params(&owner, &offer_asset, None, Some(Decimal::MAX), false)
+            .unwrap();
+    }
+
+    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
+    let config = helper.query_config().unwrap();
+    let pool = helper
+        .query_pool()
+        .unwrap()
+        .assets
+        .into_iter()
+        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
+        .collect_vec();
+
+    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
+    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+
+    assert!(
+        relative_diff < Decimal256::percent(3),
+        "Internal PCL value is off. Relative_diff: {}",
+        relative_diff
+    );
+
+    // Trying to mess the last price with lowest possible provide
+    for _ in 0..1000 {
+        helper.app.next_block(30);
+        // 0.000001 USD. minimum provide is limited to LP token precision which is 6 decimals.
+        let assets = vec![helper.assets[&test_coins[1]].with_balance(1000000000000u128)];
+        helper
+            .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
+            .unwrap();
+    }
+
+    // Check that after price scale adjustments (even they are small) internal value is still nearly balanced
+    let config = helper.query_config().unwrap();
+    let pool = helper
+        .query_pool()
+        .unwrap()
+        .assets
+        .into_iter()
+        .map(|asset| asset.amount.to_decimal256(6u8).unwrap())
+        .collect_vec();
+
+    let ixs = [pool[0], pool[1] * config.pool_state.price_state.price_scale];
+    let relative_diff = ixs[0].abs_diff(ixs[1]) / max(&ixs).unwrap();
+
+    assert!(
+        relative_diff < Decimal256::percent(3),
+        "Internal PCL value is off. Relative_diff: {}",
+        relative_diff
+    );
+}
+
+#[test]
+fn check_small_amounts_18decimals() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::cw20precise("ETH", 18),
+        TestCoin::cw20precise("USD", 18),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100e18 as u128),
+        helper.assets[&test_coins[1]].with_balance(100e18 as u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // 0.000000001 USD is swapped without rounding to zero. Only the fee is taken
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1_000_000_000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    let return_amount = helper.coin_balance(&test_coins[0], &user);
+    assert!(
+        return_amount > 995_000_000 && return_amount < 1_000_000_000,
+        "Unexpected return amount {return_amount}"
+    );
+
+    // 0.00001 of each asset mints 0.00001 LP tokens
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(10_000_000_000_000u128),
+        helper.assets[&test_coins[1]].with_balance(10_000_000_000_000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    helper.provide_liquidity(&user, &assets).unwrap();
+    let lp_amount = helper.native_balance(&helper.lp_token, &user);
+    assert!(
+        (9..=10).contains(&lp_amount),
+        "Unexpected LP amount {lp_amount}"
+    );
+
+    // Provides worth less than the smallest LP token unit are rejected
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000_000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000_000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    let err = helper.provide_liquidity(&user, &assets).unwrap_err();
+    assert_eq!(ContractError::ProvideTooSmall(6), err.downcast().unwrap());
+}
+
+#[test]
+fn check_lsd_swaps_with_price_update() {
+    let owner = Addr::unchecked("owner");
+    let half = Decimal::from_ratio(1u8, 2u8);
+    let price_scale = 0.87;
+
+    let test_coins = vec![TestCoin::native("wsteth"), TestCoin::native("eth")];
+
+    // checking swaps in PCL pair with LSD params
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(500_f64),
+        gamma: f64_to_dec(0.00000001),
+        mid_fee: f64_to_dec(0.0003),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.3),
+        repeg_profit_threshold: f64_to_dec(0.00000001),
+        min_price_scale_delta: f64_to_dec(0.0000055),
+        price_scale: f64_to_dec(price_scale),
+        ma_half_time: 600,
+        track_asset_balances: None,
+        fee_share: None,
+        fee_free_provide_until: None,
+        volatility_fee_factor: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    helper.app.next_block(1000);
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance((1e18 * price_scale) as u128),
+        helper.assets[&test_coins[1]].with_balance(1e18 as u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    helper.app.next_block(1000);
+
+    for _ in 0..10 {
+        let assets = vec![
+            helper.assets[&test_coins[0]].with_balance((1e15 * price_scale) as u128),
+            helper.assets[&test_coins[1]].with_balance(1e15 as u128),
+        ];
+        helper.provide_liquidity(&owner, &assets).unwrap();
         helper.app.next_block(1000);
     }
 
-    for _ in 0..10 {
-        let assets = vec![
-            helper.assets[&test_coins[0]].with_balance((1e13 * price_scale) as u128),
-            helper.assets[&test_coins[1]].with_balance(1e13 as u128),
-        ];
-        helper.provide_liquidity(&owner, &assets).unwrap();
-        helper.app.next_block(1000);
-    }
+    for _ in 0..10 {
+        let assets = vec![
+            helper.assets[&test_coins[0]].with_balance((1e13 * price_scale) as u128),
+            helper.assets[&test_coins[1]].with_balance(1e13 as u128),
+        ];
+        helper.provide_liquidity(&owner, &assets).unwrap();
+        helper.app.next_block(1000);
+    }
+
+    let user1 = Addr::unchecked("user1");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1e16 as u128);
+
+    for _ in 0..10 {
+        helper.give_me_money(&[offer_asset.clone()], &user1);
+        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
+        helper.app.next_block(1000);
+    }
+
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1e16 as u128);
+    for _ in 0..10 {
+        helper.give_me_money(&[offer_asset.clone()], &user1);
+        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
+        helper.app.next_block(1000);
+    }
+}
+
+#[test]
+fn test_provide_liquidity_without_funds() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let params = ConcentratedPoolParams {
+        price_scale: Decimal::from_ratio(2u8, 1u8),
+        ..common_pcl_params()
+    };
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let user1 = Addr::unchecked("user1");
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+    ];
+
+    // provide some liquidity
+    for _ in 0..3 {
+        helper.give_me_money(&assets, &user1);
+        helper.provide_liquidity(&user1, &assets).unwrap();
+    }
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: assets.clone().to_vec(),
+        slippage_tolerance: Some(f64_to_dec(0.5)),
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+    };
+
+    let err = helper
+        .app
+        .execute_contract(user1.clone(), helper.pair_addr.clone(), &msg, &[])
+        .unwrap_err();
+
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Native token balance mismatch between the argument (100000000000uluna) and the transferred (0uluna)"
+    )
+}
+
+#[test]
+fn test_tracker_contract() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let params = ConcentratedPoolParams {
+        track_asset_balances: Some(true),
+        ..common_pcl_params()
+    };
+
+    // Instantiate pair with asset balances tracking
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(5_000000u128),
+        helper.assets[&test_coins[1]].with_balance(5_000000u128),
+    ];
+
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let config = helper.query_config().unwrap();
+
+    let tracker_addr = config.tracker_addr.unwrap();
+
+    let tracker_config: TrackerConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(tracker_addr.clone(), &TrackerQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        tracker_config.token_factory_module,
+        TOKEN_FACTORY_MODULE.to_string()
+    );
+    assert_eq!(tracker_config.tracked_denom, helper.lp_token.to_string());
 
-    let user1 = Addr::unchecked("user1");
-    let offer_asset = helper.assets[&test_coins[0]].with_balance(1e16 as u128);
+    let owner_lp_funds = helper
+        .app
+        .wrap()
+        .query_balance(owner.clone(), helper.lp_token.clone())
+        .unwrap();
 
-    for _ in 0..10 {
-        helper.give_me_money(&[offer_asset.clone()], &user1);
-        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
-        helper.app.next_block(1000);
-    }
+    let total_supply = owner_lp_funds.amount + MINIMUM_LIQUIDITY_AMOUNT;
 
-    let offer_asset = helper.assets[&test_coins[1]].with_balance(1e16 as u128);
-    for _ in 0..10 {
-        helper.give_me_money(&[offer_asset.clone()], &user1);
-        helper.swap(&user1, &offer_asset, Some(half)).unwrap();
-        helper.app.next_block(1000);
-    }
+    // Set Alice's balances
+    helper
+        .app
+        .send_tokens(
+            owner.clone(),
+            alice.clone(),
+            &[Coin {
+                denom: helper.lp_token.to_string(),
+                amount: 10000u128.into(),
+            }],
+        )
+        .unwrap();
+
+    let tracker_total_supply: Uint128 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            tracker_addr.clone(),
+            &TrackerQueryMsg::TotalSupplyAt { unit: None },
+        )
+        .unwrap();
+
+    assert_eq!(total_supply, tracker_total_supply);
+
+    let alice_balance: Uint128 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &tracker_addr,
+            &TrackerQueryMsg::BalanceAt {
+                address: alice.to_string(),
+                unit: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(alice_balance.u128(), 10000);
+
+    let alice_share: Vec<Asset> = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.pair_addr,
+            &QueryMsg::Share {
+                amount: 10000u128.into(),
+            },
+        )
+        .unwrap();
+
+    let block_height = helper.app.block_info().height;
+
+    helper.app.update_block(|b| b.height += 10);
+
+    let historical_total_supply: Uint128 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &tracker_addr,
+            &TrackerQueryMsg::TotalSupplyAt {
+                unit: Some(block_height + 1),
+            },
+        )
+        .unwrap();
+    let alice_lp_balance: Uint128 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &tracker_addr,
+            &TrackerQueryMsg::BalanceAt {
+                address: alice.to_string(),
+                unit: Some(block_height + 1),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(historical_total_supply, total_supply);
+    assert_eq!(alice_lp_balance.u128(), 10000);
+
+    let historical_balance: Uint128 = helper
+        .query_asset_balance_at(&helper.assets[&test_coins[0]], block_height + 1)
+        .unwrap()
+        .unwrap();
+    let alice_hist_bal =
+        historical_balance.multiply_ratio(alice_lp_balance - Uint128::one(), total_supply);
+
+    assert_eq!(alice_share[0].amount, alice_hist_bal);
+}
+
+#[test]
+fn check_swap_rejects_unexpected_funds() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    let extra_asset = native_asset_info("random-coin".to_string()).with_balance(1_000000u128);
+    helper.give_me_money(&[offer_asset.clone(), extra_asset.clone()], &user);
+
+    let err = helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: None,
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                strict_belief_price: false,
+                referral: None,
+            },
+            &[
+                offer_asset.as_coin().unwrap(),
+                extra_asset.as_coin().unwrap(),
+            ],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnexpectedSwapFunds("random-coin".to_string()),
+        err.downcast().unwrap()
+    );
+
+    // Nothing was taken from the user
+    assert_eq!(100_000000, helper.coin_balance(&test_coins[0], &user));
+    assert_eq!(1_000000, helper.native_balance("random-coin", &user));
+
+    helper.swap(&user, &offer_asset, None).unwrap();
+    assert_eq!(0, helper.coin_balance(&test_coins[0], &user));
+}
+
+/// Oracle mock which returns the prices it was instantiated with for any consult query
+fn mock_oracle_contract() -> Box<dyn astroport_test::cw_multi_test::Contract<Empty>> {
+    Box::new(ContractWrapper::new_with_empty(
+        |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> { unimplemented!() },
+        |deps: DepsMut,
+         _: Env,
+         _: MessageInfo,
+         prices: Vec<(AssetInfo, Uint256)>|
+         -> StdResult<Response> {
+            deps.storage.set(b"prices", &to_json_vec(&prices)?);
+            Ok(Response::new())
+        },
+        |deps: Deps, _: Env, _: astroport::oracle::QueryMsg| -> StdResult<Binary> {
+            Ok(deps.storage.get(b"prices").unwrap().into())
+        },
+    ))
+}
+
+#[test]
+fn check_oracle_guard() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // The oracle prices 1 uusd at 1 uluna
+    let oracle_code_id = helper.app.store_code(mock_oracle_contract());
+    let oracle = helper
+        .app
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &vec![(
+                helper.assets[&test_coins[0]].clone(),
+                Uint256::from(1_000000u128),
+            )],
+            &[],
+            "oracle",
+            None,
+        )
+        .unwrap();
+
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableOracleGuard {
+                oracle: oracle.to_string(),
+                max_divergence: Decimal::zero(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMaxDivergence {}
+    );
+
+    let max_divergence = Decimal::percent(1);
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableOracleGuard {
+                oracle: oracle.to_string(),
+                max_divergence,
+            },
+        )
+        .unwrap();
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let pool_config: ConcentratedPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(
+        pool_config.oracle_guard,
+        Some(OracleGuardConfig {
+            oracle: oracle.clone(),
+            max_divergence,
+        })
+    );
+
+    let user = Addr::unchecked("user");
+
+    // Small swaps keep the pool price close to the oracle price
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    // A large swap pushes the pool price too far away from the oracle price
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(50_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper
+        .swap(&user, &offer_asset, Some(f64_to_dec(0.5)))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OracleDivergence { .. }
+    ));
+
+    // Swaps are not checked once the guard is disabled
+    helper
+        .update_config(&owner, &ConcentratedPoolUpdateParams::DisableOracleGuard {})
+        .unwrap();
+    helper
+        .swap(&user, &offer_asset, Some(f64_to_dec(0.5)))
+        .unwrap();
+
+    // The pool price is far away from the oracle price now
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableOracleGuard {
+                oracle: oracle.to_string(),
+                max_divergence,
+            },
+        )
+        .unwrap();
+
+    // Swaps moving the price further away are still rejected
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper
+        .swap(&user, &offer_asset, Some(f64_to_dec(0.5)))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OracleDivergence { .. }
+    ));
+
+    // Swaps bringing the price closer to the oracle price are allowed
+    // even if the divergence stays above the threshold
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper
+        .swap(&user, &offer_asset, Some(f64_to_dec(0.5)))
+        .unwrap();
+
+    // An unreachable oracle halts swaps
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableOracleGuard {
+                oracle: "not_an_oracle".to_string(),
+                max_divergence,
+            },
+        )
+        .unwrap();
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper
+        .swap(&user, &offer_asset, Some(f64_to_dec(0.5)))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OraclePriceUnavailable {}
+    );
+
+    // Disabling the guard resumes swaps
+    helper
+        .update_config(&owner, &ConcentratedPoolUpdateParams::DisableOracleGuard {})
+        .unwrap();
+    helper
+        .swap(&user, &offer_asset, Some(f64_to_dec(0.5)))
+        .unwrap();
 }
 
 #[test]
-fn test_provide_liquidity_without_funds() {
+fn check_stale_price_guard() {
     let owner = Addr::unchecked("owner");
 
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
-
-    let params = ConcentratedPoolParams {
-        price_scale: Decimal::from_ratio(2u8, 1u8),
-        ..common_pcl_params()
-    };
-
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let user1 = Addr::unchecked("user1");
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
     ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
 
-    // provide some liquidity
-    for _ in 0..3 {
-        helper.give_me_money(&assets, &user1);
-        helper.provide_liquidity(&user1, &assets).unwrap();
-    }
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMaxPriceStaleness {
+                max_price_staleness: Some(0),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMaxPriceStaleness {}
+    );
 
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: assets.clone().to_vec(),
-        slippage_tolerance: Some(f64_to_dec(0.5)),
-        auto_stake: None,
-        receiver: None,
-        min_lp_to_receive: None,
-    };
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMaxPriceStaleness {
+                max_price_staleness: Some(86400),
+            },
+        )
+        .unwrap();
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let pool_config: ConcentratedPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(pool_config.max_price_staleness, Some(86400));
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+
+    // The price is fresh right after the pool was created
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
 
+    // After a prolonged inactivity swaps must state the expected price
+    helper.app.next_block(86401);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::StalePrice { .. }
+    ));
     let err = helper
-        .app
-        .execute_contract(user1.clone(), helper.pair_addr.clone(), &msg, &[])
+        .swap_full_params(&user, &offer_asset, None, Some(Decimal::one()), false)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::StalePrice { .. }
+    ));
+    // A loose max_spread is capped while the price is stale
+    let err = helper
+        .swap_full_params(
+            &user,
+            &offer_asset,
+            Some(f64_to_dec(0.1)),
+            Some(f64_to_dec(0.97)),
+            false,
+        )
         .unwrap_err();
-
     assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Native token balance mismatch between the argument (100000000000uluna) and the transferred (0uluna)"
-    )
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PclError(PclError::MaxSpreadAssertion {})
+    );
+    helper
+        .swap_full_params(
+            &user,
+            &offer_asset,
+            Some(f64_to_dec(0.02)),
+            Some(Decimal::one()),
+            false,
+        )
+        .unwrap();
+
+    // The swap refreshed the price
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    // The price age is not checked once the guard is disabled
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMaxPriceStaleness {
+                max_price_staleness: None,
+            },
+        )
+        .unwrap();
+    helper.app.next_block(86401);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
 }
 
 #[test]
-fn test_tracker_contract() {
+fn check_min_oracle_liquidity() {
     let owner = Addr::unchecked("owner");
-    let alice = Addr::unchecked("alice");
-    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let params = ConcentratedPoolParams {
-        track_asset_balances: Some(true),
-        ..common_pcl_params()
-    };
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    // Instantiate pair with asset balances tracking
-    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
 
+    // Thin pool
     let assets = vec![
-        helper.assets[&test_coins[0]].with_balance(5_000000u128),
-        helper.assets[&test_coins[1]].with_balance(5_000000u128),
+        helper.assets[&test_coins[0]].with_balance(10_000000u128),
+        helper.assets[&test_coins[1]].with_balance(10_000000u128),
     ];
-
     helper.provide_liquidity(&owner, &assets).unwrap();
 
-    let config = helper.query_config().unwrap();
+    // Prices are served by default
+    helper.query_oracle_price().unwrap();
+    helper.query_prices().unwrap();
 
-    let tracker_addr = config.tracker_addr.unwrap();
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMinOracleLiquidity {
+                min_oracle_liquidity: Some(Uint128::zero()),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMinOracleLiquidity {}
+    );
 
-    let tracker_config: TrackerConfigResponse = helper
-        .app
-        .wrap()
-        .query_wasm_smart(tracker_addr.clone(), &TrackerQueryMsg::Config {})
+    let min_oracle_liquidity = Uint128::new(1_000_000000);
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMinOracleLiquidity {
+                min_oracle_liquidity: Some(min_oracle_liquidity),
+            },
+        )
         .unwrap();
     assert_eq!(
-        tracker_config.token_factory_module,
-        TOKEN_FACTORY_MODULE.to_string()
+        helper
+            .query_decoded_config()
+            .unwrap()
+            .params
+            .min_oracle_liquidity,
+        Some(min_oracle_liquidity)
     );
-    assert_eq!(tracker_config.tracked_denom, helper.lp_token.to_string());
 
-    let owner_lp_funds = helper
-        .app
-        .wrap()
-        .query_balance(owner.clone(), helper.lp_token.clone())
-        .unwrap();
+    let err = helper.query_oracle_price().unwrap_err();
+    assert!(err.to_string().contains("Oracle price is not reliable"));
+    let err = helper.query_prices().unwrap_err();
+    assert!(err.to_string().contains("Oracle price is not reliable"));
 
-    let total_supply = owner_lp_funds.amount + MINIMUM_LIQUIDITY_AMOUNT;
+    // Liquidity crosses the threshold
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(10_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(10_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    helper.query_oracle_price().unwrap();
+    helper.query_prices().unwrap();
 
-    // Set Alice's balances
+    // Disabling the check serves prices regardless of liquidity
     helper
-        .app
-        .send_tokens(
-            owner.clone(),
-            alice.clone(),
-            &[Coin {
-                denom: helper.lp_token.to_string(),
-                amount: 10000u128.into(),
-            }],
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetMinOracleLiquidity {
+                min_oracle_liquidity: None,
+            },
         )
         .unwrap();
+    assert_eq!(
+        helper
+            .query_decoded_config()
+            .unwrap()
+            .params
+            .min_oracle_liquidity,
+        None
+    );
+}
 
-    let tracker_total_supply: Uint128 = helper
-        .app
-        .wrap()
-        .query_wasm_smart(
-            tracker_addr.clone(),
-            &TrackerQueryMsg::TotalSupplyAt { unit: None },
-        )
-        .unwrap();
+#[test]
+fn check_cumulative_prices_carry_over() {
+    let owner = Addr::unchecked("owner");
 
-    assert_eq!(total_supply, tracker_total_supply);
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
 
-    let alice_balance: Uint128 = helper
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let old_pair = helper.pair_addr.clone();
+    let prices_before = helper.query_prices().unwrap().cumulative_prices;
+    helper.app.next_block(1000);
+    let old_prices = helper.query_prices().unwrap().cumulative_prices;
+
+    let asset_infos = vec![
+        helper.assets[&test_coins[0]].clone(),
+        helper.assets[&test_coins[1]].clone(),
+    ];
+    let replace = |pair_type: PairType| FactoryExecuteMsg::CreateReplacementPair {
+        pair_type,
+        asset_infos: asset_infos.clone(),
+        init_params: Some(to_json_binary(&common_pcl_params()).unwrap()),
+        replaced_pair: old_pair.to_string(),
+    };
+    let pcl_type = PairType::Custom("concentrated".to_string());
+
+    let err = helper
         .app
-        .wrap()
-        .query_wasm_smart(
-            &tracker_addr,
-            &TrackerQueryMsg::BalanceAt {
-                address: alice.to_string(),
-                unit: None,
-            },
+        .execute_contract(
+            user.clone(),
+            helper.factory.clone(),
+            &replace(pcl_type.clone()),
+            &[],
         )
-        .unwrap();
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<FactoryError>().unwrap(),
+        FactoryError::Unauthorized {}
+    );
 
-    assert_eq!(alice_balance.u128(), 10000);
+    // The replaced pair must be deregistered first
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &replace(pcl_type.clone()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<FactoryError>().unwrap(),
+        FactoryError::PairWasCreated {}
+    );
 
-    let alice_share: Vec<Asset> = helper
+    helper
         .app
-        .wrap()
-        .query_wasm_smart(
-            &helper.pair_addr,
-            &QueryMsg::Share {
-                amount: 10000u128.into(),
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &FactoryExecuteMsg::Deregister {
+                asset_infos: asset_infos.clone(),
             },
+            &[],
         )
         .unwrap();
 
-    let block_height = helper.app.block_info().height;
-
-    helper.app.update_block(|b| b.height += 10);
+    // Prices of different pair types are not comparable
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &replace(PairType::Xyk {}),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<FactoryError>().unwrap(),
+        FactoryError::InvalidReplacementPair {}
+    );
 
-    let historical_total_supply: Uint128 = helper
+    helper
         .app
-        .wrap()
-        .query_wasm_smart(
-            &tracker_addr,
-            &TrackerQueryMsg::TotalSupplyAt {
-                unit: Some(block_height + 1),
-            },
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &replace(pcl_type),
+            &[],
         )
         .unwrap();
-    let alice_lp_balance: Uint128 = helper
+    let new_pair_info: PairInfo = helper
         .app
         .wrap()
-        .query_wasm_smart(
-            &tracker_addr,
-            &TrackerQueryMsg::BalanceAt {
-                address: alice.to_string(),
-                unit: Some(block_height + 1),
-            },
-        )
+        .query_wasm_smart(&helper.factory, &FactoryQueryMsg::Pair { asset_infos })
         .unwrap();
+    helper.pair_addr = new_pair_info.contract_addr.clone();
+    helper.lp_token = new_pair_info.liquidity_token.clone();
 
-    assert_eq!(historical_total_supply, total_supply);
-    assert_eq!(alice_lp_balance.u128(), 10000);
+    // The new pair is seeded within the creation transaction
+    assert_eq!(helper.query_prices().unwrap().cumulative_prices, old_prices);
 
-    let historical_balance: Uint128 = helper
-        .query_asset_balance_at(&helper.assets[&test_coins[0]], block_height + 1)
-        .unwrap()
-        .unwrap();
-    let alice_hist_bal =
-        historical_balance.multiply_ratio(alice_lp_balance - Uint128::one(), total_supply);
+    // Nobody can overwrite the seed afterwards
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    let err = helper.seed_cumulative_prices(&owner, vec![]).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CumulativePricesAlreadyUsed {}
+    );
 
-    assert_eq!(alice_share[0].amount, alice_hist_bal);
+    // Prices keep accumulating at a similar rate as if the pair was never replaced
+    let seeded_prices = helper.query_prices().unwrap().cumulative_prices;
+    helper.app.next_block(1000);
+    let prices_after = helper.query_prices().unwrap().cumulative_prices;
+    for i in 0..2 {
+        let old_delta = old_prices[i].2 - prices_before[i].2;
+        let new_delta = prices_after[i].2 - seeded_prices[i].2;
+        assert!(
+            Decimal::from_ratio(old_delta.diff(new_delta), old_delta) < Decimal::percent(5),
+            "old delta {old_delta}, new delta {new_delta}"
+        );
+    }
 }
@@ -53,7 +53,7 @@ where
     C: CustomQuery,
     T: CustomMsg,
 {
-    let tmp = TMP_PAIR_INFO.load(deps.storage)?;
+    let tmp = TMP_PAIR_INFO.load(deps.storage, msg.id)?;
     if PAIRS.has(deps.storage, &tmp.pair_key) {
         return Err(ContractError::PairWasRegistered {});
     }
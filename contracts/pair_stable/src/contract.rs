@@ -5,11 +5,12 @@ use std::vec;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coin, ensure_eq, from_json, to_json_binary, Addr, Binary, Coin, CosmosMsg, Decimal,
-    Decimal256, Deps, DepsMut, Empty, Env, Fraction, MessageInfo, QuerierWrapper, Reply, Response,
-    StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, coin, ensure, ensure_eq, from_json, to_json_binary, Addr, Binary, Coin, CosmosMsg,
+    Decimal, Decimal256, Deps, DepsMut, Empty, Env, Fraction, MessageInfo, QuerierWrapper, Reply,
+    Response, SignedDecimal, StdError, StdResult, Storage, SubMsg, SubMsgResponse, SubMsgResult,
+    Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw_utils::{one_coin, PaymentError};
 use itertools::Itertools;
@@ -22,13 +23,13 @@ use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_ow
 use astroport::cosmwasm_ext::IntegerToDecimal;
 use astroport::observation::{query_observation, PrecommitObservation, OBSERVATIONS_SIZE};
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, FeeShareConfig, InstantiateMsg, StablePoolParams,
-    StablePoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS,
-    MIN_TRADE_SIZE,
+    assert_belief_price, ConfigResponse, CumulativePricesResponse, DecodedConfigResponse,
+    FeeShareConfig, InstantiateMsg, StablePoolParams, StablePoolUpdateParams, DEFAULT_SLIPPAGE,
+    MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS, MIN_TRADE_SIZE,
 };
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse,
-    StablePoolConfig,
+    Cw20HookMsg, ExecuteMsg, PoolResponse, PoolSnapshotResponse, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse, StablePoolConfig, SwapFeeBreakdownResponse,
 };
 use astroport::querier::{query_factory_config, query_fee_info, query_native_supply};
 use astroport::token_factory::{tf_burn_msg, tf_create_denom_msg, MsgCreateDenomResponse};
@@ -40,11 +41,12 @@ use crate::math::{
     calc_y, compute_d, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME,
 };
 use crate::state::{
-    get_precision, store_precisions, Config, CONFIG, OBSERVATIONS, OWNERSHIP_PROPOSAL,
+    get_precision, store_precisions, Config, CONFIG, EMIT_POOL_D, OBSERVATIONS, OWNERSHIP_PROPOSAL,
+    PAUSED, PRECISIONS, PRECISION_OVERRIDES,
 };
 use crate::utils::{
-    accumulate_prices, accumulate_swap_sizes, adjust_precision, calculate_shares,
-    check_asset_infos, check_cw20_in_pool, compute_current_amp, compute_swap,
+    accumulate_prices, accumulate_swap_sizes, adjust_precision, assert_provide_imbalance,
+    calculate_shares, check_asset_infos, check_cw20_in_pool, compute_current_amp, compute_swap,
     determine_base_quote_amount, get_assets_collection, get_share_in_assets,
     mint_liquidity_token_message, select_pools, SwapResult,
 };
@@ -115,6 +117,7 @@ pub fn instantiate(
         cumulative_prices,
         fee_share: None,
         tracker_addr: None,
+        max_provide_imbalance: None,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -216,8 +219,14 @@ pub fn execute(
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
             ..
         } => {
+            if referral.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
+
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -235,6 +244,7 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
             )
         }
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
@@ -279,6 +289,9 @@ pub fn execute(
             assets,
             min_assets_to_receive,
         } => withdraw_liquidity(deps, env, info, assets, min_assets_to_receive),
+        ExecuteMsg::Rebalance {} | ExecuteMsg::SeedCumulativePrices { .. } => {
+            Err(ContractError::NonSupported {})
+        }
     }
 }
 
@@ -297,7 +310,14 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
+            ..
         } => {
+            if referral.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
+
             let config = CONFIG.load(deps.storage)?;
 
             // Only asset contract can execute this message
@@ -318,14 +338,17 @@ pub fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
             )
         }
+        Cw20HookMsg::SwapWithTarget { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
 /// Provides liquidity with the specified input parameters.
 ///
-/// * **assets** vector with assets available in the pool.
+/// * **assets** vector with assets available in the pool. Omitted pool assets are deposited
+/// with zero amount, i.e. providing `[A]` is the same as providing `[A, 0 B]`.
 ///
 /// * **auto_stake** determines whether the resulting LP tokens are automatically staked in
 /// the Incentives contract to receive token incentives.
@@ -343,6 +366,8 @@ pub fn provide_liquidity(
     receiver: Option<String>,
     min_lp_to_receive: Option<Uint128>,
 ) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+
     let mut config = CONFIG.load(deps.storage)?;
 
     let pools = config
@@ -387,8 +412,31 @@ pub fn provide_liquidity(
         }
     }
 
+    let pool_d_attr = if is_pool_d_emitted(deps.storage)? {
+        let new_pools = config
+            .pair_info
+            .asset_infos
+            .iter()
+            .map(|info| {
+                let amount = assets_collection
+                    .iter()
+                    .find(|(deposit, _)| deposit.info.equal(info))
+                    .map(|(deposit, pool)| deposit.amount + pool)
+                    .unwrap_or_default();
+                Decimal256::with_precision(amount, get_precision(deps.storage, info)?)
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Some(attr("pool_d", compute_pool_d(&config, &env, &new_pools)?))
+    } else {
+        None
+    };
+
     let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
 
+    if let Some(max_provide_imbalance) = config.max_provide_imbalance {
+        assert_provide_imbalance(deps.storage, max_provide_imbalance, &assets_collection)?;
+    }
+
     let auto_stake = auto_stake.unwrap_or(false);
 
     let share = calculate_shares(deps.as_ref(), &env, &config, total_share, assets_collection)?;
@@ -439,13 +487,16 @@ pub fn provide_liquidity(
         CONFIG.save(deps.storage, &config)?;
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "provide_liquidity"),
-        attr("sender", info.sender),
-        attr("receiver", receiver),
-        attr("assets", assets.iter().join(", ")),
-        attr("share", share),
-    ]))
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "provide_liquidity"),
+            attr("sender", info.sender),
+            attr("receiver", receiver),
+            attr("assets", assets.iter().join(", ")),
+            attr("share", share),
+        ])
+        .add_attributes(pool_d_attr))
 }
 
 /// Withdraw liquidity from the pool.
@@ -495,16 +546,33 @@ pub fn withdraw_liquidity(
         })
         .collect::<StdResult<Vec<DecimalAsset>>>()?;
 
+    let pool_d_attr = if is_pool_d_emitted(deps.storage)? {
+        let new_pools = pools
+            .iter()
+            .zip(&refund_assets)
+            .map(|(pool, refund)| {
+                let precision = get_precision(deps.storage, &pool.info)?;
+                Ok(pool.amount - refund.amount.to_decimal256(precision)?)
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Some(attr("pool_d", compute_pool_d(&config, &env, &new_pools)?))
+    } else {
+        None
+    };
+
     if accumulate_prices(deps.storage, &env, &mut config, &pools)? {
         CONFIG.save(deps.storage, &config)?;
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "withdraw_liquidity"),
-        attr("sender", info.sender),
-        attr("withdrawn_share", amount),
-        attr("refund_assets", refund_assets.iter().join(", ")),
-    ]))
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "withdraw_liquidity"),
+            attr("sender", info.sender),
+            attr("withdrawn_share", amount),
+            attr("refund_assets", refund_assets.iter().join(", ")),
+        ])
+        .add_attributes(pool_d_attr))
 }
 
 /// Performs an swap operation with the specified parameters.
@@ -514,11 +582,16 @@ pub fn withdraw_liquidity(
 /// * **offer_asset** proposed asset for swapping.
 ///
 /// * **belief_price** is used to calculate the maximum swap spread.
+/// It is the amount of offer asset paid per one ask asset, i.e. its inverse is the minimum
+/// acceptable ask-per-offer rate. In pools with more than two assets it always refers to the
+/// offer/ask pair selected by **ask_asset_info**.
 ///
 /// * **max_spread** sets the maximum spread of the swap operation.
 ///
 /// * **to** sets the recipient of the swap operation.
 ///
+/// * **strict_belief_price** if true, the swap fails when executed at a price worse than **belief_price**.
+///
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
 #[allow(clippy::too_many_arguments)]
 pub fn swap(
@@ -530,7 +603,10 @@ pub fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    strict_belief_price: bool,
 ) -> Result<Response, ContractError> {
+    check_not_paused(deps.storage)?;
+
     let mut config = CONFIG.load(deps.storage)?;
 
     // If the asset balance already increased
@@ -600,6 +676,9 @@ pub fn swap(
         return_amount + commission_amount,
         spread_amount,
     )?;
+    if strict_belief_price {
+        assert_belief_price(belief_price, offer_asset.amount, return_amount)?;
+    }
 
     let receiver = to.unwrap_or_else(|| sender.clone());
 
@@ -651,6 +730,28 @@ pub fn swap(
         }
     }
 
+    let ask_precision = get_precision(deps.storage, &ask_pool.info)?;
+    let pool_d_attr = if is_pool_d_emitted(deps.storage)? {
+        // Everything sent out of the pool is taken from the ask pool
+        let ask_outflow =
+            (return_amount + fee_share_amount + maker_fee_amount).to_decimal256(ask_precision)?;
+        let new_pools = pools
+            .iter()
+            .map(|pool| {
+                if pool.info.equal(&offer_pool.info) {
+                    pool.amount + offer_asset_dec.amount
+                } else if pool.info.equal(&ask_pool.info) {
+                    pool.amount - ask_outflow
+                } else {
+                    pool.amount
+                }
+            })
+            .collect_vec();
+        Some(attr("pool_d", compute_pool_d(&config, &env, &new_pools)?))
+    } else {
+        None
+    };
+
     if accumulate_prices(deps.storage, &env, &mut config, &pools)? {
         CONFIG.save(deps.storage, &config)?;
     }
@@ -661,7 +762,6 @@ pub fn swap(
     // Store time series data in precommit observation.
     // Skipping small unsafe values which can seriously mess oracle price due to rounding errors.
     // This data will be reflected in observations on the next action.
-    let ask_precision = get_precision(deps.storage, &ask_pool.info)?;
     if offer_asset_dec.amount >= MIN_TRADE_SIZE
         && return_amount.to_decimal256(ask_precision)? >= MIN_TRADE_SIZE
     {
@@ -689,7 +789,8 @@ pub fn swap(
             attr("commission_amount", commission_amount),
             attr("maker_fee_amount", maker_fee_amount),
             attr("fee_share_amount", fee_share_amount),
-        ]))
+        ])
+        .add_attributes(pool_d_attr))
 }
 
 /// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
@@ -738,6 +839,16 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amount of assets that could be withdrawn from the pool
 /// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
 /// * **QueryMsg::SimulateProvide { msg }** Simulates the liquidity provision in the pair contract.
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
+/// * **QueryMsg::PoolSnapshot {}** Returns pool reserves, total share and fees using a [`PoolSnapshotResponse`] object.
+/// * **QueryMsg::SimulateSwapWithFeeBreakdown { offer_asset, ask_asset_info }** Returns a swap simulation
+/// together with the current amp using a [`SwapFeeBreakdownResponse`] object.
+/// * **QueryMsg::DecodedConfig {}** Returns the pair configuration with already decoded pool parameters
+/// using a [`DecodedConfigResponse`] object.
+/// * **QueryMsg::SlippageForTrade { offer_asset }** Returns the slippage the trade would incur at the current amp.
+/// * **QueryMsg::Imbalance {}** Returns the deviation of each asset's share of D from the equal-weight target.
+/// * **QueryMsg::MaxSingleAssetOut { lp_amount, asset_info }** Returns the maximum amount of a single asset
+/// withdrawable for the given amount of LP tokens.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -768,6 +879,28 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 .map_err(|e| StdError::generic_err(e.to_string()))?,
         ),
         QueryMsg::QueryComputeD {} => to_json_binary(&query_compute_d(deps, env)?),
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::PoolSnapshot {} => to_json_binary(&query_pool_snapshot(deps)?),
+        QueryMsg::SimulateSwapWithFeeBreakdown {
+            offer_asset,
+            ask_asset_info,
+        } => to_json_binary(&query_swap_fee_breakdown(
+            deps,
+            env,
+            offer_asset,
+            ask_asset_info,
+        )?),
+        QueryMsg::DecodedConfig {} => to_json_binary(&query_decoded_config(deps, env)?),
+        QueryMsg::SlippageForTrade { offer_asset } => {
+            to_json_binary(&query_slippage_for_trade(deps, env, offer_asset)?)
+        }
+        QueryMsg::Imbalance {} => to_json_binary(&query_imbalance(deps, env)?),
+        QueryMsg::MaxSingleAssetOut {
+            lp_amount,
+            asset_info,
+        } => to_json_binary(&query_max_single_asset_out(
+            deps, env, lp_amount, asset_info,
+        )?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -786,6 +919,25 @@ pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     Ok(resp)
 }
 
+/// Returns pool reserves, the amount of LP tokens currently minted and the pool fees
+/// in an object of type [`PoolSnapshotResponse`].
+pub fn query_pool_snapshot(deps: Deps) -> StdResult<PoolSnapshotResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps.querier, &config)?;
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+
+    Ok(PoolSnapshotResponse {
+        assets,
+        total_share,
+        total_fee: fee_info.total_fee_rate,
+        maker_fee: fee_info.maker_fee_rate,
+    })
+}
+
 /// Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
 /// The result is returned in a vector that contains objects of type [`Asset`].
 ///
@@ -798,6 +950,104 @@ pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
     Ok(refund_assets)
 }
 
+/// Returns the maximum amount of `asset_info` which can be withdrawn for `lp_amount` LP tokens.
+/// Imbalanced withdraws are disabled, so the amount is composed of the balanced withdraw share
+/// and the other assets of the share swapped into `asset_info` one after another, starting from
+/// the reserves left after the withdraw. Swap fees are charged the same way as in [`swap`].
+pub fn query_max_single_asset_out(
+    deps: Deps,
+    env: Env,
+    lp_amount: Uint128,
+    asset_info: AssetInfo,
+) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+
+    ensure!(
+        lp_amount <= total_share,
+        StdError::generic_err("LP amount exceeds the total share")
+    );
+
+    let share = get_share_in_assets(&pools, lp_amount, total_share);
+    let mut max_out = share
+        .iter()
+        .find(|asset| asset.info == asset_info)
+        .ok_or_else(|| StdError::generic_err(format!("Asset {asset_info} is not in the pool")))?
+        .amount;
+
+    // Reserves left after the balanced withdraw
+    let mut remaining_pools = pools
+        .iter()
+        .zip(&share)
+        .map(|(pool, refund)| {
+            let precision = get_precision(deps.storage, &pool.info)?;
+            Ok(DecimalAsset {
+                info: pool.info.clone(),
+                amount: Decimal256::with_precision(pool.amount - refund.amount, precision)?,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+
+    for offer_asset in share
+        .iter()
+        .filter(|asset| asset.info != asset_info && !asset.amount.is_zero())
+    {
+        let (offer_pool, ask_pool) =
+            select_pools(Some(&offer_asset.info), Some(&asset_info), &remaining_pools)
+                .map_err(|err| StdError::generic_err(format!("{err}")))?;
+        let offer_precision = get_precision(deps.storage, &offer_pool.info)?;
+        let ask_precision = get_precision(deps.storage, &ask_pool.info)?;
+        let offer_asset_dec = offer_asset.to_decimal_asset(offer_precision)?;
+
+        let SwapResult { return_amount, .. } = compute_swap(
+            deps.storage,
+            &env,
+            &config,
+            &offer_asset_dec,
+            &offer_pool,
+            &ask_pool,
+            &remaining_pools,
+        )
+        .map_err(|err| StdError::generic_err(format!("{err}")))?;
+
+        let commission_amount = fee_info.total_fee_rate.checked_mul_uint128(return_amount)?;
+        let return_amount = return_amount.saturating_sub(commission_amount);
+        max_out += return_amount;
+
+        // The fee share and the maker fee leave the pool while the LP part of the commission stays
+        let mut fees_commission_amount = commission_amount;
+        let mut fee_share_amount = Uint128::zero();
+        if let Some(fee_share) = &config.fee_share {
+            fee_share_amount = commission_amount * Decimal::from_ratio(fee_share.bps, 10000u16);
+            fees_commission_amount = fees_commission_amount.saturating_sub(fee_share_amount);
+        }
+        let maker_fee_amount = if fee_info.fee_address.is_some() {
+            fees_commission_amount * fee_info.maker_fee_rate
+        } else {
+            Uint128::zero()
+        };
+
+        // The next swap runs against the reserves updated by this one
+        let ask_outflow =
+            (return_amount + fee_share_amount + maker_fee_amount).to_decimal256(ask_precision)?;
+        for pool in remaining_pools.iter_mut() {
+            if pool.info.equal(&offer_pool.info) {
+                pool.amount += offer_asset_dec.amount;
+            } else if pool.info.equal(&ask_pool.info) {
+                pool.amount = pool.amount.checked_sub(ask_outflow)?;
+            }
+        }
+    }
+
+    Ok(max_out)
+}
+
 /// Returns information about a swap simulation in a [`SimulationResponse`] object.
 ///
 /// * **offer_asset** is the asset to swap as well as an amount of the said asset.
@@ -870,6 +1120,70 @@ pub fn query_simulation(
     })
 }
 
+/// Returns a swap simulation together with the amplification coefficient used to compute it
+/// in a [`SwapFeeBreakdownResponse`] object.
+pub fn query_swap_fee_breakdown(
+    deps: Deps,
+    env: Env,
+    offer_asset: Asset,
+    ask_asset_info: Option<AssetInfo>,
+) -> StdResult<SwapFeeBreakdownResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let amp = Decimal::from_ratio(compute_current_amp(&config, &env)?, AMP_PRECISION);
+
+    let SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    } = query_simulation(deps, env, offer_asset, ask_asset_info)?;
+
+    Ok(SwapFeeBreakdownResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+        amp,
+    })
+}
+
+/// Returns the slippage the given trade would incur at the current amp.
+/// The slippage is measured against the 1:1 rate and doesn't include swap fees.
+///
+/// * **offer_asset** is the asset to swap as well as an amount of the said asset.
+pub fn query_slippage_for_trade(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<Decimal> {
+    let config = CONFIG.load(deps.storage)?;
+    let pools = config.pair_info.query_pools_decimal(
+        &deps.querier,
+        &config.pair_info.contract_addr,
+        &config.factory_addr,
+    )?;
+
+    let (offer_pool, ask_pool) = select_pools(Some(&offer_asset.info), None, &pools)
+        .map_err(|err| StdError::generic_err(format!("{err}")))?;
+
+    let offer_precision = get_precision(deps.storage, &offer_pool.info)?;
+
+    let SwapResult {
+        return_amount,
+        spread_amount,
+    } = compute_swap(
+        deps.storage,
+        &env,
+        &config,
+        &offer_asset.to_decimal_asset(offer_precision)?,
+        &offer_pool,
+        &ask_pool,
+        &pools,
+    )
+    .map_err(|err| StdError::generic_err(format!("{err}")))?;
+
+    let total_amount = return_amount + spread_amount;
+    if total_amount.is_zero() {
+        return Ok(Decimal::zero());
+    }
+
+    Ok(Decimal::from_ratio(spread_amount, total_amount))
+}
+
 /// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
 ///
 /// * **ask_asset** is the asset to swap to as well as the desired amount of ask
@@ -982,16 +1296,50 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
     let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
     Ok(ConfigResponse {
         block_time_last: config.block_time_last,
-        params: Some(to_json_binary(&StablePoolConfig {
-            amp: Decimal::from_ratio(compute_current_amp(&config, &env)?, AMP_PRECISION),
-            fee_share: config.fee_share,
-        })?),
+        params: Some(to_json_binary(&pool_config(deps, &env, &config)?)?),
         owner: config.owner.unwrap_or(factory_config.owner),
         factory_addr: config.factory_addr,
         tracker_addr: config.tracker_addr,
     })
 }
 
+/// Returns the pair configuration with decoded pool parameters and fees in percents
+/// in a [`DecodedConfigResponse`] object.
+pub fn query_decoded_config(
+    deps: Deps,
+    env: Env,
+) -> StdResult<DecodedConfigResponse<StablePoolConfig>> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+    let hundred = Decimal::from_ratio(100u8, 1u8);
+
+    Ok(DecodedConfigResponse {
+        block_time_last: config.block_time_last,
+        params: pool_config(deps, &env, &config)?,
+        total_fee_percent: fee_info.total_fee_rate * hundred,
+        maker_fee_percent: fee_info.maker_fee_rate * hundred,
+        owner: config.owner.unwrap_or(factory_config.owner),
+        factory_addr: config.factory_addr,
+        tracker_addr: config.tracker_addr,
+    })
+}
+
+/// Assembles the stable pool parameters from the pair config.
+fn pool_config(deps: Deps, env: &Env, config: &Config) -> StdResult<StablePoolConfig> {
+    Ok(StablePoolConfig {
+        amp: Decimal::from_ratio(compute_current_amp(config, env)?, AMP_PRECISION),
+        fee_share: config.fee_share.clone(),
+        max_provide_imbalance: config.max_provide_imbalance,
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+        emit_pool_d: is_pool_d_emitted(deps.storage)?,
+    })
+}
+
 /// If `belief_price` and `max_spread` are both specified, we compute a new spread,
 /// otherwise we just use the swap spread to check `max_spread`.
 ///
@@ -1125,11 +1473,122 @@ pub fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        StablePoolUpdateParams::SetMaxProvideImbalance {
+            max_provide_imbalance,
+        } => {
+            config.max_provide_imbalance = max_provide_imbalance;
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "set_max_provide_imbalance"),
+                attr(
+                    "max_provide_imbalance",
+                    max_provide_imbalance.map_or("unlimited".to_string(), |v| v.to_string()),
+                ),
+            ]);
+        }
+        StablePoolUpdateParams::SetPaused { paused } => {
+            PAUSED.save(deps.storage, &paused)?;
+
+            response.attributes.extend([
+                attr("action", "set_paused"),
+                attr("paused", paused.to_string()),
+            ]);
+        }
+        StablePoolUpdateParams::SetEmitPoolD { enabled } => {
+            EMIT_POOL_D.save(deps.storage, &enabled)?;
+
+            response.attributes.extend([
+                attr("action", "set_emit_pool_d"),
+                attr("enabled", enabled.to_string()),
+            ]);
+        }
+        StablePoolUpdateParams::OverridePrecision {
+            asset_info,
+            precision,
+        } => {
+            override_precision(deps, &mut config, &asset_info, precision)?;
+
+            response.attributes.extend([
+                attr("action", "override_precision"),
+                attr("asset", asset_info.to_string()),
+                attr("precision", precision.to_string()),
+            ]);
+        }
     }
 
     Ok(response)
 }
 
+/// Overrides the stored precision of a cw20 token in the pool.
+/// The override is allowed only once per asset and only while the pool has no liquidity
+/// as the LP share math depends on the greatest asset precision.
+fn override_precision(
+    deps: DepsMut,
+    config: &mut Config,
+    asset_info: &AssetInfo,
+    precision: u8,
+) -> Result<(), ContractError> {
+    if !config.pair_info.asset_infos.contains(asset_info) {
+        return Err(ContractError::InvalidAsset(asset_info.to_string()));
+    }
+
+    let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    if asset_info.is_native_token() || !total_share.is_zero() {
+        return Err(ContractError::PrecisionOverrideNotAllowed {});
+    }
+
+    if PRECISION_OVERRIDES.has(deps.storage, asset_info.to_string()) {
+        return Err(ContractError::PrecisionAlreadyOverridden(
+            asset_info.to_string(),
+        ));
+    }
+
+    let stored_precision = get_precision(deps.storage, asset_info)?;
+    if precision == stored_precision {
+        return Err(ContractError::PrecisionUnchanged(
+            asset_info.to_string(),
+            precision,
+        ));
+    }
+    if precision as u32 > Decimal256::DECIMAL_PLACES {
+        return Err(ContractError::InvalidPrecision(
+            precision,
+            Decimal256::DECIMAL_PLACES,
+        ));
+    }
+
+    PRECISION_OVERRIDES.save(deps.storage, asset_info.to_string(), &stored_precision)?;
+    PRECISIONS.save(deps.storage, asset_info.to_string(), &precision)?;
+
+    config.greatest_precision = config
+        .pair_info
+        .asset_infos
+        .iter()
+        .map(|asset_info| get_precision(deps.storage, asset_info))
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .max()
+        .unwrap_or_default();
+    CONFIG.save(deps.storage, config)?;
+
+    Ok(())
+}
+
+/// Whether the pool emits the `pool_d` attribute after provides, swaps and withdrawals.
+fn is_pool_d_emitted(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(EMIT_POOL_D.may_load(storage)?.unwrap_or_default())
+}
+
+/// Returns [`ContractError::PoolPaused`] if swaps and provides are paused in the pool.
+fn check_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if PAUSED.may_load(storage)?.unwrap_or_default() {
+        return Err(ContractError::PoolPaused {});
+    }
+
+    Ok(())
+}
+
 /// Start changing the AMP value.
 ///
 /// * **next_amp** new value for AMP.
@@ -1189,19 +1648,66 @@ fn stop_changing_amp(mut config: Config, deps: DepsMut, env: Env) -> StdResult<(
 
     Ok(())
 }
+
+/// Returns the difference between each asset's share of D and the equal-weight target share (1/n).
+/// Amounts are normalized to the same precision, so a depegged asset shows up with a large deviation.
+pub fn query_imbalance(deps: Deps, env: Env) -> StdResult<Vec<(AssetInfo, SignedDecimal)>> {
+    let config = CONFIG.load(deps.storage)?;
+    let pools = config.pair_info.query_pools_decimal(
+        &deps.querier,
+        &config.pair_info.contract_addr,
+        &config.factory_addr,
+    )?;
+    let amounts = pools.iter().map(|pool| pool.amount).collect_vec();
+
+    let amp = compute_current_amp(&config, &env)?;
+    let d = compute_d(amp, &amounts)?;
+    if d.is_zero() {
+        return Err(StdError::generic_err("Pool is empty"));
+    }
+
+    let to_signed = |value: Decimal256| -> StdResult<SignedDecimal> {
+        Decimal::try_from(value)
+            .map_err(|err| StdError::generic_err(err.to_string()))
+            .and_then(|value| {
+                SignedDecimal::try_from(value).map_err(|err| StdError::generic_err(err.to_string()))
+            })
+    };
+    let target_share = to_signed(Decimal256::from_ratio(1u8, pools.len() as u8))?;
+
+    pools
+        .into_iter()
+        .map(|pool| {
+            let share = to_signed(pool.amount / d)?;
+            Ok((pool.info, share - target_share))
+        })
+        .collect()
+}
+
 /// Compute the current pool D value.
 fn query_compute_d(deps: Deps, env: Env) -> StdResult<Uint128> {
     let config = CONFIG.load(deps.storage)?;
 
-    let amp = compute_current_amp(&config, &env)?;
     let pools = config
         .pair_info
-        .query_pools_decimal(&deps.querier, env.contract.address, &config.factory_addr)?
+        .query_pools_decimal(
+            &deps.querier,
+            env.contract.address.clone(),
+            &config.factory_addr,
+        )?
         .into_iter()
         .map(|pool| pool.amount)
         .collect::<Vec<_>>();
 
-    compute_d(amp, &pools)
+    compute_pool_d(&config, &env, &pools)
+}
+
+/// Computes the D invariant for the given pool balances. The balances must be ordered
+/// as in the pair config.
+fn compute_pool_d(config: &Config, env: &Env, pools: &[Decimal256]) -> StdResult<Uint128> {
+    let amp = compute_current_amp(config, env)?;
+
+    compute_d(amp, pools)
         .map_err(|_| StdError::generic_err("Failed to calculate the D"))?
         .to_uint128_with_precision(config.greatest_precision)
 }
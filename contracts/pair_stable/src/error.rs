@@ -1,5 +1,6 @@
 use cosmwasm_std::{
-    CheckedMultiplyRatioError, ConversionOverflowError, OverflowError, StdError, Uint128,
+    CheckedMultiplyRatioError, ConversionOverflowError, Decimal, Decimal256, OverflowError,
+    StdError, Uint128,
 };
 use cw_utils::PaymentError;
 use thiserror::Error;
@@ -39,6 +40,12 @@ pub enum ContractError {
     #[error("Insufficient amount of liquidity")]
     LiquidityAmountTooSmall {},
 
+    #[error("Provide imbalance {imbalance} exceeds the maximum allowed {max_imbalance}")]
+    ProvideImbalanceExceeded {
+        imbalance: Decimal256,
+        max_imbalance: Decimal,
+    },
+
     #[error("Provided spread amount exceeds allowed limit")]
     AllowedSpreadAssertion {},
 
@@ -114,6 +121,24 @@ pub enum ContractError {
 
     #[error("Wrong asset length: expected {expected}, actual {actual}")]
     WrongAssetLength { expected: usize, actual: usize },
+
+    #[error("Operation non supported")]
+    NonSupported {},
+
+    #[error("Pool is paused")]
+    PoolPaused {},
+
+    #[error("Precision can be overridden only for cw20 tokens while the pool has no liquidity")]
+    PrecisionOverrideNotAllowed {},
+
+    #[error("Precision of {0} has already been overridden")]
+    PrecisionAlreadyOverridden(String),
+
+    #[error("Invalid precision {0}: must not exceed {1}")]
+    InvalidPrecision(u8, u32),
+
+    #[error("Precision of {0} is already {1}")]
+    PrecisionUnchanged(String, u8),
 }
 
 impl From<OverflowError> for ContractError {
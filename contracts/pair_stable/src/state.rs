@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, DepsMut, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, Decimal, DepsMut, StdResult, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
 
 use astroport::asset::{AssetInfo, PairInfo};
@@ -35,6 +35,8 @@ pub struct Config {
     pub fee_share: Option<FeeShareConfig>,
     /// The tracker contract address
     pub tracker_addr: Option<Addr>,
+    /// The maximum allowed deviation of a provide's asset ratio from the pool ratio
+    pub max_provide_imbalance: Option<Decimal>,
 }
 
 /// Circular buffer to store trade size observations
@@ -43,9 +45,19 @@ pub const OBSERVATIONS: CircularBuffer<Observation> =
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Whether swaps and provides are paused in the pool
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+/// Whether the pool D invariant is emitted as an attribute after state-changing operations
+pub const EMIT_POOL_D: Item<bool> = Item::new("emit_pool_d");
+
 /// Stores map of AssetInfo (as String) -> precision
 pub const PRECISIONS: Map<String, u8> = Map::new("precisions");
 
+/// Stores the originally reported precisions of assets which had their precision overridden.
+/// An asset precision can be overridden only once.
+pub const PRECISION_OVERRIDES: Map<String, u8> = Map::new("precision_overrides");
+
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
@@ -737,6 +737,8 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -895,6 +897,8 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -910,6 +914,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
     });
@@ -1008,6 +1014,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
     });
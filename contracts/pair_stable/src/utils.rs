@@ -10,7 +10,9 @@ use cosmwasm_std::{
 
 use itertools::Itertools;
 
-use astroport::asset::{Asset, AssetInfo, Decimal256Ext, DecimalAsset, MINIMUM_LIQUIDITY_AMOUNT};
+use astroport::asset::{
+    Asset, AssetInfo, AssetInfoExt, Decimal256Ext, DecimalAsset, MINIMUM_LIQUIDITY_AMOUNT,
+};
 use astroport::observation::{
     safe_sma_buffer_not_full, safe_sma_calculation, Observation, PrecommitObservation,
 };
@@ -473,6 +475,56 @@ pub(crate) fn calculate_shares(
     Ok(share)
 }
 
+/// Ensures that the ratio of deposited assets doesn't deviate from the current pool ratio
+/// by more than `max_imbalance`. The deviation is measured per asset as
+/// |deposit share - pool share| / pool share. Provides into an empty pool are not checked.
+pub(crate) fn assert_provide_imbalance(
+    storage: &dyn Storage,
+    max_imbalance: Decimal,
+    assets_collection: &[(Asset, Uint128)],
+) -> Result<(), ContractError> {
+    let amounts = assets_collection
+        .iter()
+        .map(|(deposit, pool)| {
+            let precision = get_precision(storage, &deposit.info)?;
+            Ok((
+                Decimal256::with_precision(deposit.amount, precision)?,
+                Decimal256::with_precision(*pool, precision)?,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let total_deposit = amounts
+        .iter()
+        .map(|(deposit, _)| *deposit)
+        .sum::<Decimal256>();
+    let total_pool = amounts.iter().map(|(_, pool)| *pool).sum::<Decimal256>();
+    if total_deposit.is_zero() || total_pool.is_zero() {
+        return Ok(());
+    }
+
+    for (deposit, pool) in amounts {
+        let deposit_share = deposit / total_deposit;
+        let pool_share = pool / total_pool;
+        let imbalance = deposit_share
+            .abs_diff(pool_share)
+            .checked_div(pool_share)
+            .unwrap_or(Decimal256::MAX);
+
+        if imbalance > Decimal256::from(max_imbalance) {
+            return Err(ContractError::ProvideImbalanceExceeded {
+                imbalance,
+                max_imbalance,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Pairs the deposited assets with their pool amounts in the order of the pool assets.
+/// Pool assets omitted from `assets` are deposited with zero amount, so a subset of assets
+/// is handled exactly like the full list with explicit zeros.
 pub(crate) fn get_assets_collection(
     deps: Deps,
     config: &Config,
@@ -481,49 +533,37 @@ pub(crate) fn get_assets_collection(
 ) -> Result<Vec<(Asset, Uint128)>, ContractError> {
     check_assets(deps.api, &assets)?;
 
-    if assets.len() != config.pair_info.asset_infos.len() {
+    if assets.len() > config.pair_info.asset_infos.len() {
         return Err(ContractError::InvalidNumberOfAssets(
             config.pair_info.asset_infos.len(),
         ));
     }
 
-    let mut non_zero_flag = false;
+    if let Some(asset) = assets.iter().find(|asset| !pools.contains_key(&asset.info)) {
+        return Err(ContractError::InvalidAsset(asset.info.to_string()));
+    }
 
-    let mut assets_collection = assets
-        .clone()
-        .into_iter()
-        .map(|asset| {
-            // Check that at least one asset is non-zero
-            if !asset.amount.is_zero() {
-                non_zero_flag = true;
-            }
+    // Check that at least one asset is non-zero
+    if assets.iter().all(|asset| asset.amount.is_zero()) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
 
-            // Get appropriate pool
+    config
+        .pair_info
+        .asset_infos
+        .iter()
+        .map(|info| {
+            let amount = assets
+                .iter()
+                .find(|asset| asset.info.eq(info))
+                .map(|asset| asset.amount)
+                .unwrap_or_default();
             let pool = pools
-                .get(&asset.info)
+                .get(info)
                 .copied()
-                .ok_or_else(|| ContractError::InvalidAsset(asset.info.to_string()))?;
+                .ok_or_else(|| ContractError::InvalidAsset(info.to_string()))?;
 
-            Ok((asset, pool))
+            Ok((info.with_balance(amount), pool))
         })
-        .collect::<Result<Vec<_>, ContractError>>()?;
-
-    // If some assets are omitted then add them explicitly with 0 deposit
-    pools.iter().for_each(|(pool_info, pool_amount)| {
-        if !assets.iter().any(|asset| asset.info.eq(pool_info)) {
-            assets_collection.push((
-                Asset {
-                    amount: Uint128::zero(),
-                    info: pool_info.clone(),
-                },
-                *pool_amount,
-            ));
-        }
-    });
-
-    if !non_zero_flag {
-        return Err(ContractError::InvalidZeroAmount {});
-    }
-
-    Ok(assets_collection)
+        .collect()
 }
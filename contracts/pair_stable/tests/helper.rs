@@ -18,7 +18,7 @@ use astroport::factory::{PairConfig, PairType};
 use astroport::observation::OracleObservation;
 use astroport::pair::{
     CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse, StablePoolParams,
+    SimulationResponse, StablePoolParams, StablePoolUpdateParams,
 };
 pub const NATIVE_TOKEN_PRECISION: u8 = 6;
 use astroport_pair_stable::contract::{execute, instantiate, query, reply};
@@ -225,6 +225,21 @@ impl Helper {
             .execute_contract(sender.clone(), self.pair_addr.clone(), &msg, &funds)
     }
 
+    pub fn update_config(
+        &mut self,
+        sender: &Addr,
+        params: &StablePoolUpdateParams,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.pair_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_json_binary(params).unwrap(),
+            },
+            &[],
+        )
+    }
+
     pub fn withdraw_liquidity(
         &mut self,
         sender: &Addr,
@@ -248,6 +263,17 @@ impl Helper {
         sender: &Addr,
         offer_asset: &Asset,
         ask_asset_info: Option<AssetInfo>,
+    ) -> AnyResult<AppResponse> {
+        self.swap_with_belief_price(sender, offer_asset, ask_asset_info, None, false)
+    }
+
+    pub fn swap_with_belief_price(
+        &mut self,
+        sender: &Addr,
+        offer_asset: &Asset,
+        ask_asset_info: Option<AssetInfo>,
+        belief_price: Option<Decimal>,
+        strict_belief_price: bool,
     ) -> AnyResult<AppResponse> {
         match &offer_asset.info {
             AssetInfo::Token { contract_addr } => {
@@ -256,9 +282,11 @@ impl Helper {
                     amount: offer_asset.amount,
                     msg: to_json_binary(&Cw20HookMsg::Swap {
                         ask_asset_info,
-                        belief_price: None,
+                        belief_price,
                         max_spread: None,
                         to: None,
+                        strict_belief_price,
+                        referral: None,
                     })
                     .unwrap(),
                 };
@@ -277,9 +305,11 @@ impl Helper {
                 let msg = ExecuteMsg::Swap {
                     offer_asset: offer_asset.clone(),
                     ask_asset_info,
-                    belief_price: None,
+                    belief_price,
                     max_spread: None,
                     to: None,
+                    strict_belief_price,
+                    referral: None,
                 };
 
                 self.app
@@ -316,6 +346,21 @@ impl Helper {
         )
     }
 
+    pub fn query_share(&self, amount: u128) -> StdResult<Vec<Asset>> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::Share {
+                amount: Uint128::new(amount),
+            },
+        )
+    }
+
+    pub fn query_compute_d(&self) -> StdResult<Uint128> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::QueryComputeD {})
+    }
+
     pub fn query_prices(&self) -> StdResult<CumulativePricesResponse> {
         self.app
             .wrap()
@@ -1,15 +1,15 @@
 #![cfg(not(tarpaulin_include))]
 
-use astroport::asset::{native_asset_info, Asset, AssetInfo, PairInfo};
+use astroport::asset::{native_asset_info, Asset, AssetInfo, PairInfo, MINIMUM_LIQUIDITY_AMOUNT};
 use astroport::factory::{
     ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
     QueryMsg as FactoryQueryMsg,
 };
 use astroport::observation::OracleObservation;
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    PoolResponse, QueryMsg, StablePoolConfig, StablePoolParams, StablePoolUpdateParams,
-    MAX_FEE_SHARE_BPS, TWAP_PRECISION,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, DecodedConfigResponse, ExecuteMsg,
+    InstantiateMsg, PoolResponse, QueryMsg, StablePoolConfig, StablePoolParams,
+    StablePoolUpdateParams, MAX_FEE_SHARE_BPS, TWAP_PRECISION,
 };
 
 use astroport_pair_stable::error::ContractError;
@@ -240,6 +240,88 @@ fn instantiate_pair(mut router: &mut TestApp, owner: &Addr) -> Addr {
     pair
 }
 
+#[test]
+fn test_minimum_liquidity_locked_on_first_provide() {
+    let owner = Addr::unchecked("owner");
+    let alice_address = Addr::unchecked("alice");
+
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+    router
+        .send_tokens(
+            owner.clone(),
+            alice_address.clone(),
+            &[
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(200_000u128),
+                },
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(200_000u128),
+                },
+            ],
+        )
+        .unwrap();
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+    let lp_token = router
+        .wrap()
+        .query_wasm_smart::<PairInfo>(pair_instance.clone(), &QueryMsg::Pair {})
+        .unwrap()
+        .liquidity_token;
+
+    let (msg, coins) =
+        provide_liquidity_msg(Uint128::new(100000), Uint128::new(100000), None, None);
+    router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    // The minimum liquidity is minted to the pair itself and stays locked there
+    let locked = router
+        .wrap()
+        .query_balance(&pair_instance, &lp_token)
+        .unwrap()
+        .amount;
+    assert_eq!(locked, MINIMUM_LIQUIDITY_AMOUNT);
+    let alice_share = router
+        .wrap()
+        .query_balance(&alice_address, &lp_token)
+        .unwrap()
+        .amount;
+    assert_eq!(alice_share.u128(), 199000);
+    assert_eq!(
+        router.wrap().query_supply(&lp_token).unwrap().amount,
+        alice_share + MINIMUM_LIQUIDITY_AMOUNT
+    );
+
+    // Subsequent provides don't lock anything
+    let (msg, coins) =
+        provide_liquidity_msg(Uint128::new(100000), Uint128::new(100000), None, None);
+    router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+    assert_eq!(
+        router
+            .wrap()
+            .query_balance(&pair_instance, &lp_token)
+            .unwrap()
+            .amount,
+        MINIMUM_LIQUIDITY_AMOUNT
+    );
+}
+
 #[test]
 fn test_provide_and_withdraw_liquidity() {
     let owner = Addr::unchecked("owner");
@@ -744,6 +826,8 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -876,6 +960,8 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -892,6 +978,8 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -1116,6 +1204,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -1287,6 +1377,36 @@ fn create_pair_with_same_assets() {
     )
 }
 
+#[test]
+fn query_decoded_config() {
+    let owner = Addr::unchecked(OWNER);
+    let mut router = mock_app(owner.clone(), vec![]);
+    let pair = instantiate_pair(&mut router, &owner);
+
+    let config: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(pair.clone(), &QueryMsg::Config {})
+        .unwrap();
+    let params: StablePoolConfig = from_json(&config.params.unwrap()).unwrap();
+
+    let decoded: DecodedConfigResponse<StablePoolConfig> = router
+        .wrap()
+        .query_wasm_smart(pair, &QueryMsg::DecodedConfig {})
+        .unwrap();
+    assert_eq!(decoded.params, params);
+    assert_eq!(decoded.params.amp, Decimal::from_ratio(100u8, 1u8));
+    assert_eq!(decoded.owner, config.owner);
+    assert_eq!(decoded.factory_addr, config.factory_addr);
+    assert_eq!(decoded.block_time_last, config.block_time_last);
+
+    // 5 bps total fee and 50% of it goes to the maker
+    assert_eq!(
+        decoded.total_fee_percent,
+        Decimal::from_str("0.05").unwrap()
+    );
+    assert_eq!(decoded.maker_fee_percent, Decimal::percent(5000));
+}
+
 #[test]
 fn update_pair_config() {
     let owner = Addr::unchecked(OWNER);
@@ -1769,6 +1889,8 @@ fn check_observe_queries() {
         belief_price: None,
         max_spread: None,
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
@@ -2012,6 +2134,71 @@ fn provide_liquidity_with_autostaking_to_generator() {
         .unwrap();
 
     assert_eq!(amount, Uint128::new(199999000));
+
+    // Auto-stake on behalf of another receiver
+    let bob_address = Addr::unchecked("bob");
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::new(10_000_000),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                amount: Uint128::new(10_000_000),
+            },
+        ],
+        slippage_tolerance: None,
+        auto_stake: Some(true),
+        receiver: Some(bob_address.to_string()),
+        min_lp_to_receive: None,
+    };
+    router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &msg,
+            &[
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(10_000_000),
+                },
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(10_000_000),
+                },
+            ],
+        )
+        .unwrap();
+
+    let amount: Uint128 = router
+        .wrap()
+        .query_wasm_smart(
+            generator_instance.to_string(),
+            &astroport::incentives::QueryMsg::Deposit {
+                lp_token: lp_token_address.to_string(),
+                user: bob_address.to_string(),
+            },
+        )
+        .unwrap();
+    // Balanced provide mints ~10% of the total supply (allowing for invariant rounding)
+    assert!(
+        (19_999_900..=20_000_000).contains(&amount.u128()),
+        "unexpected amount {amount}"
+    );
+
+    // Neither the sender nor the receiver hold LP tokens directly
+    for addr in [&alice_address, &bob_address] {
+        let balance = router
+            .wrap()
+            .query_balance(addr, &lp_token_address)
+            .unwrap();
+        assert_eq!(balance.amount, Uint128::zero());
+    }
 }
 
 #[test]
@@ -2485,6 +2672,8 @@ fn test_fee_share(
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -1,15 +1,20 @@
 #![cfg(not(tarpaulin_include))]
 
-use cosmwasm_std::{Addr, Decimal, StdError};
+use cosmwasm_std::{from_json, Addr, Decimal, SignedDecimal, StdError, Uint128};
 use itertools::Itertools;
 use std::str::FromStr;
 
-use astroport::asset::AssetInfoExt;
+use astroport::asset::{AssetInfo, AssetInfoExt};
 use astroport::cosmwasm_ext::AbsDiff;
 use astroport::observation::OracleObservation;
+use astroport::pair::{
+    ConfigResponse, PoolResponse, PoolSnapshotResponse, QueryMsg, StablePoolConfig,
+    StablePoolUpdateParams, SwapFeeBreakdownResponse,
+};
 use astroport_pair_stable::error::ContractError;
 use astroport_test::coins::TestCoin;
 use astroport_test::convert::f64_to_dec;
+use astroport_test::cw_multi_test::AppResponse;
 use helper::AppExtension;
 
 use crate::helper::Helper;
@@ -284,6 +289,416 @@ fn check_swaps() {
     assert_eq!(99_949011, helper.coin_balance(&test_coins[1], &user));
 }
 
+#[test]
+fn check_max_provide_imbalance() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let err = helper
+        .update_config(
+            &Addr::unchecked("random"),
+            &StablePoolUpdateParams::SetMaxProvideImbalance {
+                max_provide_imbalance: Some(Decimal::percent(10)),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    helper
+        .update_config(
+            &owner,
+            &StablePoolUpdateParams::SetMaxProvideImbalance {
+                max_provide_imbalance: Some(Decimal::percent(10)),
+            },
+        )
+        .unwrap();
+
+    let user = Addr::unchecked("user");
+
+    // Balanced provide passes
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(1000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1000_000000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    helper.provide_liquidity(&user, &assets, None).unwrap();
+
+    // Wildly imbalanced provide is rejected
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(1000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    let err = helper.provide_liquidity(&user, &assets, None).unwrap_err();
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::ProvideImbalanceExceeded { max_imbalance, .. } if max_imbalance == Decimal::percent(10)
+    ));
+
+    // Single-sided provide is rejected as well
+    let err = helper
+        .provide_liquidity(
+            &user,
+            &[
+                helper.assets[&test_coins[0]].with_balance(100_000000u128),
+                helper.assets[&test_coins[1]].with_balance(0u128),
+            ],
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::ProvideImbalanceExceeded { .. }
+    ));
+
+    // Removing the limit allows imbalanced provides again
+    helper
+        .update_config(
+            &owner,
+            &StablePoolUpdateParams::SetMaxProvideImbalance {
+                max_provide_imbalance: None,
+            },
+        )
+        .unwrap();
+    helper.provide_liquidity(&user, &assets, None).unwrap();
+}
+
+#[test]
+fn check_pause() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let err = helper
+        .update_config(
+            &Addr::unchecked("random"),
+            &StablePoolUpdateParams::SetPaused { paused: true },
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    helper
+        .update_config(&owner, &StablePoolUpdateParams::SetPaused { paused: true })
+        .unwrap();
+
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let params: StablePoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert!(params.paused);
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+
+    // Swaps and provides are blocked
+    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
+    assert_eq!(ContractError::PoolPaused {}, err.downcast().unwrap());
+    let cw20_offer = helper.assets[&test_coins[1]].with_balance(1000_000000u128);
+    helper.give_me_money(&[cw20_offer.clone()], &user);
+    let err = helper.swap(&user, &cw20_offer, None).unwrap_err();
+    assert_eq!(ContractError::PoolPaused {}, err.downcast().unwrap());
+    let err = helper
+        .provide_liquidity(&user, &[offer_asset.clone()], None)
+        .unwrap_err();
+    assert_eq!(ContractError::PoolPaused {}, err.downcast().unwrap());
+
+    // Withdrawals are still allowed
+    helper
+        .withdraw_liquidity(&owner, 1000_000000, vec![], None)
+        .unwrap();
+
+    helper
+        .update_config(&owner, &StablePoolUpdateParams::SetPaused { paused: false })
+        .unwrap();
+    helper.swap(&user, &offer_asset, None).unwrap();
+}
+
+#[test]
+fn check_precision_override() {
+    let owner = Addr::unchecked("owner");
+
+    // The cw20 token actually has 6 decimals but reports 8
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20precise("USDC", 8)];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let override_msg = StablePoolUpdateParams::OverridePrecision {
+        asset_info: helper.assets[&test_coins[1]].clone(),
+        precision: 6,
+    };
+
+    let err = helper
+        .update_config(&Addr::unchecked("random"), &override_msg)
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let err = helper
+        .update_config(
+            &owner,
+            &StablePoolUpdateParams::OverridePrecision {
+                asset_info: helper.assets[&test_coins[0]].clone(),
+                precision: 8,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PrecisionOverrideNotAllowed {},
+        err.downcast().unwrap()
+    );
+
+    let err = helper
+        .update_config(
+            &owner,
+            &StablePoolUpdateParams::OverridePrecision {
+                asset_info: helper.assets[&test_coins[1]].clone(),
+                precision: 19,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidPrecision(19, 18),
+        err.downcast().unwrap()
+    );
+
+    let err = helper
+        .update_config(
+            &owner,
+            &StablePoolUpdateParams::OverridePrecision {
+                asset_info: helper.assets[&test_coins[1]].clone(),
+                precision: 8,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PrecisionUnchanged(helper.assets[&test_coins[1]].to_string(), 8),
+        err.downcast().unwrap()
+    );
+
+    helper.update_config(&owner, &override_msg).unwrap();
+
+    // The override is a one-time action
+    let err = helper.update_config(&owner, &override_msg).unwrap_err();
+    assert_eq!(
+        ContractError::PrecisionAlreadyOverridden(helper.assets[&test_coins[1]].to_string()),
+        err.downcast().unwrap()
+    );
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    // Swap math treats both assets as 6 decimal ones
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(helper.assets[&test_coins[0]].clone()))
+        .unwrap();
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    let return_amount = sim_resp.return_amount.u128();
+    assert!(return_amount > 99_900000 && return_amount < 100_000000);
+    assert_eq!(return_amount, helper.coin_balance(&test_coins[0], &user));
+
+    // Overrides are not allowed once the pool has liquidity
+    let err = helper
+        .update_config(
+            &owner,
+            &StablePoolUpdateParams::OverridePrecision {
+                asset_info: helper.assets[&test_coins[1]].clone(),
+                precision: 8,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::PrecisionOverrideNotAllowed {},
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn check_pool_snapshot() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let snapshot: PoolSnapshotResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::PoolSnapshot {})
+        .unwrap();
+    let pool: PoolResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Pool {})
+        .unwrap();
+
+    assert_eq!(snapshot.assets, pool.assets);
+    assert_eq!(snapshot.total_share, pool.total_share);
+    assert!(!snapshot.total_share.is_zero());
+    assert_eq!(snapshot.total_fee, Decimal::from_ratio(5u8, 10000u16));
+    assert_eq!(snapshot.maker_fee, Decimal::percent(50));
+}
+
+#[test]
+fn check_swap_fee_breakdown() {
+    let owner = Addr::unchecked("owner");
+
+    // Stable pools support only 2 assets
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000_000000u128);
+    let breakdown: SwapFeeBreakdownResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.pair_addr,
+            &QueryMsg::SimulateSwapWithFeeBreakdown {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(breakdown.amp, Decimal::from_ratio(100u8, 1u8));
+
+    let simulation = helper.simulate_swap(&offer_asset, None).unwrap();
+    assert_eq!(breakdown.return_amount, simulation.return_amount);
+    assert_eq!(breakdown.spread_amount, simulation.spread_amount);
+    assert_eq!(breakdown.commission_amount, simulation.commission_amount);
+
+    let user = Addr::unchecked("user");
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let resp = helper.swap(&user, &offer_asset, None).unwrap();
+
+    let swap_attr = |key: &str| {
+        resp.events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+            .unwrap()
+    };
+    assert_eq!(
+        swap_attr("return_amount"),
+        breakdown.return_amount.to_string()
+    );
+    assert_eq!(
+        swap_attr("spread_amount"),
+        breakdown.spread_amount.to_string()
+    );
+    assert_eq!(
+        swap_attr("commission_amount"),
+        breakdown.commission_amount.to_string()
+    );
+}
+
+#[test]
+fn check_pool_d_attribute() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let pool_d_attr = |resp: &AppResponse| {
+        resp.events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "pool_d")
+            .map(|attr| attr.value.clone())
+    };
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+    ];
+    // The attribute is opt-in
+    let resp = helper.provide_liquidity(&owner, &assets, None).unwrap();
+    assert_eq!(pool_d_attr(&resp), None);
+
+    let err = helper
+        .update_config(
+            &Addr::unchecked("random"),
+            &StablePoolUpdateParams::SetEmitPoolD { enabled: true },
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    helper
+        .update_config(
+            &owner,
+            &StablePoolUpdateParams::SetEmitPoolD { enabled: true },
+        )
+        .unwrap();
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let params: StablePoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert!(params.emit_pool_d);
+
+    let resp = helper.provide_liquidity(&owner, &assets, None).unwrap();
+    assert_eq!(
+        pool_d_attr(&resp),
+        Some(helper.query_compute_d().unwrap().to_string())
+    );
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let resp = helper.swap(&user, &offer_asset, None).unwrap();
+    assert_eq!(
+        pool_d_attr(&resp),
+        Some(helper.query_compute_d().unwrap().to_string())
+    );
+
+    let resp = helper
+        .withdraw_liquidity(&owner, 10_000_000000, vec![], None)
+        .unwrap();
+    assert_eq!(
+        pool_d_attr(&resp),
+        Some(helper.query_compute_d().unwrap().to_string())
+    );
+}
+
 #[test]
 fn check_wrong_initializations() {
     let owner = Addr::unchecked("owner");
@@ -414,6 +829,61 @@ fn check_withdraw_charges_fees() {
     );
 }
 
+#[test]
+fn check_share_matches_withdraw() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::cw20("USDD"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    // Make the pool imbalanced
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(5_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper
+        .swap(
+            &user,
+            &offer_asset,
+            Some(helper.assets[&test_coins[1]].clone()),
+        )
+        .unwrap();
+
+    let lp_amount = helper.native_balance(&helper.lp_token, &owner) / 3;
+    let share = helper.query_share(lp_amount).unwrap();
+    assert_eq!(share.len(), 3);
+
+    let balances_before = test_coins
+        .iter()
+        .map(|coin| helper.coin_balance(coin, &owner))
+        .collect_vec();
+    helper
+        .withdraw_liquidity(&owner, lp_amount, vec![], None)
+        .unwrap();
+
+    for (coin, before) in test_coins.iter().zip(balances_before) {
+        let received = helper.coin_balance(coin, &owner) - before;
+        let expected = share
+            .iter()
+            .find(|asset| asset.info == helper.assets[coin])
+            .unwrap()
+            .amount
+            .u128();
+        assert_eq!(received, expected);
+    }
+}
+
 #[test]
 fn check_twap_based_prices() {
     let owner = Addr::unchecked("owner");
@@ -602,3 +1072,292 @@ fn check_pool_prices() {
     );
     helper.app.next_block(10);
 }
+
+#[test]
+fn check_slippage_for_trade() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let slippage_at_amp = |amp: u64| -> Decimal {
+        let mut helper = Helper::new(&owner, test_coins.clone(), amp, None).unwrap();
+
+        let assets = vec![
+            helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+            helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        ];
+        helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &helper.pair_addr,
+                &QueryMsg::SlippageForTrade {
+                    offer_asset: helper.assets[&test_coins[0]].with_balance(10_000_000000u128),
+                },
+            )
+            .unwrap()
+    };
+
+    let low_amp_slippage = slippage_at_amp(10);
+    let high_amp_slippage = slippage_at_amp(100);
+
+    // The same trade incurs less slippage in a pool with higher amp
+    assert!(!high_amp_slippage.is_zero());
+    assert!(low_amp_slippage > high_amp_slippage);
+}
+
+#[test]
+fn check_imbalance() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let query_imbalance = |helper: &Helper| -> Vec<(AssetInfo, SignedDecimal)> {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.pair_addr, &QueryMsg::Imbalance {})
+            .unwrap()
+    };
+
+    // A balanced pool is at the target
+    let imbalance = query_imbalance(&helper);
+    assert_eq!(imbalance.len(), 2);
+    for (i, (info, deviation)) in imbalance.iter().enumerate() {
+        assert_eq!(*info, helper.assets[&test_coins[i]]);
+        assert!(deviation.abs() < SignedDecimal::from_str("0.0001").unwrap());
+    }
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(20_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    // ~120k uluna and ~80k USDC with D close to 200k
+    let imbalance = query_imbalance(&helper);
+    assert!(imbalance[0].1 > SignedDecimal::percent(9));
+    assert!(imbalance[1].1 < SignedDecimal::percent(-9));
+    assert!((imbalance[0].1 + imbalance[1].1).abs() < SignedDecimal::percent(1));
+}
+
+#[test]
+fn check_belief_price_multi_asset() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::cw20("USDD"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1000_000000u128);
+    helper.give_me_money(
+        &[helper.assets[&test_coins[1]].with_balance(2000_000000u128)],
+        &user,
+    );
+    let ask_asset_info = helper.assets[&test_coins[2]].clone();
+
+    // belief_price is the amount of USDC paid per one USDD
+    let sim = helper
+        .simulate_swap(&offer_asset, Some(ask_asset_info.clone()))
+        .unwrap();
+    let execution_price = Decimal::from_ratio(offer_asset.amount, sim.return_amount);
+
+    // The pool cannot return 1 USDD per 1 USDC after fees
+    let err = helper
+        .swap_with_belief_price(
+            &user,
+            &offer_asset,
+            Some(ask_asset_info.clone()),
+            Some(Decimal::one()),
+            true,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Std(StdError::generic_err(
+            "Operation is executed at a price worse than belief price"
+        )),
+        err.downcast().unwrap()
+    );
+
+    // Belief price must be specified in strict mode
+    let err = helper
+        .swap_with_belief_price(
+            &user,
+            &offer_asset,
+            Some(ask_asset_info.clone()),
+            None,
+            true,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Std(StdError::generic_err(
+            "Strict belief price check requires belief_price to be set"
+        )),
+        err.downcast().unwrap()
+    );
+
+    // A slightly worse belief price than the simulated one passes
+    helper
+        .swap_with_belief_price(
+            &user,
+            &offer_asset,
+            Some(ask_asset_info.clone()),
+            Some(execution_price + Decimal::permille(1)),
+            true,
+        )
+        .unwrap();
+    assert_eq!(
+        sim.return_amount.u128(),
+        helper.coin_balance(&test_coins[2], &user)
+    );
+
+    // The first swap moved the USDC/USDD price so the same belief price is violated now
+    let err = helper
+        .swap_with_belief_price(
+            &user,
+            &offer_asset,
+            Some(ask_asset_info),
+            Some(execution_price),
+            true,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::Std(StdError::generic_err(
+            "Operation is executed at a price worse than belief price"
+        )),
+        err.downcast().unwrap()
+    );
+    assert_eq!(1000_000000, helper.coin_balance(&test_coins[1], &user));
+}
+
+#[test]
+fn check_max_single_asset_out() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    // Imbalance the pool so the withdraw share is not symmetric
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(20_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let lp_amount = 10_000_000000u128;
+    let ask_info = helper.assets[&test_coins[1]].clone();
+    let max_out: Uint128 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.pair_addr,
+            &QueryMsg::MaxSingleAssetOut {
+                lp_amount: lp_amount.into(),
+                asset_info: ask_info.clone(),
+            },
+        )
+        .unwrap();
+
+    // The share of the asset alone is less than the maximum
+    let share = helper.query_share(lp_amount).unwrap();
+    assert!(max_out > share[1].amount);
+
+    // Withdraw the share and swap the other asset into the chosen one
+    let ask_before = helper.coin_balance(&test_coins[1], &owner);
+    helper
+        .withdraw_liquidity(&owner, lp_amount, vec![], None)
+        .unwrap();
+    helper.swap(&owner, &share[0], Some(ask_info)).unwrap();
+    let received = helper.coin_balance(&test_coins[1], &owner) - ask_before;
+    assert_eq!(max_out.u128(), received);
+
+    let err = helper
+        .app
+        .wrap()
+        .query_wasm_smart::<Uint128>(
+            &helper.pair_addr,
+            &QueryMsg::MaxSingleAssetOut {
+                lp_amount: Uint128::MAX,
+                asset_info: helper.assets[&test_coins[0]].clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Querier contract error: Generic error: LP amount exceeds the total share"
+        )
+    );
+}
+
+#[test]
+fn check_provide_omitted_assets_as_zero() {
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut lp_amounts = vec![];
+    for explicit_zero in [false, true] {
+        let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+        let assets = vec![
+            helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+            helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        ];
+        helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+        // The omitted asset may go first in the pool asset order
+        let mut assets = vec![helper.assets[&test_coins[1]].with_balance(10_000_000000u128)];
+        if explicit_zero {
+            assets.push(helper.assets[&test_coins[0]].with_balance(0u8));
+        }
+        helper.give_me_money(&assets, &user);
+
+        let simulated: Uint128 = helper
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &helper.pair_addr,
+                &QueryMsg::SimulateProvide {
+                    assets: assets.clone(),
+                    slippage_tolerance: None,
+                },
+            )
+            .unwrap();
+        helper.provide_liquidity(&user, &assets, None).unwrap();
+
+        let lp_amount = helper.native_balance(&helper.lp_token, &user);
+        assert_eq!(simulated.u128(), lp_amount);
+        lp_amounts.push(lp_amount);
+    }
+
+    assert_eq!(lp_amounts[0], lp_amounts[1]);
+}
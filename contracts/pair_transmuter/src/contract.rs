@@ -121,8 +121,16 @@ pub fn execute(
             offer_asset,
             to,
             ask_asset_info,
+            referral,
+            strict_belief_price,
             ..
-        } => swap(deps, info, offer_asset, ask_asset_info, to),
+        } => {
+            ensure!(
+                referral.is_none() && !strict_belief_price,
+                ContractError::NotSupported {}
+            );
+            swap(deps, info, offer_asset, ask_asset_info, to)
+        }
         ExecuteMsg::WithdrawLiquidity { assets, .. } => withdraw_liquidity(deps, env, info, assets),
         _ => Err(ContractError::NotSupported {}),
     }
@@ -269,6 +269,8 @@ impl Helper {
                         belief_price: None,
                         max_spread: None,
                         to,
+                        strict_belief_price: false,
+                        referral: None,
                     })
                     .unwrap(),
                 };
@@ -290,6 +292,8 @@ impl Helper {
                     belief_price: None,
                     max_spread: None,
                     to,
+                    strict_belief_price: false,
+                    referral: None,
                 };
 
                 self.app
@@ -660,6 +660,8 @@ fn test_drain_pool() {
                 belief_price: None,
                 max_spread: None,
                 to: None,
+                strict_belief_price: false,
+                referral: None,
             },
             &[],
         )
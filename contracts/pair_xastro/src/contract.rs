@@ -92,8 +92,16 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Swap {
-            offer_asset, to, ..
+            offer_asset,
+            to,
+            referral,
+            strict_belief_price,
+            ..
         } => {
+            ensure!(
+                referral.is_none() && !strict_belief_price,
+                ContractError::NotSupported {}
+            );
             offer_asset.assert_sent_native_token_balance(&info)?;
             swap(deps, info.sender, offer_asset, to)
         }
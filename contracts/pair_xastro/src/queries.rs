@@ -5,6 +5,7 @@ use astroport::pair::{
     ConfigResponse, PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse,
 };
 use astroport::querier::query_factory_config;
+use cw2::get_contract_version;
 
 use crate::contract::{predict_stake, predict_unstake};
 use crate::error::ContractError;
@@ -54,6 +55,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
                 commission_amount: Uint128::zero(),
             })?)
         }
+        QueryMsg::Version {} => Ok(to_json_binary(&get_contract_version(deps.storage)?)?),
         _ => Err(ContractError::NotSupported {}),
     }
 }
@@ -154,6 +154,7 @@ impl Helper {
             tracking_admin: owner.to_string(),
             tracking_code_id: tracker_code_id,
             token_factory_addr: TOKEN_FACTORY_MODULE.to_string(),
+            deposit_token_admin: None,
         };
         let staking = app
             .instantiate_contract(
@@ -244,6 +245,8 @@ impl Helper {
                     belief_price: None,
                     max_spread: None,
                     to,
+                    strict_belief_price: false,
+                    referral: None,
                 };
 
                 self.app
@@ -11,7 +11,7 @@ use cosmwasm_std::{
     MessageInfo, QuerierWrapper, Reply, Response, StdError, StdResult, SubMsg, SubMsgResponse,
     SubMsgResult, Uint128, Uint256, Uint64, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw_utils::{
     one_coin, parse_reply_instantiate_data, MsgInstantiateContractResponse, PaymentError,
@@ -24,7 +24,9 @@ use astroport::asset::{
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::PairType;
 use astroport::incentives::ExecuteMsg as IncentiveExecuteMsg;
-use astroport::pair::{ConfigResponse, ReplyIds, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE};
+use astroport::pair::{
+    assert_belief_price, ConfigResponse, ReplyIds, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
+};
 use astroport::pair::{
     CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
     ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
@@ -244,8 +246,14 @@ pub fn execute(
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
             ..
         } => {
+            if referral.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
+
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -262,6 +270,7 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
             )
         }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, info, params),
@@ -284,8 +293,14 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            strict_belief_price,
+            referral,
             ..
         } => {
+            if referral.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
+
             // Only asset contract can execute this message
             let mut authorized = false;
             let config = CONFIG.load(deps.storage)?;
@@ -317,8 +332,10 @@ pub fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                strict_belief_price,
             )
         }
+        Cw20HookMsg::SwapWithTarget { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -590,6 +607,8 @@ pub fn get_share_in_assets(pools: &[Asset], amount: Uint128, total_share: Uint12
 ///
 /// * **to** sets the recipient of the swap operation.
 ///
+/// * **strict_belief_price** if true, the swap fails when executed at a price worse than **belief_price**.
+///
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
 #[allow(clippy::too_many_arguments)]
 pub fn swap(
@@ -601,6 +620,7 @@ pub fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    strict_belief_price: bool,
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
 
@@ -663,6 +683,9 @@ pub fn swap(
         return_amount + commission_amount,
         spread_amount,
     )?;
+    if strict_belief_price {
+        assert_belief_price(belief_price, offer_asset.amount, return_amount)?;
+    }
 
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -871,6 +894,8 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::SimulateProvide { assets, slippage_tolerance }** Returns the amount of LP tokens that will be minted
 ///
 /// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
+///
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -894,6 +919,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             slippage_tolerance,
         } => to_json_binary(&query_simulate_provide(deps, assets, slippage_tolerance)?),
         QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(&query_share(deps, lp_amount)?),
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
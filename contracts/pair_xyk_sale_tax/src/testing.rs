@@ -833,6 +833,8 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -1063,6 +1065,8 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -1078,6 +1082,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: Some(Decimal::percent(50)),
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
     });
@@ -1201,6 +1207,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
     });
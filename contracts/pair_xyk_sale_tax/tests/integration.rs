@@ -761,6 +761,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -810,6 +812,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            strict_belief_price: false,
+            referral: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -1288,6 +1292,8 @@ fn asset_balances_tracking_works_correctly() {
         belief_price: None,
         max_spread: None,
         to: None,
+        strict_belief_price: false,
+        referral: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
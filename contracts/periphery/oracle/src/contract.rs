@@ -1,17 +1,21 @@
 use crate::error::ContractError;
+use crate::math::{exp, ln};
 use crate::migration::PRICE_LAST_V100;
 use crate::querier::{query_cumulative_prices, query_prices};
 use crate::state::{
-    get_precision, store_precisions, Config, PriceCumulativeLast, CONFIG, PRICE_LAST,
+    get_precision, store_precisions, Config, GeometricSample, PriceCumulativeLast, CONFIG,
+    GEOMETRIC_SAMPLES, INSTANTIATED_AT, KEEPER_REWARD, PRICE_LAST, REQUIRE_TWAP, UPDATERS,
 };
 use astroport::asset::{Asset, AssetInfo};
-use astroport::oracle::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use astroport::oracle::{
+    ExecuteMsg, InstantiateMsg, MigrateMsg, PriceComparisonResponse, QueryMsg,
+};
 use astroport::pair::TWAP_PRECISION;
 use astroport::querier::query_pair_info;
 
 use cosmwasm_std::{
     entry_point, to_json_binary, Binary, Decimal256, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Uint128, Uint256,
+    SignedDecimal256, StdError, StdResult, Uint128, Uint256,
 };
 use cw2::{get_contract_version, set_contract_version};
 
@@ -22,6 +26,8 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Time between two consecutive TWAP updates.
 pub const PERIOD: u64 = 86400;
+/// The number of latest periods used to calculate the geometric TWAP.
+pub const GEOMETRIC_TWAP_PERIODS: usize = 7;
 
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -63,6 +69,15 @@ pub fn instantiate(
         block_timestamp_last: env.block.time.seconds(),
     };
     PRICE_LAST.save(deps.storage, &price)?;
+    INSTANTIATED_AT.save(deps.storage, &env.block.time.seconds())?;
+
+    if msg.geometric_twap {
+        GEOMETRIC_SAMPLES.save(deps.storage, &vec![])?;
+    }
+
+    if msg.require_twap {
+        REQUIRE_TWAP.save(deps.storage, &true)?;
+    }
 
     Ok(Response::default())
 }
@@ -71,20 +86,32 @@ pub fn instantiate(
 ///
 /// ## Variants
 /// * **ExecuteMsg::Update {}** Updates the local TWAP values for the assets in the Astroport pool.
+///
+/// * **ExecuteMsg::SetUpdaters { updaters }** Sets or removes the list of addresses allowed to update prices.
+///
+/// * **ExecuteMsg::SetKeeperReward { reward }** Sets or removes the reward paid for successful updates.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Update {} => update(deps, env),
+        ExecuteMsg::Update {} => update(deps, env, info),
+        ExecuteMsg::SetUpdaters { updaters } => set_updaters(deps, info, updaters),
+        ExecuteMsg::SetKeeperReward { reward } => set_keeper_reward(deps, info, reward),
     }
 }
 
 /// Updates the local TWAP values for the tokens in the target Astroport pool.
-pub fn update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+pub fn update(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    if let Some(updaters) = UPDATERS.may_load(deps.storage)? {
+        if !updaters.contains(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+    }
+
     let config = CONFIG.load(deps.storage)?;
     let price_last = PRICE_LAST.load(deps.storage)?;
 
@@ -96,6 +123,10 @@ pub fn update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
         return Err(ContractError::WrongPeriod {});
     }
 
+    // Pairs accumulate prices in Uint128 with wrapping addition (like Uniswap V2), so a
+    // cumulative price may overflow and restart from zero between two updates.
+    // The wrapping subtraction yields the correct delta as long as the true delta over
+    // a single period fits into Uint128, which always holds for realistic prices.
     let mut average_prices = vec![];
     for (asset1_last, asset2_last, price_last) in price_last.cumulative_prices.iter() {
         for (asset1, asset2, price) in prices.cumulative_prices.iter() {
@@ -112,33 +143,151 @@ pub fn update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
         }
     }
 
+    if let Some(mut samples) = GEOMETRIC_SAMPLES.may_load(deps.storage)? {
+        // The logarithm of a zero price is undefined thus such periods are skipped
+        if average_prices.iter().all(|(_, _, price)| !price.is_zero()) {
+            let log_prices = average_prices
+                .iter()
+                .map(|(from, to, price)| Ok((from.clone(), to.clone(), ln(*price)?)))
+                .collect::<StdResult<Vec<_>>>()?;
+            samples.push(GeometricSample {
+                log_prices,
+                duration: time_elapsed,
+            });
+            if samples.len() > GEOMETRIC_TWAP_PERIODS {
+                samples.remove(0);
+            }
+            GEOMETRIC_SAMPLES.save(deps.storage, &samples)?;
+        }
+    }
+
     let prices = PriceCumulativeLast {
         cumulative_prices: prices.cumulative_prices,
         average_prices,
         block_timestamp_last: env.block.time.seconds(),
     };
     PRICE_LAST.save(deps.storage, &prices)?;
-    Ok(Response::default())
+
+    let mut response = Response::default();
+    if let Some(reward) = KEEPER_REWARD.may_load(deps.storage)? {
+        let balance = reward
+            .info
+            .query_pool(&deps.querier, &env.contract.address)?;
+        // Updates must not depend on the reward balance thus the payment is simply skipped
+        if balance >= reward.amount {
+            response = response
+                .add_attribute("keeper_reward", reward.to_string())
+                .add_message(reward.into_msg(&info.sender)?);
+        }
+    }
+
+    Ok(response)
+}
+
+/// Sets the addresses allowed to call [`ExecuteMsg::Update`].
+/// If `updaters` is `None`, updates become permissionless.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_updaters(
+    deps: DepsMut,
+    info: MessageInfo,
+    updaters: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match updaters {
+        Some(updaters) => {
+            let updaters = updaters
+                .iter()
+                .map(|addr| deps.api.addr_validate(addr))
+                .collect::<StdResult<Vec<_>>>()?;
+            UPDATERS.save(deps.storage, &updaters)?;
+        }
+        None => UPDATERS.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attribute("action", "set_updaters"))
+}
+
+/// Sets the reward paid to the caller of a successful [`ExecuteMsg::Update`].
+/// If `reward` is `None`, keepers are not rewarded anymore.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_keeper_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    reward: Option<Asset>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match reward {
+        Some(reward) => {
+            reward.info.check(deps.api)?;
+            if reward.amount.is_zero() {
+                return Err(StdError::generic_err("Keeper reward must be positive").into());
+            }
+            KEEPER_REWARD.save(deps.storage, &reward)?;
+        }
+        None => KEEPER_REWARD.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attribute("action", "set_keeper_reward"))
 }
 
 /// Exposes all the queries available in the contract.
 ///
 /// ## Queries
 /// * **QueryMsg::Consult { token, amount }** Validates assets and calculates a new average
-/// amount with updated precision
+/// amount with updated precision. Falls back to the spot price only within the first period
+/// unless `require_twap` is enabled
+///
+/// * **QueryMsg::GeometricTwap { token, amount }** Calculates the amount using the geometric mean
+/// of the latest period average prices
+///
+/// * **QueryMsg::PriceComparison { token, amount }** Returns the amount priced at the current
+/// spot price and at the latest TWAP
+///
+/// * **QueryMsg::Updaters {}** Returns the addresses allowed to update prices
+///
+/// * **QueryMsg::KeeperReward {}** Returns the reward paid to the caller of a successful update
+///
+/// * **QueryMsg::TrackedAssets {}** Returns the assets for which the oracle provides prices
+///
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Consult { token, amount } => to_json_binary(&consult(deps, token, amount)?),
+        QueryMsg::Consult { token, amount } => to_json_binary(&consult(deps, &env, token, amount)?),
+        QueryMsg::GeometricTwap { token, amount } => {
+            to_json_binary(&consult_geometric(deps, token, amount)?)
+        }
+        QueryMsg::PriceComparison { token, amount } => {
+            to_json_binary(&price_comparison(deps, &env, token, amount)?)
+        }
+        QueryMsg::Updaters {} => to_json_binary(&UPDATERS.may_load(deps.storage)?),
+        QueryMsg::KeeperReward {} => to_json_binary(&KEEPER_REWARD.may_load(deps.storage)?),
+        QueryMsg::TrackedAssets {} => to_json_binary(&CONFIG.load(deps.storage)?.asset_infos),
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
     }
 }
 
 /// Multiplies a token amount by its latest TWAP value.
+/// A zero TWAP is expected only until the first period is accumulated, so the spot price
+/// is served within the first [`PERIOD`] after instantiation and an error is returned afterwards.
 /// * **token** token for which we multiply its TWAP value by an amount.
 ///
 /// * **amount** amount of tokens we multiply the TWAP by.
 fn consult(
     deps: Deps,
+    env: &Env,
     token: AssetInfo,
     amount: Uint128,
 ) -> Result<Vec<(AssetInfo, Uint256)>, StdError> {
@@ -153,30 +302,47 @@ fn consult(
     }
 
     if average_prices.is_empty() {
-        return Err(StdError::generic_err("Invalid Token"));
+        let tracked_assets = config
+            .asset_infos
+            .iter()
+            .map(|asset_info| asset_info.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(StdError::generic_err(format!(
+            "Invalid Token {token}. The oracle only tracks: {tracked_assets}"
+        )));
     }
 
     // Get the token's precision
     let p = get_precision(deps.storage, &token)?;
     let one = Uint128::new(10_u128.pow(p.into()));
+    let require_twap = REQUIRE_TWAP.may_load(deps.storage)?.unwrap_or_default();
+    // Oracles instantiated before the timestamp was recorded are long past their first period
+    let first_period = INSTANTIATED_AT
+        .may_load(deps.storage)?
+        .map(|instantiated_at| env.block.time.seconds() < instantiated_at + PERIOD)
+        .unwrap_or_default();
 
     average_prices
         .iter()
         .map(|(asset, price_average)| {
             if price_average.is_zero() {
-                let price = query_prices(
-                    deps.querier,
-                    config.pair.contract_addr.clone(),
-                    Asset {
-                        info: token.clone(),
-                        amount: one,
-                    },
-                    Some(asset.clone()),
-                )?
-                .return_amount;
+                // Don't fall back to the manipulable spot price if TWAP is required
+                if require_twap {
+                    return Err(StdError::generic_err(
+                        ContractError::PriceNotReady {}.to_string(),
+                    ));
+                }
+                // A zero TWAP after the first period indicates a problem
+                if !first_period {
+                    return Err(StdError::generic_err(
+                        ContractError::ZeroTwap {}.to_string(),
+                    ));
+                }
+
                 Ok((
                     asset.clone(),
-                    Uint256::from(price).multiply_ratio(Uint256::from(amount), Uint256::from(one)),
+                    spot_price(deps, &config, &token, asset, amount, one)?,
                 ))
             } else {
                 let price_precision = Uint256::from(10_u128.pow(TWAP_PRECISION.into()));
@@ -189,6 +355,101 @@ fn consult(
         .collect::<Result<Vec<(AssetInfo, Uint256)>, StdError>>()
 }
 
+/// Multiplies a token amount by the pool spot price which is simulated for one whole token.
+fn spot_price(
+    deps: Deps,
+    config: &Config,
+    token: &AssetInfo,
+    ask_asset: &AssetInfo,
+    amount: Uint128,
+    one: Uint128,
+) -> StdResult<Uint256> {
+    let price = query_prices(
+        deps.querier,
+        config.pair.contract_addr.clone(),
+        Asset {
+            info: token.clone(),
+            amount: one,
+        },
+        Some(ask_asset.clone()),
+    )?
+    .return_amount;
+
+    Ok(Uint256::from(price).multiply_ratio(Uint256::from(amount), Uint256::from(one)))
+}
+
+/// Prices a token amount at the pool spot price and at the latest TWAP.
+/// * **token** token for which we compute the prices.
+///
+/// * **amount** amount of tokens we multiply the prices by.
+fn price_comparison(
+    deps: Deps,
+    env: &Env,
+    token: AssetInfo,
+    amount: Uint128,
+) -> StdResult<Vec<(AssetInfo, PriceComparisonResponse)>> {
+    let config = CONFIG.load(deps.storage)?;
+    let twaps = consult(deps, env, token.clone(), amount)?;
+
+    let p = get_precision(deps.storage, &token)?;
+    let one = Uint128::new(10_u128.pow(p.into()));
+
+    twaps
+        .into_iter()
+        .map(|(asset, twap)| {
+            let spot = spot_price(deps, &config, &token, &asset, amount, one)?;
+            Ok((asset, PriceComparisonResponse { spot, twap }))
+        })
+        .collect()
+}
+
+/// Multiplies a token amount by the time-weighted geometric mean of the latest
+/// [`GEOMETRIC_TWAP_PERIODS`] period average prices.
+/// * **token** token for which we multiply its geometric TWAP value by an amount.
+///
+/// * **amount** amount of tokens we multiply the geometric TWAP by.
+fn consult_geometric(
+    deps: Deps,
+    token: AssetInfo,
+    amount: Uint128,
+) -> StdResult<Vec<(AssetInfo, Uint256)>> {
+    let samples = GEOMETRIC_SAMPLES
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("Geometric TWAP is not enabled"))?;
+    if samples.is_empty() {
+        return Err(StdError::generic_err("No prices were accumulated yet"));
+    }
+
+    let total_duration: u64 = samples.iter().map(|sample| sample.duration).sum();
+    let config = CONFIG.load(deps.storage)?;
+    let price_precision = Uint256::from(10_u128.pow(TWAP_PRECISION.into()));
+
+    config
+        .asset_infos
+        .iter()
+        .filter(|asset_info| !asset_info.equal(&token))
+        .map(|asset_info| {
+            let mut log_sum = SignedDecimal256::zero();
+            for sample in &samples {
+                let (_, _, log_price) = sample
+                    .log_prices
+                    .iter()
+                    .find(|(from, to, _)| from.equal(&token) && to.equal(asset_info))
+                    .ok_or_else(|| {
+                        StdError::generic_err(format!("Invalid Token {token}. It is not tracked"))
+                    })?;
+                log_sum += *log_price * SignedDecimal256::from_ratio(sample.duration, 1);
+            }
+            let price = exp(log_sum / SignedDecimal256::from_ratio(total_duration, 1))?;
+
+            Ok((
+                asset_info.clone(),
+                Uint256::from(amount) * price / price_precision,
+            ))
+        })
+        .collect()
+}
+
 /// Manages the contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
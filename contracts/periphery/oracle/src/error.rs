@@ -13,6 +13,14 @@ pub enum ContractError {
     #[error("Period not elapsed")]
     WrongPeriod {},
 
+    #[error("Price is not ready yet. Wait for the first full period to be accumulated")]
+    PriceNotReady {},
+
+    #[error(
+        "TWAP is zero after the first period. The oracle is not updated or the price is too small"
+    )]
+    ZeroTwap {},
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 }
@@ -0,0 +1,88 @@
+use cosmwasm_std::{Decimal256, Int256, SignedDecimal256, StdError, StdResult, Uint256};
+
+/// ln(2) with 18 decimal places
+const LN_2: SignedDecimal256 = SignedDecimal256::raw(693_147_180_559_945_309);
+
+/// Calculates the natural logarithm of a positive decimal.
+/// The argument is normalized to m * 2^k where m ∈ \[1, 2), then
+/// ln(m) = 2 * atanh((m - 1) / (m + 1)) is computed with its Taylor series.
+pub(crate) fn ln(x: Decimal256) -> StdResult<SignedDecimal256> {
+    if x.is_zero() {
+        return Err(StdError::generic_err("Logarithm of zero is undefined"));
+    }
+
+    let two = Decimal256::from_ratio(2u8, 1u8);
+    let mut m = x;
+    let mut k = 0i64;
+    while m >= two {
+        m /= two;
+        k += 1;
+    }
+    while m < Decimal256::one() {
+        m *= two;
+        k -= 1;
+    }
+
+    let z = (m - Decimal256::one()) / (m + Decimal256::one());
+    let z2 = z * z;
+    let mut term = z;
+    let mut sum = z;
+    let mut n = 1u64;
+    loop {
+        term *= z2;
+        if term.is_zero() {
+            break;
+        }
+        sum += term / Decimal256::from_ratio(2 * n + 1, 1u8);
+        n += 1;
+    }
+
+    Ok(to_signed(sum + sum)? + SignedDecimal256::from_ratio(k, 1) * LN_2)
+}
+
+/// Calculates e^x. The argument is split into k * ln(2) + r where r ∈ \[0, ln(2)),
+/// e^r is computed with its Taylor series and then scaled by 2^k.
+pub(crate) fn exp(x: SignedDecimal256) -> StdResult<Decimal256> {
+    let mut k = (x / LN_2).floor();
+    let mut r = x - k * LN_2;
+    // Compensate the rounding of the division
+    if r.is_negative() {
+        k -= SignedDecimal256::one();
+        r += LN_2;
+    }
+    let r = to_unsigned(r)?;
+
+    let mut term = Decimal256::one();
+    let mut sum = Decimal256::one();
+    let mut n = 1u64;
+    loop {
+        term = term * r / Decimal256::from_ratio(n, 1u8);
+        if term.is_zero() {
+            break;
+        }
+        sum += term;
+        n += 1;
+    }
+
+    let two = Decimal256::from_ratio(2u8, 1u8);
+    let k = k.to_int_floor();
+    let mut steps = Int256::zero();
+    while steps > k {
+        sum /= two;
+        steps -= Int256::from(1);
+    }
+    while steps < k {
+        sum = sum.checked_mul(two)?;
+        steps += Int256::from(1);
+    }
+
+    Ok(sum)
+}
+
+fn to_signed(value: Decimal256) -> StdResult<SignedDecimal256> {
+    Ok(SignedDecimal256::new(Int256::try_from(value.atomics())?))
+}
+
+fn to_unsigned(value: SignedDecimal256) -> StdResult<Decimal256> {
+    Ok(Decimal256::new(Uint256::try_from(value.atomics())?))
+}
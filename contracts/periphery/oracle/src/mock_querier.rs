@@ -105,7 +105,7 @@ impl WasmMockQuerier {
                             to_json_binary(&PairInfo {
                                 asset_infos,
                                 contract_addr: Addr::unchecked("pair"),
-                                liquidity_token: Addr::unchecked("lp_token"),
+                                liquidity_token: "lp_token".to_string(),
                                 pair_type: PairType::Xyk {},
                             })
                             .into(),
@@ -199,6 +199,12 @@ impl WasmMockQuerier {
             .set(pair, assert, total, cumulative_prices)
     }
 
+    pub fn with_balance(&mut self, balances: &[(&String, &[Coin])]) {
+        for (addr, balance) in balances {
+            self.base.update_balance(addr.to_string(), balance.to_vec());
+        }
+    }
+
     // Configure the mint whitelist mock querier
     pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
         self.token_querier = TokenQuerier::new(balances);
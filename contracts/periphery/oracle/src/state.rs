@@ -1,13 +1,32 @@
 use cosmwasm_schema::cw_serde;
 
-use astroport::asset::{AssetInfo, PairInfo};
-use cosmwasm_std::{Addr, Decimal256, DepsMut, StdResult, Storage, Uint128};
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use cosmwasm_std::{Addr, Decimal256, DepsMut, SignedDecimal256, StdResult, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
 
 /// Stores the contract config at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
 /// Stores the latest cumulative and average prices at the given key
 pub const PRICE_LAST: Item<PriceCumulativeLast> = Item::new("price_last");
+/// Stores the addresses allowed to update prices. If not set, anyone can call update
+pub const UPDATERS: Item<Vec<Addr>> = Item::new("updaters");
+/// Stores the reward paid to the caller of a successful update
+pub const KEEPER_REWARD: Item<Asset> = Item::new("keeper_reward");
+/// Stores the log-prices of the latest periods. Only set if the geometric TWAP is enabled
+pub const GEOMETRIC_SAMPLES: Item<Vec<GeometricSample>> = Item::new("geometric_samples");
+/// Stores whether consulting must fail until the first period is accumulated. Only set if enabled
+pub const REQUIRE_TWAP: Item<bool> = Item::new("require_twap");
+/// Stores the instantiation timestamp. The spot price fallback is only served within the first period after it
+pub const INSTANTIATED_AT: Item<u64> = Item::new("instantiated_at");
+
+/// This structure stores the natural logarithms of the average prices over a single period
+#[cw_serde]
+pub struct GeometricSample {
+    /// The vector contains ln(average price) for each pair of assets in the pool
+    pub log_prices: Vec<(AssetInfo, AssetInfo, SignedDecimal256)>,
+    /// The period duration in seconds
+    pub duration: u64,
+}
 
 /// This structure stores the latest cumulative and average token prices for the target pool
 #[cw_serde]
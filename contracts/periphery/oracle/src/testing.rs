@@ -1,10 +1,15 @@
-use crate::contract::{execute, instantiate};
-use crate::mock_querier::mock_dependencies;
-use astroport::asset::{Asset, AssetInfo};
-use astroport::oracle::{ExecuteMsg, InstantiateMsg};
-use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{Addr, Decimal256, Uint128, Uint256};
+use crate::contract::{execute, instantiate, query};
+use crate::math::{exp, ln};
+use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
+use astroport::asset::{Asset, AssetInfo, AssetInfoExt};
+use astroport::oracle::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    coin, from_json, Addr, BankMsg, Coin, CosmosMsg, Decimal256, OwnedDeps, SignedDecimal256,
+    StdError, Uint128, Uint256,
+};
 use std::ops::Mul;
+use std::str::FromStr;
 
 #[test]
 fn decimal_overflow() {
@@ -64,6 +69,8 @@ fn oracle_overflow() {
     let instantiate_msg = InstantiateMsg {
         factory_contract: factory.to_string(),
         asset_infos: vec![astro_asset_info, usdc_asset_info],
+        geometric_twap: false,
+        require_twap: false,
     };
 
     // Set cumulative price to 192738282u128
@@ -107,3 +114,622 @@ fn oracle_overflow() {
     env.block.time = env.block.time.plus_seconds(86400);
     execute(deps.as_mut(), env, info, ExecuteMsg::Update {}).unwrap();
 }
+
+#[test]
+fn cumulative_price_wrapping() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+
+    let astro_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("astro-token"),
+    };
+    let usdc_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("usdc-token"),
+    };
+    let asset = vec![
+        Asset {
+            info: astro_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: usdc_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+    ];
+    let set_cumulative_prices = |querier: &mut WasmMockQuerier, cumulative_prices: [u128; 2]| {
+        querier.set_cumulative_price(
+            Addr::unchecked("pair"),
+            asset.clone(),
+            Uint128::from(1000u128),
+            vec![
+                (
+                    asset[0].info.clone(),
+                    asset[1].info.clone(),
+                    Uint128::from(cumulative_prices[0]),
+                ),
+                (
+                    asset[1].info.clone(),
+                    asset[0].info.clone(),
+                    Uint128::from(cumulative_prices[1]),
+                ),
+            ],
+        )
+    };
+
+    // Prices of 2 and 0.5 accumulated over a day (cumulative prices have 6 decimals)
+    let deltas = [2_000_000u128 * 86400, 500_000u128 * 86400];
+    // The first cumulative price is right before the wrap point while the second is far from it
+    let last = [u128::MAX - deltas[0] / 2, 1_000_000u128];
+    set_cumulative_prices(&mut deps.querier, last);
+
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        InstantiateMsg {
+            factory_contract: "factory".to_string(),
+            asset_infos: vec![astro_asset_info.clone(), usdc_asset_info.clone()],
+            geometric_twap: false,
+            require_twap: false,
+        },
+    )
+    .unwrap();
+
+    let current = [
+        last[0].wrapping_add(deltas[0]),
+        last[1].wrapping_add(deltas[1]),
+    ];
+    // The first cumulative price overflowed
+    assert!(current[0] < last[0]);
+    set_cumulative_prices(&mut deps.querier, current);
+
+    env.block.time = env.block.time.plus_seconds(86400);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("random", &[]),
+        ExecuteMsg::Update {},
+    )
+    .unwrap();
+
+    for (token, expected) in [
+        (&astro_asset_info, 2_000_000u128),
+        (&usdc_asset_info, 500_000u128),
+    ] {
+        let consulted: Vec<(AssetInfo, Uint256)> = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Consult {
+                    token: token.clone(),
+                    amount: Uint128::new(1_000_000),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(consulted[0].1, Uint256::from(expected));
+    }
+}
+
+/// Returns mock dependencies with an ASTRO/USDC pair whose cumulative prices are both 1000
+fn mock_astro_usdc_pair(
+    contract_balance: &[Coin],
+) -> (
+    OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    AssetInfo,
+    AssetInfo,
+) {
+    let mut deps = mock_dependencies(contract_balance);
+
+    let astro_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("astro-token"),
+    };
+    let usdc_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("usdc-token"),
+    };
+    let asset = vec![
+        astro_asset_info.with_balance(0u8),
+        usdc_asset_info.with_balance(0u8),
+    ];
+    deps.querier.set_cumulative_price(
+        Addr::unchecked("pair"),
+        asset.clone(),
+        Uint128::from(1000u128),
+        vec![
+            (
+                asset[0].info.clone(),
+                asset[1].info.clone(),
+                Uint128::from(1000u128),
+            ),
+            (
+                asset[1].info.clone(),
+                asset[0].info.clone(),
+                Uint128::from(1000u128),
+            ),
+        ],
+    );
+
+    (deps, astro_asset_info, usdc_asset_info)
+}
+
+#[test]
+fn update_permissions() {
+    let (mut deps, astro_asset_info, usdc_asset_info) = mock_astro_usdc_pair(&[]);
+    let owner = mock_info("owner", &[]);
+    let mut env = mock_env();
+
+    let instantiate_msg = InstantiateMsg {
+        factory_contract: "factory".to_string(),
+        asset_infos: vec![astro_asset_info, usdc_asset_info],
+        geometric_twap: false,
+        require_twap: false,
+    };
+    instantiate(deps.as_mut(), env.clone(), owner.clone(), instantiate_msg).unwrap();
+
+    // Updates are permissionless by default
+    let updaters: Option<Vec<Addr>> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Updaters {}).unwrap()).unwrap();
+    assert_eq!(updaters, None);
+
+    env.block.time = env.block.time.plus_seconds(86400);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("random", &[]),
+        ExecuteMsg::Update {},
+    )
+    .unwrap();
+
+    // Only the owner can set updaters
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("random", &[]),
+        ExecuteMsg::SetUpdaters {
+            updaters: Some(vec!["keeper".to_string()]),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        owner.clone(),
+        ExecuteMsg::SetUpdaters {
+            updaters: Some(vec!["keeper".to_string()]),
+        },
+    )
+    .unwrap();
+    let updaters: Option<Vec<Addr>> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Updaters {}).unwrap()).unwrap();
+    assert_eq!(updaters, Some(vec![Addr::unchecked("keeper")]));
+
+    // Restricted mode: only keepers can update prices
+    env.block.time = env.block.time.plus_seconds(86400);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("random", &[]),
+        ExecuteMsg::Update {},
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::Update {},
+    )
+    .unwrap();
+
+    // Removing the allowlist makes updates permissionless again
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        owner,
+        ExecuteMsg::SetUpdaters { updaters: None },
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(86400);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("random", &[]),
+        ExecuteMsg::Update {},
+    )
+    .unwrap();
+}
+
+#[test]
+fn consult_untracked_token() {
+    let (mut deps, astro_asset_info, usdc_asset_info) = mock_astro_usdc_pair(&[]);
+    let env = mock_env();
+
+    let instantiate_msg = InstantiateMsg {
+        factory_contract: "factory".to_string(),
+        asset_infos: vec![astro_asset_info.clone(), usdc_asset_info.clone()],
+        geometric_twap: false,
+        require_twap: false,
+    };
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    let tracked_assets: Vec<AssetInfo> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::TrackedAssets {}).unwrap()).unwrap();
+    assert_eq!(tracked_assets, vec![astro_asset_info, usdc_asset_info]);
+
+    let err = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Consult {
+            token: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::new(1000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Invalid Token uluna. The oracle only tracks: astro-token, usdc-token"
+    );
+}
+
+#[test]
+fn log_exp_roundtrip() {
+    assert_eq!(ln(Decimal256::one()).unwrap(), SignedDecimal256::zero());
+    assert!(ln(Decimal256::zero()).is_err());
+    assert_eq!(exp(SignedDecimal256::zero()).unwrap(), Decimal256::one());
+
+    for value in [
+        "0.000001",
+        "0.25",
+        "0.5",
+        "1.5",
+        "4",
+        "1000000",
+        "123456789.123",
+    ] {
+        let x = Decimal256::from_str(value).unwrap();
+        let roundtrip = exp(ln(x).unwrap()).unwrap();
+        let diff = if roundtrip > x {
+            roundtrip - x
+        } else {
+            x - roundtrip
+        };
+        assert!(
+            diff / x < Decimal256::from_str("0.000000000001").unwrap(),
+            "{value}: {roundtrip}"
+        );
+    }
+}
+
+#[test]
+fn geometric_twap() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+
+    let astro_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("astro-token"),
+    };
+    let usdc_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("usdc-token"),
+    };
+    let asset = vec![
+        Asset {
+            info: astro_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: usdc_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+    ];
+
+    let mut cumulative_prices = [0u128, 0u128];
+    let set_cumulative_prices = |querier: &mut WasmMockQuerier, cumulative_prices: [u128; 2]| {
+        querier.set_cumulative_price(
+            Addr::unchecked("pair"),
+            asset.clone(),
+            Uint128::from(1000u128),
+            vec![
+                (
+                    asset[0].info.clone(),
+                    asset[1].info.clone(),
+                    Uint128::from(cumulative_prices[0]),
+                ),
+                (
+                    asset[1].info.clone(),
+                    asset[0].info.clone(),
+                    Uint128::from(cumulative_prices[1]),
+                ),
+            ],
+        )
+    };
+    set_cumulative_prices(&mut deps.querier, cumulative_prices);
+
+    let instantiate_msg = InstantiateMsg {
+        factory_contract: "factory".to_string(),
+        asset_infos: vec![astro_asset_info.clone(), usdc_asset_info.clone()],
+        geometric_twap: true,
+        require_twap: false,
+    };
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    let amount = Uint128::new(1_000_000);
+    let geometric_query = |token: &AssetInfo| QueryMsg::GeometricTwap {
+        token: token.clone(),
+        amount,
+    };
+
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        geometric_query(&astro_asset_info),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("No prices were accumulated yet"));
+
+    // Volatile daily prices of ASTRO in USDC
+    let daily_prices = [(1u128, 1u128), (4, 1), (1, 1), (4, 1)];
+    let mut arithmetic_sum = [Uint256::zero(), Uint256::zero()];
+    for (numerator, denominator) in daily_prices {
+        // Cumulative prices have TWAP_PRECISION (6) decimals
+        cumulative_prices[0] += 1_000_000 * 86400 * numerator / denominator;
+        cumulative_prices[1] += 1_000_000 * 86400 * denominator / numerator;
+        set_cumulative_prices(&mut deps.querier, cumulative_prices);
+
+        env.block.time = env.block.time.plus_seconds(86400);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("random", &[]),
+            ExecuteMsg::Update {},
+        )
+        .unwrap();
+
+        for (i, token) in [&astro_asset_info, &usdc_asset_info]
+            .into_iter()
+            .enumerate()
+        {
+            let consulted: Vec<(AssetInfo, Uint256)> = from_json(
+                query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::Consult {
+                        token: token.clone(),
+                        amount,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            arithmetic_sum[i] += consulted[0].1;
+        }
+    }
+
+    let periods = Uint256::from(daily_prices.len() as u128);
+    // Arithmetic means: (1 + 4 + 1 + 4) / 4 = 2.5 and (1 + 0.25 + 1 + 0.25) / 4 = 0.625
+    assert_eq!(arithmetic_sum[0] / periods, Uint256::from(2_500_000u128));
+    assert_eq!(arithmetic_sum[1] / periods, Uint256::from(625_000u128));
+
+    // Geometric means: (1 * 4 * 1 * 4) ^ (1/4) = 2 and (1 * 0.25 * 1 * 0.25) ^ (1/4) = 0.5
+    for (token, expected) in [
+        (&astro_asset_info, 2_000_000u128),
+        (&usdc_asset_info, 500_000u128),
+    ] {
+        let geometric: Vec<(AssetInfo, Uint256)> =
+            from_json(query(deps.as_ref(), env.clone(), geometric_query(token)).unwrap()).unwrap();
+        let expected = Uint256::from(expected);
+        let diff = if geometric[0].1 > expected {
+            geometric[0].1 - expected
+        } else {
+            expected - geometric[0].1
+        };
+        assert!(diff <= Uint256::one(), "{} != {expected}", geometric[0].1);
+    }
+}
+
+#[test]
+fn require_twap() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+
+    let astro_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("astro-token"),
+    };
+    let usdc_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("usdc-token"),
+    };
+    let asset = vec![
+        Asset {
+            info: astro_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: usdc_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+    ];
+    let set_cumulative_prices = |querier: &mut WasmMockQuerier, cumulative_prices: [u128; 2]| {
+        querier.set_cumulative_price(
+            Addr::unchecked("pair"),
+            asset.clone(),
+            Uint128::from(1000u128),
+            vec![
+                (
+                    asset[0].info.clone(),
+                    asset[1].info.clone(),
+                    Uint128::from(cumulative_prices[0]),
+                ),
+                (
+                    asset[1].info.clone(),
+                    asset[0].info.clone(),
+                    Uint128::from(cumulative_prices[1]),
+                ),
+            ],
+        )
+    };
+    set_cumulative_prices(&mut deps.querier, [0, 0]);
+
+    let instantiate_msg = InstantiateMsg {
+        factory_contract: "factory".to_string(),
+        asset_infos: vec![astro_asset_info.clone(), usdc_asset_info],
+        geometric_twap: false,
+        require_twap: true,
+    };
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    let consult_msg = QueryMsg::Consult {
+        token: astro_asset_info,
+        amount: Uint128::new(1_000_000),
+    };
+
+    // No spot price fallback before the first period is accumulated
+    let err = query(deps.as_ref(), env.clone(), consult_msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Price is not ready yet. Wait for the first full period to be accumulated"
+        )
+    );
+
+    // ASTRO costs 2 USDC over the first period. Cumulative prices have TWAP_PRECISION (6) decimals
+    set_cumulative_prices(&mut deps.querier, [2_000_000 * 86400, 500_000 * 86400]);
+    env.block.time = env.block.time.plus_seconds(86400);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("random", &[]),
+        ExecuteMsg::Update {},
+    )
+    .unwrap();
+
+    let consulted: Vec<(AssetInfo, Uint256)> =
+        from_json(query(deps.as_ref(), env, consult_msg).unwrap()).unwrap();
+    assert_eq!(consulted[0].1, Uint256::from(2_000_000u128));
+}
+
+#[test]
+fn geometric_twap_disabled() {
+    let (mut deps, astro_asset_info, usdc_asset_info) = mock_astro_usdc_pair(&[]);
+    let env = mock_env();
+
+    let instantiate_msg = InstantiateMsg {
+        factory_contract: "factory".to_string(),
+        asset_infos: vec![astro_asset_info.clone(), usdc_asset_info],
+        geometric_twap: false,
+        require_twap: false,
+    };
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    let err = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::GeometricTwap {
+            token: astro_asset_info,
+            amount: Uint128::new(1000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Geometric TWAP is not enabled"));
+}
+
+#[test]
+fn keeper_reward() {
+    let (mut deps, astro_asset_info, usdc_asset_info) = mock_astro_usdc_pair(&[coin(150, "uluna")]);
+    let owner = mock_info("owner", &[]);
+    let keeper = mock_info("keeper", &[]);
+    let mut env = mock_env();
+
+    let instantiate_msg = InstantiateMsg {
+        factory_contract: "factory".to_string(),
+        asset_infos: vec![astro_asset_info, usdc_asset_info],
+        geometric_twap: false,
+        require_twap: false,
+    };
+    instantiate(deps.as_mut(), env.clone(), owner.clone(), instantiate_msg).unwrap();
+
+    let reward = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        amount: Uint128::new(100),
+    };
+
+    // Only the owner can set the reward
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        keeper.clone(),
+        ExecuteMsg::SetKeeperReward {
+            reward: Some(reward.clone()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        owner,
+        ExecuteMsg::SetKeeperReward {
+            reward: Some(reward.clone()),
+        },
+    )
+    .unwrap();
+    let keeper_reward: Option<Asset> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::KeeperReward {}).unwrap()).unwrap();
+    assert_eq!(keeper_reward, Some(reward.clone()));
+
+    // A successful update pays the keeper
+    env.block.time = env.block.time.plus_seconds(86400);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        keeper.clone(),
+        ExecuteMsg::Update {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "keeper".to_string(),
+            amount: vec![coin(100, "uluna")],
+        })
+    );
+
+    // The remaining balance is not enough to pay the reward, yet the update succeeds
+    deps.querier
+        .with_balance(&[(&MOCK_CONTRACT_ADDR.to_string(), &[coin(50, "uluna")])]);
+    env.block.time = env.block.time.plus_seconds(86400);
+    let res = execute(deps.as_mut(), env, keeper, ExecuteMsg::Update {}).unwrap();
+    assert!(res.messages.is_empty());
+}
@@ -5,7 +5,7 @@ use astroport_test::cw_multi_test::{AppBuilder, AppResponse, ContractWrapper, Ex
 use astroport_test::modules::stargate::{MockStargate, StargateApp as App};
 use cosmwasm_std::{
     attr, to_json_binary, Addr, BlockInfo, Coin, Decimal, QueryRequest, StdResult, Uint128,
-    WasmQuery,
+    Uint256, WasmQuery,
 };
 use cw20::{BalanceResponse, Cw20QueryMsg, MinterResponse};
 
@@ -15,7 +15,7 @@ use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport::factory::{PairConfig, PairType};
 
 use astroport::oracle::QueryMsg::Consult;
-use astroport::oracle::{ExecuteMsg, InstantiateMsg};
+use astroport::oracle::{ExecuteMsg, InstantiateMsg, PriceComparisonResponse, QueryMsg};
 
 const OWNER: &str = "owner";
 
@@ -534,6 +534,8 @@ fn consult() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        geometric_twap: false,
+        require_twap: false,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -722,6 +724,8 @@ fn consult2() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        geometric_twap: false,
+        require_twap: false,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -936,6 +940,8 @@ fn consult_zero_price() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        geometric_twap: false,
+        require_twap: false,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -1082,3 +1088,239 @@ fn consult_zero_price() {
     // Price is too small thus we get zero
     assert_eq!(res[0].1.u128(), 0u128);
 }
+
+#[test]
+fn price_comparison() {
+    let mut router = mock_app(None, None);
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user0000");
+    let (astro_token_instance, factory_instance, oracle_code_id) =
+        instantiate_contracts(&mut router, owner.clone());
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: usdc_token_instance.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: astro_token_instance.clone(),
+        },
+    ];
+
+    let assets = vec![
+        Asset {
+            info: asset_infos[0].clone(),
+            amount: Uint128::from(100_000_000_u128),
+        },
+        Asset {
+            info: asset_infos[1].clone(),
+            amount: Uint128::from(100_000_000_u128),
+        },
+    ];
+
+    let pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        assets.clone(),
+    );
+    provide_liquidity(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &pair_info,
+        assets.clone(),
+    )
+    .unwrap();
+
+    let msg = InstantiateMsg {
+        factory_contract: factory_instance.to_string(),
+        asset_infos: asset_infos.clone(),
+        geometric_twap: false,
+        require_twap: false,
+    };
+    let oracle_instance = router
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            String::from("ORACLE"),
+            None,
+        )
+        .unwrap();
+
+    router.update_block(next_day);
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &ExecuteMsg::Update {},
+            &[],
+        )
+        .unwrap();
+
+    let query_comparison = |router: &App| -> Vec<(AssetInfo, PriceComparisonResponse)> {
+        router
+            .wrap()
+            .query_wasm_smart(
+                &oracle_instance,
+                &QueryMsg::PriceComparison {
+                    token: asset_infos[0].clone(),
+                    amount: Uint128::from(1_000_000u128),
+                },
+            )
+            .unwrap()
+    };
+
+    // The pool is balanced, so both prices are close to 1:1
+    let res = query_comparison(&router);
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].0, asset_infos[1]);
+    assert_eq!(res[0].1.twap, Uint256::from(1_000_000u128));
+    assert!(res[0].1.spot < res[0].1.twap);
+
+    // A recent swap moves the spot price while the TWAP stays the same
+    let swap_amount = Uint128::from(50_000_000u128);
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        user.clone(),
+        swap_amount,
+    );
+    router
+        .execute_contract(
+            user.clone(),
+            usdc_token_instance.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: pair_info.contract_addr.to_string(),
+                amount: swap_amount,
+                msg: to_json_binary(&astroport::pair::Cw20HookMsg::Swap {
+                    ask_asset_info: None,
+                    belief_price: None,
+                    max_spread: Some(Decimal::percent(50)),
+                    to: None,
+                    strict_belief_price: false,
+                    referral: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let after_swap = query_comparison(&router);
+    assert_eq!(after_swap[0].1.twap, res[0].1.twap);
+    assert!(after_swap[0].1.spot < res[0].1.spot);
+    assert_ne!(after_swap[0].1.spot, after_swap[0].1.twap);
+}
+
+#[test]
+fn consult_spot_fallback_first_period_only() {
+    let mut router = mock_app(None, None);
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user0000");
+    let (astro_token_instance, factory_instance, oracle_code_id) =
+        instantiate_contracts(&mut router, owner.clone());
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: usdc_token_instance.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: astro_token_instance.clone(),
+        },
+    ];
+
+    let assets = vec![
+        Asset {
+            info: asset_infos[0].clone(),
+            amount: Uint128::from(100_000_000_u128),
+        },
+        Asset {
+            info: asset_infos[1].clone(),
+            amount: Uint128::from(100_000_000_u128),
+        },
+    ];
+
+    let pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        assets.clone(),
+    );
+    provide_liquidity(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &pair_info,
+        assets.clone(),
+    )
+    .unwrap();
+
+    let oracle_instance = router
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &InstantiateMsg {
+                factory_contract: factory_instance.to_string(),
+                asset_infos: asset_infos.clone(),
+                geometric_twap: false,
+                require_twap: false,
+            },
+            &[],
+            String::from("ORACLE"),
+            None,
+        )
+        .unwrap();
+
+    let consult = |router: &App| -> StdResult<Vec<(AssetInfo, Uint256)>> {
+        router.wrap().query_wasm_smart(
+            &oracle_instance,
+            &Consult {
+                token: asset_infos[0].clone(),
+                amount: Uint128::from(1_000_000u128),
+            },
+        )
+    };
+
+    // The average is expected to be zero within the first period thus the spot price is served
+    let res = consult(&router).unwrap();
+    assert!(!res[0].1.is_zero());
+    assert!(res[0].1 < Uint256::from(1_000_000u128));
+
+    // The first period has passed but no update was made, so the zero average is anomalous
+    router.update_block(next_day);
+    let err = consult(&router).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("TWAP is zero after the first period"));
+
+    // Once the oracle is updated the TWAP is served
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &ExecuteMsg::Update {},
+            &[],
+        )
+        .unwrap();
+    let res = consult(&router).unwrap();
+    assert_eq!(res[0].1, Uint256::from(1_000_000u128));
+}
@@ -1,21 +1,27 @@
+use std::collections::HashSet;
+
 use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, wasm_execute, Addr, Api, Binary, Decimal, Deps,
-    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, SubMsgResult, Uint128,
+    attr, entry_point, from_json, to_json_binary, wasm_execute, Addr, Api, Binary, CosmosMsg,
+    Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg,
+    SubMsgResult, Uint128,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ReceiveMsg;
 
-use astroport::asset::{addr_opt_validate, Asset, AssetInfo};
+use astroport::asset::{addr_opt_validate, Asset, AssetInfo, AssetInfoExt, PairInfo};
+use astroport::factory::{pair_key, PairsResponse, QueryMsg as FactoryQueryMsg};
 use astroport::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
-use astroport::querier::query_pair_info;
+use astroport::querier::{query_factory_config, query_pair_info};
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation, SwapResponseData, MAX_SWAP_OPERATIONS,
+    BestRouteResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, FrontendFee, InstantiateMsg,
+    MigrateMsg, QueryMsg, ReachableAssetsResponse, RoutePreference, SimulateSwapOperationsResponse,
+    SwapOperation, SwapResponseData, MAX_FRONTEND_FEE_BPS, MAX_REACHABLE_HOPS, MAX_RECIPIENTS,
+    MAX_ROUTE_CANDIDATES, MAX_SCANNED_PAIRS, MAX_SWAP_OPERATIONS,
 };
 
 use crate::error::ContractError;
 use crate::operations::execute_swap_operation;
-use crate::state::{Config, ReplyData, CONFIG, REPLY_DATA};
+use crate::state::{Config, FrontendFeeData, ReplyData, CONFIG, PAIR_CACHE, REPLY_DATA};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-router";
@@ -24,6 +30,9 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const AFTER_SWAP_REPLY_ID: u64 = 1;
 
+/// The number of pairs requested from the factory per page
+const PAIRS_PAGE_LIMIT: u32 = 30;
+
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -53,11 +62,19 @@ pub fn instantiate(
 /// * **ExecuteMsg::ExecuteSwapOperations {
 ///             operations,
 ///             minimum_receive,
-///             to
+///             to,
+///             max_spread,
+///             frontend_fee,
+///             recipients,
+///             allow_partial
 ///         }** Performs swap operations with the specified parameters.
 ///
 /// * **ExecuteMsg::ExecuteSwapOperation { operation, to }** Execute a single swap operation.
 ///
+/// * **ExecuteMsg::CachePairs { pairs }** Resolves pair addresses via the factory and caches them.
+///
+/// * **ExecuteMsg::InvalidatePairs { pairs }** Removes pairs from the cache.
+///
 /// * **ExecuteMsg::AssertMinimumReceive {
 ///             asset_info,
 ///             prev_balance,
@@ -78,6 +95,9 @@ pub fn execute(
             minimum_receive,
             to,
             max_spread,
+            frontend_fee,
+            recipients,
+            allow_partial,
         } => execute_swap_operations(
             deps,
             env,
@@ -86,6 +106,9 @@ pub fn execute(
             minimum_receive,
             to,
             max_spread,
+            frontend_fee,
+            recipients,
+            allow_partial,
         ),
         ExecuteMsg::ExecuteSwapOperation {
             operation,
@@ -93,6 +116,8 @@ pub fn execute(
             max_spread,
             single,
         } => execute_swap_operation(deps, env, info, operation, to, max_spread, single),
+        ExecuteMsg::CachePairs { pairs } => cache_pairs(deps, info, pairs),
+        ExecuteMsg::InvalidatePairs { pairs } => invalidate_pairs(deps, info, pairs),
     }
 }
 
@@ -110,6 +135,9 @@ pub fn receive_cw20(
             minimum_receive,
             to,
             max_spread,
+            frontend_fee,
+            recipients,
+            allow_partial,
         } => execute_swap_operations(
             deps,
             env,
@@ -118,6 +146,9 @@ pub fn receive_cw20(
             minimum_receive,
             to,
             max_spread,
+            frontend_fee,
+            recipients,
+            allow_partial,
         ),
     }
 }
@@ -131,6 +162,13 @@ pub fn receive_cw20(
 /// * **minimum_receive** used to guarantee that the ask amount is above a minimum amount.
 ///
 /// * **to** recipient of the ask tokens.
+///
+/// * **frontend_fee** optional fee taken from the final output and sent to the frontend address.
+///
+/// * **recipients** optional recipients splitting the final output by fractions.
+///
+/// * **allow_partial** whether to stop at a hop without liquidity after the first one and send out
+/// the output of the last successful hop.
 #[allow(clippy::too_many_arguments)]
 pub fn execute_swap_operations(
     deps: DepsMut,
@@ -140,12 +178,76 @@ pub fn execute_swap_operations(
     minimum_receive: Option<Uint128>,
     to: Option<String>,
     max_spread: Option<Decimal>,
+    frontend_fee: Option<FrontendFee>,
+    recipients: Option<Vec<(String, Decimal)>>,
+    allow_partial: bool,
 ) -> Result<Response, ContractError> {
     assert_operations(deps.api, &operations)?;
 
-    let to = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
+    let recipients = recipients
+        .map(|recipients| validate_recipients(deps.api, &to, recipients))
+        .transpose()?
+        .unwrap_or_default();
+
+    let recipient = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
+
+    let frontend_fee = frontend_fee
+        .map(|fee| {
+            if fee.bps == 0 || fee.bps > MAX_FRONTEND_FEE_BPS {
+                return Err(ContractError::FrontendFeeOutOfBounds {});
+            }
+
+            Ok(FrontendFeeData {
+                address: deps.api.addr_validate(&fee.address)?,
+                bps: fee.bps,
+                recipient: recipient.clone(),
+            })
+        })
+        .transpose()?;
+
+    // If a frontend fee is charged or the output is split,
+    // the router receives the output first and then distributes it
+    let to = if frontend_fee.is_some() || !recipients.is_empty() {
+        env.contract.address.clone()
+    } else {
+        recipient
+    };
     let target_asset_info = operations.last().unwrap().get_target_asset_info();
     let operations_len = operations.len();
+    let partial_route = if allow_partial {
+        // The output of a stopped route is bounded only by the spread check of every executed hop.
+        // Hops of such routes are checked like single swaps against an explicit max_spread
+        if max_spread.is_none() {
+            return Err(ContractError::PartialRouteWithoutMaxSpread {});
+        }
+
+        let offer_assets = operations
+            .iter()
+            .map(|op| match op {
+                SwapOperation::AstroSwap {
+                    offer_asset_info, ..
+                } => Ok(offer_asset_info.clone()),
+                SwapOperation::NativeSwap { .. } => Err(ContractError::NativeSwapNotSupported {}),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Each hop swaps the whole router balance of its offer asset. The balance an asset had
+        // before the route is only part of the output if an earlier hop already swapped it away
+        offer_assets
+            .iter()
+            .enumerate()
+            .map(|(index, asset_info)| {
+                let prev_balance = if offer_assets[..index].contains(asset_info) {
+                    Uint128::zero()
+                } else {
+                    asset_info.query_pool(&deps.querier, &env.contract.address)?
+                };
+                Ok((asset_info.clone(), prev_balance))
+            })
+            .collect::<StdResult<Vec<_>>>()?
+    } else {
+        vec![]
+    };
 
     let messages = operations
         .into_iter()
@@ -158,7 +260,7 @@ pub fn execute_swap_operations(
                         operation: op,
                         to: Some(to.to_string()),
                         max_spread,
-                        single: operations_len == 1,
+                        single: operations_len == 1 || allow_partial,
                     },
                     vec![],
                 )
@@ -170,7 +272,7 @@ pub fn execute_swap_operations(
                         operation: op,
                         to: None,
                         max_spread,
-                        single: operations_len == 1,
+                        single: operations_len == 1 || allow_partial,
                     },
                     vec![],
                 )
@@ -187,43 +289,177 @@ pub fn execute_swap_operations(
             prev_balance,
             minimum_receive,
             receiver: to.to_string(),
+            frontend_fee,
+            recipients,
+            partial_route,
+            executed_hops: 0,
+            stopped_at: None,
         },
     )?;
 
     Ok(Response::new().add_submessages(messages))
 }
 
+/// Checks that the sender is the factory owner.
+fn assert_factory_owner(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, config.astroport_factory)?;
+
+    if *sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+/// Resolves pair addresses via the factory and caches them.
+///
+/// * **pairs** asset pairs to resolve.
+fn cache_pairs(
+    deps: DepsMut,
+    info: MessageInfo,
+    pairs: Vec<[AssetInfo; 2]>,
+) -> Result<Response, ContractError> {
+    assert_factory_owner(deps.as_ref(), &info.sender)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut attrs = vec![attr("action", "cache_pairs")];
+    for asset_infos in pairs {
+        let pair_info = query_pair_info(&deps.querier, &config.astroport_factory, &asset_infos)?;
+        PAIR_CACHE.save(
+            deps.storage,
+            &pair_key(&asset_infos),
+            &pair_info.contract_addr,
+        )?;
+        attrs.push(attr("pair", pair_info.contract_addr));
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Removes pairs from the cache so they are resolved via the factory again.
+///
+/// * **pairs** asset pairs to remove from the cache.
+fn invalidate_pairs(
+    deps: DepsMut,
+    info: MessageInfo,
+    pairs: Vec<[AssetInfo; 2]>,
+) -> Result<Response, ContractError> {
+    assert_factory_owner(deps.as_ref(), &info.sender)?;
+
+    for asset_infos in &pairs {
+        PAIR_CACHE.remove(deps.storage, &pair_key(asset_infos));
+    }
+
+    Ok(Response::new().add_attribute("action", "invalidate_pairs"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg {
         Reply {
             id: AFTER_SWAP_REPLY_ID,
             result: SubMsgResult::Ok(..),
         } => {
             let reply_data = REPLY_DATA.load(deps.storage)?;
+            distribute_route_output(deps, env, reply_data)
+        }
+        _ => Err(StdError::generic_err("Failed to process reply").into()),
+    }
+}
+
+/// Sends out the output of a route, charging the frontend fee and splitting it between
+/// recipients if requested. If the route stopped at a hop without liquidity, the output is
+/// the increase of the router's balance of the asset it stopped at and the minimum receive amount
+/// is not checked.
+fn distribute_route_output(
+    deps: DepsMut,
+    env: Env,
+    reply_data: ReplyData,
+) -> Result<Response, ContractError> {
+    let (asset_info, mut swap_amount) =
+        if let Some((stopped_at, prev_balance)) = &reply_data.stopped_at {
+            let balance = stopped_at.query_pool(&deps.querier, &env.contract.address)?;
+            (stopped_at.clone(), balance.checked_sub(*prev_balance)?)
+        } else {
             let receiver_balance = reply_data
                 .asset_info
-                .query_pool(&deps.querier, reply_data.receiver)?;
-            let swap_amount = receiver_balance.checked_sub(reply_data.prev_balance)?;
-
-            if let Some(minimum_receive) = reply_data.minimum_receive {
-                if swap_amount < minimum_receive {
-                    return Err(ContractError::AssertionMinimumReceive {
-                        receive: minimum_receive,
-                        amount: swap_amount,
-                    });
-                }
-            }
+                .query_pool(&deps.querier, &reply_data.receiver)?;
+            (
+                reply_data.asset_info.clone(),
+                receiver_balance.checked_sub(reply_data.prev_balance)?,
+            )
+        };
 
-            // Reply data makes sense ONLY if the first token in multi-hop swap is native.
-            let data = to_json_binary(&SwapResponseData {
-                return_amount: swap_amount,
-            })?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attrs = vec![];
+    if let Some(frontend_fee) = &reply_data.frontend_fee {
+        let fee_amount = swap_amount.multiply_ratio(frontend_fee.bps, 10000u16);
+        swap_amount -= fee_amount;
+
+        if !fee_amount.is_zero() {
+            messages.push(
+                asset_info
+                    .with_balance(fee_amount)
+                    .into_msg(&frontend_fee.address)?,
+            );
+        }
+        attrs.push(("frontend_fee_amount", fee_amount.to_string()));
+    }
+
+    if !reply_data.recipients.is_empty() {
+        // The last recipient receives the remainder so that no rounding dust is left
+        let mut remaining = swap_amount;
+        let last_index = reply_data.recipients.len() - 1;
+        for (index, (recipient, fraction)) in reply_data.recipients.iter().enumerate() {
+            let amount = if index == last_index {
+                remaining
+            } else {
+                swap_amount * *fraction
+            };
+            remaining -= amount;
 
-            Ok(Response::new().set_data(data))
+            if !amount.is_zero() {
+                messages.push(asset_info.with_balance(amount).into_msg(recipient)?);
+            }
+        }
+    } else if let Some(frontend_fee) = &reply_data.frontend_fee {
+        if !swap_amount.is_zero() {
+            messages.push(
+                asset_info
+                    .with_balance(swap_amount)
+                    .into_msg(&frontend_fee.recipient)?,
+            );
+        }
+    } else if reply_data.stopped_at.is_some() && !swap_amount.is_zero() {
+        // The output of a stopped route is held by the router
+        messages.push(
+            asset_info
+                .with_balance(swap_amount)
+                .into_msg(&reply_data.receiver)?,
+        );
+    }
+
+    if let Some((stopped_at, _)) = &reply_data.stopped_at {
+        attrs.push(("stopped_at", stopped_at.to_string()));
+    } else if let Some(minimum_receive) = reply_data.minimum_receive {
+        if swap_amount < minimum_receive {
+            return Err(ContractError::AssertionMinimumReceive {
+                receive: minimum_receive,
+                amount: swap_amount,
+            });
         }
-        _ => Err(StdError::generic_err("Failed to process reply").into()),
     }
+
+    // Reply data makes sense ONLY if the first token in multi-hop swap is native.
+    let data = to_json_binary(&SwapResponseData {
+        return_amount: swap_amount,
+    })?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attrs)
+        .set_data(data))
 }
 
 /// Exposes all the queries available in the contract.
@@ -233,6 +469,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             offer_amount,
 ///             operations,
 ///         }** Simulates one or multiple swap operations and returns the end result in a [`SimulateSwapOperationsResponse`] object.
+/// * **QueryMsg::BestRoute { offer_amount, candidates, preference }** Simulates each candidate route and returns
+/// the index, output and fee rate of the best one in a [`BestRouteResponse`] object.
+/// * **QueryMsg::ReachableAssets { from, max_hops }** Returns the assets reachable from the starting asset
+/// within the specified number of hops.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -245,6 +485,19 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
             offer_amount,
             operations,
         )?)?),
+        QueryMsg::BestRoute {
+            offer_amount,
+            candidates,
+            preference,
+        } => Ok(to_json_binary(&query_best_route(
+            deps,
+            offer_amount,
+            candidates,
+            preference.unwrap_or_default(),
+        )?)?),
+        QueryMsg::ReachableAssets { from, max_hops } => Ok(to_json_binary(
+            &query_reachable_assets(deps, from, max_hops)?,
+        )?),
     }
 }
 
@@ -252,7 +505,8 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
 pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let state = CONFIG.load(deps.storage)?;
     let resp = ConfigResponse {
-        astroport_factory: state.astroport_factory.into_string(),
+        astroport_factory: state.astroport_factory,
+        max_swap_operations: MAX_SWAP_OPERATIONS as u32,
     };
 
     Ok(resp)
@@ -293,11 +547,25 @@ fn simulate_swap_operations(
     offer_amount: Uint128,
     operations: Vec<SwapOperation>,
 ) -> Result<SimulateSwapOperationsResponse, ContractError> {
+    let (amount, _) = simulate_route(deps, offer_amount, operations)?;
+
+    Ok(SimulateSwapOperationsResponse { amount })
+}
+
+/// Simulates swap operations and returns the end amount together with the compounded fee rate
+/// charged by all pools along the route.
+fn simulate_route(
+    deps: Deps,
+    offer_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<(Uint128, Decimal), ContractError> {
     assert_operations(deps.api, &operations)?;
 
     let config = CONFIG.load(deps.storage)?;
     let astroport_factory = config.astroport_factory;
     let mut return_amount = offer_amount;
+    // The share of the output left after the fees of all pools along the route
+    let mut retained = Decimal::one();
 
     for operation in operations.into_iter() {
         match operation {
@@ -322,6 +590,13 @@ fn simulate_swap_operations(
                     },
                 )?;
 
+                let gross_amount = res.return_amount + res.commission_amount;
+                if !gross_amount.is_zero() {
+                    retained = retained
+                        * (Decimal::one()
+                            - Decimal::from_ratio(res.commission_amount, gross_amount));
+                }
+
                 return_amount = res.return_amount;
             }
             SwapOperation::NativeSwap { .. } => {
@@ -330,11 +605,169 @@ fn simulate_swap_operations(
         }
     }
 
-    Ok(SimulateSwapOperationsResponse {
-        amount: return_amount,
+    Ok((return_amount, Decimal::one() - retained))
+}
+
+/// Simulates every candidate route and returns the index, output and fee rate of the best one.
+/// Candidates which fail to simulate (e.g. due to a missing pool) are skipped.
+///
+/// * **offer_amount** amount of offer assets being swapped.
+///
+/// * **candidates** is a list of candidate routes. All of them must swap the same offer asset
+/// into the same ask asset.
+///
+/// * **preference** is the objective used to compare candidates.
+fn query_best_route(
+    deps: Deps,
+    offer_amount: Uint128,
+    candidates: Vec<Vec<SwapOperation>>,
+    preference: RoutePreference,
+) -> Result<BestRouteResponse, ContractError> {
+    if candidates.is_empty() || candidates.len() > MAX_ROUTE_CANDIDATES {
+        return Err(ContractError::InvalidCandidatesNumber {});
+    }
+
+    let route_ends = |operations: &[SwapOperation]| {
+        operations
+            .first()
+            .zip(operations.last())
+            .map(|(first, last)| (first.get_offer_asset_info(), last.get_target_asset_info()))
+    };
+    let expected_ends = route_ends(&candidates[0]);
+    if candidates
+        .iter()
+        .any(|operations| route_ends(operations) != expected_ends)
+    {
+        return Err(ContractError::CandidatesMismatch {});
+    }
+
+    let mut best: Option<BestRouteResponse> = None;
+    let mut last_err = None;
+    for (index, operations) in candidates.into_iter().enumerate() {
+        match simulate_route(deps, offer_amount, operations) {
+            Ok((amount, fee_rate)) => {
+                let is_better = best.as_ref().map_or(true, |best| match preference {
+                    RoutePreference::HighestOutput => amount > best.amount,
+                    RoutePreference::LowestFee => {
+                        fee_rate < best.fee_rate
+                            || (fee_rate == best.fee_rate && amount > best.amount)
+                    }
+                });
+                if is_better {
+                    best = Some(BestRouteResponse {
+                        index: index as u32,
+                        amount,
+                        fee_rate,
+                    });
+                }
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    best.ok_or_else(|| last_err.unwrap_or(ContractError::MustProvideOperations {}))
+}
+
+/// Returns the assets reachable from the starting asset within the specified number of hops
+/// in the order they were discovered. The starting asset itself is not included.
+/// The traversal is bounded by [`MAX_REACHABLE_HOPS`] hops and [`MAX_SCANNED_PAIRS`] factory pairs.
+/// The response is flagged as truncated if the factory has more pairs than that.
+///
+/// * **from** is the starting asset.
+///
+/// * **max_hops** is the maximum number of hops.
+fn query_reachable_assets(
+    deps: Deps,
+    from: AssetInfo,
+    max_hops: u32,
+) -> Result<ReachableAssetsResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let max_hops = max_hops.min(MAX_REACHABLE_HOPS);
+
+    // Pairs are fetched past the limit to tell whether any were left out
+    let mut pairs: Vec<PairInfo> = vec![];
+    let mut start_after = None;
+    while pairs.len() <= MAX_SCANNED_PAIRS {
+        let resp: PairsResponse = deps.querier.query_wasm_smart(
+            &config.astroport_factory,
+            &FactoryQueryMsg::Pairs {
+                start_after,
+                limit: Some(PAIRS_PAGE_LIMIT),
+            },
+        )?;
+        let fetched = resp.pairs.len();
+        start_after = resp.pairs.last().map(|pair| pair.asset_infos.clone());
+        pairs.extend(resp.pairs);
+
+        if fetched < PAIRS_PAGE_LIMIT as usize {
+            break;
+        }
+    }
+    let truncated = pairs.len() > MAX_SCANNED_PAIRS;
+    pairs.truncate(MAX_SCANNED_PAIRS);
+
+    let mut visited = HashSet::from([from.clone()]);
+    let mut frontier = vec![from];
+    let mut reachable = vec![];
+    for _ in 0..max_hops {
+        let mut next = vec![];
+        for pair in &pairs {
+            if pair
+                .asset_infos
+                .iter()
+                .any(|asset| frontier.contains(asset))
+            {
+                for asset in &pair.asset_infos {
+                    if visited.insert(asset.clone()) {
+                        next.push(asset.clone());
+                    }
+                }
+            }
+        }
+
+        if next.is_empty() {
+            break;
+        }
+        reachable.extend(next.iter().cloned());
+        frontier = next;
+    }
+
+    Ok(ReachableAssetsResponse {
+        assets: reachable,
+        truncated,
     })
 }
 
+/// Validates recipients splitting the swap output.
+/// Fractions must be positive and sum to one. Recipients can't be combined with `to`.
+fn validate_recipients(
+    api: &dyn Api,
+    to: &Option<String>,
+    recipients: Vec<(String, Decimal)>,
+) -> Result<Vec<(Addr, Decimal)>, ContractError> {
+    if to.is_some() {
+        return Err(ContractError::RecipientsConflict {});
+    }
+
+    if recipients.is_empty()
+        || recipients.len() > MAX_RECIPIENTS
+        || recipients.iter().any(|(_, fraction)| fraction.is_zero())
+        || recipients
+            .iter()
+            .try_fold(Decimal::zero(), |acc, (_, fraction)| {
+                acc.checked_add(*fraction)
+            })
+            != Ok(Decimal::one())
+    {
+        return Err(ContractError::InvalidRecipients {});
+    }
+
+    recipients
+        .into_iter()
+        .map(|(addr, fraction)| Ok((api.addr_validate(&addr)?, fraction)))
+        .collect()
+}
+
 /// Validates swap operations.
 ///
 /// * **operations** is a vector that contains objects of type [`SwapOperation`]. These are all the swap operations we check.
@@ -1,3 +1,4 @@
+use astroport::router::{MAX_FRONTEND_FEE_BPS, MAX_RECIPIENTS, MAX_ROUTE_CANDIDATES};
 use cosmwasm_std::{OverflowError, StdError, Uint128};
 use thiserror::Error;
 
@@ -41,6 +42,33 @@ pub enum ContractError {
     #[error("Native swap operations are not supported!")]
     NativeSwapNotSupported {},
 
+    #[error(
+        "Frontend fee is 0 or exceeds maximum allowed value of {} bps",
+        MAX_FRONTEND_FEE_BPS
+    )]
+    FrontendFeeOutOfBounds {},
+
+    #[error(
+        "Must specify between 1 and {} candidate routes!",
+        MAX_ROUTE_CANDIDATES
+    )]
+    InvalidCandidatesNumber {},
+
+    #[error("All candidate routes must swap the same offer asset into the same ask asset!")]
+    CandidatesMismatch {},
+
+    #[error(
+        "Must specify between 1 and {} recipients with positive fractions summing to one!",
+        MAX_RECIPIENTS
+    )]
+    InvalidRecipients {},
+
+    #[error("Can't specify both to and recipients!")]
+    RecipientsConflict {},
+
+    #[error("Partial routes must specify max_spread!")]
+    PartialRouteWithoutMaxSpread {},
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 }
@@ -1,15 +1,16 @@
 use astroport::asset::{Asset, AssetInfo};
-use astroport::pair::ExecuteMsg as PairExecuteMsg;
+use astroport::factory::{pair_key, PAIRS};
+use astroport::pair::{ExecuteMsg as PairExecuteMsg, PoolResponse, QueryMsg as PairQueryMsg};
 use astroport::querier::{query_balance, query_pair_info, query_token_balance};
 use astroport::router::SwapOperation;
 use cosmwasm_std::{
-    to_json_binary, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, StdResult,
-    WasmMsg,
+    attr, to_json_binary, Addr, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo,
+    QuerierWrapper, Response, StdError, StdResult, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
 use crate::error::ContractError;
-use crate::state::CONFIG;
+use crate::state::{CONFIG, PAIR_CACHE, REPLY_DATA};
 
 /// Execute a swap operation.
 ///
@@ -31,17 +32,68 @@ pub fn execute_swap_operation(
         return Err(ContractError::Unauthorized {});
     }
 
+    // Skip the remaining hops of a route which stopped at a hop without liquidity
+    let mut reply_data = REPLY_DATA.may_load(deps.storage)?;
+    if reply_data
+        .as_ref()
+        .is_some_and(|reply_data| reply_data.stopped_at.is_some())
+    {
+        return Ok(Response::new().add_attribute("action", "skip_swap_operation"));
+    }
+
     let message = match operation {
         SwapOperation::AstroSwap {
             offer_asset_info,
             ask_asset_info,
         } => {
+            let asset_infos = [offer_asset_info.clone(), ask_asset_info.clone()];
             let config = CONFIG.load(deps.storage)?;
-            let pair_info = query_pair_info(
-                &deps.querier,
-                config.astroport_factory,
-                &[offer_asset_info.clone(), ask_asset_info.clone()],
-            )?;
+            let key = pair_key(&asset_infos);
+            // A cached pair is used only while the factory still has it registered for these assets.
+            // Reading the factory storage directly is cheaper than the Pair query
+            let cached = match PAIR_CACHE.may_load(deps.storage, &key)? {
+                Some(cached) => {
+                    let registered =
+                        PAIRS.query(&deps.querier, config.astroport_factory.clone(), &key)?;
+                    if registered.as_ref() != Some(&cached) {
+                        PAIR_CACHE.remove(deps.storage, &key);
+                    }
+                    registered.filter(|registered| *registered == cached)
+                }
+                None => None,
+            };
+            let pair_addr = match cached {
+                Some(pair_addr) => pair_addr,
+                None => {
+                    query_pair_info(&deps.querier, config.astroport_factory, &asset_infos)?
+                        .contract_addr
+                }
+            };
+
+            // A hop after the first one of a partial route stops the route if its pair
+            // has no liquidity. Any other failure reverts the whole route
+            if let Some(reply_data) = reply_data
+                .as_mut()
+                .filter(|reply_data| !reply_data.partial_route.is_empty())
+            {
+                let hop = reply_data.executed_hops;
+                reply_data.executed_hops += 1;
+                if hop > 0 && !has_liquidity(&deps.querier, &pair_addr)? {
+                    let stopped_at = reply_data
+                        .partial_route
+                        .get(hop as usize)
+                        .cloned()
+                        .ok_or_else(|| StdError::generic_err("Unknown swap operation"))?;
+                    reply_data.stopped_at = Some(stopped_at.clone());
+                    REPLY_DATA.save(deps.storage, reply_data)?;
+
+                    return Ok(Response::new().add_attributes([
+                        attr("action", "stop_route"),
+                        attr("stopped_at", stopped_at.0.to_string()),
+                    ]));
+                }
+                REPLY_DATA.save(deps.storage, reply_data)?;
+            }
 
             let amount = match &offer_asset_info {
                 AssetInfo::NativeToken { denom } => {
@@ -57,7 +109,7 @@ pub fn execute_swap_operation(
             };
 
             asset_into_swap_msg(
-                pair_info.contract_addr.to_string(),
+                pair_addr.to_string(),
                 offer_asset,
                 ask_asset_info,
                 max_spread,
@@ -71,6 +123,13 @@ pub fn execute_swap_operation(
     Ok(Response::new().add_message(message))
 }
 
+/// Returns whether the pair holds a non-zero amount of each of its assets.
+fn has_liquidity(querier: &QuerierWrapper, pair_contract: &Addr) -> StdResult<bool> {
+    let pool: PoolResponse = querier.query_wasm_smart(pair_contract, &PairQueryMsg::Pool {})?;
+
+    Ok(pool.assets.iter().all(|asset| !asset.amount.is_zero()))
+}
+
 /// Creates a message of type [`CosmosMsg`] representing a swap operation.
 ///
 /// * **pair_contract** Astroport pair contract for which the swap operation is performed.
@@ -111,6 +170,8 @@ pub fn asset_into_swap_msg(
                 belief_price,
                 max_spread,
                 to,
+                strict_belief_price: false,
+                referral: None,
             })?,
         })),
         AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -124,6 +185,8 @@ pub fn asset_into_swap_msg(
                     belief_price,
                     max_spread,
                     to,
+                    strict_belief_price: false,
+                    referral: None,
                 })?,
             })?,
         })),
@@ -1,11 +1,15 @@
 use astroport::asset::AssetInfo;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map};
 
 /// Stores the contract config at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Cached pair addresses keyed by the factory [`astroport::factory::pair_key`] of the pair assets.
+/// Entries are re-validated against the factory storage before use
+pub const PAIR_CACHE: Map<&[u8], Addr> = Map::new("pair_cache");
+
 /// This structure holds the main parameters for the router
 #[cw_serde]
 pub struct Config {
@@ -21,4 +25,32 @@ pub struct ReplyData {
     pub prev_balance: Uint128,
     pub minimum_receive: Option<Uint128>,
     pub receiver: String,
+    /// Frontend fee to charge from the swap output. If set, the router receives the output
+    /// and forwards it to the final recipient after the fee is deducted
+    pub frontend_fee: Option<FrontendFeeData>,
+    /// Recipients splitting the swap output by fractions. If set, the router receives the output
+    /// and distributes it after the frontend fee is deducted
+    #[serde(default)]
+    pub recipients: Vec<(Addr, Decimal)>,
+    /// Offer assets of all hops with the router balance held before the route which is not part
+    /// of the hop output, if the route may stop at a hop without liquidity. Empty otherwise
+    #[serde(default)]
+    pub partial_route: Vec<(AssetInfo, Uint128)>,
+    /// The number of hops of a partial route which have started executing
+    #[serde(default)]
+    pub executed_hops: u64,
+    /// The offer asset of the hop without liquidity the route stopped at with the router balance
+    /// excluded from the output. The remaining hops are skipped
+    #[serde(default)]
+    pub stopped_at: Option<(AssetInfo, Uint128)>,
+}
+
+#[cw_serde]
+pub struct FrontendFeeData {
+    /// The address which receives the fee
+    pub address: Addr,
+    /// The fee in bps
+    pub bps: u16,
+    /// The final recipient of the swap output
+    pub recipient: Addr,
 }
@@ -50,6 +50,9 @@ fn execute_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        frontend_fee: None,
+        recipients: None,
+        allow_partial: false,
     };
 
     let env = mock_env();
@@ -87,6 +90,9 @@ fn execute_swap_operations() {
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
         max_spread: None,
+        frontend_fee: None,
+        recipients: None,
+        allow_partial: false,
     };
 
     let env = mock_env();
@@ -203,6 +209,9 @@ fn execute_swap_operations() {
             minimum_receive: None,
             to: Some(String::from("addr0002")),
             max_spread: None,
+            frontend_fee: None,
+            recipients: None,
+            allow_partial: false,
         })
         .unwrap(),
     });
@@ -351,6 +360,8 @@ fn execute_swap_operation() {
                         belief_price: None,
                         max_spread: None,
                         to: Some(String::from("addr0000")),
+                        strict_belief_price: false,
+                        referral: None,
                     })
                     .unwrap()
                 })
@@ -455,6 +466,9 @@ fn assert_maximum_receive_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        frontend_fee: None,
+        recipients: None,
+        allow_partial: false,
     };
 
     let env = mock_env();
@@ -98,6 +98,7 @@ impl FactoryHelper {
             owner: owner.to_string(),
             whitelist_code_id: 0,
             coin_registry_address: "coin_registry".to_string(),
+            tracker_config: None,
         };
 
         let factory = router
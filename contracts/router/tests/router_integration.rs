@@ -1,11 +1,16 @@
 #![cfg(not(tarpaulin_include))]
 
-use cosmwasm_std::{coins, from_json, to_json_binary, Addr, Empty, StdError};
+use cosmwasm_std::{coins, from_json, to_json_binary, Addr, Decimal, Empty, StdError, Uint128};
 use cw20::Cw20ExecuteMsg;
 
-use astroport::asset::{native_asset_info, token_asset_info};
+use astroport::asset::{native_asset_info, token_asset_info, AssetInfo};
+use astroport::factory;
 use astroport::factory::PairType;
-use astroport::router::{ExecuteMsg, InstantiateMsg, SwapOperation, SwapResponseData};
+use astroport::router::{
+    BestRouteResponse, ConfigResponse, ExecuteMsg, FrontendFee, InstantiateMsg, QueryMsg,
+    ReachableAssetsResponse, RoutePreference, SimulateSwapOperationsResponse, SwapOperation,
+    SwapResponseData, MAX_FRONTEND_FEE_BPS, MAX_SWAP_OPERATIONS,
+};
 use astroport_router::error::ContractError;
 use astroport_test::cw_multi_test::{AppBuilder, Contract, ContractWrapper, Executor};
 use astroport_test::modules::stargate::{MockStargate, StargateApp as App};
@@ -31,6 +36,40 @@ fn mock_app() -> App {
         .build(|_, _, _| {})
 }
 
+#[test]
+fn query_config() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&router, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            astroport_factory: helper.factory,
+            max_swap_operations: MAX_SWAP_OPERATIONS as u32,
+        }
+    );
+}
+
 #[test]
 fn router_does_not_enforce_spread_assertion() {
     let mut app = mock_app();
@@ -96,6 +135,9 @@ fn router_does_not_enforce_spread_assertion() {
                     minimum_receive: None,
                     to: None,
                     max_spread: None,
+                    frontend_fee: None,
+                    recipients: None,
+                    allow_partial: false,
                 })
                 .unwrap(),
             },
@@ -126,6 +168,9 @@ fn router_does_not_enforce_spread_assertion() {
                     minimum_receive: None,
                     to: None,
                     max_spread: None,
+                    frontend_fee: None,
+                    recipients: None,
+                    allow_partial: false,
                 })
                 .unwrap(),
             },
@@ -217,6 +262,9 @@ fn route_through_pairs_with_natives() {
                 to: None,
                 max_spread: None,
                 minimum_receive: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
             },
             &[],
         )
@@ -238,6 +286,9 @@ fn route_through_pairs_with_natives() {
                 to: None,
                 max_spread: None,
                 minimum_receive: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
             },
             &[],
         )
@@ -248,69 +299,817 @@ fn route_through_pairs_with_natives() {
             offer_asset: denom_x.to_string(),
             ask_asset: denom_x.to_string()
         }
-    );
+    );
+
+    // End sanity checks
+
+    mint_native(&mut app, &denom_x, 50_000_000000, &owner).unwrap();
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            router.clone(),
+            &ExecuteMsg::ExecuteSwapOperations {
+                operations: vec![
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: native_asset_info(denom_x.to_string()),
+                        ask_asset_info: native_asset_info(denom_y.to_string()),
+                    },
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: native_asset_info(denom_y.to_string()),
+                        ask_asset_info: native_asset_info(denom_z.to_string()),
+                    },
+                ],
+                minimum_receive: None,
+                to: None,
+                max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
+            },
+            &coins(50_000_000000, denom_x),
+        )
+        .unwrap();
+
+    let resp_data: SwapResponseData = from_json(&resp.data.unwrap()).unwrap();
+
+    assert_eq!(resp_data.return_amount.u128(), 32_258_064515);
+
+    mint_native(&mut app, &denom_x, 50_000_000000, &owner).unwrap();
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            router,
+            &ExecuteMsg::ExecuteSwapOperations {
+                operations: vec![
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: native_asset_info(denom_x.to_string()),
+                        ask_asset_info: native_asset_info(denom_y.to_string()),
+                    },
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: native_asset_info(denom_y.to_string()),
+                        ask_asset_info: native_asset_info(denom_z.to_string()),
+                    },
+                ],
+                minimum_receive: Some(50_000_000000u128.into()), // <--- enforcing minimum receive with 1:1 rate (which practically impossible)
+                to: None,
+                max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
+            },
+            &coins(50_000_000000, denom_x),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AssertionMinimumReceive {
+            receive: 50_000_000000u128.into(),
+            amount: 15_360_983102u128.into()
+        }
+    );
+}
+
+#[test]
+fn simulation_matches_execution_with_native_legs() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let denom_x = "denom_x";
+    let denom_y = "denom_y";
+    let denom_z = "denom_z";
+
+    for (a, b, typ, liq) in [
+        (&denom_x, &denom_y, PairType::Xyk {}, 100_000_000000),
+        (&denom_y, &denom_z, PairType::Stable {}, 1_000_000_000000),
+    ] {
+        let pair = helper
+            .create_pair(
+                &mut app,
+                &owner,
+                typ,
+                [
+                    native_asset_info(a.to_string()),
+                    native_asset_info(b.to_string()),
+                ],
+                None,
+            )
+            .unwrap();
+        mint_native(&mut app, a, liq, &pair).unwrap();
+        mint_native(&mut app, b, liq, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: native_asset_info(denom_x.to_string()),
+            ask_asset_info: native_asset_info(denom_y.to_string()),
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: native_asset_info(denom_y.to_string()),
+            ask_asset_info: native_asset_info(denom_z.to_string()),
+        },
+    ];
+
+    let offer_amount = Uint128::new(10_000_000000);
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount,
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+
+    // Native legs forward the whole intermediate balance, so the executed output equals the quote
+    mint_native(&mut app, &denom_x, offer_amount.u128(), &owner).unwrap();
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            router,
+            &ExecuteMsg::ExecuteSwapOperations {
+                operations,
+                minimum_receive: Some(simulation.amount),
+                to: None,
+                max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
+            },
+            &coins(offer_amount.u128(), denom_x),
+        )
+        .unwrap();
+    let resp_data: SwapResponseData = from_json(&resp.data.unwrap()).unwrap();
+    assert_eq!(resp_data.return_amount, simulation.amount);
+    assert_eq!(
+        app.wrap().query_balance(&owner, denom_z).unwrap().amount,
+        simulation.amount
+    );
+}
+
+#[test]
+fn partial_route_returns_intermediate_asset() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let denom_x = "denom_x";
+    let denom_y = "denom_y";
+    let denom_z = "denom_z";
+    let denom_w = "denom_w";
+
+    // The second pair has no liquidity thus the last hop stops the route.
+    // The third pair is too shallow for the route output
+    for (a, b, liq) in [
+        (&denom_x, &denom_y, 100_000_000000),
+        (&denom_y, &denom_z, 0),
+        (&denom_y, &denom_w, 1_000000),
+    ] {
+        let pair = helper
+            .create_pair(
+                &mut app,
+                &owner,
+                PairType::Xyk {},
+                [
+                    native_asset_info(a.to_string()),
+                    native_asset_info(b.to_string()),
+                ],
+                None,
+            )
+            .unwrap();
+        if liq > 0 {
+            mint_native(&mut app, a, liq, &pair).unwrap();
+            mint_native(&mut app, b, liq, &pair).unwrap();
+        }
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let first_hop = SwapOperation::AstroSwap {
+        offer_asset_info: native_asset_info(denom_x.to_string()),
+        ask_asset_info: native_asset_info(denom_y.to_string()),
+    };
+    let operations = vec![
+        first_hop.clone(),
+        SwapOperation::AstroSwap {
+            offer_asset_info: native_asset_info(denom_y.to_string()),
+            ask_asset_info: native_asset_info(denom_z.to_string()),
+        },
+    ];
+    let swap_msg = |allow_partial: bool, max_spread| ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        minimum_receive: Some(Uint128::new(1_000000)),
+        to: None,
+        max_spread,
+        frontend_fee: None,
+        recipients: None,
+        allow_partial,
+    };
+
+    let offer_amount = 1_000_000000u128;
+    mint_native(&mut app, &denom_x, 2 * offer_amount, &owner).unwrap();
+
+    // The whole route reverts by default
+    app.execute_contract(
+        owner.clone(),
+        router.clone(),
+        &swap_msg(false, None),
+        &coins(offer_amount, denom_x),
+    )
+    .unwrap_err();
+
+    // Partial routes must bound the slippage of every hop
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            router.clone(),
+            &swap_msg(true, None),
+            &coins(offer_amount, denom_x),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PartialRouteWithoutMaxSpread {}
+    );
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            router.clone(),
+            &swap_msg(true, Some(Decimal::permille(1))),
+            &coins(offer_amount, denom_x),
+        )
+        .unwrap_err();
+    assert!(
+        err.root_cause()
+            .to_string()
+            .contains("Operation exceeds max spread limit"),
+        "Unexpected error: {}",
+        err.root_cause()
+    );
+
+    // Funds which were already held by the router are not paid out
+    let router_dust = 1_000u128;
+    mint_native(&mut app, &denom_y, router_dust, &router).unwrap();
+
+    let first_hop_sim: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: offer_amount.into(),
+                operations: vec![first_hop],
+            },
+        )
+        .unwrap();
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            router.clone(),
+            &swap_msg(true, Some(Decimal::percent(5))),
+            &coins(offer_amount, denom_x),
+        )
+        .unwrap();
+    let resp_data: SwapResponseData = from_json(&resp.data.unwrap()).unwrap();
+    assert_eq!(resp_data.return_amount, first_hop_sim.amount);
+
+    // The intermediate asset is sent to the user and only the dust is left in the router
+    assert_eq!(
+        app.wrap().query_balance(&owner, denom_y).unwrap().amount,
+        first_hop_sim.amount
+    );
+    assert!(app
+        .wrap()
+        .query_balance(&owner, denom_z)
+        .unwrap()
+        .amount
+        .is_zero());
+    assert_eq!(
+        app.wrap()
+            .query_balance(&owner, denom_x)
+            .unwrap()
+            .amount
+            .u128(),
+        offer_amount
+    );
+    for (denom, amount) in [(denom_x, 0), (denom_y, router_dust), (denom_z, 0)] {
+        assert_eq!(
+            app.wrap()
+                .query_balance(&router, denom)
+                .unwrap()
+                .amount
+                .u128(),
+            amount
+        );
+    }
+
+    // Failures other than missing liquidity revert the whole route
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            router.clone(),
+            &ExecuteMsg::ExecuteSwapOperations {
+                operations: vec![
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: native_asset_info(denom_x.to_string()),
+                        ask_asset_info: native_asset_info(denom_y.to_string()),
+                    },
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: native_asset_info(denom_y.to_string()),
+                        ask_asset_info: native_asset_info(denom_w.to_string()),
+                    },
+                ],
+                minimum_receive: None,
+                to: None,
+                max_spread: Some(Decimal::percent(5)),
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: true,
+            },
+            &coins(offer_amount, denom_x),
+        )
+        .unwrap_err();
+    assert!(
+        err.root_cause()
+            .to_string()
+            .contains("Operation exceeds max spread limit"),
+        "Unexpected error: {}",
+        err.root_cause()
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&owner, denom_x)
+            .unwrap()
+            .amount
+            .u128(),
+        offer_amount
+    );
+}
+
+#[test]
+fn cached_pairs() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let asset_infos = [
+        native_asset_info("denom_x".to_string()),
+        native_asset_info("denom_y".to_string()),
+    ];
+    let pair = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            asset_infos.clone(),
+            None,
+        )
+        .unwrap();
+    mint_native(&mut app, "denom_x", 100_000_000000, &pair).unwrap();
+    mint_native(&mut app, "denom_y", 100_000_000000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    // The pair is cached with the reversed assets order; the cache key doesn't depend on it
+    let reversed_pair = [asset_infos[1].clone(), asset_infos[0].clone()];
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random"),
+            router.clone(),
+            &ExecuteMsg::CachePairs {
+                pairs: vec![reversed_pair.clone()],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute_contract(
+        owner.clone(),
+        router.clone(),
+        &ExecuteMsg::CachePairs {
+            pairs: vec![reversed_pair],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let user = Addr::unchecked("user");
+    let swap_msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![SwapOperation::AstroSwap {
+            offer_asset_info: asset_infos[0].clone(),
+            ask_asset_info: asset_infos[1].clone(),
+        }],
+        minimum_receive: None,
+        to: None,
+        max_spread: None,
+        frontend_fee: None,
+        recipients: None,
+        allow_partial: false,
+    };
+    mint_native(&mut app, "denom_x", 2_000000, &user).unwrap();
+    app.execute_contract(
+        user.clone(),
+        router.clone(),
+        &swap_msg,
+        &coins(1_000000, "denom_x"),
+    )
+    .unwrap();
+    let balance = app.wrap().query_balance(&user, "denom_y").unwrap();
+    assert!(!balance.amount.is_zero());
+
+    // Invalidated pairs are resolved via the factory again
+    app.execute_contract(
+        owner.clone(),
+        router.clone(),
+        &ExecuteMsg::InvalidatePairs {
+            pairs: vec![asset_infos.clone()],
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user.clone(),
+        router.clone(),
+        &swap_msg,
+        &coins(500_000, "denom_x"),
+    )
+    .unwrap();
+
+    // A cached pair is not used once the factory doesn't know it anymore
+    app.execute_contract(
+        owner.clone(),
+        router.clone(),
+        &ExecuteMsg::CachePairs {
+            pairs: vec![asset_infos.clone()],
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &factory::ExecuteMsg::Deregister {
+            asset_infos: asset_infos.to_vec(),
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            user.clone(),
+            router.clone(),
+            &swap_msg,
+            &coins(500_000, "denom_x"),
+        )
+        .unwrap_err();
+    assert!(
+        err.root_cause()
+            .to_string()
+            .contains("Querier contract error"),
+        "Unexpected error: {}",
+        err.root_cause()
+    );
+}
+
+#[test]
+fn route_with_frontend_fee() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let denom_x = "denom_x";
+    let denom_y = "denom_y";
+
+    let pair = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [
+                native_asset_info(denom_x.to_string()),
+                native_asset_info(denom_y.to_string()),
+            ],
+            None,
+        )
+        .unwrap();
+    mint_native(&mut app, denom_x, 100_000_000000, &pair).unwrap();
+    mint_native(&mut app, denom_y, 100_000_000000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![SwapOperation::AstroSwap {
+        offer_asset_info: native_asset_info(denom_x.to_string()),
+        ask_asset_info: native_asset_info(denom_y.to_string()),
+    }];
+    let frontend = Addr::unchecked("frontend");
+    let user = Addr::unchecked("user");
+    let swap_msg = |frontend_fee: Option<FrontendFee>| ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        minimum_receive: None,
+        to: None,
+        max_spread: None,
+        frontend_fee,
+        recipients: None,
+        allow_partial: false,
+    };
+
+    mint_native(&mut app, denom_x, 3_000_000000, &user).unwrap();
+
+    for bps in [0, MAX_FRONTEND_FEE_BPS + 1] {
+        let err = app
+            .execute_contract(
+                user.clone(),
+                router.clone(),
+                &swap_msg(Some(FrontendFee {
+                    address: frontend.to_string(),
+                    bps,
+                })),
+                &coins(1_000_000000, denom_x),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::FrontendFeeOutOfBounds {}
+        );
+    }
+
+    // Swap with a frontend fee
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: 1_000_000000u128.into(),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    let resp = app
+        .execute_contract(
+            user.clone(),
+            router.clone(),
+            &swap_msg(Some(FrontendFee {
+                address: frontend.to_string(),
+                bps: 50,
+            })),
+            &coins(1_000_000000, denom_x),
+        )
+        .unwrap();
+
+    let fee_amount = simulation.amount.multiply_ratio(50u128, 10000u128);
+    let resp_data: SwapResponseData = from_json(&resp.data.unwrap()).unwrap();
+    assert_eq!(resp_data.return_amount, simulation.amount - fee_amount);
+    assert_eq!(
+        app.wrap().query_balance(&frontend, denom_y).unwrap().amount,
+        fee_amount
+    );
+    assert_eq!(
+        app.wrap().query_balance(&user, denom_y).unwrap().amount,
+        simulation.amount - fee_amount
+    );
+    assert!(app
+        .wrap()
+        .query_balance(&router, denom_y)
+        .unwrap()
+        .amount
+        .is_zero());
+
+    // Swap without a frontend fee leaves the output intact
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: 1_000_000000u128.into(),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    let user_balance = app.wrap().query_balance(&user, denom_y).unwrap().amount;
+    let resp = app
+        .execute_contract(
+            user.clone(),
+            router.clone(),
+            &swap_msg(None),
+            &coins(1_000_000000, denom_x),
+        )
+        .unwrap();
+
+    let resp_data: SwapResponseData = from_json(&resp.data.unwrap()).unwrap();
+    assert_eq!(resp_data.return_amount, simulation.amount);
+    assert_eq!(
+        app.wrap().query_balance(&user, denom_y).unwrap().amount,
+        user_balance + simulation.amount
+    );
+    assert_eq!(
+        app.wrap().query_balance(&frontend, denom_y).unwrap().amount,
+        fee_amount
+    );
+}
+
+#[test]
+fn route_with_recipients() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let denom_x = "denom_x";
+    let denom_y = "denom_y";
+
+    let pair = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [
+                native_asset_info(denom_x.to_string()),
+                native_asset_info(denom_y.to_string()),
+            ],
+            None,
+        )
+        .unwrap();
+    mint_native(&mut app, denom_x, 100_000_000000, &pair).unwrap();
+    mint_native(&mut app, denom_y, 100_000_000000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![SwapOperation::AstroSwap {
+        offer_asset_info: native_asset_info(denom_x.to_string()),
+        ask_asset_info: native_asset_info(denom_y.to_string()),
+    }];
+    let user = Addr::unchecked("user");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let swap_msg = |to: Option<String>, recipients: Vec<(String, Decimal)>| {
+        ExecuteMsg::ExecuteSwapOperations {
+            operations: operations.clone(),
+            minimum_receive: None,
+            to,
+            max_spread: None,
+            frontend_fee: None,
+            recipients: Some(recipients),
+            allow_partial: false,
+        }
+    };
 
-    // End sanity checks
+    mint_native(&mut app, denom_x, 1_000_000000, &user).unwrap();
 
-    mint_native(&mut app, &denom_x, 50_000_000000, &owner).unwrap();
+    for (to, recipients, expected_err) in [
+        (
+            None,
+            vec![
+                (alice.to_string(), Decimal::percent(30)),
+                (bob.to_string(), Decimal::percent(60)),
+            ],
+            ContractError::InvalidRecipients {},
+        ),
+        (None, vec![], ContractError::InvalidRecipients {}),
+        (
+            None,
+            vec![
+                (alice.to_string(), Decimal::zero()),
+                (bob.to_string(), Decimal::one()),
+            ],
+            ContractError::InvalidRecipients {},
+        ),
+        (
+            Some(alice.to_string()),
+            vec![(bob.to_string(), Decimal::one())],
+            ContractError::RecipientsConflict {},
+        ),
+    ] {
+        let err = app
+            .execute_contract(
+                user.clone(),
+                router.clone(),
+                &swap_msg(to, recipients),
+                &coins(1_000_000000, denom_x),
+            )
+            .unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), expected_err);
+    }
+
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: 1_000_000000u128.into(),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
     let resp = app
         .execute_contract(
-            owner.clone(),
+            user.clone(),
             router.clone(),
-            &ExecuteMsg::ExecuteSwapOperations {
-                operations: vec![
-                    SwapOperation::AstroSwap {
-                        offer_asset_info: native_asset_info(denom_x.to_string()),
-                        ask_asset_info: native_asset_info(denom_y.to_string()),
-                    },
-                    SwapOperation::AstroSwap {
-                        offer_asset_info: native_asset_info(denom_y.to_string()),
-                        ask_asset_info: native_asset_info(denom_z.to_string()),
-                    },
+            &swap_msg(
+                None,
+                vec![
+                    (alice.to_string(), Decimal::percent(30)),
+                    (bob.to_string(), Decimal::percent(70)),
                 ],
-                minimum_receive: None,
-                to: None,
-                max_spread: None,
-            },
-            &coins(50_000_000000, denom_x),
+            ),
+            &coins(1_000_000000, denom_x),
         )
         .unwrap();
 
     let resp_data: SwapResponseData = from_json(&resp.data.unwrap()).unwrap();
+    assert_eq!(resp_data.return_amount, simulation.amount);
 
-    assert_eq!(resp_data.return_amount.u128(), 32_258_064515);
-
-    mint_native(&mut app, &denom_x, 50_000_000000, &owner).unwrap();
-    let err = app
-        .execute_contract(
-            owner.clone(),
-            router,
-            &ExecuteMsg::ExecuteSwapOperations {
-                operations: vec![
-                    SwapOperation::AstroSwap {
-                        offer_asset_info: native_asset_info(denom_x.to_string()),
-                        ask_asset_info: native_asset_info(denom_y.to_string()),
-                    },
-                    SwapOperation::AstroSwap {
-                        offer_asset_info: native_asset_info(denom_y.to_string()),
-                        ask_asset_info: native_asset_info(denom_z.to_string()),
-                    },
-                ],
-                minimum_receive: Some(50_000_000000u128.into()), // <--- enforcing minimum receive with 1:1 rate (which practically impossible)
-                to: None,
-                max_spread: None,
-            },
-            &coins(50_000_000000, denom_x),
-        )
-        .unwrap_err();
-
+    let alice_amount = simulation.amount * Decimal::percent(30);
     assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::AssertionMinimumReceive {
-            receive: 50_000_000000u128.into(),
-            amount: 15_360_983102u128.into()
-        }
+        app.wrap().query_balance(&alice, denom_y).unwrap().amount,
+        alice_amount
+    );
+    assert_eq!(
+        app.wrap().query_balance(&bob, denom_y).unwrap().amount,
+        simulation.amount - alice_amount
     );
+    assert!(app
+        .wrap()
+        .query_balance(&router, denom_y)
+        .unwrap()
+        .amount
+        .is_zero());
+    assert!(app
+        .wrap()
+        .query_balance(&user, denom_y)
+        .unwrap()
+        .amount
+        .is_zero());
 }
 
 #[test]
@@ -485,6 +1284,9 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
             })
             .unwrap(),
         },
@@ -572,6 +1374,9 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
             })
             .unwrap(),
         },
@@ -664,6 +1469,9 @@ fn test_swap_route() {
                 minimum_receive: Some(Uint128::new(9_997_000)),
                 to: None,
                 max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
             },
             &[],
         )
@@ -799,6 +1607,9 @@ fn test_swap_route() {
                 minimum_receive: Some(donated_atom),
                 to: None,
                 max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
             })
             .unwrap(),
         },
@@ -857,6 +1668,9 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                frontend_fee: None,
+                recipients: None,
+                allow_partial: false,
             },
             &[],
         )
@@ -893,3 +1707,339 @@ fn test_swap_route() {
     let profit = balance_res.balance.saturating_sub(donated_atom);
     println!("Attacker2's profit: {:?}", profit);
 }
+
+#[test]
+fn best_route_picks_highest_output() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+
+    // The direct pool is shallow while the two hop route goes through deep pools
+    for (a, b, liq) in [
+        (&token_x, &token_z, 1_000_000000),
+        (&token_x, &token_y, 1_000_000_000000),
+        (&token_y, &token_z, 1_000_000_000000),
+    ] {
+        let pair = helper
+            .create_pair(
+                &mut app,
+                &owner,
+                PairType::Xyk {},
+                [token_asset_info(a.clone()), token_asset_info(b.clone())],
+                None,
+            )
+            .unwrap();
+        mint(&mut app, &owner, a, liq, &pair).unwrap();
+        mint(&mut app, &owner, b, liq, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let direct = vec![SwapOperation::AstroSwap {
+        offer_asset_info: token_asset_info(token_x.clone()),
+        ask_asset_info: token_asset_info(token_z.clone()),
+    }];
+    let two_hops = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_y.clone()),
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_y.clone()),
+            ask_asset_info: token_asset_info(token_z.clone()),
+        },
+    ];
+    let offer_amount = Uint128::new(100_000000);
+
+    let best: BestRouteResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::BestRoute {
+                offer_amount,
+                candidates: vec![direct.clone(), two_hops.clone()],
+                preference: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(best.index, 1);
+
+    let direct_sim: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount,
+                operations: direct.clone(),
+            },
+        )
+        .unwrap();
+    let two_hops_sim: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount,
+                operations: two_hops,
+            },
+        )
+        .unwrap();
+    assert_eq!(best.amount, two_hops_sim.amount);
+    assert!(best.amount > direct_sim.amount);
+
+    // Candidates must describe the same swap
+    let err = app
+        .wrap()
+        .query_wasm_smart::<BestRouteResponse>(
+            &router,
+            &QueryMsg::BestRoute {
+                offer_amount,
+                candidates: vec![
+                    direct,
+                    vec![SwapOperation::AstroSwap {
+                        offer_asset_info: token_asset_info(token_x.clone()),
+                        ask_asset_info: token_asset_info(token_y.clone()),
+                    }],
+                ],
+                preference: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Querier contract error: {}",
+            ContractError::CandidatesMismatch {}
+        ))
+    );
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<BestRouteResponse>(
+            &router,
+            &QueryMsg::BestRoute {
+                offer_amount,
+                candidates: vec![],
+                preference: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Querier contract error: {}",
+            ContractError::InvalidCandidatesNumber {}
+        ))
+    );
+}
+
+#[test]
+fn best_route_preferences() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    // Stable pairs in this setup charge a 1% swap fee while xyk pairs are fee-free
+    let factory_config: factory::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &factory::QueryMsg::Config {})
+        .unwrap();
+    let stable_config = factory_config
+        .pair_configs
+        .into_iter()
+        .find(|config| config.pair_type == PairType::Stable {})
+        .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &factory::ExecuteMsg::UpdatePairConfig {
+            config: factory::PairConfig {
+                total_fee_bps: 100,
+                ..stable_config
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+
+    // The direct pool is fee-free but shallow while the two hop route goes through deep pools with fees
+    for (a, b, liq, pair_type) in [
+        (&token_x, &token_z, 1_000_000000, PairType::Xyk {}),
+        (&token_x, &token_y, 1_000_000_000000, PairType::Stable {}),
+        (&token_y, &token_z, 1_000_000_000000, PairType::Stable {}),
+    ] {
+        let pair = helper
+            .create_pair(
+                &mut app,
+                &owner,
+                pair_type,
+                [token_asset_info(a.clone()), token_asset_info(b.clone())],
+                None,
+            )
+            .unwrap();
+        mint(&mut app, &owner, a, liq, &pair).unwrap();
+        mint(&mut app, &owner, b, liq, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let direct = vec![SwapOperation::AstroSwap {
+        offer_asset_info: token_asset_info(token_x.clone()),
+        ask_asset_info: token_asset_info(token_z.clone()),
+    }];
+    let two_hops = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_y.clone()),
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_y.clone()),
+            ask_asset_info: token_asset_info(token_z.clone()),
+        },
+    ];
+    let query_best_route = |preference| -> BestRouteResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &router,
+                &QueryMsg::BestRoute {
+                    offer_amount: Uint128::new(100_000000),
+                    candidates: vec![direct.clone(), two_hops.clone()],
+                    preference,
+                },
+            )
+            .unwrap()
+    };
+
+    let highest_output = query_best_route(Some(RoutePreference::HighestOutput));
+    assert_eq!(highest_output.index, 1);
+    // Two hops with a 1% fee each compound to ~1.99%
+    assert!(
+        highest_output.fee_rate > Decimal::permille(19)
+            && highest_output.fee_rate < Decimal::percent(2),
+        "{}",
+        highest_output.fee_rate
+    );
+    assert_eq!(query_best_route(None), highest_output);
+
+    let lowest_fee = query_best_route(Some(RoutePreference::LowestFee));
+    assert_eq!(lowest_fee.index, 0);
+    assert_eq!(lowest_fee.fee_rate, Decimal::zero());
+    assert!(lowest_fee.amount < highest_output.amount);
+}
+
+#[test]
+fn reachable_assets() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["TOA", "TOB", "TOC", "TOD", "TOE", "TOF"]
+        .into_iter()
+        .map(|name| {
+            token_asset_info(instantiate_token(
+                &mut app,
+                helper.cw20_token_code_id,
+                &owner,
+                name,
+                None,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    // A - B - C - D chain and a standalone E - F pair
+    for (a, b) in [
+        (tokens[0].clone(), tokens[1].clone()),
+        (tokens[1].clone(), tokens[2].clone()),
+        (tokens[2].clone(), tokens[3].clone()),
+        (tokens[4].clone(), tokens[5].clone()),
+    ] {
+        helper
+            .create_pair(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let query_reachable = |max_hops: u32| -> Vec<AssetInfo> {
+        let resp: ReachableAssetsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &router,
+                &QueryMsg::ReachableAssets {
+                    from: tokens[0].clone(),
+                    max_hops,
+                },
+            )
+            .unwrap();
+        assert!(!resp.truncated);
+        let mut assets = resp.assets;
+        assets.sort_by_key(|asset| asset.to_string());
+        assets
+    };
+    let sorted = |mut assets: Vec<AssetInfo>| {
+        assets.sort_by_key(|asset| asset.to_string());
+        assets
+    };
+
+    assert_eq!(query_reachable(0), vec![]);
+    assert_eq!(query_reachable(1), vec![tokens[1].clone()]);
+    assert_eq!(
+        query_reachable(2),
+        sorted(vec![tokens[1].clone(), tokens[2].clone()])
+    );
+    assert_eq!(
+        query_reachable(10),
+        sorted(vec![
+            tokens[1].clone(),
+            tokens[2].clone(),
+            tokens[3].clone()
+        ])
+    );
+}
@@ -41,6 +41,7 @@ fn main() {
         amount: Default::default(),
         last_rewards_index: Default::default(),
         last_claim_time: 0,
+        lock: None,
     };
     let user_info_storage_bytes = to_json_binary(&user_info).unwrap().len();
     println!("user info storage bytes {user_info_storage_bytes}");
@@ -5,7 +5,9 @@ use cw_utils::PaymentError;
 use thiserror::Error;
 
 use astroport::factory::PairType;
-use astroport::incentives::MAX_REWARD_TOKENS;
+use astroport::incentives::{
+    MAX_HARVEST_FEE_BPS, MAX_LOCK_MULTIPLIER, MAX_REWARD_TOKENS, MAX_REWARD_VESTING_DURATION,
+};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -27,6 +29,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Pool {lp_token} is still in use. Only deprecated pools can be wound down")]
+    PoolNotDeprecated { lp_token: String },
+
     #[error("Duplicated pool found")]
     DuplicatedPoolFound {},
 
@@ -64,12 +69,66 @@ pub enum ContractError {
     #[error("No orphaned rewards to claim")]
     NoOrphanedRewards {},
 
+    #[error("No reward dust to sweep for pool {lp_token}")]
+    NoRewardDust { lp_token: String },
+
     #[error("Failed to set 0 alloc point for pool {lp_token}")]
     ZeroAllocPoint { lp_token: String },
 
+    #[error(
+        "Total alloc points overflow. The sum of pool alloc points must not exceed {}",
+        Uint128::MAX
+    )]
+    AllocPointsOverflow {},
+
     #[error("Failed to migrate contract")]
     MigrationError {},
 
+    #[error("Account {account} has no registered withdraw pubkey")]
+    NoWithdrawPubkey { account: String },
+
+    #[error("Invalid withdraw pubkey")]
+    InvalidWithdrawPubkey {},
+
+    #[error("Invalid withdraw signature")]
+    InvalidWithdrawSignature {},
+
     #[error("Sent insufficient reward {reward} for pool {lp_token}")]
     InsuffiicientRewardToken { reward: String, lp_token: String },
+
+    #[error("Harvest fee must not exceed {MAX_HARVEST_FEE_BPS} bps")]
+    HarvestFeeTooHigh {},
+
+    #[error("Lock multiplier must be greater than 1 and not exceed {MAX_LOCK_MULTIPLIER}")]
+    InvalidLockMultiplier {},
+
+    #[error("Lock for boost is disabled")]
+    LockBoostDisabled {},
+
+    #[error("Lock duration must be at least {min_lock_duration} seconds")]
+    LockDurationTooShort { min_lock_duration: u64 },
+
+    #[error("Lock duration must not exceed {max_lock_duration} seconds")]
+    LockDurationTooLong { max_lock_duration: u64 },
+
+    #[error("Minimum lock duration must not exceed the maximum lock duration")]
+    InvalidLockDurations {},
+
+    #[error("Position is locked until {unlock_ts}")]
+    PositionLocked { unlock_ts: u64 },
+
+    #[error("Rewards can't be claimed until block {next_claim_block}")]
+    ClaimCooldown { next_claim_block: u64 },
+
+    #[error("Vesting contract is underfunded: available {available}, required {required}")]
+    VestingUnderfunded {
+        available: Uint128,
+        required: Uint128,
+    },
+
+    #[error("Reward vesting duration must not exceed {MAX_REWARD_VESTING_DURATION} seconds")]
+    RewardVestingDurationTooLong {},
+
+    #[error("Pool {lp_token} is not in the active pools list")]
+    PoolNotActive { lp_token: String },
 }
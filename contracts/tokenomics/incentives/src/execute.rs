@@ -3,11 +3,12 @@ use std::collections::HashSet;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, ensure, from_json, Addr, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128,
+    attr, ensure, from_json, to_json_vec, Addr, Binary, Decimal, DepsMut, Env, MessageInfo,
+    OverflowError, OverflowOperation, Response, StdError, StdResult, Uint128,
 };
 use cw_utils::one_coin;
 use itertools::Itertools;
+use sha2::{Digest, Sha256};
 
 use astroport::asset::{
     addr_opt_validate, determine_asset_info, validate_native_denom, Asset, AssetInfo, AssetInfoExt,
@@ -16,17 +17,22 @@ use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_ow
 use astroport::factory;
 use astroport::factory::PairType;
 use astroport::incentives::{
-    Cw20Msg, ExecuteMsg, IncentivizationFeeInfo, RewardType, TOKEN_TRANSFER_GAS_LIMIT,
+    Cw20Msg, ExecuteMsg, HarvestFeeInfo, IncentivizationFeeInfo, LockBoostConfig, PositionLock,
+    RewardType, WithdrawPermit, MAX_HARVEST_FEE_BPS, MAX_LOCK_MULTIPLIER,
+    MAX_REWARD_VESTING_DURATION, TOKEN_TRANSFER_GAS_LIMIT,
 };
 
 use crate::error::ContractError;
 use crate::state::{
-    Op, PoolInfo, UserInfo, ACTIVE_POOLS, BLOCKED_TOKENS, CONFIG, OWNERSHIP_PROPOSAL,
+    Op, PoolInfo, UserInfo, ACTIVE_POOLS, BLOCKED_TOKENS, CLAIM_COOLDOWN_BLOCKS, CONFIG,
+    DEPOSIT_BLOCKS, FALLBACK_POOL, HARVEST_FEE, LAST_CLAIM_BLOCKS, LOCK_BOOST, MIN_HOLDING_BLOCKS,
+    OWNERSHIP_PROPOSAL, PARTIAL_VESTING_PAYOUTS, REWARD_RECIPIENTS, REWARD_VESTING_DURATIONS,
+    WITHDRAW_NONCES, WITHDRAW_PUBKEYS,
 };
 use crate::utils::{
-    asset_info_key, claim_orphaned_rewards, claim_rewards, deactivate_blocked_pools,
-    deactivate_pool, incentivize_many, is_pool_registered, query_pair_info,
-    remove_reward_from_pool,
+    asset_info_key, check_claim_cooldown, claim_orphaned_rewards, claim_rewards,
+    deactivate_blocked_pools, deactivate_pool, incentivize_many, is_pool_registered,
+    query_pair_info, remove_reward_from_pool, sweep_reward_dust, update_deposit_block,
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -38,6 +44,7 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::SetupPools { pools } => setup_pools(deps, env, info, pools),
+        ExecuteMsg::SetBatch { pools } => set_batch(deps, env, info, pools),
         ExecuteMsg::ClaimRewards { lp_tokens } => {
             // Check for duplicated pools
             ensure!(
@@ -56,6 +63,12 @@ pub fn execute(
                 })
                 .collect::<Result<Vec<_>, ContractError>>()?;
 
+            // Only explicit claims are subject to the cooldown.
+            // Deposits and withdrawals claim rewards too but must never be blocked
+            for (lp_asset, _, _) in &tuples {
+                check_claim_cooldown(deps.storage, &env, lp_asset, &info.sender)?;
+            }
+
             // Convert to mutable references
             let mut_tuples = tuples
                 .iter_mut()
@@ -64,7 +77,14 @@ pub fn execute(
 
             // Compose response. Return early in case of error
             let config = CONFIG.load(deps.storage)?;
-            let response = claim_rewards(deps.storage, &config, env, &info.sender, mut_tuples)?;
+            let response = claim_rewards(
+                deps.storage,
+                deps.querier,
+                &config,
+                env,
+                &info.sender,
+                mut_tuples,
+            )?;
 
             // Save updates in state
             for (lp_asset, pool_info, user_pos) in tuples {
@@ -95,7 +115,16 @@ pub fn execute(
 
             deposit(deps, env, maybe_lp, info.sender, recipient)
         }
-        ExecuteMsg::Withdraw { lp_token, amount } => withdraw(deps, env, info, lp_token, amount),
+        ExecuteMsg::Withdraw { lp_token, amount } => {
+            withdraw(deps, env, info.sender, lp_token, amount)
+        }
+        ExecuteMsg::SetWithdrawPubkey { pubkey } => set_withdraw_pubkey(deps, info, pubkey),
+        ExecuteMsg::WithdrawFor {
+            lp_token,
+            account,
+            amount,
+            signature,
+        } => withdraw_for(deps, env, lp_token, account, amount, signature),
         ExecuteMsg::SetTokensPerSecond { amount } => set_tokens_per_second(deps, env, info, amount),
         ExecuteMsg::Incentivize { lp_token, schedule } => {
             incentivize_many(deps, info, env, vec![(lp_token, schedule)])
@@ -118,6 +147,37 @@ pub fn execute(
         ExecuteMsg::ClaimOrphanedRewards { limit, receiver } => {
             claim_orphaned_rewards(deps, info, limit, receiver)
         }
+        ExecuteMsg::SweepRewardDust {
+            lp_token,
+            recipient,
+        } => sweep_reward_dust(deps, info, lp_token, recipient),
+        ExecuteMsg::SetHarvestFee { harvest_fee_info } => {
+            set_harvest_fee(deps, info, harvest_fee_info)
+        }
+        ExecuteMsg::SetMinHoldingBlocks {
+            lp_token,
+            min_holding_blocks,
+        } => set_min_holding_blocks(deps, info, lp_token, min_holding_blocks),
+        ExecuteMsg::SetClaimCooldown {
+            lp_token,
+            cooldown_blocks,
+        } => set_claim_cooldown(deps, info, lp_token, cooldown_blocks),
+        ExecuteMsg::SetPoolRewardsPaused { lp_token, paused } => {
+            set_pool_rewards_paused(deps, env, info, lp_token, paused)
+        }
+        ExecuteMsg::SetRewardVestingDuration { lp_token, duration } => {
+            set_reward_vesting_duration(deps, info, lp_token, duration)
+        }
+        ExecuteMsg::SetRewardRecipient { recipient } => set_reward_recipient(deps, info, recipient),
+        ExecuteMsg::SetFallbackPool { lp_token } => set_fallback_pool(deps, info, lp_token),
+        ExecuteMsg::SetPartialVestingPayouts { enabled } => {
+            set_partial_vesting_payouts(deps, info, enabled)
+        }
+        ExecuteMsg::SetLockBoost { config } => set_lock_boost(deps, info, config),
+        ExecuteMsg::LockPosition { lp_token, duration } => {
+            lock_position(deps, env, info, lp_token, duration)
+        }
+        ExecuteMsg::ExpireLock { lp_token, user } => expire_lock(deps, env, lp_token, user),
         ExecuteMsg::UpdateConfig {
             astro_token,
             vesting_contract,
@@ -140,6 +200,9 @@ pub fn execute(
         }
         ExecuteMsg::DeactivatePool { lp_token } => deactivate_pool(deps, info, env, lp_token),
         ExecuteMsg::DeactivateBlockedPools {} => deactivate_blocked_pools(deps, env),
+        ExecuteMsg::RescueStuckLp { lp_token, users } => {
+            rescue_stuck_lp(deps, env, info, lp_token, users)
+        }
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config = CONFIG.load(deps.storage)?;
 
@@ -195,15 +258,31 @@ fn deposit(
     let mut pool_info = PoolInfo::may_load(deps.storage, &maybe_lp.info)?.unwrap_or_default();
     let mut user_info = UserInfo::may_load_position(deps.storage, &staker, &maybe_lp.info)?
         .unwrap_or_else(|| UserInfo::new(&env));
+    let block_height = env.block.height;
+    let is_locked = user_info.is_locked(&env);
 
     let response = claim_rewards(
         deps.storage,
+        deps.querier,
         &config,
         env,
         &staker,
         vec![(&maybe_lp.info, &mut pool_info, &mut user_info)],
     )?;
 
+    // Expired locks don't boost new deposits
+    if !is_locked {
+        user_info.set_lock(None, &mut pool_info);
+    }
+
+    update_deposit_block(
+        deps.storage,
+        block_height,
+        &maybe_lp.info,
+        &staker,
+        user_info.amount,
+        maybe_lp.amount,
+    )?;
     user_info.update_and_sync_position(Op::Add(maybe_lp.amount), &mut pool_info);
     pool_info.save(deps.storage, &maybe_lp.info)?;
     user_info.save(deps.storage, &staker, &maybe_lp.info)?;
@@ -219,13 +298,23 @@ fn deposit(
 fn withdraw(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    account: Addr,
     lp_token: String,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
 
-    let mut user_info = UserInfo::load_position(deps.storage, &info.sender, &lp_token_asset)?;
+    let user_info = UserInfo::load_position(deps.storage, &account, &lp_token_asset)?;
+
+    if let Some(lock) = user_info
+        .lock
+        .as_ref()
+        .filter(|_| user_info.is_locked(&env))
+    {
+        return Err(ContractError::PositionLocked {
+            unlock_ts: lock.unlock_ts,
+        });
+    }
 
     if user_info.amount < amount {
         Err(ContractError::AmountExceedsBalance {
@@ -233,29 +322,9 @@ fn withdraw(
             withdraw_amount: amount,
         })
     } else {
-        let mut pool_info = PoolInfo::load(deps.storage, &lp_token_asset)?;
-
-        let config = CONFIG.load(deps.storage)?;
-        let response = claim_rewards(
-            deps.storage,
-            &config,
-            env,
-            &info.sender,
-            vec![(&lp_token_asset, &mut pool_info, &mut user_info)],
-        )?;
+        let response = withdraw_position(deps, env, &account, &lp_token_asset, user_info, amount)?;
 
-        user_info.update_and_sync_position(Op::Sub(amount), &mut pool_info);
-        pool_info.save(deps.storage, &lp_token_asset)?;
-        if user_info.amount.is_zero() {
-            // If user has withdrawn all LP tokens, we can remove his position
-            user_info.remove(deps.storage, &info.sender, &lp_token_asset);
-        } else {
-            user_info.save(deps.storage, &info.sender, &lp_token_asset)?;
-        }
-
-        let transfer_msg = lp_token_asset.with_balance(amount).into_msg(info.sender)?;
-
-        Ok(response.add_message(transfer_msg).add_attributes([
+        Ok(response.add_attributes([
             attr("action", "withdraw"),
             attr("lp_token", lp_token_asset.to_string()),
             attr("amount", amount),
@@ -263,6 +332,173 @@ fn withdraw(
     }
 }
 
+/// Claims outstanding rewards, removes the position lock and sends `amount` LP tokens
+/// back to the position owner. Lock and balance checks are up to the caller.
+fn withdraw_position(
+    deps: DepsMut,
+    env: Env,
+    account: &Addr,
+    lp_token_asset: &AssetInfo,
+    mut user_info: UserInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut pool_info = PoolInfo::load(deps.storage, lp_token_asset)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let response = claim_rewards(
+        deps.storage,
+        deps.querier,
+        &config,
+        env,
+        account,
+        vec![(lp_token_asset, &mut pool_info, &mut user_info)],
+    )?;
+
+    user_info.set_lock(None, &mut pool_info);
+    user_info.update_and_sync_position(Op::Sub(amount), &mut pool_info);
+    pool_info.save(deps.storage, lp_token_asset)?;
+    if user_info.amount.is_zero() {
+        // If user has withdrawn all LP tokens, we can remove his position
+        user_info.remove(deps.storage, account, lp_token_asset);
+        DEPOSIT_BLOCKS.remove(deps.storage, (lp_token_asset, account));
+        LAST_CLAIM_BLOCKS.remove(deps.storage, (lp_token_asset, account));
+    } else {
+        user_info.save(deps.storage, account, lp_token_asset)?;
+    }
+
+    let transfer_msg = lp_token_asset
+        .with_balance(amount)
+        .into_msg(account.clone())?;
+
+    Ok(response.add_message(transfer_msg))
+}
+
+/// Withdraws whole positions of the listed users from a deprecated pool back to the users.
+/// The pool must be inactive and either deregistered in the factory, contain a blocked token
+/// or have a blocked pair type.
+fn rescue_stuck_lp(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lp_token: String,
+    users: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.owner, ContractError::Unauthorized {});
+
+    let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+    let pool_info = PoolInfo::load(deps.storage, &lp_token_asset)?;
+    let pair_info = query_pair_info(deps.as_ref(), &lp_token_asset)?;
+
+    let is_deregistered = is_pool_registered(deps.querier, &config, &pair_info, &lp_token).is_err();
+    let has_blocked_token = pair_info
+        .asset_infos
+        .iter()
+        .any(|asset| BLOCKED_TOKENS.has(deps.storage, &asset_info_key(asset)));
+    let blacklisted_pair_types: Vec<PairType> = deps
+        .querier
+        .query_wasm_smart(&config.factory, &factory::QueryMsg::BlacklistedPairTypes {})?;
+    let is_blocked_pair_type = blacklisted_pair_types.contains(&pair_info.pair_type);
+
+    ensure!(
+        !pool_info.is_active_pool()
+            && (is_deregistered || has_blocked_token || is_blocked_pair_type),
+        ContractError::PoolNotDeprecated { lp_token }
+    );
+
+    let mut response = Response::new().add_attributes([
+        attr("action", "rescue_stuck_lp"),
+        attr("lp_token", lp_token_asset.to_string()),
+    ]);
+
+    for user in users.iter().unique() {
+        let user = deps.api.addr_validate(user)?;
+        if let Some(user_info) = UserInfo::may_load_position(deps.storage, &user, &lp_token_asset)?
+        {
+            let amount = user_info.amount;
+            let withdraw_response = withdraw_position(
+                deps.branch(),
+                env.clone(),
+                &user,
+                &lp_token_asset,
+                user_info,
+                amount,
+            )?;
+
+            response = response
+                .add_submessages(withdraw_response.messages)
+                .add_attributes(withdraw_response.attributes)
+                .add_attributes([attr("user", user), attr("amount", amount)]);
+        }
+    }
+
+    Ok(response)
+}
+
+fn set_withdraw_pubkey(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Option<Binary>,
+) -> Result<Response, ContractError> {
+    match pubkey {
+        Some(pubkey) => {
+            // Compressed (33 bytes) or uncompressed (65 bytes) secp256k1 public keys are accepted
+            ensure!(
+                matches!(pubkey.len(), 33 | 65),
+                ContractError::InvalidWithdrawPubkey {}
+            );
+            WITHDRAW_PUBKEYS.save(deps.storage, &info.sender, &pubkey)?;
+        }
+        None => WITHDRAW_PUBKEYS.remove(deps.storage, &info.sender),
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_withdraw_pubkey"),
+        attr("account", info.sender),
+    ]))
+}
+
+fn withdraw_for(
+    deps: DepsMut,
+    env: Env,
+    lp_token: String,
+    account: String,
+    amount: Uint128,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let account = deps.api.addr_validate(&account)?;
+    let pubkey = WITHDRAW_PUBKEYS
+        .may_load(deps.storage, &account)?
+        .ok_or_else(|| ContractError::NoWithdrawPubkey {
+            account: account.to_string(),
+        })?;
+    let nonce = WITHDRAW_NONCES
+        .may_load(deps.storage, &account)?
+        .unwrap_or_default();
+
+    let permit = WithdrawPermit {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        account: account.to_string(),
+        lp_token: lp_token.clone(),
+        amount,
+        nonce,
+    };
+    let hash = Sha256::digest(to_json_vec(&permit)?);
+    let is_valid = deps
+        .api
+        .secp256k1_verify(&hash, &signature, &pubkey)
+        .map_err(|_| ContractError::InvalidWithdrawSignature {})?;
+    ensure!(is_valid, ContractError::InvalidWithdrawSignature {});
+
+    // Bump the nonce so the same signature can't be replayed
+    WITHDRAW_NONCES.save(deps.storage, &account, &(nonce + 1))?;
+
+    let response = withdraw(deps, env, account.clone(), lp_token, amount)?;
+
+    Ok(response.add_attributes([attr("account", account), attr("nonce", nonce.to_string())]))
+}
+
 pub fn setup_pools(
     deps: DepsMut,
     env: Env,
@@ -324,10 +560,19 @@ pub fn setup_pools(
         let mut pool_info = PoolInfo::load(deps.storage, &lp_token_asset)?;
         pool_info.update_rewards(deps.storage, &env, &lp_token_asset)?;
         pool_info.disable_astro_rewards();
+        // Pools which remain active keep their pause
+        if !setup_pools.iter().any(|(lp, _)| lp == &lp_token_asset) {
+            pool_info.rewards_paused = false;
+        }
         pool_info.save(deps.storage, &lp_token_asset)?;
     }
 
-    config.total_alloc_points = setup_pools.iter().map(|(_, alloc)| alloc).sum();
+    config.total_alloc_points = setup_pools
+        .iter()
+        .try_fold(Uint128::zero(), |total, (_, alloc)| {
+            total.checked_add(*alloc)
+        })
+        .map_err(|_| ContractError::AllocPointsOverflow {})?;
 
     // Set astro rewards for new active pools
     for (active_pool, alloc_points) in &setup_pools {
@@ -343,6 +588,46 @@ pub fn setup_pools(
     Ok(Response::new().add_attribute("action", "setup_pools"))
 }
 
+pub fn set_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pools: Vec<(String, Uint128)>,
+) -> Result<Response, ContractError> {
+    ensure!(
+        pools.iter().map(|(lp_token, _)| lp_token).all_unique(),
+        ContractError::DuplicatedPoolFound {}
+    );
+
+    // Merge new alloc points into the current active set
+    let mut active_pools = ACTIVE_POOLS
+        .load(deps.storage)?
+        .into_iter()
+        .map(|(lp_asset, alloc_points)| (lp_asset.to_string(), alloc_points))
+        .collect_vec();
+    for (lp_token, alloc_points) in pools {
+        let lp_asset = determine_asset_info(&lp_token, deps.api)?.to_string();
+        match active_pools.iter_mut().find(|(pool, _)| *pool == lp_asset) {
+            Some((_, pool_alloc_points)) => *pool_alloc_points = alloc_points,
+            None => active_pools.push((lp_asset, alloc_points)),
+        }
+    }
+
+    // Total alloc points change, so all active pools are updated at once
+    let response = setup_pools(deps, env, info, active_pools)?;
+
+    Ok(Response::new()
+        .add_submessages(response.messages)
+        .add_events(response.events)
+        .add_attribute("action", "set_batch")
+        .add_attributes(
+            response
+                .attributes
+                .into_iter()
+                .filter(|attr| attr.key != "action"),
+        ))
+}
+
 fn set_tokens_per_second(
     deps: DepsMut,
     env: Env,
@@ -465,6 +750,389 @@ fn update_config(
     Ok(Response::new().add_attributes(attrs))
 }
 
+fn set_harvest_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    harvest_fee_info: Option<HarvestFeeInfo>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attrs = vec![attr("action", "set_harvest_fee")];
+
+    if let Some(harvest_fee_info) = harvest_fee_info {
+        deps.api
+            .addr_validate(harvest_fee_info.fee_receiver.as_str())?;
+        ensure!(
+            harvest_fee_info.fee_bps <= MAX_HARVEST_FEE_BPS,
+            ContractError::HarvestFeeTooHigh {}
+        );
+        attrs.push(attr("fee_receiver", &harvest_fee_info.fee_receiver));
+        attrs.push(attr("fee_bps", harvest_fee_info.fee_bps.to_string()));
+
+        HARVEST_FEE.save(deps.storage, &harvest_fee_info)?;
+    } else {
+        HARVEST_FEE.remove(deps.storage);
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+fn set_min_holding_blocks(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token: String,
+    min_holding_blocks: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+
+    if min_holding_blocks == 0 {
+        MIN_HOLDING_BLOCKS.remove(deps.storage, &lp_token_asset);
+    } else {
+        MIN_HOLDING_BLOCKS.save(deps.storage, &lp_token_asset, &min_holding_blocks)?;
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_min_holding_blocks"),
+        attr("lp_token", lp_token),
+        attr("min_holding_blocks", min_holding_blocks.to_string()),
+    ]))
+}
+
+fn set_claim_cooldown(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token: String,
+    cooldown_blocks: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+
+    if cooldown_blocks == 0 {
+        CLAIM_COOLDOWN_BLOCKS.remove(deps.storage, &lp_token_asset);
+    } else {
+        CLAIM_COOLDOWN_BLOCKS.save(deps.storage, &lp_token_asset, &cooldown_blocks)?;
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_claim_cooldown"),
+        attr("lp_token", lp_token),
+        attr("cooldown_blocks", cooldown_blocks.to_string()),
+    ]))
+}
+
+fn set_pool_rewards_paused(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lp_token: String,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+
+    let alloc_points = ACTIVE_POOLS
+        .load(deps.storage)?
+        .into_iter()
+        .find(|(lp_asset, _)| lp_asset == &lp_token_asset)
+        .map(|(_, alloc_points)| alloc_points)
+        .ok_or_else(|| ContractError::PoolNotActive {
+            lp_token: lp_token.clone(),
+        })?;
+
+    let mut pool_info = PoolInfo::load(deps.storage, &lp_token_asset)?;
+    pool_info.update_rewards(deps.storage, &env, &lp_token_asset)?;
+    pool_info.rewards_paused = paused;
+    pool_info.set_astro_rewards(&config, alloc_points);
+    pool_info.save(deps.storage, &lp_token_asset)?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_pool_rewards_paused"),
+        attr("lp_token", lp_token),
+        attr("paused", paused.to_string()),
+    ]))
+}
+
+fn set_reward_vesting_duration(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token: String,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ensure!(
+        duration <= MAX_REWARD_VESTING_DURATION,
+        ContractError::RewardVestingDurationTooLong {}
+    );
+
+    let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+
+    if duration == 0 {
+        REWARD_VESTING_DURATIONS.remove(deps.storage, &lp_token_asset);
+    } else {
+        REWARD_VESTING_DURATIONS.save(deps.storage, &lp_token_asset, &duration)?;
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_reward_vesting_duration"),
+        attr("lp_token", lp_token),
+        attr("duration", duration.to_string()),
+    ]))
+}
+
+fn set_reward_recipient(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let recipient = match recipient {
+        Some(recipient) => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            REWARD_RECIPIENTS.save(deps.storage, &info.sender, &recipient)?;
+            recipient
+        }
+        None => {
+            REWARD_RECIPIENTS.remove(deps.storage, &info.sender);
+            info.sender.clone()
+        }
+    };
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_reward_recipient"),
+        attr("user", info.sender),
+        attr("recipient", recipient),
+    ]))
+}
+
+fn set_fallback_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attrs = vec![attr("action", "set_fallback_pool")];
+
+    if let Some(lp_token) = lp_token {
+        let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+        FALLBACK_POOL.save(deps.storage, &lp_token_asset)?;
+        attrs.push(attr("lp_token", lp_token));
+    } else {
+        FALLBACK_POOL.remove(deps.storage);
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+fn set_partial_vesting_payouts(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PARTIAL_VESTING_PAYOUTS.save(deps.storage, &enabled)?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_partial_vesting_payouts"),
+        attr("enabled", enabled.to_string()),
+    ]))
+}
+
+fn set_lock_boost(
+    deps: DepsMut,
+    info: MessageInfo,
+    lock_boost: Option<LockBoostConfig>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attrs = vec![attr("action", "set_lock_boost")];
+
+    if let Some(lock_boost) = lock_boost {
+        ensure!(
+            lock_boost.multiplier > Decimal::one() && lock_boost.multiplier <= MAX_LOCK_MULTIPLIER,
+            ContractError::InvalidLockMultiplier {}
+        );
+        ensure!(
+            lock_boost.min_lock_duration <= lock_boost.max_lock_duration,
+            ContractError::InvalidLockDurations {}
+        );
+        attrs.push(attr(
+            "min_lock_duration",
+            lock_boost.min_lock_duration.to_string(),
+        ));
+        attrs.push(attr(
+            "max_lock_duration",
+            lock_boost.max_lock_duration.to_string(),
+        ));
+        attrs.push(attr("multiplier", lock_boost.multiplier.to_string()));
+
+        LOCK_BOOST.save(deps.storage, &lock_boost)?;
+    } else {
+        LOCK_BOOST.remove(deps.storage);
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+fn lock_position(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lp_token: String,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let lock_boost = LOCK_BOOST
+        .may_load(deps.storage)?
+        .ok_or(ContractError::LockBoostDisabled {})?;
+    ensure!(
+        duration >= lock_boost.min_lock_duration,
+        ContractError::LockDurationTooShort {
+            min_lock_duration: lock_boost.min_lock_duration
+        }
+    );
+    ensure!(
+        duration <= lock_boost.max_lock_duration,
+        ContractError::LockDurationTooLong {
+            max_lock_duration: lock_boost.max_lock_duration
+        }
+    );
+
+    let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+    let mut user_info = UserInfo::load_position(deps.storage, &info.sender, &lp_token_asset)?;
+
+    let unlock_ts = env
+        .block
+        .time
+        .seconds()
+        .checked_add(duration)
+        .ok_or_else(|| {
+            OverflowError::new(OverflowOperation::Add, env.block.time.seconds(), duration)
+        })?;
+    if let Some(lock) = &user_info.lock {
+        ensure!(
+            unlock_ts >= lock.unlock_ts,
+            StdError::generic_err(format!(
+                "Lock can only be extended. Current unlock time is {}",
+                lock.unlock_ts
+            ))
+        );
+    }
+
+    let mut pool_info = PoolInfo::load(deps.storage, &lp_token_asset)?;
+    let config = CONFIG.load(deps.storage)?;
+    let response = claim_rewards(
+        deps.storage,
+        deps.querier,
+        &config,
+        env,
+        &info.sender,
+        vec![(&lp_token_asset, &mut pool_info, &mut user_info)],
+    )?;
+
+    user_info.set_lock(
+        Some(PositionLock {
+            unlock_ts,
+            multiplier: lock_boost.multiplier,
+        }),
+        &mut pool_info,
+    );
+    pool_info.save(deps.storage, &lp_token_asset)?;
+    user_info.save(deps.storage, &info.sender, &lp_token_asset)?;
+
+    Ok(response.add_attributes([
+        attr("action", "lock_position"),
+        attr("lp_token", lp_token_asset.to_string()),
+        attr("unlock_ts", unlock_ts.to_string()),
+        attr("multiplier", lock_boost.multiplier.to_string()),
+    ]))
+}
+
+fn expire_lock(
+    deps: DepsMut,
+    env: Env,
+    lp_token: String,
+    user: String,
+) -> Result<Response, ContractError> {
+    let lp_token_asset = determine_asset_info(&lp_token, deps.api)?;
+    let user = deps.api.addr_validate(&user)?;
+    let mut user_info = UserInfo::load_position(deps.storage, &user, &lp_token_asset)?;
+
+    match &user_info.lock {
+        Some(lock) if user_info.is_locked(&env) => {
+            return Err(ContractError::PositionLocked {
+                unlock_ts: lock.unlock_ts,
+            })
+        }
+        None => return Err(StdError::generic_err("Position is not locked").into()),
+        _ => {}
+    }
+
+    let mut pool_info = PoolInfo::load(deps.storage, &lp_token_asset)?;
+    let config = CONFIG.load(deps.storage)?;
+    let response = claim_rewards(
+        deps.storage,
+        deps.querier,
+        &config,
+        env,
+        &user,
+        vec![(&lp_token_asset, &mut pool_info, &mut user_info)],
+    )?;
+
+    user_info.set_lock(None, &mut pool_info);
+    pool_info.save(deps.storage, &lp_token_asset)?;
+    user_info.save(deps.storage, &user, &lp_token_asset)?;
+
+    Ok(response.add_attributes([
+        attr("action", "expire_lock"),
+        attr("lp_token", lp_token_asset.to_string()),
+    ]))
+}
+
 fn update_blocked_pool_tokens(
     deps: DepsMut,
     env: Env,
@@ -544,11 +1212,12 @@ fn update_blocked_pool_tokens(
                 let mut pool_info = PoolInfo::load(deps.storage, lp_token_asset)?;
                 pool_info.update_rewards(deps.storage, &env, lp_token_asset)?;
                 pool_info.disable_astro_rewards();
+                pool_info.rewards_paused = false;
                 pool_info.save(deps.storage, lp_token_asset)?;
                 reduce_total_alloc_points += *alloc_points;
             }
 
-            let new_active_pools = active_pools
+            let mut new_active_pools = active_pools
                 .iter()
                 .filter_map(|(lp_asset, _, alloc_points)| {
                     if to_disable
@@ -562,9 +1231,19 @@ fn update_blocked_pool_tokens(
                 })
                 .collect_vec();
 
-            config.total_alloc_points = config
-                .total_alloc_points
-                .checked_sub(reduce_total_alloc_points)?;
+            // Redirect alloc points to the fallback pool if it is still active.
+            // Otherwise, they are removed from the total
+            let fallback_pool = FALLBACK_POOL.may_load(deps.storage)?;
+            if let Some((_, alloc_points)) = new_active_pools
+                .iter_mut()
+                .find(|(lp_asset, _)| Some(&*lp_asset) == fallback_pool.as_ref())
+            {
+                *alloc_points += reduce_total_alloc_points;
+            } else {
+                config.total_alloc_points = config
+                    .total_alloc_points
+                    .checked_sub(reduce_total_alloc_points)?;
+            }
 
             for (lp_asset, alloc_points) in &new_active_pools {
                 let mut pool_info = PoolInfo::load(deps.storage, lp_asset)?;
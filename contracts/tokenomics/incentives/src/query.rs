@@ -1,20 +1,30 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    ensure, to_json_binary, Binary, Deps, Env, Order, StdError, StdResult, Uint128,
+    ensure, to_json_binary, Addr, Binary, Decimal256, Deps, Env, Order, StdError, StdResult,
+    Uint128,
 };
+use cw2::get_contract_version;
 use cw_storage_plus::Bound;
 use itertools::Itertools;
 
 use astroport::asset::{determine_asset_info, Asset, AssetInfo, AssetInfoExt};
-use astroport::incentives::{QueryMsg, RewardType, ScheduleResponse, MAX_PAGE_LIMIT};
+use astroport::incentives::{
+    PoolPendingRewards, QueryMsg, RewardType, ScheduleResponse, MAX_PAGE_LIMIT,
+};
+use astroport::{pair, vesting};
 
 use crate::error::ContractError;
 use crate::state::{
-    list_pool_stakers, PoolInfo, UserInfo, ACTIVE_POOLS, BLOCKED_TOKENS, CONFIG,
-    EXTERNAL_REWARD_SCHEDULES, POOLS,
+    list_pool_stakers, PoolInfo, UserInfo, ACTIVE_POOLS, BLOCKED_TOKENS, CLAIM_COOLDOWN_BLOCKS,
+    CONFIG, EXTERNAL_REWARD_SCHEDULES, FALLBACK_POOL, HARVEST_FEE, LOCK_BOOST, MIN_HOLDING_BLOCKS,
+    OWED_REWARDS, PARTIAL_VESTING_PAYOUTS, POOLS, REWARD_VESTING_DURATIONS, TOTAL_DISTRIBUTED,
+    WITHDRAW_NONCES,
+};
+use crate::utils::{
+    asset_info_key, from_key_to_asset_info, is_holding_period_met, query_pair_info,
+    reward_recipient,
 };
-use crate::utils::{asset_info_key, from_key_to_asset_info};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
@@ -31,6 +41,17 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
         QueryMsg::PendingRewards { lp_token, user } => Ok(to_json_binary(&query_pending_rewards(
             deps, env, user, lp_token,
         )?)?),
+        QueryMsg::PendingRewardsAll {
+            user,
+            start_after,
+            limit,
+        } => Ok(to_json_binary(&query_pending_rewards_all(
+            deps,
+            env,
+            user,
+            start_after,
+            limit,
+        )?)?),
         QueryMsg::RewardInfo { lp_token } => {
             let lp_asset = determine_asset_info(&lp_token, deps.api)?;
             let mut pool_info = PoolInfo::load(deps.storage, &lp_asset)?;
@@ -112,7 +133,206 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
                 .collect_vec();
             Ok(to_json_binary(&pools)?)
         }
+        QueryMsg::VestingBalance {} => {
+            let config = CONFIG.load(deps.storage)?;
+            let amount: Uint128 = deps.querier.query_wasm_smart(
+                &config.vesting_contract,
+                &vesting::QueryMsg::AvailableAmount {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            Ok(to_json_binary(&amount)?)
+        }
+        QueryMsg::StakedValue { lp_token, user } => {
+            Ok(to_json_binary(&query_staked_value(deps, lp_token, user)?)?)
+        }
+        QueryMsg::Version {} => Ok(to_json_binary(&get_contract_version(deps.storage)?)?),
+        QueryMsg::WithdrawNonce { account } => {
+            let account = deps.api.addr_validate(&account)?;
+            let nonce = WITHDRAW_NONCES
+                .may_load(deps.storage, &account)?
+                .unwrap_or_default();
+            Ok(to_json_binary(&nonce)?)
+        }
+        QueryMsg::HarvestFee {} => Ok(to_json_binary(&HARVEST_FEE.may_load(deps.storage)?)?),
+        QueryMsg::MinHoldingBlocks { lp_token } => {
+            let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+            let min_holding_blocks = MIN_HOLDING_BLOCKS
+                .may_load(deps.storage, &lp_asset)?
+                .unwrap_or_default();
+            Ok(to_json_binary(&min_holding_blocks)?)
+        }
+        QueryMsg::PoolRewardsPaused { lp_token } => {
+            let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+            let paused = PoolInfo::may_load(deps.storage, &lp_asset)?
+                .map(|pool_info| pool_info.rewards_paused)
+                .unwrap_or_default();
+            Ok(to_json_binary(&paused)?)
+        }
+        QueryMsg::ClaimCooldown { lp_token } => {
+            let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+            let cooldown_blocks = CLAIM_COOLDOWN_BLOCKS
+                .may_load(deps.storage, &lp_asset)?
+                .unwrap_or_default();
+            Ok(to_json_binary(&cooldown_blocks)?)
+        }
+        QueryMsg::RewardVestingDuration { lp_token } => {
+            let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+            let duration = REWARD_VESTING_DURATIONS
+                .may_load(deps.storage, &lp_asset)?
+                .unwrap_or_default();
+            Ok(to_json_binary(&duration)?)
+        }
+        QueryMsg::FallbackPool {} => Ok(to_json_binary(&FALLBACK_POOL.may_load(deps.storage)?)?),
+        QueryMsg::PartialVestingPayouts {} => Ok(to_json_binary(
+            &PARTIAL_VESTING_PAYOUTS
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        )?),
+        QueryMsg::OwedRewards { user } => {
+            let user = deps.api.addr_validate(&user)?;
+            let owed = OWED_REWARDS
+                .may_load(deps.storage, &user)?
+                .unwrap_or_default();
+            Ok(to_json_binary(&owed)?)
+        }
+        QueryMsg::LockBoost {} => Ok(to_json_binary(&LOCK_BOOST.may_load(deps.storage)?)?),
+        QueryMsg::PositionLock { lp_token, user } => {
+            let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+            let user_addr = deps.api.addr_validate(&user)?;
+            let lock = UserInfo::may_load_position(deps.storage, &user_addr, &lp_asset)?
+                .and_then(|pos| pos.lock);
+            Ok(to_json_binary(&lock)?)
+        }
+        QueryMsg::TotalDistributed {} => Ok(to_json_binary(
+            &TOTAL_DISTRIBUTED
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        )?),
+        QueryMsg::ProjectedRewards {
+            lp_token,
+            amount,
+            seconds,
+        } => Ok(to_json_binary(&query_projected_rewards(
+            deps, env, lp_token, amount, seconds,
+        )?)?),
+        QueryMsg::PoolsContainingAsset { asset_info } => Ok(to_json_binary(
+            &query_pools_containing_asset(deps, asset_info)?,
+        )?),
+        QueryMsg::RewardRecipient { user } => {
+            let user = deps.api.addr_validate(&user)?;
+            Ok(to_json_binary(&reward_recipient(deps.storage, &user)?)?)
+        }
+        QueryMsg::HealthCheck { lp_token, users } => Ok(to_json_binary(&query_health_check(
+            deps, env, lp_token, users,
+        )?)?),
+    }
+}
+
+/// Returns the users whose claims would fail on inconsistent reward indexes.
+/// Outstanding rewards of every position are simulated the same way as in [`query_pending_rewards`].
+fn query_health_check(
+    deps: Deps,
+    env: Env,
+    lp_token: String,
+    users: Vec<String>,
+) -> Result<Vec<Addr>, ContractError> {
+    let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+
+    let mut pool_info = PoolInfo::load(deps.storage, &lp_asset)?;
+    pool_info.update_rewards(deps.storage, &env, &lp_asset)?;
+
+    let mut unhealthy = vec![];
+    for user in users {
+        let user_addr = deps.api.addr_validate(&user)?;
+        let mut pos =
+            if let Some(pos) = UserInfo::may_load_position(deps.storage, &user_addr, &lp_asset)? {
+                pos
+            } else {
+                continue;
+            };
+
+        let healthy = pos.has_consistent_indexes(deps.storage, &lp_asset, &pool_info)?
+            && pos
+                .claim_finished_rewards(deps.storage, &lp_asset, &pool_info)
+                .is_ok()
+            && pos
+                .reset_user_index(deps.storage, &lp_asset, &pool_info)
+                .is_ok()
+            && pool_info.clone().calculate_rewards(&mut pos).is_ok();
+
+        if !healthy {
+            unhealthy.push(user_addr);
+        }
+    }
+
+    Ok(unhealthy)
+}
+
+/// Resolves the pair of every active pool and returns the LP tokens of the pools
+/// which include `asset_info`.
+fn query_pools_containing_asset(deps: Deps, asset_info: AssetInfo) -> StdResult<Vec<String>> {
+    ACTIVE_POOLS
+        .load(deps.storage)?
+        .into_iter()
+        .filter_map(|(lp_asset, _)| {
+            query_pair_info(deps, &lp_asset)
+                .map(|pair_info| {
+                    pair_info
+                        .asset_infos
+                        .contains(&asset_info)
+                        .then(|| lp_asset.to_string())
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+/// Projects rewards a new position of `amount` LP tokens would receive over the next `seconds`
+/// assuming current rewards per second and pool composition stay constant.
+/// The new position is added to the total pool weight so its own dilution is accounted for.
+fn query_projected_rewards(
+    deps: Deps,
+    env: Env,
+    lp_token: String,
+    amount: Uint128,
+    seconds: u64,
+) -> StdResult<Vec<Asset>> {
+    let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+    let mut pool_info = PoolInfo::load(deps.storage, &lp_asset)?;
+    pool_info.update_rewards(deps.storage, &env, &lp_asset)?;
+
+    let total_weight = pool_info.total_lp + pool_info.total_boost + amount;
+    if total_weight.is_zero() {
+        return Ok(vec![]);
     }
+    let share = Decimal256::from_ratio(amount, total_weight);
+
+    pool_info
+        .rewards
+        .iter()
+        .map(|reward_info| {
+            let projected = reward_info.rps * Decimal256::from_ratio(seconds, 1u8) * share;
+            Ok(reward_info
+                .reward
+                .asset_info()
+                .with_balance(Uint128::try_from(projected.to_uint_floor())?))
+        })
+        .collect()
+}
+
+/// Returns the underlying pool assets of the LP tokens staked by a user.
+/// The pair is resolved from the LP token and queried for its [`pair::QueryMsg::Share`].
+fn query_staked_value(deps: Deps, lp_token: String, user: String) -> StdResult<Vec<Asset>> {
+    let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+    let user_addr = deps.api.addr_validate(&user)?;
+    let amount = UserInfo::may_load_position(deps.storage, &user_addr, &lp_asset)?
+        .map(|pos| pos.amount)
+        .unwrap_or_default();
+
+    let pair_info = query_pair_info(deps, &lp_asset)?;
+    deps.querier
+        .query_wasm_smart(pair_info.contract_addr, &pair::QueryMsg::Share { amount })
 }
 
 fn list_pools(
@@ -159,6 +379,9 @@ fn query_blocked_tokens(
     .collect()
 }
 
+/// Returns the rewards a claim of the user position would pay out now.
+/// The harvest fee is deducted from ASTRO rewards. ASTRO rewards of positions which haven't been held
+/// for the pool's minimum holding period are forfeited on claim and reported as zero.
 pub fn query_pending_rewards(
     deps: Deps,
     env: Env,
@@ -179,12 +402,18 @@ pub fn query_pending_rewards(
     // Reset user reward index for all finished schedules
     pos.reset_user_index(deps.storage, &lp_asset, &pool_info)?;
 
-    let active_rewards = pool_info
-        .calculate_rewards(&mut pos)?
-        .into_iter()
-        .map(|(_, asset)| asset);
+    let forfeit = !is_holding_period_met(deps.storage, &env, &lp_asset, &user_addr)?;
 
-    outstanding_rewards.extend(active_rewards);
+    for (is_external, mut asset) in pool_info.calculate_rewards(&mut pos)? {
+        if !is_external {
+            asset.amount = if forfeit {
+                Uint128::zero()
+            } else {
+                deduct_harvest_fee(deps, asset.amount)?
+            };
+        }
+        outstanding_rewards.push(asset);
+    }
 
     let aggregated = outstanding_rewards
         .into_iter()
@@ -199,6 +428,80 @@ pub fn query_pending_rewards(
     Ok(aggregated)
 }
 
+/// Deducts the harvest fee from ASTRO rewards the same way it is taken on claim.
+fn deduct_harvest_fee(deps: Deps, amount: Uint128) -> StdResult<Uint128> {
+    Ok(match HARVEST_FEE.may_load(deps.storage)? {
+        Some(harvest_fee) => amount - amount.multiply_ratio(harvest_fee.fee_bps, 10000u16),
+        None => amount,
+    })
+}
+
+/// Returns outstanding rewards of all user positions in the pools from the requested page
+/// of [`list_pools`]. Pools without a user position are skipped.
+/// Amounts are computed as in [`query_pending_rewards`].
+pub fn query_pending_rewards_all(
+    deps: Deps,
+    env: Env,
+    user: String,
+    start_after: Option<String>,
+    limit: Option<u8>,
+) -> Result<Vec<PoolPendingRewards>, ContractError> {
+    let user_addr = deps.api.addr_validate(&user)?;
+
+    let mut pending_rewards = vec![];
+    for lp_token in list_pools(deps, start_after, limit)? {
+        let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+        let mut pos =
+            if let Some(pos) = UserInfo::may_load_position(deps.storage, &user_addr, &lp_asset)? {
+                pos
+            } else {
+                continue;
+            };
+
+        let mut pool_info = PoolInfo::load(deps.storage, &lp_asset)?;
+        pool_info.update_rewards(deps.storage, &env, &lp_asset)?;
+
+        let mut external_rewards =
+            pos.claim_finished_rewards(deps.storage, &lp_asset, &pool_info)?;
+        pos.reset_user_index(deps.storage, &lp_asset, &pool_info)?;
+
+        let mut astro_pending = Uint128::zero();
+        for (is_external, reward) in pool_info.calculate_rewards(&mut pos)? {
+            if is_external {
+                external_rewards.push(reward);
+            } else {
+                astro_pending += reward.amount;
+            }
+        }
+        astro_pending = deduct_harvest_fee(deps, astro_pending)?;
+
+        // ASTRO rewards of positions held for less than the minimum holding period are forfeited
+        if !is_holding_period_met(deps.storage, &env, &lp_asset, &user_addr)? {
+            astro_pending = Uint128::zero();
+        }
+
+        let mut external_pending: Vec<Asset> = vec![];
+        for reward in external_rewards {
+            if let Some(aggregated) = external_pending
+                .iter_mut()
+                .find(|asset| asset.info == reward.info)
+            {
+                aggregated.amount += reward.amount;
+            } else {
+                external_pending.push(reward);
+            }
+        }
+
+        pending_rewards.push(PoolPendingRewards {
+            lp_token,
+            astro_pending,
+            external_pending,
+        });
+    }
+
+    Ok(pending_rewards)
+}
+
 pub fn query_external_reward_schedules(
     deps: Deps,
     env: Env,
@@ -268,3 +571,76 @@ pub fn query_external_reward_schedules(
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod unit_tests {
+    use cosmwasm_std::from_json;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+
+    use crate::state::FINISHED_REWARD_INDEXES;
+
+    use super::*;
+
+    #[test]
+    fn test_health_check() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let now = env.block.time.seconds();
+
+        let lp_token = AssetInfo::cw20_unchecked("lp_token");
+        let reward = AssetInfo::native("reward");
+
+        PoolInfo {
+            total_lp: Uint128::new(200),
+            last_update_ts: now,
+            ..Default::default()
+        }
+        .save(deps.as_mut().storage, &lp_token)
+        .unwrap();
+        FINISHED_REWARD_INDEXES
+            .save(
+                deps.as_mut().storage,
+                (&lp_token, now - 10),
+                &vec![(reward.clone(), Decimal256::one())],
+            )
+            .unwrap();
+
+        let position = |index: Decimal256| UserInfo {
+            amount: Uint128::new(100),
+            last_rewards_index: vec![(
+                RewardType::Ext {
+                    info: reward.clone(),
+                    next_update_ts: now - 10,
+                },
+                index,
+            )],
+            last_claim_time: now - 100,
+            lock: None,
+        };
+
+        let healthy = Addr::unchecked("healthy");
+        let inconsistent = Addr::unchecked("inconsistent");
+        position(Decimal256::percent(50))
+            .save(deps.as_mut().storage, &healthy, &lp_token)
+            .unwrap();
+        position(Decimal256::percent(200))
+            .save(deps.as_mut().storage, &inconsistent, &lp_token)
+            .unwrap();
+
+        let resp = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::HealthCheck {
+                lp_token: "lp_token".to_string(),
+                users: vec![
+                    healthy.to_string(),
+                    inconsistent.to_string(),
+                    "no_position".to_string(),
+                ],
+            },
+        )
+        .unwrap();
+        let unhealthy: Vec<Addr> = from_json(resp).unwrap();
+        assert_eq!(unhealthy, vec![inconsistent]);
+    }
+}
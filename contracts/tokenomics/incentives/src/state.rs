@@ -1,13 +1,18 @@
 use std::collections::{HashMap, HashSet};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal256, Env, Order, StdError, StdResult, Storage, Uint128, Uint256};
+use cosmwasm_std::{
+    Addr, Binary, Decimal, Decimal256, Env, Order, StdError, StdResult, Storage, Uint128, Uint256,
+    Uint512,
+};
 use cw_storage_plus::{Bound, Item, Map};
 use itertools::Itertools;
 
 use astroport::asset::{Asset, AssetInfo, AssetInfoExt};
 use astroport::common::OwnershipProposal;
-use astroport::incentives::{Config, IncentivesSchedule};
+use astroport::incentives::{
+    Config, HarvestFeeInfo, IncentivesSchedule, LockBoostConfig, PositionLock,
+};
 use astroport::incentives::{PoolInfoResponse, RewardInfo, RewardType};
 use astroport::incentives::{MAX_PAGE_LIMIT, MAX_REWARD_TOKENS};
 
@@ -44,6 +49,41 @@ pub const EXTERNAL_REWARD_SCHEDULES: Map<(&AssetInfo, &AssetInfo, u64), Decimal2
 /// key: Key: binary representing [`AssetInfo`] converted with [`asset_info_key`],
 /// value: total amount of orphaned tokens
 pub const ORPHANED_REWARDS: Map<&[u8], Uint128> = Map::new("orphaned_rewards");
+/// Stores secp256k1 public keys which authorize signed withdrawals on behalf of accounts
+pub const WITHDRAW_PUBKEYS: Map<&Addr, Binary> = Map::new("withdraw_pubkeys");
+/// Stores the nonce which the next signed withdrawal of an account must use
+pub const WITHDRAW_NONCES: Map<&Addr, u64> = Map::new("withdraw_nonces");
+/// Fee taken from claimed ASTRO emissions. There is no fee if not set
+pub const HARVEST_FEE: Item<HarvestFeeInfo> = Item::new("harvest_fee");
+/// Minimum number of blocks LP tokens must stay staked before they are eligible for rewards.
+/// key: lp_token, value: number of blocks. Pools without an entry have no holding requirement
+pub const MIN_HOLDING_BLOCKS: Map<&AssetInfo, u64> = Map::new("min_holding_blocks");
+/// Deposit block height of user positions weighted by deposited amounts.
+/// key: (lp_token, user_addr), value: block height
+pub const DEPOSIT_BLOCKS: Map<(&AssetInfo, &Addr), u64> = Map::new("deposit_blocks");
+/// Number of blocks a user must wait between two reward claims.
+/// key: lp_token, value: number of blocks. Pools without an entry have no cooldown
+pub const CLAIM_COOLDOWN_BLOCKS: Map<&AssetInfo, u64> = Map::new("claim_cooldown_blocks");
+/// Duration in seconds over which ASTRO rewards claimed from a pool vest.
+/// key: lp_token, value: duration. Pools without an entry pay rewards out instantly
+pub const REWARD_VESTING_DURATIONS: Map<&AssetInfo, u64> = Map::new("reward_vesting_durations");
+/// Block height of the last reward claim in pools with a claim cooldown.
+/// key: (lp_token, user_addr), value: block height
+pub const LAST_CLAIM_BLOCKS: Map<(&AssetInfo, &Addr), u64> = Map::new("last_claim_blocks");
+/// LP token of the pool which receives alloc points of pools deactivated by token blocklisting
+pub const FALLBACK_POOL: Item<AssetInfo> = Item::new("fallback_pool");
+/// Whether ASTRO claims exceeding the amount available in the vesting contract are paid partially.
+/// Such claims fail if not set
+pub const PARTIAL_VESTING_PAYOUTS: Item<bool> = Item::new("partial_vesting_payouts");
+/// ASTRO rewards which weren't paid out because the vesting contract was underfunded.
+/// key: user_addr
+pub const OWED_REWARDS: Map<&Addr, Uint128> = Map::new("owed_rewards");
+/// Running total of ASTRO emissions claimed by stakers from the vesting contract
+pub const TOTAL_DISTRIBUTED: Item<Uint128> = Item::new("total_distributed");
+/// Parameters of the lock for boost mechanism. New locks are not allowed if not set
+pub const LOCK_BOOST: Item<LockBoostConfig> = Item::new("lock_boost");
+/// Addresses which receive claimed rewards instead of the stakers. Rewards go to the staker if not set
+pub const REWARD_RECIPIENTS: Map<&Addr, Addr> = Map::new("reward_recipients");
 
 impl RewardInfoExt for RewardInfo {
     /// This function is tightly coupled with [`UserInfo`] structure. It iterates over all user's
@@ -51,7 +91,7 @@ impl RewardInfoExt for RewardInfo {
     /// calculates the reward amount.
     /// Otherwise it assumes user never claimed this particular reward and their reward index is 0.
     /// Their position will be synced with pool indexes later on.
-    fn calculate_reward(&self, user_info: &UserInfo) -> StdResult<Uint128> {
+    fn calculate_reward(&self, user_info: &UserInfo) -> StdResult<(Uint128, Decimal256)> {
         let user_index_opt = user_info
             .last_rewards_index
             .iter()
@@ -62,16 +102,22 @@ impl RewardInfoExt for RewardInfo {
         // rewards from past schedules.
         // Outstanding rewards from finished schedules are handled in claim_finished_rewards().
         // To account current active period properly we need to consider user index as 0.
-        let user_amount = Uint256::from(user_info.amount);
-        let u256_result = match user_index_opt {
-            Some((_, user_reward_index)) if *user_reward_index > self.index => {
-                self.index * user_amount
+        let user_amount = Uint256::from(user_info.weight());
+        let index_diff = match user_index_opt {
+            Some((_, user_reward_index)) if *user_reward_index <= self.index => {
+                self.index - *user_reward_index
             }
-            None => self.index * user_amount,
-            Some((_, user_reward_index)) => (self.index - *user_reward_index) * user_amount,
+            _ => self.index,
         };
 
-        Ok(u256_result.try_into()?)
+        let amount = (index_diff * user_amount).try_into()?;
+        // The fractional part of index_diff * user_amount which can't be paid out
+        let one = Decimal256::one().atomics();
+        let dust_atomics =
+            (Uint512::from(index_diff.atomics()) * Uint512::from(user_amount)) % Uint512::from(one);
+        let dust = Decimal256::new(dust_atomics.try_into()?);
+
+        Ok((amount, dust))
     }
 }
 
@@ -84,6 +130,16 @@ pub struct PoolInfo {
     pub rewards: Vec<RewardInfo>,
     /// Last time when reward indexes were updated
     pub last_update_ts: u64,
+    /// Extra reward weight of locked positions on top of their LP amounts
+    #[serde(default)]
+    pub total_boost: Uint128,
+    /// Rewards of active schedules lost on rounding down user claims. Can be swept by the owner.
+    #[serde(default)]
+    pub reward_dust: Vec<(RewardType, Decimal256)>,
+    /// Whether ASTRO emissions are paused in this pool. A paused pool keeps its alloc points
+    /// in the active set and resumes at the same rate once unpaused.
+    #[serde(default)]
+    pub rewards_paused: bool,
     /// Rewards to remove; In-memory hash map to avoid unnecessary state writes;
     /// Key: reward type, value: (reward index, orphaned rewards)
     /// NOTE: this is not part of serialized structure in state!
@@ -171,7 +227,7 @@ impl PoolInfo {
             } else {
                 // Allowing the first depositor to claim orphaned rewards
                 reward_info.index += (reward_info.orphaned + collected_rewards)
-                    / Decimal256::from_ratio(self.total_lp, 1u8);
+                    / Decimal256::from_ratio(self.total_lp + self.total_boost, 1u8);
                 reward_info.orphaned = Decimal256::zero();
             }
 
@@ -194,44 +250,79 @@ impl PoolInfo {
 
     /// This function calculates all rewards for a specific user position.
     /// Converts them to [`Asset`]. Returns array of tuples (is_external_reward, Asset).
-    pub fn calculate_rewards(&self, user_info: &mut UserInfo) -> StdResult<Vec<(bool, Asset)>> {
-        self.rewards
-            .iter()
-            .map(|reward_info| {
-                let amount = reward_info.calculate_reward(user_info)?;
-                Ok((
-                    reward_info.reward.is_external(),
-                    reward_info.reward.asset_info().with_balance(amount),
-                ))
-            })
-            .collect()
+    /// Fractions lost on rounding are accumulated in [`PoolInfo::reward_dust`].
+    pub fn calculate_rewards(&mut self, user_info: &mut UserInfo) -> StdResult<Vec<(bool, Asset)>> {
+        let mut result = Vec::with_capacity(self.rewards.len());
+        for reward_info in &self.rewards {
+            let (amount, dust) = reward_info.calculate_reward(user_info)?;
+            add_reward_dust(&mut self.reward_dust, &reward_info.reward, dust);
+            result.push((
+                reward_info.reward.is_external(),
+                reward_info.reward.asset_info().with_balance(amount),
+            ));
+        }
+
+        Ok(result)
+    }
+
+    /// Moves rewards forfeited by a user into [`PoolInfo::reward_dust`] so the owner can sweep them.
+    pub fn forfeit_reward(&mut self, reward: &RewardType, amount: Uint128) {
+        add_reward_dust(
+            &mut self.reward_dust,
+            reward,
+            Decimal256::from_ratio(amount, 1u8),
+        );
+    }
+
+    /// Takes the whole part of the accumulated rounding dust for each reward.
+    /// Fractional remainders are kept for further accumulation.
+    pub fn take_reward_dust(&mut self) -> StdResult<Vec<(RewardType, Uint128)>> {
+        let mut swept = vec![];
+        for (reward, dust) in self.reward_dust.iter_mut() {
+            let amount = dust.to_uint_floor();
+            if !amount.is_zero() {
+                *dust -= Decimal256::from_ratio(amount, 1u8);
+                swept.push((reward.clone(), amount.try_into()?));
+            }
+        }
+        self.reward_dust.retain(|(_, dust)| !dust.is_zero());
+
+        Ok(swept)
     }
 
     /// Set astro per second for this pool according to alloc points and general astro per second value
+    /// If ASTRO rewards are paused in this pool, reward per second is set to zero.
     pub fn set_astro_rewards(&mut self, config: &Config, alloc_points: Uint128) {
-        if let Some(astro_reward_info) = self.rewards.iter_mut().find(|r| !r.reward.is_external()) {
-            astro_reward_info.rps = Decimal256::from_ratio(
-                config.astro_per_second * alloc_points,
+        let rps = if self.rewards_paused {
+            Decimal256::zero()
+        } else {
+            // Uint128 * Uint128 always fits into Uint256
+            Decimal256::from_ratio(
+                config.astro_per_second.full_mul(alloc_points),
                 config.total_alloc_points,
-            );
+            )
+        };
+
+        if let Some(astro_reward_info) = self.rewards.iter_mut().find(|r| !r.reward.is_external()) {
+            astro_reward_info.rps = rps;
         } else {
             self.rewards.push(RewardInfo {
                 reward: RewardType::Int(config.astro_token.clone()),
-                rps: Decimal256::from_ratio(
-                    config.astro_per_second * alloc_points,
-                    config.total_alloc_points,
-                ),
+                rps,
                 index: Default::default(),
                 orphaned: Default::default(),
             });
         }
     }
 
-    /// Check whether this pools receiving ASTRO emissions
+    /// Check whether this pool is in the active set, i.e. receiving ASTRO emissions
+    /// or having them temporarily paused
     pub fn is_active_pool(&self) -> bool {
-        self.rewards
-            .iter()
-            .any(|r| !r.reward.is_external() && !r.rps.is_zero())
+        self.rewards_paused
+            || self
+                .rewards
+                .iter()
+                .any(|r| !r.reward.is_external() && !r.rps.is_zero())
     }
 
     /// This function disables ASTRO rewards in a specific pool.
@@ -513,6 +604,25 @@ pub enum Op<T> {
     Noop,
 }
 
+/// Accumulates the amount in the dust entry of the specified reward.
+fn add_reward_dust(
+    reward_dust: &mut Vec<(RewardType, Decimal256)>,
+    reward: &RewardType,
+    amount: Decimal256,
+) {
+    if amount.is_zero() {
+        return;
+    }
+
+    match reward_dust
+        .iter_mut()
+        .find(|(dust_reward, _)| dust_reward.matches(reward))
+    {
+        Some((_, total_dust)) => *total_dust += amount,
+        None => reward_dust.push((reward.clone(), amount)),
+    }
+}
+
 #[cw_serde]
 /// This structure stores user position in a specific pool.
 pub struct UserInfo {
@@ -522,6 +632,9 @@ pub struct UserInfo {
     pub last_rewards_index: Vec<(RewardType, Decimal256)>,
     /// The last time user claimed rewards
     pub last_claim_time: u64,
+    /// Lock of the position which boosts its rewards
+    #[serde(default)]
+    pub lock: Option<PositionLock>,
 }
 
 impl UserInfo {
@@ -531,9 +644,38 @@ impl UserInfo {
             amount: Uint128::zero(),
             last_rewards_index: vec![],
             last_claim_time: env.block.time.seconds(),
+            lock: None,
         }
     }
 
+    /// Extra reward weight the position receives from its lock
+    pub fn boost(&self) -> Uint128 {
+        self.lock
+            .as_ref()
+            .map(|lock| self.amount * (lock.multiplier - Decimal::one()))
+            .unwrap_or_default()
+    }
+
+    /// Reward weight of the position, i.e. staked LP tokens plus the lock boost
+    pub fn weight(&self) -> Uint128 {
+        self.amount + self.boost()
+    }
+
+    /// Whether the position is locked at the current block
+    pub fn is_locked(&self, env: &Env) -> bool {
+        self.lock
+            .as_ref()
+            .is_some_and(|lock| lock.unlock_ts > env.block.time.seconds())
+    }
+
+    /// Set or remove the position lock and update the pool boost accordingly.
+    /// Must be called only after user rewards are synced with pool indexes.
+    pub fn set_lock(&mut self, lock: Option<PositionLock>, pool_info: &mut PoolInfo) {
+        pool_info.total_boost -= self.boost();
+        self.lock = lock;
+        pool_info.total_boost += self.boost();
+    }
+
     /// Loads user position from state. If position doesn't exist returns an error.
     /// Can be used in context where position must exist.
     pub fn load_position(
@@ -633,7 +775,7 @@ impl UserInfo {
             .iter()
             .map(|(reward, (index, _))| (reward.asset_info().clone(), *index));
 
-        let lp_tokens_amount = Uint256::from(self.amount);
+        let lp_tokens_amount = Uint256::from(self.weight());
 
         finished_iter
             .chain(to_remove_iter)
@@ -672,9 +814,57 @@ impl UserInfo {
             .collect()
     }
 
+    /// Checks that the user index of every finished external reward doesn't exceed the index
+    /// the reward finished with. Otherwise [`UserInfo::claim_finished_rewards`] can't calculate
+    /// outstanding rewards and the claim fails.
+    pub fn has_consistent_indexes(
+        &self,
+        storage: &dyn Storage,
+        lp_token: &AssetInfo,
+        pool_info: &PoolInfo,
+    ) -> StdResult<bool> {
+        let finished_iter = FINISHED_REWARD_INDEXES
+            .prefix(lp_token)
+            .range(
+                storage,
+                Some(Bound::exclusive(self.last_claim_time)),
+                None,
+                Order::Ascending,
+            )
+            .map(|res| res.map(|(_, indexes)| indexes))
+            .collect::<StdResult<Vec<_>>>()?
+            .into_iter()
+            .flatten();
+
+        let to_remove_iter = pool_info
+            .rewards_to_remove
+            .iter()
+            .map(|(reward, (index, _))| (reward.asset_info().clone(), *index));
+
+        // Only the first finished schedule of each reward is compared with the user index
+        let mut checked = HashSet::new();
+        for (reward_info, finished_index) in finished_iter.chain(to_remove_iter) {
+            if !checked.insert(reward_info.clone()) {
+                continue;
+            }
+
+            let inconsistent = self.last_rewards_index.iter().any(|(reward_type, index)| {
+                reward_type.is_external()
+                    && reward_type.asset_info() == &reward_info
+                    && *index > finished_index
+            });
+            if inconsistent {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Add/remove LP tokens from user position and pool info.
     /// Sync reward indexes and set last claim time.
     pub fn update_and_sync_position(&mut self, operation: Op<Uint128>, pool_info: &mut PoolInfo) {
+        pool_info.total_boost -= self.boost();
         match operation {
             Op::Add(amount) => {
                 self.amount += amount;
@@ -686,6 +876,7 @@ impl UserInfo {
             }
             Op::Noop => {}
         }
+        pool_info.total_boost += self.boost();
 
         self.last_rewards_index = pool_info
             .rewards
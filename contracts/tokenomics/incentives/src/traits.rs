@@ -1,8 +1,9 @@
-use cosmwasm_std::{StdResult, Uint128};
+use cosmwasm_std::{Decimal256, StdResult, Uint128};
 
 use crate::state::UserInfo;
 
 /// This trait is meant to extend [`astroport::incentives::RewardInfo`].
 pub trait RewardInfoExt {
-    fn calculate_reward(&self, user_info: &UserInfo) -> StdResult<Uint128>;
+    /// Returns the reward amount rounded down along with the fractional part lost on rounding.
+    fn calculate_reward(&self, user_info: &UserInfo) -> StdResult<(Uint128, Decimal256)>;
 }
@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
+
 use cosmwasm_std::{
-    attr, ensure, wasm_execute, Addr, BankMsg, Deps, DepsMut, Env, MessageInfo, Order,
-    QuerierWrapper, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    attr, coins, ensure, to_json_binary, wasm_execute, Addr, BankMsg, CosmosMsg, Deps, DepsMut,
+    Env, MessageInfo, Order, QuerierWrapper, ReplyOn, Response, StdError, StdResult, Storage,
+    SubMsg, Uint128, Uint256,
 };
 use itertools::Itertools;
 
@@ -8,34 +11,63 @@ use astroport::asset::{
     determine_asset_info, pair_info_by_pool, AssetInfo, AssetInfoExt, PairInfo,
 };
 use astroport::factory::PairType;
-use astroport::incentives::{Config, IncentivesSchedule, InputSchedule, MAX_ORPHANED_REWARD_LIMIT};
+use astroport::incentives::{
+    Config, IncentivesSchedule, InputSchedule, RewardType, MAX_ORPHANED_REWARD_LIMIT,
+};
+use astroport::vesting::{VestingAccount, VestingSchedule, VestingSchedulePoint};
 use astroport::{factory, pair, vesting};
 
 use crate::error::ContractError;
 use crate::reply::POST_TRANSFER_REPLY_ID;
 use crate::state::{
-    Op, PoolInfo, UserInfo, ACTIVE_POOLS, BLOCKED_TOKENS, CONFIG, ORPHANED_REWARDS,
+    Op, PoolInfo, UserInfo, ACTIVE_POOLS, BLOCKED_TOKENS, CLAIM_COOLDOWN_BLOCKS, CONFIG,
+    DEPOSIT_BLOCKS, HARVEST_FEE, LAST_CLAIM_BLOCKS, MIN_HOLDING_BLOCKS, ORPHANED_REWARDS,
+    OWED_REWARDS, PARTIAL_VESTING_PAYOUTS, REWARD_RECIPIENTS, REWARD_VESTING_DURATIONS,
+    TOTAL_DISTRIBUTED,
 };
 
 /// Claim all rewards and compose [`Response`] object containing all attributes and messages.
-/// This function doesn't mutate the state but mutates in-memory objects.
-/// Function caller is responsible for updating the state.
+/// This function only updates the [`TOTAL_DISTRIBUTED`] counter in state
+/// but mutates in-memory objects.
+/// Function caller is responsible for updating the pool and position state.
 /// If vesting_contract is None this function reads config from state and gets vesting address.
+/// ASTRO rewards of positions which haven't been held for the pool's minimum holding period
+/// are forfeited to the pool's reward dust. Pending rewards are synced on every deposit,
+/// thus forfeited rewards were earned within the holding period. External rewards are always paid.
+/// If the vesting contract can't cover ASTRO rewards, the claim either fails or,
+/// if [`PARTIAL_VESTING_PAYOUTS`] is enabled, pays out the available amount only
+/// and records the shortfall in [`OWED_REWARDS`] to be paid out on later claims.
+/// Rewards are sent to the user's reward recipient, see [`reward_recipient`].
+/// ASTRO rewards from pools with reward vesting are registered as vesting schedules
+/// of the recipient in the vesting contract, see [`register_reward_vesting`].
+/// Expired position locks are removed.
 pub fn claim_rewards(
-    storage: &dyn Storage,
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
     config: &Config,
     env: Env,
     user: &Addr,
     pool_tuples: Vec<(&AssetInfo, &mut PoolInfo, &mut UserInfo)>,
 ) -> Result<Response, ContractError> {
     let mut attrs = vec![attr("action", "claim_rewards"), attr("user", user)];
+    let recipient = reward_recipient(storage, user)?;
+    if &recipient != user {
+        attrs.push(attr("reward_recipient", &recipient));
+    }
     let mut external_rewards = vec![];
     let mut protocol_reward_amount = Uint128::zero();
+    // ASTRO rewards from pools with reward vesting aggregated by vesting duration
+    let mut vesting_rewards: BTreeMap<u64, Uint128> = BTreeMap::new();
     for (lp_token_asset, pool_info, pos) in pool_tuples {
         attrs.push(attr("claimed_position", lp_token_asset.to_string()));
 
         pool_info.update_rewards(storage, &env, lp_token_asset)?;
 
+        let vesting_duration = REWARD_VESTING_DURATIONS
+            .may_load(storage, lp_token_asset)?
+            .unwrap_or_default();
+        let forfeit = !is_holding_period_met(storage, &env, lp_token_asset, user)?;
+
         // Claim outstanding rewards from finished schedules
         for finished_reward in pos.claim_finished_rewards(storage, lp_token_asset, pool_info)? {
             if !finished_reward.amount.is_zero() {
@@ -48,6 +80,15 @@ pub fn claim_rewards(
         pos.reset_user_index(storage, lp_token_asset, pool_info)?;
 
         for (is_external, reward_asset) in pool_info.calculate_rewards(pos)? {
+            if forfeit && !is_external {
+                if !reward_asset.amount.is_zero() {
+                    attrs.push(attr("forfeited_reward", reward_asset.to_string()));
+                    pool_info
+                        .forfeit_reward(&RewardType::Int(reward_asset.info), reward_asset.amount);
+                }
+                continue;
+            }
+
             attrs.push(attr("claimed_reward", reward_asset.to_string()));
 
             if !reward_asset.amount.is_zero() {
@@ -55,12 +96,22 @@ pub fn claim_rewards(
                     external_rewards.push(reward_asset);
                 } else {
                     protocol_reward_amount += reward_asset.amount;
+                    if vesting_duration > 0 {
+                        *vesting_rewards.entry(vesting_duration).or_default() +=
+                            reward_asset.amount;
+                    }
                 }
             }
         }
 
         // Sync user index with pool index. It removes all finished schedules from user info.
         pos.update_and_sync_position(Op::Noop, pool_info);
+
+        // An expired lock stops boosting rewards once the position is touched
+        if pos.lock.is_some() && !pos.is_locked(&env) {
+            pos.set_lock(None, pool_info);
+            attrs.push(attr("expired_lock", lp_token_asset.to_string()));
+        }
     }
 
     // Aggregating rewards by asset info.
@@ -72,20 +123,126 @@ pub fn claim_rewards(
         .map(|(info, assets)| {
             let amount: Uint128 = assets.into_iter().map(|asset| asset.amount).sum();
             info.with_balance(amount).into_submsg(
-                user,
+                &recipient,
                 Some((ReplyOn::Error, POST_TRANSFER_REPLY_ID)),
                 config.token_transfer_gas_limit,
             )
         })
         .collect::<StdResult<Vec<_>>>()?;
 
-    // Claim Astroport rewards
+    // Rewards left unpaid by previous claims are paid out instantly
+    let owed = OWED_REWARDS.may_load(storage, user)?.unwrap_or_default();
+    if !owed.is_zero() {
+        attrs.push(attr("owed_reward", owed));
+        protocol_reward_amount += owed;
+    }
+
+    let total_protocol_reward = protocol_reward_amount;
+
+    // Make sure the vesting contract is able to cover ASTRO rewards
     if !protocol_reward_amount.is_zero() {
+        let available: Uint128 = querier.query_wasm_smart(
+            &config.vesting_contract,
+            &vesting::QueryMsg::AvailableAmount {
+                address: env.contract.address.to_string(),
+            },
+        )?;
+        if available < protocol_reward_amount {
+            ensure!(
+                PARTIAL_VESTING_PAYOUTS
+                    .may_load(storage)?
+                    .unwrap_or_default(),
+                ContractError::VestingUnderfunded {
+                    available,
+                    required: protocol_reward_amount,
+                }
+            );
+            let shortfall = protocol_reward_amount - available;
+            attrs.push(attr("vesting_shortfall", shortfall));
+            OWED_REWARDS.save(storage, user, &shortfall)?;
+            protocol_reward_amount = available;
+        } else if !owed.is_zero() {
+            OWED_REWARDS.remove(storage, user);
+        }
+    }
+
+    if !protocol_reward_amount.is_zero() {
+        TOTAL_DISTRIBUTED.update::<_, StdError>(storage, |total| {
+            Ok(total
+                .unwrap_or_default()
+                .checked_add(protocol_reward_amount)?)
+        })?;
+    }
+
+    // Divert the harvest fee from Astroport rewards
+    if let Some(harvest_fee) = HARVEST_FEE.may_load(storage)? {
+        let fee_amount = protocol_reward_amount.multiply_ratio(harvest_fee.fee_bps, 10000u16);
+        if !fee_amount.is_zero() {
+            messages.push(SubMsg::new(wasm_execute(
+                &config.vesting_contract,
+                &vesting::ExecuteMsg::Claim {
+                    recipient: Some(harvest_fee.fee_receiver.to_string()),
+                    amount: Some(fee_amount),
+                },
+                vec![],
+            )?));
+            attrs.push(attr("harvest_fee", fee_amount));
+            protocol_reward_amount -= fee_amount;
+        }
+    }
+
+    // Rewards from pools with reward vesting are claimed to this contract
+    // and registered back in the vesting contract as schedules of the recipient.
+    // Harvest fee and vesting shortfall are deducted proportionally.
+    let mut vested_amount = Uint128::zero();
+    if !protocol_reward_amount.is_zero() && !vesting_rewards.is_empty() {
+        let now = env.block.time.seconds();
+        let mut schedules = vec![];
+        for (duration, amount) in vesting_rewards {
+            let amount = amount.multiply_ratio(protocol_reward_amount, total_protocol_reward);
+            if amount.is_zero() {
+                continue;
+            }
+            schedules.push(VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: now,
+                    amount: Uint128::zero(),
+                },
+                end_point: Some(VestingSchedulePoint {
+                    time: now + duration,
+                    amount,
+                }),
+            });
+            vested_amount += amount;
+        }
+
+        if !vested_amount.is_zero() {
+            attrs.push(attr("vesting_reward", vested_amount));
+            messages.push(SubMsg::new(wasm_execute(
+                &config.vesting_contract,
+                &vesting::ExecuteMsg::Claim {
+                    recipient: Some(env.contract.address.to_string()),
+                    amount: Some(vested_amount),
+                },
+                vec![],
+            )?));
+            messages.push(SubMsg::new(register_reward_vesting(
+                config,
+                &recipient,
+                schedules,
+                vested_amount,
+            )?));
+        }
+    }
+
+    // Claim Astroport rewards
+    let instant_amount = protocol_reward_amount - vested_amount;
+    if !instant_amount.is_zero() {
         messages.push(SubMsg::new(wasm_execute(
             &config.vesting_contract,
             &vesting::ExecuteMsg::Claim {
-                recipient: Some(user.to_string()),
-                amount: Some(protocol_reward_amount),
+                recipient: Some(recipient.to_string()),
+                amount: Some(instant_amount),
             },
             vec![],
         )?));
@@ -96,6 +253,121 @@ pub fn claim_rewards(
         .add_submessages(messages))
 }
 
+/// Builds a message registering vesting schedules of claimed ASTRO rewards for the recipient
+/// in the vesting contract. ASTRO must be held by this contract when the message is executed.
+pub fn register_reward_vesting(
+    config: &Config,
+    recipient: &Addr,
+    schedules: Vec<VestingSchedule>,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    let vesting_accounts = vec![VestingAccount {
+        address: recipient.to_string(),
+        schedules,
+    }];
+
+    let msg = match &config.astro_token {
+        AssetInfo::NativeToken { denom } => wasm_execute(
+            &config.vesting_contract,
+            &vesting::ExecuteMsg::RegisterVestingAccounts { vesting_accounts },
+            coins(amount.u128(), denom),
+        )?,
+        AssetInfo::Token { contract_addr } => wasm_execute(
+            contract_addr,
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: config.vesting_contract.to_string(),
+                amount,
+                msg: to_json_binary(&vesting::Cw20HookMsg::RegisterVestingAccounts {
+                    vesting_accounts,
+                })?,
+            },
+            vec![],
+        )?,
+    };
+
+    Ok(msg.into())
+}
+
+/// Returns the address which receives rewards claimed by the user.
+/// Defaults to the user itself.
+pub fn reward_recipient(storage: &dyn Storage, user: &Addr) -> StdResult<Addr> {
+    Ok(REWARD_RECIPIENTS
+        .may_load(storage, user)?
+        .unwrap_or_else(|| user.clone()))
+}
+
+/// Checks whether the user position has been held for the minimum holding period of the pool.
+/// Positions without a recorded deposit block are considered to be held long enough.
+pub fn is_holding_period_met(
+    storage: &dyn Storage,
+    env: &Env,
+    lp_token: &AssetInfo,
+    user: &Addr,
+) -> StdResult<bool> {
+    let min_holding_blocks = MIN_HOLDING_BLOCKS
+        .may_load(storage, lp_token)?
+        .unwrap_or_default();
+    if min_holding_blocks == 0 {
+        return Ok(true);
+    }
+
+    Ok(DEPOSIT_BLOCKS
+        .may_load(storage, (lp_token, user))?
+        .map_or(true, |deposit_block| {
+            env.block.height >= deposit_block.saturating_add(min_holding_blocks)
+        }))
+}
+
+/// Ensures the claim cooldown of the pool has passed since the last user claim
+/// and records the current block as the last claim block.
+/// Does nothing for pools without a claim cooldown.
+pub fn check_claim_cooldown(
+    storage: &mut dyn Storage,
+    env: &Env,
+    lp_token: &AssetInfo,
+    user: &Addr,
+) -> Result<(), ContractError> {
+    let cooldown_blocks = CLAIM_COOLDOWN_BLOCKS
+        .may_load(storage, lp_token)?
+        .unwrap_or_default();
+    if cooldown_blocks == 0 {
+        return Ok(());
+    }
+
+    if let Some(last_claim_block) = LAST_CLAIM_BLOCKS.may_load(storage, (lp_token, user))? {
+        let next_claim_block = last_claim_block.saturating_add(cooldown_blocks);
+        ensure!(
+            env.block.height >= next_claim_block,
+            ContractError::ClaimCooldown { next_claim_block }
+        );
+    }
+
+    LAST_CLAIM_BLOCKS.save(storage, (lp_token, user), &env.block.height)?;
+
+    Ok(())
+}
+
+/// Moves the deposit block of the user position towards the current block
+/// proportionally to the deposited amount.
+pub fn update_deposit_block(
+    storage: &mut dyn Storage,
+    block_height: u64,
+    lp_token: &AssetInfo,
+    user: &Addr,
+    staked_amount: Uint128,
+    deposit_amount: Uint128,
+) -> StdResult<()> {
+    let deposit_block = DEPOSIT_BLOCKS
+        .may_load(storage, (lp_token, user))?
+        .unwrap_or_default();
+    let weighted = (Uint128::from(deposit_block).full_mul(staked_amount)
+        + Uint128::from(block_height).full_mul(deposit_amount))
+        / Uint256::from(staked_amount + deposit_amount);
+    let deposit_block = Uint128::try_from(weighted)?.u128() as u64;
+
+    DEPOSIT_BLOCKS.save(storage, (lp_token, user), &deposit_block)
+}
+
 /// Only factory can set the allocation points to zero for the specified pool.
 /// Called from deregistration context in factory.
 pub fn deactivate_pool(
@@ -124,6 +396,7 @@ pub fn deactivate_pool(
 
             pool_info.update_rewards(deps.storage, &env, &lp_token_asset)?;
             pool_info.disable_astro_rewards();
+            pool_info.rewards_paused = false;
             pool_info.save(deps.storage, &lp_token_asset)?;
 
             config.total_alloc_points = config.total_alloc_points.checked_sub(alloc_points)?;
@@ -168,6 +441,7 @@ pub fn deactivate_blocked_pools(deps: DepsMut, env: Env) -> Result<Response, Con
         if blocked_pair_types.contains(&pair_info.pair_type) {
             pool_info.update_rewards(deps.storage, &env, lp_token_asset)?;
             pool_info.disable_astro_rewards();
+            pool_info.rewards_paused = false;
             pool_info.save(deps.storage, lp_token_asset)?;
 
             config.total_alloc_points = config.total_alloc_points.checked_sub(*alloc_points)?;
@@ -485,6 +759,64 @@ pub fn claim_orphaned_rewards(
     Ok(Response::new().add_submessages(messages))
 }
 
+/// Sends the whole part of the rounding dust accumulated in a pool to the recipient.
+/// Protocol ASTRO dust is claimed from the vesting contract.
+pub fn sweep_reward_dust(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.owner, ContractError::Unauthorized {});
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let lp_asset = determine_asset_info(&lp_token, deps.api)?;
+    let mut pool_info = PoolInfo::load(deps.storage, &lp_asset)?;
+
+    let swept = pool_info.take_reward_dust()?;
+    ensure!(
+        !swept.is_empty(),
+        ContractError::NoRewardDust {
+            lp_token: lp_asset.to_string()
+        }
+    );
+    pool_info.save(deps.storage, &lp_asset)?;
+
+    let mut messages = vec![];
+    let mut attrs = vec![
+        attr("action", "sweep_reward_dust"),
+        attr("lp_token", lp_asset.to_string()),
+        attr("recipient", &recipient),
+    ];
+
+    for (reward, amount) in swept {
+        let reward_asset = reward.asset_info().with_balance(amount);
+        attrs.push(attr("swept_reward", reward_asset.to_string()));
+
+        if reward.is_external() {
+            messages.push(reward_asset.into_submsg(
+                &recipient,
+                Some((ReplyOn::Error, POST_TRANSFER_REPLY_ID)),
+                config.token_transfer_gas_limit,
+            )?);
+        } else {
+            messages.push(SubMsg::new(wasm_execute(
+                &config.vesting_contract,
+                &vesting::ExecuteMsg::Claim {
+                    recipient: Some(recipient.to_string()),
+                    amount: Some(amount),
+                },
+                vec![],
+            )?));
+        }
+    }
+
+    Ok(Response::new()
+        .add_attributes(attrs)
+        .add_submessages(messages))
+}
+
 pub fn asset_info_key(asset_info: &AssetInfo) -> Vec<u8> {
     let mut bytes = vec![];
     match asset_info {
@@ -8,17 +8,20 @@ use astroport::asset::{Asset, AssetInfo, AssetInfoExt, PairInfo};
 use astroport::astro_converter::OutpostBurnParams;
 use astroport::factory::{PairConfig, PairType};
 use astroport::incentives::{
-    Config, ExecuteMsg, IncentivesSchedule, IncentivizationFeeInfo, InputSchedule,
-    PoolInfoResponse, QueryMsg, RewardInfo, ScheduleResponse,
+    Config, ExecuteMsg, HarvestFeeInfo, IncentivesSchedule, IncentivizationFeeInfo, InputSchedule,
+    LockBoostConfig, PoolInfoResponse, PoolPendingRewards, PositionLock, QueryMsg, RewardInfo,
+    ScheduleResponse,
 };
 use astroport::pair::StablePoolParams;
-use astroport::vesting::{MigrateMsg, VestingAccount, VestingSchedule, VestingSchedulePoint};
+use astroport::vesting::{
+    MigrateMsg, VestingAccount, VestingAccountResponse, VestingSchedule, VestingSchedulePoint,
+};
 use astroport::{astro_converter, factory, native_coin_registry, pair, vesting};
 use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
 use cosmwasm_std::{
-    coin, to_json_binary, Addr, Api, BlockInfo, CanonicalAddr, Coin, Decimal256, Empty, Env,
-    GovMsg, IbcMsg, IbcQuery, RecoverPubkeyError, StdError, StdResult, Storage, Timestamp, Uint128,
-    VerificationError,
+    coin, to_json_binary, Addr, Api, Binary, BlockInfo, CanonicalAddr, Coin, Decimal256, Empty,
+    Env, GovMsg, IbcMsg, IbcQuery, RecoverPubkeyError, StdError, StdResult, Storage, Timestamp,
+    Uint128, VerificationError,
 };
 use cw20::MinterResponse;
 use itertools::Itertools;
@@ -29,7 +32,7 @@ use astroport_test::cw_multi_test::{
 };
 use astroport_test::modules::stargate::MockStargate;
 
-use crate::helper::broken_cw20;
+use crate::helper::{broken_cw20, mock_vesting};
 
 fn factory_contract() -> Box<dyn Contract<Empty>> {
     Box::new(
@@ -115,6 +118,14 @@ fn broken_token_contract() -> Box<dyn Contract<Empty>> {
     ))
 }
 
+fn mock_vesting_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new_with_empty(
+        mock_vesting::execute,
+        mock_vesting::instantiate,
+        mock_vesting::query,
+    ))
+}
+
 fn generator_contract() -> Box<dyn Contract<Empty>> {
     Box::new(
         ContractWrapper::new_with_empty(
@@ -480,6 +491,54 @@ impl Helper {
         )
     }
 
+    pub fn set_batch(&mut self, from: &Addr, pools: Vec<(String, u128)>) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetBatch {
+                pools: pools
+                    .into_iter()
+                    .map(|(pool, amount)| (pool, amount.into()))
+                    .collect(),
+            },
+            &[],
+        )
+    }
+
+    pub fn set_withdraw_pubkey(
+        &mut self,
+        from: &Addr,
+        pubkey: Option<Binary>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetWithdrawPubkey { pubkey },
+            &[],
+        )
+    }
+
+    pub fn withdraw_for(
+        &mut self,
+        relayer: &Addr,
+        account: &Addr,
+        lp_token: &str,
+        amount: impl Into<Uint128>,
+        signature: Binary,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            relayer.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::WithdrawFor {
+                lp_token: lp_token.to_string(),
+                account: account.to_string(),
+                amount: amount.into(),
+                signature,
+            },
+            &[],
+        )
+    }
+
     pub fn deactivate_pool(&mut self, from: &Addr, lp_token: &str) -> AnyResult<AppResponse> {
         self.app.execute_contract(
             from.clone(),
@@ -491,6 +550,23 @@ impl Helper {
         )
     }
 
+    pub fn rescue_stuck_lp(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        users: &[&Addr],
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::RescueStuckLp {
+                lp_token: lp_token.to_string(),
+                users: users.iter().map(|user| user.to_string()).collect(),
+            },
+            &[],
+        )
+    }
+
     pub fn deactivate_pool_full_flow(
         &mut self,
         asset_infos: &[AssetInfo],
@@ -593,6 +669,202 @@ impl Helper {
         )
     }
 
+    pub fn set_harvest_fee(
+        &mut self,
+        from: &Addr,
+        harvest_fee_info: Option<HarvestFeeInfo>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetHarvestFee { harvest_fee_info },
+            &[],
+        )
+    }
+
+    pub fn set_min_holding_blocks(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        min_holding_blocks: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetMinHoldingBlocks {
+                lp_token: lp_token.to_string(),
+                min_holding_blocks,
+            },
+            &[],
+        )
+    }
+
+    pub fn set_claim_cooldown(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        cooldown_blocks: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetClaimCooldown {
+                lp_token: lp_token.to_string(),
+                cooldown_blocks,
+            },
+            &[],
+        )
+    }
+
+    pub fn set_pool_rewards_paused(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        paused: bool,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetPoolRewardsPaused {
+                lp_token: lp_token.to_string(),
+                paused,
+            },
+            &[],
+        )
+    }
+
+    pub fn set_reward_vesting_duration(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        duration: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetRewardVestingDuration {
+                lp_token: lp_token.to_string(),
+                duration,
+            },
+            &[],
+        )
+    }
+
+    /// Allows the incentives contract to register vesting schedules of claimed rewards
+    pub fn add_vesting_registrar(&mut self) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            self.owner.clone(),
+            self.vesting.clone(),
+            &vesting::ExecuteMsg::UpdateRegistrars {
+                add: vec![self.generator.to_string()],
+                remove: vec![],
+            },
+            &[],
+        )
+    }
+
+    pub fn claim_vesting(&mut self, from: &Addr) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.vesting.clone(),
+            &vesting::ExecuteMsg::Claim {
+                recipient: None,
+                amount: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn set_reward_recipient(
+        &mut self,
+        from: &Addr,
+        recipient: Option<&Addr>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetRewardRecipient {
+                recipient: recipient.map(|addr| addr.to_string()),
+            },
+            &[],
+        )
+    }
+
+    pub fn set_partial_vesting_payouts(
+        &mut self,
+        from: &Addr,
+        enabled: bool,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetPartialVestingPayouts { enabled },
+            &[],
+        )
+    }
+
+    pub fn set_fallback_pool(
+        &mut self,
+        from: &Addr,
+        lp_token: Option<&str>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetFallbackPool {
+                lp_token: lp_token.map(|lp_token| lp_token.to_string()),
+            },
+            &[],
+        )
+    }
+
+    pub fn set_lock_boost(
+        &mut self,
+        from: &Addr,
+        config: Option<LockBoostConfig>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SetLockBoost { config },
+            &[],
+        )
+    }
+
+    pub fn lock_position(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        duration: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::LockPosition {
+                lp_token: lp_token.to_string(),
+                duration,
+            },
+            &[],
+        )
+    }
+
+    pub fn expire_lock(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        user: &Addr,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::ExpireLock {
+                lp_token: lp_token.to_string(),
+                user: user.to_string(),
+            },
+            &[],
+        )
+    }
+
     pub fn create_schedule(
         &self,
         asset: &Asset,
@@ -659,6 +931,20 @@ impl Helper {
             .unwrap()
     }
 
+    pub fn init_mock_vesting(&mut self, available_amount: u128) -> Addr {
+        let mock_vesting_code = self.app.store_code(mock_vesting_contract());
+        self.app
+            .instantiate_contract(
+                mock_vesting_code,
+                self.owner.clone(),
+                &Uint128::new(available_amount),
+                &[],
+                "Mock Vesting",
+                None,
+            )
+            .unwrap()
+    }
+
     pub fn incentivize_many(
         &mut self,
         from: &Addr,
@@ -801,6 +1087,23 @@ impl Helper {
         )
     }
 
+    pub fn sweep_reward_dust(
+        &mut self,
+        from: &Addr,
+        lp_token: &str,
+        recipient: impl Into<String>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            from.clone(),
+            self.generator.clone(),
+            &ExecuteMsg::SweepRewardDust {
+                lp_token: lp_token.to_string(),
+                recipient: recipient.into(),
+            },
+            &[],
+        )
+    }
+
     pub fn claim_rewards(&mut self, from: &Addr, lp_tokens: Vec<String>) -> AnyResult<AppResponse> {
         self.app.execute_contract(
             from.clone(),
@@ -868,6 +1171,20 @@ impl Helper {
             .unwrap()
     }
 
+    pub fn query_pending_rewards_all(&self, user: &Addr) -> Vec<PoolPendingRewards> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::PendingRewardsAll {
+                    user: user.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+    }
+
     pub fn query_pending_rewards(&self, user: &Addr, lp_token: &str) -> Vec<Asset> {
         self.app
             .wrap()
@@ -888,6 +1205,136 @@ impl Helper {
             .unwrap()
     }
 
+    pub fn query_harvest_fee(&self) -> Option<HarvestFeeInfo> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.generator, &QueryMsg::HarvestFee {})
+            .unwrap()
+    }
+
+    pub fn query_min_holding_blocks(&self, lp_token: &str) -> u64 {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::MinHoldingBlocks {
+                    lp_token: lp_token.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_projected_rewards(
+        &self,
+        lp_token: &str,
+        amount: u128,
+        seconds: u64,
+    ) -> Vec<Asset> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::ProjectedRewards {
+                    lp_token: lp_token.to_string(),
+                    amount: amount.into(),
+                    seconds,
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_pool_rewards_paused(&self, lp_token: &str) -> bool {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::PoolRewardsPaused {
+                    lp_token: lp_token.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_claim_cooldown(&self, lp_token: &str) -> u64 {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::ClaimCooldown {
+                    lp_token: lp_token.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_fallback_pool(&self) -> Option<AssetInfo> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.generator, &QueryMsg::FallbackPool {})
+            .unwrap()
+    }
+
+    pub fn query_vesting_account(&self, user: &Addr) -> StdResult<VestingAccountResponse> {
+        self.app.wrap().query_wasm_smart(
+            &self.vesting,
+            &vesting::QueryMsg::VestingAccount {
+                address: user.to_string(),
+            },
+        )
+    }
+
+    pub fn query_vesting_claimable(&self, user: &Addr) -> Uint128 {
+        self.app
+            .wrap()
+            .query_wasm_smart::<vesting::ClaimableResponse>(
+                &self.vesting,
+                &vesting::QueryMsg::Claimable {
+                    address: user.to_string(),
+                },
+            )
+            .unwrap()
+            .amount
+    }
+
+    pub fn query_owed_rewards(&self, user: &Addr) -> Uint128 {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::OwedRewards {
+                    user: user.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_partial_vesting_payouts(&self) -> bool {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.generator, &QueryMsg::PartialVestingPayouts {})
+            .unwrap()
+    }
+
+    pub fn query_total_distributed(&self) -> Uint128 {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.generator, &QueryMsg::TotalDistributed {})
+            .unwrap()
+    }
+
+    pub fn query_position_lock(&self, lp_token: &str, user: &Addr) -> Option<PositionLock> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::PositionLock {
+                    lp_token: lp_token.to_string(),
+                    user: user.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
     pub fn query_deposit(&self, lp_token: &str, user: &Addr) -> StdResult<u128> {
         self.app
             .wrap()
@@ -901,6 +1348,43 @@ impl Helper {
             .map(|x| x.u128())
     }
 
+    pub fn query_withdraw_nonce(&self, account: &Addr) -> StdResult<u64> {
+        self.app.wrap().query_wasm_smart(
+            &self.generator,
+            &QueryMsg::WithdrawNonce {
+                account: account.to_string(),
+            },
+        )
+    }
+
+    pub fn query_reward_recipient(&self, user: &Addr) -> Addr {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::RewardRecipient {
+                    user: user.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    pub fn query_vesting_balance(&self) -> StdResult<Uint128> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.generator, &QueryMsg::VestingBalance {})
+    }
+
+    pub fn query_staked_value(&self, lp_token: &str, user: &Addr) -> StdResult<Vec<Asset>> {
+        self.app.wrap().query_wasm_smart(
+            &self.generator,
+            &QueryMsg::StakedValue {
+                lp_token: lp_token.to_string(),
+                user: user.to_string(),
+            },
+        )
+    }
+
     pub fn is_fee_needed(&self, lp_token: &str, reward: &AssetInfo) -> bool {
         self.app
             .wrap()
@@ -1005,6 +1489,18 @@ impl Helper {
             .unwrap()
     }
 
+    pub fn query_pools_containing_asset(&self, asset_info: &AssetInfo) -> Vec<String> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.generator,
+                &QueryMsg::PoolsContainingAsset {
+                    asset_info: asset_info.clone(),
+                },
+            )
+            .unwrap()
+    }
+
     pub fn create_pair(&mut self, asset_infos: &[AssetInfo]) -> AnyResult<PairInfo> {
         let asset_infos = asset_infos.to_vec();
         self.app
@@ -0,0 +1,56 @@
+use cosmwasm_std::{
+    coin, to_json_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128,
+};
+use cw_storage_plus::Item;
+
+use astroport::vesting::{ExecuteMsg, QueryMsg};
+
+/// Amount returned by the mocked AvailableAmount query
+const AVAILABLE_AMOUNT: Item<Uint128> = Item::new("available_amount");
+
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    available_amount: Uint128,
+) -> StdResult<Response> {
+    AVAILABLE_AMOUNT.save(deps.storage, &available_amount)?;
+    Ok(Response::new())
+}
+
+/// Pays claims out of the contract balance which must hold a single denom.
+/// Registering vesting accounts tops up the available amount by the attached funds
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Claim { recipient, amount } => {
+            let amount = amount.unwrap_or_default();
+            AVAILABLE_AMOUNT.update(deps.storage, |available| available.checked_sub(amount))?;
+
+            let balances = deps.querier.query_all_balances(env.contract.address)?;
+            let denom = &balances
+                .first()
+                .ok_or_else(|| StdError::generic_err("Mock vesting is not funded"))?
+                .denom;
+
+            Ok(Response::new().add_message(BankMsg::Send {
+                to_address: recipient.unwrap_or_else(|| info.sender.to_string()),
+                amount: vec![coin(amount.u128(), denom)],
+            }))
+        }
+        ExecuteMsg::RegisterVestingAccounts { .. } => {
+            let amount: Uint128 = info.funds.iter().map(|coin| coin.amount).sum();
+            AVAILABLE_AMOUNT.update(deps.storage, |available| available.checked_add(amount))?;
+
+            Ok(Response::new())
+        }
+        _ => Err(StdError::generic_err("Not supported")),
+    }
+}
+
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::AvailableAmount { .. } => to_json_binary(&AVAILABLE_AMOUNT.load(deps.storage)?),
+        _ => Err(StdError::generic_err("Not supported")),
+    }
+}
@@ -1,5 +1,6 @@
 #![cfg(not(tarpaulin_include))]
 pub mod broken_cw20;
 mod helper;
+pub mod mock_vesting;
 
 pub use helper::*;
@@ -1,12 +1,19 @@
 use std::str::FromStr;
 
-use astroport::asset::{native_asset_info, AssetInfo, AssetInfoExt};
+use astroport::asset::{native_asset_info, Asset, AssetInfo, AssetInfoExt};
 use astroport::incentives::{
-    ExecuteMsg, IncentivizationFeeInfo, InputSchedule, ScheduleResponse, EPOCHS_START,
-    EPOCH_LENGTH, MAX_REWARD_TOKENS,
+    ExecuteMsg, HarvestFeeInfo, IncentivizationFeeInfo, InputSchedule, LockBoostConfig,
+    PositionLock, ScheduleResponse, WithdrawPermit, EPOCHS_START, EPOCH_LENGTH,
+    MAX_HARVEST_FEE_BPS, MAX_REWARD_TOKENS, MAX_REWARD_VESTING_DURATION,
+};
+use astroport::pair;
+use astroport::vesting::{VestingInfo, VestingSchedule, VestingSchedulePoint};
+use cosmwasm_std::{
+    coin, coins, to_json_vec, Addr, Binary, Decimal, Decimal256, Timestamp, Uint128,
 };
-use cosmwasm_std::{coin, coins, Decimal256, Timestamp, Uint128};
 use itertools::Itertools;
+use k256::ecdsa::signature::Signer;
+use k256::ecdsa::{Signature, SigningKey};
 
 use astroport_incentives::error::ContractError;
 use astroport_test::cw_multi_test::Executor;
@@ -294,461 +301,592 @@ fn test_claim_rewards() {
 }
 
 #[test]
-fn test_incentives() {
+fn test_harvest_fee() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
-    let incentivization_fee = helper.incentivization_fee.clone();
+    let treasury = TestAddr::new("treasury");
+    let user = TestAddr::new("user");
 
-    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let asset_infos = [AssetInfo::native("uusd"), AssetInfo::native("eur")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
     let lp_token = pair_info.liquidity_token.to_string();
-
     let provide_assets = [
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just make following calculations easier
-    // since first depositor gets small cut of LP tokens
     helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
+        .provide_liquidity(&owner, &provide_assets, &pair_info.contract_addr, false)
         .unwrap();
-
-    let user = TestAddr::new("user");
     helper
         .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    let bank = TestAddr::new("bank");
-    let reward_asset_info = AssetInfo::native("reward");
-    let reward = reward_asset_info.with_balance(1000_000000u128);
-    helper.mint_assets(&bank, &[reward.clone()]);
-    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
-    helper.mint_coin(&bank, &incentivization_fee);
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(1_000000).unwrap();
+    assert_eq!(helper.query_harvest_fee(), None);
 
-    // Check general validation
-    let err = helper
-        .incentivize(&bank, &lp_token, schedule.clone(), &[])
-        .unwrap_err();
+    // Without the harvest fee the user receives all pending ASTRO
+    helper.next_block(10);
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    let bal_before = helper.snapshot_balances(&user, &pending);
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let bal_after = helper.snapshot_balances(&user, &pending);
+    assert_rewards(&bal_before, &bal_after, &pending);
     assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::IncentivizationFeeExpected {
-            fee: incentivization_fee.to_string(),
-            lp_token: lp_token.clone(),
-            new_reward_token: reward_asset_info.to_string(),
-        }
+        astro.query_pool(&helper.app.wrap(), &treasury).unwrap(),
+        Uint128::zero()
     );
+
+    let harvest_fee_info = HarvestFeeInfo {
+        fee_receiver: treasury.clone(),
+        fee_bps: 500,
+    };
+
     let err = helper
-        .incentivize(&bank, &lp_token, schedule.clone(), &coins(1, "astro"))
+        .set_harvest_fee(&user, Some(harvest_fee_info.clone()))
         .unwrap_err();
     assert_eq!(
         err.downcast::<ContractError>().unwrap(),
-        ContractError::IncentivizationFeeExpected {
-            fee: incentivization_fee.to_string(),
-            lp_token: lp_token.clone(),
-            new_reward_token: reward_asset_info.to_string(),
-        }
+        ContractError::Unauthorized {}
     );
-    let additional_random_funds = coin(1000u128, "uusd");
-    helper.mint_coin(&bank, &additional_random_funds);
+
     let err = helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            schedule.clone(),
-            &[additional_random_funds, incentivization_fee.clone()],
+        .set_harvest_fee(
+            &owner,
+            Some(HarvestFeeInfo {
+                fee_bps: MAX_HARVEST_FEE_BPS + 1,
+                ..harvest_fee_info.clone()
+            }),
         )
         .unwrap_err();
     assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Supplied coins contain uusd that is not in the input asset vector"
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::HarvestFeeTooHigh {}
     );
 
     helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            schedule.clone(),
-            &[incentivization_fee.clone()],
-        )
-        .unwrap();
-
-    // Check maker received incentivization fee
-    let maker_amount = helper
-        .app
-        .wrap()
-        .query_balance(TestAddr::new("maker"), &incentivization_fee.denom)
+        .set_harvest_fee(&owner, Some(harvest_fee_info.clone()))
         .unwrap();
-    assert_eq!(maker_amount.amount, incentivization_fee.amount);
+    assert_eq!(helper.query_harvest_fee(), Some(harvest_fee_info));
 
-    helper.app.update_block(|block| {
-        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts)
-    });
+    // 5% of claimed ASTRO is routed to the treasury. Pending rewards are reported net of the fee
+    helper.next_block(10);
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    let pending_astro = pending
+        .iter()
+        .find(|asset| asset.info == astro)
+        .unwrap()
+        .amount;
+    let user_before = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let user_after = astro.query_pool(&helper.app.wrap(), &user).unwrap();
 
-    // Iterate over 2 weeks by 1 day and claim rewards
-    loop {
-        if helper.app.block_info().time.seconds() > internal_sch.end_ts {
-            break;
-        }
+    let fee = astro.query_pool(&helper.app.wrap(), &treasury).unwrap();
+    assert!(!fee.is_zero());
+    assert_eq!(fee, (pending_astro + fee).multiply_ratio(500u16, 10000u16));
+    assert_eq!(user_after - user_before, pending_astro);
 
-        let pending = helper.query_pending_rewards(&user, &lp_token);
-        let bal_before = helper.snapshot_balances(&user, &pending);
+    // Disabling the fee restores the previous behavior
+    helper.set_harvest_fee(&owner, None).unwrap();
+    assert_eq!(helper.query_harvest_fee(), None);
 
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    helper.next_block(10);
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    let bal_before = helper.snapshot_balances(&user, &pending);
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let bal_after = helper.snapshot_balances(&user, &pending);
+    assert_rewards(&bal_before, &bal_after, &pending);
+    assert_eq!(
+        astro.query_pool(&helper.app.wrap(), &treasury).unwrap(),
+        fee
+    );
+}
 
-        let bal_after = helper.snapshot_balances(&user, &pending);
-        assert_rewards(&bal_before, &bal_after, &pending);
+#[test]
+fn test_total_distributed() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let user1 = TestAddr::new("user1");
+    let user2 = TestAddr::new("user2");
 
-        helper
-            .app
-            .update_block(|block| block.time = block.time.plus_seconds(86400));
-    }
+    let pair1 = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("bar")])
+        .unwrap()
+        .liquidity_token
+        .to_string();
+    let pair2 = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("usd")])
+        .unwrap()
+        .liquidity_token
+        .to_string();
 
-    let reward_balance = reward_asset_info
-        .query_pool(&helper.app.wrap(), &user)
+    helper
+        .setup_pools(vec![(pair1.clone(), 1), (pair2.clone(), 3)])
         .unwrap();
-    // A small amount of reward is lost due to rounding
-    assert_eq!(reward_balance.u128(), 999_999986);
+    helper.set_tokens_per_second(1_000000).unwrap();
 
-    // Claim after schedule ended doesn't do anything
-    for _ in 0..5 {
-        helper
-            .app
-            .update_block(|block| block.time = block.time.plus_seconds(86400));
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-        let new_reward_balance = reward_asset_info
-            .query_pool(&helper.app.wrap(), &user)
-            .unwrap();
-        assert_eq!(new_reward_balance, reward_balance);
+    assert_eq!(helper.query_total_distributed(), Uint128::zero());
+
+    for (user, lp_token) in [(&user1, &pair1), (&user2, &pair2)] {
+        let lp = native_asset_info(lp_token.clone()).with_balance(10000u16);
+        helper.mint_coin(user, &lp.as_coin().unwrap());
+        helper.stake(user, lp).unwrap();
     }
+
+    helper.next_block(100);
+    helper.claim_rewards(&user1, vec![pair1.clone()]).unwrap();
+    let user1_claimed = astro.query_pool(&helper.app.wrap(), &user1).unwrap();
+    assert!(!user1_claimed.is_zero());
+    assert_eq!(helper.query_total_distributed(), user1_claimed);
+
+    helper.next_block(100);
+    helper.claim_rewards(&user1, vec![pair1.clone()]).unwrap();
+    helper.unstake(&user2, &pair2, 10000u16).unwrap();
+
+    let total_claimed = astro.query_pool(&helper.app.wrap(), &user1).unwrap()
+        + astro.query_pool(&helper.app.wrap(), &user2).unwrap();
+    assert_eq!(helper.query_total_distributed(), total_claimed);
 }
 
 #[test]
-fn test_cw20_incentives() {
+fn test_lock_boost() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
-    let incentivization_fee = helper.incentivization_fee.clone();
+    let user1 = TestAddr::new("user1");
+    let user2 = TestAddr::new("user2");
 
-    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
-    let pair_info = helper.create_pair(&asset_infos).unwrap();
-    let lp_token = pair_info.liquidity_token.to_string();
+    let lp_token = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("bar")])
+        .unwrap()
+        .liquidity_token
+        .to_string();
 
-    let provide_assets = [
-        asset_infos[0].with_balance(100000u64),
-        asset_infos[1].with_balance(100000u64),
-    ];
-    // Owner provides liquidity first just make following calculations easier
-    // since first depositor gets small cut of LP tokens
-    helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
-        .unwrap();
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(1_000000).unwrap();
 
-    let user = TestAddr::new("user");
-    helper
-        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
-        .unwrap();
+    for user in [&user1, &user2] {
+        let lp = native_asset_info(lp_token.clone()).with_balance(10000u16);
+        helper.mint_coin(user, &lp.as_coin().unwrap());
+        helper.stake(user, lp).unwrap();
+    }
 
-    let bank = TestAddr::new("bank");
-    let reward_cw20 = helper.init_cw20("reward", None);
-    let reward_asset_info = AssetInfo::cw20(reward_cw20);
-    let reward = reward_asset_info.with_balance(1000_000000u128);
-    helper.mint_assets(&bank, &[reward.clone()]);
+    let err = helper.lock_position(&user1, &lp_token, 1000).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LockBoostDisabled {}
+    );
 
-    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
-    helper.mint_coin(&bank, &incentivization_fee);
-    helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            schedule.clone(),
-            &[incentivization_fee.clone()],
+    let lock_boost = LockBoostConfig {
+        min_lock_duration: 1000,
+        max_lock_duration: 10000,
+        multiplier: Decimal::from_str("2").unwrap(),
+    };
+    let err = helper
+        .set_lock_boost(&user1, Some(lock_boost.clone()))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    for multiplier in ["1", "3.1"] {
+        let err = helper
+            .set_lock_boost(
+                &owner,
+                Some(LockBoostConfig {
+                    multiplier: Decimal::from_str(multiplier).unwrap(),
+                    ..lock_boost.clone()
+                }),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidLockMultiplier {}
+        );
+    }
+    let err = helper
+        .set_lock_boost(
+            &owner,
+            Some(LockBoostConfig {
+                max_lock_duration: 999,
+                ..lock_boost.clone()
+            }),
         )
-        .unwrap();
-
-    helper.app.update_block(|block| {
-        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts)
-    });
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidLockDurations {}
+    );
+    helper.set_lock_boost(&owner, Some(lock_boost)).unwrap();
 
-    // Iterate over 2 weeks by 1 day and claim rewards
-    loop {
-        if helper.app.block_info().time.seconds() > internal_sch.end_ts {
-            break;
+    let err = helper.lock_position(&user1, &lp_token, 999).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LockDurationTooShort {
+            min_lock_duration: 1000
+        }
+    );
+    let err = helper.lock_position(&user1, &lp_token, 10001).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LockDurationTooLong {
+            max_lock_duration: 10000
         }
+    );
 
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    helper.lock_position(&user1, &lp_token, 1000).unwrap();
+    let unlock_ts = helper.app.block_info().time.seconds() + 1000;
+    assert_eq!(
+        helper.query_position_lock(&lp_token, &user1),
+        Some(PositionLock {
+            unlock_ts,
+            multiplier: Decimal::from_str("2").unwrap(),
+        })
+    );
 
+    let astro_pending = |helper: &Helper, user: &Addr| {
         helper
-            .app
-            .update_block(|block| block.time = block.time.plus_seconds(86400));
-    }
-
-    let reward_balance = reward_asset_info
-        .query_pool(&helper.app.wrap(), &user)
-        .unwrap();
-    // A small amount of reward is lost due to rounding
-    assert_eq!(reward_balance.u128(), 999_999986);
+            .query_pending_rewards(user, &lp_token)
+            .into_iter()
+            .find(|asset| asset.info == astro)
+            .map(|asset| asset.amount.u128())
+            .unwrap_or_default()
+    };
+    let user2_before = astro_pending(&helper, &user2);
 
-    // Claiming after schedule ended doesn't do anything
-    for _ in 0..5 {
-        helper
-            .app
-            .update_block(|block| block.time = block.time.plus_seconds(86400));
+    helper.next_block(600);
 
-        let pending = helper.query_pending_rewards(&user, &lp_token);
-        let bal_before = helper.snapshot_balances(&user, &pending);
+    // The locked position earns twice as much as the unlocked one of the same size
+    let user1_rewards = astro_pending(&helper, &user1);
+    let user2_rewards = astro_pending(&helper, &user2) - user2_before;
+    assert_eq!(user1_rewards, 400_000000);
+    assert_eq!(user2_rewards, 200_000000);
 
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let err = helper.unstake(&user1, &lp_token, 10000u16).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PositionLocked { unlock_ts }
+    );
+    let err = helper.expire_lock(&user2, &lp_token, &user1).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PositionLocked { unlock_ts }
+    );
 
-        let bal_after = helper.snapshot_balances(&user, &pending);
-        assert_rewards(&bal_before, &bal_after, &pending);
+    helper.next_block(400);
 
-        let new_reward_balance = reward_asset_info
-            .query_pool(&helper.app.wrap(), &user)
-            .unwrap();
-        assert_eq!(new_reward_balance, reward_balance);
-    }
-}
+    // Anyone can remove an expired lock
+    helper.expire_lock(&user2, &lp_token, &user1).unwrap();
+    assert_eq!(helper.query_position_lock(&lp_token, &user1), None);
 
-#[test]
-fn test_large_incentives() {
-    let astro = native_asset_info("astro".to_string());
-    let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let owner = helper.owner.clone();
-    let incentivization_fee = helper.incentivization_fee.clone();
+    helper
+        .claim_rewards(&user2, vec![lp_token.clone()])
+        .unwrap();
+    helper.next_block(100);
+    assert_eq!(
+        astro_pending(&helper, &user1),
+        astro_pending(&helper, &user2)
+    );
 
-    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
-    let pair_info = helper.create_pair(&asset_infos).unwrap();
-    let lp_token = pair_info.liquidity_token.to_string();
-
-    let provide_assets = [
-        asset_infos[0].with_balance(100000u64),
-        asset_infos[1].with_balance(100000u64),
-    ];
-    // Owner provides liquidity first just make following calculations easier
-    // since first depositor gets small cut of LP tokens
+    // Claiming drops an expired lock without waiting for ExpireLock
+    helper.lock_position(&user2, &lp_token, 1000).unwrap();
+    helper.next_block(1000);
     helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
+        .claim_rewards(&user2, vec![lp_token.clone()])
         .unwrap();
+    assert_eq!(helper.query_position_lock(&lp_token, &user2), None);
 
-    let user = TestAddr::new("user");
     helper
-        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .claim_rewards(&user1, vec![lp_token.clone()])
         .unwrap();
+    helper.next_block(100);
+    assert_eq!(
+        astro_pending(&helper, &user1),
+        astro_pending(&helper, &user2)
+    );
 
-    let bank = TestAddr::new("bank");
-    let reward_asset_info = AssetInfo::native("reward");
-    let reward = reward_asset_info.with_balance(2839081665193567584256u128);
-    helper.mint_assets(&bank, &[reward.clone()]);
+    helper.unstake(&user1, &lp_token, 10000u16).unwrap();
+}
 
-    let (schedule, internal_sch) = helper.create_schedule(&reward, 1).unwrap();
+#[test]
+fn test_min_holding_blocks() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let treasury = TestAddr::new("treasury");
+    let user = TestAddr::new("user");
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(1_000000).unwrap();
+
+    let owner_lp = native_asset_info(lp_token.clone()).with_balance(10000u16);
+    helper.mint_coin(&owner, &owner_lp.as_coin().unwrap());
+    helper.stake(&owner, owner_lp).unwrap();
+
+    assert_eq!(helper.query_min_holding_blocks(&lp_token), 0);
+
+    let err = helper
+        .set_min_holding_blocks(&user, &lp_token, 5)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    helper.set_min_holding_blocks(&owner, &lp_token, 5).unwrap();
+    assert_eq!(helper.query_min_holding_blocks(&lp_token), 5);
+
+    // External rewards are not subject to the holding period
+    let bank = TestAddr::new("bank");
+    let ext_reward = AssetInfo::native("ext").with_balance(1_000_000_000000u128);
+    helper.mint_coin(&bank, &ext_reward.as_coin().unwrap());
+    let incentivization_fee = helper.incentivization_fee.clone();
     helper.mint_coin(&bank, &incentivization_fee);
+    let (schedule, _) = helper.create_schedule(&ext_reward, 1).unwrap();
     helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            schedule.clone(),
-            &[incentivization_fee.clone()],
-        )
+        .incentivize(&bank, &lp_token, schedule, &[incentivization_fee])
         .unwrap();
 
-    // Test claims between short periods
-    for _ in 0..10 {
-        helper.next_block(100);
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    // Depositing and withdrawing before the holding period ends yields no ASTRO rewards
+    let user_lp = native_asset_info(lp_token.clone()).with_balance(10000u16);
+    helper.mint_coin(&user, &user_lp.as_coin().unwrap());
+    helper.stake(&user, user_lp.clone()).unwrap();
+
+    helper.next_block(10);
+    // Pending ASTRO rewards are reported as zero as a claim would forfeit them
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    for asset in &pending {
+        assert_eq!(asset.amount.is_zero(), asset.info == astro, "{asset}");
     }
+    let pending_all = helper.query_pending_rewards_all(&user);
+    assert!(pending_all[0].astro_pending.is_zero());
+    assert!(!pending_all[0].external_pending[0].amount.is_zero());
 
-    // Jump to the end of the schedule
+    helper.unstake(&user, &lp_token, 10000u16).unwrap();
+    assert_eq!(
+        astro.query_pool(&helper.app.wrap(), &user).unwrap(),
+        Uint128::zero()
+    );
+    assert!(!ext_reward
+        .info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap()
+        .is_zero());
+
+    // Forfeited rewards are left in the pool's reward dust
     helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts));
+        .sweep_reward_dust(&owner, &lp_token, &treasury)
+        .unwrap();
+    assert!(!astro
+        .query_pool(&helper.app.wrap(), &treasury)
+        .unwrap()
+        .is_zero());
 
+    // Holding past the threshold yields full rewards
+    helper.stake(&user, user_lp).unwrap();
+    for _ in 0..5 {
+        helper.next_block(10);
+    }
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    let bal_before = helper.snapshot_balances(&user, &pending);
     helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let bal_after = helper.snapshot_balances(&user, &pending);
+    assert_rewards(&bal_before, &bal_after, &pending);
+
+    // Disabling the requirement removes it
+    helper.set_min_holding_blocks(&owner, &lp_token, 0).unwrap();
+    assert_eq!(helper.query_min_holding_blocks(&lp_token), 0);
 }
 
 #[test]
-fn test_multiple_schedules_same_reward() {
+fn test_claim_cooldown() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
-    let incentivization_fee = helper.incentivization_fee.clone();
+    let user = TestAddr::new("user");
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
     let lp_token = pair_info.liquidity_token.to_string();
 
-    let provide_assets = [
-        asset_infos[0].with_balance(100000u64),
-        asset_infos[1].with_balance(100000u64),
-    ];
-    // Owner provides liquidity first just make following calculations easier
-    // since first depositor gets small cut of LP tokens
-    helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
-        .unwrap();
-
-    // Incentivize with ASTRO
     helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
-    helper.set_tokens_per_second(100).unwrap();
+    helper.set_tokens_per_second(1_000000).unwrap();
 
-    let user = TestAddr::new("user");
-    helper
-        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
-        .unwrap();
+    let user_lp = native_asset_info(lp_token.clone()).with_balance(10000u16);
+    helper.mint_coin(&user, &user_lp.as_coin().unwrap());
+    helper.stake(&user, user_lp).unwrap();
 
-    let bank = TestAddr::new("bank");
-    let reward_asset_info = AssetInfo::native("reward");
-    let reward = reward_asset_info.with_balance(1000_000000u128);
+    assert_eq!(helper.query_claim_cooldown(&lp_token), 0);
 
-    // Create multiple overlapping schedules with the same reward token starting right away
-    let schedules: Vec<_> = (1..=5)
-        .into_iter()
-        .map(|i| helper.create_schedule(&reward, i).unwrap())
-        .collect();
-    for (ind, (schedule, _)) in schedules.iter().enumerate() {
-        helper.mint_assets(&bank, &[reward.clone()]);
-        if ind == 0 {
-            // attach incentivization fee on the first schedule
-            helper.mint_coin(&bank, &incentivization_fee);
-            helper
-                .incentivize(
-                    &bank,
-                    &lp_token,
-                    schedule.clone(),
-                    &[incentivization_fee.clone()],
-                )
-                .unwrap();
-        } else {
-            helper
-                .incentivize(&bank, &lp_token, schedule.clone(), &[])
-                .unwrap();
-        }
-    }
+    let err = helper.set_claim_cooldown(&user, &lp_token, 5).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
 
-    let time_before_claims = helper.app.block_info().time.seconds();
+    helper.set_claim_cooldown(&owner, &lp_token, 5).unwrap();
+    assert_eq!(helper.query_claim_cooldown(&lp_token), 5);
 
-    // Rewards started right away
-    helper
-        .app
-        .update_block(|block| block.time = block.time.plus_seconds(86400));
+    // The first claim is not limited
+    helper.next_block(10);
     helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-    let reward_balance = reward_asset_info
-        .query_pool(&helper.app.wrap(), &user)
-        .unwrap();
-    assert_eq!(reward_balance.u128(), 207_189296);
-    // And received ASTRO rewards
-    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
-    assert_eq!(astro_reward_balance.u128(), 86400 * 100);
+    let next_claim_block = helper.app.block_info().height + 5;
 
-    // Iterate till the end of the longest schedule by 1 day and claim rewards
-    loop {
-        let pending = helper.query_pending_rewards(&user, &lp_token);
-        let bal_before = helper.snapshot_balances(&user, &pending);
+    // A claim within the cooldown is rejected while rewards keep accruing
+    helper.next_block(10);
+    let err = helper
+        .claim_rewards(&user, vec![lp_token.clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ClaimCooldown { next_claim_block }
+    );
 
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    // Deposits and withdrawals are not blocked by the cooldown
+    let extra_lp = native_asset_info(lp_token.clone()).with_balance(1u8);
+    helper.mint_coin(&user, &extra_lp.as_coin().unwrap());
+    helper.stake(&user, extra_lp).unwrap();
+    helper.unstake(&user, &lp_token, 5000u128).unwrap();
+    assert_eq!(helper.query_deposit(&lp_token, &user).unwrap(), 5001);
 
-        let bal_after = helper.snapshot_balances(&user, &pending);
-        assert_rewards(&bal_before, &bal_after, &pending);
+    // The cooldown is still running for explicit claims
+    let err = helper
+        .claim_rewards(&user, vec![lp_token.clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ClaimCooldown { next_claim_block }
+    );
 
-        if helper.app.block_info().time.seconds() > schedules.last().cloned().unwrap().1.end_ts {
-            break;
-        } else {
-            helper.next_block(86400)
-        }
+    for _ in 0..4 {
+        helper.next_block(10);
     }
-
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    let bal_before = helper.snapshot_balances(&user, &pending);
     helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-    let reward_balance = reward_asset_info
-        .query_pool(&helper.app.wrap(), &user)
-        .unwrap();
-    // A small amount of reward is lost due to rounding
-    assert_eq!(reward_balance.u128(), 4999_999972);
+    let bal_after = helper.snapshot_balances(&user, &pending);
+    assert_rewards(&bal_before, &bal_after, &pending);
 
-    let time_now = helper.app.block_info().time.seconds();
-    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
-    assert_eq!(
-        astro_reward_balance.u128(),
-        u128::from(time_now - time_before_claims) * 100
-    );
+    // Disabling the cooldown removes it
+    helper.set_claim_cooldown(&owner, &lp_token, 0).unwrap();
+    assert_eq!(helper.query_claim_cooldown(&lp_token), 0);
 }
 
 #[test]
-fn test_astro_can_bypass_rewards_limit() {
+fn test_pool_rewards_pause() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let incentivization_fee = helper.incentivization_fee.clone();
+    let owner = helper.owner.clone();
+    let user = TestAddr::new("user");
 
-    let lp_token = helper
-        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("bar")])
-        .unwrap()
-        .liquidity_token
-        .to_string();
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
 
-    let bank = TestAddr::new("bank");
+    let err = helper
+        .set_pool_rewards_paused(&owner, &lp_token, true)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PoolNotActive {
+            lp_token: lp_token.clone()
+        }
+    );
 
-    let schedules: Vec<_> = (1..=MAX_REWARD_TOKENS)
-        .into_iter()
-        .map(|i| {
-            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
-            let reward = reward_asset_info.with_balance(1000_000000u128);
-            helper.create_schedule(&reward, 1).unwrap()
-        })
-        .collect();
-    // Create multiple schedules with different rewards (starts on the next week)
-    for (schedule, _) in &schedules {
-        helper.mint_assets(&bank, &[schedule.reward.clone()]);
-        helper.mint_coin(&bank, &incentivization_fee);
-        helper
-            .incentivize(
-                &bank,
-                &lp_token,
-                schedule.clone(),
-                &[incentivization_fee.clone()],
-            )
-            .unwrap();
-    }
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(1_000000).unwrap();
+
+    let user_lp = native_asset_info(lp_token.clone()).with_balance(10000u16);
+    helper.mint_coin(&user, &user_lp.as_coin().unwrap());
+    helper.stake(&user, user_lp).unwrap();
+
+    let err = helper
+        .set_pool_rewards_paused(&user, &lp_token, true)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    helper.next_block(10);
+    let accrued_before_pause = helper.query_pending_rewards(&user, &lp_token)[0].amount;
+    assert!(!accrued_before_pause.is_zero());
 
-    // ASTRO can always be added no matter what MAX_REWARD_TOKENS limit is
-    let astro_reward = astro.with_balance(1000_000000u128);
-    helper.mint_assets(&bank, &[astro_reward.clone()]);
     helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            InputSchedule {
-                reward: astro_reward.clone(),
-                duration_periods: 1,
-            },
-            &[],
-        )
+        .set_pool_rewards_paused(&owner, &lp_token, true)
+        .unwrap();
+    assert!(helper.query_pool_rewards_paused(&lp_token));
+
+    // Nothing accrues while paused but the pool keeps its alloc points
+    helper.next_block(10);
+    assert_eq!(
+        helper.query_pending_rewards(&user, &lp_token)[0].amount,
+        accrued_before_pause
+    );
+    assert_eq!(
+        helper.active_pools(),
+        vec![(lp_token.clone(), Uint128::new(100))]
+    );
+
+    helper
+        .set_pool_rewards_paused(&owner, &lp_token, false)
         .unwrap();
+    assert!(!helper.query_pool_rewards_paused(&lp_token));
+
+    // Accrual resumes at the prior rate
+    helper.next_block(10);
+    assert_eq!(
+        helper.query_pending_rewards(&user, &lp_token)[0].amount,
+        accrued_before_pause * Uint128::new(2)
+    );
 }
 
 #[test]
-fn test_multiple_schedules_different_reward() {
+fn test_projected_rewards() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let owner = helper.owner.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(1_000000).unwrap();
+
+    // The only staker gets all emissions
+    assert_eq!(
+        helper.query_projected_rewards(&lp_token, 10000, 100),
+        vec![astro.with_balance(100_000000u128)]
+    );
+
+    let user = TestAddr::new("user");
+    let user_lp = native_asset_info(lp_token.clone()).with_balance(30000u16);
+    helper.mint_coin(&user, &user_lp.as_coin().unwrap());
+    helper.stake(&user, user_lp).unwrap();
+
+    // 1 ASTRO per second * 100 seconds * 10000 / (30000 + 10000)
+    let projected = helper.query_projected_rewards(&lp_token, 10000, 100);
+    assert_eq!(projected, vec![astro.with_balance(25_000000u128)]);
+
+    // The projection matches actual rewards if the pool state doesn't change
+    let user2 = TestAddr::new("user2");
+    let user2_lp = native_asset_info(lp_token.clone()).with_balance(10000u16);
+    helper.mint_coin(&user2, &user2_lp.as_coin().unwrap());
+    helper.stake(&user2, user2_lp).unwrap();
+    helper.next_block(100);
+    assert_eq!(helper.query_pending_rewards(&user2, &lp_token), projected);
+}
+
+#[test]
+fn test_incentives() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
     let incentivization_fee = helper.incentivization_fee.clone();
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
@@ -759,7 +897,7 @@ fn test_multiple_schedules_different_reward() {
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just to make following calculations easier
+    // Owner provides liquidity first just make following calculations easier
     // since first depositor gets small cut of LP tokens
     helper
         .provide_liquidity(
@@ -770,81 +908,83 @@ fn test_multiple_schedules_different_reward() {
         )
         .unwrap();
 
-    // Incentivize with ASTRO
-    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
-    helper.set_tokens_per_second(100).unwrap();
-
     let user = TestAddr::new("user");
     helper
         .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
     let bank = TestAddr::new("bank");
-
-    let schedules: Vec<_> = (1..=MAX_REWARD_TOKENS)
-        .into_iter()
-        .map(|i| {
-            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
-            let reward = reward_asset_info.with_balance(1000_000000u128);
-            helper.create_schedule(&reward, 2).unwrap()
-        })
-        .collect();
-    // Create multiple schedules with different rewards (starts on the next week)
-    for (schedule, _) in &schedules {
-        helper.mint_assets(&bank, &[schedule.reward.clone()]);
-        helper.mint_coin(&bank, &incentivization_fee);
-        helper
-            .incentivize(
-                &bank,
-                &lp_token,
-                schedule.clone(),
-                &[incentivization_fee.clone()],
-            )
-            .unwrap();
-    }
-
-    // Can't incentivize with one more reward token
-    let reward_asset_info = AssetInfo::native(format!("reward{}", MAX_REWARD_TOKENS + 1));
+    let reward_asset_info = AssetInfo::native("reward");
     let reward = reward_asset_info.with_balance(1000_000000u128);
-    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
-    helper.mint_assets(&bank, &[schedule.reward.clone()]);
+    helper.mint_assets(&bank, &[reward.clone()]);
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
     helper.mint_coin(&bank, &incentivization_fee);
+
+    // Check general validation
+    let err = helper
+        .incentivize(&bank, &lp_token, schedule.clone(), &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncentivizationFeeExpected {
+            fee: incentivization_fee.to_string(),
+            lp_token: lp_token.clone(),
+            new_reward_token: reward_asset_info.to_string(),
+        }
+    );
+    let err = helper
+        .incentivize(&bank, &lp_token, schedule.clone(), &coins(1, "astro"))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncentivizationFeeExpected {
+            fee: incentivization_fee.to_string(),
+            lp_token: lp_token.clone(),
+            new_reward_token: reward_asset_info.to_string(),
+        }
+    );
+    let additional_random_funds = coin(1000u128, "uusd");
+    helper.mint_coin(&bank, &additional_random_funds);
     let err = helper
         .incentivize(
             &bank,
             &lp_token,
             schedule.clone(),
-            &[incentivization_fee.clone()],
+            &[additional_random_funds, incentivization_fee.clone()],
         )
         .unwrap_err();
     assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::TooManyRewardTokens {
-            lp_token: lp_token.clone()
-        }
+        err.root_cause().to_string(),
+        "Generic error: Supplied coins contain uusd that is not in the input asset vector"
     );
 
-    let time_before_claims = helper.app.block_info().time.seconds();
-
-    // Rewards started right away
     helper
+        .incentivize(
+            &bank,
+            &lp_token,
+            schedule.clone(),
+            &[incentivization_fee.clone()],
+        )
+        .unwrap();
+
+    // Check maker received incentivization fee
+    let maker_amount = helper
         .app
-        .update_block(|block| block.time = block.time.plus_seconds(86400));
-    for (schedule, _) in &schedules {
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-        let reward_balance = schedule
-            .reward
-            .info
-            .query_pool(&helper.app.wrap(), &user)
-            .unwrap();
-        assert_eq!(reward_balance.u128(), 47_629547);
-    }
-    // And received ASTRO rewards
-    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
-    assert_eq!(astro_reward_balance.u128(), 86400 * 100);
+        .wrap()
+        .query_balance(TestAddr::new("maker"), &incentivization_fee.denom)
+        .unwrap();
+    assert_eq!(maker_amount.amount, incentivization_fee.amount);
 
-    // Iterate till the end of the longest schedule by 1 day and claim rewards
+    helper.app.update_block(|block| {
+        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts)
+    });
+
+    // Iterate over 2 weeks by 1 day and claim rewards
     loop {
+        if helper.app.block_info().time.seconds() > internal_sch.end_ts {
+            break;
+        }
+
         let pending = helper.query_pending_rewards(&user, &lp_token);
         let bal_before = helper.snapshot_balances(&user, &pending);
 
@@ -853,41 +993,32 @@ fn test_multiple_schedules_different_reward() {
         let bal_after = helper.snapshot_balances(&user, &pending);
         assert_rewards(&bal_before, &bal_after, &pending);
 
-        if helper.app.block_info().time.seconds() > schedules.last().cloned().unwrap().1.end_ts {
-            break;
-        } else {
-            helper
-                .app
-                .update_block(|block| block.time = block.time.plus_seconds(86400));
-        }
+        helper
+            .app
+            .update_block(|block| block.time = block.time.plus_seconds(86400));
     }
 
-    for (schedule, _) in &schedules {
+    let reward_balance = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+    // A small amount of reward is lost due to rounding
+    assert_eq!(reward_balance.u128(), 999_999986);
+
+    // Claim after schedule ended doesn't do anything
+    for _ in 0..5 {
+        helper
+            .app
+            .update_block(|block| block.time = block.time.plus_seconds(86400));
         helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-        let reward_balance = schedule
-            .reward
-            .info
+        let new_reward_balance = reward_asset_info
             .query_pool(&helper.app.wrap(), &user)
             .unwrap();
-        // Total amount is a bit off because of rounding due to Decimal256 type
-        assert_eq!(
-            reward_balance.u128(),
-            999_999980,
-            "Balance for {} is wrong",
-            schedule.reward.info
-        );
+        assert_eq!(new_reward_balance, reward_balance);
     }
-
-    let time_now = helper.app.block_info().time.seconds();
-    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
-    assert_eq!(
-        astro_reward_balance.u128(),
-        u128::from(time_now - time_before_claims) * 100
-    );
 }
 
 #[test]
-fn test_incentivize_many() {
+fn test_cw20_incentives() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
@@ -901,14 +1032,14 @@ fn test_incentivize_many() {
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just to make following calculations easier
+    // Owner provides liquidity first just make following calculations easier
     // since first depositor gets small cut of LP tokens
     helper
         .provide_liquidity(
             &owner,
             &provide_assets,
             &pair_info.contract_addr,
-            false, // Owner doesn't stake in generator
+            false, // Owner doesn't stake in incentives
         )
         .unwrap();
 
@@ -918,108 +1049,51 @@ fn test_incentivize_many() {
         .unwrap();
 
     let bank = TestAddr::new("bank");
-
-    let schedules: Vec<_> = (1..=MAX_REWARD_TOKENS)
-        .into_iter()
-        .map(|i| {
-            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
-            let reward = reward_asset_info.with_balance(1000_000000u128);
-            helper.create_schedule(&reward, 2).unwrap()
-        })
-        .collect();
-
-    // Create multiple schedules with different rewards (starts on the next week)
-    let incentives = schedules
-        .iter()
-        .map(|(schedule, _)| {
-            helper.mint_assets(&bank, &[schedule.reward.clone()]);
-            helper.mint_coin(&bank, &incentivization_fee);
-            (lp_token.as_str(), schedule.clone())
-        })
-        .collect_vec();
-
-    // Missing incentivize fee
-    let err = helper
-        .incentivize_many(&bank, incentives.clone(), &[])
-        .unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::IncentivizationFeeExpected {
-            fee: incentivization_fee.to_string(),
-            lp_token: lp_token.clone(),
-            new_reward_token: schedules[0].0.reward.info.to_string(),
-        }
-    );
-
-    // Add random coins
-    let random_coin = coin(1000, "random");
-    helper.mint_coin(&bank, &random_coin);
-    let err = helper
-        .incentivize_many(
-            &bank,
-            incentives.clone(),
-            &[
-                coin(
-                    incentivization_fee.amount.u128() * schedules.len() as u128,
-                    &incentivization_fee.denom,
-                ),
-                random_coin,
-            ],
-        )
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Supplied coins contain random that is not in the input asset vector"
-    );
-
-    helper
-        .incentivize_many(
-            &bank,
-            incentives,
-            &coins(
-                incentivization_fee.amount.u128() * schedules.len() as u128,
-                &incentivization_fee.denom,
-            ),
-        )
-        .unwrap();
-
-    // Can't incentivize with one more reward token
-    let reward_asset_info = AssetInfo::native(format!("reward{}", MAX_REWARD_TOKENS + 1));
+    let reward_cw20 = helper.init_cw20("reward", None);
+    let reward_asset_info = AssetInfo::cw20(reward_cw20);
     let reward = reward_asset_info.with_balance(1000_000000u128);
-    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
-    helper.mint_assets(&bank, &[schedule.reward.clone()]);
+    helper.mint_assets(&bank, &[reward.clone()]);
+
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
     helper.mint_coin(&bank, &incentivization_fee);
-    let err = helper
+    helper
         .incentivize(
             &bank,
             &lp_token,
             schedule.clone(),
             &[incentivization_fee.clone()],
         )
-        .unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::TooManyRewardTokens {
-            lp_token: lp_token.clone()
+        .unwrap();
+
+    helper.app.update_block(|block| {
+        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts)
+    });
+
+    // Iterate over 2 weeks by 1 day and claim rewards
+    loop {
+        if helper.app.block_info().time.seconds() > internal_sch.end_ts {
+            break;
         }
-    );
 
-    // Rewards started right away
-    helper
-        .app
-        .update_block(|block| block.time = block.time.plus_seconds(86400));
-    for (schedule, _) in &schedules {
         helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-        let reward_balance = schedule
-            .reward
-            .info
-            .query_pool(&helper.app.wrap(), &user)
-            .unwrap();
-        assert_eq!(reward_balance.u128(), 47_629547);
+
+        helper
+            .app
+            .update_block(|block| block.time = block.time.plus_seconds(86400));
     }
 
-    // Iterate till the end of the longest schedule by 1 day and claim rewards
-    loop {
+    let reward_balance = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+    // A small amount of reward is lost due to rounding
+    assert_eq!(reward_balance.u128(), 999_999986);
+
+    // Claiming after schedule ended doesn't do anything
+    for _ in 0..5 {
+        helper
+            .app
+            .update_block(|block| block.time = block.time.plus_seconds(86400));
+
         let pending = helper.query_pending_rewards(&user, &lp_token);
         let bal_before = helper.snapshot_balances(&user, &pending);
 
@@ -1028,34 +1102,15 @@ fn test_incentivize_many() {
         let bal_after = helper.snapshot_balances(&user, &pending);
         assert_rewards(&bal_before, &bal_after, &pending);
 
-        if helper.app.block_info().time.seconds() > schedules.last().cloned().unwrap().1.end_ts {
-            break;
-        } else {
-            helper
-                .app
-                .update_block(|block| block.time = block.time.plus_seconds(86400));
-        }
-    }
-
-    for (schedule, _) in &schedules {
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-        let reward_balance = schedule
-            .reward
-            .info
+        let new_reward_balance = reward_asset_info
             .query_pool(&helper.app.wrap(), &user)
             .unwrap();
-        // Total amount is a bit off because of rounding due to Decimal256 type
-        assert_eq!(
-            reward_balance.u128(),
-            999_999980,
-            "Balance for {} is wrong",
-            schedule.reward.info
-        );
+        assert_eq!(new_reward_balance, reward_balance);
     }
 }
 
 #[test]
-fn test_claim_between_different_periods() {
+fn test_18_decimals_cw20_incentives() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
@@ -1069,15 +1124,8 @@ fn test_claim_between_different_periods() {
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just to make following calculations easier
-    // since first depositor gets small cut of LP tokens
     helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
+        .provide_liquidity(&owner, &provide_assets, &pair_info.contract_addr, false)
         .unwrap();
 
     let user = TestAddr::new("user");
@@ -1085,66 +1133,62 @@ fn test_claim_between_different_periods() {
         .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    // External incentives
+    // Reward amounts are accrued in raw units so token decimals don't affect the math
     let bank = TestAddr::new("bank");
-    let reward_asset_info = AssetInfo::native("reward");
-    let reward = reward_asset_info.with_balance(1000_000000u128);
-
-    // Create multiple overlapping schedules with the same reward token starting right away
-    let schedules: Vec<_> = (1..=2)
-        .into_iter()
-        .map(|i| helper.create_schedule(&reward, i).unwrap())
-        .collect();
-    for (ind, (schedule, _)) in schedules.iter().enumerate() {
-        helper.mint_assets(&bank, &[reward.clone()]);
-        if ind == 0 {
-            // attach incentivization fee on the first schedule
-            helper.mint_coin(&bank, &incentivization_fee);
-            helper
-                .incentivize(
-                    &bank,
-                    &lp_token,
-                    schedule.clone(),
-                    &[incentivization_fee.clone()],
-                )
-                .unwrap();
-        } else {
-            helper
-                .incentivize(&bank, &lp_token, schedule.clone(), &[])
-                .unwrap();
-        }
-    }
-
-    // Incentivize with ASTRO
-    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
-    helper.set_tokens_per_second(100).unwrap();
-
-    let time_before_claims = helper.app.block_info().time.seconds();
+    let reward_cw20 = helper.init_cw20("reward", Some(18));
+    let reward_asset_info = AssetInfo::cw20(reward_cw20);
+    let reward = reward_asset_info.with_balance(1000_000000000000000000u128);
+    helper.mint_assets(&bank, &[reward.clone()]);
 
-    // Shift time by 15 days
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 1).unwrap();
+    helper.mint_coin(&bank, &incentivization_fee);
     helper
-        .app
-        .update_block(|block| block.time = block.time.plus_seconds(15 * 86400));
+        .incentivize(&bank, &lp_token, schedule, &[incentivization_fee.clone()])
+        .unwrap();
 
+    helper.app.update_block(|block| {
+        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts + 86400)
+    });
     helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
 
-    let time_now = helper.app.block_info().time.seconds();
-    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    let total_lp = helper.pool_info(&lp_token).unwrap().total_lp;
+    let expected_index = internal_sch.rps * Decimal256::from_ratio(86400u64, 1u8)
+        / Decimal256::from_ratio(total_lp, 1u8);
+    let reward_info = helper
+        .query_reward_info(&lp_token)
+        .into_iter()
+        .find(|info| info.reward.asset_info() == &reward_asset_info)
+        .unwrap();
+    assert_eq!(reward_info.index, expected_index);
+
+    // The user is the only staker and receives the whole day of rewards
+    let reward_balance = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
     assert_eq!(
-        astro_reward_balance.u128(),
-        u128::from(time_now - time_before_claims) * 100
+        reward_balance.u128(),
+        dec256_to_u128_floor(expected_index * Decimal256::from_ratio(total_lp, 1u8))
     );
+    let day_rewards =
+        dec256_to_u128_floor(internal_sch.rps * Decimal256::from_ratio(86400u64, 1u8));
+    assert!(day_rewards - reward_balance.u128() <= 1);
+
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts));
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let reward_balance = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+    assert!(reward.amount - reward_balance < Uint128::new(1_000000));
 }
 
 #[test]
-fn test_astro_external_reward() {
+fn test_large_incentives() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
-
     let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
@@ -1154,7 +1198,7 @@ fn test_astro_external_reward() {
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just to make following calculations easier
+    // Owner provides liquidity first just make following calculations easier
     // since first depositor gets small cut of LP tokens
     helper
         .provide_liquidity(
@@ -1165,75 +1209,47 @@ fn test_astro_external_reward() {
         )
         .unwrap();
 
-    // Incentivize with ASTRO
-    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
-    helper.set_tokens_per_second(100).unwrap();
-
-    let active_pools = helper.active_pools();
-    assert_eq!(active_pools, vec![(lp_token.clone(), 100u8.into())]);
-
-    // Setup external rewards: 2 equal external ASTRO rewards that must be summed up
-    let bank = TestAddr::new("bank");
-    let reward = astro.with_balance(2u128 * 7 * 86400 * 25); // 25 uastro per second
-    let (schedule, internal_sch) = helper.create_schedule(&reward, 1).unwrap();
-    helper.mint_assets(&bank, &[reward.clone()]);
-    helper
-        .incentivize(&bank, &lp_token, schedule.clone(), &[])
-        .unwrap();
-    helper.mint_assets(&bank, &[reward.clone()]);
-    helper
-        .incentivize(&bank, &lp_token, schedule.clone(), &[])
-        .unwrap();
-
-    // Prepare user's liquidity
     let user = TestAddr::new("user");
     helper
         .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    let time_before_claims = helper.app.block_info().time.seconds();
-
-    helper.next_block(86400);
-
-    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
-    assert_eq!(astro_reward_balance.u128(), 86400 * (100 + 50));
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = AssetInfo::native("reward");
+    let reward = reward_asset_info.with_balance(2839081665193567584256u128);
+    helper.mint_assets(&bank, &[reward.clone()]);
 
-    // Iterate till the end of the schedule by 1 day and claim rewards
-    loop {
-        let pending = helper.query_pending_rewards(&user, &lp_token);
-        let bal_before = helper.snapshot_balances(&user, &pending);
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 1).unwrap();
+    helper.mint_coin(&bank, &incentivization_fee);
+    helper
+        .incentivize(
+            &bank,
+            &lp_token,
+            schedule.clone(),
+            &[incentivization_fee.clone()],
+        )
+        .unwrap();
 
+    // Test claims between short periods
+    for _ in 0..10 {
+        helper.next_block(100);
         helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-
-        let bal_after = helper.snapshot_balances(&user, &pending);
-        assert_rewards(&bal_before, &bal_after, &pending);
-
-        if helper.app.block_info().time.seconds() > internal_sch.end_ts {
-            break;
-        } else {
-            helper.next_block(86400);
-        }
     }
 
-    let time_now = helper.app.block_info().time.seconds();
-    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
-    assert_eq!(
-        astro_reward_balance.u128(),
-        u128::from(time_now - time_before_claims) * 100 // protocol rewards
-            + u128::from(internal_sch.end_ts - time_before_claims) * 50 // external rewards
-    );
-}
-
-#[test]
-fn test_astro_protocol_reward_if_denom_changed() {
-    let astro = native_asset_info("ibc/old_cw20_astro".to_string());
-    let mut helper = Helper::new("owner", &astro, true).unwrap();
+    // Jump to the end of the schedule
     helper
         .app
-        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
+        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts));
+
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+}
 
+#[test]
+fn test_multiple_schedules_same_reward() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
@@ -1243,7 +1259,7 @@ fn test_astro_protocol_reward_if_denom_changed() {
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just to make following calculations easier
+    // Owner provides liquidity first just make following calculations easier
     // since first depositor gets small cut of LP tokens
     helper
         .provide_liquidity(
@@ -1258,55 +1274,56 @@ fn test_astro_protocol_reward_if_denom_changed() {
     helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
     helper.set_tokens_per_second(100).unwrap();
 
-    // Prepare user's liquidity
     let user = TestAddr::new("user");
     helper
         .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    let time_before_claims = helper.app.block_info().time.seconds();
-
-    let cycle_end = helper.app.block_info().time.seconds() + 86400 * 7;
-
-    // Iterate one week by 1 day and claim rewards
-    loop {
-        let pending = helper.query_pending_rewards(&user, &lp_token);
-        let bal_before = helper.snapshot_balances(&user, &pending);
-
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-
-        let bal_after = helper.snapshot_balances(&user, &pending);
-        assert_rewards(&bal_before, &bal_after, &pending);
-
-        if helper.app.block_info().time.seconds() > cycle_end {
-            break;
-        } else {
-            helper.next_block(86400);
-        }
-    }
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = AssetInfo::native("reward");
+    let reward = reward_asset_info.with_balance(1000_000000u128);
 
-    let new_astro = native_asset_info("new_astro".to_string());
+    // Create multiple overlapping schedules with the same reward token starting right away
+    let schedules: Vec<_> = (1..=5)
+        .into_iter()
+        .map(|i| helper.create_schedule(&reward, i).unwrap())
+        .collect();
+    for (ind, (schedule, _)) in schedules.iter().enumerate() {
+        helper.mint_assets(&bank, &[reward.clone()]);
+        if ind == 0 {
+            // attach incentivization fee on the first schedule
+            helper.mint_coin(&bank, &incentivization_fee);
+            helper
+                .incentivize(
+                    &bank,
+                    &lp_token,
+                    schedule.clone(),
+                    &[incentivization_fee.clone()],
+                )
+                .unwrap();
+        } else {
+            helper
+                .incentivize(&bank, &lp_token, schedule.clone(), &[])
+                .unwrap();
+        }
+    }
 
-    // Set new astro token. It replaces old astro token for all active pools
-    let msg = ExecuteMsg::UpdateConfig {
-        astro_token: Some(new_astro.clone()),
-        vesting_contract: None,
-        generator_controller: None,
-        guardian: None,
-        incentivization_fee_info: None,
-        token_transfer_gas_limit: None,
-    };
+    let time_before_claims = helper.app.block_info().time.seconds();
+
+    // Rewards started right away
     helper
         .app
-        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
+        .update_block(|block| block.time = block.time.plus_seconds(86400));
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let reward_balance = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
         .unwrap();
+    assert_eq!(reward_balance.u128(), 207_189296);
+    // And received ASTRO rewards
+    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    assert_eq!(astro_reward_balance.u128(), 86400 * 100);
 
-    // migrate vesting contract with new astro denom; convert all astro to astro2 under the hood
-    helper.migrate_vesting(&new_astro.to_string()).unwrap();
-
-    let cycle_end = helper.app.block_info().time.seconds() + 86400 * 7;
-
-    // Iterate one more week by 1 day and claim rewards (should be in new ASTRO)
+    // Iterate till the end of the longest schedule by 1 day and claim rewards
     loop {
         let pending = helper.query_pending_rewards(&user, &lp_token);
         let bal_before = helper.snapshot_balances(&user, &pending);
@@ -1316,320 +1333,1932 @@ fn test_astro_protocol_reward_if_denom_changed() {
         let bal_after = helper.snapshot_balances(&user, &pending);
         assert_rewards(&bal_before, &bal_after, &pending);
 
-        if helper.app.block_info().time.seconds() > cycle_end {
+        if helper.app.block_info().time.seconds() > schedules.last().cloned().unwrap().1.end_ts {
             break;
         } else {
-            helper.next_block(86400);
+            helper.next_block(86400)
         }
     }
 
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let reward_balance = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+    // A small amount of reward is lost due to rounding
+    assert_eq!(reward_balance.u128(), 4999_999972);
+
     let time_now = helper.app.block_info().time.seconds();
     let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
-    let new_astro_reward_balance = new_astro.query_pool(&helper.app.wrap(), &user).unwrap();
     assert_eq!(
-        astro_reward_balance.u128() + new_astro_reward_balance.u128(),
+        astro_reward_balance.u128(),
         u128::from(time_now - time_before_claims) * 100
     );
 }
 
 #[test]
-fn test_blocked_tokens() {
-    let astro = native_asset_info("ibc/old_cw20_astro".to_string());
+fn test_astro_can_bypass_rewards_limit() {
+    let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let owner = helper.owner.clone();
-    let guardian = TestAddr::new("guardian");
-
-    let tokens = [
-        AssetInfo::native("usd"),
-        AssetInfo::native("foo"),
-        AssetInfo::native("blk"),
-        AssetInfo::native("bar"),
-    ];
-    let norm_pair1_info = helper
-        .create_pair(&[tokens[0].clone(), tokens[1].clone()])
-        .unwrap();
-    let norm_pair2_info = helper
-        .create_pair(&[tokens[0].clone(), tokens[3].clone()])
-        .unwrap();
+    let incentivization_fee = helper.incentivization_fee.clone();
 
-    // Check general validation
-    let err = helper
-        .block_tokens(&guardian, &[astro.clone()])
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        format!(
-            "Generic error: Blocking ASTRO token {} is prohibited",
-            &astro
-        )
-    );
-    let err = helper
-        .block_tokens(&TestAddr::new("random"), &[tokens[2].clone()])
-        .unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::Unauthorized {}
-    );
-    let err = helper
-        .unblock_tokens(&owner, &[tokens[2].clone()])
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        format!(
-            "Generic error: Token {} wasn't found in the blocked list",
-            &tokens[2]
-        )
-    );
+    let lp_token = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("bar")])
+        .unwrap()
+        .liquidity_token
+        .to_string();
 
-    let err = helper
-        .update_blocklist(&owner, &[tokens[2].clone(), tokens[2].clone()], &[])
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Duplicated tokens found"
-    );
+    let bank = TestAddr::new("bank");
 
-    let err = helper
-        .update_blocklist(&owner, &[], &[tokens[0].clone(), tokens[0].clone()])
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Duplicated tokens found"
-    );
+    let schedules: Vec<_> = (1..=MAX_REWARD_TOKENS)
+        .into_iter()
+        .map(|i| {
+            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
+            let reward = reward_asset_info.with_balance(1000_000000u128);
+            helper.create_schedule(&reward, 1).unwrap()
+        })
+        .collect();
+    // Create multiple schedules with different rewards (starts on the next week)
+    for (schedule, _) in &schedules {
+        helper.mint_assets(&bank, &[schedule.reward.clone()]);
+        helper.mint_coin(&bank, &incentivization_fee);
+        helper
+            .incentivize(
+                &bank,
+                &lp_token,
+                schedule.clone(),
+                &[incentivization_fee.clone()],
+            )
+            .unwrap();
+    }
 
-    let err = helper
-        .update_blocklist(
-            &owner,
-            &[tokens[0].clone()],
-            &[tokens[0].clone(), tokens[1].clone()],
+    // ASTRO can always be added no matter what MAX_REWARD_TOKENS limit is
+    let astro_reward = astro.with_balance(1000_000000u128);
+    helper.mint_assets(&bank, &[astro_reward.clone()]);
+    helper
+        .incentivize(
+            &bank,
+            &lp_token,
+            InputSchedule {
+                reward: astro_reward.clone(),
+                duration_periods: 1,
+            },
+            &[],
         )
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Duplicated tokens found"
-    );
+        .unwrap();
+}
 
-    // Block 'blk' token
-    helper.block_tokens(&owner, &[tokens[2].clone()]).unwrap();
+#[test]
+fn test_multiple_schedules_different_reward() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
 
-    let blocked = helper.blocked_tokens();
-    assert_eq!(blocked[0], tokens[2]);
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
 
-    let err = helper
-        .block_tokens(&owner, &[tokens[2].clone()])
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        format!(
-            "Generic error: Token {} is already in the blocked list",
-            &tokens[2]
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
         )
-    );
-
-    // Create pair with blocked token 'blk' and stake in incentives contract.
-    // Incentives should allow it.
-    let blk_pair_info = helper
-        .create_pair(&[tokens[0].clone(), tokens[2].clone()])
         .unwrap();
 
-    // Try to add ASTRO emissions to the 'blk' pair
-    let err = helper
-        .setup_pools(vec![
-            (norm_pair1_info.liquidity_token.to_string(), 1),
-            (norm_pair2_info.liquidity_token.to_string(), 1),
-            (blk_pair_info.liquidity_token.to_string(), 1),
-        ])
-        .unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::BlockedToken {
-            token: tokens[2].to_string()
-        }
-    );
-
-    // Activate allowed pairs
-    helper
-        .setup_pools(vec![
-            (norm_pair1_info.liquidity_token.to_string(), 1),
-            (norm_pair2_info.liquidity_token.to_string(), 1),
-        ])
-        .unwrap();
+    // Incentivize with ASTRO
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
     helper.set_tokens_per_second(100).unwrap();
 
-    helper.next_block(1000);
-
-    // Unblock 'blk' token and remove norm_pair1 from active set
-    helper.unblock_tokens(&owner, &[tokens[2].clone()]).unwrap();
+    let user = TestAddr::new("user");
     helper
-        .setup_pools(vec![
-            (blk_pair_info.liquidity_token.to_string(), 1),
-            (norm_pair2_info.liquidity_token.to_string(), 1),
-        ])
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    // For simplicity we have no stakers in this test. However, all rewards are accrued in 'orphaned_rewards'
-    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // 50 astro * 1000 passed seconds
-    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
-    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 0); // This pair was just incentivized in this block
+    let bank = TestAddr::new("bank");
 
-    helper.next_block(1000);
+    let schedules: Vec<_> = (1..=MAX_REWARD_TOKENS)
+        .into_iter()
+        .map(|i| {
+            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
+            let reward = reward_asset_info.with_balance(1000_000000u128);
+            helper.create_schedule(&reward, 2).unwrap()
+        })
+        .collect();
+    // Create multiple schedules with different rewards (starts on the next week)
+    for (schedule, _) in &schedules {
+        helper.mint_assets(&bank, &[schedule.reward.clone()]);
+        helper.mint_coin(&bank, &incentivization_fee);
+        helper
+            .incentivize(
+                &bank,
+                &lp_token,
+                schedule.clone(),
+                &[incentivization_fee.clone()],
+            )
+            .unwrap();
+    }
 
-    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated pool didn't get anything
-    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 2000);
-    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
+    // Can't incentivize with one more reward token
+    let reward_asset_info = AssetInfo::native(format!("reward{}", MAX_REWARD_TOKENS + 1));
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
+    helper.mint_assets(&bank, &[schedule.reward.clone()]);
+    helper.mint_coin(&bank, &incentivization_fee);
+    let err = helper
+        .incentivize(
+            &bank,
+            &lp_token,
+            schedule.clone(),
+            &[incentivization_fee.clone()],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TooManyRewardTokens {
+            lp_token: lp_token.clone()
+        }
+    );
 
-    // Block poor 'blk' token again. It should automatically deactivate blk_pair
+    let time_before_claims = helper.app.block_info().time.seconds();
+
+    // Rewards started right away
     helper
-        .block_tokens(&guardian, &[tokens[2].clone()])
-        .unwrap();
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(86400));
+    for (schedule, _) in &schedules {
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+        let reward_balance = schedule
+            .reward
+            .info
+            .query_pool(&helper.app.wrap(), &user)
+            .unwrap();
+        assert_eq!(reward_balance.u128(), 47_629547);
+    }
+    // And received ASTRO rewards
+    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    assert_eq!(astro_reward_balance.u128(), 86400 * 100);
 
-    helper.next_block(1000);
+    // Iterate till the end of the longest schedule by 1 day and claim rewards
+    loop {
+        let pending = helper.query_pending_rewards(&user, &lp_token);
+        let bal_before = helper.snapshot_balances(&user, &pending);
 
-    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated pool didn't get anything
-    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+        let bal_after = helper.snapshot_balances(&user, &pending);
+        assert_rewards(&bal_before, &bal_after, &pending);
+
+        if helper.app.block_info().time.seconds() > schedules.last().cloned().unwrap().1.end_ts {
+            break;
+        } else {
+            helper
+                .app
+                .update_block(|block| block.time = block.time.plus_seconds(86400));
+        }
+    }
+
+    for (schedule, _) in &schedules {
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+        let reward_balance = schedule
+            .reward
+            .info
+            .query_pool(&helper.app.wrap(), &user)
+            .unwrap();
+        // Total amount is a bit off because of rounding due to Decimal256 type
+        assert_eq!(
+            reward_balance.u128(),
+            999_999980,
+            "Balance for {} is wrong",
+            schedule.reward.info
+        );
+    }
+
+    let time_now = helper.app.block_info().time.seconds();
+    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
     assert_eq!(
-        dec256_to_u128_floor(reward_info[0].orphaned),
-        50 * 2000 + 100 * 1000
-    ); // this pools is the only active atm
-    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated blk pair didn't get anything
+        astro_reward_balance.u128(),
+        u128::from(time_now - time_before_claims) * 100
+    );
 }
 
 #[test]
-fn test_blocked_pair_types() {
+fn test_incentivize_many() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
 
-    let tokens = [
-        AssetInfo::native("usd"),
-        AssetInfo::native("foo"),
-        AssetInfo::native("bar"),
-    ];
-    let norm_pair1_info = helper
-        .create_pair(&[tokens[0].clone(), tokens[1].clone()])
-        .unwrap();
-    let norm_pair2_info = helper
-        .create_pair(&[tokens[0].clone(), tokens[2].clone()])
-        .unwrap();
-    let blk_pair_info = helper.create_stable_pair(&[tokens[1].clone(), tokens[2].clone()]);
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
 
-    // Activate all pairs. blk pair is not blocked yet
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
     helper
-        .setup_pools(vec![
-            (norm_pair1_info.liquidity_token.to_string(), 1),
-            (norm_pair2_info.liquidity_token.to_string(), 1),
-            (blk_pair_info.liquidity_token.to_string(), 1),
-        ])
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in generator
+        )
         .unwrap();
-    helper.set_tokens_per_second(150).unwrap();
-
-    helper.next_block(1000);
 
-    // Block 'blk' pair
+    let user = TestAddr::new("user");
     helper
-        .block_pair_type(&owner, blk_pair_info.pair_type.clone())
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    // For simplicity we have no stakers in this test. However, all rewards are accrued in 'orphaned_rewards'
-    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // 50 astro * 1000 passed seconds
-    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
-    // Although this pair is blocked, it still gets rewards until manually deactivated
-    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
+    let bank = TestAddr::new("bank");
 
-    // Deactivate 'blk' pair
-    helper.deactivate_blocked().unwrap();
+    let schedules: Vec<_> = (1..=MAX_REWARD_TOKENS)
+        .into_iter()
+        .map(|i| {
+            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
+            let reward = reward_asset_info.with_balance(1000_000000u128);
+            helper.create_schedule(&reward, 2).unwrap()
+        })
+        .collect();
 
-    helper.next_block(1000);
+    // Create multiple schedules with different rewards (starts on the next week)
+    let incentives = schedules
+        .iter()
+        .map(|(schedule, _)| {
+            helper.mint_assets(&bank, &[schedule.reward.clone()]);
+            helper.mint_coin(&bank, &incentivization_fee);
+            (lp_token.as_str(), schedule.clone())
+        })
+        .collect_vec();
 
-    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
-    assert_eq!(
-        dec256_to_u128_floor(reward_info[0].orphaned),
-        50 * 1000 + 75 * 1000
-    );
-    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+    // Missing incentivize fee
+    let err = helper
+        .incentivize_many(&bank, incentives.clone(), &[])
+        .unwrap_err();
     assert_eq!(
-        dec256_to_u128_floor(reward_info[0].orphaned),
-        50 * 1000 + 75 * 1000
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncentivizationFeeExpected {
+            fee: incentivization_fee.to_string(),
+            lp_token: lp_token.clone(),
+            new_reward_token: schedules[0].0.reward.info.to_string(),
+        }
     );
-    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated blk pair didn't get anything
 
-    // Next time setup pool won't allow to activate 'blk' pair
+    // Add random coins
+    let random_coin = coin(1000, "random");
+    helper.mint_coin(&bank, &random_coin);
     let err = helper
-        .setup_pools(vec![
-            (norm_pair1_info.liquidity_token.to_string(), 1),
-            (norm_pair2_info.liquidity_token.to_string(), 1),
-            (blk_pair_info.liquidity_token.to_string(), 1),
-        ])
+        .incentivize_many(
+            &bank,
+            incentives.clone(),
+            &[
+                coin(
+                    incentivization_fee.amount.u128() * schedules.len() as u128,
+                    &incentivization_fee.denom,
+                ),
+                random_coin,
+            ],
+        )
         .unwrap_err();
     assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::BlockedPairType {
-            pair_type: blk_pair_info.pair_type.clone()
-        }
+        err.root_cause().to_string(),
+        "Generic error: Supplied coins contain random that is not in the input asset vector"
     );
 
-    // All subsequent deactivate_blocked calls will do nothing
-    helper.deactivate_blocked().unwrap();
+    helper
+        .incentivize_many(
+            &bank,
+            incentives,
+            &coins(
+                incentivization_fee.amount.u128() * schedules.len() as u128,
+                &incentivization_fee.denom,
+            ),
+        )
+        .unwrap();
 
-    // Lets check factory deactivation logic
-    // Only factory can deactivate pair
+    // Can't incentivize with one more reward token
+    let reward_asset_info = AssetInfo::native(format!("reward{}", MAX_REWARD_TOKENS + 1));
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
+    helper.mint_assets(&bank, &[schedule.reward.clone()]);
+    helper.mint_coin(&bank, &incentivization_fee);
+    let err = helper
+        .incentivize(
+            &bank,
+            &lp_token,
+            schedule.clone(),
+            &[incentivization_fee.clone()],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TooManyRewardTokens {
+            lp_token: lp_token.clone()
+        }
+    );
+
+    // Rewards started right away
+    helper
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(86400));
+    for (schedule, _) in &schedules {
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+        let reward_balance = schedule
+            .reward
+            .info
+            .query_pool(&helper.app.wrap(), &user)
+            .unwrap();
+        assert_eq!(reward_balance.u128(), 47_629547);
+    }
+
+    // Iterate till the end of the longest schedule by 1 day and claim rewards
+    loop {
+        let pending = helper.query_pending_rewards(&user, &lp_token);
+        let bal_before = helper.snapshot_balances(&user, &pending);
+
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+        let bal_after = helper.snapshot_balances(&user, &pending);
+        assert_rewards(&bal_before, &bal_after, &pending);
+
+        if helper.app.block_info().time.seconds() > schedules.last().cloned().unwrap().1.end_ts {
+            break;
+        } else {
+            helper
+                .app
+                .update_block(|block| block.time = block.time.plus_seconds(86400));
+        }
+    }
+
+    for (schedule, _) in &schedules {
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+        let reward_balance = schedule
+            .reward
+            .info
+            .query_pool(&helper.app.wrap(), &user)
+            .unwrap();
+        // Total amount is a bit off because of rounding due to Decimal256 type
+        assert_eq!(
+            reward_balance.u128(),
+            999_999980,
+            "Balance for {} is wrong",
+            schedule.reward.info
+        );
+    }
+}
+
+#[test]
+fn test_claim_between_different_periods() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
+        )
+        .unwrap();
+
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    // External incentives
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = AssetInfo::native("reward");
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+
+    // Create multiple overlapping schedules with the same reward token starting right away
+    let schedules: Vec<_> = (1..=2)
+        .into_iter()
+        .map(|i| helper.create_schedule(&reward, i).unwrap())
+        .collect();
+    for (ind, (schedule, _)) in schedules.iter().enumerate() {
+        helper.mint_assets(&bank, &[reward.clone()]);
+        if ind == 0 {
+            // attach incentivization fee on the first schedule
+            helper.mint_coin(&bank, &incentivization_fee);
+            helper
+                .incentivize(
+                    &bank,
+                    &lp_token,
+                    schedule.clone(),
+                    &[incentivization_fee.clone()],
+                )
+                .unwrap();
+        } else {
+            helper
+                .incentivize(&bank, &lp_token, schedule.clone(), &[])
+                .unwrap();
+        }
+    }
+
+    // Incentivize with ASTRO
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
+
+    let time_before_claims = helper.app.block_info().time.seconds();
+
+    // Shift time by 15 days
+    helper
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(15 * 86400));
+
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+    let time_now = helper.app.block_info().time.seconds();
+    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    assert_eq!(
+        astro_reward_balance.u128(),
+        u128::from(time_now - time_before_claims) * 100
+    );
+}
+
+#[test]
+fn test_astro_external_reward() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
+
+    let owner = helper.owner.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
+        )
+        .unwrap();
+
+    // Incentivize with ASTRO
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
+
+    let active_pools = helper.active_pools();
+    assert_eq!(active_pools, vec![(lp_token.clone(), 100u8.into())]);
+
+    // Setup external rewards: 2 equal external ASTRO rewards that must be summed up
+    let bank = TestAddr::new("bank");
+    let reward = astro.with_balance(2u128 * 7 * 86400 * 25); // 25 uastro per second
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 1).unwrap();
+    helper.mint_assets(&bank, &[reward.clone()]);
+    helper
+        .incentivize(&bank, &lp_token, schedule.clone(), &[])
+        .unwrap();
+    helper.mint_assets(&bank, &[reward.clone()]);
+    helper
+        .incentivize(&bank, &lp_token, schedule.clone(), &[])
+        .unwrap();
+
+    // Prepare user's liquidity
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    let time_before_claims = helper.app.block_info().time.seconds();
+
+    helper.next_block(86400);
+
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    assert_eq!(astro_reward_balance.u128(), 86400 * (100 + 50));
+
+    // Iterate till the end of the schedule by 1 day and claim rewards
+    loop {
+        let pending = helper.query_pending_rewards(&user, &lp_token);
+        let bal_before = helper.snapshot_balances(&user, &pending);
+
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+        let bal_after = helper.snapshot_balances(&user, &pending);
+        assert_rewards(&bal_before, &bal_after, &pending);
+
+        if helper.app.block_info().time.seconds() > internal_sch.end_ts {
+            break;
+        } else {
+            helper.next_block(86400);
+        }
+    }
+
+    let time_now = helper.app.block_info().time.seconds();
+    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    assert_eq!(
+        astro_reward_balance.u128(),
+        u128::from(time_now - time_before_claims) * 100 // protocol rewards
+            + u128::from(internal_sch.end_ts - time_before_claims) * 50 // external rewards
+    );
+}
+
+#[test]
+fn test_astro_protocol_reward_if_denom_changed() {
+    let astro = native_asset_info("ibc/old_cw20_astro".to_string());
+    let mut helper = Helper::new("owner", &astro, true).unwrap();
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
+
+    let owner = helper.owner.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
+        )
+        .unwrap();
+
+    // Incentivize with ASTRO
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
+
+    // Prepare user's liquidity
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    let time_before_claims = helper.app.block_info().time.seconds();
+
+    let cycle_end = helper.app.block_info().time.seconds() + 86400 * 7;
+
+    // Iterate one week by 1 day and claim rewards
+    loop {
+        let pending = helper.query_pending_rewards(&user, &lp_token);
+        let bal_before = helper.snapshot_balances(&user, &pending);
+
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+        let bal_after = helper.snapshot_balances(&user, &pending);
+        assert_rewards(&bal_before, &bal_after, &pending);
+
+        if helper.app.block_info().time.seconds() > cycle_end {
+            break;
+        } else {
+            helper.next_block(86400);
+        }
+    }
+
+    let new_astro = native_asset_info("new_astro".to_string());
+
+    // Set new astro token. It replaces old astro token for all active pools
+    let msg = ExecuteMsg::UpdateConfig {
+        astro_token: Some(new_astro.clone()),
+        vesting_contract: None,
+        generator_controller: None,
+        guardian: None,
+        incentivization_fee_info: None,
+        token_transfer_gas_limit: None,
+    };
+    helper
+        .app
+        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
+        .unwrap();
+
+    // migrate vesting contract with new astro denom; convert all astro to astro2 under the hood
+    helper.migrate_vesting(&new_astro.to_string()).unwrap();
+
+    let cycle_end = helper.app.block_info().time.seconds() + 86400 * 7;
+
+    // Iterate one more week by 1 day and claim rewards (should be in new ASTRO)
+    loop {
+        let pending = helper.query_pending_rewards(&user, &lp_token);
+        let bal_before = helper.snapshot_balances(&user, &pending);
+
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+        let bal_after = helper.snapshot_balances(&user, &pending);
+        assert_rewards(&bal_before, &bal_after, &pending);
+
+        if helper.app.block_info().time.seconds() > cycle_end {
+            break;
+        } else {
+            helper.next_block(86400);
+        }
+    }
+
+    let time_now = helper.app.block_info().time.seconds();
+    let astro_reward_balance = astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    let new_astro_reward_balance = new_astro.query_pool(&helper.app.wrap(), &user).unwrap();
+    assert_eq!(
+        astro_reward_balance.u128() + new_astro_reward_balance.u128(),
+        u128::from(time_now - time_before_claims) * 100
+    );
+}
+
+#[test]
+fn test_blocked_tokens() {
+    let astro = native_asset_info("ibc/old_cw20_astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let guardian = TestAddr::new("guardian");
+
+    let tokens = [
+        AssetInfo::native("usd"),
+        AssetInfo::native("foo"),
+        AssetInfo::native("blk"),
+        AssetInfo::native("bar"),
+    ];
+    let norm_pair1_info = helper
+        .create_pair(&[tokens[0].clone(), tokens[1].clone()])
+        .unwrap();
+    let norm_pair2_info = helper
+        .create_pair(&[tokens[0].clone(), tokens[3].clone()])
+        .unwrap();
+
+    // Check general validation
+    let err = helper
+        .block_tokens(&guardian, &[astro.clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Generic error: Blocking ASTRO token {} is prohibited",
+            &astro
+        )
+    );
+    let err = helper
+        .block_tokens(&TestAddr::new("random"), &[tokens[2].clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    let err = helper
+        .unblock_tokens(&owner, &[tokens[2].clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Generic error: Token {} wasn't found in the blocked list",
+            &tokens[2]
+        )
+    );
+
+    let err = helper
+        .update_blocklist(&owner, &[tokens[2].clone(), tokens[2].clone()], &[])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Duplicated tokens found"
+    );
+
+    let err = helper
+        .update_blocklist(&owner, &[], &[tokens[0].clone(), tokens[0].clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Duplicated tokens found"
+    );
+
+    let err = helper
+        .update_blocklist(
+            &owner,
+            &[tokens[0].clone()],
+            &[tokens[0].clone(), tokens[1].clone()],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Duplicated tokens found"
+    );
+
+    // Block 'blk' token
+    helper.block_tokens(&owner, &[tokens[2].clone()]).unwrap();
+
+    let blocked = helper.blocked_tokens();
+    assert_eq!(blocked[0], tokens[2]);
+
+    let err = helper
+        .block_tokens(&owner, &[tokens[2].clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Generic error: Token {} is already in the blocked list",
+            &tokens[2]
+        )
+    );
+
+    // Create pair with blocked token 'blk' and stake in incentives contract.
+    // Incentives should allow it.
+    let blk_pair_info = helper
+        .create_pair(&[tokens[0].clone(), tokens[2].clone()])
+        .unwrap();
+
+    // Try to add ASTRO emissions to the 'blk' pair
+    let err = helper
+        .setup_pools(vec![
+            (norm_pair1_info.liquidity_token.to_string(), 1),
+            (norm_pair2_info.liquidity_token.to_string(), 1),
+            (blk_pair_info.liquidity_token.to_string(), 1),
+        ])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockedToken {
+            token: tokens[2].to_string()
+        }
+    );
+
+    // Activate allowed pairs
+    helper
+        .setup_pools(vec![
+            (norm_pair1_info.liquidity_token.to_string(), 1),
+            (norm_pair2_info.liquidity_token.to_string(), 1),
+        ])
+        .unwrap();
+    helper.set_tokens_per_second(100).unwrap();
+
+    helper.next_block(1000);
+
+    // Unblock 'blk' token and remove norm_pair1 from active set
+    helper.unblock_tokens(&owner, &[tokens[2].clone()]).unwrap();
+    helper
+        .setup_pools(vec![
+            (blk_pair_info.liquidity_token.to_string(), 1),
+            (norm_pair2_info.liquidity_token.to_string(), 1),
+        ])
+        .unwrap();
+
+    // For simplicity we have no stakers in this test. However, all rewards are accrued in 'orphaned_rewards'
+    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // 50 astro * 1000 passed seconds
+    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
+    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 0); // This pair was just incentivized in this block
+
+    helper.next_block(1000);
+
+    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated pool didn't get anything
+    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 2000);
+    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
+
+    // Block poor 'blk' token again. It should automatically deactivate blk_pair
+    helper
+        .block_tokens(&guardian, &[tokens[2].clone()])
+        .unwrap();
+
+    helper.next_block(1000);
+
+    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated pool didn't get anything
+    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+    assert_eq!(
+        dec256_to_u128_floor(reward_info[0].orphaned),
+        50 * 2000 + 100 * 1000
+    ); // this pools is the only active atm
+    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated blk pair didn't get anything
+}
+
+#[test]
+fn test_blocked_tokens_fallback_pool() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+
+    let tokens = [
+        AssetInfo::native("usd"),
+        AssetInfo::native("foo"),
+        AssetInfo::native("blk"),
+    ];
+    let norm_pair_info = helper
+        .create_pair(&[tokens[0].clone(), tokens[1].clone()])
+        .unwrap();
+    let blk_pair_info = helper
+        .create_pair(&[tokens[0].clone(), tokens[2].clone()])
+        .unwrap();
+    let norm_lp = norm_pair_info.liquidity_token.to_string();
+    let blk_lp = blk_pair_info.liquidity_token.to_string();
+
+    helper
+        .setup_pools(vec![(norm_lp.clone(), 1), (blk_lp.clone(), 3)])
+        .unwrap();
+    helper.set_tokens_per_second(100).unwrap();
+
+    let err = helper
+        .set_fallback_pool(&TestAddr::new("random"), Some(&norm_lp))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    helper.set_fallback_pool(&owner, Some(&norm_lp)).unwrap();
+    assert_eq!(
+        helper.query_fallback_pool(),
+        Some(AssetInfo::native(&norm_lp))
+    );
+
+    let total_alloc_points = helper.query_config().total_alloc_points;
+    assert_eq!(total_alloc_points.u128(), 4);
+
+    // Blocking 'blk' deactivates blk_pair and moves its alloc points to the fallback pool
+    helper.block_tokens(&owner, &[tokens[2].clone()]).unwrap();
+
+    assert_eq!(
+        helper.active_pools(),
+        vec![(norm_lp.clone(), Uint128::new(4))]
+    );
+    assert_eq!(helper.query_config().total_alloc_points, total_alloc_points);
+
+    helper.next_block(1000);
+
+    // The fallback pool receives the whole emission
+    let reward_info = helper.query_reward_info(&norm_lp);
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 100 * 1000);
+
+    // Without the fallback pool alloc points are removed as usual
+    helper.set_fallback_pool(&owner, None).unwrap();
+    assert_eq!(helper.query_fallback_pool(), None);
+
+    helper.unblock_tokens(&owner, &[tokens[2].clone()]).unwrap();
+    helper
+        .setup_pools(vec![(norm_lp.clone(), 1), (blk_lp.clone(), 3)])
+        .unwrap();
+    helper.block_tokens(&owner, &[tokens[2].clone()]).unwrap();
+
+    assert_eq!(helper.active_pools(), vec![(norm_lp, Uint128::new(1))]);
+    assert_eq!(helper.query_config().total_alloc_points.u128(), 1);
+}
+
+#[test]
+fn test_blocked_pair_types() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+
+    let tokens = [
+        AssetInfo::native("usd"),
+        AssetInfo::native("foo"),
+        AssetInfo::native("bar"),
+    ];
+    let norm_pair1_info = helper
+        .create_pair(&[tokens[0].clone(), tokens[1].clone()])
+        .unwrap();
+    let norm_pair2_info = helper
+        .create_pair(&[tokens[0].clone(), tokens[2].clone()])
+        .unwrap();
+    let blk_pair_info = helper.create_stable_pair(&[tokens[1].clone(), tokens[2].clone()]);
+
+    // Activate all pairs. blk pair is not blocked yet
+    helper
+        .setup_pools(vec![
+            (norm_pair1_info.liquidity_token.to_string(), 1),
+            (norm_pair2_info.liquidity_token.to_string(), 1),
+            (blk_pair_info.liquidity_token.to_string(), 1),
+        ])
+        .unwrap();
+    helper.set_tokens_per_second(150).unwrap();
+
+    helper.next_block(1000);
+
+    // Block 'blk' pair
+    helper
+        .block_pair_type(&owner, blk_pair_info.pair_type.clone())
+        .unwrap();
+
+    // For simplicity we have no stakers in this test. However, all rewards are accrued in 'orphaned_rewards'
+    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // 50 astro * 1000 passed seconds
+    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
+    // Although this pair is blocked, it still gets rewards until manually deactivated
+    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000);
+
+    // Deactivate 'blk' pair
+    helper.deactivate_blocked().unwrap();
+
+    helper.next_block(1000);
+
+    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
+    assert_eq!(
+        dec256_to_u128_floor(reward_info[0].orphaned),
+        50 * 1000 + 75 * 1000
+    );
+    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+    assert_eq!(
+        dec256_to_u128_floor(reward_info[0].orphaned),
+        50 * 1000 + 75 * 1000
+    );
+    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated blk pair didn't get anything
+
+    // Next time setup pool won't allow to activate 'blk' pair
+    let err = helper
+        .setup_pools(vec![
+            (norm_pair1_info.liquidity_token.to_string(), 1),
+            (norm_pair2_info.liquidity_token.to_string(), 1),
+            (blk_pair_info.liquidity_token.to_string(), 1),
+        ])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockedPairType {
+            pair_type: blk_pair_info.pair_type.clone()
+        }
+    );
+
+    // All subsequent deactivate_blocked calls will do nothing
+    helper.deactivate_blocked().unwrap();
+
+    // Lets check factory deactivation logic
+    // Only factory can deactivate pair
+    let err = helper
+        .deactivate_pool(&owner, norm_pair1_info.liquidity_token.as_str())
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Deactivate norm_pair1_info by its asset infos
+    helper
+        .deactivate_pool_full_flow(&[tokens[0].clone(), tokens[1].clone()])
+        .unwrap();
+
+    let err = helper
+        .setup_pools(vec![
+            (norm_pair1_info.liquidity_token.to_string(), 1),
+            (norm_pair2_info.liquidity_token.to_string(), 1),
+        ])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Generic error: The pair is not registered: {}-{}",
+            &tokens[0], &tokens[1]
+        )
+    );
+
+    helper.next_block(1000);
+
+    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
+    assert_eq!(
+        dec256_to_u128_floor(reward_info[0].orphaned),
+        50 * 1000 + 75 * 1000 // deactivated pool gets nothing
+    );
+    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
+    assert_eq!(
+        dec256_to_u128_floor(reward_info[0].orphaned),
+        50 * 1000 + 75 * 1000 + 150 * 1000
+    );
+    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
+    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated blk pair still gets nothing
+}
+
+#[test]
+fn test_incentives_with_blocked() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+
+    // Try to incentivize with blocked token
+    let bank = TestAddr::new("bank");
+    let blocked_token = AssetInfo::native("blocked_reward");
+    helper
+        .block_tokens(&owner, &[blocked_token.clone()])
+        .unwrap();
+    let reward = blocked_token.with_balance(1000_000000u128);
+    helper.mint_assets(&bank, &[reward.clone()]);
+
+    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
+    helper.mint_coin(&bank, &incentivization_fee);
+    let err = helper
+        .incentivize(
+            &bank,
+            pair_info.liquidity_token.as_str(),
+            schedule.clone(),
+            &[incentivization_fee.clone()],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockedToken {
+            token: blocked_token.to_string()
+        }
+    );
+}
+
+#[test]
+fn test_remove_rewards() {
+    let astro = native_asset_info("astro".to_string());
+
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
+
+    let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
+        )
+        .unwrap();
+
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = AssetInfo::native("reward");
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+    let (schedule, _) = helper.create_schedule(&reward, 1).unwrap();
+
+    helper.mint_assets(&bank, &[reward.clone()]);
+    helper.mint_coin(&bank, &incentivization_fee);
+
+    helper
+        .incentivize(
+            &bank,
+            &lp_token,
+            schedule.clone(),
+            &[incentivization_fee.clone()],
+        )
+        .unwrap();
+
+    // 5 days
+    for _ in 0..5 {
+        helper.next_block(86400);
+
+        let pending = helper.query_pending_rewards(&user, &lp_token);
+        let bal_before = helper.snapshot_balances(&user, &pending);
+
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+        let bal_after = helper.snapshot_balances(&user, &pending);
+        assert_rewards(&bal_before, &bal_after, &pending);
+    }
+
+    // Assume 1 day passed and then reward gets deregistered
+    helper.next_block(86400);
+
+    let receiver = TestAddr::new("receiver");
+
+    // Only owner is able to remove reward
+    let err = helper
+        .remove_reward(
+            &TestAddr::new("random"),
+            &lp_token,
+            &reward_asset_info.to_string(),
+            false,
+            &receiver,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    helper
+        .remove_reward(
+            &owner,
+            &lp_token,
+            &reward_asset_info.to_string(),
+            false,
+            &receiver,
+        )
+        .unwrap();
+
+    // User must be allowed to claim rewards for the last 1 day
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+    let outstanding = reward_asset_info
+        .query_pool(&helper.app.wrap(), &receiver)
+        .unwrap()
+        .u128();
+    let claimed = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap()
+        .u128();
+    assert_eq!(outstanding, 571_428571); // ~ 8 * 71428571 (8 days left)
+    assert_eq!(claimed, 428_571426); // // claimed 6 days in a row ~ 6 * 71428571
+    assert_eq!(outstanding + claimed, 999_999997); // ~ initial reward amount i.e. 1000_000000
+}
+
+#[test]
+fn test_long_unclaimed_rewards() {
+    let astro = native_asset_info("astro".to_string());
+
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
+
+    let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
+        )
+        .unwrap();
+
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    let bank = TestAddr::new("bank");
+
+    let schedules: Vec<_> = (1..=2)
+        .into_iter()
+        .map(|i| {
+            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
+            let reward = reward_asset_info.with_balance(50_000_000000u128);
+            // Create 2 schedules with different duration
+            [
+                helper.create_schedule(&reward, 20).unwrap(),
+                helper.create_schedule(&reward, 10).unwrap(),
+            ]
+        })
+        .flatten()
+        .collect();
+    let max_end = schedules.iter().map(|(_, sch)| sch.end_ts).max().unwrap();
+    // Create multiple schedules with different rewards (starts on the next week)
+    for (ind, (schedule, _)) in schedules.iter().enumerate() {
+        helper.mint_assets(&bank, &[schedule.reward.clone()]);
+        let mut attach_funds = vec![];
+        if ind % 2 == 0 {
+            helper.mint_coin(&bank, &incentivization_fee);
+            attach_funds = vec![incentivization_fee.clone()]
+        }
+        helper
+            .incentivize(&bank, &lp_token, schedule.clone(), &attach_funds)
+            .unwrap();
+    }
+
+    // Start from the starting point and jump over 5 weeks
+    helper.app.update_block(|block| {
+        block.time = Timestamp::from_seconds(schedules[0].1.next_epoch_start_ts + 86400 * 7 * 5);
+    });
+
+    // Deregister reward1
+    let receiver = TestAddr::new("receiver");
+    helper
+        .remove_reward(
+            &owner,
+            &lp_token,
+            &schedules[0].0.reward.info.to_string(),
+            false,
+            &receiver,
+        )
+        .unwrap();
+    let deregister_amount = schedules[0]
+        .0
+        .reward
+        .info
+        .query_pool(&helper.app.wrap(), &receiver)
+        .unwrap()
+        .u128();
+    assert_eq!(deregister_amount, 58441_558441);
+
+    // Iterate till the end of the longest schedule by 1 day and claim rewards
+    loop {
+        let pending = helper.query_pending_rewards(&user, &lp_token);
+        let bal_before = helper.snapshot_balances(&user, &pending);
+
+        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+        let bal_after = helper.snapshot_balances(&user, &pending);
+        assert_rewards(&bal_before, &bal_after, &pending);
+
+        if helper.app.block_info().time.seconds() > max_end {
+            break;
+        } else {
+            helper.next_block(86400);
+        }
+    }
+
+    let claimed_reward1 = schedules[0]
+        .0
+        .reward
+        .info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap()
+        .u128();
+    assert_eq!(deregister_amount + claimed_reward1, 99999_999999);
+
+    for (schedule, _) in schedules.iter().skip(2) {
+        let bal = schedule
+            .reward
+            .info
+            .query_pool(&helper.app.wrap(), &user)
+            .unwrap()
+            .u128();
+        assert_eq!(bal, 99_999_999943); // All rewards are claimed
+    }
+}
+
+#[test]
+fn test_queries() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // doesnt stake
+        )
+        .unwrap();
+
+    for i in 0..10 {
+        let user = TestAddr::new(&format!("user_{i}"));
+        helper
+            .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+            .unwrap();
+    }
+
+    let user = TestAddr::new("user_1");
+
+    assert_eq!(helper.query_deposit(&lp_token, &user).unwrap(), 100000);
+
+    let random = TestAddr::new("random");
+    let amount = helper.query_deposit(&lp_token, &random).unwrap();
+    assert_eq!(amount, 0);
+
+    let amount = helper.query_deposit(random.as_str(), &user).unwrap();
+    assert_eq!(amount, 0);
+
+    // This Lp doesn't exist
+    helper.pool_info(random.as_str()).unwrap_err();
+
+    let pool_info = helper.pool_info(&lp_token).unwrap();
+    assert_eq!(pool_info.rewards, []);
+    assert_eq!(pool_info.total_lp.u128(), 1_000_000); // 100_000 per user
+    assert_eq!(
+        pool_info.last_update_ts,
+        helper.app.block_info().time.seconds()
+    );
+
+    let stakers = helper.pool_stakers(&lp_token, None, None);
+    assert_eq!(
+        stakers[5],
+        ("wasm1_user_5".to_string(), Uint128::from(100_000u128))
+    );
+    let total = stakers
+        .iter()
+        .fold(Uint128::zero(), |acc, (_, bal)| acc + bal);
+    assert_eq!(total, pool_info.total_lp);
+
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = AssetInfo::native("reward");
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+
+    // Create multiple overlapping schedules with the same reward token starting right away
+    let schedules: Vec<_> = (1..=5)
+        .into_iter()
+        .map(|i| helper.create_schedule(&reward, i).unwrap())
+        .collect();
+    for (ind, (schedule, _)) in schedules.iter().enumerate() {
+        helper.mint_assets(&bank, &[reward.clone()]);
+        if ind == 0 {
+            // attach incentivization fee on the first schedule
+            helper.mint_coin(&bank, &incentivization_fee);
+            helper
+                .incentivize(
+                    &bank,
+                    &lp_token,
+                    schedule.clone(),
+                    &[incentivization_fee.clone()],
+                )
+                .unwrap();
+        } else {
+            helper
+                .incentivize(&bank, &lp_token, schedule.clone(), &[])
+                .unwrap();
+        }
+    }
+
+    let res = helper
+        .query_ext_reward_schedules(&lp_token, &reward_asset_info, None, None)
+        .unwrap();
+    assert_eq!(
+        res,
+        [
+            ScheduleResponse {
+                rps: Decimal256::from_str("2398.02426572720408957").unwrap(),
+                start_ts: 1696810000,
+                end_ts: 1698019200,
+            },
+            ScheduleResponse {
+                rps: Decimal256::from_str("1571.031212468851459733").unwrap(),
+                start_ts: 1698019200,
+                end_ts: 1698624000,
+            },
+            ScheduleResponse {
+                rps: Decimal256::from_str("1019.76329626157472324").unwrap(),
+                start_ts: 1698624000,
+                end_ts: 1699228800,
+            },
+            ScheduleResponse {
+                rps: Decimal256::from_str("606.335150073382231096").unwrap(),
+                start_ts: 1699228800,
+                end_ts: 1699833600,
+            },
+            ScheduleResponse {
+                rps: Decimal256::from_str("275.603571822290816888").unwrap(),
+                start_ts: 1699833600,
+                end_ts: 1700438400,
+            },
+        ]
+    );
+    let res = helper
+        .query_ext_reward_schedules(&lp_token, &reward_asset_info, None, Some(1))
+        .unwrap();
+    assert_eq!(res.len(), 1);
+    let res = helper
+        .query_ext_reward_schedules(&lp_token, &reward_asset_info, Some(1699228800), None)
+        .unwrap();
+    assert_eq!(res.len(), 2);
+
+    let pools = helper.all_pools();
+    assert_eq!(pools, vec![lp_token.clone()]);
+}
+
+#[test]
+fn test_staked_value() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    let provide_assets = [
+        asset_infos[0].with_balance(100_000u64),
+        asset_infos[1].with_balance(200_000u64),
+    ];
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    let staked = helper.query_deposit(&lp_token, &user).unwrap();
+    assert!(staked > 0);
+
+    let share: Vec<Asset> = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &pair_info.contract_addr,
+            &pair::QueryMsg::Share {
+                amount: staked.into(),
+            },
+        )
+        .unwrap();
+    let staked_value = helper.query_staked_value(&lp_token, &user).unwrap();
+    assert_eq!(staked_value, share);
+    assert_eq!(staked_value[0].info, asset_infos[0]);
+    assert_eq!(staked_value[1].info, asset_infos[1]);
+
+    // User without a position has zero staked value
+    let staked_value = helper
+        .query_staked_value(&lp_token, &TestAddr::new("random"))
+        .unwrap();
+    assert!(staked_value.iter().all(|asset| asset.amount.is_zero()));
+}
+
+#[test]
+fn test_vesting_balance() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+
+    // The helper vests all ASTRO to the generator at once
+    assert_eq!(helper.query_vesting_balance().unwrap(), Uint128::MAX);
+
+    let mock_vesting = helper.init_mock_vesting(1_000_000);
+    helper
+        .app
+        .execute_contract(
+            helper.owner.clone(),
+            helper.generator.clone(),
+            &ExecuteMsg::UpdateConfig {
+                astro_token: None,
+                vesting_contract: Some(mock_vesting.to_string()),
+                generator_controller: None,
+                guardian: None,
+                incentivization_fee_info: None,
+                token_transfer_gas_limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert_eq!(
+        helper.query_vesting_balance().unwrap(),
+        Uint128::new(1_000_000)
+    );
+}
+
+#[test]
+fn test_alloc_points_overflow() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+
+    let pair1 = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("bar")])
+        .unwrap();
+    let pair2 = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("baz")])
+        .unwrap();
+
+    let err = helper
+        .setup_pools(vec![
+            (pair1.liquidity_token.to_string(), u128::MAX),
+            (pair2.liquidity_token.to_string(), 1),
+        ])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AllocPointsOverflow {}
+    );
+    assert_eq!(helper.query_config().total_alloc_points, Uint128::zero());
+
+    // Alloc points right at the boundary are accepted
+    helper
+        .setup_pools(vec![
+            (pair1.liquidity_token.to_string(), u128::MAX - 1),
+            (pair2.liquidity_token.to_string(), 1),
+        ])
+        .unwrap();
+    assert_eq!(helper.query_config().total_alloc_points, Uint128::MAX);
+
+    // Large emissions don't overflow the per-pool reward rate computation
+    helper.set_tokens_per_second(u128::MAX / 2).unwrap();
+    let reward_info = helper.query_reward_info(&pair2.liquidity_token);
+    assert_eq!(
+        reward_info[0].rps,
+        Decimal256::from_ratio(u128::MAX / 2, u128::MAX)
+    );
+
+    // total_alloc_points stays consistent after the pools are reconfigured
+    helper
+        .setup_pools(vec![(pair2.liquidity_token.to_string(), 10)])
+        .unwrap();
+    assert_eq!(helper.query_config().total_alloc_points, Uint128::new(10));
+}
+
+#[test]
+fn test_set_batch() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+
+    let pairs = ["bar", "baz", "qux", "quux"]
+        .iter()
+        .map(|denom| {
+            helper
+                .create_pair(&[AssetInfo::native("foo"), AssetInfo::native(*denom)])
+                .unwrap()
+                .liquidity_token
+        })
+        .collect_vec();
+
+    helper
+        .setup_pools(vec![(pairs[0].clone(), 10), (pairs[1].clone(), 20)])
+        .unwrap();
+
+    let err = helper
+        .set_batch(&TestAddr::new("random"), vec![(pairs[0].clone(), 1)])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = helper
+        .set_batch(&owner, vec![(pairs[0].clone(), 1), (pairs[0].clone(), 2)])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DuplicatedPoolFound {}
+    );
+
+    // Update two active pools and add a new one at once
+    helper
+        .set_batch(
+            &owner,
+            vec![
+                (pairs[1].clone(), 50),
+                (pairs[2].clone(), 30),
+                (pairs[3].clone(), 40),
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(
+        helper.query_config().total_alloc_points,
+        Uint128::new(10 + 50 + 30 + 40)
+    );
+    assert_eq!(
+        helper.active_pools(),
+        vec![
+            (pairs[0].clone(), Uint128::new(10)),
+            (pairs[1].clone(), Uint128::new(50)),
+            (pairs[2].clone(), Uint128::new(30)),
+            (pairs[3].clone(), Uint128::new(40)),
+        ]
+    );
+}
+
+#[test]
+fn test_update_config() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+
+    let new_vesting = TestAddr::new("new_vesting");
+    let new_generator_controller = TestAddr::new("new_generator_controller");
+    let new_guardian = TestAddr::new("new_guardian");
+    let new_incentivization_fee_info = IncentivizationFeeInfo {
+        fee_receiver: TestAddr::new("new_fee_receiver"),
+        fee: coin(1000, "uusd"),
+    };
+    let new_gas_limit = 800_000;
+
+    let msg = ExecuteMsg::UpdateConfig {
+        astro_token: Some(AssetInfo::native("new_astro")),
+        vesting_contract: Some(new_vesting.to_string()),
+        generator_controller: Some(new_generator_controller.to_string()),
+        guardian: Some(new_guardian.to_string()),
+        incentivization_fee_info: Some(new_incentivization_fee_info.clone()),
+        token_transfer_gas_limit: Some(new_gas_limit),
+    };
+
+    let err = helper
+        .app
+        .execute_contract(TestAddr::new("random"), helper.generator.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    helper
+        .app
+        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
+        .unwrap();
+
+    let config = helper.query_config();
+    assert_eq!(config.astro_token, AssetInfo::native("new_astro"));
+    assert_eq!(config.vesting_contract, new_vesting);
+    assert_eq!(
+        config.generator_controller.unwrap(),
+        new_generator_controller
+    );
+    assert_eq!(config.guardian.unwrap(), new_guardian);
+    assert_eq!(
+        config.incentivization_fee_info.unwrap(),
+        new_incentivization_fee_info
+    );
+    assert_eq!(config.token_transfer_gas_limit.unwrap(), new_gas_limit);
+}
+
+#[test]
+fn test_change_ownership() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+
+    let new_owner = TestAddr::new("new_owner");
+
+    // New owner
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: new_owner.to_string(),
+        expires_in: 100, // seconds
+    };
+
+    // Unauthorized check
+    let err = helper
+        .app
+        .execute_contract(
+            TestAddr::new("not_owner"),
+            helper.generator.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+
+    // Claim before proposal
+    let err = helper
+        .app
+        .execute_contract(
+            new_owner.clone(),
+            helper.generator.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Ownership proposal not found"
+    );
+
+    // Propose new owner
+    helper
+        .app
+        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
+        .unwrap();
+
+    // Claim from invalid addr
+    let err = helper
+        .app
+        .execute_contract(
+            TestAddr::new("invalid_addr"),
+            helper.generator.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+
+    // Drop ownership proposal
+    helper
+        .app
+        .execute_contract(
+            helper.owner.clone(),
+            helper.generator.clone(),
+            &ExecuteMsg::DropOwnershipProposal {},
+            &[],
+        )
+        .unwrap();
+
+    // Claim ownership
+    let err = helper
+        .app
+        .execute_contract(
+            new_owner.clone(),
+            helper.generator.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Ownership proposal not found"
+    );
+
+    // Propose new owner again
+    helper
+        .app
+        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
+        .unwrap();
+    helper
+        .app
+        .execute_contract(
+            new_owner.clone(),
+            helper.generator.clone(),
+            &ExecuteMsg::ClaimOwnership {},
+            &[],
+        )
+        .unwrap();
+
+    assert_eq!(helper.query_config().owner.to_string(), new_owner)
+}
+
+#[test]
+fn test_incentive_without_funds() {
+    let astro = native_asset_info("astro".to_string());
+    let usdc = native_asset_info("usdc".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just make following calculations easier // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
+        )
+        .unwrap();
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = usdc.clone();
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+    helper.mint_assets(&bank, &[reward.clone()]);
+    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
+    let incentivization_fee = helper.incentivization_fee.clone();
+    helper.mint_coin(&bank, &incentivization_fee);
+    // add reward
+    let err = helper
+        .app
+        .execute_contract(
+            bank.clone(),
+            helper.generator.clone(),
+            &ExecuteMsg::Incentivize {
+                lp_token: lp_token.to_string(),
+                schedule,
+            },
+            &[incentivization_fee], // only send incentivization fee without reward
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsuffiicientRewardToken {
+            reward: reward_asset_info.to_string(),
+            lp_token
+        }
+    )
+}
+
+#[test]
+fn test_claim_excess_rewards() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let mut pools = vec![
+        ("uusd", "eur", "".to_string(), vec!["user1", "user2"], 100),
+        ("uusd", "tokenA", "".to_string(), vec!["user1"], 50),
+        ("uusd", "tokenB", "".to_string(), vec!["user2"], 50),
+    ];
+    let mut active_pools = vec![];
+    for (token1, token2, lp_token, stakers, alloc_points) in pools.iter_mut() {
+        let asset_infos = [AssetInfo::native(*token1), AssetInfo::native(*token2)];
+        let pair_info = helper.create_pair(&asset_infos).unwrap();
+        *lp_token = pair_info.liquidity_token.to_string();
+        active_pools.push((pair_info.liquidity_token.to_string(), *alloc_points));
+        let provide_assets = [
+            asset_infos[0].with_balance(100000u64),
+            asset_infos[1].with_balance(100000u64),
+        ];
+        // Owner provides liquidity first just to make following calculations easier
+        // since first depositor gets small cut of LP tokens
+        helper
+            .provide_liquidity(
+                &owner,
+                &provide_assets,
+                &pair_info.contract_addr,
+                false, // Owner doesn't stake in incentives
+            )
+            .unwrap();
+
+        for staker in stakers {
+            let staker_addr = TestAddr::new(staker);
+            // Pool doesn't exist in incentives yet
+            let astro_before = astro.query_pool(&helper.app.wrap(), &staker_addr).unwrap();
+            helper
+                .claim_rewards(
+                    &staker_addr,
+                    vec![
+                        pair_info.liquidity_token.to_string(),
+                        pair_info.liquidity_token.to_string(),
+                    ],
+                )
+                .unwrap_err();
+            let astro_after = astro.query_pool(&helper.app.wrap(), &staker_addr).unwrap();
+            assert_eq!((astro_after - astro_before).u128(), 0);
+
+            helper
+                .provide_liquidity(
+                    &staker_addr,
+                    &provide_assets,
+                    &pair_info.contract_addr,
+                    true,
+                )
+                .unwrap();
+        }
+    }
+
+    helper.setup_pools(active_pools).unwrap();
+    helper.set_tokens_per_second(1_000000).unwrap();
+    helper
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(5));
+    let user1 = TestAddr::new("user1");
+    let astro_before = astro.query_pool(&helper.app.wrap(), &user1).unwrap();
     let err = helper
-        .deactivate_pool(&owner, norm_pair1_info.liquidity_token.as_str())
+        .claim_rewards(
+            &user1,
+            vec![
+                pools[0].2.to_string(),
+                pools[1].2.to_string(),
+                pools[0].2.to_string(),
+                pools[1].2.to_string(),
+            ],
+        )
         .unwrap_err();
     assert_eq!(
         err.downcast::<ContractError>().unwrap(),
-        ContractError::Unauthorized {}
+        ContractError::DuplicatedPoolFound {}
     );
 
-    // Deactivate norm_pair1_info by its asset infos
     helper
-        .deactivate_pool_full_flow(&[tokens[0].clone(), tokens[1].clone()])
+        .claim_rewards(&user1, vec![pools[0].2.to_string(), pools[1].2.to_string()])
         .unwrap();
-
-    let err = helper
-        .setup_pools(vec![
-            (norm_pair1_info.liquidity_token.to_string(), 1),
-            (norm_pair2_info.liquidity_token.to_string(), 1),
-        ])
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        format!(
-            "Generic error: The pair is not registered: {}-{}",
-            &tokens[0], &tokens[1]
-        )
-    );
-
-    helper.next_block(1000);
-
-    let reward_info = helper.query_reward_info(norm_pair1_info.liquidity_token.as_str());
-    assert_eq!(
-        dec256_to_u128_floor(reward_info[0].orphaned),
-        50 * 1000 + 75 * 1000 // deactivated pool gets nothing
-    );
-    let reward_info = helper.query_reward_info(norm_pair2_info.liquidity_token.as_str());
-    assert_eq!(
-        dec256_to_u128_floor(reward_info[0].orphaned),
-        50 * 1000 + 75 * 1000 + 150 * 1000
-    );
-    let reward_info = helper.query_reward_info(blk_pair_info.liquidity_token.as_str());
-    assert_eq!(dec256_to_u128_floor(reward_info[0].orphaned), 50 * 1000); // deactivated blk pair still gets nothing
+    let astro_after = astro.query_pool(&helper.app.wrap(), &user1).unwrap();
+    assert_eq!((astro_after - astro_before).u128(), 2_500000);
 }
 
 #[test]
-fn test_incentives_with_blocked() {
+fn test_user_claim_less() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
@@ -1637,43 +3266,101 @@ fn test_incentives_with_blocked() {
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
 
-    // Try to incentivize with blocked token
-    let bank = TestAddr::new("bank");
-    let blocked_token = AssetInfo::native("blocked_reward");
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
     helper
-        .block_tokens(&owner, &[blocked_token.clone()])
+        .provide_liquidity(
+            &owner,
+            &provide_assets,
+            &pair_info.contract_addr,
+            false, // Owner doesn't stake in incentives
+        )
         .unwrap();
-    let reward = blocked_token.with_balance(1000_000000u128);
+
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = AssetInfo::native("reward");
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+
+    // create reward schedule
     helper.mint_assets(&bank, &[reward.clone()]);
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
+    helper.mint_coin(&bank, &incentivization_fee);
+    helper
+        .incentivize(
+            &bank,
+            &lp_token,
+            schedule.clone(),
+            &[incentivization_fee.clone()],
+        )
+        .unwrap();
 
-    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
+    helper.app.update_block(|block| {
+        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts)
+    });
+
+    // user claim, sets user index
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+    // finish 1st schedule, reward goes to FINISHED_REWARD_INDEXES
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts + 1));
+
+    // create reward schedule again
+    helper.mint_assets(&bank, &[reward.clone()]);
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
     helper.mint_coin(&bank, &incentivization_fee);
-    let err = helper
+    helper
         .incentivize(
             &bank,
-            pair_info.liquidity_token.as_str(),
+            &lp_token,
             schedule.clone(),
             &[incentivization_fee.clone()],
         )
-        .unwrap_err();
+        .unwrap();
+
+    // few seconds before schedule finishes
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts - 1));
+
+    // user claim rewards as (global index - user index), which is incorrect
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+    // finish 2nd schedule
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts + 1));
+
+    // user claim all rewards
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+
+    // check user rewards
+    let new_reward_balance = reward_asset_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+
     assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::BlockedToken {
-            token: blocked_token.to_string()
-        }
+        new_reward_balance.u128(),
+        (reward.amount + reward.amount).u128() - 2 // rounding error
     );
 }
 
 #[test]
-fn test_remove_rewards() {
+fn test_broken_cw20_incentives() {
     let astro = native_asset_info("astro".to_string());
-
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
-
     let owner = helper.owner.clone();
     let incentivization_fee = helper.incentivization_fee.clone();
 
@@ -1685,7 +3372,7 @@ fn test_remove_rewards() {
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just to make following calculations easier
+    // Owner provides liquidity first just make following calculations easier
     // since first depositor gets small cut of LP tokens
     helper
         .provide_liquidity(
@@ -1702,974 +3389,986 @@ fn test_remove_rewards() {
         .unwrap();
 
     let bank = TestAddr::new("bank");
-    let reward_asset_info = AssetInfo::native("reward");
-    let reward = reward_asset_info.with_balance(1000_000000u128);
-    let (schedule, _) = helper.create_schedule(&reward, 1).unwrap();
 
-    helper.mint_assets(&bank, &[reward.clone()]);
-    helper.mint_coin(&bank, &incentivization_fee);
+    let schedules: Vec<_> = (1..=2)
+        .into_iter()
+        .map(|i| {
+            let reward_asset_info = if i == 1 {
+                AssetInfo::native(format!("reward{i}"))
+            } else {
+                let reward_cw20 = helper.init_broken_cw20("reward", None);
+                AssetInfo::cw20(reward_cw20)
+            };
 
-    helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            schedule.clone(),
-            &[incentivization_fee.clone()],
-        )
-        .unwrap();
+            let reward = reward_asset_info.with_balance(1000_000000u128);
+            helper.create_schedule(&reward, 1).unwrap()
+        })
+        .collect();
 
-    // 5 days
-    for _ in 0..5 {
-        helper.next_block(86400);
+    // Create multiple schedules with different rewards
+    for (schedule, _) in &schedules {
+        helper.mint_assets(&bank, &[schedule.reward.clone()]);
+        helper.mint_coin(&bank, &incentivization_fee);
+        helper
+            .incentivize(
+                &bank,
+                &lp_token,
+                schedule.clone(),
+                &[incentivization_fee.clone()],
+            )
+            .unwrap();
+    }
 
-        let pending = helper.query_pending_rewards(&user, &lp_token);
-        let bal_before = helper.snapshot_balances(&user, &pending);
+    helper.app.update_block(|block| {
+        block.time = Timestamp::from_seconds(schedules[0].1.next_epoch_start_ts)
+    });
+
+    // Iterate by 1 day and claim rewards
+    loop {
+        if helper.app.block_info().time.seconds() > schedules[0].1.end_ts {
+            break;
+        }
 
         helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
 
-        let bal_after = helper.snapshot_balances(&user, &pending);
-        assert_rewards(&bal_before, &bal_after, &pending);
+        helper
+            .app
+            .update_block(|block| block.time = block.time.plus_seconds(86400));
+    }
+
+    // Valid native coin reward was accrued properly
+    let valid_reward_balance = schedules[0]
+        .1
+        .reward_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+    assert_eq!(valid_reward_balance.u128(), 999_999994);
+
+    // Broken cw20 reward was not accrued because incentives contract simply ignores it
+    let broken_reward_balance = schedules[1]
+        .1
+        .reward_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+    assert_eq!(broken_reward_balance.u128(), 0);
+}
+
+#[test]
+fn test_factory_deregisters_any_pool() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let asset_infos = &[AssetInfo::native("usd"), AssetInfo::native("foo")];
+
+    // factory contract create pair
+    helper.create_pair(asset_infos).unwrap();
+    // ensure pair created
+    let pair_info = helper.query_pair_info(asset_infos);
+    assert_eq!(pair_info.asset_infos, asset_infos);
+
+    // Incentives contract doesn't have such pool yet but it doesn't block deregistration
+    helper.deactivate_pool_full_flow(asset_infos).unwrap();
+}
+
+#[test]
+fn test_rescue_stuck_lp() {
+    let astro = native_asset_info("astro".to_string());
+    let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
+
+    helper.setup_pools(vec![(lp_token.clone(), 1)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
+
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    let user1 = TestAddr::new("user1");
+    let user2 = TestAddr::new("user2");
+    for user in [&user1, &user2] {
+        helper
+            .provide_liquidity(user, &provide_assets, &pair_info.contract_addr, true)
+            .unwrap();
     }
+    let user1_staked = helper.query_deposit(&lp_token, &user1).unwrap();
+    let user2_staked = helper.query_deposit(&lp_token, &user2).unwrap();
 
-    // Assume 1 day passed and then reward gets deregistered
-    helper.next_block(86400);
+    helper.next_block(1000);
 
-    let receiver = TestAddr::new("receiver");
+    // Active pools can't be wound down
+    let err = helper
+        .rescue_stuck_lp(&owner, &lp_token, &[&user1, &user2])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PoolNotDeprecated {
+            lp_token: lp_token.clone()
+        }
+    );
 
-    // Only owner is able to remove reward
+    helper.deactivate_pool_full_flow(&asset_infos).unwrap();
+
+    // Only owner can rescue LP tokens
     let err = helper
-        .remove_reward(
-            &TestAddr::new("random"),
-            &lp_token,
-            &reward_asset_info.to_string(),
-            false,
-            &receiver,
-        )
+        .rescue_stuck_lp(&user1, &lp_token, &[&user1, &user2])
         .unwrap_err();
     assert_eq!(
         err.downcast::<ContractError>().unwrap(),
         ContractError::Unauthorized {}
     );
 
+    // Users without a position are skipped
+    let random = TestAddr::new("random");
     helper
-        .remove_reward(
-            &owner,
-            &lp_token,
-            &reward_asset_info.to_string(),
-            false,
-            &receiver,
-        )
+        .rescue_stuck_lp(&owner, &lp_token, &[&user1, &user2, &random])
         .unwrap();
 
-    // User must be allowed to claim rewards for the last 1 day
-    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    for (user, staked) in [(&user1, user1_staked), (&user2, user2_staked)] {
+        let lp_balance = helper.app.wrap().query_balance(user, &lp_token).unwrap();
+        assert_eq!(lp_balance.amount.u128(), staked);
+        assert_eq!(helper.query_deposit(&lp_token, user).unwrap(), 0);
 
-    let outstanding = reward_asset_info
-        .query_pool(&helper.app.wrap(), &receiver)
-        .unwrap()
-        .u128();
-    let claimed = reward_asset_info
-        .query_pool(&helper.app.wrap(), &user)
-        .unwrap()
-        .u128();
-    assert_eq!(outstanding, 571_428571); // ~ 8 * 71428571 (8 days left)
-    assert_eq!(claimed, 428_571426); // // claimed 6 days in a row ~ 6 * 71428571
-    assert_eq!(outstanding + claimed, 999_999997); // ~ initial reward amount i.e. 1000_000000
+        // Outstanding ASTRO rewards were claimed as well
+        let astro_balance = helper.app.wrap().query_balance(user, "astro").unwrap();
+        assert!(!astro_balance.amount.is_zero());
+    }
+
+    // Nothing was sent to the owner
+    let owner_lp = helper.app.wrap().query_balance(&owner, &lp_token).unwrap();
+    assert!(owner_lp.amount.is_zero());
 }
 
 #[test]
-fn test_long_unclaimed_rewards() {
+fn test_orphaned_rewards() {
     let astro = native_asset_info("astro".to_string());
-
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(EPOCHS_START + EPOCH_LENGTH));
-
-    let owner = helper.owner.clone();
     let incentivization_fee = helper.incentivization_fee.clone();
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
     let lp_token = pair_info.liquidity_token.to_string();
 
-    let provide_assets = [
-        asset_infos[0].with_balance(100000u64),
-        asset_infos[1].with_balance(100000u64),
-    ];
-    // Owner provides liquidity first just to make following calculations easier
-    // since first depositor gets small cut of LP tokens
-    helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
-        .unwrap();
-
-    let user = TestAddr::new("user");
-    helper
-        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
-        .unwrap();
-
     let bank = TestAddr::new("bank");
 
-    let schedules: Vec<_> = (1..=2)
+    let schedules: Vec<_> = (1..=(MAX_REWARD_TOKENS - 1))
         .into_iter()
         .map(|i| {
             let reward_asset_info = AssetInfo::native(format!("reward{i}"));
-            let reward = reward_asset_info.with_balance(50_000_000000u128);
-            // Create 2 schedules with different duration
-            [
-                helper.create_schedule(&reward, 20).unwrap(),
-                helper.create_schedule(&reward, 10).unwrap(),
-            ]
+            let reward = reward_asset_info.with_balance(1000_000000u128);
+            helper.create_schedule(&reward, 2).unwrap()
         })
-        .flatten()
         .collect();
-    let max_end = schedules.iter().map(|(_, sch)| sch.end_ts).max().unwrap();
-    // Create multiple schedules with different rewards (starts on the next week)
-    for (ind, (schedule, _)) in schedules.iter().enumerate() {
+    // Create multiple schedules with different rewards
+    for (schedule, _) in &schedules {
         helper.mint_assets(&bank, &[schedule.reward.clone()]);
-        let mut attach_funds = vec![];
-        if ind % 2 == 0 {
-            helper.mint_coin(&bank, &incentivization_fee);
-            attach_funds = vec![incentivization_fee.clone()]
-        }
+        helper.mint_coin(&bank, &incentivization_fee);
         helper
-            .incentivize(&bank, &lp_token, schedule.clone(), &attach_funds)
+            .incentivize(
+                &bank,
+                &lp_token,
+                schedule.clone(),
+                &[incentivization_fee.clone()],
+            )
             .unwrap();
     }
 
-    // Start from the starting point and jump over 5 weeks
+    // Timing out all schedules. nobody stakes LP tokens, rewards become orphaned
     helper.app.update_block(|block| {
-        block.time = Timestamp::from_seconds(schedules[0].1.next_epoch_start_ts + 86400 * 7 * 5);
+        block.time = Timestamp::from_seconds(schedules.last().cloned().unwrap().1.end_ts + 1)
     });
 
-    // Deregister reward1
-    let receiver = TestAddr::new("receiver");
+    let orph_receiver = TestAddr::new("orphaned_rewards_receiver");
+
+    // Check that there are still no orphaned rewards to claim
+    let err = helper
+        .claim_orphaned_rewards(None, &orph_receiver)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoOrphanedRewards {}
+    );
+
+    // Add one more reward thus triggering finished rewards cleanup
+    let reward =
+        AssetInfo::native(format!("reward{MAX_REWARD_TOKENS}")).with_balance(1000_000000u128);
+    let (new_schedule, int_new_schedule) = helper.create_schedule(&reward, 2).unwrap();
+    helper.mint_assets(&bank, &[reward]);
+    helper.mint_coin(&bank, &incentivization_fee);
     helper
-        .remove_reward(
-            &owner,
+        .incentivize(
+            &bank,
             &lp_token,
-            &schedules[0].0.reward.info.to_string(),
-            false,
-            &receiver,
+            new_schedule,
+            &[incentivization_fee.clone()],
         )
         .unwrap();
-    let deregister_amount = schedules[0]
-        .0
-        .reward
-        .info
-        .query_pool(&helper.app.wrap(), &receiver)
-        .unwrap()
-        .u128();
-    assert_eq!(deregister_amount, 58441_558441);
-
-    // Iterate till the end of the longest schedule by 1 day and claim rewards
-    loop {
-        let pending = helper.query_pending_rewards(&user, &lp_token);
-        let bal_before = helper.snapshot_balances(&user, &pending);
-
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-
-        let bal_after = helper.snapshot_balances(&user, &pending);
-        assert_rewards(&bal_before, &bal_after, &pending);
-
-        if helper.app.block_info().time.seconds() > max_end {
-            break;
-        } else {
-            helper.next_block(86400);
-        }
-    }
-
-    let claimed_reward1 = schedules[0]
-        .0
-        .reward
-        .info
-        .query_pool(&helper.app.wrap(), &user)
-        .unwrap()
-        .u128();
-    assert_eq!(deregister_amount + claimed_reward1, 99999_999999);
-
-    for (schedule, _) in schedules.iter().skip(2) {
-        let bal = schedule
-            .reward
-            .info
-            .query_pool(&helper.app.wrap(), &user)
-            .unwrap()
-            .u128();
-        assert_eq!(bal, 99_999_999943); // All rewards are claimed
-    }
-}
-
-#[test]
-fn test_queries() {
-    let astro = native_asset_info("astro".to_string());
-    let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let owner = helper.owner.clone();
-    let incentivization_fee = helper.incentivization_fee.clone();
-
-    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
-    let pair_info = helper.create_pair(&asset_infos).unwrap();
-    let lp_token = pair_info.liquidity_token.to_string();
 
-    // Owner provides liquidity first just to make following calculations easier
-    // since first depositor gets small cut of LP tokens
+    // Provide to check that user is only eligible for the last added reward
     let provide_assets = [
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
+    let user = TestAddr::new("user");
     helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // doesnt stake
-        )
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    for i in 0..10 {
-        let user = TestAddr::new(&format!("user_{i}"));
-        helper
-            .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
-            .unwrap();
-    }
-
-    let user = TestAddr::new("user_1");
-
-    assert_eq!(helper.query_deposit(&lp_token, &user).unwrap(), 100000);
-
-    let random = TestAddr::new("random");
-    let amount = helper.query_deposit(&lp_token, &random).unwrap();
-    assert_eq!(amount, 0);
-
-    let amount = helper.query_deposit(random.as_str(), &user).unwrap();
-    assert_eq!(amount, 0);
+    helper
+        .app
+        .update_block(|block| block.time = Timestamp::from_seconds(int_new_schedule.end_ts + 1));
 
-    // This Lp doesn't exist
-    helper.pool_info(random.as_str()).unwrap_err();
+    // Claim rewards and assert user only gets reward5
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
 
-    let pool_info = helper.pool_info(&lp_token).unwrap();
-    assert_eq!(pool_info.rewards, []);
-    assert_eq!(pool_info.total_lp.u128(), 1_000_000); // 100_000 per user
-    assert_eq!(
-        pool_info.last_update_ts,
-        helper.app.block_info().time.seconds()
-    );
+    for (schedule, _) in &schedules {
+        let reward_balance = schedule
+            .reward
+            .info
+            .query_pool(&helper.app.wrap(), &user)
+            .unwrap();
+        assert_eq!(reward_balance.u128(), 0);
+    }
 
-    let stakers = helper.pool_stakers(&lp_token, None, None);
-    assert_eq!(
-        stakers[5],
-        ("wasm1_user_5".to_string(), Uint128::from(100_000u128))
-    );
-    let total = stakers
-        .iter()
-        .fold(Uint128::zero(), |acc, (_, bal)| acc + bal);
-    assert_eq!(total, pool_info.total_lp);
+    let reward_balance = int_new_schedule
+        .reward_info
+        .query_pool(&helper.app.wrap(), &user)
+        .unwrap();
+    assert_eq!(reward_balance.u128(), 999_999999);
 
-    let bank = TestAddr::new("bank");
-    let reward_asset_info = AssetInfo::native("reward");
-    let reward = reward_asset_info.with_balance(1000_000000u128);
+    // Owner claims first orphaned rewards
+    helper
+        .claim_orphaned_rewards(Some(1), &orph_receiver)
+        .unwrap();
 
-    // Create multiple overlapping schedules with the same reward token starting right away
-    let schedules: Vec<_> = (1..=5)
-        .into_iter()
-        .map(|i| helper.create_schedule(&reward, i).unwrap())
-        .collect();
-    for (ind, (schedule, _)) in schedules.iter().enumerate() {
-        helper.mint_assets(&bank, &[reward.clone()]);
-        if ind == 0 {
-            // attach incentivization fee on the first schedule
-            helper.mint_coin(&bank, &incentivization_fee);
-            helper
-                .incentivize(
-                    &bank,
-                    &lp_token,
-                    schedule.clone(),
-                    &[incentivization_fee.clone()],
-                )
-                .unwrap();
-        } else {
-            helper
-                .incentivize(&bank, &lp_token, schedule.clone(), &[])
-                .unwrap();
-        }
+    // Owner claims all orphaned rewards
+    helper.claim_orphaned_rewards(None, &orph_receiver).unwrap();
+
+    for (schedule, _) in &schedules {
+        let reward_balance = schedule
+            .reward
+            .info
+            .query_pool(&helper.app.wrap(), &orph_receiver)
+            .unwrap();
+        assert_eq!(reward_balance.u128(), 999999999);
     }
 
-    let res = helper
-        .query_ext_reward_schedules(&lp_token, &reward_asset_info, None, None)
-        .unwrap();
+    // Try to claim again
+    let err = helper
+        .claim_orphaned_rewards(None, &orph_receiver)
+        .unwrap_err();
     assert_eq!(
-        res,
-        [
-            ScheduleResponse {
-                rps: Decimal256::from_str("2398.02426572720408957").unwrap(),
-                start_ts: 1696810000,
-                end_ts: 1698019200,
-            },
-            ScheduleResponse {
-                rps: Decimal256::from_str("1571.031212468851459733").unwrap(),
-                start_ts: 1698019200,
-                end_ts: 1698624000,
-            },
-            ScheduleResponse {
-                rps: Decimal256::from_str("1019.76329626157472324").unwrap(),
-                start_ts: 1698624000,
-                end_ts: 1699228800,
-            },
-            ScheduleResponse {
-                rps: Decimal256::from_str("606.335150073382231096").unwrap(),
-                start_ts: 1699228800,
-                end_ts: 1699833600,
-            },
-            ScheduleResponse {
-                rps: Decimal256::from_str("275.603571822290816888").unwrap(),
-                start_ts: 1699833600,
-                end_ts: 1700438400,
-            },
-        ]
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoOrphanedRewards {}
     );
-    let res = helper
-        .query_ext_reward_schedules(&lp_token, &reward_asset_info, None, Some(1))
-        .unwrap();
-    assert_eq!(res.len(), 1);
-    let res = helper
-        .query_ext_reward_schedules(&lp_token, &reward_asset_info, Some(1699228800), None)
-        .unwrap();
-    assert_eq!(res.len(), 2);
-
-    let pools = helper.all_pools();
-    assert_eq!(pools, vec![lp_token.clone()]);
 }
 
 #[test]
-fn test_update_config() {
+fn test_withdraw_for() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
 
-    let new_vesting = TestAddr::new("new_vesting");
-    let new_generator_controller = TestAddr::new("new_generator_controller");
-    let new_guardian = TestAddr::new("new_guardian");
-    let new_incentivization_fee_info = IncentivizationFeeInfo {
-        fee_receiver: TestAddr::new("new_fee_receiver"),
-        fee: coin(1000, "uusd"),
-    };
-    let new_gas_limit = 800_000;
+    let user = TestAddr::new("user");
+    let relayer = TestAddr::new("relayer");
+    let native_lp = native_asset_info(lp_token.to_string()).with_balance(10000u16);
+    helper.mint_coin(&user, &native_lp.as_coin().unwrap());
+    helper.stake(&user, native_lp).unwrap();
 
-    let msg = ExecuteMsg::UpdateConfig {
-        astro_token: Some(AssetInfo::native("new_astro")),
-        vesting_contract: Some(new_vesting.to_string()),
-        generator_controller: Some(new_generator_controller.to_string()),
-        guardian: Some(new_guardian.to_string()),
-        incentivization_fee_info: Some(new_incentivization_fee_info.clone()),
-        token_transfer_gas_limit: Some(new_gas_limit),
+    let signing_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let sign = |helper: &Helper, amount: u128, nonce: u64| -> Binary {
+        let permit = WithdrawPermit {
+            chain_id: helper.app.block_info().chain_id,
+            contract: helper.generator.to_string(),
+            account: user.to_string(),
+            lp_token: lp_token.clone(),
+            amount: amount.into(),
+            nonce,
+        };
+        let signature: Signature = signing_key.sign(&to_json_vec(&permit).unwrap());
+        Binary::from(signature.to_bytes().as_slice())
     };
 
+    // The account hasn't registered a pubkey yet
+    let signature = sign(&helper, 1000, 0);
     let err = helper
-        .app
-        .execute_contract(TestAddr::new("random"), helper.generator.clone(), &msg, &[])
+        .withdraw_for(&relayer, &user, &lp_token, 1000u128, signature.clone())
         .unwrap_err();
     assert_eq!(
         err.downcast::<ContractError>().unwrap(),
-        ContractError::Unauthorized {}
+        ContractError::NoWithdrawPubkey {
+            account: user.to_string()
+        }
+    );
+
+    let err = helper
+        .set_withdraw_pubkey(&user, Some(Binary::from(vec![1u8; 10])))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidWithdrawPubkey {}
+    );
+
+    let pubkey = signing_key.verifying_key().to_encoded_point(true);
+    helper
+        .set_withdraw_pubkey(&user, Some(Binary::from(pubkey.as_bytes())))
+        .unwrap();
+    assert_eq!(helper.query_withdraw_nonce(&user).unwrap(), 0);
+
+    // Signature over a different amount is rejected
+    let err = helper
+        .withdraw_for(&relayer, &user, &lp_token, 2000u128, signature.clone())
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidWithdrawSignature {}
+    );
+
+    // Malformed signature is rejected
+    let err = helper
+        .withdraw_for(
+            &relayer,
+            &user,
+            &lp_token,
+            1000u128,
+            Binary::from(vec![0u8; 3]),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidWithdrawSignature {}
     );
 
     helper
+        .withdraw_for(&relayer, &user, &lp_token, 1000u128, signature.clone())
+        .unwrap();
+    assert_eq!(helper.query_deposit(&lp_token, &user).unwrap(), 9000);
+    assert_eq!(helper.query_withdraw_nonce(&user).unwrap(), 1);
+    // LP tokens are sent to the account, not to the relayer
+    let user_lp = helper.app.wrap().query_balance(&user, &lp_token).unwrap();
+    assert_eq!(user_lp.amount.u128(), 1000);
+    let relayer_lp = helper
         .app
-        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
+        .wrap()
+        .query_balance(&relayer, &lp_token)
         .unwrap();
+    assert_eq!(relayer_lp.amount.u128(), 0);
 
-    let config = helper.query_config();
-    assert_eq!(config.astro_token, AssetInfo::native("new_astro"));
-    assert_eq!(config.vesting_contract, new_vesting);
+    // Replaying the same signature fails since the nonce has been bumped
+    let err = helper
+        .withdraw_for(&relayer, &user, &lp_token, 1000u128, signature)
+        .unwrap_err();
     assert_eq!(
-        config.generator_controller.unwrap(),
-        new_generator_controller
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidWithdrawSignature {}
     );
-    assert_eq!(config.guardian.unwrap(), new_guardian);
+
+    let signature = sign(&helper, 500, 1);
+    helper
+        .withdraw_for(&relayer, &user, &lp_token, 500u128, signature)
+        .unwrap();
+    assert_eq!(helper.query_deposit(&lp_token, &user).unwrap(), 8500);
+    assert_eq!(helper.query_withdraw_nonce(&user).unwrap(), 2);
+
+    // Once the pubkey is removed signed withdrawals are disabled
+    helper.set_withdraw_pubkey(&user, None).unwrap();
+    let signature = sign(&helper, 500, 2);
+    let err = helper
+        .withdraw_for(&relayer, &user, &lp_token, 500u128, signature)
+        .unwrap_err();
     assert_eq!(
-        config.incentivization_fee_info.unwrap(),
-        new_incentivization_fee_info
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoWithdrawPubkey {
+            account: user.to_string()
+        }
     );
-    assert_eq!(config.token_transfer_gas_limit.unwrap(), new_gas_limit);
 }
 
 #[test]
-fn test_change_ownership() {
+fn test_sweep_reward_dust() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
+    let incentivization_fee = helper.incentivization_fee.clone();
 
-    let new_owner = TestAddr::new("new_owner");
+    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
+    let pair_info = helper.create_pair(&asset_infos).unwrap();
+    let lp_token = pair_info.liquidity_token.to_string();
 
-    // New owner
-    let msg = ExecuteMsg::ProposeNewOwner {
-        owner: new_owner.to_string(),
-        expires_in: 100, // seconds
-    };
+    // Many tiny stakers
+    let users = (0..10)
+        .map(|i| TestAddr::new(&format!("user{i}")))
+        .collect_vec();
+    for user in &users {
+        let lp = native_asset_info(lp_token.clone()).with_balance(3u8);
+        helper.mint_coin(user, &lp.as_coin().unwrap());
+        helper.stake(user, lp).unwrap();
+    }
 
-    // Unauthorized check
-    let err = helper
-        .app
-        .execute_contract(
-            TestAddr::new("not_owner"),
-            helper.generator.clone(),
-            &msg,
-            &[],
-        )
-        .unwrap_err();
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    let bank = TestAddr::new("bank");
+    let reward_asset_info = AssetInfo::native("reward");
+    let reward = reward_asset_info.with_balance(1000_000000u128);
+    helper.mint_assets(&bank, &[reward.clone()]);
+    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
+    helper.mint_coin(&bank, &incentivization_fee);
+    helper
+        .incentivize(&bank, &lp_token, schedule, &[incentivization_fee])
+        .unwrap();
 
-    // Claim before proposal
+    let recipient = TestAddr::new("recipient");
     let err = helper
-        .app
-        .execute_contract(
-            new_owner.clone(),
-            helper.generator.clone(),
-            &ExecuteMsg::ClaimOwnership {},
-            &[],
-        )
+        .sweep_reward_dust(&owner, &lp_token, &recipient)
         .unwrap_err();
     assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Ownership proposal not found"
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoRewardDust {
+            lp_token: lp_token.clone()
+        }
     );
 
-    // Propose new owner
-    helper
-        .app
-        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
-        .unwrap();
+    helper.app.update_block(|block| {
+        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts)
+    });
 
-    // Claim from invalid addr
-    let err = helper
-        .app
-        .execute_contract(
-            TestAddr::new("invalid_addr"),
-            helper.generator.clone(),
-            &ExecuteMsg::ClaimOwnership {},
-            &[],
-        )
-        .unwrap_err();
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    // Every user claims every 6 hours until the schedule ends
+    while helper.app.block_info().time.seconds() <= internal_sch.end_ts + 21600 {
+        for user in &users {
+            helper.claim_rewards(user, vec![lp_token.clone()]).unwrap();
+        }
+        helper.next_block(21600);
+    }
 
-    // Drop ownership proposal
-    helper
-        .app
-        .execute_contract(
-            helper.owner.clone(),
-            helper.generator.clone(),
-            &ExecuteMsg::DropOwnershipProposal {},
-            &[],
-        )
-        .unwrap();
+    let claimed: u128 = users
+        .iter()
+        .map(|user| {
+            reward_asset_info
+                .query_pool(&helper.app.wrap(), user)
+                .unwrap()
+                .u128()
+        })
+        .sum();
+    assert!(claimed < 1000_000000);
 
-    // Claim ownership
     let err = helper
-        .app
-        .execute_contract(
-            new_owner.clone(),
-            helper.generator.clone(),
-            &ExecuteMsg::ClaimOwnership {},
-            &[],
-        )
+        .sweep_reward_dust(&TestAddr::new("random"), &lp_token, &recipient)
         .unwrap_err();
     assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Ownership proposal not found"
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
     );
 
-    // Propose new owner again
-    helper
-        .app
-        .execute_contract(helper.owner.clone(), helper.generator.clone(), &msg, &[])
-        .unwrap();
     helper
-        .app
-        .execute_contract(
-            new_owner.clone(),
-            helper.generator.clone(),
-            &ExecuteMsg::ClaimOwnership {},
-            &[],
-        )
+        .sweep_reward_dust(&owner, &lp_token, &recipient)
         .unwrap();
+    let swept = reward_asset_info
+        .query_pool(&helper.app.wrap(), &recipient)
+        .unwrap()
+        .u128();
+    assert!(swept > 0);
+    // Only fractional remainders are left unrecovered: the pool remainder plus
+    // rounding of the claims made after the schedule had finished
+    assert!(claimed + swept <= 1000_000000);
+    assert!(1000_000000 - (claimed + swept) <= users.len() as u128 + 1);
 
-    assert_eq!(helper.query_config().owner.to_string(), new_owner)
+    // The whole part of the dust has been swept already
+    let err = helper
+        .sweep_reward_dust(&owner, &lp_token, &recipient)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoRewardDust {
+            lp_token: lp_token.clone()
+        }
+    );
 }
 
 #[test]
-fn test_incentive_without_funds() {
+fn test_underfunded_vesting() {
     let astro = native_asset_info("astro".to_string());
-    let usdc = native_asset_info("usdc".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
+
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
     let lp_token = pair_info.liquidity_token.to_string();
+
     let provide_assets = [
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-    // Owner provides liquidity first just make following calculations easier // since first depositor gets small cut of LP tokens
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
     helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
+        .provide_liquidity(&owner, &provide_assets, &pair_info.contract_addr, false)
         .unwrap();
-    let bank = TestAddr::new("bank");
-    let reward_asset_info = usdc.clone();
-    let reward = reward_asset_info.with_balance(1000_000000u128);
-    helper.mint_assets(&bank, &[reward.clone()]);
-    let (schedule, _) = helper.create_schedule(&reward, 2).unwrap();
-    let incentivization_fee = helper.incentivization_fee.clone();
-    helper.mint_coin(&bank, &incentivization_fee);
-    // add reward
-    let err = helper
+
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
+
+    let user = TestAddr::new("user");
+    helper
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .unwrap();
+
+    // Replace vesting with one which can only cover 1000 ASTRO
+    let mock_vesting = helper.init_mock_vesting(1000);
+    helper.mint_coin(&mock_vesting, &coin(1000, "astro"));
+    helper
         .app
         .execute_contract(
-            bank.clone(),
+            owner.clone(),
             helper.generator.clone(),
-            &ExecuteMsg::Incentivize {
-                lp_token: lp_token.to_string(),
-                schedule,
+            &ExecuteMsg::UpdateConfig {
+                astro_token: None,
+                vesting_contract: Some(mock_vesting.to_string()),
+                generator_controller: None,
+                guardian: None,
+                incentivization_fee_info: None,
+                token_transfer_gas_limit: None,
             },
-            &[incentivization_fee], // only send incentivization fee without reward
+            &[],
         )
-        .unwrap_err();
-
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::InsuffiicientRewardToken {
-            reward: reward_asset_info.to_string(),
-            lp_token
-        }
-    )
-}
+        .unwrap();
 
-#[test]
-fn test_claim_excess_rewards() {
-    let astro = native_asset_info("astro".to_string());
-    let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let owner = helper.owner.clone();
-    let mut pools = vec![
-        ("uusd", "eur", "".to_string(), vec!["user1", "user2"], 100),
-        ("uusd", "tokenA", "".to_string(), vec!["user1"], 50),
-        ("uusd", "tokenB", "".to_string(), vec!["user2"], 50),
-    ];
-    let mut active_pools = vec![];
-    for (token1, token2, lp_token, stakers, alloc_points) in pools.iter_mut() {
-        let asset_infos = [AssetInfo::native(*token1), AssetInfo::native(*token2)];
-        let pair_info = helper.create_pair(&asset_infos).unwrap();
-        *lp_token = pair_info.liquidity_token.to_string();
-        active_pools.push((pair_info.liquidity_token.to_string(), *alloc_points));
-        let provide_assets = [
-            asset_infos[0].with_balance(100000u64),
-            asset_infos[1].with_balance(100000u64),
-        ];
-        // Owner provides liquidity first just to make following calculations easier
-        // since first depositor gets small cut of LP tokens
-        helper
-            .provide_liquidity(
-                &owner,
-                &provide_assets,
-                &pair_info.contract_addr,
-                false, // Owner doesn't stake in incentives
-            )
-            .unwrap();
+    helper.next_block(100);
 
-        for staker in stakers {
-            let staker_addr = TestAddr::new(staker);
-            // Pool doesn't exist in incentives yet
-            let astro_before = astro.query_pool(&helper.app.wrap(), &staker_addr).unwrap();
-            helper
-                .claim_rewards(
-                    &staker_addr,
-                    vec![
-                        pair_info.liquidity_token.to_string(),
-                        pair_info.liquidity_token.to_string(),
-                    ],
-                )
-                .unwrap_err();
-            let astro_after = astro.query_pool(&helper.app.wrap(), &staker_addr).unwrap();
-            assert_eq!((astro_after - astro_before).u128(), 0);
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].info, astro);
+    let required = pending[0].amount;
+    assert!(required > Uint128::new(1000));
 
-            helper
-                .provide_liquidity(
-                    &staker_addr,
-                    &provide_assets,
-                    &pair_info.contract_addr,
-                    true,
-                )
-                .unwrap();
+    // Claims exceeding the available amount fail by default
+    assert!(!helper.query_partial_vesting_payouts());
+    let err = helper
+        .claim_rewards(&user, vec![lp_token.clone()])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::VestingUnderfunded {
+            available: Uint128::new(1000),
+            required,
         }
-    }
+    );
 
-    helper.setup_pools(active_pools).unwrap();
-    helper.set_tokens_per_second(1_000000).unwrap();
-    helper
-        .app
-        .update_block(|block| block.time = block.time.plus_seconds(5));
-    let user1 = TestAddr::new("user1");
-    let astro_before = astro.query_pool(&helper.app.wrap(), &user1).unwrap();
     let err = helper
-        .claim_rewards(
-            &user1,
-            vec![
-                pools[0].2.to_string(),
-                pools[1].2.to_string(),
-                pools[0].2.to_string(),
-                pools[1].2.to_string(),
-            ],
-        )
+        .set_partial_vesting_payouts(&TestAddr::new("random"), true)
         .unwrap_err();
     assert_eq!(
         err.downcast::<ContractError>().unwrap(),
-        ContractError::DuplicatedPoolFound {}
+        ContractError::Unauthorized {}
+    );
+
+    helper.set_partial_vesting_payouts(&owner, true).unwrap();
+    assert!(helper.query_partial_vesting_payouts());
+
+    // The user receives what is available and the shortfall is owed
+    let resp = helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    assert!(resp.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "vesting_shortfall"
+            && attr.value == (required.u128() - 1000).to_string())));
+    assert_eq!(
+        helper
+            .app
+            .wrap()
+            .query_balance(&user, "astro")
+            .unwrap()
+            .amount
+            .u128(),
+        1000
     );
+    assert_eq!(helper.query_total_distributed().u128(), 1000);
+    assert!(helper
+        .query_pending_rewards(&user, &lp_token)
+        .iter()
+        .all(|asset| asset.amount.is_zero()));
+    let owed = required - Uint128::new(1000);
+    assert_eq!(helper.query_owed_rewards(&user), owed);
+
+    // Nothing is left in the vesting contract, claims still succeed and the debt grows
+    helper.next_block(10);
+    let pending = helper.query_pending_rewards(&user, &lp_token)[0].amount;
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    assert_eq!(helper.query_total_distributed().u128(), 1000);
+    let owed = owed + pending;
+    assert_eq!(helper.query_owed_rewards(&user), owed);
 
+    // Refill the vesting contract
+    helper.mint_coin(&owner, &coin(owed.u128(), "astro"));
     helper
-        .claim_rewards(&user1, vec![pools[0].2.to_string(), pools[1].2.to_string()])
+        .app
+        .execute_contract(
+            owner.clone(),
+            mock_vesting.clone(),
+            &astroport::vesting::ExecuteMsg::RegisterVestingAccounts {
+                vesting_accounts: vec![],
+            },
+            &coins(owed.u128(), "astro"),
+        )
         .unwrap();
-    let astro_after = astro.query_pool(&helper.app.wrap(), &user1).unwrap();
-    assert_eq!((astro_after - astro_before).u128(), 2_500000);
+
+    // The owed rewards are paid out on the next claim. It doesn't need any LP token
+    helper.claim_rewards(&user, vec![]).unwrap();
+    assert_eq!(
+        helper
+            .app
+            .wrap()
+            .query_balance(&user, "astro")
+            .unwrap()
+            .amount,
+        owed + Uint128::new(1000)
+    );
+    assert_eq!(helper.query_total_distributed(), owed + Uint128::new(1000));
+    assert!(helper.query_owed_rewards(&user).is_zero());
 }
 
 #[test]
-fn test_user_claim_less() {
+fn test_reward_vesting() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
     let owner = helper.owner.clone();
-    let incentivization_fee = helper.incentivization_fee.clone();
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
     let lp_token = pair_info.liquidity_token.to_string();
+
     let provide_assets = [
         asset_infos[0].with_balance(100000u64),
         asset_infos[1].with_balance(100000u64),
     ];
-
     // Owner provides liquidity first just to make following calculations easier
     // since first depositor gets small cut of LP tokens
     helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
-        .unwrap();
-
-    let user = TestAddr::new("user");
-    helper
-        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+        .provide_liquidity(&owner, &provide_assets, &pair_info.contract_addr, false)
         .unwrap();
 
-    let bank = TestAddr::new("bank");
-    let reward_asset_info = AssetInfo::native("reward");
-    let reward = reward_asset_info.with_balance(1000_000000u128);
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
 
-    // create reward schedule
-    helper.mint_assets(&bank, &[reward.clone()]);
-    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
-    helper.mint_coin(&bank, &incentivization_fee);
+    let err = helper
+        .set_reward_vesting_duration(&TestAddr::new("random"), &lp_token, 1000)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    let err = helper
+        .set_reward_vesting_duration(&owner, &lp_token, MAX_REWARD_VESTING_DURATION + 1)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RewardVestingDurationTooLong {}
+    );
     helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            schedule.clone(),
-            &[incentivization_fee.clone()],
-        )
+        .set_reward_vesting_duration(&owner, &lp_token, 1000)
         .unwrap();
 
-    helper.app.update_block(|block| {
-        block.time = Timestamp::from_seconds(internal_sch.next_epoch_start_ts)
-    });
-
-    // user claim, sets user index
-    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-
-    // finish 1st schedule, reward goes to FINISHED_REWARD_INDEXES
-    helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts + 1));
-
-    // create reward schedule again
-    helper.mint_assets(&bank, &[reward.clone()]);
-    let (schedule, internal_sch) = helper.create_schedule(&reward, 2).unwrap();
-    helper.mint_coin(&bank, &incentivization_fee);
+    let user = TestAddr::new("user");
     helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            schedule.clone(),
-            &[incentivization_fee.clone()],
-        )
+        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
-    // few seconds before schedule finishes
-    helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts - 1));
+    helper.next_block(100);
 
-    // user claim rewards as (global index - user index), which is incorrect
-    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    let claimed = pending[0].amount;
+    assert!(!claimed.is_zero());
 
-    // finish 2nd schedule
-    helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(internal_sch.end_ts + 1));
+    // The vesting contract must allow the incentives contract to register schedules
+    let err = helper
+        .claim_rewards(&user, vec![lp_token.clone()])
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+    helper.add_vesting_registrar().unwrap();
 
-    // user claim all rewards
+    // Claimed ASTRO appears as a vesting schedule rather than a direct balance
     helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    let user_astro = |helper: &Helper| {
+        helper
+            .app
+            .wrap()
+            .query_balance(&user, "astro")
+            .unwrap()
+            .amount
+    };
+    assert_eq!(user_astro(&helper), Uint128::zero());
 
-    // check user rewards
-    let new_reward_balance = reward_asset_info
-        .query_pool(&helper.app.wrap(), &user)
-        .unwrap();
+    let now = helper.app.block_info().time.seconds();
+    assert_eq!(
+        helper.query_vesting_account(&user).unwrap().info,
+        VestingInfo {
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: now,
+                    amount: Uint128::zero(),
+                },
+                end_point: Some(VestingSchedulePoint {
+                    time: now + 1000,
+                    amount: claimed,
+                }),
+            }],
+            released_amount: Uint128::zero(),
+        }
+    );
+    // The incentives contract doesn't hold vesting rewards
+    assert_eq!(
+        helper
+            .app
+            .wrap()
+            .query_balance(&helper.generator, "astro")
+            .unwrap()
+            .amount,
+        Uint128::zero()
+    );
 
+    // Half of the rewards vest in half of the duration
+    helper.next_block(500);
     assert_eq!(
-        new_reward_balance.u128(),
-        (reward.amount + reward.amount).u128() - 2 // rounding error
+        helper.query_vesting_claimable(&user),
+        claimed.multiply_ratio(1u8, 2u8)
     );
+    helper.claim_vesting(&user).unwrap();
+    assert_eq!(user_astro(&helper), claimed.multiply_ratio(1u8, 2u8));
+
+    // The rest is vested at the end of the duration
+    helper.next_block(500);
+    helper.claim_vesting(&user).unwrap();
+    assert_eq!(user_astro(&helper), claimed);
+
+    // Rewards are paid out instantly once vesting is disabled
+    helper
+        .set_reward_vesting_duration(&owner, &lp_token, 0)
+        .unwrap();
+    helper.next_block(100);
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    assert_eq!(user_astro(&helper), claimed + pending[0].amount);
 }
 
 #[test]
-fn test_broken_cw20_incentives() {
+fn test_pending_rewards_all() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let owner = helper.owner.clone();
     let incentivization_fee = helper.incentivization_fee.clone();
 
-    let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
-    let pair_info = helper.create_pair(&asset_infos).unwrap();
-    let lp_token = pair_info.liquidity_token.to_string();
-
-    let provide_assets = [
-        asset_infos[0].with_balance(100000u64),
-        asset_infos[1].with_balance(100000u64),
-    ];
-    // Owner provides liquidity first just make following calculations easier
-    // since first depositor gets small cut of LP tokens
-    helper
-        .provide_liquidity(
-            &owner,
-            &provide_assets,
-            &pair_info.contract_addr,
-            false, // Owner doesn't stake in incentives
-        )
+    let astro_pool = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("bar")])
         .unwrap();
+    let astro_lp = astro_pool.liquidity_token.to_string();
+    helper.setup_pools(vec![(astro_lp.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
 
-    let user = TestAddr::new("user");
-    helper
-        .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
+    // The second pool receives two external rewards only
+    let dual_pool = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("baz")])
         .unwrap();
-
+    let dual_lp = dual_pool.liquidity_token.to_string();
     let bank = TestAddr::new("bank");
-
-    let schedules: Vec<_> = (1..=2)
-        .into_iter()
-        .map(|i| {
-            let reward_asset_info = if i == 1 {
-                AssetInfo::native(format!("reward{i}"))
-            } else {
-                let reward_cw20 = helper.init_broken_cw20("reward", None);
-                AssetInfo::cw20(reward_cw20)
-            };
-
-            let reward = reward_asset_info.with_balance(1000_000000u128);
-            helper.create_schedule(&reward, 1).unwrap()
-        })
-        .collect();
-
-    // Create multiple schedules with different rewards
-    for (schedule, _) in &schedules {
+    let reward_infos = [AssetInfo::native("reward1"), AssetInfo::native("reward2")];
+    for reward_info in &reward_infos {
+        let (schedule, _) = helper
+            .create_schedule(&reward_info.with_balance(1000_000000u128), 1)
+            .unwrap();
         helper.mint_assets(&bank, &[schedule.reward.clone()]);
         helper.mint_coin(&bank, &incentivization_fee);
         helper
-            .incentivize(
-                &bank,
-                &lp_token,
-                schedule.clone(),
-                &[incentivization_fee.clone()],
-            )
+            .incentivize(&bank, &dual_lp, schedule, &[incentivization_fee.clone()])
             .unwrap();
     }
 
-    helper.app.update_block(|block| {
-        block.time = Timestamp::from_seconds(schedules[0].1.next_epoch_start_ts)
-    });
-
-    // Iterate by 1 day and claim rewards
-    loop {
-        if helper.app.block_info().time.seconds() > schedules[0].1.end_ts {
-            break;
-        }
-
-        helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
-
+    let user = TestAddr::new("user");
+    for pool in [&astro_pool, &dual_pool] {
+        let provide_assets = pool
+            .asset_infos
+            .iter()
+            .map(|info| info.with_balance(100000u64))
+            .collect_vec();
         helper
-            .app
-            .update_block(|block| block.time = block.time.plus_seconds(86400));
+            .provide_liquidity(&user, &provide_assets, &pool.contract_addr, true)
+            .unwrap();
     }
 
-    // Valid native coin reward was accrued properly
-    let valid_reward_balance = schedules[0]
-        .1
-        .reward_info
-        .query_pool(&helper.app.wrap(), &user)
+    helper.next_block(1000);
+
+    let pending_all = helper.query_pending_rewards_all(&user);
+    assert_eq!(pending_all.len(), 2);
+
+    let astro_pending = pending_all
+        .iter()
+        .find(|pending| pending.lp_token == astro_lp)
         .unwrap();
-    assert_eq!(valid_reward_balance.u128(), 999_999994);
+    assert_eq!(
+        vec![astro.with_balance(astro_pending.astro_pending)],
+        helper.query_pending_rewards(&user, &astro_lp)
+    );
+    assert!(!astro_pending.astro_pending.is_zero());
+    assert!(astro_pending.external_pending.is_empty());
 
-    // Broken cw20 reward was not accrued because incentives contract simply ignores it
-    let broken_reward_balance = schedules[1]
-        .1
-        .reward_info
-        .query_pool(&helper.app.wrap(), &user)
+    // All external reward tokens are itemized
+    let dual_pending = pending_all
+        .iter()
+        .find(|pending| pending.lp_token == dual_lp)
         .unwrap();
-    assert_eq!(broken_reward_balance.u128(), 0);
+    assert!(dual_pending.astro_pending.is_zero());
+    assert_eq!(
+        dual_pending
+            .external_pending
+            .iter()
+            .map(|asset| asset.info.clone())
+            .collect_vec(),
+        reward_infos.to_vec()
+    );
+    assert!(dual_pending
+        .external_pending
+        .iter()
+        .all(|asset| !asset.amount.is_zero()));
+    assert_eq!(
+        dual_pending.external_pending,
+        helper.query_pending_rewards(&user, &dual_lp)
+    );
+
+    // Users without positions have no pending rewards
+    assert!(helper
+        .query_pending_rewards_all(&TestAddr::new("random"))
+        .is_empty());
 }
 
 #[test]
-fn test_factory_deregisters_any_pool() {
+fn test_pools_containing_asset() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
-    let asset_infos = &[AssetInfo::native("usd"), AssetInfo::native("foo")];
 
-    // factory contract create pair
-    helper.create_pair(asset_infos).unwrap();
-    // ensure pair created
-    let pair_info = helper.query_pair_info(asset_infos);
-    assert_eq!(pair_info.asset_infos, asset_infos);
+    let foo_bar = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("bar")])
+        .unwrap()
+        .liquidity_token
+        .to_string();
+    let foo_baz = helper
+        .create_pair(&[AssetInfo::native("foo"), AssetInfo::native("baz")])
+        .unwrap()
+        .liquidity_token
+        .to_string();
+    let bar_baz = helper
+        .create_pair(&[AssetInfo::native("bar"), AssetInfo::native("baz")])
+        .unwrap()
+        .liquidity_token
+        .to_string();
+    helper
+        .setup_pools(vec![
+            (foo_bar.clone(), 100),
+            (foo_baz.clone(), 100),
+            (bar_baz.clone(), 100),
+        ])
+        .unwrap();
 
-    // Incentives contract doesn't have such pool yet but it doesn't block deregistration
-    helper.deactivate_pool_full_flow(asset_infos).unwrap();
+    let mut pools = helper.query_pools_containing_asset(&AssetInfo::native("foo"));
+    pools.sort();
+    let mut expected = vec![foo_bar.clone(), foo_baz.clone()];
+    expected.sort();
+    assert_eq!(pools, expected);
+
+    assert!(helper
+        .query_pools_containing_asset(&AssetInfo::native("unknown"))
+        .is_empty());
+
+    // Blocking the asset deactivates exactly the returned pools
+    let owner = helper.owner.clone();
+    helper
+        .block_tokens(&owner, &[AssetInfo::native("foo")])
+        .unwrap();
+    assert_eq!(
+        helper.active_pools(),
+        vec![(bar_baz.clone(), Uint128::new(100))]
+    );
+    assert!(helper
+        .query_pools_containing_asset(&AssetInfo::native("foo"))
+        .is_empty());
 }
 
 #[test]
-fn test_orphaned_rewards() {
+fn test_reward_recipient() {
     let astro = native_asset_info("astro".to_string());
     let mut helper = Helper::new("owner", &astro, false).unwrap();
+    let owner = helper.owner.clone();
     let incentivization_fee = helper.incentivization_fee.clone();
 
     let asset_infos = [AssetInfo::native("foo"), AssetInfo::native("bar")];
     let pair_info = helper.create_pair(&asset_infos).unwrap();
     let lp_token = pair_info.liquidity_token.to_string();
 
-    let bank = TestAddr::new("bank");
-
-    let schedules: Vec<_> = (1..=(MAX_REWARD_TOKENS - 1))
-        .into_iter()
-        .map(|i| {
-            let reward_asset_info = AssetInfo::native(format!("reward{i}"));
-            let reward = reward_asset_info.with_balance(1000_000000u128);
-            helper.create_schedule(&reward, 2).unwrap()
-        })
-        .collect();
-    // Create multiple schedules with different rewards
-    for (schedule, _) in &schedules {
-        helper.mint_assets(&bank, &[schedule.reward.clone()]);
-        helper.mint_coin(&bank, &incentivization_fee);
-        helper
-            .incentivize(
-                &bank,
-                &lp_token,
-                schedule.clone(),
-                &[incentivization_fee.clone()],
-            )
-            .unwrap();
-    }
-
-    // Timing out all schedules. nobody stakes LP tokens, rewards become orphaned
-    helper.app.update_block(|block| {
-        block.time = Timestamp::from_seconds(schedules.last().cloned().unwrap().1.end_ts + 1)
-    });
-
-    let orph_receiver = TestAddr::new("orphaned_rewards_receiver");
+    let provide_assets = [
+        asset_infos[0].with_balance(100000u64),
+        asset_infos[1].with_balance(100000u64),
+    ];
+    // Owner provides liquidity first just to make following calculations easier
+    // since first depositor gets small cut of LP tokens
+    helper
+        .provide_liquidity(&owner, &provide_assets, &pair_info.contract_addr, false)
+        .unwrap();
 
-    // Check that there are still no orphaned rewards to claim
-    let err = helper
-        .claim_orphaned_rewards(None, &orph_receiver)
-        .unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::NoOrphanedRewards {}
-    );
+    helper.setup_pools(vec![(lp_token.clone(), 100)]).unwrap();
+    helper.set_tokens_per_second(100).unwrap();
 
-    // Add one more reward thus triggering finished rewards cleanup
-    let reward =
-        AssetInfo::native(format!("reward{MAX_REWARD_TOKENS}")).with_balance(1000_000000u128);
-    let (new_schedule, int_new_schedule) = helper.create_schedule(&reward, 2).unwrap();
-    helper.mint_assets(&bank, &[reward]);
+    let bank = TestAddr::new("bank");
+    let reward_info = AssetInfo::native("reward");
+    let (schedule, _) = helper
+        .create_schedule(&reward_info.with_balance(1000_000000u128), 1)
+        .unwrap();
+    helper.mint_assets(&bank, &[schedule.reward.clone()]);
     helper.mint_coin(&bank, &incentivization_fee);
     helper
-        .incentivize(
-            &bank,
-            &lp_token,
-            new_schedule,
-            &[incentivization_fee.clone()],
-        )
+        .incentivize(&bank, &lp_token, schedule, &[incentivization_fee.clone()])
         .unwrap();
 
-    // Provide to check that user is only eligible for the last added reward
-    let provide_assets = [
-        asset_infos[0].with_balance(100000u64),
-        asset_infos[1].with_balance(100000u64),
-    ];
     let user = TestAddr::new("user");
+    let cold_wallet = TestAddr::new("cold_wallet");
     helper
         .provide_liquidity(&user, &provide_assets, &pair_info.contract_addr, true)
         .unwrap();
 
+    // Rewards go to the staker by default
+    assert_eq!(helper.query_reward_recipient(&user), user);
+
     helper
-        .app
-        .update_block(|block| block.time = Timestamp::from_seconds(int_new_schedule.end_ts + 1));
+        .set_reward_recipient(&user, Some(&cold_wallet))
+        .unwrap();
+    assert_eq!(helper.query_reward_recipient(&user), cold_wallet);
 
-    // Claim rewards and assert user only gets reward5
-    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    helper.next_block(100);
 
-    for (schedule, _) in &schedules {
-        let reward_balance = schedule
-            .reward
-            .info
-            .query_pool(&helper.app.wrap(), &user)
-            .unwrap();
-        assert_eq!(reward_balance.u128(), 0);
-    }
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    assert_eq!(pending.len(), 2);
+    assert!(pending.iter().all(|asset| !asset.amount.is_zero()));
 
-    let reward_balance = int_new_schedule
-        .reward_info
-        .query_pool(&helper.app.wrap(), &user)
-        .unwrap();
-    assert_eq!(reward_balance.u128(), 999_999999);
+    let balance = |helper: &Helper, addr: &Addr, denom: &str| {
+        helper.app.wrap().query_balance(addr, denom).unwrap().amount
+    };
 
-    // Owner claims first orphaned rewards
-    helper
-        .claim_orphaned_rewards(Some(1), &orph_receiver)
-        .unwrap();
+    // ASTRO and external rewards are routed to the recipient
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    for asset in &pending {
+        let denom = asset.info.to_string();
+        assert_eq!(balance(&helper, &cold_wallet, &denom), asset.amount);
+        assert_eq!(balance(&helper, &user, &denom), Uint128::zero());
+    }
 
-    // Owner claims all orphaned rewards
-    helper.claim_orphaned_rewards(None, &orph_receiver).unwrap();
+    // Unsetting the recipient sends rewards to the staker again
+    helper.set_reward_recipient(&user, None).unwrap();
+    assert_eq!(helper.query_reward_recipient(&user), user);
 
-    for (schedule, _) in &schedules {
-        let reward_balance = schedule
-            .reward
-            .info
-            .query_pool(&helper.app.wrap(), &orph_receiver)
-            .unwrap();
-        assert_eq!(reward_balance.u128(), 999999999);
+    helper.next_block(100);
+    let pending = helper.query_pending_rewards(&user, &lp_token);
+    helper.claim_rewards(&user, vec![lp_token.clone()]).unwrap();
+    for asset in &pending {
+        assert_eq!(
+            balance(&helper, &user, &asset.info.to_string()),
+            asset.amount
+        );
     }
-
-    // Try to claim again
-    let err = helper
-        .claim_orphaned_rewards(None, &orph_receiver)
-        .unwrap_err();
-    assert_eq!(
-        err.downcast::<ContractError>().unwrap(),
-        ContractError::NoOrphanedRewards {}
-    );
 }
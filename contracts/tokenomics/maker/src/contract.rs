@@ -3,29 +3,34 @@ use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use cosmwasm_std::{
-    attr, ensure, entry_point, to_json_binary, to_json_string, Addr, Attribute, Binary, Decimal,
-    Deps, DepsMut, Env, MessageInfo, Order, ReplyOn, Response, StdError, StdResult, SubMsg,
-    Uint128, Uint64,
+    attr, coins, ensure, entry_point, to_json_binary, to_json_string, wasm_execute, Addr,
+    Attribute, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, ReplyOn, Response,
+    StdError, StdResult, SubMsg, Uint128, Uint64,
 };
 use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 
 use astroport::asset::{addr_opt_validate, Asset, AssetInfo, AssetInfoExt};
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::factory::UpdateAddr;
 use astroport::maker::{
     AssetWithLimit, BalancesResponse, Config, ConfigResponse, ExecuteMsg, InstantiateMsg,
-    MigrateMsg, QueryMsg, SecondReceiverConfig, SecondReceiverParams, UpdateDevFundConfig,
+    MigrateMsg, QueryMsg, SecondReceiverConfig, SecondReceiverParams, StatusResponse,
+    UpdateDevFundConfig,
 };
 use astroport::pair::MAX_ALLOWED_SLIPPAGE;
 
 use crate::error::ContractError;
 use crate::migration::migrate_from_v120_plus;
 use crate::reply::PROCESS_DEV_FUND_REPLY_ID;
-use crate::state::{BRIDGES, CONFIG, LAST_COLLECT_TS, OWNERSHIP_PROPOSAL};
+use crate::state::{
+    CollectMinAstroOut, BRIDGES, COLLECT_MIN_ASTRO_OUT, CONFIG, DISTRIBUTE_AS_XASTRO,
+    LAST_COLLECT_TS, MAX_COLLECT_ASSETS, MAX_LIMIT, OWNERSHIP_PROPOSAL,
+};
 use crate::utils::{
-    build_distribute_msg, build_send_msg, build_swap_msg, get_pool, try_build_swap_msg,
-    update_second_receiver_cfg, validate_bridge, validate_cooldown, BRIDGES_EXECUTION_MAX_DEPTH,
-    BRIDGES_INITIAL_DEPTH,
+    build_distribute_msg, build_send_msg, build_swap_msg, get_pool, simulate_swap_to_astro,
+    try_build_swap_msg, update_second_receiver_cfg, validate_bridge, validate_cooldown,
+    BRIDGES_EXECUTION_MAX_DEPTH, BRIDGES_INITIAL_DEPTH, ESTIMATED_SWAP_GAS,
 };
 
 /// Contract name that is used for migration.
@@ -149,7 +154,7 @@ pub fn instantiate(
 /// Exposes execute functions available in the contract.
 ///
 /// ## Variants
-/// * **ExecuteMsg::Collect { assets }** Swaps collected fee tokens to ASTRO
+/// * **ExecuteMsg::Collect { assets, min_astro_out }** Swaps collected fee tokens to ASTRO
 /// and distributes the ASTRO between xASTRO and vxASTRO stakers.
 ///
 /// * **ExecuteMsg::UpdateConfig {
@@ -174,6 +179,14 @@ pub fn instantiate(
 /// * **ExecuteMsg::ClaimOwnership {}** Claims contract ownership.
 ///
 /// * **ExecuteMsg::EnableRewards** Enables collected ASTRO (pre Maker upgrade) to be distributed to xASTRO stakers.
+///
+/// * **ExecuteMsg::RecoverAsset { asset_info, recipient }** Transfers out an asset which can't be swapped to ASTRO.
+///
+/// * **ExecuteMsg::SetDistributeAsXastro { distribute_as_xastro }** Enables or disables distributing
+/// the governance share as xASTRO.
+///
+/// * **ExecuteMsg::SetMaxCollectAssets { max_assets }** Sets or removes the maximum number of assets
+/// per collect call.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -182,7 +195,10 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Collect { assets } => collect(deps, env, assets),
+        ExecuteMsg::Collect {
+            assets,
+            min_astro_out,
+        } => collect(deps, env, assets, min_astro_out),
         ExecuteMsg::UpdateConfig {
             factory_contract,
             staking_contract,
@@ -270,16 +286,89 @@ pub fn execute(
 
             Ok(Response::default().add_attribute("action", "enable_rewards"))
         }
+        ExecuteMsg::RecoverAsset {
+            asset_info,
+            recipient,
+        } => recover_asset(deps, env, info, asset_info, recipient),
+        ExecuteMsg::SetDistributeAsXastro {
+            distribute_as_xastro,
+        } => set_distribute_as_xastro(deps, info, distribute_as_xastro),
+        ExecuteMsg::SetMaxCollectAssets { max_assets } => {
+            set_max_collect_assets(deps, info, max_assets)
+        }
+    }
+}
+
+/// Enables or disables staking the governance share into xASTRO before sending it to the
+/// governance contract. Only the owner can execute this.
+fn set_distribute_as_xastro(
+    deps: DepsMut,
+    info: MessageInfo,
+    distribute_as_xastro: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if distribute_as_xastro {
+        ensure!(
+            config.astro_token.is_native_token()
+                && config.staking_contract.is_some()
+                && config.governance_contract.is_some(),
+            ContractError::XastroDistributionUnavailable {}
+        );
+        DISTRIBUTE_AS_XASTRO.save(deps.storage, &true)?;
+    } else {
+        DISTRIBUTE_AS_XASTRO.remove(deps.storage);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_distribute_as_xastro"),
+        attr("distribute_as_xastro", distribute_as_xastro.to_string()),
+    ]))
+}
+
+/// Sets or removes the maximum number of assets a single collect call can swap.
+/// Only the owner can execute this.
+fn set_max_collect_assets(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_assets: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match max_assets {
+        Some(max_assets) => {
+            ensure!(max_assets > 0, ContractError::InvalidMaxCollectAssets {});
+            MAX_COLLECT_ASSETS.save(deps.storage, &max_assets)?;
+        }
+        None => MAX_COLLECT_ASSETS.remove(deps.storage),
     }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_max_collect_assets"),
+        attr(
+            "max_assets",
+            max_assets.map_or_else(|| "none".to_string(), |max| max.to_string()),
+        ),
+    ]))
 }
 
 /// Swaps fee tokens to ASTRO and distribute the resulting ASTRO to xASTRO and vxASTRO stakers.
 ///
 /// * **assets** array with fee tokens being swapped to ASTRO.
+///
+/// * **min_astro_out** minimum total amount of ASTRO the swaps must produce.
+/// ASTRO held by the Maker before the collect is excluded, so only swap output is checked.
 fn collect(
     deps: DepsMut,
     env: Env,
     assets: Vec<AssetWithLimit>,
+    min_astro_out: Option<Uint128>,
 ) -> Result<Response, ContractError> {
     let mut cfg = CONFIG.load(deps.storage)?;
 
@@ -305,6 +394,28 @@ fn collect(
         return Err(ContractError::DuplicatedAsset {});
     }
 
+    if let Some(max_assets) = MAX_COLLECT_ASSETS.may_load(deps.storage)? {
+        ensure!(
+            assets.len() <= max_assets as usize,
+            ContractError::TooManyCollectAssets {
+                max_assets,
+                assets: assets.len(),
+            }
+        );
+    }
+
+    // The floor is checked right before the resulting ASTRO is distributed
+    if let Some(min_astro_out) = min_astro_out {
+        let astro_balance_before = astro.query_pool(&deps.querier, &env.contract.address)?;
+        COLLECT_MIN_ASTRO_OUT.save(
+            deps.storage,
+            &CollectMinAstroOut {
+                astro_balance_before,
+                min_astro_out,
+            },
+        )?;
+    }
+
     // Swap all non ASTRO tokens
     let (mut response, bridge_assets) = swap_assets(
         deps.as_ref(),
@@ -531,6 +642,18 @@ fn distribute(
     let mut amount = cfg
         .astro_token
         .query_pool(&deps.querier, &env.contract.address)?;
+
+    if let Some(check) = COLLECT_MIN_ASTRO_OUT.may_load(deps.storage)? {
+        COLLECT_MIN_ASTRO_OUT.remove(deps.storage);
+        let astro_out = amount.saturating_sub(check.astro_balance_before);
+        if astro_out < check.min_astro_out {
+            return Err(ContractError::MinAstroOutNotReached {
+                astro_out,
+                min_astro_out: check.min_astro_out,
+            });
+        }
+    }
+
     if amount.is_zero() {
         return Ok((result, attributes));
     }
@@ -599,15 +722,35 @@ fn distribute(
             .checked_sub(second_receiver_amount)?
             .multiply_ratio(Uint128::from(cfg.governance_percent), Uint128::new(100));
 
+        let distribute_as_xastro = DISTRIBUTE_AS_XASTRO
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+
         if !amount.is_zero() {
-            result.push(SubMsg::new(build_send_msg(
-                &Asset {
-                    info: cfg.astro_token.clone(),
-                    amount,
-                },
-                governance_contract.to_string(),
-                None,
-            )?))
+            match (
+                distribute_as_xastro,
+                &cfg.staking_contract,
+                &cfg.astro_token,
+            ) {
+                (true, Some(staking_contract), AssetInfo::NativeToken { denom }) => {
+                    // Stake ASTRO and send the minted xASTRO directly to the governance contract
+                    result.push(SubMsg::new(wasm_execute(
+                        staking_contract,
+                        &astroport::staking::ExecuteMsg::Enter {
+                            receiver: Some(governance_contract.to_string()),
+                        },
+                        coins(amount.u128(), denom),
+                    )?))
+                }
+                _ => result.push(SubMsg::new(build_send_msg(
+                    &Asset {
+                        info: cfg.astro_token.clone(),
+                        amount,
+                    },
+                    governance_contract.to_string(),
+                    None,
+                )?)),
+            }
         }
 
         amount
@@ -870,6 +1013,53 @@ fn update_bridges(
     Ok(Response::default().add_attribute("action", "update_bridges"))
 }
 
+/// Transfers the whole balance of an asset which has no swap route to ASTRO.
+///
+/// * **asset_info** asset to recover.
+///
+/// * **recipient** address which receives the recovered asset.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn recover_asset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    asset_info.check(deps.api)?;
+
+    let balance = asset_info.query_pool(&deps.querier, &env.contract.address)?;
+    if balance.is_zero() {
+        return Err(StdError::generic_err(format!("Maker has no {asset_info} to recover")).into());
+    }
+
+    // Assets which can reach ASTRO must go through the regular distribution
+    if asset_info == cfg.astro_token
+        || swap(deps.as_ref(), &cfg, asset_info.clone(), balance).is_ok()
+    {
+        return Err(ContractError::AssetConvertible(asset_info.to_string()));
+    }
+
+    Ok(Response::new()
+        .add_message(asset_info.with_balance(balance).into_msg(&recipient)?)
+        .add_attributes([
+            attr("action", "recover_asset"),
+            attr("asset", asset_info.to_string()),
+            attr("amount", balance),
+            attr("recipient", recipient),
+        ]))
+}
+
 /// Exposes all the queries available in the contract.
 ///
 /// ## Queries
@@ -880,12 +1070,39 @@ fn update_bridges(
 ///
 /// * **QueryMsg::Bridges {}** Returns the bridges used for swapping fee tokens
 /// using a vector of [`(String, String)`] denoting Asset -> Bridge connections.
+///
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
+///
+/// * **QueryMsg::Status {}** Returns the fee split configuration along with the current ASTRO balance
+/// using a [`StatusResponse`] object.
+///
+/// * **QueryMsg::DistributeAsXastro {}** Returns whether the governance share is distributed as xASTRO.
+///
+/// * **QueryMsg::MaxCollectAssets {}** Returns the maximum number of assets per collect call if it is set.
+///
+/// * **QueryMsg::WorthCollecting { gas_price }** Returns the native fee token balances whose swap to ASTRO
+/// outweighs the estimated gas cost at the specified gas price.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_get_config(deps)?),
         QueryMsg::Balances { assets } => to_json_binary(&query_get_balances(deps, env, assets)?),
-        QueryMsg::Bridges {} => to_json_binary(&query_bridges(deps)?),
+        QueryMsg::Bridges { start_after, limit } => {
+            to_json_binary(&query_bridges(deps, start_after, limit)?)
+        }
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::Status {} => to_json_binary(&query_status(deps, env)?),
+        QueryMsg::DistributeAsXastro {} => to_json_binary(
+            &DISTRIBUTE_AS_XASTRO
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::MaxCollectAssets {} => {
+            to_json_binary(&MAX_COLLECT_ASSETS.may_load(deps.storage)?)
+        }
+        QueryMsg::WorthCollecting { gas_price } => {
+            to_json_binary(&query_worth_collecting(deps, env, gas_price)?)
+        }
     }
 }
 
@@ -908,6 +1125,23 @@ fn query_get_config(deps: Deps) -> StdResult<ConfigResponse> {
     })
 }
 
+/// Returns the fee split configuration and Maker's ASTRO balance using a [`StatusResponse`] object.
+fn query_status(deps: Deps, env: Env) -> StdResult<StatusResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let astro_balance = config
+        .astro_token
+        .query_pool(&deps.querier, &env.contract.address)?;
+
+    Ok(StatusResponse {
+        governance_percent: config.governance_percent,
+        max_spread: config.max_spread,
+        astro_token: config.astro_token,
+        staking_contract: config.staking_contract,
+        governance_contract: config.governance_contract,
+        astro_balance,
+    })
+}
+
 /// Returns Maker's fee token balances for specific tokens using a [`BalancesResponse`] object.
 ///
 /// * **assets** array with assets for which we query the Maker's balances.
@@ -928,10 +1162,53 @@ fn query_get_balances(deps: Deps, env: Env, assets: Vec<AssetInfo>) -> StdResult
     Ok(resp)
 }
 
+/// Returns the native fee token balances whose simulated ASTRO output exceeds the estimated gas
+/// cost of their swap route. Balances which can't be swapped to ASTRO are skipped.
+///
+/// * **gas_price** is the price of one gas unit denominated in ASTRO.
+fn query_worth_collecting(deps: Deps, env: Env, gas_price: Uint128) -> StdResult<Vec<Asset>> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut worth_collecting = vec![];
+
+    for coin in deps.querier.query_all_balances(&env.contract.address)? {
+        let asset = Asset::native(coin.denom, coin.amount);
+        if asset.info == cfg.astro_token {
+            continue;
+        }
+
+        if let Ok((astro_out, swaps)) =
+            simulate_swap_to_astro(deps, &cfg, &asset.info, asset.amount, BRIDGES_INITIAL_DEPTH)
+        {
+            let gas_cost = gas_price.checked_mul(Uint128::from(ESTIMATED_SWAP_GAS * swaps))?;
+            if astro_out > gas_cost {
+                worth_collecting.push(asset);
+            }
+        }
+    }
+
+    Ok(worth_collecting)
+}
+
 /// Returns bridge tokens used for swapping fee tokens to ASTRO.
-fn query_bridges(deps: Deps) -> StdResult<Vec<(String, String)>> {
+///
+/// * **start_after** is the asset from which the function starts to fetch results.
+///
+/// * **limit** is the number of items to retrieve. All bridges are returned if not set.
+fn query_bridges(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, String)>> {
+    let limit = limit.map_or(usize::MAX, |limit| limit.min(MAX_LIMIT) as usize);
+
     BRIDGES
-        .range(deps.storage, None, None, Order::Ascending)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
         .map(|bridge| {
             let (bridge, asset) = bridge?;
             Ok((bridge, asset.to_string()))
@@ -1,5 +1,5 @@
 use astroport::asset::AssetInfo;
-use cosmwasm_std::{DivideByZeroError, OverflowError, StdError};
+use cosmwasm_std::{DivideByZeroError, OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 /// This enum describes maker contract errors
@@ -49,6 +49,24 @@ pub enum ContractError {
 
     #[error("Incorrect cooldown. Min: {min}, Max: {max}")]
     IncorrectCooldown { min: u64, max: u64 },
+
+    #[error("Asset {0} can be swapped to ASTRO thus it can't be recovered")]
+    AssetConvertible(String),
+
+    #[error("xASTRO distribution requires native ASTRO, the staking and the governance contracts")]
+    XastroDistributionUnavailable {},
+
+    #[error("Collect accepts at most {max_assets} assets, got {assets}")]
+    TooManyCollectAssets { max_assets: u32, assets: usize },
+
+    #[error("The maximum number of assets per collect must be greater than zero")]
+    InvalidMaxCollectAssets {},
+
+    #[error("Collect produced {astro_out} ASTRO which is less than the minimum {min_astro_out}")]
+    MinAstroOutNotReached {
+        astro_out: Uint128,
+        min_astro_out: Uint128,
+    },
 }
 
 impl From<OverflowError> for ContractError {
@@ -1,6 +1,8 @@
 use astroport::asset::AssetInfo;
 use astroport::common::OwnershipProposal;
 use astroport::maker::Config;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
 use cw_storage_plus::{Item, Map};
 
 /// Stores the contract configuration at the given key
@@ -11,5 +13,28 @@ pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_pro
 
 /// Stores bridge tokens used to swap fee tokens to ASTRO
 pub const BRIDGES: Map<String, AssetInfo> = Map::new("bridges");
+
+/// ## Pagination settings
+/// The maximum limit for reading bridges from [`BRIDGES`]
+pub const MAX_LIMIT: u32 = 30;
+
+/// Whether the governance share is staked and distributed as xASTRO instead of ASTRO
+pub const DISTRIBUTE_AS_XASTRO: Item<bool> = Item::new("distribute_as_xastro");
+
+/// The maximum number of assets a single collect call can swap. Not limited if not set
+pub const MAX_COLLECT_ASSETS: Item<u32> = Item::new("max_collect_assets");
+
 /// Stores the latest timestamp when fees were collected
 pub const LAST_COLLECT_TS: Item<u64> = Item::new("last_collect_ts");
+
+/// The ASTRO output floor of the collect currently being processed
+#[cw_serde]
+pub struct CollectMinAstroOut {
+    /// The Maker ASTRO balance before fee tokens were swapped
+    pub astro_balance_before: Uint128,
+    /// The minimum amount of ASTRO the swaps must produce
+    pub min_astro_out: Uint128,
+}
+
+/// Stores the ASTRO output floor between the collect call and the final ASTRO distribution
+pub const COLLECT_MIN_ASTRO_OUT: Item<CollectMinAstroOut> = Item::new("collect_min_astro_out");
@@ -9,7 +9,7 @@ use astroport::maker::{
     Config, ExecuteMsg, SecondReceiverConfig, SecondReceiverParams, COOLDOWN_LIMITS,
     MAX_SECOND_RECEIVER_CUT,
 };
-use astroport::pair::Cw20HookMsg;
+use astroport::pair::{Cw20HookMsg, QueryMsg as PairQueryMsg, SimulationResponse};
 use astroport::querier::query_pair_info;
 
 use crate::error::ContractError;
@@ -21,6 +21,8 @@ pub const BRIDGES_INITIAL_DEPTH: u64 = 0;
 pub const BRIDGES_MAX_DEPTH: u64 = 2;
 /// Swap execution depth limit
 pub const BRIDGES_EXECUTION_MAX_DEPTH: u64 = 5;
+/// The estimated gas spent by a single swap on a collect route
+pub const ESTIMATED_SWAP_GAS: u64 = 300_000;
 
 /// The function checks from<>to pool exists and creates swap message.
 ///
@@ -73,6 +75,8 @@ pub fn build_swap_msg(
                 belief_price: None,
                 max_spread: Some(max_spread),
                 to: None,
+                strict_belief_price: false,
+                referral: None,
             })?,
             funds: vec![offer_asset.as_coin()?],
         }))
@@ -87,6 +91,8 @@ pub fn build_swap_msg(
                     belief_price: None,
                     max_spread: Some(max_spread),
                     to: None,
+                    strict_belief_price: false,
+                    referral: None,
                 })?,
             })?,
             funds: vec![],
@@ -201,6 +207,73 @@ pub fn get_pool(
     .map_err(|_| ContractError::InvalidBridgeNoPool(from.to_string(), to.to_string()))
 }
 
+/// Returns the pool and the asset which collect swaps `from` to on the way to ASTRO.
+/// The route is chosen in the same order as in collect: a configured bridge, the default bridge
+/// and a direct pool with ASTRO.
+fn next_hop(
+    deps: Deps,
+    cfg: &Config,
+    from: &AssetInfo,
+) -> Result<(PairInfo, AssetInfo), ContractError> {
+    if let Some(bridge) = BRIDGES.may_load(deps.storage, from.to_string())? {
+        let pool = get_pool(&deps.querier, &cfg.factory_contract, from, &bridge)?;
+        return Ok((pool, bridge));
+    }
+
+    if let Some(default_bridge) = &cfg.default_bridge {
+        if from != default_bridge {
+            if let Ok(pool) = get_pool(&deps.querier, &cfg.factory_contract, from, default_bridge) {
+                return Ok((pool, default_bridge.clone()));
+            }
+        }
+    }
+
+    get_pool(&deps.querier, &cfg.factory_contract, from, &cfg.astro_token)
+        .map(|pool| (pool, cfg.astro_token.clone()))
+        .map_err(|_| ContractError::CannotSwap(from.clone()))
+}
+
+/// Simulates swapping a fee token to ASTRO along the route used by collect.
+/// Returns the simulated ASTRO amount and the number of swaps on the route.
+///
+/// * **from** asset we want to swap.
+///
+/// * **amount_in** amount of tokens to swap.
+///
+/// * **depth** current depth of the route. It is intended to prevent dead loops in recursive calls.
+pub fn simulate_swap_to_astro(
+    deps: Deps,
+    cfg: &Config,
+    from: &AssetInfo,
+    amount_in: Uint128,
+    depth: u64,
+) -> Result<(Uint128, u64), ContractError> {
+    if from == &cfg.astro_token {
+        return Ok((amount_in, 0));
+    }
+
+    if depth >= BRIDGES_EXECUTION_MAX_DEPTH {
+        return Err(ContractError::MaxBridgeDepth(depth));
+    }
+
+    let (pool, to) = next_hop(deps, cfg, from)?;
+    let simulation: SimulationResponse = deps.querier.query_wasm_smart(
+        &pool.contract_addr,
+        &PairQueryMsg::Simulation {
+            offer_asset: Asset {
+                info: from.clone(),
+                amount: amount_in,
+            },
+            ask_asset_info: Some(to.clone()),
+        },
+    )?;
+
+    let (astro_out, swaps) =
+        simulate_swap_to_astro(deps, cfg, &to, simulation.return_amount, depth + 1)?;
+
+    Ok((astro_out, swaps + 1))
+}
+
 /// For native tokens of type [`AssetInfo`] uses method [`astro_satellite_package::ExecuteMsg::TransferAstro`]
 /// to send a token amount to a recipient.
 ///
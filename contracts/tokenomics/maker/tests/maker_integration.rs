@@ -7,7 +7,7 @@ use astroport_test::cw_multi_test::{
 };
 use astroport_test::modules::stargate::{MockStargate, StargateApp as TestApp};
 use cosmwasm_std::{
-    attr, coin, to_json_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env,
+    attr, coin, to_json_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env,
     MessageInfo, QueryRequest, Response, StdResult, Uint128, Uint64, WasmQuery,
 };
 use cw20::{BalanceResponse, Cw20QueryMsg, MinterResponse};
@@ -20,7 +20,8 @@ use astroport::asset::{
 use astroport::factory::{PairConfig, PairType, UpdateAddr};
 use astroport::maker::{
     AssetWithLimit, BalancesResponse, ConfigResponse, DevFundConfig, ExecuteMsg, InstantiateMsg,
-    QueryMsg, SecondReceiverConfig, SecondReceiverParams, UpdateDevFundConfig, COOLDOWN_LIMITS,
+    QueryMsg, SecondReceiverConfig, SecondReceiverParams, StatusResponse, UpdateDevFundConfig,
+    COOLDOWN_LIMITS,
 };
 use astroport_maker::error::ContractError;
 use cw20_base::msg::InstantiateMsg as TokenInstantiateMsg;
@@ -115,6 +116,36 @@ fn mock_fee_distributor_contract() -> Box<dyn Contract<Empty>> {
     ))
 }
 
+/// Staking mock which exchanges ASTRO for xASTRO 1:1 out of its pre-minted xASTRO balance
+fn mock_staking_contract() -> Box<dyn Contract<Empty>> {
+    let instantiate = |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+        Ok(Default::default())
+    };
+    let execute = |_: DepsMut,
+                   _: Env,
+                   info: MessageInfo,
+                   msg: astroport::staking::ExecuteMsg|
+     -> StdResult<Response> {
+        match msg {
+            astroport::staking::ExecuteMsg::Enter { receiver } => {
+                let amount = info.funds[0].amount;
+                Ok(Response::new().add_message(BankMsg::Send {
+                    to_address: receiver.unwrap_or_else(|| info.sender.to_string()),
+                    amount: vec![coin(amount.u128(), "xastro")],
+                }))
+            }
+            _ => unimplemented!(),
+        }
+    };
+    let empty_query = |_: Deps, _: Env, _: Empty| -> StdResult<Binary> { unimplemented!() };
+
+    Box::new(ContractWrapper::new_with_empty(
+        execute,
+        instantiate,
+        empty_query,
+    ))
+}
+
 fn instantiate_contracts(
     mut router: &mut TestApp,
     owner: Addr,
@@ -791,7 +822,10 @@ fn test_maker_collect(
         .execute_contract(
             Addr::unchecked("anyone"),
             maker_instance.clone(),
-            &ExecuteMsg::Collect { assets },
+            &ExecuteMsg::Collect {
+                assets,
+                min_astro_out: None,
+            },
             &[],
         )
         .unwrap();
@@ -1226,7 +1260,10 @@ fn collect_err_no_swap_pair() {
         )
         .unwrap();
 
-    let msg = ExecuteMsg::Collect { assets };
+    let msg = ExecuteMsg::Collect {
+        assets,
+        min_astro_out: None,
+    };
 
     let e = router
         .execute_contract(maker_instance.clone(), maker_instance.clone(), &msg, &[])
@@ -1238,6 +1275,129 @@ fn collect_err_no_swap_pair() {
     );
 }
 
+#[test]
+fn recover_asset() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uabc".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "ukrt".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+    let user = Addr::unchecked("user0000");
+    let staking = Addr::unchecked("staking");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking,
+        Uint64::new(50),
+        None,
+        None,
+        None,
+        None,
+    );
+
+    // Only ukrt has a swap route to ASTRO
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            native_asset("ukrt".to_string(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .send_tokens(
+            owner.clone(),
+            maker_instance.clone(),
+            &[coin(20, "ukrt"), coin(30, "uabc")],
+        )
+        .unwrap();
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        astro_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(10),
+    );
+
+    let recipient = Addr::unchecked("recipient");
+    let recover_msg = |asset_info: AssetInfo| ExecuteMsg::RecoverAsset {
+        asset_info,
+        recipient: recipient.to_string(),
+    };
+
+    let err = router
+        .execute_contract(
+            user.clone(),
+            maker_instance.clone(),
+            &recover_msg(native_asset_info("uabc".to_string())),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // Assets which can be swapped to ASTRO as well as ASTRO itself can't be recovered
+    for asset_info in [
+        native_asset_info("ukrt".to_string()),
+        token_asset_info(astro_token_instance.clone()),
+    ] {
+        let err = router
+            .execute_contract(
+                owner.clone(),
+                maker_instance.clone(),
+                &recover_msg(asset_info.clone()),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::AssetConvertible(asset_info.to_string()),
+            err.downcast().unwrap()
+        );
+    }
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &recover_msg(native_asset_info("uabc".to_string())),
+            &[],
+        )
+        .unwrap();
+    let recipient_balance = router.wrap().query_balance(&recipient, "uabc").unwrap();
+    assert_eq!(recipient_balance.amount.u128(), 30);
+    let maker_balance = router
+        .wrap()
+        .query_balance(&maker_instance, "uabc")
+        .unwrap();
+    assert!(maker_balance.amount.is_zero());
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &recover_msg(native_asset_info("uabc".to_string())),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Maker has no uabc to recover"
+    );
+}
+
 #[test]
 fn update_bridges() {
     let owner = Addr::unchecked("owner");
@@ -1355,7 +1515,11 @@ fn update_bridges() {
         .wrap()
         .query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: maker_instance.to_string(),
-            msg: to_json_binary(&QueryMsg::Bridges {}).unwrap(),
+            msg: to_json_binary(&QueryMsg::Bridges {
+                start_after: None,
+                limit: None,
+            })
+            .unwrap(),
         }))
         .unwrap();
 
@@ -1367,6 +1531,31 @@ fn update_bridges() {
         ]
     );
 
+    // Paginate over bridges
+    let resp: Vec<(String, String)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::Bridges {
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp, vec![(String::from("ukrt"), String::from("uusd"))]);
+
+    let resp: Vec<(String, String)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::Bridges {
+                start_after: Some(String::from("ukrt")),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp, vec![(String::from("uluna"), String::from("uusd"))]);
+
     let msg = ExecuteMsg::UpdateBridges {
         remove: Some(vec![native_asset_info(String::from("ukrt"))]),
         add: None,
@@ -1381,7 +1570,11 @@ fn update_bridges() {
         .wrap()
         .query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: maker_instance.to_string(),
-            msg: to_json_binary(&QueryMsg::Bridges {}).unwrap(),
+            msg: to_json_binary(&QueryMsg::Bridges {
+                start_after: None,
+                limit: None,
+            })
+            .unwrap(),
         }))
         .unwrap();
 
@@ -1598,6 +1791,7 @@ fn collect_with_asset_limit() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets_with_duplicate.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -1613,6 +1807,7 @@ fn collect_with_asset_limit() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -1902,6 +2097,7 @@ fn collect_with_second_receiver() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2053,6 +2249,7 @@ fn test_collect_cooldown() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2068,6 +2265,7 @@ fn test_collect_cooldown() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2086,10 +2284,69 @@ fn test_collect_cooldown() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
+            },
+            &[],
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_status() {
+    let owner = Addr::unchecked("owner");
+    let staking = Addr::unchecked("staking");
+    let mut router = mock_app(owner.clone(), vec![]);
+
+    let (astro_token_instance, _, maker_instance, governance_instance) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        50u64.into(),
+        Some(Decimal::percent(10)),
+        None,
+        None,
+        None,
+    );
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        astro_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000_000),
+    );
+
+    // Rewards aren't enabled yet, so the collected ASTRO stays in the Maker
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(astro_token_instance.clone()),
+                    limit: None,
+                }],
+                min_astro_out: None,
             },
             &[],
         )
         .unwrap();
+
+    let status: StatusResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Status {})
+        .unwrap();
+    assert_eq!(
+        status,
+        StatusResponse {
+            governance_percent: 50u64.into(),
+            max_spread: Decimal::percent(10),
+            astro_token: token_asset_info(astro_token_instance),
+            staking_contract: Some(staking),
+            governance_contract: Some(governance_instance),
+            astro_balance: Uint128::new(1_000_000),
+        }
+    );
 }
 
 fn set_dev_fund_config(
@@ -2245,6 +2502,7 @@ fn test_dev_fund_fee() {
                 info: AssetInfo::native(usdc),
                 limit: None,
             }],
+            min_astro_out: None,
         },
         &[],
     )
@@ -2312,6 +2570,7 @@ fn test_dev_fund_fee() {
                 info: AssetInfo::native(usdc),
                 limit: None,
             }],
+            min_astro_out: None,
         },
         &[],
     )
@@ -2356,6 +2615,240 @@ fn test_dev_fund_fee() {
     );
 }
 
+#[test]
+fn test_collect_min_astro_out() {
+    let usdc = "uusdc";
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(owner.clone(), vec![coin(300_000_000_000u128, usdc)]);
+
+    let staking = Addr::unchecked("staking");
+    let (astro_token, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut app,
+        owner.clone(),
+        staking.clone(),
+        0u64.into(),
+        Some(Decimal::from_str("0.5").unwrap()),
+        None,
+        None,
+        None,
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        maker_instance.clone(),
+        &ExecuteMsg::EnableRewards { blocks: 1 },
+        &[],
+    )
+    .unwrap();
+
+    let pair_info = create_pair(
+        &mut app,
+        owner.clone(),
+        owner.clone(),
+        &factory_instance,
+        vec![
+            AssetInfo::native(usdc).with_balance(100_000_000000u128),
+            AssetInfo::cw20(astro_token.clone()).with_balance(100_000_000000u128),
+        ],
+        None,
+    );
+
+    // Emulate usdc income to the Maker contract
+    mint_coins(
+        &mut app,
+        maker_instance.to_string(),
+        &[coin(1000_000000u128, usdc)],
+    );
+
+    // Someone dumps USDC into the pool right before the collect
+    let whale_swap = AssetInfo::native(usdc).with_balance(50_000_000000u128);
+    app.execute_contract(
+        owner.clone(),
+        pair_info.contract_addr.clone(),
+        &astroport::pair::ExecuteMsg::Swap {
+            offer_asset: whale_swap.clone(),
+            ask_asset_info: None,
+            belief_price: None,
+            max_spread: Some(Decimal::percent(50)),
+            to: None,
+            strict_belief_price: false,
+            referral: None,
+        },
+        &[whale_swap.as_coin().unwrap()],
+    )
+    .unwrap();
+
+    let simulation: astroport::pair::SimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_info.contract_addr,
+            &astroport::pair::QueryMsg::Simulation {
+                offer_asset: AssetInfo::native(usdc).with_balance(1000_000000u128),
+                ask_asset_info: None,
+            },
+        )
+        .unwrap();
+
+    let collect_msg = |min_astro_out: u128| ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: AssetInfo::native(usdc),
+            limit: None,
+        }],
+        min_astro_out: Some(min_astro_out.into()),
+    };
+
+    // The manipulated pool produces less ASTRO than the floor so the whole collect reverts
+    let err = app
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg(900_000000),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::MinAstroOutNotReached {
+            astro_out: simulation.return_amount,
+            min_astro_out: 900_000000u128.into(),
+        }
+        .to_string()
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&maker_instance, usdc)
+            .unwrap()
+            .amount
+            .u128(),
+        1000_000000
+    );
+    check_balance(&mut app, staking.clone(), astro_token.clone(), 0u128.into());
+
+    // A floor below the actual output passes
+    app.execute_contract(
+        Addr::unchecked("anyone"),
+        maker_instance.clone(),
+        &collect_msg(simulation.return_amount.u128()),
+        &[],
+    )
+    .unwrap();
+    check_balance(
+        &mut app,
+        staking.clone(),
+        astro_token.clone(),
+        simulation.return_amount,
+    );
+}
+
+#[test]
+fn test_worth_collecting() {
+    let usdc = "uusdc";
+    let luna = "uluna";
+    let atom = "uatom";
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            coin(1_000_000_000_000u128, usdc),
+            coin(1_000_000_000_000u128, luna),
+            coin(1_000_000_000_000u128, atom),
+        ],
+    );
+
+    let staking = Addr::unchecked("staking");
+    let (astro_token, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut app,
+        owner.clone(),
+        staking.clone(),
+        0u64.into(),
+        None,
+        None,
+        None,
+        None,
+    );
+
+    for denom in [usdc, luna] {
+        create_pair(
+            &mut app,
+            owner.clone(),
+            owner.clone(),
+            &factory_instance,
+            vec![
+                AssetInfo::native(denom).with_balance(100_000_000000u128),
+                AssetInfo::cw20(astro_token.clone()).with_balance(100_000_000000u128),
+            ],
+            None,
+        );
+    }
+    // ATOM is swapped to ASTRO through USDC
+    create_pair(
+        &mut app,
+        owner.clone(),
+        owner.clone(),
+        &factory_instance,
+        vec![
+            AssetInfo::native(atom).with_balance(100_000_000000u128),
+            AssetInfo::native(usdc).with_balance(100_000_000000u128),
+        ],
+        None,
+    );
+    app.execute_contract(
+        owner.clone(),
+        maker_instance.clone(),
+        &ExecuteMsg::UpdateBridges {
+            add: Some(vec![(AssetInfo::native(atom), AssetInfo::native(usdc))]),
+            remove: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    mint_coins(
+        &mut app,
+        maker_instance.to_string(),
+        &[
+            // Worth a single swap
+            coin(1000_000000u128, usdc),
+            // Dust
+            coin(10u128, luna),
+            // Worth more than a single swap but less than the two swaps on its route
+            coin(500_000u128, atom),
+            // Can't be swapped to ASTRO
+            coin(1000_000000u128, "junk"),
+        ],
+    );
+
+    let worth_collecting = |app: &TestApp, gas_price: u128| -> Vec<Asset> {
+        app.wrap()
+            .query_wasm_smart(
+                &maker_instance,
+                &QueryMsg::WorthCollecting {
+                    gas_price: gas_price.into(),
+                },
+            )
+            .unwrap()
+    };
+
+    // The estimated gas cost is 300_000 ASTRO per swap
+    assert_eq!(
+        worth_collecting(&app, 1),
+        vec![AssetInfo::native(usdc).with_balance(1000_000000u128)]
+    );
+
+    // Without gas costs every swappable balance is worth collecting
+    assert_eq!(
+        worth_collecting(&app, 0),
+        vec![
+            AssetInfo::native(atom).with_balance(500_000u128),
+            AssetInfo::native(luna).with_balance(10u128),
+            AssetInfo::native(usdc).with_balance(1000_000000u128),
+        ]
+    );
+
+    // Large enough gas price excludes everything
+    assert_eq!(worth_collecting(&app, 1_000_000), vec![]);
+}
+
 struct CheckDistributedAstro {
     maker_amount: Uint128,
     governance_amount: Uint128,
@@ -2590,7 +3083,10 @@ fn distribute_initially_accrued_fees() {
         .execute_contract(
             Addr::unchecked("anyone"),
             maker_instance.clone(),
-            &ExecuteMsg::Collect { assets },
+            &ExecuteMsg::Collect {
+                assets,
+                min_astro_out: None,
+            },
             &[],
         )
         .unwrap();
@@ -2644,6 +3140,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2660,6 +3157,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2674,6 +3172,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2699,6 +3198,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2732,6 +3232,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2757,6 +3258,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -2786,7 +3288,10 @@ fn distribute_initially_accrued_fees() {
         .execute_contract(
             Addr::unchecked("anyone"),
             maker_instance.clone(),
-            &ExecuteMsg::Collect { assets },
+            &ExecuteMsg::Collect {
+                assets,
+                min_astro_out: None,
+            },
             &[],
         )
         .unwrap();
@@ -2974,6 +3479,7 @@ fn collect_3pools() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                min_astro_out: None,
             },
             &[],
         )
@@ -3030,3 +3536,265 @@ fn collect_3pools() {
     assert_eq!(balances[0].amount.u128(), 100_000);
     assert_eq!(balances[1].amount.u128(), 100_000);
 }
+
+#[test]
+fn test_distribute_as_xastro() {
+    let owner = Addr::unchecked("owner");
+    let astro = native_asset_info("astro".to_string());
+
+    for distribute_as_xastro in [false, true] {
+        let mut app = mock_app(owner.clone(), vec![]);
+
+        let (_, _, maker_instance, governance_instance) = instantiate_contracts(
+            &mut app,
+            owner.clone(),
+            Addr::unchecked("staking"),
+            50u64.into(),
+            None,
+            None,
+            None,
+            None,
+        );
+
+        // Cw20 ASTRO can't be staked
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                maker_instance.clone(),
+                &ExecuteMsg::SetDistributeAsXastro {
+                    distribute_as_xastro: true,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::XastroDistributionUnavailable {}
+        );
+
+        let staking_code_id = app.store_code(mock_staking_contract());
+        let staking = app
+            .instantiate_contract(
+                staking_code_id,
+                owner.clone(),
+                &Empty {},
+                &[],
+                "Staking",
+                None,
+            )
+            .unwrap();
+        mint_coins(&mut app, &staking, &[coin(1_000_000, "xastro")]);
+
+        app.execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateConfig {
+                factory_contract: None,
+                staking_contract: Some(staking.to_string()),
+                governance_contract: None,
+                governance_percent: None,
+                basic_asset: None,
+                max_spread: None,
+                second_receiver_params: None,
+                collect_cooldown: None,
+                astro_token: Some(astro.clone()),
+                dev_fund_config: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let err = app
+            .execute_contract(
+                Addr::unchecked("anyone"),
+                maker_instance.clone(),
+                &ExecuteMsg::SetDistributeAsXastro {
+                    distribute_as_xastro: true,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+
+        app.execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::SetDistributeAsXastro {
+                distribute_as_xastro,
+            },
+            &[],
+        )
+        .unwrap();
+        let enabled: bool = app
+            .wrap()
+            .query_wasm_smart(&maker_instance, &QueryMsg::DistributeAsXastro {})
+            .unwrap();
+        assert_eq!(enabled, distribute_as_xastro);
+
+        app.execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+        mint_coins(&mut app, &maker_instance, &[coin(100_000, "astro")]);
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: astro.clone(),
+                    limit: None,
+                }],
+                min_astro_out: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let balance = |app: &TestApp, addr: &Addr, denom: &str| {
+            app.wrap().query_balance(addr, denom).unwrap().amount.u128()
+        };
+
+        // The staking share is always sent as ASTRO
+        if distribute_as_xastro {
+            assert_eq!(balance(&app, &governance_instance, "astro"), 0);
+            assert_eq!(balance(&app, &governance_instance, "xastro"), 50_000);
+            assert_eq!(balance(&app, &staking, "astro"), 100_000);
+        } else {
+            assert_eq!(balance(&app, &governance_instance, "astro"), 50_000);
+            assert_eq!(balance(&app, &governance_instance, "xastro"), 0);
+            assert_eq!(balance(&app, &staking, "astro"), 50_000);
+        }
+    }
+}
+
+#[test]
+fn test_max_collect_assets() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(owner.clone(), vec![]);
+
+    let (_, _, maker_instance, _) = instantiate_contracts(
+        &mut app,
+        owner.clone(),
+        Addr::unchecked("staking"),
+        50u64.into(),
+        None,
+        None,
+        None,
+        None,
+    );
+
+    let query_max_collect_assets = |app: &TestApp| -> Option<u32> {
+        app.wrap()
+            .query_wasm_smart(&maker_instance, &QueryMsg::MaxCollectAssets {})
+            .unwrap()
+    };
+    assert_eq!(query_max_collect_assets(&app), None);
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random"),
+            maker_instance.clone(),
+            &ExecuteMsg::SetMaxCollectAssets {
+                max_assets: Some(2),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::SetMaxCollectAssets {
+                max_assets: Some(0),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidMaxCollectAssets {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        maker_instance.clone(),
+        &ExecuteMsg::SetMaxCollectAssets {
+            max_assets: Some(2),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_max_collect_assets(&app), Some(2));
+
+    let assets = ["asset0", "asset1", "asset2"]
+        .into_iter()
+        .map(|denom| AssetWithLimit {
+            info: AssetInfo::native(denom),
+            limit: None,
+        })
+        .collect::<Vec<_>>();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: assets.clone(),
+                min_astro_out: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TooManyCollectAssets {
+            max_assets: 2,
+            assets: 3,
+        }
+    );
+
+    // A list within the cap is collected
+    app.execute_contract(
+        Addr::unchecked("anyone"),
+        maker_instance.clone(),
+        &ExecuteMsg::Collect {
+            assets: assets[..2].to_vec(),
+            min_astro_out: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Removing the cap allows any number of assets again
+    app.execute_contract(
+        owner.clone(),
+        maker_instance.clone(),
+        &ExecuteMsg::SetMaxCollectAssets { max_assets: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_max_collect_assets(&app), None);
+
+    app.execute_contract(
+        Addr::unchecked("anyone"),
+        maker_instance.clone(),
+        &ExecuteMsg::Collect {
+            assets,
+            min_astro_out: None,
+        },
+        &[],
+    )
+    .unwrap();
+}
@@ -1,15 +1,15 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coin, ensure, to_json_binary, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    attr, coin, ensure, to_json_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw_utils::{must_pay, parse_reply_instantiate_data, MsgInstantiateContractResponse};
 use osmosis_std::types::cosmos::bank::v1beta1::{DenomUnit, Metadata};
 use osmosis_std::types::osmosis::tokenfactory::v1beta1::{
     MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint, MsgSetBeforeSendHook,
-    MsgSetDenomMetadata,
+    MsgSetDenomMetadata, TokenfactoryQuerier,
 };
 
 use astroport::staking::{
@@ -17,7 +17,7 @@ use astroport::staking::{
 };
 
 use crate::error::ContractError;
-use crate::state::{CONFIG, TRACKER_DATA};
+use crate::state::{CONFIG, CUMULATIVE_FLOWS, DEPOSIT_HISTORY, TRACKER_DATA};
 
 /// Contract name that is used for migration.
 pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
@@ -68,6 +68,21 @@ pub fn instantiate(
         )
     );
 
+    // Pin deposit_token_denom to the expected TokenFactory admin if requested
+    if let Some(expected_admin) = &msg.deposit_token_admin {
+        let admin = TokenfactoryQuerier::new(&deps.querier)
+            .denom_authority_metadata(msg.deposit_token_denom.clone())?
+            .authority_metadata
+            .map(|metadata| metadata.admin)
+            .unwrap_or_default();
+        ensure!(
+            &admin == expected_admin,
+            StdError::generic_err(format!(
+                "deposit_token_denom admin {admin} doesn't match expected admin {expected_admin}"
+            ))
+        );
+    }
+
     // Validate addresses
     deps.api.addr_validate(&msg.token_factory_addr)?;
     deps.api.addr_validate(&msg.tracking_admin)?;
@@ -145,6 +160,7 @@ pub fn execute(
             let recipient = receiver.unwrap_or_else(|| info.sender.to_string());
             execute_leave(deps, env, info, recipient)
         }
+        ExecuteMsg::Donate {} => execute_donate(deps, env, info),
     }
 }
 
@@ -231,6 +247,36 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
     }
 }
 
+/// Donates the attached ASTRO to xASTRO holders.
+/// Emits the donated amount together with the resulting exchange rate.
+fn execute_donate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Ensure that the correct denom is sent. Sending zero tokens is prohibited on chain level
+    let amount = must_pay(&info, &config.astro_denom)?;
+
+    // Amount sent along with the message already included
+    let total_deposit = deps
+        .querier
+        .query_balance(&env.contract.address, &config.astro_denom)?
+        .amount;
+    let total_shares = deps.querier.query_supply(&config.xastro_denom)?.amount;
+
+    // The donation would be captured by the first staker otherwise
+    ensure!(!total_shares.is_zero(), ContractError::NoStakers {});
+
+    DEPOSIT_HISTORY.save(deps.storage, &total_deposit, env.block.time.seconds())?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "donate"),
+        attr("astro_amount", amount),
+        attr(
+            "exchange_rate",
+            exchange_rate(total_deposit, total_shares).to_string(),
+        ),
+    ]))
+}
+
 /// Enter stakes TokenFactory ASTRO for xASTRO.
 /// Returns composed Response object and minted xASTRO in the form of [`Coin`].
 /// Subsequent messages are added after,
@@ -255,6 +301,7 @@ fn execute_enter(
     let total_shares = deps.querier.query_supply(&config.xastro_denom)?.amount;
 
     let mut messages: Vec<CosmosMsg> = vec![];
+    let mut locked_amount = Uint128::zero();
 
     let mint_amount = if total_shares.is_zero() || total_deposit.is_zero() {
         // There needs to be a minimum amount initially staked, thus the result
@@ -272,6 +319,7 @@ fn execute_enter(
             }
             .into(),
         );
+        locked_amount = MINIMUM_STAKE_AMOUNT;
 
         amount - MINIMUM_STAKE_AMOUNT
     } else {
@@ -282,6 +330,16 @@ fn execute_enter(
         return Err(ContractError::StakeAmountTooSmall {});
     }
 
+    DEPOSIT_HISTORY.save(
+        deps.storage,
+        &(total_deposit + amount),
+        env.block.time.seconds(),
+    )?;
+
+    let mut flows = CUMULATIVE_FLOWS.may_load(deps.storage)?.unwrap_or_default();
+    flows.total_entered += amount;
+    CUMULATIVE_FLOWS.save(deps.storage, &flows)?;
+
     let minted_coins = coin(mint_amount.u128(), config.xastro_denom);
 
     // Mint new xASTRO tokens to the staking contract
@@ -308,6 +366,14 @@ fn execute_enter(
             .add_attributes([
                 attr("astro_amount", amount),
                 attr("xastro_amount", mint_amount),
+                attr(
+                    "exchange_rate",
+                    exchange_rate(
+                        total_deposit + amount,
+                        total_shares + locked_amount + mint_amount,
+                    )
+                    .to_string(),
+                ),
             ]),
         minted_coins,
     ))
@@ -337,6 +403,16 @@ fn execute_leave(
     // deposit and shares
     let return_amount = amount.multiply_ratio(total_deposit, total_shares);
 
+    DEPOSIT_HISTORY.save(
+        deps.storage,
+        &(total_deposit - return_amount),
+        env.block.time.seconds(),
+    )?;
+
+    let mut flows = CUMULATIVE_FLOWS.may_load(deps.storage)?.unwrap_or_default();
+    flows.total_left += return_amount;
+    CUMULATIVE_FLOWS.save(deps.storage, &flows)?;
+
     let messages: Vec<CosmosMsg> = vec![
         // Burn the received xASTRO tokens
         MsgBurn {
@@ -374,6 +450,10 @@ fn execute_leave(
             attr("recipient", recipient),
             attr("xastro_amount", amount),
             attr("astro_amount", return_amount),
+            attr(
+                "exchange_rate",
+                exchange_rate(total_deposit - return_amount, total_shares - amount).to_string(),
+            ),
         ]))
 }
 
@@ -390,6 +470,17 @@ fn execute_leave(
 /// * **QueryMsg::BalanceAt { address, timestamp }** Returns the xASTRO balance of the given address at the given timestamp
 ///
 /// * **QueryMsg::TotalSupplyAt { timestamp }** Returns xASTRO total supply at the given timestamp
+///
+/// * **QueryMsg::ExchangeRateAt { timestamp }** Returns the amount of ASTRO per 1 xASTRO at the given timestamp
+///
+/// * **QueryMsg::LockedShares {}** Returns the amount of xASTRO permanently locked in the staking contract
+///
+/// * **QueryMsg::MinStakeAmount {}** Returns the minimum amount of ASTRO which mints at least 1 xASTRO
+///
+/// * **QueryMsg::CumulativeFlows {}** Returns the cumulative amounts of ASTRO staked and unstaked
+/// since flow tracking was introduced
+///
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -440,5 +531,93 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 
             to_json_binary(&amount)
         }
+        QueryMsg::ExchangeRateAt { timestamp } => {
+            to_json_binary(&query_exchange_rate_at(deps, env, timestamp)?)
+        }
+        QueryMsg::LockedShares {} => {
+            let config = CONFIG.load(deps.storage)?;
+
+            // The 1 xASTRO snapshot transfers are sent from the contract to itself
+            // so they never change this balance
+            let locked_shares = deps
+                .querier
+                .query_balance(env.contract.address, config.xastro_denom)?
+                .amount;
+            to_json_binary(&locked_shares)
+        }
+        QueryMsg::MinStakeAmount {} => to_json_binary(&query_min_stake_amount(deps, env)?),
+        QueryMsg::CumulativeFlows {} => {
+            to_json_binary(&CUMULATIVE_FLOWS.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::Version {} => to_json_binary(&get_contract_version(deps.storage)?),
+    }
+}
+
+/// Returns the minimum amount of ASTRO which mints at least 1 xASTRO in [`execute_enter`].
+fn query_min_stake_amount(deps: Deps, env: Env) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let total_deposit = deps
+        .querier
+        .query_balance(env.contract.address, config.astro_denom)?
+        .amount;
+    let total_shares = deps.querier.query_supply(config.xastro_denom)?.amount;
+
+    if total_shares.is_zero() || total_deposit.is_zero() {
+        // The first stake must exceed the permanently locked amount
+        Ok(MINIMUM_STAKE_AMOUNT + Uint128::one())
+    } else {
+        // The smallest amount for which amount * total_shares / total_deposit >= 1
+        Ok((total_deposit + total_shares - Uint128::one()) / total_shares)
+    }
+}
+
+/// Returns the amount of ASTRO per 1 xASTRO at the given timestamp.
+/// The historical total supply is taken from the tracker contract while the total deposit
+/// is taken from the deposit history recorded on every enter and leave.
+fn query_exchange_rate_at(deps: Deps, env: Env, timestamp: Option<u64>) -> StdResult<Decimal> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let (total_deposit, total_shares) = match timestamp {
+        Some(timestamp) if timestamp < env.block.time.seconds() => {
+            let tracker_config = TRACKER_DATA.load(deps.storage)?;
+            let total_shares: Uint128 = deps.querier.query_wasm_smart(
+                tracker_config.tracker_addr,
+                &astroport_v4::tokenfactory_tracker::QueryMsg::TotalSupplyAt {
+                    timestamp: Some(timestamp),
+                },
+            )?;
+            let total_deposit = if total_shares.is_zero() {
+                Uint128::zero()
+            } else {
+                DEPOSIT_HISTORY
+                    .may_load_at_height(deps.storage, timestamp)?
+                    .ok_or_else(|| {
+                        StdError::generic_err(format!(
+                            "Deposit history is not available at {timestamp}"
+                        ))
+                    })?
+            };
+
+            (total_deposit, total_shares)
+        }
+        _ => (
+            deps.querier
+                .query_balance(env.contract.address, config.astro_denom)?
+                .amount,
+            deps.querier.query_supply(config.xastro_denom)?.amount,
+        ),
+    };
+
+    Ok(exchange_rate(total_deposit, total_shares))
+}
+
+/// Returns the amount of ASTRO per 1 xASTRO for the given deposit and xASTRO supply.
+fn exchange_rate(total_deposit: Uint128, total_shares: Uint128) -> Decimal {
+    if total_shares.is_zero() {
+        // The first stake mints xASTRO 1:1
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(total_deposit, total_shares)
     }
 }
@@ -22,6 +22,9 @@ pub enum ContractError {
     #[error("Insufficient amount of Stake")]
     StakeAmountTooSmall {},
 
+    #[error("Can't donate ASTRO while there are no stakers")]
+    NoStakers {},
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 
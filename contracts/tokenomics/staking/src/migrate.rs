@@ -7,19 +7,28 @@ use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION};
 use astroport::pair::MigrateMsg;
 
 use crate::error::ContractError;
+use crate::state::{CONFIG, DEPOSIT_HISTORY};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
 
     match contract_version.contract.as_ref() {
         "astroport-staking" => match contract_version.version.as_ref() {
-            "2.0.0" | "2.1.0" => {}
+            "2.0.0" | "2.1.0" | "2.3.0" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
     }
 
+    // Start the deposit history from the current total deposit
+    let config = CONFIG.load(deps.storage)?;
+    let total_deposit = deps
+        .querier
+        .query_balance(&env.contract.address, &config.astro_denom)?
+        .amount;
+    DEPOSIT_HISTORY.save(deps.storage, &total_deposit, env.block.time.seconds())?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
@@ -1,9 +1,22 @@
-use cw_storage_plus::Item;
+use cosmwasm_std::Uint128;
+use cw_storage_plus::{Item, SnapshotItem, Strategy};
 
-use astroport::staking::{Config, TrackerData};
+use astroport::staking::{Config, CumulativeFlows, TrackerData};
 
 /// Stores the contract config at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
 
 /// Stores the tracker contract instantiate data at the given key
 pub const TRACKER_DATA: Item<TrackerData> = Item::new("tracker_data");
+
+/// Stores the history of ASTRO deposited in the contract, keyed by timestamp
+pub const DEPOSIT_HISTORY: SnapshotItem<Uint128> = SnapshotItem::new(
+    "deposit_history",
+    "deposit_history_chpts",
+    "deposit_history_chlg",
+    Strategy::EveryBlock,
+);
+
+/// Stores the cumulative amounts of ASTRO staked and unstaked.
+/// Contracts upgraded from 2.3.0 or earlier count from the upgrade, as earlier flows can't be recovered.
+pub const CUMULATIVE_FLOWS: Item<CumulativeFlows> = Item::new("cumulative_flows");
@@ -4,7 +4,7 @@ use anyhow::Result as AnyResult;
 use cosmwasm_schema::serde::Serialize;
 use cosmwasm_std::testing::MockApi;
 use cosmwasm_std::{
-    coins, to_json_binary, Addr, Coin, DepsMut, Empty, Env, GovMsg, IbcMsg, IbcQuery,
+    coins, to_json_binary, Addr, Coin, Decimal, DepsMut, Empty, Env, GovMsg, IbcMsg, IbcQuery,
     MemoryStorage, MessageInfo, Response, StdResult, Uint128,
 };
 use cw_multi_test::{
@@ -16,7 +16,7 @@ use astroport::staking::{Config, ExecuteMsg, InstantiateMsg, QueryMsg, TrackerDa
 
 use crate::common::stargate::StargateKeeper;
 
-fn staking_contract() -> Box<dyn Contract<Empty>> {
+pub fn staking_contract() -> Box<dyn Contract<Empty>> {
     Box::new(
         ContractWrapper::new_with_empty(
             astroport_staking::contract::execute,
@@ -27,7 +27,7 @@ fn staking_contract() -> Box<dyn Contract<Empty>> {
     )
 }
 
-fn tracker_contract() -> Box<dyn Contract<Empty>> {
+pub fn tracker_contract() -> Box<dyn Contract<Empty>> {
     Box::new(
         ContractWrapper::new_with_empty(
             |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
@@ -82,6 +82,7 @@ impl Helper {
             tracking_admin: owner.to_string(),
             tracking_code_id: tracker_code_id,
             token_factory_addr: TOKEN_FACTORY_MODULE.to_string(),
+            deposit_token_admin: None,
         };
         let staking = app
             .instantiate_contract(
@@ -149,6 +150,15 @@ impl Helper {
         )
     }
 
+    pub fn donate(&mut self, sender: &Addr, amount: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.staking.clone(),
+            &ExecuteMsg::Donate {},
+            &coins(amount, ASTRO_DENOM),
+        )
+    }
+
     pub fn unstake(&mut self, sender: &Addr, amount: u128) -> AnyResult<AppResponse> {
         self.app.execute_contract(
             sender.clone(),
@@ -185,6 +195,12 @@ impl Helper {
             .query_wasm_smart(&self.staking, &QueryMsg::TotalSupplyAt { timestamp })
     }
 
+    pub fn query_exchange_rate_at(&self, timestamp: Option<u64>) -> StdResult<Decimal> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.staking, &QueryMsg::ExchangeRateAt { timestamp })
+    }
+
     pub fn mint_coin(&mut self, to: &Addr, coin: Coin) {
         // .init_balance() erases previous balance thus I use such hack and create intermediate "denom admin"
         let denom_admin = Addr::unchecked(format!("{}_admin", &coin.denom));
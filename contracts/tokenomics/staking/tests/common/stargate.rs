@@ -4,12 +4,14 @@ use anyhow::Result as AnyResult;
 use cosmwasm_schema::schemars::JsonSchema;
 use cosmwasm_schema::serde::de::DeserializeOwned;
 use cosmwasm_std::{
-    coin, Addr, Api, BankMsg, Binary, BlockInfo, CustomQuery, Querier, Storage, SubMsgResponse,
+    coin, to_json_binary, Addr, Api, BankMsg, Binary, BlockInfo, CustomQuery, Querier, Storage,
+    SubMsgResponse,
 };
 use cw_multi_test::{AppResponse, BankSudo, CosmosRouter, Stargate};
 use osmosis_std::types::osmosis::tokenfactory::v1beta1::{
-    MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint, MsgSetBeforeSendHook,
-    MsgSetDenomMetadata,
+    DenomAuthorityMetadata, MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint,
+    MsgSetBeforeSendHook, MsgSetDenomMetadata, QueryDenomAuthorityMetadataRequest,
+    QueryDenomAuthorityMetadataResponse,
 };
 
 #[derive(Default)]
@@ -104,21 +106,26 @@ impl Stargate for StargateKeeper {
         _storage: &dyn Storage,
         _querier: &dyn Querier,
         _block: &BlockInfo,
-        _path: String,
-        _data: Binary,
+        path: String,
+        data: Binary,
     ) -> AnyResult<Binary> {
-        unimplemented!("Stargate queries are not implemented")
-        // match path.as_str() {
-        //     "/osmosis.poolmanager.v1beta1.Query/Params" => {
-        //         Ok(to_json_binary(&poolmanager::v1beta1::ParamsResponse {
-        //             params: Some(poolmanager::v1beta1::Params {
-        //                 pool_creation_fee: vec![coin(1000_000000, "uosmo").into()],
-        //                 taker_fee_params: None,
-        //                 authorized_quote_denoms: vec![],
-        //             }),
-        //         })?)
-        //     }
-        //     _ => Err(anyhow::anyhow!("Unexpected stargate query request {path}")),
-        // }
+        match path.as_str() {
+            "/osmosis.tokenfactory.v1beta1.Query/DenomAuthorityMetadata" => {
+                let request: QueryDenomAuthorityMetadataRequest = data.try_into()?;
+                // Denom creator is considered to be the admin: factory/{creator}/{subdenom}
+                let authority_metadata =
+                    request
+                        .denom
+                        .split('/')
+                        .nth(1)
+                        .map(|admin| DenomAuthorityMetadata {
+                            admin: admin.to_string(),
+                        });
+                Ok(to_json_binary(&QueryDenomAuthorityMetadataResponse {
+                    authority_metadata,
+                })?)
+            }
+            _ => Err(anyhow::anyhow!("Unexpected stargate query request {path}")),
+        }
     }
 }
@@ -4,17 +4,20 @@ use std::collections::HashMap;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    coin, coins, from_json, Addr, BankMsg, Binary, BlockInfo, Deps, DepsMut, Empty, Env,
+    coin, coins, from_json, Addr, BankMsg, Binary, BlockInfo, Decimal, Deps, DepsMut, Empty, Env,
     MessageInfo, Response, StdResult, Timestamp, Uint128,
 };
-use cw_multi_test::{Contract, ContractWrapper, Executor, TOKEN_FACTORY_MODULE};
+use cw2::ContractVersion;
+use cw_multi_test::{AppResponse, Contract, ContractWrapper, Executor, TOKEN_FACTORY_MODULE};
 use cw_utils::PaymentError;
 use itertools::Itertools;
 
-use astroport::staking::{Config, ExecuteMsg, QueryMsg, StakingResponse, TrackerData};
+use astroport::staking::{
+    Config, CumulativeFlows, ExecuteMsg, InstantiateMsg, QueryMsg, StakingResponse, TrackerData,
+};
 use astroport_staking::error::ContractError;
 
-use crate::common::helper::{Helper, ASTRO_DENOM};
+use crate::common::helper::{staking_contract, tracker_contract, Helper, ASTRO_DENOM};
 
 mod common;
 
@@ -51,6 +54,54 @@ fn test_instantiate() {
             tracker_addr: "contract1".to_string(),
         }
     );
+
+    let version: ContractVersion = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.staking, &QueryMsg::Version {})
+        .unwrap();
+    assert_eq!(
+        version,
+        ContractVersion {
+            contract: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_instantiate_with_deposit_token_admin() {
+    let owner = Addr::unchecked("owner");
+
+    let mut helper = Helper::new(&owner).unwrap();
+    let staking_code_id = helper.app.store_code(staking_contract());
+    let tracker_code_id = helper.app.store_code(tracker_contract());
+
+    let msg = InstantiateMsg {
+        deposit_token_denom: ASTRO_DENOM.to_string(),
+        tracking_admin: owner.to_string(),
+        tracking_code_id: tracker_code_id,
+        token_factory_addr: TOKEN_FACTORY_MODULE.to_string(),
+        deposit_token_admin: Some("random".to_string()),
+    };
+    let err = helper
+        .app
+        .instantiate_contract(staking_code_id, owner.clone(), &msg, &[], "Staking", None)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: deposit_token_denom admin assembly doesn't match expected admin random"
+    );
+
+    // ASTRO_DENOM was created by the assembly
+    let msg = InstantiateMsg {
+        deposit_token_admin: Some("assembly".to_string()),
+        ..msg
+    };
+    helper
+        .app
+        .instantiate_contract(staking_code_id, owner.clone(), &msg, &[], "Staking", None)
+        .unwrap();
 }
 
 #[test]
@@ -249,6 +300,34 @@ fn test_enter_and_leave() {
     assert_eq!(amount.u128(), 10000);
 }
 
+#[test]
+fn test_locked_shares() {
+    let owner = Addr::unchecked("owner");
+
+    let mut helper = Helper::new(&owner).unwrap();
+
+    let query_locked_shares = |helper: &Helper| -> Uint128 {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.staking, &QueryMsg::LockedShares {})
+            .unwrap()
+    };
+    assert_eq!(query_locked_shares(&helper).u128(), 0);
+
+    let alice = Addr::unchecked("alice");
+    helper.give_astro(10000, &alice);
+    helper.stake(&alice, 1100).unwrap();
+
+    // The minimum stake amount is locked on the first stake
+    assert_eq!(query_locked_shares(&helper).u128(), 1000);
+
+    // Subsequent stakes and unstakes don't change the locked amount
+    helper.stake(&alice, 5000).unwrap();
+    helper.unstake(&alice, 100).unwrap();
+    assert_eq!(query_locked_shares(&helper).u128(), 1000);
+}
+
 #[test]
 fn should_work_with_more_than_one_participant() {
     let owner = Addr::unchecked("owner");
@@ -452,6 +531,251 @@ fn test_historical_queries() {
     assert_eq!(total_deposit, staking_astro_balance);
 }
 
+#[test]
+fn test_historical_exchange_rate() {
+    let owner = Addr::unchecked("owner");
+
+    let mut helper = Helper::new(&owner).unwrap();
+    helper.app.set_block(BlockInfo {
+        height: 1000,
+        time: Timestamp::from_seconds(1700000000),
+        chain_id: "".to_string(),
+    });
+    let start = helper.app.block_info().time.seconds();
+
+    helper.stake(&owner, 1001).unwrap();
+    assert_eq!(helper.query_exchange_rate_at(None).unwrap(), Decimal::one());
+
+    let user = Addr::unchecked("user");
+    let staking = helper.staking.clone();
+    let mut history = vec![];
+
+    for i in 1..=5u128 {
+        helper.next_block(100);
+
+        // Donate ASTRO to the staking contract
+        helper
+            .app
+            .send_tokens(owner.clone(), staking.clone(), &coins(i * 100, ASTRO_DENOM))
+            .unwrap();
+
+        // Donation is recorded on the next stake
+        helper.give_astro(1_000000, &user);
+        helper.stake(&user, 1_000000).unwrap();
+
+        let total_deposit = helper.query_balance(&staking, ASTRO_DENOM).unwrap();
+        let total_supply = helper.query_xastro_supply_at(None).unwrap();
+        let rate = helper.query_exchange_rate_at(None).unwrap();
+        assert_eq!(rate, Decimal::from_ratio(total_deposit, total_supply));
+
+        history.push((helper.app.block_info().time.seconds() + 1, rate)); // rate change takes effect from the next block
+    }
+
+    helper.next_block(100);
+
+    // Every donation raises the rate
+    assert!(history
+        .iter()
+        .tuple_windows()
+        .all(|((_, prev), (_, next))| next > prev));
+
+    for (timestamp, rate) in history {
+        assert_eq!(
+            helper.query_exchange_rate_at(Some(timestamp)).unwrap(),
+            rate,
+            "Invalid exchange rate at {timestamp}"
+        );
+    }
+
+    // No xASTRO existed before the first stake
+    assert_eq!(
+        helper.query_exchange_rate_at(Some(start)).unwrap(),
+        Decimal::one()
+    );
+}
+
+#[test]
+fn test_donate() {
+    let owner = Addr::unchecked("owner");
+
+    let mut helper = Helper::new(&owner).unwrap();
+
+    // Donations are rejected while there are no stakers
+    let err = helper.donate(&owner, 1000).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoStakers {}
+    );
+
+    let user = Addr::unchecked("user");
+    helper.give_astro(10_000, &user);
+    helper.stake(&user, 10_000).unwrap();
+    assert_eq!(helper.query_exchange_rate_at(None).unwrap(), Decimal::one());
+
+    let resp = helper.donate(&owner, 5_000).unwrap();
+    let rate = helper.query_exchange_rate_at(None).unwrap();
+    assert_eq!(rate, Decimal::from_ratio(15_000u128, 10_000u128));
+
+    let donate_event = resp
+        .events
+        .iter()
+        .find(|event| {
+            event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "donate")
+        })
+        .unwrap();
+    for (key, value) in [
+        ("astro_amount", "5000".to_string()),
+        ("exchange_rate", rate.to_string()),
+    ] {
+        assert!(donate_event
+            .attributes
+            .iter()
+            .any(|attr| attr.key == key && attr.value == value));
+    }
+
+    // Existing stakers receive the donation on leave
+    let xastro_denom = helper.xastro_denom.clone();
+    let user_xastro = helper.query_balance(&user, &xastro_denom).unwrap();
+    helper.unstake(&user, user_xastro.u128()).unwrap();
+    assert_eq!(
+        helper.query_balance(&user, ASTRO_DENOM).unwrap(),
+        user_xastro * rate
+    );
+}
+
+#[test]
+fn test_exchange_rate_attributes() {
+    fn assert_rate_attr(resp: &AppResponse, action: &str, rate: Decimal) {
+        let event = resp
+            .events
+            .iter()
+            .find(|event| {
+                event
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.key == "action" && attr.value == action)
+            })
+            .unwrap();
+        assert!(
+            event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "exchange_rate" && attr.value == rate.to_string()),
+            "exchange_rate {rate} not found in {action} event"
+        );
+    }
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = Helper::new(&owner).unwrap();
+    let alice = Addr::unchecked("alice");
+    helper.give_astro(100_000, &alice);
+
+    // Initial stake
+    let resp = helper.stake(&alice, 10_000).unwrap();
+    let rate = helper.query_exchange_rate_at(None).unwrap();
+    assert_eq!(rate, Decimal::one());
+    assert_rate_attr(&resp, "enter", rate);
+
+    let resp = helper.donate(&alice, 5_000).unwrap();
+    let rate = helper.query_exchange_rate_at(None).unwrap();
+    assert_eq!(rate, Decimal::from_ratio(15_000u128, 10_000u128));
+    assert_rate_attr(&resp, "donate", rate);
+
+    let resp = helper.stake(&alice, 3_000).unwrap();
+    let rate = helper.query_exchange_rate_at(None).unwrap();
+    assert_rate_attr(&resp, "enter", rate);
+
+    let resp = helper.unstake(&alice, 1_000).unwrap();
+    let rate = helper.query_exchange_rate_at(None).unwrap();
+    assert_rate_attr(&resp, "leave", rate);
+}
+
+#[test]
+fn test_cumulative_flows() {
+    let owner = Addr::unchecked("owner");
+    let mut helper = Helper::new(&owner).unwrap();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    helper.give_astro(100_000, &alice);
+    helper.give_astro(100_000, &bob);
+
+    let query_flows = |helper: &Helper| -> CumulativeFlows {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.staking, &QueryMsg::CumulativeFlows {})
+            .unwrap()
+    };
+    assert_eq!(query_flows(&helper), CumulativeFlows::default());
+
+    helper.stake(&alice, 10_000).unwrap();
+    helper.stake(&bob, 20_000).unwrap();
+    assert_eq!(
+        query_flows(&helper),
+        CumulativeFlows {
+            total_entered: Uint128::new(30_000),
+            total_left: Uint128::zero(),
+        }
+    );
+
+    // Donations grow TVL but aren't staking flows
+    helper.donate(&alice, 5_000).unwrap();
+
+    let resp_data = helper.unstake(&bob, 10_000).unwrap().data.unwrap();
+    let first_left: StakingResponse = from_json(&resp_data).unwrap();
+    let resp_data = helper.unstake(&alice, 5_000).unwrap().data.unwrap();
+    let second_left: StakingResponse = from_json(&resp_data).unwrap();
+
+    assert_eq!(
+        query_flows(&helper),
+        CumulativeFlows {
+            total_entered: Uint128::new(30_000),
+            total_left: first_left.astro_amount + second_left.astro_amount,
+        }
+    );
+
+    // Counters keep growing while the current deposit goes down
+    helper.stake(&bob, 1_000).unwrap();
+    assert_eq!(query_flows(&helper).total_entered, Uint128::new(31_000));
+}
+
+#[test]
+fn test_min_stake_amount() {
+    let owner = Addr::unchecked("owner");
+
+    let mut helper = Helper::new(&owner).unwrap();
+
+    let query_min_stake_amount = |helper: &Helper| -> Uint128 {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.staking, &QueryMsg::MinStakeAmount {})
+            .unwrap()
+    };
+
+    // The first stake must exceed the locked minimum
+    assert_eq!(query_min_stake_amount(&helper).u128(), 1001);
+
+    let user = Addr::unchecked("user");
+    helper.give_astro(100_000, &user);
+    helper.stake(&user, 10_000).unwrap();
+    assert_eq!(query_min_stake_amount(&helper).u128(), 1);
+
+    // Donations raise the exchange rate to 3.5 ASTRO per xASTRO
+    helper.donate(&owner, 25_000).unwrap();
+    assert_eq!(query_min_stake_amount(&helper).u128(), 4);
+
+    let err = helper.stake(&user, 3).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::StakeAmountTooSmall {}
+    );
+    helper.stake(&user, 4).unwrap();
+}
+
 #[test]
 fn test_different_query_results() {
     let owner = Addr::unchecked("owner");
@@ -566,9 +890,21 @@ fn test_hooks() {
     let alice = Addr::unchecked("alice");
     helper.give_astro(30000, &alice);
 
-    helper
+    let resp = helper
         .stake_with_hook(&alice, 10000, absorber.to_string(), &AbsorberMsg::Absorb {})
         .unwrap();
+    let rate = helper.query_exchange_rate_at(None).unwrap();
+    assert!(resp.events.iter().any(|event| {
+        event
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "enter_with_hook")
+            && event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "exchange_rate" && attr.value == rate.to_string())
+    }));
+
     let absorber_balance = helper
         .app
         .wrap()
@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     attr, coins, ensure, entry_point, from_json, to_json_binary, wasm_execute, Addr, Binary, Deps,
-    DepsMut, Env, MessageInfo, Response, StdError, StdResult, SubMsg, Uint128,
+    DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg, Uint128,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ReceiveMsg;
@@ -10,20 +10,22 @@ use astroport::asset::{addr_opt_validate, token_asset_info, AssetInfo, AssetInfo
 use astroport::astro_converter;
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::vesting::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, OrderBy, QueryMsg,
-    VestingAccount, VestingAccountResponse, VestingAccountsResponse, VestingInfo, VestingSchedule,
-    VestingSchedulePoint,
+    ClaimableResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    OrderBy, QueryMsg, ScheduleBreakdown, TotalVestingResponse, VestingAccount,
+    VestingAccountResponse, VestingAccountsResponse, VestingInfo, VestingSchedule,
+    VestingSchedulePoint, VestingStatsResponse, DEFAULT_MAX_SCHEDULES, MAX_SCHEDULES_LIMIT,
 };
 
 use crate::error::ContractError;
-use crate::state::{read_vesting_infos, Config, CONFIG, OWNERSHIP_PROPOSAL, VESTING_INFO};
+use crate::state::{
+    read_vesting_infos, Config, ACCOUNT_COUNT, CONFIG, MAX_SCHEDULES, OWNERSHIP_PROPOSAL,
+    REGISTRARS, TOTAL_VESTING, VESTING_INFO,
+};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-vesting";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-/// Maximum limit of schedules per user
-const SCHEDULES_LIMIT: usize = 8;
 
 /// Creates a new contract with the specified parameters in [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -44,6 +46,8 @@ pub fn instantiate(
             vesting_token: msg.vesting_token,
         },
     )?;
+    TOTAL_VESTING.save(deps.storage, &TotalVestingResponse::default())?;
+    ACCOUNT_COUNT.save(deps.storage, &0)?;
 
     Ok(Response::new())
 }
@@ -61,6 +65,10 @@ pub fn instantiate(
 /// * **ExecuteMsg::WithdrawFromActiveSchedule { account, recipient, withdraw_amount }**
 /// Withdraws tokens from the only one active vesting schedule of the specified account.
 ///
+/// * **ExecuteMsg::SetMaxSchedules { max_schedules }** Sets the maximum number of schedules per account.
+///
+/// * **ExecuteMsg::UpdateRegistrars { add, remove }** Updates accounts allowed to register vesting accounts.
+///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a new request to change contract ownership.
 ///
 /// * **ExecuteMsg::DropOwnershipProposal {}** Removes a request to change contract ownership.
@@ -79,9 +87,10 @@ pub fn execute(
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::RegisterVestingAccounts { vesting_accounts } => {
             let config = CONFIG.load(deps.storage)?;
+            let allowed = is_registrar(deps.storage, &config, &info.sender);
 
             match &config.vesting_token {
-                AssetInfo::NativeToken { denom } if info.sender == config.owner => {
+                AssetInfo::NativeToken { denom } if allowed => {
                     let amount = must_pay(&info, denom)?;
                     register_vesting_accounts(deps, env, vesting_accounts, amount)
                 }
@@ -93,6 +102,10 @@ pub fn execute(
             recipient,
             withdraw_amount,
         } => withdraw_from_active_schedule(deps, env, info, account, recipient, withdraw_amount),
+        ExecuteMsg::SetMaxSchedules { max_schedules } => {
+            set_max_schedules(deps, info, max_schedules)
+        }
+        ExecuteMsg::UpdateRegistrars { add, remove } => update_registrars(deps, info, add, remove),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config: Config = CONFIG.load(deps.storage)?;
 
@@ -139,7 +152,10 @@ fn receive_cw20(
     let config = CONFIG.load(deps.storage)?;
 
     // Permission check
-    if cw20_msg.sender != config.owner || token_asset_info(info.sender) != config.vesting_token {
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    if !is_registrar(deps.storage, &config, &sender)
+        || token_asset_info(info.sender) != config.vesting_token
+    {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -150,7 +166,13 @@ fn receive_cw20(
     }
 }
 
+/// Returns whether the address is allowed to register vesting accounts.
+fn is_registrar(storage: &dyn Storage, config: &Config, addr: &Addr) -> bool {
+    addr == config.owner || REGISTRARS.has(storage, addr)
+}
+
 /// Create new vesting schedules.
+/// Fully vested and claimed schedules of the accounts are removed, see [`prune_claimed_schedules`].
 ///
 /// * **vesting_accounts** list of accounts and associated vesting schedules to create.
 ///
@@ -164,6 +186,10 @@ pub fn register_vesting_accounts(
     let response = Response::new();
 
     let mut to_deposit = Uint128::zero();
+    let mut new_accounts = 0u64;
+    let max_schedules = MAX_SCHEDULES
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_MAX_SCHEDULES) as usize;
 
     for mut vesting_account in vesting_accounts {
         let mut released_amount = Uint128::zero();
@@ -181,15 +207,18 @@ pub fn register_vesting_accounts(
         }
 
         if let Some(mut old_info) = VESTING_INFO.may_load(deps.storage, &account_address)? {
-            if old_info.schedules.len() + 1 > SCHEDULES_LIMIT {
-                return Err(ContractError::ExceedSchedulesMaximumLimit(
-                    vesting_account.address,
-                ));
-            };
+            prune_claimed_schedules(env.block.time.seconds(), &mut old_info);
             released_amount = old_info.released_amount;
             vesting_account.schedules.append(&mut old_info.schedules);
+        } else {
+            new_accounts += 1;
         }
 
+        ensure!(
+            vesting_account.schedules.len() <= max_schedules,
+            ContractError::ExceedSchedulesMaximumLimit(vesting_account.address)
+        );
+
         VESTING_INFO.save(
             deps.storage,
             &account_address,
@@ -204,6 +233,14 @@ pub fn register_vesting_accounts(
         return Err(ContractError::VestingScheduleAmountError {});
     }
 
+    TOTAL_VESTING.update::<_, StdError>(deps.storage, |mut totals| {
+        totals.total_scheduled = totals.total_scheduled.checked_add(to_deposit)?;
+        Ok(totals)
+    })?;
+    if new_accounts > 0 {
+        ACCOUNT_COUNT.update::<_, StdError>(deps.storage, |count| Ok(count + new_accounts))?;
+    }
+
     Ok(response.add_attributes({
         vec![
             attr("action", "register_vesting_accounts"),
@@ -212,6 +249,82 @@ pub fn register_vesting_accounts(
     }))
 }
 
+/// Removes fully vested schedules which are covered by the released amount, oldest first.
+/// The released amount is reduced accordingly, so the amount available to claim doesn't change.
+fn prune_claimed_schedules(current_time: u64, vesting_info: &mut VestingInfo) {
+    let mut released_amount = vesting_info.released_amount;
+    vesting_info.schedules.retain(|sch| {
+        let end_point = sch.end_point.unwrap_or(sch.start_point);
+        if end_point.time <= current_time && end_point.amount <= released_amount {
+            released_amount -= end_point.amount;
+            false
+        } else {
+            true
+        }
+    });
+    vesting_info.released_amount = released_amount;
+}
+
+/// Sets the maximum number of schedules a single account can hold.
+///
+/// * **max_schedules** new limit. Must be between 1 and [`MAX_SCHEDULES_LIMIT`].
+fn set_max_schedules(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_schedules: u32,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ensure!(
+        (1..=MAX_SCHEDULES_LIMIT).contains(&max_schedules),
+        ContractError::InvalidMaxSchedules {}
+    );
+
+    MAX_SCHEDULES.save(deps.storage, &max_schedules)?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_max_schedules"),
+        attr("max_schedules", max_schedules.to_string()),
+    ]))
+}
+
+/// Adds and removes accounts which are allowed to register vesting accounts besides the owner.
+///
+/// * **add** accounts to allow.
+///
+/// * **remove** accounts to disallow.
+fn update_registrars(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for addr in &add {
+        REGISTRARS.save(deps.storage, &deps.api.addr_validate(addr)?, &())?;
+    }
+    for addr in &remove {
+        REGISTRARS.remove(deps.storage, &deps.api.addr_validate(addr)?);
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "update_registrars"),
+        attr("added", add.join(",")),
+        attr("removed", remove.join(",")),
+    ]))
+}
+
 /// Asserts the validity of a list of vesting schedules.
 ///
 /// * **addr** receiver of the vested tokens.
@@ -272,6 +385,11 @@ pub fn claim(
 
         vesting_info.released_amount = vesting_info.released_amount.checked_add(claim_amount)?;
         VESTING_INFO.save(deps.storage, &info.sender, &vesting_info)?;
+
+        TOTAL_VESTING.update::<_, StdError>(deps.storage, |mut totals| {
+            totals.total_released = totals.total_released.checked_add(claim_amount)?;
+            Ok(totals)
+        })?;
     };
 
     Ok(response.add_attributes(vec![
@@ -403,6 +521,12 @@ fn withdraw_from_active_schedule(
 
     VESTING_INFO.save(deps.storage, &acc, &vesting_info)?;
 
+    // Withdrawn tokens are no longer part of any vesting schedule
+    TOTAL_VESTING.update::<_, StdError>(deps.storage, |mut totals| {
+        totals.total_scheduled = totals.total_scheduled.checked_sub(amount)?;
+        Ok(totals)
+    })?;
+
     let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or(info.sender);
     let transfer_msg = config
         .vesting_token
@@ -431,6 +555,20 @@ fn withdraw_from_active_schedule(
 ///         }** Returns a list of vesting schedules together with their vesting recipients.
 ///
 /// * **QueryMsg::AvailableAmount { address }** Returns the available amount of tokens that can be claimed by a specific vesting recipient.
+///
+/// * **QueryMsg::Claimable { address }** Returns the amount of tokens a specific address can claim at the current block.
+///
+/// * **QueryMsg::ScheduleBreakdown { address }** Returns the vested and remaining amounts of every schedule of a specific address.
+///
+/// * **QueryMsg::TotalVesting {}** Returns the total amount of scheduled and released tokens across all vesting accounts.
+///
+/// * **QueryMsg::MaxSchedules {}** Returns the maximum number of schedules a single account can hold.
+///
+/// * **QueryMsg::Registrars {}** Returns the accounts allowed to register vesting accounts besides the owner.
+///
+/// * **QueryMsg::Stats {}** Returns the number of accounts and the scheduled, released and remaining totals.
+///
+/// * **QueryMsg::Version {}** Returns the contract name and version stored via cw2.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -451,10 +589,42 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::AvailableAmount { address } => Ok(to_json_binary(
             &query_vesting_available_amount(deps, env, address)?,
         )?),
+        QueryMsg::Claimable { address } => {
+            Ok(to_json_binary(&query_claimable(deps, env, address)?)?)
+        }
+        QueryMsg::ScheduleBreakdown { address } => Ok(to_json_binary(&query_schedule_breakdown(
+            deps, env, address,
+        )?)?),
         QueryMsg::Timestamp {} => Ok(to_json_binary(&query_timestamp(env)?)?),
+        QueryMsg::TotalVesting {} => Ok(to_json_binary(&TOTAL_VESTING.load(deps.storage)?)?),
+        QueryMsg::MaxSchedules {} => Ok(to_json_binary(
+            &MAX_SCHEDULES
+                .may_load(deps.storage)?
+                .unwrap_or(DEFAULT_MAX_SCHEDULES),
+        )?),
+        QueryMsg::Registrars {} => Ok(to_json_binary(
+            &REGISTRARS
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?,
+        )?),
+        QueryMsg::Stats {} => Ok(to_json_binary(&query_stats(deps)?)?),
+        QueryMsg::Version {} => Ok(to_json_binary(&get_contract_version(deps.storage)?)?),
     }
 }
 
+/// Returns aggregate statistics across all vesting accounts using a [`VestingStatsResponse`] object.
+/// All values come from running totals, so the query doesn't depend on the number of accounts.
+pub fn query_stats(deps: Deps) -> StdResult<VestingStatsResponse> {
+    let totals = TOTAL_VESTING.load(deps.storage)?;
+
+    Ok(VestingStatsResponse {
+        account_count: ACCOUNT_COUNT.load(deps.storage)?,
+        total_scheduled: totals.total_scheduled,
+        total_released: totals.total_released,
+        total_remaining: totals.total_scheduled.checked_sub(totals.total_released)?,
+    })
+}
+
 /// Returns the vesting contract configuration using a [`ConfigResponse`] object.
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
@@ -517,6 +687,78 @@ pub fn query_vesting_available_amount(deps: Deps, env: Env, address: String) ->
     Ok(available_amount)
 }
 
+/// Returns the amount of tokens that can be claimed by a specific address at the current block.
+/// Addresses without vesting schedules have nothing to claim.
+pub fn query_claimable(deps: Deps, env: Env, address: String) -> StdResult<ClaimableResponse> {
+    let address = deps.api.addr_validate(&address)?;
+
+    let amount = match VESTING_INFO.may_load(deps.storage, &address)? {
+        Some(info) => compute_available_amount(env.block.time.seconds(), &info)?,
+        None => Uint128::zero(),
+    };
+
+    Ok(ClaimableResponse {
+        amount,
+        has_claimable: !amount.is_zero(),
+    })
+}
+
+/// Returns the vested and remaining amounts of every schedule of a specific address at the current block.
+/// Addresses without vesting schedules get an empty list.
+pub fn query_schedule_breakdown(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<Vec<ScheduleBreakdown>> {
+    let address = deps.api.addr_validate(&address)?;
+    let current_time = env.block.time.seconds();
+
+    let schedules = VESTING_INFO
+        .may_load(deps.storage, &address)?
+        .map(|info| info.schedules)
+        .unwrap_or_default();
+
+    schedules
+        .iter()
+        .map(|sch| {
+            let end_point = sch.end_point.unwrap_or(sch.start_point);
+            let vested_so_far = if sch.start_point.time > current_time {
+                Uint128::zero()
+            } else {
+                calc_schedule_unlocked_amount(sch, current_time)?
+            };
+
+            Ok(ScheduleBreakdown {
+                start: sch.start_point.time,
+                end: end_point.time,
+                total: end_point.amount,
+                vested_so_far,
+                remaining: end_point.amount.checked_sub(vested_so_far)?,
+            })
+        })
+        .collect()
+}
+
+/// Computes the running totals from all existing vesting accounts.
+/// Used to initialize [`TOTAL_VESTING`] and [`ACCOUNT_COUNT`] for contracts deployed before they were introduced.
+fn init_total_vesting(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut totals = TotalVestingResponse::default();
+    let mut account_count = 0u64;
+
+    for item in VESTING_INFO.range(storage, None, None, Order::Ascending) {
+        let (_, info) = item?;
+        account_count += 1;
+        for sch in &info.schedules {
+            let amount = sch.end_point.unwrap_or(sch.start_point).amount;
+            totals.total_scheduled = totals.total_scheduled.checked_add(amount)?;
+        }
+        totals.total_released = totals.total_released.checked_add(info.released_amount)?;
+    }
+
+    TOTAL_VESTING.save(storage, &totals)?;
+    ACCOUNT_COUNT.save(storage, &account_count)
+}
+
 /// Manages contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
@@ -570,7 +812,10 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, Con
 
                 config.vesting_token = AssetInfo::native(&converter_config.new_astro_denom);
                 CONFIG.save(deps.storage, &config)?;
+
+                init_total_vesting(deps.storage)?;
             }
+            "1.4.0" => init_total_vesting(deps.storage)?,
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
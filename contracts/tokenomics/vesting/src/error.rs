@@ -2,6 +2,8 @@ use cosmwasm_std::{OverflowError, StdError, Uint128};
 use cw_utils::PaymentError;
 use thiserror::Error;
 
+use astroport::vesting::MAX_SCHEDULES_LIMIT;
+
 /// This enum describes generator vesting contract errors
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -40,6 +42,9 @@ pub enum ContractError {
     #[error("For account {0} number of schedules exceeds maximum limit")]
     ExceedSchedulesMaximumLimit(String),
 
+    #[error("Max schedules must be between 1 and {MAX_SCHEDULES_LIMIT}")]
+    InvalidMaxSchedules {},
+
     #[error("Failed to withdraw from active schedule: amount left {0}")]
     NotEnoughTokens(Uint128),
 }
@@ -2,7 +2,7 @@ use cosmwasm_schema::cw_serde;
 
 use astroport::asset::AssetInfo;
 use astroport::common::OwnershipProposal;
-use astroport::vesting::{OrderBy, VestingInfo};
+use astroport::vesting::{OrderBy, TotalVestingResponse, VestingInfo};
 use cosmwasm_std::{Addr, Deps, StdResult};
 use cw_storage_plus::{Bound, Item, Map};
 
@@ -21,6 +21,20 @@ pub const CONFIG: Item<Config> = Item::new("config");
 /// The first key is the address of an account that's vesting, the second key is an object of type [`VestingInfo`].
 pub const VESTING_INFO: Map<&Addr, VestingInfo> = Map::new("vesting_info");
 
+/// Stores the running totals of scheduled and released tokens across all vesting accounts.
+pub const TOTAL_VESTING: Item<TotalVestingResponse> = Item::new("total_vesting");
+
+/// Stores the number of accounts which have ever been registered.
+pub const ACCOUNT_COUNT: Item<u64> = Item::new("account_count");
+
+/// Stores the maximum number of schedules per account. [`DEFAULT_MAX_SCHEDULES`] applies if not set.
+///
+/// [`DEFAULT_MAX_SCHEDULES`]: astroport::vesting::DEFAULT_MAX_SCHEDULES
+pub const MAX_SCHEDULES: Item<u32> = Item::new("max_schedules");
+
+/// Accounts which are allowed to register vesting accounts besides the owner.
+pub const REGISTRARS: Map<&Addr, ()> = Map::new("registrars");
+
 /// Contains a proposal to change contract ownership.
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
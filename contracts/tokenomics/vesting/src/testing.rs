@@ -1,9 +1,14 @@
-use crate::contract::{execute, instantiate, query};
-use astroport::vesting::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::contract::{execute, instantiate, migrate, query};
+use crate::state::{ACCOUNT_COUNT, TOTAL_VESTING, VESTING_INFO};
+use astroport::vesting::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, TotalVestingResponse,
+    VestingInfo, VestingSchedule, VestingSchedulePoint, VestingStatsResponse,
+};
 
 use astroport::asset::{token_asset_info, AssetInfo};
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_json, Addr};
+use cosmwasm_std::{from_json, Addr, Uint128};
+use cw2::set_contract_version;
 
 #[test]
 fn proper_initialization() {
@@ -132,3 +137,55 @@ fn update_owner() {
         from_json(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
     assert_eq!(new_owner, config.owner);
 }
+
+#[test]
+fn migrate_from_1_4_0_initializes_totals() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        vesting_token: AssetInfo::NativeToken {
+            denom: "astro".to_string(),
+        },
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // Emulate the state of a 1.4.0 contract which didn't track totals
+    TOTAL_VESTING.remove(deps.as_mut().storage);
+    ACCOUNT_COUNT.remove(deps.as_mut().storage);
+    set_contract_version(deps.as_mut().storage, "astroport-vesting", "1.4.0").unwrap();
+    for (user, amount, released) in [("user1", 1000u128, 200u128), ("user2", 500, 0)] {
+        let info = VestingInfo {
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: 0,
+                    amount: Uint128::new(amount),
+                },
+                end_point: None,
+            }],
+            released_amount: Uint128::new(released),
+        };
+        VESTING_INFO
+            .save(deps.as_mut().storage, &Addr::unchecked(user), &info)
+            .unwrap();
+    }
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            converter_contract: "converter".to_string(),
+        },
+    )
+    .unwrap();
+
+    let totals: TotalVestingResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::TotalVesting {}).unwrap()).unwrap();
+    assert_eq!(totals.total_scheduled.u128(), 1500);
+    assert_eq!(totals.total_released.u128(), 200);
+
+    let stats: VestingStatsResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats.account_count, 2);
+    assert_eq!(stats.total_remaining.u128(), 1300);
+}
@@ -10,11 +10,14 @@ use astroport::asset::{native_asset_info, token_asset_info, AssetInfo};
 use astroport::astro_converter;
 use astroport::astro_converter::OutpostBurnParams;
 use astroport::querier::query_balance;
+use astroport::vesting::{
+    ClaimableResponse, QueryMsg, ScheduleBreakdown, TotalVestingResponse, VestingAccountResponse,
+    VestingAccountsResponse, VestingInfo, VestingStatsResponse,
+};
 use astroport::vesting::{
     Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, VestingAccount, VestingSchedule,
-    VestingSchedulePoint,
+    VestingSchedulePoint, DEFAULT_MAX_SCHEDULES, MAX_SCHEDULES_LIMIT,
 };
-use astroport::vesting::{QueryMsg, VestingAccountResponse, VestingAccountsResponse, VestingInfo};
 use astroport_vesting::error::ContractError;
 use astroport_vesting::state::Config;
 
@@ -385,6 +388,211 @@ fn claim_native() {
     assert_eq!(user1_vesting_amount.clone(), Uint128::new(0u128));
 }
 
+#[test]
+fn claimable() {
+    let user1 = Addr::unchecked(USER1);
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app(&owner);
+
+    let vesting_instance = instantiate_vesting_remote_chain(&mut app);
+
+    let current_time = app.block_info().time.seconds();
+
+    let msg = ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: current_time + 100,
+                    amount: Uint128::zero(),
+                },
+                end_point: Some(VestingSchedulePoint {
+                    time: current_time + 200,
+                    amount: Uint128::new(100),
+                }),
+            }],
+        }],
+    };
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &msg,
+        &coins(100, IBC_ASTRO),
+    )
+    .unwrap();
+
+    let query_claimable = |app: &App, address: &Addr| -> ClaimableResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &vesting_instance,
+                &QueryMsg::Claimable {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    };
+
+    // Nothing is claimable before the schedule starts
+    assert_eq!(
+        query_claimable(&app, &user1),
+        ClaimableResponse {
+            amount: Uint128::zero(),
+            has_claimable: false
+        }
+    );
+
+    // Addresses without schedules have nothing to claim
+    assert_eq!(
+        query_claimable(&app, &Addr::unchecked(USER2)),
+        ClaimableResponse {
+            amount: Uint128::zero(),
+            has_claimable: false
+        }
+    );
+
+    // Half of the schedule is claimable in the middle of it
+    app.update_block(|b| b.time = b.time.plus_seconds(150));
+    assert_eq!(
+        query_claimable(&app, &user1),
+        ClaimableResponse {
+            amount: Uint128::new(50),
+            has_claimable: true
+        }
+    );
+
+    // Everything is claimable after the schedule ends
+    app.update_block(|b| b.time = b.time.plus_seconds(100));
+    assert_eq!(
+        query_claimable(&app, &user1),
+        ClaimableResponse {
+            amount: Uint128::new(100),
+            has_claimable: true
+        }
+    );
+
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_claimable(&app, &user1),
+        ClaimableResponse {
+            amount: Uint128::zero(),
+            has_claimable: false
+        }
+    );
+}
+
+#[test]
+fn schedule_breakdown() {
+    let user1 = Addr::unchecked(USER1);
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app(&owner);
+
+    let vesting_instance = instantiate_vesting_remote_chain(&mut app);
+
+    let current_time = app.block_info().time.seconds();
+
+    let msg = ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: vec![
+                VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: current_time,
+                        amount: Uint128::new(100),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: current_time + 200,
+                        amount: Uint128::new(300),
+                    }),
+                },
+                VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: current_time + 200,
+                        amount: Uint128::zero(),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: current_time + 400,
+                        amount: Uint128::new(1000),
+                    }),
+                },
+            ],
+        }],
+    };
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &msg,
+        &coins(1300, IBC_ASTRO),
+    )
+    .unwrap();
+
+    let query_breakdown = |app: &App, address: &Addr| -> Vec<ScheduleBreakdown> {
+        app.wrap()
+            .query_wasm_smart(
+                &vesting_instance,
+                &QueryMsg::ScheduleBreakdown {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    };
+
+    // Addresses without schedules have an empty breakdown
+    assert_eq!(query_breakdown(&app, &Addr::unchecked(USER2)), vec![]);
+
+    // The first schedule is halfway through, the second one hasn't started yet
+    app.update_block(|b| b.time = b.time.plus_seconds(100));
+    assert_eq!(
+        query_breakdown(&app, &user1),
+        vec![
+            ScheduleBreakdown {
+                start: current_time,
+                end: current_time + 200,
+                total: Uint128::new(300),
+                vested_so_far: Uint128::new(200),
+                remaining: Uint128::new(100),
+            },
+            ScheduleBreakdown {
+                start: current_time + 200,
+                end: current_time + 400,
+                total: Uint128::new(1000),
+                vested_so_far: Uint128::zero(),
+                remaining: Uint128::new(1000),
+            }
+        ]
+    );
+
+    // The first schedule is fully vested, the second one is halfway through
+    app.update_block(|b| b.time = b.time.plus_seconds(200));
+    let breakdown = query_breakdown(&app, &user1);
+    assert_eq!(breakdown[0].vested_so_far, Uint128::new(300));
+    assert_eq!(breakdown[0].remaining, Uint128::zero());
+    assert_eq!(breakdown[1].vested_so_far, Uint128::new(500));
+    assert_eq!(breakdown[1].remaining, Uint128::new(500));
+
+    // The breakdown matches the claimable amount while nothing is claimed
+    let claimable: ClaimableResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &vesting_instance,
+            &QueryMsg::Claimable {
+                address: user1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(claimable.amount, Uint128::new(800));
+}
+
 #[test]
 fn claim_after_migration() {
     let user1 = Addr::unchecked(USER1);
@@ -1430,6 +1638,465 @@ fn withdraw_overlapping_schedules2() {
     );
 }
 
+#[test]
+fn max_schedules_per_account() {
+    let owner = Addr::unchecked(OWNER1);
+    let user1 = Addr::unchecked(USER1);
+    let mut app = mock_app(&owner);
+    let vesting_instance = instantiate_vesting_remote_chain(&mut app);
+
+    let max_schedules: u32 = app
+        .wrap()
+        .query_wasm_smart(&vesting_instance, &QueryMsg::MaxSchedules {})
+        .unwrap();
+    assert_eq!(max_schedules, DEFAULT_MAX_SCHEDULES);
+
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            vesting_instance.clone(),
+            &ExecuteMsg::SetMaxSchedules { max_schedules: 2 },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    for max_schedules in [0, MAX_SCHEDULES_LIMIT + 1] {
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                vesting_instance.clone(),
+                &ExecuteMsg::SetMaxSchedules { max_schedules },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidMaxSchedules {},
+            err.downcast().unwrap()
+        );
+    }
+
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::SetMaxSchedules { max_schedules: 2 },
+        &[],
+    )
+    .unwrap();
+
+    let current_time = app.block_info().time.seconds();
+    let register_msg = |schedules_num: u64| ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: (0..schedules_num)
+                .map(|i| VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: current_time + 100 + i,
+                        amount: Uint128::zero(),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: current_time + 200 + i,
+                        amount: Uint128::new(100),
+                    }),
+                })
+                .collect(),
+        }],
+    };
+
+    // Registering more schedules than allowed in a single batch is rejected
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            vesting_instance.clone(),
+            &register_msg(3),
+            &coins(300, IBC_ASTRO),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ExceedSchedulesMaximumLimit(user1.to_string()),
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &register_msg(2),
+        &coins(200, IBC_ASTRO),
+    )
+    .unwrap();
+
+    // Existing schedules count towards the limit
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            vesting_instance.clone(),
+            &register_msg(1),
+            &coins(100, IBC_ASTRO),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ExceedSchedulesMaximumLimit(user1.to_string()),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn registrars() {
+    let owner = Addr::unchecked(OWNER1);
+    let user1 = Addr::unchecked(USER1);
+    let registrar = Addr::unchecked(USER2);
+    let mut app = mock_app(&owner);
+    let vesting_instance = instantiate_vesting_remote_chain(&mut app);
+
+    app.send_tokens(owner.clone(), registrar.clone(), &coins(1000, IBC_ASTRO))
+        .unwrap();
+
+    let schedule = |start: u64| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: start,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: start + 100,
+            amount: Uint128::new(100),
+        }),
+    };
+    let register_msg = |start: u64| ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: vec![schedule(start)],
+        }],
+    };
+
+    let current_time = app.block_info().time.seconds();
+    let err = app
+        .execute_contract(
+            registrar.clone(),
+            vesting_instance.clone(),
+            &register_msg(current_time),
+            &coins(100, IBC_ASTRO),
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let update_msg = ExecuteMsg::UpdateRegistrars {
+        add: vec![registrar.to_string()],
+        remove: vec![],
+    };
+    let err = app
+        .execute_contract(
+            registrar.clone(),
+            vesting_instance.clone(),
+            &update_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    app.execute_contract(owner.clone(), vesting_instance.clone(), &update_msg, &[])
+        .unwrap();
+    let registrars: Vec<Addr> = app
+        .wrap()
+        .query_wasm_smart(&vesting_instance, &QueryMsg::Registrars {})
+        .unwrap();
+    assert_eq!(registrars, vec![registrar.clone()]);
+
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::SetMaxSchedules { max_schedules: 1 },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        registrar.clone(),
+        vesting_instance.clone(),
+        &register_msg(current_time),
+        &coins(100, IBC_ASTRO),
+    )
+    .unwrap();
+
+    // A fully vested schedule counts towards the limit until it is claimed
+    app.update_block(|b| b.time = b.time.plus_seconds(100));
+    let current_time = app.block_info().time.seconds();
+    let err = app
+        .execute_contract(
+            registrar.clone(),
+            vesting_instance.clone(),
+            &register_msg(current_time),
+            &coins(100, IBC_ASTRO),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ExceedSchedulesMaximumLimit(user1.to_string()),
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Claimed schedules are removed on registration
+    app.execute_contract(
+        registrar.clone(),
+        vesting_instance.clone(),
+        &register_msg(current_time),
+        &coins(100, IBC_ASTRO),
+    )
+    .unwrap();
+    let vesting_account: VestingAccountResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &vesting_instance,
+            &QueryMsg::VestingAccount {
+                address: user1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        vesting_account.info,
+        VestingInfo {
+            schedules: vec![schedule(current_time)],
+            released_amount: Uint128::zero(),
+        }
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::UpdateRegistrars {
+            add: vec![],
+            remove: vec![registrar.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            registrar.clone(),
+            vesting_instance.clone(),
+            &register_msg(current_time),
+            &coins(100, IBC_ASTRO),
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+}
+
+#[test]
+fn total_vesting() {
+    let owner = Addr::unchecked(OWNER1);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let mut app = mock_app(&owner);
+    let token_code_id = store_token_code(&mut app);
+    let astro_token = instantiate_token(&mut app, token_code_id, "Astro", None);
+    let vesting_instance = instantiate_vesting(&mut app, &astro_token);
+
+    let query_totals = |app: &App| -> TotalVestingResponse {
+        app.wrap()
+            .query_wasm_smart(&vesting_instance, &QueryMsg::TotalVesting {})
+            .unwrap()
+    };
+
+    assert_eq!(query_totals(&app), TotalVestingResponse::default());
+
+    let current_time = app.block_info().time.seconds();
+    let msg = Cw20ExecuteMsg::Send {
+        contract: vesting_instance.to_string(),
+        msg: to_json_binary(&Cw20HookMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![
+                VestingAccount {
+                    address: user1.to_string(),
+                    schedules: vec![VestingSchedule {
+                        start_point: VestingSchedulePoint {
+                            time: current_time,
+                            amount: Uint128::zero(),
+                        },
+                        end_point: Some(VestingSchedulePoint {
+                            time: current_time + 100,
+                            amount: Uint128::new(1000),
+                        }),
+                    }],
+                },
+                VestingAccount {
+                    address: user2.to_string(),
+                    schedules: vec![VestingSchedule {
+                        start_point: VestingSchedulePoint {
+                            time: current_time,
+                            amount: Uint128::new(500),
+                        },
+                        end_point: None,
+                    }],
+                },
+            ],
+        })
+        .unwrap(),
+        amount: Uint128::new(1500),
+    };
+    app.execute_contract(owner.clone(), astro_token.clone(), &msg, &[])
+        .unwrap();
+
+    assert_eq!(
+        query_totals(&app),
+        TotalVestingResponse {
+            total_scheduled: Uint128::new(1500),
+            total_released: Uint128::zero(),
+        }
+    );
+
+    app.update_block(|b| b.time = b.time.plus_seconds(50));
+
+    claim_and_check(&mut app, &user1, &vesting_instance, &astro_token, 500);
+    claim_and_check(&mut app, &user2, &vesting_instance, &astro_token, 500);
+
+    assert_eq!(
+        query_totals(&app),
+        TotalVestingResponse {
+            total_scheduled: Uint128::new(1500),
+            total_released: Uint128::new(1000),
+        }
+    );
+
+    // Withdrawn tokens are removed from the scheduled total
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::WithdrawFromActiveSchedule {
+            account: user1.to_string(),
+            recipient: None,
+            withdraw_amount: Uint128::new(100),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.update_block(|b| b.time = b.time.plus_seconds(50));
+    claim_and_check(&mut app, &user1, &vesting_instance, &astro_token, 900);
+
+    let totals = query_totals(&app);
+    assert_eq!(
+        totals,
+        TotalVestingResponse {
+            total_scheduled: Uint128::new(1400),
+            total_released: Uint128::new(1400),
+        }
+    );
+    check_token_balance(
+        &mut app,
+        &astro_token,
+        &vesting_instance,
+        (totals.total_scheduled - totals.total_released).u128(),
+    );
+}
+
+#[test]
+fn vesting_stats() {
+    let owner = Addr::unchecked(OWNER1);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let mut app = mock_app(&owner);
+    let token_code_id = store_token_code(&mut app);
+    let astro_token = instantiate_token(&mut app, token_code_id, "Astro", None);
+    let vesting_instance = instantiate_vesting(&mut app, &astro_token);
+
+    let query_stats = |app: &App| -> VestingStatsResponse {
+        app.wrap()
+            .query_wasm_smart(&vesting_instance, &QueryMsg::Stats {})
+            .unwrap()
+    };
+
+    assert_eq!(query_stats(&app), VestingStatsResponse::default());
+
+    let current_time = app.block_info().time.seconds();
+    let register_msg = |accounts: Vec<&Addr>, amount: u128| Cw20ExecuteMsg::Send {
+        contract: vesting_instance.to_string(),
+        msg: to_json_binary(&Cw20HookMsg::RegisterVestingAccounts {
+            vesting_accounts: accounts
+                .into_iter()
+                .map(|account| VestingAccount {
+                    address: account.to_string(),
+                    schedules: vec![VestingSchedule {
+                        start_point: VestingSchedulePoint {
+                            time: current_time,
+                            amount: Uint128::zero(),
+                        },
+                        end_point: Some(VestingSchedulePoint {
+                            time: current_time + 100,
+                            amount: Uint128::new(amount),
+                        }),
+                    }],
+                })
+                .collect(),
+        })
+        .unwrap(),
+        amount: Uint128::new(amount * 2),
+    };
+
+    app.execute_contract(
+        owner.clone(),
+        astro_token.clone(),
+        &register_msg(vec![&user1, &user2], 1000),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_stats(&app),
+        VestingStatsResponse {
+            account_count: 2,
+            total_scheduled: Uint128::new(2000),
+            total_released: Uint128::zero(),
+            total_remaining: Uint128::new(2000),
+        }
+    );
+
+    // Adding schedules to an existing account doesn't change the account count
+    app.execute_contract(
+        owner.clone(),
+        astro_token.clone(),
+        &register_msg(vec![&user1, &user1], 500),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_stats(&app),
+        VestingStatsResponse {
+            account_count: 2,
+            total_scheduled: Uint128::new(3000),
+            total_released: Uint128::zero(),
+            total_remaining: Uint128::new(3000),
+        }
+    );
+
+    app.update_block(|b| b.time = b.time.plus_seconds(50));
+    claim_and_check(&mut app, &user1, &vesting_instance, &astro_token, 1000);
+    claim_and_check(&mut app, &user2, &vesting_instance, &astro_token, 500);
+
+    let stats = query_stats(&app);
+    assert_eq!(
+        stats,
+        VestingStatsResponse {
+            account_count: 2,
+            total_scheduled: Uint128::new(3000),
+            total_released: Uint128::new(1500),
+            total_remaining: Uint128::new(1500),
+        }
+    );
+    check_token_balance(
+        &mut app,
+        &astro_token,
+        &vesting_instance,
+        stats.total_remaining.u128(),
+    );
+}
+
 fn mock_app(owner: &Addr) -> App {
     App::new(|app, _, storage| {
         app.bank
@@ -1,7 +1,6 @@
 use cosmwasm_schema::write_api;
 
-use astroport::xastro_token::{InstantiateMsg, QueryMsg};
-use cw20_base::msg::ExecuteMsg;
+use astroport::xastro_token::{ExecuteMsg, InstantiateMsg, QueryMsg};
 
 fn main() {
     write_api! {
@@ -1,6 +1,6 @@
 use cosmwasm_std::{
-    attr, entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
-    StdError, StdResult, Uint128,
+    attr, entry_point, to_json_binary, Binary, Deps, DepsMut, Env, Int128, MessageInfo, Order,
+    Response, StdError, StdResult, Uint128,
 };
 use cw20::{
     AllAccountsResponse, BalanceResponse, Cw20Coin, Cw20ReceiveMsg, EmbeddedLogo, Logo, LogoInfo,
@@ -12,14 +12,15 @@ use cw20_base::allowances::{
 
 use crate::state::{capture_total_supply_history, check_minter, get_total_supply_at, BALANCES};
 use astroport::asset::addr_opt_validate;
-use astroport::xastro_token::{InstantiateMsg, MigrateMsg, QueryMsg};
+use astroport::xastro_token::{
+    AllowanceIncrease, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, MAX_ALLOWANCES_BATCH,
+};
 use cw2::{get_contract_version, set_contract_version};
 use cw20_base::contract::{
     execute_update_marketing, execute_upload_logo, query_download_logo, query_marketing_info,
     query_minter, query_token_info,
 };
 use cw20_base::enumerable::query_owner_allowances;
-use cw20_base::msg::ExecuteMsg;
 use cw20_base::state::{MinterData, TokenInfo, LOGO, MARKETING_INFO, TOKEN_INFO};
 use cw20_base::ContractError;
 use cw_storage_plus::Bound;
@@ -195,10 +196,14 @@ pub fn create_accounts(deps: &mut DepsMut, env: &Env, accounts: &[Cw20Coin]) ->
 ///
 /// * **ExecuteMsg::Mint { recipient, amount }** Mints tokens.
 ///
+/// * **ExecuteMsg::UpdateMintCap { cap }** Sets or lowers the total supply cap.
+///
 /// * **ExecuteMsg::IncreaseAllowance { spender, amount, expires }** Increases allowance.
 ///
 /// * **ExecuteMsg::DecreaseAllowance { spender, amount, expires }** Decreases allowance.
 ///
+/// * **ExecuteMsg::IncreaseAllowanceBatch { allowances }** Increases allowances of several spenders.
+///
 /// * **ExecuteMsg::TransferFrom { owner, recipient, amount }** Transfers tokens from.
 ///
 /// * **ExecuteMsg::BurnFrom { owner, amount }** Burns tokens from.
@@ -226,6 +231,7 @@ pub fn execute(
             msg,
         } => execute_send(deps, env, info, contract, amount, msg),
         ExecuteMsg::Mint { recipient, amount } => execute_mint(deps, env, info, recipient, amount),
+        ExecuteMsg::UpdateMintCap { cap } => execute_update_mint_cap(deps, env, info, cap),
         ExecuteMsg::IncreaseAllowance {
             spender,
             amount,
@@ -236,6 +242,9 @@ pub fn execute(
             amount,
             expires,
         } => execute_decrease_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::IncreaseAllowanceBatch { allowances } => {
+            execute_increase_allowance_batch(deps, env, info, allowances)
+        }
         ExecuteMsg::TransferFrom {
             owner,
             recipient,
@@ -254,10 +263,58 @@ pub fn execute(
             marketing,
         } => execute_update_marketing(deps, env, info, project, description, marketing),
         ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
-        _ => Err(StdError::generic_err("Unsupported execute message").into()),
     }
 }
 
+/// Increases allowances of several spenders in a single message.
+/// All spenders are validated before any allowance is changed, so either all allowances are increased or none.
+pub fn execute_increase_allowance_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allowances: Vec<AllowanceIncrease>,
+) -> Result<Response, ContractError> {
+    if allowances.is_empty() {
+        return Err(StdError::generic_err("Allowances list must not be empty").into());
+    }
+    if allowances.len() > MAX_ALLOWANCES_BATCH {
+        return Err(StdError::generic_err(format!(
+            "Too many allowances in a batch. Maximum is {MAX_ALLOWANCES_BATCH}"
+        ))
+        .into());
+    }
+    for allowance in &allowances {
+        let spender = deps.api.addr_validate(&allowance.spender)?;
+        if spender == info.sender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+    }
+
+    let mut attributes = vec![attr("action", "increase_allowance_batch")];
+    for AllowanceIncrease {
+        spender,
+        amount,
+        expires,
+    } in allowances
+    {
+        let resp = execute_increase_allowance(
+            deps.branch(),
+            env.clone(),
+            info.clone(),
+            spender,
+            amount,
+            expires,
+        )?;
+        attributes.extend(
+            resp.attributes
+                .into_iter()
+                .filter(|attribute| attribute.key != "action"),
+        );
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
 /// Executes a token transfer.
 pub fn execute_transfer(
     deps: DepsMut,
@@ -379,6 +436,53 @@ pub fn execute_mint(
     ]))
 }
 
+/// Sets a new total supply cap which is enforced on subsequent mints.
+/// The cap can only be lowered and never below the current total supply.
+/// Only the contract admin can execute this.
+///
+/// * **cap** new maximum total supply.
+pub fn execute_update_mint_cap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cap: Uint128,
+) -> Result<Response, ContractError> {
+    let contract_info = deps
+        .querier
+        .query_wasm_contract_info(&env.contract.address)?;
+    if contract_info.admin != Some(info.sender.to_string()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config = TOKEN_INFO.load(deps.storage)?;
+    if config.mint.is_none() {
+        return Err(StdError::generic_err("Minting is disabled").into());
+    }
+
+    if let Some(current_cap) = config.get_cap() {
+        if cap > current_cap {
+            return Err(StdError::generic_err(format!(
+                "Cap can't be raised above the current cap {current_cap}"
+            ))
+            .into());
+        }
+    }
+    if cap < config.total_supply {
+        return Err(StdError::generic_err(format!(
+            "Cap can't be lower than the current total supply {}",
+            config.total_supply
+        ))
+        .into());
+    }
+
+    if let Some(mint_data) = config.mint.as_mut() {
+        mint_data.cap = Some(cap);
+    }
+    TOKEN_INFO.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_mint_cap"), attr("cap", cap)]))
+}
+
 /// Executes a token send.
 ///
 /// * **contract** token contract.
@@ -587,6 +691,9 @@ pub fn execute_send_from(
 /// * **BalanceAt { address, block }** Returns the balance of the given address at the given block
 /// using a [`BalanceResponse`] object.
 ///
+/// * **BalanceDelta { address, start_block, end_block }** Returns the signed change of the address balance
+/// between the given blocks.
+///
 /// * **TotalSupplyAt { block }** Returns the total supply at the given block.
 ///
 /// * **TokenInfo {}** Returns the token metadata - name, decimals, supply, etc
@@ -616,6 +723,11 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::BalanceAt { address, block } => {
             to_json_binary(&query_balance_at(deps, address, block)?)
         }
+        QueryMsg::BalanceDelta {
+            address,
+            start_block,
+            end_block,
+        } => to_json_binary(&query_balance_delta(deps, address, start_block, end_block)?),
         QueryMsg::TotalSupplyAt { block } => {
             to_json_binary(&get_total_supply_at(deps.storage, block)?)
         }
@@ -655,6 +767,30 @@ pub fn query_balance_at(deps: Deps, address: String, block: u64) -> StdResult<Ba
     Ok(BalanceResponse { balance })
 }
 
+/// Returns the signed change of the address balance between `start_block` and `end_block`.
+pub fn query_balance_delta(
+    deps: Deps,
+    address: String,
+    start_block: u64,
+    end_block: u64,
+) -> StdResult<Int128> {
+    if start_block > end_block {
+        return Err(StdError::generic_err(
+            "start_block must not be greater than end_block",
+        ));
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    let start_balance = BALANCES
+        .may_load_at_height(deps.storage, &address, start_block)?
+        .unwrap_or_default();
+    let end_balance = BALANCES
+        .may_load_at_height(deps.storage, &address, end_block)?
+        .unwrap_or_default();
+
+    Ok(Int128::try_from(end_balance)? - Int128::try_from(start_balance)?)
+}
+
 /// Returns the current balances of multiple accounts.
 ///
 /// * **start_after** account from which to start querying for balances.
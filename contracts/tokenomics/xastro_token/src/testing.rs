@@ -1,21 +1,23 @@
 use crate::contract::{
     execute, execute_burn_from, execute_send_from, execute_transfer_from, instantiate,
-    query_all_accounts, query_balance, query_balance_at,
+    query_all_accounts, query_balance, query_balance_at, query_balance_delta,
 };
 use crate::state::get_total_supply_at;
-use astroport::xastro_token::InstantiateMsg;
+use astroport::xastro_token::{
+    AllowanceIncrease, ExecuteMsg, InstantiateMsg, MAX_ALLOWANCES_BATCH,
+};
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    Addr, Binary, BlockInfo, ContractInfo, CosmosMsg, Deps, DepsMut, Env, StdError, SubMsg,
-    Timestamp, Uint128, WasmMsg,
+    to_json_binary, Addr, Binary, BlockInfo, ContractInfo, ContractInfoResponse, ContractResult,
+    CosmosMsg, Deps, DepsMut, Env, Int128, StdError, SubMsg, SystemResult, Timestamp, Uint128,
+    WasmMsg, WasmQuery,
 };
 use cw20::{
     AllAccountsResponse, BalanceResponse, Cw20Coin, Cw20ReceiveMsg, MinterResponse,
     TokenInfoResponse,
 };
-use cw20_base::allowances::execute_increase_allowance;
+use cw20_base::allowances::{execute_increase_allowance, query_allowance};
 use cw20_base::contract::{query_minter, query_token_info};
-use cw20_base::msg::ExecuteMsg;
 use cw20_base::ContractError;
 
 pub struct MockEnvParams {
@@ -294,6 +296,95 @@ fn others_cannot_mint() {
     assert_eq!(err, ContractError::Unauthorized {});
 }
 
+#[test]
+fn update_mint_cap() {
+    let mut deps = mock_dependencies();
+
+    let genesis = String::from("genesis");
+    let amount = Uint128::new(1_000);
+    let minter = String::from("minter");
+    let admin = String::from("admin");
+    do_instantiate_with_minter(deps.as_mut(), &genesis, amount, &minter, None);
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::ContractInfo { .. } => {
+            let mut contract_info = ContractInfoResponse::new(1, "creator");
+            contract_info.admin = Some("admin".to_string());
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&contract_info).unwrap()))
+        }
+        _ => unimplemented!(),
+    });
+
+    // Only the contract admin can set the cap
+    for sender in ["anyone", minter.as_str()] {
+        let msg = ExecuteMsg::UpdateMintCap {
+            cap: Uint128::new(2_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    // The cap can't be set below the current total supply
+    let msg = ExecuteMsg::UpdateMintCap {
+        cap: Uint128::new(999),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info(&admin, &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "Cap can't be lower than the current total supply 1000"
+        ))
+    );
+
+    let msg = ExecuteMsg::UpdateMintCap {
+        cap: Uint128::new(2_000),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(&admin, &[]), msg).unwrap();
+    assert_eq!(
+        query_minter(deps.as_ref()).unwrap().unwrap().cap,
+        Some(Uint128::new(2_000))
+    );
+
+    // The cap can't be raised
+    let msg = ExecuteMsg::UpdateMintCap {
+        cap: Uint128::new(3_000),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info(&admin, &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "Cap can't be raised above the current cap 2000"
+        ))
+    );
+
+    // Mints beyond the new cap fail
+    let msg = ExecuteMsg::Mint {
+        recipient: String::from("lucky"),
+        amount: Uint128::new(1_001),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    // Lowering the cap further is allowed and enforced as well
+    let msg = ExecuteMsg::UpdateMintCap {
+        cap: Uint128::new(1_500),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(&admin, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Mint {
+        recipient: String::from("lucky"),
+        amount: Uint128::new(501),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    let msg = ExecuteMsg::Mint {
+        recipient: String::from("lucky"),
+        amount: Uint128::new(500),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), "lucky"), Uint128::new(500));
+}
+
 #[test]
 fn no_one_mints_if_minter_unset() {
     let mut deps = mock_dependencies();
@@ -982,3 +1073,135 @@ fn test_balance_history() {
         }
     );
 }
+
+#[test]
+fn increase_allowance_batch() {
+    let mut deps = mock_dependencies();
+    let owner = String::from("addr0001");
+    do_instantiate(deps.as_mut(), &owner, Uint128::new(1_000_000));
+
+    let spenders = ["pool0001", "pool0002", "pool0003"];
+    let allowances = spenders
+        .iter()
+        .enumerate()
+        .map(|(i, spender)| AllowanceIncrease {
+            spender: spender.to_string(),
+            amount: Uint128::new(1000 * (i as u128 + 1)),
+            expires: None,
+        })
+        .collect::<Vec<_>>();
+
+    // Empty batch is rejected
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        ExecuteMsg::IncreaseAllowanceBatch { allowances: vec![] },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("Allowances list must not be empty"))
+    );
+
+    // Batch size is bounded
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        ExecuteMsg::IncreaseAllowanceBatch {
+            allowances: vec![allowances[0].clone(); MAX_ALLOWANCES_BATCH + 1],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(format!(
+            "Too many allowances in a batch. Maximum is {MAX_ALLOWANCES_BATCH}"
+        )))
+    );
+
+    // Nothing is applied if one of the spenders is invalid
+    let mut invalid_batch = allowances.clone();
+    invalid_batch.push(AllowanceIncrease {
+        spender: owner.clone(),
+        amount: Uint128::new(1),
+        expires: None,
+    });
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        ExecuteMsg::IncreaseAllowanceBatch {
+            allowances: invalid_batch,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CannotSetOwnAccount {});
+    for spender in spenders {
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.to_string()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::zero());
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&owner, &[]),
+        ExecuteMsg::IncreaseAllowanceBatch {
+            allowances: allowances.clone(),
+        },
+    )
+    .unwrap();
+
+    for AllowanceIncrease {
+        spender, amount, ..
+    } in allowances
+    {
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender).unwrap();
+        assert_eq!(allowance.allowance, amount);
+    }
+}
+
+#[test]
+fn balance_delta() {
+    let mut deps = mock_dependencies();
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let amount1 = Uint128::from(12340000u128);
+    let transfer = Uint128::from(76543u128);
+
+    do_instantiate(deps.as_mut(), &addr1, amount1);
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let env = test_mock_env(MockEnvParams {
+        block_height: 100_000,
+        ..Default::default()
+    });
+    let msg = ExecuteMsg::Transfer {
+        recipient: addr2.clone(),
+        amount: transfer,
+    };
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let delta = Int128::new(transfer.u128() as i128);
+    assert_eq!(
+        query_balance_delta(deps.as_ref(), addr1.clone(), 99_000, 100_001).unwrap(),
+        -delta
+    );
+    assert_eq!(
+        query_balance_delta(deps.as_ref(), addr2.clone(), 99_000, 100_001).unwrap(),
+        delta
+    );
+
+    // No change within a window that ends before the transfer
+    assert_eq!(
+        query_balance_delta(deps.as_ref(), addr1.clone(), 99_000, 100_000).unwrap(),
+        Int128::zero()
+    );
+
+    let err = query_balance_delta(deps.as_ref(), addr1, 100_001, 99_000).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("start_block must not be greater than end_block")
+    );
+}
@@ -23,7 +23,8 @@ use crate::querier::{
 pub const UUSD_DENOM: &str = "uusd";
 /// LUNA token denomination
 pub const ULUNA_DENOM: &str = "uluna";
-/// Minimum initial LP share
+/// Minimum initial LP share. The xyk, stable and concentrated pairs mint this amount to themselves
+/// on the first provide and never burn it, so the pool can't be fully drained by its first depositor
 pub const MINIMUM_LIQUIDITY_AMOUNT: Uint128 = Uint128::new(1_000);
 /// Maximum denom length
 pub const DENOM_MAX_LENGTH: usize = 128;
@@ -135,6 +135,14 @@ where
     ]))
 }
 
+/// Returns whether `address` is the current contract owner.
+/// A proposed owner is not considered the owner until the proposal is claimed.
+///
+/// `owner` is the current owner.
+pub fn query_is_owner(api: &dyn Api, owner: &Addr, address: &str) -> StdResult<bool> {
+    Ok(api.addr_validate(address)? == owner)
+}
+
 /// Bulk validation and conversion between [`String`] -> [`Addr`] for an array of addresses.
 /// If any address is invalid, the function returns [`StdError`].
 pub fn validate_addresses(api: &dyn Api, admins: &[String]) -> StdResult<Vec<Addr>> {
@@ -1,12 +1,30 @@
-use crate::asset::{AssetInfo, PairInfo};
+use crate::asset::{Asset, AssetInfo, PairInfo};
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
+use cw_storage_plus::Map;
+use itertools::Itertools;
 use std::fmt::{Display, Formatter, Result};
 
 const MAX_TOTAL_FEE_BPS: u16 = 10_000;
 const MAX_MAKER_FEE_BPS: u16 = 10_000;
 
+/// Saves created pairs (from olders to latest). Keyed by [`pair_key`]
+pub const PAIRS: Map<&[u8], Addr> = Map::new("pair_info");
+
+/// Calculates a pair key from the specified parameters in the `asset_infos` variable.
+///
+/// `asset_infos` is an array with multiple items of type [`AssetInfo`].
+pub fn pair_key(asset_infos: &[AssetInfo]) -> Vec<u8> {
+    asset_infos
+        .iter()
+        .map(AssetInfo::as_bytes)
+        .sorted()
+        .flatten()
+        .copied()
+        .collect()
+}
+
 /// This structure holds the main contract parameters.
 #[cw_serde]
 pub struct Config {
@@ -22,6 +40,8 @@ pub struct Config {
     pub whitelist_code_id: u64,
     /// The address of the contract that contains the coins with their precision
     pub coin_registry_address: Addr,
+    /// The admin of the instantiated pair contracts. Falls back to the owner if not set
+    pub pair_admin: Option<Addr>,
 }
 
 /// This enum describes available pair types.
@@ -43,6 +63,9 @@ pub enum PairType {
     Custom(String),
 }
 
+/// The number of blocks after its effective block during which a proposed fee change can be applied
+pub const FEE_CHANGE_EXPIRY_BLOCKS: u64 = 100_000;
+
 /// Returns a raw encoded string representing the name of each pool type
 impl Display for PairType {
     fn fmt(&self, fmt: &mut Formatter) -> Result {
@@ -109,6 +132,49 @@ pub struct InstantiateMsg {
     pub tracker_config: Option<TrackerConfig>,
 }
 
+/// The parameters of a single pair created by [`ExecuteMsg::CreatePairs`]
+#[cw_serde]
+pub struct CreatePairParams {
+    /// The pair type (exposed in [`PairType`])
+    pub pair_type: PairType,
+    /// The assets to create the pool for
+    pub asset_infos: Vec<AssetInfo>,
+    /// Optional binary serialised parameters for custom pool types
+    pub init_params: Option<Binary>,
+}
+
+/// The fees of a pair type proposed by [`ExecuteMsg::ProposeFeeChange`]
+#[cw_serde]
+pub struct FeeChange {
+    /// The total fees (in bps) charged by pairs of the type
+    pub total_fee_bps: u16,
+    /// The amount of fees (in bps) collected by the Maker contract from pairs of the type
+    pub maker_fee_bps: u16,
+}
+
+/// A proposed fee change waiting for its timelock to pass
+#[cw_serde]
+pub struct PendingFeeChange {
+    /// The proposed fees
+    pub config: FeeChange,
+    /// The block height at which the change was proposed
+    pub proposed_block: u64,
+    /// The block height starting from which the change can be applied.
+    /// A fee change delay raised after the proposal postpones it accordingly
+    pub effective_block: u64,
+    /// The last block height at which the change can be applied
+    pub expiry_block: u64,
+}
+
+/// A reduction or removal of the fee change delay waiting for the current delay to pass
+#[cw_serde]
+pub struct PendingFeeChangeDelay {
+    /// The new delay. `None` removes the timelock
+    pub delay_blocks: Option<u64>,
+    /// The block height starting from which the new delay is in effect
+    pub effective_block: u64,
+}
+
 /// This structure describes the execute messages of the contract.
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -145,11 +211,102 @@ pub enum ExecuteMsg {
         /// Optional binary serialised parameters for custom pool types
         init_params: Option<Binary>,
     },
+    /// Instantiates several pair contracts of any pair types in a single transaction.
+    /// Funds are not forwarded, thus pairs which require funds to be instantiated
+    /// must be created with [`ExecuteMsg::CreatePair`].
+    CreatePairs { pairs: Vec<CreatePairParams> },
+    /// Instantiates a new pair contract and provides the initial liquidity on behalf of the factory.
+    /// The minted LP tokens are permanently locked in the factory (protocol-owned liquidity).
+    /// Native assets must be sent along with the message, CW20 tokens require an allowance for the factory.
+    CreatePairWithLockedLiquidity {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+        /// The assets to create the pool for
+        asset_infos: Vec<AssetInfo>,
+        /// Optional binary serialised parameters for custom pool types
+        init_params: Option<Binary>,
+        /// The initial liquidity which will be locked forever
+        initial_liquidity: Vec<Asset>,
+    },
+    /// Instantiates a pair which replaces a deregistered pair and seeds it with the current
+    /// cumulative prices of the replaced pair within the same transaction, so oracles reading
+    /// the new pair see prices continuing from the old ones.
+    /// Both pairs must have the same type and assets. Only concentrated pairs accept a seed.
+    /// Only the owner can execute this.
+    CreateReplacementPair {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+        /// The assets to create the pool for
+        asset_infos: Vec<AssetInfo>,
+        /// Optional binary serialised parameters for custom pool types
+        init_params: Option<Binary>,
+        /// The deregistered pair whose cumulative prices are carried over
+        replaced_pair: String,
+    },
     /// Deregister removes a previously created pair.
     Deregister {
         /// The assets for which we deregister a pool
         asset_infos: Vec<AssetInfo>,
     },
+    /// Sets or removes the default max spread for swaps in pairs of the specified type.
+    /// Pairs created afterwards inherit it unless they set their own one.
+    /// Only xyk pairs support a default max spread, other pair types are rejected.
+    /// Only the owner can execute this.
+    SetDefaultMaxSpread {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+        /// The default max spread. None removes it
+        max_spread: Option<Decimal>,
+    },
+    /// Sets or removes the admin of newly instantiated pair contracts.
+    /// Pairs are administered by the owner if it is not set. Only the owner can execute this.
+    SetPairAdmin {
+        /// The pair admin address. None removes it
+        admin: Option<String>,
+    },
+    /// Sets or removes the TWAP oracle linked to a pair. Only the owner can execute this.
+    SetPairOracle {
+        /// The assets of the pair
+        asset_infos: Vec<AssetInfo>,
+        /// The oracle address. None removes the link
+        oracle: Option<String>,
+    },
+    /// Updates the list of pairs which must be migrated before they can be used again.
+    /// Older pair versions read this list via a raw query and halt operations while listed.
+    /// Only the owner can execute this.
+    SetPairsToMigrate {
+        /// Pair addresses to add to the list
+        add: Vec<String>,
+        /// Pair addresses to remove from the list
+        remove: Vec<String>,
+    },
+    /// Enables or disables rejecting the creation of pairs which charge maker fees while the fee
+    /// address is not set. Only the owner can execute this.
+    SetRequireFeeAddress { require: bool },
+    /// Enables or disables rejecting the creation of pairs which include an LP token of a pair
+    /// registered in this factory. Only the owner can execute this.
+    SetRejectLpTokenAssets { reject: bool },
+    /// Sets or removes the number of blocks a fee change must wait before it can be applied.
+    /// While set, [`ExecuteMsg::UpdatePairConfig`] can't change the fees of existing pair types,
+    /// they must go through [`ExecuteMsg::ProposeFeeChange`] instead.
+    /// Increasing the delay takes effect immediately, while reducing or removing it is itself
+    /// timelocked by the current delay. Only the owner can execute this.
+    SetFeeChangeDelay { delay_blocks: Option<u64> },
+    /// Records a fee change for a pair type which can be applied once the fee change delay passes.
+    /// Replaces the pending change of the pair type if any. Only the owner can execute this.
+    ProposeFeeChange {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+        /// The new fees
+        config: FeeChange,
+    },
+    /// Applies the pending fee change of a pair type once its effective block is reached
+    /// and before it expires. The current fee change delay counted from the proposal block
+    /// must have passed as well. Only the owner can execute this.
+    ApplyFeeChange {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+    },
     /// ProposeNewOwner creates a proposal to change contract ownership.
     /// The validity period for the proposal is set in the `expires_in` variable.
     ProposeNewOwner {
@@ -185,17 +342,100 @@ pub enum QueryMsg {
         /// The number of pairs to read and return. It is an [`Option`] type.
         limit: Option<u32>,
     },
+    /// PairExists returns whether a pair with the specified assets is registered in the factory.
+    #[returns(bool)]
+    PairExists {
+        /// The assets of the pair
+        asset_infos: Vec<AssetInfo>,
+    },
+    /// PairByLpToken returns information about the pair which owns the specified LP token.
+    #[returns(PairInfo)]
+    PairByLpToken {
+        /// The LP token denom
+        lp_token: String,
+    },
     /// FeeInfo returns fee parameters for a specific pair. The response is returned using a [`FeeInfoResponse`] structure
     #[returns(FeeInfoResponse)]
     FeeInfo {
         /// The pair type for which we return fee information. Pair type is a [`PairType`] struct
         pair_type: PairType,
     },
+    /// FeeInfoByPair returns fee parameters for a registered pair resolved by its address
+    #[returns(FeeInfoResponse)]
+    FeeInfoByPair {
+        /// The pair contract address
+        pair_addr: String,
+    },
     /// Returns a vector that contains blacklisted pair types
     #[returns(Vec<PairType>)]
     BlacklistedPairTypes {},
     #[returns(TrackerConfig)]
     TrackerConfig {},
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
+    /// Returns the protocol-owned liquidity locked for a pair created with
+    /// [`ExecuteMsg::CreatePairWithLockedLiquidity`]
+    #[returns(LockedLiquidityResponse)]
+    LockedLiquidity {
+        /// The assets of the pair
+        asset_infos: Vec<AssetInfo>,
+    },
+    /// Returns the default max spread inherited by new pairs of the specified type if it is set
+    #[returns(Option<Decimal>)]
+    DefaultMaxSpread { pair_type: PairType },
+    /// Returns the TWAP oracle linked to a pair if it is set
+    #[returns(Option<Addr>)]
+    PairOracle {
+        /// The assets of the pair
+        asset_infos: Vec<AssetInfo>,
+    },
+    /// Returns pairs registered between the specified blocks (both inclusive)
+    /// ordered by their creation block.
+    /// Pairs registered before factory 1.10.0 have no recorded creation block and are never returned,
+    /// use [`QueryMsg::Pairs`] to read them
+    #[returns(PairsResponse)]
+    PairsCreatedBetween {
+        /// The first block of the range
+        start_block: u64,
+        /// The last block of the range
+        end_block: u64,
+        /// The assets of the pair to start reading after
+        start_after: Option<Vec<AssetInfo>>,
+        /// The number of pairs to read
+        limit: Option<u32>,
+    },
+    /// Returns the key under which the factory stores a pair with the specified assets.
+    /// The key is built from the raw bytes of each asset denom or contract address,
+    /// sorted bytewise and concatenated. No other normalization is applied,
+    /// e.g. denoms are case-sensitive. Thus the key doesn't depend on the assets order
+    #[returns(Binary)]
+    PairKey { asset_infos: Vec<AssetInfo> },
+    /// Returns the pairs which must be migrated before they can be used again
+    #[returns(Vec<Addr>)]
+    PairsToMigrate {},
+    /// Returns whether the specified address is the current contract owner
+    #[returns(bool)]
+    IsOwner { address: String },
+    /// Returns all configured pair types together with their fee and status settings
+    #[returns(Vec<PairConfig>)]
+    PairTypesInfo {},
+    /// Returns whether maker fees are currently collected, i.e. the fee address is set.
+    /// Pairs leave the maker fee in the pool for LPs otherwise
+    #[returns(bool)]
+    MakerFeeCollectionActive {},
+    /// Returns whether pairs including LP tokens of registered pairs are rejected
+    #[returns(bool)]
+    RejectLpTokenAssets {},
+    /// Returns the number of blocks fee changes must wait before they can be applied
+    #[returns(Option<u64>)]
+    FeeChangeDelay {},
+    /// Returns the pending fee change of a pair type
+    #[returns(Option<PendingFeeChange>)]
+    PendingFeeChange { pair_type: PairType },
+    /// Returns the pending reduction or removal of the fee change delay
+    #[returns(Option<PendingFeeChangeDelay>)]
+    PendingFeeChangeDelay {},
 }
 
 #[cw_serde]
@@ -220,6 +460,8 @@ pub struct ConfigResponse {
     pub whitelist_code_id: u64,
     /// The address of the contract that contains the coins and their accuracy
     pub coin_registry_address: Addr,
+    /// The admin of the instantiated pair contracts. The owner is used if not set
+    pub pair_admin: Option<Addr>,
 }
 
 /// A custom struct for each query response that returns an array of objects of type [`PairInfo`].
@@ -240,6 +482,19 @@ pub struct FeeInfoResponse {
     pub maker_fee_bps: u16,
 }
 
+/// This structure describes the protocol-owned liquidity locked for a pair.
+#[cw_serde]
+pub struct LockedLiquidityResponse {
+    /// The pair address
+    pub pair_addr: Addr,
+    /// The LP token denom
+    pub lp_token: String,
+    /// The address holding the locked LP tokens
+    pub lock_addr: Addr,
+    /// The amount of locked LP tokens
+    pub amount: Uint128,
+}
+
 /// This is an enum used for setting and removing a contract address.
 #[cw_serde]
 pub enum UpdateAddr {
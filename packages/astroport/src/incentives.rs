@@ -2,7 +2,7 @@ use std::hash::{Hash, Hasher};
 use std::ops::RangeInclusive;
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin, Decimal256, Env, StdError, StdResult, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Decimal256, Env, StdError, StdResult, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 use crate::asset::{Asset, AssetInfo};
@@ -30,6 +30,15 @@ pub const MAX_PAGE_LIMIT: u8 = 50;
 /// Max number of orphaned rewards to claim at a time
 pub const MAX_ORPHANED_REWARD_LIMIT: u8 = 10;
 
+/// Max allowed harvest fee in bps (10%)
+pub const MAX_HARVEST_FEE_BPS: u16 = 1000;
+
+/// Max allowed reward multiplier for locked positions
+pub const MAX_LOCK_MULTIPLIER: Decimal = Decimal::raw(3_000000000000000000);
+
+/// Max allowed vesting duration of claimed ASTRO rewards (1 year)
+pub const MAX_REWARD_VESTING_DURATION: u64 = 86400 * 365;
+
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner: String,
@@ -102,11 +111,21 @@ pub enum ExecuteMsg {
         /// The list of pools with allocation point.
         pools: Vec<(String, Uint128)>,
     },
+    /// Update allocation points of the listed pools in one go. Pools which are not active yet are added
+    /// to the active set, other active pools keep their allocation points.
+    /// Only the owner or generator controller can execute this.
+    SetBatch {
+        /// The list of pools with their new allocation point.
+        pools: Vec<(String, Uint128)>,
+    },
     /// Update rewards and return it to user.
     ClaimRewards {
         /// The LP token cw20 address or token factory denom
         lp_tokens: Vec<String>,
     },
+    /// Set the address which receives rewards claimed by the message sender.
+    /// LP tokens are still withdrawn to the sender. Passing `None` sends rewards to the sender again.
+    SetRewardRecipient { recipient: Option<String> },
     /// Receives a message of type [`Cw20ReceiveMsg`]. Handles cw20 LP token deposits.
     Receive(Cw20ReceiveMsg),
     /// Stake LP tokens in the Generator. LP tokens staked on behalf of recipient if recipient is set.
@@ -119,6 +138,25 @@ pub enum ExecuteMsg {
         /// The amount to withdraw. Must not exceed total staked amount.
         amount: Uint128,
     },
+    /// Register the secp256k1 public key which authorizes [`ExecuteMsg::WithdrawFor`] on behalf
+    /// of the message sender. Passing `None` removes the registered key.
+    SetWithdrawPubkey {
+        /// Compressed or uncompressed secp256k1 public key
+        pubkey: Option<Binary>,
+    },
+    /// Withdraw LP tokens on behalf of an account which signed a [`WithdrawPermit`].
+    /// Anyone can relay this message. LP tokens are always sent to the account and rewards to
+    /// the account's reward recipient.
+    WithdrawFor {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        /// The account whose position is withdrawn
+        account: String,
+        /// The amount to withdraw. Must not exceed total staked amount.
+        amount: Uint128,
+        /// secp256k1 signature over the sha256 hash of the JSON serialized [`WithdrawPermit`]
+        signature: Binary,
+    },
     /// Set a new amount of ASTRO to distribute per seconds.
     /// Only the owner can execute this.
     SetTokensPerSecond {
@@ -163,6 +201,90 @@ pub enum ExecuteMsg {
         /// Receiver of orphaned rewards
         receiver: String,
     },
+    /// Send rewards lost on rounding down user claims in a specific pool to the recipient.
+    /// Only the owner can execute this.
+    SweepRewardDust {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        /// Receiver of the swept rewards
+        recipient: String,
+    },
+    /// Set the fee taken from claimed ASTRO emissions. `None` disables the fee.
+    /// Only the owner can execute this.
+    SetHarvestFee {
+        harvest_fee_info: Option<HarvestFeeInfo>,
+    },
+    /// Set the minimum number of blocks LP tokens must stay staked in a pool to be eligible
+    /// for ASTRO rewards. ASTRO rewards claimed earlier are forfeited to the pool's reward dust.
+    /// External rewards are not affected.
+    /// Zero disables the requirement. Only the owner can execute this.
+    SetMinHoldingBlocks {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        min_holding_blocks: u64,
+    },
+    /// Set the number of blocks a user must wait between two [`ExecuteMsg::ClaimRewards`] calls
+    /// in a pool. Deposits, withdrawals and lock updates are never blocked by the cooldown.
+    /// Rewards keep accruing during the cooldown. Zero disables the cooldown.
+    /// Only the owner can execute this.
+    SetClaimCooldown {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        cooldown_blocks: u64,
+    },
+    /// Pause or resume ASTRO emissions in an active pool. Unlike blocklisting, a paused pool
+    /// keeps its alloc points and stays in the active set, so unpausing restores the prior rate.
+    /// Only the owner can execute this.
+    SetPoolRewardsPaused {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        paused: bool,
+    },
+    /// Set the duration over which ASTRO rewards claimed from a pool vest linearly.
+    /// Claimed rewards are registered as a vesting schedule of the reward recipient
+    /// in the vesting contract, which must allow this contract to register schedules.
+    /// Every claim adds a schedule, so the recipient must claim vested ASTRO from the vesting
+    /// contract to stay within its schedules limit.
+    /// Zero pays rewards out instantly. Only the owner can execute this.
+    SetRewardVestingDuration {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        /// Vesting duration in seconds
+        duration: u64,
+    },
+    /// Set the pool which receives alloc points of active pools deactivated by token blocklisting.
+    /// When the fallback pool is not active, alloc points are removed as usual.
+    /// None removes the fallback pool. Only the owner can execute this.
+    SetFallbackPool {
+        /// The LP token cw20 address or token factory denom
+        lp_token: Option<String>,
+    },
+    /// Set how ASTRO claims are handled when the vesting contract can't cover them.
+    /// If enabled, users receive the available amount and the shortfall is owed to them.
+    /// Owed rewards are paid out on later claims once the vesting contract is refilled,
+    /// see [`QueryMsg::OwedRewards`]. Otherwise, such claims fail. Only the owner can execute this.
+    SetPartialVestingPayouts { enabled: bool },
+    /// Set the parameters of the lock for boost mechanism.
+    /// None disables new locks while existing ones stay in effect until they expire.
+    /// Only the owner can execute this.
+    SetLockBoost { config: Option<LockBoostConfig> },
+    /// Lock the whole sender position in a pool for `duration` seconds in exchange for
+    /// boosted rewards. Outstanding rewards are claimed beforehand.
+    /// An existing lock can only be extended.
+    LockPosition {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        /// Lock duration in seconds
+        duration: u64,
+    },
+    /// Remove an expired lock from a position so it stops earning boosted rewards.
+    /// Outstanding rewards are claimed to the position owner. Anyone can execute this.
+    /// Claims, deposits and withdrawals of the position remove an expired lock as well
+    ExpireLock {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        user: String,
+    },
     /// Update config.
     /// Only the owner can execute it.
     UpdateConfig {
@@ -197,6 +319,17 @@ pub enum ExecuteMsg {
     DeactivatePool { lp_token: String },
     /// Go through active pools and deactivate the ones which pair type is blocked
     DeactivateBlockedPools {},
+    /// Withdraw the whole staked LP position of each listed user back to that user together
+    /// with outstanding rewards. Position locks are ignored. Users without a position are skipped.
+    /// Works only for inactive pools which are deregistered in the factory, contain blocked tokens
+    /// or have a blocked pair type.
+    /// Only the owner can execute this.
+    RescueStuckLp {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        /// Users whose positions are withdrawn
+        users: Vec<String>,
+    },
     /// Creates a request to change contract ownership
     /// Only the current owner can execute this.
     ProposeNewOwner {
@@ -232,9 +365,23 @@ pub enum QueryMsg {
     /// Deposit returns the LP token amount deposited in a specific generator
     #[returns(Uint128)]
     Deposit { lp_token: String, user: String },
-    /// PendingToken returns the amount of rewards that can be claimed by an account that deposited a specific LP token in a generator
+    /// PendingToken returns the amount of rewards that can be claimed by an account that deposited a specific LP token in a generator.
+    /// The harvest fee is deducted from ASTRO rewards. ASTRO rewards are zero while the position is
+    /// within the pool's minimum holding period as a claim would forfeit them
     #[returns(Vec<Asset>)]
     PendingRewards { lp_token: String, user: String },
+    /// Returns outstanding rewards of a user in every pool where the user has a position.
+    /// ASTRO emissions are reported separately from external rewards which are itemized by token.
+    /// Pagination applies to the pools returned by [`QueryMsg::ListPools`] rather than to positions.
+    /// Amounts are computed as in [`QueryMsg::PendingRewards`]
+    #[returns(Vec<PoolPendingRewards>)]
+    PendingRewardsAll {
+        user: String,
+        /// Start after specified LP token
+        start_after: Option<String>,
+        /// Limit number of scanned pools
+        limit: Option<u8>,
+    },
     /// RewardInfo returns reward information for a specified LP token
     #[returns(Vec<RewardInfo>)]
     RewardInfo { lp_token: String },
@@ -279,6 +426,101 @@ pub enum QueryMsg {
     #[returns(Vec<(String, Uint128)>)]
     /// Returns the list of all pools receiving astro emissions
     ActivePools {},
+    /// Returns the amount of ASTRO the vesting contract has available for this contract
+    #[returns(Uint128)]
+    VestingBalance {},
+    /// Returns the underlying pool assets of the LP tokens staked by a user
+    #[returns(Vec<Asset>)]
+    StakedValue { lp_token: String, user: String },
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
+    /// Returns the nonce which the next [`WithdrawPermit`] of the account must be signed with
+    #[returns(u64)]
+    WithdrawNonce { account: String },
+    /// Returns the fee taken from claimed ASTRO emissions if it is set
+    #[returns(Option<HarvestFeeInfo>)]
+    HarvestFee {},
+    /// Returns the minimum number of blocks LP tokens must stay staked in a pool to be eligible for rewards
+    #[returns(u64)]
+    MinHoldingBlocks { lp_token: String },
+    /// Returns the number of blocks a user must wait between two reward claims in a pool
+    #[returns(u64)]
+    ClaimCooldown { lp_token: String },
+    /// Returns whether ASTRO emissions are paused in a pool
+    #[returns(bool)]
+    PoolRewardsPaused { lp_token: String },
+    /// Returns the duration in seconds over which ASTRO rewards claimed from a pool vest
+    #[returns(u64)]
+    RewardVestingDuration { lp_token: String },
+    /// Returns the pool which receives alloc points of pools deactivated by token blocklisting
+    #[returns(Option<AssetInfo>)]
+    FallbackPool {},
+    /// Returns whether ASTRO claims are paid partially when the vesting contract is underfunded
+    #[returns(bool)]
+    PartialVestingPayouts {},
+    /// Returns ASTRO rewards of a user which weren't paid out because the vesting contract was
+    /// underfunded. They are paid out on the next claim, which may have an empty list of LP tokens
+    #[returns(Uint128)]
+    OwedRewards { user: String },
+    /// Returns the total amount of ASTRO emissions claimed by stakers across all pools,
+    /// including harvest fees
+    #[returns(Uint128)]
+    TotalDistributed {},
+    /// Returns the parameters of the lock for boost mechanism if it is enabled
+    #[returns(Option<LockBoostConfig>)]
+    LockBoost {},
+    /// Returns the lock of a user position if it exists
+    #[returns(Option<PositionLock>)]
+    PositionLock { lp_token: String, user: String },
+    /// Estimates rewards a new position of `amount` LP tokens would receive over the next `seconds`.
+    /// The projection assumes the pool state stays constant: current rewards per second
+    /// (including ASTRO emissions and active external schedules) and the total staked amount
+    /// don't change, no other stakers join or leave and the position is not locked.
+    /// Harvest fees are not deducted.
+    #[returns(Vec<Asset>)]
+    ProjectedRewards {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        /// The amount of LP tokens to stake
+        amount: Uint128,
+        /// The projection horizon in seconds
+        seconds: u64,
+    },
+    /// Returns the LP tokens of the active pools whose pair includes the specified asset,
+    /// i.e. the pools which would be deactivated if the asset were blocked
+    #[returns(Vec<String>)]
+    PoolsContainingAsset { asset_info: AssetInfo },
+    /// Returns the address which receives rewards claimed by a user
+    #[returns(Addr)]
+    RewardRecipient { user: String },
+    /// Returns the listed users whose reward indexes are inconsistent with the pool state
+    /// so their reward claims would fail. Users without a position in the pool are skipped.
+    #[returns(Vec<Addr>)]
+    HealthCheck {
+        /// The LP token cw20 address or token factory denom
+        lp_token: String,
+        /// Users to check
+        users: Vec<String>,
+    },
+}
+
+/// The payload an account signs to authorize [`ExecuteMsg::WithdrawFor`].
+/// The nonce is increased after every successful withdrawal, so each permit can be used only once.
+#[cw_serde]
+pub struct WithdrawPermit {
+    /// The chain the permit is valid on
+    pub chain_id: String,
+    /// The incentives contract address
+    pub contract: String,
+    /// The account whose position is withdrawn
+    pub account: String,
+    /// The LP token cw20 address or token factory denom
+    pub lp_token: String,
+    /// The amount to withdraw
+    pub amount: Uint128,
+    /// The current withdraw nonce of the account
+    pub nonce: u64,
 }
 
 #[cw_serde]
@@ -289,6 +531,34 @@ pub struct IncentivizationFeeInfo {
     pub fee: Coin,
 }
 
+#[cw_serde]
+pub struct HarvestFeeInfo {
+    /// Fee receiver can be either a contract or a wallet.
+    pub fee_receiver: Addr,
+    /// The share of claimed ASTRO emissions sent to the fee receiver. Bounded by [`MAX_HARVEST_FEE_BPS`]
+    pub fee_bps: u16,
+}
+
+/// Parameters of the optional lock for boost mechanism
+#[cw_serde]
+pub struct LockBoostConfig {
+    /// Minimum duration (in seconds) a position must be locked for to receive the boost
+    pub min_lock_duration: u64,
+    /// Maximum duration (in seconds) a position can be locked for at once
+    pub max_lock_duration: u64,
+    /// Reward multiplier applied to locked positions. Bounded by [`MAX_LOCK_MULTIPLIER`]
+    pub multiplier: Decimal,
+}
+
+/// Lock of a staked position. Locked LP tokens can't be withdrawn until `unlock_ts`
+#[cw_serde]
+pub struct PositionLock {
+    /// Timestamp after which the position can be withdrawn
+    pub unlock_ts: u64,
+    /// Reward multiplier the position was locked with
+    pub multiplier: Decimal,
+}
+
 #[cw_serde]
 pub struct Config {
     /// Address allowed to change contract parameters
@@ -403,6 +673,17 @@ pub struct PoolInfoResponse {
     pub last_update_ts: u64,
 }
 
+/// Outstanding rewards of a user position in a pool
+#[cw_serde]
+pub struct PoolPendingRewards {
+    /// The LP token cw20 address or token factory denom
+    pub lp_token: String,
+    /// Outstanding ASTRO emissions
+    pub astro_pending: Uint128,
+    /// Outstanding external rewards aggregated by token
+    pub external_pending: Vec<Asset>,
+}
+
 #[cw_serde]
 pub struct ScheduleResponse {
     pub rps: Decimal256,
@@ -93,6 +93,11 @@ pub enum ExecuteMsg {
     Collect {
         /// The assets to swap to ASTRO
         assets: Vec<AssetWithLimit>,
+        /// The minimum total amount of ASTRO the swaps must produce.
+        /// The whole collect is reverted if less ASTRO is received.
+        /// ASTRO the Maker already held before the collect, including ASTRO fees collected as is,
+        /// doesn't count towards this amount
+        min_astro_out: Option<Uint128>,
     },
     /// Updates general settings
     UpdateConfig {
@@ -139,6 +144,22 @@ pub enum ExecuteMsg {
     ClaimOwnership {},
     /// Enables the distribution of current fees accrued in the contract over "blocks" number of blocks
     EnableRewards { blocks: u64 },
+    /// Transfers the whole balance of an asset which can't be swapped to ASTRO to the recipient.
+    /// ASTRO and assets having a swap route to ASTRO can't be recovered.
+    /// Only the owner can execute this.
+    RecoverAsset {
+        asset_info: AssetInfo,
+        recipient: String,
+    },
+    /// Enables or disables distributing the governance (vxASTRO stakers) share as xASTRO.
+    /// When enabled, the share is staked via the staking contract's `Enter` and the minted xASTRO
+    /// is forwarded to the governance contract. Requires native ASTRO and the staking contract set.
+    /// Only the owner can execute this.
+    SetDistributeAsXastro { distribute_as_xastro: bool },
+    /// Sets the maximum number of assets a single [`ExecuteMsg::Collect`] call can swap.
+    /// Operators have to split longer lists into several calls. Passing `None` removes the limit.
+    /// Only the owner can execute this.
+    SetMaxCollectAssets { max_assets: Option<u32> },
 }
 
 /// This structure describes the query functions available in the contract.
@@ -151,8 +172,33 @@ pub enum QueryMsg {
     /// Returns the balance for each asset in the specified input parameters
     #[returns(BalancesResponse)]
     Balances { assets: Vec<AssetInfo> },
+    /// Returns configured bridges as (from, to) asset pairs
     #[returns(Vec<(String, String)>)]
-    Bridges {},
+    Bridges {
+        /// The asset to start reading from
+        start_after: Option<String>,
+        /// The number of bridges to return. All bridges are returned if not set
+        limit: Option<u32>,
+    },
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
+    /// Returns the fee split configuration along with the current ASTRO balance
+    #[returns(StatusResponse)]
+    Status {},
+    /// Returns whether the governance share is distributed as xASTRO
+    #[returns(bool)]
+    DistributeAsXastro {},
+    /// Returns the maximum number of assets a single collect call can swap if it is limited
+    #[returns(Option<u32>)]
+    MaxCollectAssets {},
+    /// Returns the native fee token balances whose simulated ASTRO output exceeds the estimated
+    /// gas cost of swapping them to ASTRO. CW20 balances can't be enumerated thus they are not checked
+    #[returns(Vec<Asset>)]
+    WorthCollecting {
+        /// The price of one gas unit denominated in ASTRO
+        gas_price: Uint128,
+    },
 }
 
 /// A custom struct that holds contract parameters and is used to retrieve them.
@@ -184,6 +230,23 @@ pub struct ConfigResponse {
     pub second_receiver_cfg: Option<SecondReceiverConfig>,
 }
 
+/// This structure holds the fee split configuration and the ASTRO accrued by the Maker.
+#[cw_serde]
+pub struct StatusResponse {
+    /// The percentage of fees that go to governance_contract
+    pub governance_percent: Uint64,
+    /// The maximum spread used when swapping fee tokens to ASTRO
+    pub max_spread: Decimal,
+    /// The ASTRO token asset info
+    pub astro_token: AssetInfo,
+    /// The xASTRO staking contract address
+    pub staking_contract: Option<Addr>,
+    /// The governance contract address (fee distributor for vxASTRO stakers)
+    pub governance_contract: Option<Addr>,
+    /// The current ASTRO balance of the Maker
+    pub astro_balance: Uint128,
+}
+
 /// A custom struct used to return multiple asset balances.
 #[cw_serde]
 pub struct BalancesResponse {
@@ -1,6 +1,6 @@
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Uint128, Uint256};
+use cosmwasm_std::{Addr, Uint128, Uint256};
 
 /// This structure stores general parameters for the contract.
 #[cw_serde]
@@ -9,6 +9,13 @@ pub struct InstantiateMsg {
     pub factory_contract: String,
     /// The assets that have a pool for which this contract provides price feeds
     pub asset_infos: Vec<AssetInfo>,
+    /// Whether to additionally accumulate log-prices in order to provide a geometric-mean TWAP
+    #[serde(default)]
+    pub geometric_twap: bool,
+    /// Whether [`QueryMsg::Consult`] must fail until the first full period is accumulated
+    /// instead of falling back to the spot price
+    #[serde(default)]
+    pub require_twap: bool,
 }
 
 /// This structure describes the execute functions available in the contract.
@@ -16,13 +23,22 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// Update/accumulate prices
     Update {},
+    /// Restricts [`ExecuteMsg::Update`] to the specified addresses.
+    /// Passing `None` makes updates permissionless again. Only the owner can execute this.
+    SetUpdaters { updaters: Option<Vec<String>> },
+    /// Sets the reward paid to the caller of a successful [`ExecuteMsg::Update`].
+    /// The reward is paid from the contract balance and skipped if the balance is insufficient.
+    /// Passing `None` disables rewards. Only the owner can execute this.
+    SetKeeperReward { reward: Option<Asset> },
 }
 
 /// This structure describes the query messages available in the contract.
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    /// Calculates a new TWAP with updated precision
+    /// Calculates a new TWAP with updated precision.
+    /// Falls back to the spot price within the first period after instantiation unless the oracle
+    /// was instantiated with `require_twap` enabled. A zero TWAP after the first period is an error
     #[returns(Vec<(AssetInfo, Uint256)>)]
     Consult {
         /// The asset for which to compute a new TWAP value
@@ -30,6 +46,45 @@ pub enum QueryMsg {
         /// The amount of tokens for which to compute the token price
         amount: Uint128,
     },
+    /// Calculates the geometric mean of the latest period average prices.
+    /// Only available if the oracle was instantiated with `geometric_twap` enabled
+    #[returns(Vec<(AssetInfo, Uint256)>)]
+    GeometricTwap {
+        /// The asset for which to compute the geometric TWAP value
+        token: AssetInfo,
+        /// The amount of tokens for which to compute the token price
+        amount: Uint128,
+    },
+    /// Returns the spot price and the TWAP of a token side by side computed at the same block.
+    /// The TWAP is the same as returned by [`QueryMsg::Consult`]
+    #[returns(Vec<(AssetInfo, PriceComparisonResponse)>)]
+    PriceComparison {
+        /// The asset for which to compute the prices
+        token: AssetInfo,
+        /// The amount of tokens for which to compute the token price
+        amount: Uint128,
+    },
+    /// Returns the addresses allowed to update prices. `None` means updates are permissionless
+    #[returns(Option<Vec<Addr>>)]
+    Updaters {},
+    /// Returns the reward paid to the caller of a successful update
+    #[returns(Option<Asset>)]
+    KeeperReward {},
+    /// Returns the assets for which the oracle provides prices
+    #[returns(Vec<AssetInfo>)]
+    TrackedAssets {},
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
+}
+
+/// This structure describes the current and the average price of a token amount.
+#[cw_serde]
+pub struct PriceComparisonResponse {
+    /// The amount priced at the current pool spot price
+    pub spot: Uint256,
+    /// The amount priced at the latest TWAP
+    pub twap: Uint256,
 }
 
 /// This structure describes a migration message.
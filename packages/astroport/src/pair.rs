@@ -4,7 +4,9 @@ use cosmwasm_schema::{cw_serde, QueryResponses};
 use crate::asset::{Asset, AssetInfo, PairInfo};
 
 use crate::factory::PairType;
-use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, StdError, Uint128, Uint64};
+use cosmwasm_std::{
+    Addr, Binary, Decimal, Decimal256, SignedDecimal, StdError, StdResult, Uint128, Uint64,
+};
 use cw20::Cw20ReceiveMsg;
 
 /// The default swap slippage
@@ -13,6 +15,8 @@ pub const DEFAULT_SLIPPAGE: &str = "0.005";
 pub const MAX_ALLOWED_SLIPPAGE: &str = "0.5";
 /// The maximum fee share allowed, 10%
 pub const MAX_FEE_SHARE_BPS: u16 = 1000;
+/// The maximum referral fee allowed, 10% of the swap fee
+pub const MAX_REFERRAL_FEE_BPS: u16 = 1000;
 
 /// Decimal precision for TWAP results
 pub const TWAP_PRECISION: u8 = 6;
@@ -66,6 +70,16 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// If true, `belief_price` is a hard price limit. The swap fails if its execution price
+        /// is worse than `belief_price` regardless of `max_spread`.
+        /// `belief_price` is the amount of offer asset paid per one ask asset. In pools with more
+        /// than two assets it refers to the offer/ask pair selected by `ask_asset_info`.
+        /// Transmuter and xASTRO pairs reject swaps with this flag set
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        strict_belief_price: bool,
+        /// Optional referral which receives a part of the swap fee.
+        /// Only supported by concentrated pairs, other pairs reject swaps with a referral
+        referral: Option<Referral>,
     },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
@@ -81,6 +95,17 @@ pub enum ExecuteMsg {
     DropOwnershipProposal {},
     /// Used to claim contract ownership.
     ClaimOwnership {},
+    /// Runs the repeg step of a concentrated pool without a swap. Callable by anyone.
+    /// Not supported by other pair types
+    Rebalance {},
+    /// Overwrites the cumulative prices of a concentrated pool with a starting baseline,
+    /// e.g. carried over from another venue. The baseline is treated as accumulated up to the
+    /// current block. Only allowed before the first swap. Callable by the pair owner and by
+    /// the factory when it replaces a pair. Not supported by other pair types
+    SeedCumulativePrices {
+        /// (offer asset, ask asset, cumulative price) triples
+        prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
+    },
 }
 
 /// This structure describes a CW20 hook message.
@@ -92,6 +117,24 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// If true, `belief_price` is a hard price limit. The swap fails if its execution price
+        /// is worse than `belief_price` regardless of `max_spread`.
+        /// `belief_price` is the amount of offer asset paid per one ask asset. In pools with more
+        /// than two assets it refers to the offer/ask pair selected by `ask_asset_info`.
+        /// Transmuter and xASTRO pairs reject swaps with this flag set
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        strict_belief_price: bool,
+        /// Optional referral which receives a part of the swap fee.
+        /// Only supported by concentrated pairs, other pairs reject swaps with a referral
+        referral: Option<Referral>,
+    },
+    /// Swap only as much of the received amount as is needed to receive `target_out` ask tokens.
+    /// The unused part of the received amount is refunded to the sender. If the target can't be
+    /// reached, the whole received amount is swapped
+    SwapWithTarget {
+        ask_asset_info: Option<AssetInfo>,
+        target_out: Uint128,
+        max_spread: Option<Decimal>,
     },
 }
 
@@ -123,6 +166,13 @@ pub enum QueryMsg {
         offer_asset_info: Option<AssetInfo>,
         ask_asset: Asset,
     },
+    /// Returns a swap simulation together with the spread relative to `belief_price`
+    /// in a [`SimulateWithSpreadResponse`] object
+    #[returns(SimulateWithSpreadResponse)]
+    SimulateWithSpread {
+        offer_asset: Asset,
+        belief_price: Decimal,
+    },
     /// Returns information about the cumulative prices in a [`CumulativePricesResponse`] object
     #[returns(CumulativePricesResponse)]
     CumulativePrices {},
@@ -147,6 +197,40 @@ pub enum QueryMsg {
         assets: Vec<Asset>,
         slippage_tolerance: Option<Decimal>,
     },
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
+    /// Returns the minimum amount of each pool asset which must be provided to mint
+    /// at least one LP token unit given current reserves
+    #[returns(Vec<Asset>)]
+    MinProvideAmounts {},
+    /// Returns pool reserves, total share and fees in a single [`PoolSnapshotResponse`] object
+    #[returns(PoolSnapshotResponse)]
+    PoolSnapshot {},
+    /// Returns a swap simulation along with the amplification used to compute it
+    #[returns(SwapFeeBreakdownResponse)]
+    SimulateSwapWithFeeBreakdown {
+        offer_asset: Asset,
+        ask_asset_info: Option<AssetInfo>,
+    },
+    /// Returns the pair configuration with already decoded stableswap pool parameters
+    #[returns(DecodedConfigResponse<StablePoolConfig>)]
+    DecodedConfig {},
+    /// Returns the slippage (excluding fees) the given trade would incur at the current amp
+    #[returns(Decimal)]
+    SlippageForTrade { offer_asset: Asset },
+    /// Returns the deviation of each asset's share of D from the equal-weight target (1/n).
+    /// Positive values mean the pool holds more of the asset than in the balanced state
+    #[returns(Vec<(AssetInfo, SignedDecimal)>)]
+    Imbalance {},
+    /// Returns the maximum amount of a single asset which can be withdrawn for the given amount
+    /// of LP tokens, i.e. the balanced withdraw with the other assets swapped into the chosen one.
+    /// Swap fees and the remaining reserves are taken into account
+    #[returns(Uint128)]
+    MaxSingleAssetOut {
+        lp_amount: Uint128,
+        asset_info: AssetInfo,
+    },
 }
 
 /// This struct is used to return a query result with the total amount of LP tokens and assets in a specific pool.
@@ -158,6 +242,19 @@ pub struct PoolResponse {
     pub total_share: Uint128,
 }
 
+/// This struct is used to return pool reserves, total share and fees in one response.
+#[cw_serde]
+pub struct PoolSnapshotResponse {
+    /// The assets in the pool together with asset amounts
+    pub assets: Vec<Asset>,
+    /// The total amount of LP tokens currently issued
+    pub total_share: Uint128,
+    /// The total swap fee rate
+    pub total_fee: Decimal,
+    /// The share of the swap fee sent to the Maker
+    pub maker_fee: Decimal,
+}
+
 /// This struct is used to return a query result with the general contract configuration.
 #[cw_serde]
 pub struct ConfigResponse {
@@ -182,6 +279,15 @@ pub struct FeeShareConfig {
     pub recipient: Addr,
 }
 
+/// Holds the referral attached to a swap
+#[cw_serde]
+pub struct Referral {
+    /// The address receiving the referral fee
+    pub address: String,
+    /// The share of the swap fee sent to the referral address
+    pub fee_bps: u16,
+}
+
 /// This structure holds the parameters that are returned from a swap simulation response
 #[cw_serde]
 pub struct SimulationResponse {
@@ -193,6 +299,53 @@ pub struct SimulationResponse {
     pub commission_amount: Uint128,
 }
 
+/// This structure describes a pair's configuration with the pool parameters decoded into typed fields.
+/// Fees are expressed in percents, e.g. 0.3 means 0.3%.
+#[cw_serde]
+pub struct DecodedConfigResponse<T> {
+    /// Last timestamp when the cumulative prices in the pool were updated
+    pub block_time_last: u64,
+    /// The pool parameters
+    pub params: T,
+    /// The percentage of the swap amount charged as a fee.
+    /// For concentrated pools this is the current dynamic fee
+    pub total_fee_percent: Decimal,
+    /// The percentage of the swap fee sent to the Maker contract
+    pub maker_fee_percent: Decimal,
+    /// The contract owner
+    pub owner: Addr,
+    /// The factory contract address
+    pub factory_addr: Addr,
+    /// Tracker contract address
+    pub tracker_addr: Option<Addr>,
+}
+
+/// This structure holds the full breakdown of a simulated stableswap.
+#[cw_serde]
+pub struct SwapFeeBreakdownResponse {
+    /// The amount of ask assets returned by the swap
+    pub return_amount: Uint128,
+    /// The spread used in the swap operation
+    pub spread_amount: Uint128,
+    /// The amount of fees charged by the transaction
+    pub commission_amount: Uint128,
+    /// The amplification coefficient used in the swap math
+    pub amp: Decimal,
+}
+
+/// This structure holds a swap simulation together with the spread relative to a belief price.
+#[cw_serde]
+pub struct SimulateWithSpreadResponse {
+    /// The amount of ask assets returned by the swap
+    pub return_amount: Uint128,
+    /// The amount of fees charged by the transaction
+    pub commission_amount: Uint128,
+    /// The amount of ask assets expected at the belief price
+    pub expected_return: Uint128,
+    /// The spread relative to the belief price. This is the value compared against `max_spread` in a swap
+    pub spread: Decimal,
+}
+
 /// This structure holds the parameters that are returned from a reverse swap simulation response.
 #[cw_serde]
 pub struct ReverseSimulationResponse {
@@ -227,6 +380,32 @@ pub struct XYKPoolParams {
     /// They will not be tracked if the parameter is ignored.
     /// It can not be disabled later once enabled.
     pub track_asset_balances: Option<bool>,
+    /// The maximum swap offer amount as a fraction of the offer asset reserve.
+    /// Swaps are unlimited if the parameter is ignored.
+    pub max_swap_ratio: Option<Decimal>,
+    /// Timestamp (in seconds) before which swaps are rejected. Liquidity can be provided beforehand.
+    /// Trading starts immediately if the parameter is ignored.
+    pub trading_start_time: Option<u64>,
+    /// The max spread applied to swaps that don't specify one.
+    /// The factory default for the pair type is used if the parameter is ignored.
+    pub default_max_spread: Option<Decimal>,
+    /// The maximum pool balance of each listed asset. Provides which would push a pool balance
+    /// above its cap are rejected. Deposits are unlimited if the parameter is ignored.
+    pub max_pool_balance: Option<Vec<(AssetInfo, Uint128)>>,
+}
+
+/// The name of the custom pair type used by weighted XYK-style pairs (e.g. 80/20 pools)
+pub const WEIGHTED_PAIR_TYPE: &str = "weighted";
+
+/// The sum of the asset weights in a weighted pair
+pub const WEIGHTS_TOTAL: u64 = 100;
+
+/// This structure holds weighted pool parameters.
+/// They are passed as `init_params` when creating a `PairType::Custom("weighted")` pair.
+#[cw_serde]
+pub struct WeightedPoolParams {
+    /// Weights are percentages in the order of the pair's assets and must sum to [`WEIGHTS_TOTAL`]
+    pub weights: [u64; 2],
 }
 
 /// This structure stores a XYK pool's configuration.
@@ -236,6 +415,15 @@ pub struct XYKPoolConfig {
     pub track_asset_balances: bool,
     // The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// The maximum swap offer amount as a fraction of the offer asset reserve.
+    /// None means unlimited
+    pub max_swap_ratio: Option<Decimal>,
+    /// Timestamp (in seconds) before which swaps are rejected
+    pub trading_start_time: Option<u64>,
+    /// The max spread applied to swaps that don't specify one
+    pub default_max_spread: Option<Decimal>,
+    /// The maximum pool balance of each listed asset. None means unlimited
+    pub max_pool_balance: Option<Vec<(AssetInfo, Uint128)>>,
 }
 
 /// This enum stores the option available to enable asset balances tracking over blocks.
@@ -249,6 +437,16 @@ pub enum XYKPoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Sets the maximum swap offer amount as a fraction of the offer asset reserve.
+    /// None removes the limit.
+    SetMaxSwapRatio {
+        max_swap_ratio: Option<Decimal>,
+    },
+    /// Sets the maximum pool balance of each listed asset. Assets which are not listed are unlimited.
+    /// None removes all caps.
+    SetMaxPoolBalance {
+        max_pool_balance: Option<Vec<(AssetInfo, Uint128)>>,
+    },
 }
 
 /// This structure holds stableswap pool parameters.
@@ -267,6 +465,16 @@ pub struct StablePoolConfig {
     pub amp: Decimal,
     // The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// The maximum allowed deviation of a provide's asset ratio from the pool ratio.
+    /// None means unlimited
+    pub max_provide_imbalance: Option<Decimal>,
+    /// Whether swaps and provides are paused. Withdrawals are always allowed
+    #[serde(default)]
+    pub paused: bool,
+    /// Whether the pool D invariant is emitted as the `pool_d` attribute after provides,
+    /// swaps and withdrawals
+    #[serde(default)]
+    pub emit_pool_d: bool,
 }
 
 /// This enum stores the options available to start and stop changing a stableswap pool's amplification.
@@ -285,6 +493,24 @@ pub enum StablePoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Sets the maximum allowed provide imbalance. None removes the limit.
+    SetMaxProvideImbalance {
+        max_provide_imbalance: Option<Decimal>,
+    },
+    /// Pauses or unpauses swaps and provides. Withdrawals stay available while paused.
+    SetPaused {
+        paused: bool,
+    },
+    /// Enables or disables the `pool_d` attribute emitted after provides, swaps and withdrawals
+    SetEmitPoolD {
+        enabled: bool,
+    },
+    /// Overrides the stored precision of a cw20 token which reports wrong `decimals`.
+    /// Allowed only while the pool has no liquidity and only once per asset.
+    OverridePrecision {
+        asset_info: AssetInfo,
+        precision: u8,
+    },
 }
 
 /// A `reply` call code ID used for sub-messages.
@@ -309,6 +535,37 @@ impl TryFrom<u64> for ReplyIds {
     }
 }
 
+/// Ensures the swap is executed at `belief_price` or better.
+/// Unlike the max spread check, no deviation from `belief_price` is tolerated.
+///
+/// * **belief_price** the worst acceptable price, i.e. the amount of offer asset paid per one ask asset.
+/// The swap is rejected if fewer than `offer_amount / belief_price` ask tokens are returned.
+///
+/// * **offer_amount** amount of assets to swap.
+///
+/// * **return_amount** amount of assets the trader receives after fees.
+pub fn assert_belief_price(
+    belief_price: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+) -> StdResult<()> {
+    let belief_price = belief_price.ok_or_else(|| {
+        StdError::generic_err("Strict belief price check requires belief_price to be set")
+    })?;
+    let expected_return = offer_amount
+        * belief_price
+            .inv()
+            .ok_or_else(|| StdError::generic_err("Belief price must not be zero!"))?;
+
+    if return_amount.is_zero() || return_amount < expected_return {
+        return Err(StdError::generic_err(
+            "Operation is executed at a price worse than belief price",
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,6 +587,9 @@ mod tests {
                 to_json_binary(&StablePoolConfig {
                     amp: Decimal::one(),
                     fee_share: None,
+                    max_provide_imbalance: None,
+                    paused: false,
+                    emit_pool_d: false,
                 })
                 .unwrap(),
             ),
@@ -1,14 +1,20 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint64};
+use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128, Uint64};
 
 use crate::asset::PairInfo;
 use crate::asset::{Asset, AssetInfo};
 use crate::observation::OracleObservation;
 use crate::pair::{
-    ConfigResponse, CumulativePricesResponse, FeeShareConfig, PoolResponse,
-    ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, DecodedConfigResponse, FeeShareConfig, PoolResponse,
+    PoolSnapshotResponse, ReverseSimulationResponse, SimulationResponse,
 };
 
+/// The maximum share of the maker fee which can be diverted to a local fee share recipient, 100%
+pub const MAX_LOCAL_FEE_SHARE_BPS: u16 = 10000;
+
+/// The maximum share of the accrued maker fees paid to a rebalance caller, 10%
+pub const MAX_REBALANCE_BOUNTY_BPS: u16 = 1000;
+
 /// This structure holds concentrated pool parameters.
 #[cw_serde]
 pub struct ConcentratedPoolParams {
@@ -38,6 +44,15 @@ pub struct ConcentratedPoolParams {
     pub track_asset_balances: Option<bool>,
     /// The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// Timestamp (in seconds) until which imbalanced provides are not charged the provide fee.
+    /// There is no fee-free window if the parameter is omitted.
+    #[serde(default)]
+    pub fee_free_provide_until: Option<u64>,
+    /// Enables a swap fee floor which rises with the divergence between the last and the oracle prices:
+    /// floor = min(out_fee, volatility_fee_factor * |last_price - oracle_price| / oracle_price).
+    /// There is no floor if the parameter is omitted.
+    #[serde(default)]
+    pub volatility_fee_factor: Option<Decimal>,
 }
 
 /// This structure holds concentrated pool parameters which can be changed immediately.
@@ -76,6 +91,74 @@ pub enum ConcentratedPoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Sets a swap fee multiplier (from 0 to 1) for the specified swap sender
+    SetFeeDiscount {
+        address: String,
+        multiplier: Decimal,
+    },
+    /// Removes the swap fee discount for the specified address
+    RemoveFeeDiscount {
+        address: String,
+    },
+    /// Sets the factor of the volatility-linked swap fee floor. None disables the floor.
+    SetVolatilityFeeFactor {
+        factor: Option<Decimal>,
+    },
+    /// Diverts a share of the maker fee to a local recipient. The rest goes to the factory fee address.
+    EnableLocalFeeShare {
+        /// The share of the maker fee in bps bounded by [`MAX_LOCAL_FEE_SHARE_BPS`]
+        bps: u16,
+        /// The local fee share recipient
+        address: String,
+    },
+    /// Sends the whole maker fee to the factory fee address
+    DisableLocalFeeShare {},
+    /// Halts swaps which move the pool spot price away from the oracle TWAP by more than
+    /// `max_divergence`. Swaps bringing the spot price closer to the oracle price are always allowed.
+    /// If the oracle can't be queried or returns a zero price, swaps are halted until it recovers
+    /// or the guard is disabled
+    EnableOracleGuard {
+        /// The oracle contract tracking the pair assets
+        oracle: String,
+        /// The maximum relative divergence between the pool and oracle prices
+        max_divergence: Decimal,
+    },
+    /// Disables the oracle divergence check
+    DisableOracleGuard {},
+    /// Sets the maximum age (in seconds) of the internal oracle price. Once it is older,
+    /// swaps must specify both `belief_price` and `max_spread`, the latter capped at 1%.
+    /// `None` disables the check
+    SetMaxPriceStaleness {
+        max_price_staleness: Option<u64>,
+    },
+    /// Keeps the maker fee sent to the factory fee address in the pair contract instead of
+    /// transferring it on every swap. Accrued fees are swept by [`ConcentratedPoolUpdateParams::CollectFees`]
+    /// or automatically once the accrued amount of an asset reaches `sweep_threshold`
+    EnableFeeAccrual {
+        /// The accrued amount of an asset (in the asset's own units) which triggers a sweep
+        sweep_threshold: Option<Uint128>,
+    },
+    /// Sends the maker fee to the factory fee address on every swap again.
+    /// Already accrued fees stay in the contract until collected
+    DisableFeeAccrual {},
+    /// Sends all accrued maker fees to the factory fee address
+    CollectFees {},
+    /// Sets the share of the accrued maker fees paid to the caller of [`crate::pair::ExecuteMsg::Rebalance`]
+    /// when it actually repegs the pool. `None` disables the bounty
+    SetRebalanceBounty {
+        /// The share of the accrued maker fees in bps bounded by [`MAX_REBALANCE_BOUNTY_BPS`]
+        bps: Option<u16>,
+    },
+    /// Sets the minimum LP token supply below which `OraclePrice` and `CumulativePrices` queries
+    /// fail instead of serving easily manipulable prices. `None` disables the check
+    SetMinOracleLiquidity {
+        min_oracle_liquidity: Option<Uint128>,
+    },
+    /// Sets the maximum referral fee (in bps of the swap fee) accepted by swaps.
+    /// Bounded by [`crate::pair::MAX_REFERRAL_FEE_BPS`]. Zero disables referrals
+    SetMaxReferralFee {
+        bps: u16,
+    },
 }
 
 /// This structure stores a CL pool's configuration.
@@ -105,6 +188,51 @@ pub struct ConcentratedPoolConfig {
     pub track_asset_balances: bool,
     /// The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// Timestamp (in seconds) until which imbalanced provides are not charged the provide fee
+    #[serde(default)]
+    pub fee_free_provide_until: Option<u64>,
+    /// The factor of the volatility-linked swap fee floor
+    #[serde(default)]
+    pub volatility_fee_factor: Option<Decimal>,
+    /// The share of the maker fee sent to a local recipient
+    #[serde(default)]
+    pub local_fee_share: Option<FeeShareConfig>,
+    /// The oracle divergence check applied to swaps
+    #[serde(default)]
+    pub oracle_guard: Option<OracleGuardConfig>,
+    /// The maximum age (in seconds) of the internal oracle price before swaps require
+    /// an explicit `belief_price` and `max_spread`
+    #[serde(default)]
+    pub max_price_staleness: Option<u64>,
+    /// Maker fee accrual settings. Maker fees are sent on every swap if not set
+    #[serde(default)]
+    pub fee_accrual: Option<FeeAccrualConfig>,
+    /// The share of the accrued maker fees (in bps) paid to a rebalance caller
+    #[serde(default)]
+    pub rebalance_bounty_bps: Option<u16>,
+    /// The minimum LP token supply required to serve oracle prices
+    #[serde(default)]
+    pub min_oracle_liquidity: Option<Uint128>,
+    /// The maximum referral fee (in bps of the swap fee).
+    /// [`crate::pair::MAX_REFERRAL_FEE_BPS`] applies if not set
+    #[serde(default)]
+    pub max_referral_fee_bps: Option<u16>,
+}
+
+/// This structure describes the maker fee accrual settings.
+#[cw_serde]
+pub struct FeeAccrualConfig {
+    /// The accrued amount of an asset (in the asset's own units) which triggers a sweep
+    pub sweep_threshold: Option<Uint128>,
+}
+
+/// This structure describes the oracle divergence check applied to swaps.
+#[cw_serde]
+pub struct OracleGuardConfig {
+    /// The oracle contract queried for the pair TWAP
+    pub oracle: Addr,
+    /// The maximum relative divergence between the pool spot price and the oracle price
+    pub max_divergence: Decimal,
 }
 
 /// This structure describes the query messages available in the contract.
@@ -163,6 +291,110 @@ pub enum QueryMsg {
     /// Returns an estimation of assets received for the given amount of LP tokens
     #[returns(Vec<Asset>)]
     SimulateWithdraw { lp_amount: Uint128 },
+    /// Returns the swap fee multiplier applied to the specified address
+    #[returns(Decimal)]
+    FeeMultiplier { address: String },
+    /// Returns the time of the last internal price state update and how long ago it happened
+    #[returns(PriceFreshnessResponse)]
+    PriceFreshness {},
+    /// Returns the total maker fees collected by the pool for each asset
+    #[returns(Vec<Asset>)]
+    MakerFees {},
+    /// Returns the maker fees accrued in the contract and not yet sent to the fee address
+    #[returns(Vec<Asset>)]
+    AccruedMakerFees {},
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
+    /// Returns pool reserves, total share and fees in a single [`PoolSnapshotResponse`] object.
+    /// The total fee is the dynamic fee at the current pool balances
+    #[returns(PoolSnapshotResponse)]
+    PoolSnapshot {},
+    /// Returns the price bands around the price scale within which arbitrage is not profitable
+    /// at both bounds of the dynamic fee (`mid_fee` and `out_fee`)
+    #[returns(PriceBandResponse)]
+    PriceBand {},
+    /// Returns the internal EMA oracle price advanced to the current block.
+    /// Unlike the price scale, it is not affected by repegging.
+    #[returns(Decimal256)]
+    OraclePrice {},
+    /// Returns the pair configuration with already decoded concentrated pool parameters
+    #[returns(DecodedConfigResponse<ConcentratedPoolConfig>)]
+    DecodedConfig {},
+    /// Returns the internal reserves used by the invariant math along with the computed D
+    #[returns(InternalReservesResponse)]
+    InternalReserves {},
+    /// Returns whether the specified address is the current pair owner
+    #[returns(bool)]
+    IsOwner { address: String },
+    /// Returns the all-in price of a unit trade including the dynamic fee
+    /// along with the fee-free spot price
+    #[returns(EffectivePriceResponse)]
+    EffectivePrice {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+    /// Returns the asset amounts which, provided balanced at the current price scale,
+    /// mint approximately `lp_amount` LP tokens
+    #[returns(Vec<Asset>)]
+    ProvideAmountsForLp { lp_amount: Uint128 },
+    /// Returns the per second decay factor of the EMA oracle price implied by `ma_half_time`
+    #[returns(OracleSmoothingResponse)]
+    OracleSmoothing {},
+}
+
+/// This structure describes the no-arbitrage price bands of the pool.
+/// The dynamic fee moves from `mid_fee` in a balanced pool to `out_fee` in an imbalanced one,
+/// so the actual band lies between the two.
+/// Prices are denominated in the second asset per unit of the first asset.
+#[cw_serde]
+pub struct PriceBandResponse {
+    /// The lower bound at the mid fee: price_scale * (1 - mid_fee)
+    pub mid_fee_lower: Decimal256,
+    /// The upper bound at the mid fee: price_scale / (1 - mid_fee)
+    pub mid_fee_upper: Decimal256,
+    /// The lower bound at the out fee: price_scale * (1 - out_fee)
+    pub out_fee_lower: Decimal256,
+    /// The upper bound at the out fee: price_scale / (1 - out_fee)
+    pub out_fee_upper: Decimal256,
+}
+
+/// This structure describes the price of a unit trade.
+/// Prices are denominated in the ask asset per unit of the offer asset.
+#[cw_serde]
+pub struct EffectivePriceResponse {
+    /// The rate received by a trader after the dynamic fee is deducted
+    pub effective_price: Decimal256,
+    /// The fee-free rate of the same trade
+    pub spot_price: Decimal256,
+}
+
+/// This structure describes how fresh the pool's internal price state is.
+#[cw_serde]
+pub struct PriceFreshnessResponse {
+    /// Timestamp (in seconds) of the last internal price state update
+    pub last_price_update: u64,
+    /// Seconds elapsed since the last price state update
+    pub elapsed: u64,
+}
+
+/// This structure describes the smoothing of the EMA oracle price.
+#[cw_serde]
+pub struct OracleSmoothingResponse {
+    /// The weight the previous oracle price keeps after one second: 0.5^(1 / ma_half_time).
+    /// Values closer to one mean slower reaction to new prices
+    pub decay_factor: Decimal256,
+    /// Half-time in seconds after which the previous oracle price keeps half of its weight
+    pub ma_half_time: u64,
+}
+
+/// This structure describes the pool's internal reserves as seen by the invariant math.
+#[cw_serde]
+pub struct InternalReservesResponse {
+    /// Reserves normalized to the common precision, the second one scaled by price_scale
+    pub xs: Vec<Decimal256>,
+    /// Invariant D computed on the reserves above. Zero if the pool is empty
+    pub d: Decimal256,
 }
 
 #[cw_serde]
@@ -1,11 +1,21 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 use crate::asset::AssetInfo;
 
 pub const MAX_SWAP_OPERATIONS: usize = 50;
+/// The maximum number of candidate routes which can be compared in a single query
+pub const MAX_ROUTE_CANDIDATES: usize = 10;
+/// The maximum frontend fee which can be charged from the swap output (in bps)
+pub const MAX_FRONTEND_FEE_BPS: u16 = 100;
+/// The maximum number of recipients the swap output can be split between
+pub const MAX_RECIPIENTS: usize = 10;
+/// The maximum number of hops explored by the reachable assets query
+pub const MAX_REACHABLE_HOPS: u32 = 4;
+/// The maximum number of factory pairs scanned by the reachable assets query
+pub const MAX_SCANNED_PAIRS: usize = 300;
 
 /// This structure holds the parameters used for creating a contract.
 #[cw_serde]
@@ -34,6 +44,17 @@ pub enum SwapOperation {
 }
 
 impl SwapOperation {
+    pub fn get_offer_asset_info(&self) -> AssetInfo {
+        match self {
+            SwapOperation::NativeSwap { offer_denom, .. } => AssetInfo::NativeToken {
+                denom: offer_denom.clone(),
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info, ..
+            } => offer_asset_info.clone(),
+        }
+    }
+
     pub fn get_target_asset_info(&self) -> AssetInfo {
         match self {
             SwapOperation::NativeSwap { ask_denom, .. } => AssetInfo::NativeToken {
@@ -44,6 +65,15 @@ impl SwapOperation {
     }
 }
 
+/// This structure describes an optional fee charged by a frontend from the final swap output.
+#[cw_serde]
+pub struct FrontendFee {
+    /// The address which receives the fee
+    pub address: String,
+    /// The fee in bps. Must not exceed [`MAX_FRONTEND_FEE_BPS`]
+    pub bps: u16,
+}
+
 /// This structure describes the execute messages available in the contract.
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -55,6 +85,17 @@ pub enum ExecuteMsg {
         minimum_receive: Option<Uint128>,
         to: Option<String>,
         max_spread: Option<Decimal>,
+        /// Optional fee taken from the final output and sent to the frontend address
+        frontend_fee: Option<FrontendFee>,
+        /// Optional recipients splitting the final output by fractions which must sum to one.
+        /// Can't be used together with `to`
+        recipients: Option<Vec<(String, Decimal)>>,
+        /// If the pair of a hop after the first one has no liquidity, stop the route and send out
+        /// the output of the last successful hop instead of reverting. Other failures still revert.
+        /// Requires `max_spread` which is then enforced on every hop, since `minimum_receive`
+        /// can't be checked for such an output
+        #[serde(default)]
+        allow_partial: bool,
     },
 
     /// Internal use
@@ -65,6 +106,14 @@ pub enum ExecuteMsg {
         max_spread: Option<Decimal>,
         single: bool,
     },
+    /// Resolves pair addresses for the given asset pairs via the factory and caches them.
+    /// Swap operations through cached pairs skip the factory query as long as the factory
+    /// still has the cached pair registered for these assets.
+    /// Only the factory owner can execute this
+    CachePairs { pairs: Vec<[AssetInfo; 2]> },
+    /// Removes the given asset pairs from the cache so they are resolved via the factory again.
+    /// Only the factory owner can execute this
+    InvalidatePairs { pairs: Vec<[AssetInfo; 2]> },
 }
 
 #[cw_serde]
@@ -83,6 +132,17 @@ pub enum Cw20HookMsg {
         to: Option<String>,
         /// Max spread
         max_spread: Option<Decimal>,
+        /// Optional fee taken from the final output and sent to the frontend address
+        frontend_fee: Option<FrontendFee>,
+        /// Optional recipients splitting the final output by fractions which must sum to one.
+        /// Can't be used together with `to`
+        recipients: Option<Vec<(String, Decimal)>>,
+        /// If the pair of a hop after the first one has no liquidity, stop the route and send out
+        /// the output of the last successful hop instead of reverting. Other failures still revert.
+        /// Requires `max_spread` which is then enforced on every hop, since `minimum_receive`
+        /// can't be checked for such an output
+        #[serde(default)]
+        allow_partial: bool,
     },
 }
 
@@ -101,13 +161,46 @@ pub enum QueryMsg {
         /// The swap operations to perform, each swap involving a specific pool
         operations: Vec<SwapOperation>,
     },
+    /// BestRoute simulates each candidate route and returns the best one according to the preference
+    #[returns(BestRouteResponse)]
+    BestRoute {
+        /// The amount of tokens to swap
+        offer_amount: Uint128,
+        /// Candidate routes. All of them must swap the same offer asset into the same ask asset
+        candidates: Vec<Vec<SwapOperation>>,
+        /// The objective used to compare candidates. Defaults to [`RoutePreference::HighestOutput`]
+        preference: Option<RoutePreference>,
+    },
+    /// ReachableAssets returns the assets which can be reached from the starting asset
+    /// within the specified number of hops over the pairs registered in the factory.
+    /// Only the first [`MAX_SCANNED_PAIRS`] factory pairs are scanned
+    #[returns(ReachableAssetsResponse)]
+    ReachableAssets {
+        /// The starting asset
+        from: AssetInfo,
+        /// The maximum number of hops. Capped at [`MAX_REACHABLE_HOPS`]
+        max_hops: u32,
+    },
+}
+
+/// This enum describes the objectives used to compare candidate routes
+#[cw_serde]
+#[derive(Default)]
+pub enum RoutePreference {
+    /// Prefer the route with the lowest compounded fee rate. Ties are broken by the greatest output
+    LowestFee,
+    /// Prefer the route with the greatest output
+    #[default]
+    HighestOutput,
 }
 
 /// This structure describes a custom struct to return a query response containing the base contract configuration.
 #[cw_serde]
 pub struct ConfigResponse {
     /// The Astroport factory contract address
-    pub astroport_factory: String,
+    pub astroport_factory: Addr,
+    /// The maximum number of swap operations in a single route
+    pub max_swap_operations: u32,
 }
 
 /// This structure describes a custom struct to return a query response containing the end amount of a swap simulation
@@ -117,6 +210,27 @@ pub struct SimulateSwapOperationsResponse {
     pub amount: Uint128,
 }
 
+/// This structure describes a custom struct to return a query response containing the best candidate route
+#[cw_serde]
+pub struct BestRouteResponse {
+    /// The index of the best route in the candidates list
+    pub index: u32,
+    /// The amount of tokens received when swapping through the best route
+    pub amount: Uint128,
+    /// The compounded fee rate charged by all pools along the best route
+    pub fee_rate: Decimal,
+}
+
+/// This structure describes a custom struct to return a query response containing reachable assets
+#[cw_serde]
+pub struct ReachableAssetsResponse {
+    /// The reachable assets in the order they were discovered
+    pub assets: Vec<AssetInfo>,
+    /// Whether the factory has more than [`MAX_SCANNED_PAIRS`] pairs, in which case
+    /// assets reachable only over the pairs beyond the limit are missing
+    pub truncated: bool,
+}
+
 /// This structure describes a migration message.
 /// We currently take no arguments for migrations.
 #[cw_serde]
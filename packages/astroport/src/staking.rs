@@ -1,5 +1,5 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Binary, Decimal, Uint128};
 
 /// This structure describes the parameters used for creating a contract.
 #[cw_serde]
@@ -12,6 +12,8 @@ pub struct InstantiateMsg {
     pub tracking_code_id: u64,
     /// Token factory module address. Contract creator must ensure that the address is exact token factory module address.
     pub token_factory_addr: String,
+    /// Expected TokenFactory admin of the deposit token. Instantiation fails if the actual admin differs
+    pub deposit_token_admin: Option<String>,
 }
 
 /// This structure describes the execute messages available in the contract.
@@ -29,6 +31,8 @@ pub enum ExecuteMsg {
     /// Burns xASTRO in exchange for ASTRO.
     /// The receiver is optional. If not set, the sender will receive the ASTRO.
     Leave { receiver: Option<String> },
+    /// Donates attached ASTRO to xASTRO holders. Increases the exchange rate without minting xASTRO.
+    Donate {},
 }
 
 /// This structure describes the query messages available in the contract.
@@ -57,6 +61,26 @@ pub enum QueryMsg {
     /// Returns current total supply if timestamp unset.
     #[returns(Uint128)]
     TotalSupplyAt { timestamp: Option<u64> },
+    /// ExchangeRateAt returns the amount of ASTRO per 1 xASTRO at the given timestamp.
+    /// Returns the current rate if timestamp unset.
+    /// Total deposit is recorded on every enter and leave, thus donations are reflected from the next stake or unstake.
+    #[returns(Decimal)]
+    ExchangeRateAt { timestamp: Option<u64> },
+    /// Returns the amount of xASTRO held by the staking contract itself.
+    /// This is the minimum stake permanently locked on the first deposit.
+    #[returns(Uint128)]
+    LockedShares {},
+    /// Returns the minimum amount of ASTRO one must stake to receive at least 1 xASTRO
+    /// at the current exchange rate. The first stake must also cover the permanently locked minimum.
+    #[returns(Uint128)]
+    MinStakeAmount {},
+    /// Returns the cumulative amounts of ASTRO staked and unstaked since flow tracking was introduced.
+    /// Contracts upgraded from 2.3.0 or earlier start counting at the upgrade.
+    #[returns(CumulativeFlows)]
+    CumulativeFlows {},
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
 }
 
 /// This structure stores the main parameters for the staking contract.
@@ -81,6 +105,17 @@ pub struct TrackerData {
     pub tracker_addr: String,
 }
 
+/// Staking throughput of the contract since flow tracking was introduced.
+/// Flows of contracts upgraded from 2.3.0 or earlier are counted from the upgrade.
+#[cw_serde]
+#[derive(Default)]
+pub struct CumulativeFlows {
+    /// The total amount of ASTRO staked
+    pub total_entered: Uint128,
+    /// The total amount of ASTRO returned to unstakers
+    pub total_left: Uint128,
+}
+
 /// The structure returned as part of set_data when staking or unstaking
 #[cw_serde]
 pub struct StakingResponse {
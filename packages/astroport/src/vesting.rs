@@ -4,6 +4,12 @@ use crate::asset::AssetInfo;
 use cosmwasm_std::{Addr, Order, Uint128};
 use cw20::Cw20ReceiveMsg;
 
+/// Number of schedules an account may hold when no custom limit is set
+pub const DEFAULT_MAX_SCHEDULES: u32 = 8;
+/// Upper bound for the configurable schedules limit. Every claim and every vesting query
+/// iterates over all account schedules, so an unbounded list could make them run out of gas
+pub const MAX_SCHEDULES_LIMIT: u32 = 32;
+
 /// This structure describes the parameters used for creating a contract.
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -25,7 +31,10 @@ pub enum ExecuteMsg {
     },
     /// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template
     Receive(Cw20ReceiveMsg),
-    /// RegisterVestingAccounts registers vesting targets/accounts
+    /// RegisterVestingAccounts registers vesting targets/accounts.
+    /// Fully vested and claimed schedules of the accounts are removed to free up the schedules limit.
+    /// ## Executor
+    /// Only the current owner or a registrar can execute this
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
     },
@@ -38,6 +47,21 @@ pub enum ExecuteMsg {
         /// The amount of tokens to withdraw
         withdraw_amount: Uint128,
     },
+    /// Sets the maximum number of schedules a single account can hold.
+    /// The limit is enforced when vesting accounts are registered.
+    /// ## Executor
+    /// Only the current owner can execute this
+    SetMaxSchedules { max_schedules: u32 },
+    /// Adds and removes accounts which are allowed to register vesting accounts besides the owner,
+    /// e.g. the incentives contract vesting claimed rewards
+    /// ## Executor
+    /// Only the current owner can execute this
+    UpdateRegistrars {
+        /// Accounts to allow
+        add: Vec<String>,
+        /// Accounts to disallow
+        remove: Vec<String>,
+    },
     /// Creates a request to change contract ownership
     /// ## Executor
     /// Only the current owner can execute this
@@ -114,9 +138,33 @@ pub enum QueryMsg {
     /// Returns the total unvested amount of tokens for a specific address.
     #[returns(Uint128)]
     AvailableAmount { address: String },
+    /// Returns the amount of tokens a specific address can claim at the current block
+    /// using a [`ClaimableResponse`] object.
+    #[returns(ClaimableResponse)]
+    Claimable { address: String },
+    /// Returns the vested and remaining amounts of every schedule of a specific address
+    /// at the current block using a vector of [`ScheduleBreakdown`] objects.
+    #[returns(Vec<ScheduleBreakdown>)]
+    ScheduleBreakdown { address: String },
     /// Timestamp returns the current timestamp
     #[returns(u64)]
     Timestamp {},
+    /// Returns the total amount of tokens scheduled and released across all vesting accounts
+    /// using a [`TotalVestingResponse`] object.
+    #[returns(TotalVestingResponse)]
+    TotalVesting {},
+    /// Returns the maximum number of schedules a single account can hold
+    #[returns(u32)]
+    MaxSchedules {},
+    /// Returns the accounts which are allowed to register vesting accounts besides the owner
+    #[returns(Vec<Addr>)]
+    Registrars {},
+    /// Returns aggregate statistics across all vesting accounts using a [`VestingStatsResponse`] object
+    #[returns(VestingStatsResponse)]
+    Stats {},
+    /// Returns the contract name and version stored via cw2
+    #[returns(cw2::ContractVersion)]
+    Version {},
 }
 
 /// This structure describes a custom struct used to return the contract configuration.
@@ -144,6 +192,54 @@ pub struct VestingAccountsResponse {
     pub vesting_accounts: Vec<VestingAccountResponse>,
 }
 
+/// This structure describes a custom struct used to return the amount claimable by an address.
+#[cw_serde]
+pub struct ClaimableResponse {
+    /// The amount of tokens that can be claimed at the current block
+    pub amount: Uint128,
+    /// Whether there is anything to claim
+    pub has_claimable: bool,
+}
+
+/// This structure describes the state of a single vesting schedule at the current block.
+#[cw_serde]
+pub struct ScheduleBreakdown {
+    /// The time when the schedule starts
+    pub start: u64,
+    /// The time when the schedule ends. Equals `start` for schedules without an end point
+    pub end: u64,
+    /// The total amount of tokens vested by the schedule
+    pub total: Uint128,
+    /// The amount of tokens vested by the schedule so far. Claimed tokens are not subtracted
+    pub vested_so_far: Uint128,
+    /// The amount of tokens yet to be vested by the schedule
+    pub remaining: Uint128,
+}
+
+/// This structure describes a custom struct used to return the aggregated vesting amounts.
+#[cw_serde]
+#[derive(Default)]
+pub struct TotalVestingResponse {
+    /// The total amount of tokens registered in all vesting schedules
+    pub total_scheduled: Uint128,
+    /// The total amount of tokens already released from all vesting schedules
+    pub total_released: Uint128,
+}
+
+/// This structure describes a custom struct used to return aggregate vesting statistics.
+#[cw_serde]
+#[derive(Default)]
+pub struct VestingStatsResponse {
+    /// The number of accounts which have ever been registered
+    pub account_count: u64,
+    /// The total amount of tokens registered in all vesting schedules
+    pub total_scheduled: Uint128,
+    /// The total amount of tokens already released from all vesting schedules
+    pub total_released: Uint128,
+    /// The total amount of tokens which are not released yet
+    pub total_remaining: Uint128,
+}
+
 /// This enum describes the types of sorting that can be applied to some piece of data
 #[cw_serde]
 pub enum OrderBy {
@@ -175,7 +271,10 @@ pub struct MigrateMsg {
 /// This structure describes a CW20 hook message.
 #[cw_serde]
 pub enum Cw20HookMsg {
-    /// RegisterVestingAccounts registers vesting targets/accounts
+    /// RegisterVestingAccounts registers vesting targets/accounts.
+    /// Fully vested and claimed schedules of the accounts are removed to free up the schedules limit.
+    /// ## Executor
+    /// Only the current owner or a registrar can execute this
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
     },
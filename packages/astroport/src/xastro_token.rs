@@ -1,10 +1,11 @@
 // TODO: DEPRECATE
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{StdError, StdResult, Uint128};
+use cosmwasm_std::{Binary, Int128, StdError, StdResult, Uint128};
 use cw20::{
     AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse, Cw20Coin,
-    DownloadLogoResponse, Logo, MarketingInfoResponse, MinterResponse, TokenInfoResponse,
+    DownloadLogoResponse, Expiration, Logo, MarketingInfoResponse, MinterResponse,
+    TokenInfoResponse,
 };
 
 /// This structure describes the marketing info settings such as project, description, and token logo.
@@ -37,6 +38,84 @@ pub struct InstantiateMsg {
     pub marketing: Option<InstantiateMarketingInfo>,
 }
 
+/// The maximum number of allowances that can be increased in a single batch.
+pub const MAX_ALLOWANCES_BATCH: usize = 50;
+
+/// This structure describes a single allowance increase within a batch.
+#[cw_serde]
+pub struct AllowanceIncrease {
+    /// The address allowed to spend the owner's tokens
+    pub spender: String,
+    /// The amount by which the allowance is increased
+    pub amount: Uint128,
+    /// Optional new expiration of the allowance
+    pub expires: Option<Expiration>,
+}
+
+/// This enum describes the execute messages available in the contract.
+/// It mirrors the supported subset of the CW20 base messages and adds xASTRO specific ones.
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Transfer is a base message to move tokens to another account without triggering actions
+    Transfer { recipient: String, amount: Uint128 },
+    /// Burn is a base message to destroy tokens forever
+    Burn { amount: Uint128 },
+    /// Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Allows spender to access an additional amount tokens from the owner's (env.sender) account.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the spender's access of tokens from the owner's (env.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Increases allowances of several spenders at once. Either all increases are applied or none.
+    IncreaseAllowanceBatch { allowances: Vec<AllowanceIncrease> },
+    /// Transfers amount tokens from owner -> recipient if `env.sender` has sufficient pre-approval.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Sends amount tokens from owner -> contract if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Destroys tokens forever
+    BurnFrom { owner: String, amount: Uint128 },
+    /// If authorized, creates amount new tokens and adds to the recipient balance.
+    Mint { recipient: String, amount: Uint128 },
+    /// Sets or lowers the maximum total supply. The cap can never be raised
+    /// or set below the current total supply. Only the contract admin can execute this.
+    UpdateMintCap { cap: Uint128 },
+    /// If authorized, updates marketing metadata.
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token.
+        project: Option<String>,
+        /// A longer description of the token and it's utility. Designed for tooltips or such
+        description: Option<String>,
+        /// The address (if any) who can update this data structure
+        marketing: Option<String>,
+    },
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token
+    UploadLogo(Logo),
+}
+
 /// This enum describes the query messages available in the contract.
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -47,6 +126,13 @@ pub enum QueryMsg {
     /// BalanceAt returns balance of the given address at the given block, 0 if unset.
     #[returns(BalanceResponse)]
     BalanceAt { address: String, block: u64 },
+    /// BalanceDelta returns the signed change of the address balance between the given blocks.
+    #[returns(Int128)]
+    BalanceDelta {
+        address: String,
+        start_block: u64,
+        end_block: u64,
+    },
     /// TotalSupplyAt returns the total token supply at the given block.
     #[returns(Uint128)]
     TotalSupplyAt { block: u64 },
@@ -82,6 +82,7 @@ where
 
 impl PoolParams {
     /// Intended to update current pool parameters. Performs validation of the new parameters.
+    /// Parameters are validated as a group, thus if any of them is invalid nothing is updated.
     /// Returns a vector of attributes with updated parameters.
     ///
     /// * `update_params` - an object which contains new pool parameters. Any of the parameters may be omitted.
@@ -89,29 +90,24 @@ impl PoolParams {
         &mut self,
         update_params: UpdatePoolParams,
     ) -> Result<Vec<Attribute>, PclError> {
+        // Changes are applied to a copy so that the whole bundle is either accepted or rejected
+        let mut new_params = self.clone();
         let mut attributes = vec![];
         if let Some(mid_fee) = update_params.mid_fee {
             validate_param("mid_fee", mid_fee, MIN_FEE, MAX_FEE)?;
-            self.mid_fee = mid_fee;
+            new_params.mid_fee = mid_fee;
             attributes.push(attr("mid_fee", mid_fee.to_string()));
         }
 
         if let Some(out_fee) = update_params.out_fee {
             validate_param("out_fee", out_fee, MIN_FEE, MAX_FEE)?;
-            if out_fee <= self.mid_fee {
-                return Err(StdError::generic_err(format!(
-                    "out_fee {out_fee} must be more than mid_fee {}",
-                    self.mid_fee
-                ))
-                .into());
-            }
-            self.out_fee = out_fee;
+            new_params.out_fee = out_fee;
             attributes.push(attr("out_fee", out_fee.to_string()));
         }
 
         if let Some(fee_gamma) = update_params.fee_gamma {
             validate_param("fee_gamma", fee_gamma, FEE_GAMMA_MIN, FEE_GAMMA_MAX)?;
-            self.fee_gamma = fee_gamma;
+            new_params.fee_gamma = fee_gamma;
             attributes.push(attr("fee_gamma", fee_gamma.to_string()));
         }
 
@@ -122,7 +118,7 @@ impl PoolParams {
                 REPEG_PROFIT_THRESHOLD_MIN,
                 REPEG_PROFIT_THRESHOLD_MAX,
             )?;
-            self.repeg_profit_threshold = repeg_profit_threshold;
+            new_params.repeg_profit_threshold = repeg_profit_threshold;
             attributes.push(attr(
                 "repeg_profit_threshold",
                 repeg_profit_threshold.to_string(),
@@ -136,7 +132,7 @@ impl PoolParams {
                 PRICE_SCALE_DELTA_MIN,
                 PRICE_SCALE_DELTA_MAX,
             )?;
-            self.min_price_scale_delta = min_price_scale_delta;
+            new_params.min_price_scale_delta = min_price_scale_delta;
             attributes.push(attr(
                 "min_price_scale_delta",
                 min_price_scale_delta.to_string(),
@@ -150,10 +146,21 @@ impl PoolParams {
                 *MA_HALF_TIME_LIMITS.start(),
                 *MA_HALF_TIME_LIMITS.end(),
             )?;
-            self.ma_half_time = ma_half_time;
+            new_params.ma_half_time = ma_half_time;
             attributes.push(attr("ma_half_time", ma_half_time.to_string()));
         }
 
+        // Cross-field invariants are checked against the resulting set of parameters
+        if new_params.out_fee <= new_params.mid_fee {
+            return Err(StdError::generic_err(format!(
+                "out_fee {} must be more than mid_fee {}",
+                new_params.out_fee, new_params.mid_fee
+            ))
+            .into());
+        }
+
+        *self = new_params;
+
         Ok(attributes)
     }
 
@@ -190,6 +190,7 @@ pub struct SwapResult {
     pub spread_fee: Decimal256,
     pub maker_fee: Decimal256,
     pub share_fee: Decimal256,
+    pub referral_fee: Decimal256,
     pub total_fee: Decimal256,
 }
 
@@ -197,11 +198,16 @@ impl SwapResult {
     /// Calculates **last price** for PCL repeg algo
     pub fn calc_last_price(&self, offer_amount: Decimal256, offer_ind: usize) -> Decimal256 {
         if offer_ind == 0 {
-            offer_amount / (self.dy + self.maker_fee + self.share_fee)
+            offer_amount / self.ask_amount_out()
         } else {
-            (self.dy + self.maker_fee + self.share_fee) / offer_amount
+            self.ask_amount_out() / offer_amount
         }
     }
+
+    /// Total amount of ask asset leaving the pool including all fees except LP fee
+    pub fn ask_amount_out(&self) -> Decimal256 {
+        self.dy + self.maker_fee + self.share_fee + self.referral_fee
+    }
 }
 
 /// Performs swap simulation to calculate a price.
@@ -219,6 +225,8 @@ pub fn calc_last_prices(xs: &[Decimal256], config: &Config, env: &Env) -> StdRes
         env,
         Decimal256::zero(),
         Decimal256::zero(),
+        Decimal256::zero(),
+        Decimal256::one(),
     )?
     .calc_last_price(offer_amount, 0);
 
@@ -256,6 +264,9 @@ pub fn accumulate_prices(env: &Env, config: &mut Config, last_real_price: Decima
 }
 
 /// Calculate swap result.
+/// **fee_multiplier** scales the total swap fee before it is split between the maker, the fee share,
+/// the referral and LPs. The referral fee is taken from the LP/maker part.
+#[allow(clippy::too_many_arguments)]
 pub fn compute_swap(
     xs: &[Decimal256],
     offer_amount: Decimal256,
@@ -264,6 +275,36 @@ pub fn compute_swap(
     env: &Env,
     maker_fee_share: Decimal256,
     share_fee_share: Decimal256,
+    referral_fee_share: Decimal256,
+    fee_multiplier: Decimal256,
+) -> StdResult<SwapResult> {
+    compute_swap_with_fee_floor(
+        xs,
+        offer_amount,
+        ask_ind,
+        config,
+        env,
+        maker_fee_share,
+        share_fee_share,
+        referral_fee_share,
+        fee_multiplier,
+        Decimal256::zero(),
+    )
+}
+
+/// Same as [`compute_swap`] but the dynamic fee rate is raised to at least **fee_floor**.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_swap_with_fee_floor(
+    xs: &[Decimal256],
+    offer_amount: Decimal256,
+    ask_ind: usize,
+    config: &Config,
+    env: &Env,
+    maker_fee_share: Decimal256,
+    share_fee_share: Decimal256,
+    referral_fee_share: Decimal256,
+    fee_multiplier: Decimal256,
+    fee_floor: Decimal256,
 ) -> StdResult<SwapResult> {
     let offer_ind = 1 ^ ask_ind;
 
@@ -291,28 +332,42 @@ pub fn compute_swap(
         offer_amount.saturating_sub(dy / config.pool_state.price_state.oracle_price)
     };
 
-    let fee_rate = config.pool_params.fee(&ixs);
-    let total_fee = fee_rate * dy;
+    let fee_rate = config.pool_params.fee(&ixs).max(fee_floor);
+    let total_fee = fee_rate * dy * fee_multiplier;
     dy -= total_fee;
 
     let share_fee = total_fee * share_fee_share;
+    let referral_fee = total_fee * referral_fee_share;
 
     Ok(SwapResult {
         dy,
         spread_fee,
-        maker_fee: (total_fee - share_fee) * maker_fee_share,
+        maker_fee: (total_fee - share_fee - referral_fee) * maker_fee_share,
         share_fee,
+        referral_fee,
         total_fee,
     })
 }
 
 /// Returns an amount of offer assets for a specified amount of ask assets.
 pub fn compute_offer_amount(
+    xs: &[Decimal256],
+    want_amount: Decimal256,
+    ask_ind: usize,
+    config: &Config,
+    env: &Env,
+) -> StdResult<(Decimal256, Decimal256, Decimal256)> {
+    compute_offer_amount_with_fee_floor(xs, want_amount, ask_ind, config, env, Decimal256::zero())
+}
+
+/// Same as [`compute_offer_amount`] but the fee rate is raised to at least **fee_floor**.
+pub fn compute_offer_amount_with_fee_floor(
     xs: &[Decimal256],
     mut want_amount: Decimal256,
     ask_ind: usize,
     config: &Config,
     env: &Env,
+    fee_floor: Decimal256,
 ) -> StdResult<(Decimal256, Decimal256, Decimal256)> {
     let offer_ind = 1 ^ ask_ind;
 
@@ -327,10 +382,8 @@ pub fn compute_offer_amount(
     let d = calc_d(&ixs, &amp_gamma)?;
 
     // It's hard to predict fee rate thus we use maximum possible fee rate
-    let before_fee = want_amount
-        * (Decimal256::one() - Decimal256::from(config.pool_params.out_fee))
-            .inv()
-            .unwrap();
+    let fee_rate = Decimal256::from(config.pool_params.out_fee).max(fee_floor);
+    let before_fee = want_amount * (Decimal256::one() - fee_rate).inv().unwrap();
     let mut fee = before_fee - want_amount;
 
     ixs[ask_ind] -= before_fee;